@@ -0,0 +1,54 @@
+//! Waits for gamepad events with epoll instead of polling `next_event()` in a loop.
+
+#[cfg(target_os = "linux")]
+fn main() {
+    use gilrs_core::Gilrs;
+    use std::io;
+
+    env_logger::init();
+
+    let mut gilrs = Gilrs::new().unwrap();
+    let epoll = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+    if epoll < 0 {
+        panic!("epoll_create1 failed: {}", io::Error::last_os_error());
+    }
+
+    loop {
+        // Kernel removes closed descriptors from epoll, so we only need to add new ones.
+        for fd in gilrs
+            .event_source_fd()
+            .into_iter()
+            .chain(gilrs.device_fds().iter().cloned())
+        {
+            let mut event = libc::epoll_event {
+                events: libc::EPOLLIN as u32,
+                u64: fd as u64,
+            };
+            if unsafe { libc::epoll_ctl(epoll, libc::EPOLL_CTL_ADD, fd, &mut event) } < 0 {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() != Some(libc::EEXIST) {
+                    panic!("epoll_ctl failed for fd {}: {}", fd, err);
+                }
+            }
+        }
+
+        let mut events = [libc::epoll_event { events: 0, u64: 0 }; 16];
+        let n = unsafe { libc::epoll_wait(epoll, events.as_mut_ptr(), events.len() as i32, -1) };
+        if n < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            panic!("epoll_wait failed: {}", err);
+        }
+
+        while let Some(ev) = gilrs.next_event() {
+            println!("{:?}", ev);
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    println!("This example works only on Linux.");
+}
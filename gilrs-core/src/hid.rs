@@ -0,0 +1,556 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Parser of HID report descriptors, used by backends that read raw HID reports (currently only
+//! FreeBSD). It understands only as much of the format as is needed to find buttons and axes of
+//! gamepads: variable input items, usages, logical ranges and report IDs.
+#![cfg_attr(not(target_os = "freebsd"), allow(dead_code))]
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+
+pub const PAGE_GENERIC_DESKTOP: u16 = 0x01;
+pub const PAGE_SIMULATION: u16 = 0x02;
+pub const PAGE_BUTTON: u16 = 0x09;
+
+pub const USAGE_GD_JOYSTICK: u16 = 0x04;
+pub const USAGE_GD_GAMEPAD: u16 = 0x05;
+pub const USAGE_GD_MULTI_AXIS: u16 = 0x08;
+pub const USAGE_GD_X: u16 = 0x30;
+pub const USAGE_GD_Y: u16 = 0x31;
+pub const USAGE_GD_Z: u16 = 0x32;
+pub const USAGE_GD_RX: u16 = 0x33;
+pub const USAGE_GD_RY: u16 = 0x34;
+pub const USAGE_GD_RZ: u16 = 0x35;
+pub const USAGE_GD_SLIDER: u16 = 0x36;
+pub const USAGE_GD_DIAL: u16 = 0x37;
+pub const USAGE_GD_WHEEL: u16 = 0x38;
+pub const USAGE_GD_HATSWITCH: u16 = 0x39;
+pub const USAGE_GD_DPAD_UP: u16 = 0x90;
+pub const USAGE_GD_DPAD_DOWN: u16 = 0x91;
+pub const USAGE_GD_DPAD_RIGHT: u16 = 0x92;
+pub const USAGE_GD_DPAD_LEFT: u16 = 0x93;
+pub const USAGE_SIM_ACCELERATOR: u16 = 0xc4;
+pub const USAGE_SIM_BRAKE: u16 = 0xc5;
+
+// Item tags, already shifted together with item type.
+const MAIN_INPUT: u8 = 0x80;
+const MAIN_OUTPUT: u8 = 0x90;
+const MAIN_COLLECTION: u8 = 0xa0;
+const MAIN_FEATURE: u8 = 0xb0;
+const MAIN_END_COLLECTION: u8 = 0xc0;
+const GLOBAL_USAGE_PAGE: u8 = 0x04;
+const GLOBAL_LOGICAL_MIN: u8 = 0x14;
+const GLOBAL_LOGICAL_MAX: u8 = 0x24;
+const GLOBAL_REPORT_SIZE: u8 = 0x74;
+const GLOBAL_REPORT_ID: u8 = 0x84;
+const GLOBAL_REPORT_COUNT: u8 = 0x94;
+const GLOBAL_PUSH: u8 = 0xa4;
+const GLOBAL_POP: u8 = 0xb4;
+const LOCAL_USAGE: u8 = 0x08;
+const LOCAL_USAGE_MIN: u8 = 0x18;
+const LOCAL_USAGE_MAX: u8 = 0x28;
+const LONG_ITEM: u8 = 0xfe;
+
+const COLLECTION_APPLICATION: u32 = 0x01;
+
+const INPUT_CONSTANT: u32 = 0x01;
+const INPUT_VARIABLE: u32 = 0x02;
+
+// Same limits as in Linux's HID parser.
+const MAX_REPORT_SIZE: u32 = 256;
+const MAX_REPORT_COUNT: u32 = 12288;
+const MAX_REPORT_BITS: u32 = 16384 * 8;
+
+/// Single value in input report.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Field {
+    pub report_id: u8,
+    pub page: u16,
+    pub usage: u16,
+    /// Offset of the value in bits, counted from the start of report without report ID.
+    pub offset: u32,
+    pub size: u32,
+    pub logical_min: i32,
+    pub logical_max: i32,
+}
+
+impl Field {
+    /// Reads value of this field from report. `report` must not start with report ID. Returns
+    /// `None` if report is too short.
+    pub fn read(&self, report: &[u8]) -> Option<i32> {
+        if self.size == 0 || self.size > 32 {
+            return None;
+        }
+
+        let mut value = 0u32;
+        for i in 0..self.size {
+            let bit = self.offset.checked_add(i)?;
+            let byte = *report.get((bit / 8) as usize)?;
+            if (byte >> (bit % 8)) & 1 != 0 {
+                value |= 1 << i;
+            }
+        }
+
+        if self.logical_min < 0 && self.size < 32 && value & (1 << (self.size - 1)) != 0 {
+            value |= !0 << self.size;
+        }
+
+        Some(value as i32)
+    }
+
+    pub fn is_axis(&self) -> bool {
+        match (self.page, self.usage) {
+            (PAGE_GENERIC_DESKTOP, USAGE_GD_X..=USAGE_GD_WHEEL) => true,
+            (PAGE_SIMULATION, USAGE_SIM_ACCELERATOR) | (PAGE_SIMULATION, USAGE_SIM_BRAKE) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_button(&self) -> bool {
+        match (self.page, self.usage) {
+            (PAGE_BUTTON, _) => true,
+            (PAGE_GENERIC_DESKTOP, USAGE_GD_DPAD_UP..=USAGE_GD_DPAD_LEFT) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_hat(&self) -> bool {
+        self.page == PAGE_GENERIC_DESKTOP && self.usage == USAGE_GD_HATSWITCH
+    }
+
+    /// Converts value of hat switch to values of X and Y axes in [-1, 1] range. Y axis points
+    /// downwards.
+    ///
+    /// Hat switches report 8 (sometimes 4) directions, starting from up and proceeding
+    /// clockwise. Any value outside of logical range means that hat is centered.
+    pub fn hat_to_axes(&self, value: i32) -> (i32, i32) {
+        let range = self
+            .logical_max
+            .checked_sub(self.logical_min)
+            .and_then(|range| range.checked_add(1));
+        let shifted = value.checked_sub(self.logical_min).unwrap_or(-1);
+        let direction = match range {
+            Some(4) if (0..4).contains(&shifted) => shifted * 2,
+            Some(8) if (0..8).contains(&shifted) => shifted,
+            _ => -1,
+        };
+
+        let x = match direction {
+            5..=7 => -1,
+            1..=3 => 1,
+            _ => 0,
+        };
+        let y = match direction {
+            3..=5 => 1,
+            0 | 1 | 7 => -1,
+            _ => 0,
+        };
+
+        (x, y)
+    }
+}
+
+/// Parsed report descriptor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReportDescriptor {
+    /// Usage page and usage of the first application collection.
+    pub application: Option<(u16, u16)>,
+    /// Variable, non constant fields of input reports.
+    pub inputs: Vec<Field>,
+    /// True if reports are prefixed with report ID.
+    pub has_report_ids: bool,
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Globals {
+    page: u16,
+    logical_min: i32,
+    logical_max: i32,
+    report_size: u32,
+    report_id: u8,
+    report_count: u32,
+}
+
+#[derive(Debug, Default)]
+struct Locals {
+    usages: Vec<u32>,
+    usage_min: Option<u32>,
+    usage_max: Option<u32>,
+}
+
+impl Locals {
+    /// Returns extended (page in high 16 bits) usage of nth value of main item.
+    fn usage(&self, n: u32, page: u16) -> Option<u32> {
+        let usage = if !self.usages.is_empty() {
+            let idx = (n as usize).min(self.usages.len() - 1);
+            self.usages[idx]
+        } else if let (Some(min), Some(max)) = (self.usage_min, self.usage_max) {
+            min.saturating_add(n).min(max)
+        } else {
+            return None;
+        };
+
+        if usage > 0xffff {
+            Some(usage)
+        } else {
+            Some(u32::from(page) << 16 | usage)
+        }
+    }
+}
+
+impl ReportDescriptor {
+    pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let mut desc = ReportDescriptor::default();
+        let mut globals = Globals::default();
+        let mut stack = Vec::new();
+        let mut locals = Locals::default();
+        let mut offsets: HashMap<u8, u32> = HashMap::new();
+        let mut pos = 0;
+
+        while pos < data.len() {
+            let prefix = data[pos];
+
+            if prefix == LONG_ITEM {
+                let size = *data.get(pos + 1).ok_or(ParseError::UnexpectedEnd)? as usize;
+                pos += 3 + size;
+                if pos > data.len() {
+                    return Err(ParseError::UnexpectedEnd);
+                }
+                continue;
+            }
+
+            let size = match prefix & 0x03 {
+                3 => 4,
+                size => size as usize,
+            };
+            let bytes = data
+                .get(pos + 1..pos + 1 + size)
+                .ok_or(ParseError::UnexpectedEnd)?;
+            pos += 1 + size;
+
+            let value = bytes
+                .iter()
+                .rev()
+                .fold(0u32, |acc, &b| acc << 8 | u32::from(b));
+            let signed = match size {
+                1 => i32::from(value as u8 as i8),
+                2 => i32::from(value as u16 as i16),
+                _ => value as i32,
+            };
+
+            match prefix & 0xfc {
+                MAIN_INPUT => {
+                    let id = globals.report_id;
+                    let offset = offsets.entry(id).or_insert(0);
+                    // Both values are capped, so this can't overflow.
+                    let len = globals.report_count * globals.report_size;
+                    let end = offset
+                        .checked_add(len)
+                        .filter(|&end| end <= MAX_REPORT_BITS)
+                        .ok_or(ParseError::ReportTooLong(id))?;
+
+                    if value & (INPUT_CONSTANT | INPUT_VARIABLE) == INPUT_VARIABLE
+                        && globals.report_size != 0
+                    {
+                        for n in 0..globals.report_count {
+                            if let Some(usage) = locals.usage(n, globals.page) {
+                                desc.inputs.push(Field {
+                                    report_id: id,
+                                    page: (usage >> 16) as u16,
+                                    usage: usage as u16,
+                                    offset: *offset + n * globals.report_size,
+                                    size: globals.report_size,
+                                    logical_min: globals.logical_min,
+                                    logical_max: globals.logical_max,
+                                });
+                            }
+                        }
+                    }
+
+                    *offset = end;
+                    locals = Locals::default();
+                }
+                MAIN_OUTPUT | MAIN_FEATURE | MAIN_END_COLLECTION => locals = Locals::default(),
+                MAIN_COLLECTION => {
+                    if value == COLLECTION_APPLICATION && desc.application.is_none() {
+                        if let Some(usage) = locals.usage(0, globals.page) {
+                            desc.application = Some(((usage >> 16) as u16, usage as u16));
+                        }
+                    }
+                    locals = Locals::default();
+                }
+                GLOBAL_USAGE_PAGE => globals.page = value as u16,
+                GLOBAL_LOGICAL_MIN => globals.logical_min = signed,
+                GLOBAL_LOGICAL_MAX => {
+                    // Logical maximum is unsigned if logical minimum is not negative.
+                    globals.logical_max = if globals.logical_min >= 0 {
+                        value as i32
+                    } else {
+                        signed
+                    }
+                }
+                GLOBAL_REPORT_SIZE if value > MAX_REPORT_SIZE => {
+                    return Err(ParseError::InvalidReportSize(value))
+                }
+                GLOBAL_REPORT_SIZE => globals.report_size = value,
+                GLOBAL_REPORT_ID => {
+                    globals.report_id = value as u8;
+                    desc.has_report_ids = true;
+                }
+                GLOBAL_REPORT_COUNT if value > MAX_REPORT_COUNT => {
+                    return Err(ParseError::InvalidReportCount(value))
+                }
+                GLOBAL_REPORT_COUNT => globals.report_count = value,
+                GLOBAL_PUSH => stack.push(globals),
+                GLOBAL_POP => globals = stack.pop().ok_or(ParseError::StackUnderflow)?,
+                LOCAL_USAGE => locals.usages.push(value),
+                LOCAL_USAGE_MIN => locals.usage_min = Some(value),
+                LOCAL_USAGE_MAX => locals.usage_max = Some(value),
+                _ => (),
+            }
+        }
+
+        Ok(desc)
+    }
+
+    /// Returns true if the first application collection is joystick, gamepad or multi-axis
+    /// controller.
+    pub fn is_gamepad(&self) -> bool {
+        match self.application {
+            Some((PAGE_GENERIC_DESKTOP, USAGE_GD_JOYSTICK))
+            | Some((PAGE_GENERIC_DESKTOP, USAGE_GD_GAMEPAD))
+            | Some((PAGE_GENERIC_DESKTOP, USAGE_GD_MULTI_AXIS)) => true,
+            _ => false,
+        }
+    }
+
+    /// Splits report read from device into report ID and the rest of report.
+    pub fn split_report<'a>(&self, report: &'a [u8]) -> Option<(u8, &'a [u8])> {
+        if self.has_report_ids {
+            report.split_first().map(|(&id, rest)| (id, rest))
+        } else {
+            Some((0, report))
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParseError {
+    UnexpectedEnd,
+    StackUnderflow,
+    InvalidReportSize(u32),
+    InvalidReportCount(u32),
+    /// Report with given ID is longer than 16 KiB.
+    ReportTooLong(u8),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedEnd => {
+                f.write_str("report descriptor ended in the middle of item")
+            }
+            ParseError::StackUnderflow => f.write_str("pop item without matching push"),
+            ParseError::InvalidReportSize(size) => write!(f, "invalid report size {}", size),
+            ParseError::InvalidReportCount(count) => write!(f, "invalid report count {}", count),
+            ParseError::ReportTooLong(id) => write!(f, "report {} is too long", id),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generic USB gamepad with 2 axes, hat switch and 12 buttons.
+    const GAMEPAD: &[u8] = &[
+        0x05, 0x01, // Usage Page (Generic Desktop)
+        0x09, 0x05, // Usage (Game Pad)
+        0xa1, 0x01, // Collection (Application)
+        0x85, 0x01, //   Report ID (1)
+        0x15, 0x00, //   Logical Minimum (0)
+        0x26, 0xff, 0x00, //   Logical Maximum (255)
+        0x75, 0x08, //   Report Size (8)
+        0x95, 0x02, //   Report Count (2)
+        0x09, 0x30, //   Usage (X)
+        0x09, 0x31, //   Usage (Y)
+        0x81, 0x02, //   Input (Data, Variable, Absolute)
+        0x25, 0x07, //   Logical Maximum (7)
+        0x75, 0x04, //   Report Size (4)
+        0x95, 0x01, //   Report Count (1)
+        0x09, 0x39, //   Usage (Hat switch)
+        0x81, 0x42, //   Input (Data, Variable, Absolute, Null State)
+        0x95, 0x01, //   Report Count (1)
+        0x81, 0x03, //   Input (Constant)
+        0x05, 0x09, //   Usage Page (Button)
+        0x19, 0x01, //   Usage Minimum (1)
+        0x29, 0x0c, //   Usage Maximum (12)
+        0x25, 0x01, //   Logical Maximum (1)
+        0x75, 0x01, //   Report Size (1)
+        0x95, 0x0c, //   Report Count (12)
+        0x81, 0x02, //   Input (Data, Variable, Absolute)
+        0xc0, // End Collection
+    ];
+
+    #[test]
+    fn parse_gamepad() {
+        let desc = ReportDescriptor::parse(GAMEPAD).unwrap();
+        assert_eq!(
+            desc.application,
+            Some((PAGE_GENERIC_DESKTOP, USAGE_GD_GAMEPAD))
+        );
+        assert!(desc.is_gamepad());
+        assert!(desc.has_report_ids);
+        assert_eq!(desc.inputs.len(), 15);
+
+        let y = desc.inputs[1];
+        assert_eq!((y.page, y.usage), (PAGE_GENERIC_DESKTOP, USAGE_GD_Y));
+        assert_eq!((y.offset, y.size), (8, 8));
+        assert_eq!((y.logical_min, y.logical_max), (0, 255));
+        assert!(y.is_axis());
+
+        let hat = desc.inputs[2];
+        assert!(hat.is_hat());
+        assert_eq!((hat.offset, hat.size, hat.logical_max), (16, 4, 7));
+
+        let last = desc.inputs[14];
+        assert_eq!((last.page, last.usage), (PAGE_BUTTON, 12));
+        assert_eq!(last.offset, 24 + 11);
+        assert!(last.is_button());
+    }
+
+    #[test]
+    fn read_report() {
+        let desc = ReportDescriptor::parse(GAMEPAD).unwrap();
+        let (id, report) = desc
+            .split_report(&[0x01, 0x80, 0xff, 0x02, 0x01, 0x08])
+            .unwrap();
+        assert_eq!(id, 1);
+
+        let values: Vec<_> = desc
+            .inputs
+            .iter()
+            .map(|f| f.read(report).unwrap())
+            .collect();
+        assert_eq!(&values[..3], &[0x80, 0xff, 2]);
+        // Buttons 1 and 12.
+        assert_eq!(values[3], 1);
+        assert_eq!(&values[4..14], &[0; 10]);
+        assert_eq!(values[14], 1);
+
+        assert_eq!(desc.inputs[0].read(&[]), None);
+    }
+
+    #[test]
+    fn signed_values() {
+        let field = Field {
+            report_id: 0,
+            page: PAGE_GENERIC_DESKTOP,
+            usage: USAGE_GD_X,
+            offset: 4,
+            size: 8,
+            logical_min: -127,
+            logical_max: 127,
+        };
+        assert_eq!(field.read(&[0xf0, 0x0f]), Some(-1));
+        assert_eq!(field.read(&[0x10, 0x00]), Some(1));
+
+        let desc = ReportDescriptor::parse(&[0x15, 0x81, 0x25, 0x7f]).unwrap();
+        assert!(desc.inputs.is_empty());
+        assert_eq!(
+            ReportDescriptor::parse(&[0x26, 0xff]),
+            Err(ParseError::UnexpectedEnd)
+        );
+        assert_eq!(
+            ReportDescriptor::parse(&[0xb4]),
+            Err(ParseError::StackUnderflow)
+        );
+    }
+
+    #[test]
+    fn hat_switch() {
+        let hat = Field {
+            report_id: 0,
+            page: PAGE_GENERIC_DESKTOP,
+            usage: USAGE_GD_HATSWITCH,
+            offset: 0,
+            size: 4,
+            logical_min: 0,
+            logical_max: 7,
+        };
+        assert_eq!(hat.hat_to_axes(0), (0, -1));
+        assert_eq!(hat.hat_to_axes(3), (1, 1));
+        assert_eq!(hat.hat_to_axes(6), (-1, 0));
+        assert_eq!(hat.hat_to_axes(8), (0, 0));
+
+        let hat4 = Field {
+            logical_min: 1,
+            logical_max: 4,
+            ..hat
+        };
+        assert_eq!(hat4.hat_to_axes(2), (1, 0));
+        assert_eq!(hat4.hat_to_axes(0), (0, 0));
+
+        let hat_full_range = Field {
+            logical_min: std::i32::MIN,
+            logical_max: std::i32::MAX,
+            ..hat
+        };
+        assert_eq!(hat_full_range.hat_to_axes(0), (0, 0));
+        assert_eq!(hat4.hat_to_axes(std::i32::MIN), (0, 0));
+    }
+
+    #[test]
+    fn truncated_descriptor() {
+        for len in 0..GAMEPAD.len() {
+            match ReportDescriptor::parse(&GAMEPAD[..len]) {
+                Ok(desc) => assert!(desc.inputs.len() <= 15),
+                Err(e) => assert_eq!(e, ParseError::UnexpectedEnd),
+            }
+        }
+        assert_eq!(
+            ReportDescriptor::parse(&[0xfe, 0x10]),
+            Err(ParseError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn overflowing_descriptor() {
+        // Report Size (0xffffffff)
+        assert_eq!(
+            ReportDescriptor::parse(&[0x77, 0xff, 0xff, 0xff, 0xff]),
+            Err(ParseError::InvalidReportSize(0xffff_ffff))
+        );
+        // Report Count (0x10000)
+        assert_eq!(
+            ReportDescriptor::parse(&[0x97, 0x00, 0x00, 0x01, 0x00]),
+            Err(ParseError::InvalidReportCount(0x1_0000))
+        );
+
+        // Report Size (256), Report Count (512), Usage Minimum (0xfffffffe), Usage Maximum
+        // (0xffffffff), Input. Report can't be longer, so second Input fails.
+        let mut data = vec![0x76, 0x00, 0x01, 0x96, 0x00, 0x02];
+        data.extend_from_slice(&[0x1b, 0xfe, 0xff, 0xff, 0xff, 0x2b, 0xff, 0xff, 0xff, 0xff]);
+        data.extend_from_slice(&[0x81, 0x02]);
+        let desc = ReportDescriptor::parse(&data).unwrap();
+        assert_eq!(desc.inputs.len(), 512);
+        assert_eq!(desc.inputs[511].usage, 0xffff);
+        data.extend_from_slice(&[0x09, 0x30, 0x81, 0x02]);
+        assert_eq!(
+            ReportDescriptor::parse(&data),
+            Err(ParseError::ReportTooLong(0))
+        );
+
+        // Report Size (0) doesn't add fields.
+        let data = [0x75, 0x00, 0x95, 0x10, 0x09, 0x30, 0x81, 0x02];
+        assert!(ReportDescriptor::parse(&data).unwrap().inputs.is_empty());
+    }
+}
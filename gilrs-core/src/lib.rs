@@ -6,15 +6,32 @@ use std::fmt::Display;
 use std::fmt::Formatter;
 
 use std::error;
+use std::io;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 use std::time::SystemTime;
 
+mod hid;
 mod platform;
 pub mod utils;
 
 /// True, if Y axis of sticks commonly points downwards.
 pub const IS_Y_AXIS_REVERSED: bool = platform::IS_Y_AXIS_REVERSED;
 
+/// True, if `Event::time` is timestamp set by kernel when event was generated (`input_event` time
+/// on Linux). Otherwise it's time when event was read by gilrs-core.
+pub const HAS_KERNEL_TIMESTAMPS: bool = platform::HAS_KERNEL_TIMESTAMPS;
+
+/// True, if more than one `Gilrs` can exist at the same time in one process, each receiving all
+/// events. Otherwise `Gilrs::new()` returns `Error::AlreadyInUse` while another instance is alive.
+/// Currently only FreeBSD doesn't support it, because uhid(4) devices can be opened only once.
+pub const SUPPORTS_MULTIPLE_INSTANCES: bool = platform::SUPPORTS_MULTIPLE_INSTANCES;
+
+/// Number of alive `Gilrs` instances.
+static INSTANCES: AtomicUsize = AtomicUsize::new(0);
+
 /// Allow control of gamepad's force feedback.
 #[derive(Debug)]
 pub struct FfDevice {
@@ -22,12 +39,51 @@ pub struct FfDevice {
 }
 
 impl FfDevice {
+    /// Returns maximum number of force feedback effects that can be uploaded to device at the same
+    /// time. On Linux this is number of effect slots reported by kernel (commonly 16). Other
+    /// platforms don't have effect slots, because effects are mixed in software, so Windows and
+    /// Android report 16, the same as most Linux drivers, and macOS conservative default of 4.
+    ///
+    /// Gilrs doesn't limit number of played effects to this value, it's only informational.
+    pub fn query_max_effects(&self) -> usize {
+        self.inner.query_max_effects()
+    }
+
+    /// Returns false if device was removed since it was opened and has to be opened again with
+    /// `Gamepad::ff_device()`. Devices that don't hold OS handle always return true.
+    pub fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
     /// Sets magnitude for strong and weak ff motors.
     pub fn set_ff_state(&mut self, strong: u16, weak: u16, min_duration: Duration) {
         self.inner.set_ff_state(strong, weak, min_duration)
     }
 }
 
+/// Raw HID reports of gamepad. Created with `Gamepad::raw_reports()`.
+#[cfg(feature = "raw-hid")]
+#[derive(Debug)]
+pub struct RawReports {
+    inner: platform::RawReports,
+}
+
+#[cfg(feature = "raw-hid")]
+impl RawReports {
+    /// Copies next input report to `buf` and returns its length, or `None` if there are no new
+    /// reports. Never blocks. Reports longer than `buf` are truncated. For devices that use
+    /// numbered reports, first byte is report ID.
+    pub fn next_report(&mut self, buf: &mut [u8]) -> Option<usize> {
+        self.inner.next_report(buf)
+    }
+
+    /// Sends output report to device, for example to control LEDs. First byte of `report` has to
+    /// be report ID, or 0 if device doesn't use numbered reports. Returns number of written bytes.
+    pub fn write_report(&mut self, report: &[u8]) -> io::Result<usize> {
+        self.inner.write_report(report)
+    }
+}
+
 /// Holds information about gamepad event.
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Event {
@@ -55,16 +111,137 @@ pub enum EventType {
     AxisValueChanged(i32, EvCode),
     Connected,
     Disconnected,
+    /// Device still exists, but it stopped delivering events, for example because system suspended
+    /// it. `Gamepad::is_connected()` returns false until `Resumed` is emitted. Suspended device
+    /// can also be removed, in which case `Disconnected` is emitted.
+    Suspended,
+    /// Suspended device delivers events again.
+    Resumed,
+    /// Finger touched, moved on or was lifted from gamepad's touchpad. `x` and `y` are in
+    /// [0.0, 1.0] range, (0.0, 0.0) is top left corner. Only emitted if `Gamepad::has_touchpad()`
+    /// returns true.
+    TouchpadTouch {
+        finger: u8,
+        x: f32,
+        y: f32,
+        pressed: bool,
+    },
 }
 
 /// Holds information about expected axis range and deadzone.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct AxisInfo {
     pub min: i32,
     pub max: i32,
     pub deadzone: Option<u32>,
 }
 
+impl AxisInfo {
+    /// Returns builder of `AxisInfo`, useful for overriding ranges reported by miscalibrated
+    /// devices.
+    ///
+    /// ```
+    /// use gilrs_core::AxisInfo;
+    ///
+    /// let info = AxisInfo::builder().range(0, 65535).deadzone(4000).build();
+    /// assert_eq!((info.min, info.max, info.deadzone), (0, 65535, Some(4000)));
+    /// ```
+    pub fn builder() -> AxisInfoBuilder {
+        AxisInfoBuilder::new()
+    }
+
+    /// Limits `raw` to `[min, max]` range. Some drivers report values outside of range that they
+    /// advertise. If range is not valid, `raw` is returned unchanged.
+    pub fn clamp(&self, raw: i32) -> i32 {
+        if !self.is_valid_range() {
+            raw
+        } else if raw < self.min {
+            self.min
+        } else if raw > self.max {
+            self.max
+        } else {
+            raw
+        }
+    }
+
+    /// Returns `true` if `min` is smaller than `max`.
+    pub fn is_valid_range(&self) -> bool {
+        self.min < self.max
+    }
+
+    /// Returns deadzone in the same scale as normalized axis value. If `unipolar` is `true`, value
+    /// is normalized to [0.0, 1.0] (for example trigger used as button), otherwise to
+    /// [-1.0, 1.0], so the same raw deadzone is twice as large.
+    ///
+    /// Returns `Some(0.0)` if range is not valid and `None` if deadzone is unknown.
+    pub fn deadzone_normalized(&self, unipolar: bool) -> Option<f32> {
+        if !self.is_valid_range() {
+            return Some(0.0);
+        }
+
+        let deadzone = self.deadzone?;
+
+        let range = f64::from(self.max) - f64::from(self.min);
+        let scale = if unipolar { 1.0 } else { 2.0 };
+
+        Some((f64::from(deadzone) / range * scale) as f32)
+    }
+}
+
+/// Builder of [`AxisInfo`](struct.AxisInfo.html). By default range is `-32768..=32767` and
+/// deadzone is unknown.
+#[derive(Copy, Clone, Debug)]
+pub struct AxisInfoBuilder {
+    info: AxisInfo,
+}
+
+impl AxisInfoBuilder {
+    /// Creates builder with default values. See also `AxisInfo::builder()`.
+    pub fn new() -> Self {
+        AxisInfoBuilder {
+            info: AxisInfo {
+                min: -32768,
+                max: 32767,
+                deadzone: None,
+            },
+        }
+    }
+
+    /// Sets minimal value of axis.
+    pub fn min(mut self, min: i32) -> Self {
+        self.info.min = min;
+        self
+    }
+
+    /// Sets maximal value of axis.
+    pub fn max(mut self, max: i32) -> Self {
+        self.info.max = max;
+        self
+    }
+
+    /// Sets both minimal and maximal value of axis.
+    pub fn range(self, min: i32, max: i32) -> Self {
+        self.min(min).max(max)
+    }
+
+    /// Sets deadzone in the same units as `min` and `max`.
+    pub fn deadzone(mut self, deadzone: u32) -> Self {
+        self.info.deadzone = Some(deadzone);
+        self
+    }
+
+    /// Creates `AxisInfo`.
+    pub fn build(self) -> AxisInfo {
+        self.info
+    }
+}
+
+impl Default for AxisInfoBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// State of device's power supply.
 ///
 /// Battery level is reported as integer between 0 and 100.
@@ -82,6 +259,7 @@ pub struct AxisInfo {
 /// };
 /// ```
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub enum PowerInfo {
     /// Failed to determine power status.
     Unknown,
@@ -95,20 +273,54 @@ pub enum PowerInfo {
     Charged,
 }
 
+/// Connection status of gamepad.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum Status {
+    /// Gamepad is connected.
+    Connected,
+    /// Gamepad was connected, but it is not anymore.
+    Disconnected,
+    /// Gamepad with this ID was never observed as connected.
+    NotObserved,
+    /// Gamepad is known to exist, but it doesn't deliver events, for example because system
+    /// suspended it. Currently only Linux reports it, when gamepad's event device
+    /// (`/dev/input/eventN`) is removed, but its parent input device is not. Windows backend
+    /// doesn't report it, because XInput doesn't have window that could receive
+    /// `WM_DEVICECHANGE`.
+    Suspended,
+}
+
 /// Struct used to manage gamepads and retrieve events.
+///
+/// Multiple instances can be used at the same time if `SUPPORTS_MULTIPLE_INSTANCES` is true. Each
+/// of them opens devices on its own and receives all events.
 #[derive(Debug)]
 pub struct Gilrs {
     inner: platform::Gilrs,
+    // Declared after `inner`, so devices are closed before instance is released.
+    _instance: InstanceGuard,
 }
 
 impl Gilrs {
     pub fn new() -> Result<Self, Error> {
-        let inner = platform::Gilrs::new().map_err(|e| match e {
-            PlatformError::NotImplemented(inner) => Error::NotImplemented(Gilrs { inner }),
-            PlatformError::Other(e) => Error::Other(e),
-        })?;
+        let instance = InstanceGuard::acquire(&INSTANCES, SUPPORTS_MULTIPLE_INSTANCES)
+            .ok_or(Error::AlreadyInUse)?;
+        let inner = match platform::Gilrs::new() {
+            Ok(inner) => inner,
+            Err(PlatformError::NotImplemented(inner)) => {
+                return Err(Error::NotImplemented(Gilrs {
+                    inner,
+                    _instance: instance,
+                }))
+            }
+            Err(PlatformError::Other(e)) => return Err(Error::Other(e)),
+        };
 
-        Ok(Gilrs { inner })
+        Ok(Gilrs {
+            inner,
+            _instance: instance,
+        })
     }
 
     /// Returns oldest event or `None` if all events were processed.
@@ -134,6 +346,133 @@ impl Gilrs {
     pub fn last_gamepad_hint(&self) -> usize {
         self.inner.last_gamepad_hint()
     }
+
+    /// Enumerates connected devices again, in case some hotplug events were missed. Returns
+    /// number of `Connected` and `Disconnected` events generated for gamepads that appeared or
+    /// disappeared since last check. These events are returned by `next_event()`.
+    ///
+    /// On Linux this enumerates devices with udev, on macOS it asks IOHIDManager for matching
+    /// devices, on FreeBSD it lists uhid devices in `/dev` and on Windows it checks all XInput
+    /// slots. On web, gamepads are enumerated on every call to `next_event()`, so this function
+    /// does nothing. FreeBSD backend also does the same scan once per second in `next_event()`.
+    /// On Android devices are enumerated through JNI, so it only works after
+    /// [`android_set_java_vm()`](#method.android_set_java_vm), and they are also scanned once per
+    /// second.
+    pub fn rescan_gamepads(&mut self) -> usize {
+        self.inner.rescan_gamepads()
+    }
+
+    /// Sets how often gamepads are polled for input, in Hz. Lower rate reduces CPU and power usage
+    /// when application doesn't need every report.
+    ///
+    /// On Windows this changes how often XInput state is read (100 Hz by default). On macOS it
+    /// sets report interval of all connected and later connected devices, device's driver may
+    /// ignore it. Linux, FreeBSD and web deliver events as they arrive, so this function returns
+    /// `PollingRateError::NotSupported` there.
+    pub fn set_polling_rate(&mut self, hz: u32) -> Result<(), PollingRateError> {
+        if hz == 0 {
+            return Err(PollingRateError::InvalidRate);
+        }
+
+        self.inner.set_polling_rate(hz)
+    }
+
+    /// Returns file descriptor of udev monitor, which becomes readable when input device is added
+    /// or removed. Together with [`device_fds()`](#method.device_fds) it can be registered in
+    /// epoll, mio or tokio's `AsyncFd`, so `next_event()` is only called when there is something
+    /// to read. See `examples/epoll.rs`.
+    ///
+    /// Descriptor is owned by `Gilrs`, don't read from it or close it.
+    #[cfg(target_os = "linux")]
+    pub fn event_source_fd(&self) -> Option<RawFd> {
+        self.inner.event_source_fd()
+    }
+
+    /// Returns file descriptors of connected gamepads and their touchpads, which become readable
+    /// when device has new events.
+    ///
+    /// Descriptors are owned by `Gilrs`, don't read from them or close them. They are closed when
+    /// device is disconnected and new ones are opened for connected devices, so the list should be
+    /// checked again every time `next_event()` returns `None` and after `rescan_gamepads()`.
+    #[cfg(target_os = "linux")]
+    pub fn device_fds(&self) -> &[RawFd] {
+        self.inner.device_fds()
+    }
+
+    /// Handles input event forwarded by application. `event` is pointer to NDK's `AInputEvent`,
+    /// for example from `android_activity::input::KeyEvent` or `MotionEvent`, or one received by
+    /// `onInputEvent` of `android_native_app_glue`.
+    ///
+    /// Returns true if event came from gamepad or joystick and was handled by gilrs, in which
+    /// case application shouldn't handle it again. Events from devices that weren't reported
+    /// with [`android_device_added()`](#method.android_device_added) or found by enumeration
+    /// add them. If device can't be queried through JNI, it gets placeholder name, nil UUID and
+    /// axes of typical gamepad. These are replaced when the device is reported later.
+    ///
+    /// # Safety
+    ///
+    /// `event` must be null or point to valid `AInputEvent`.
+    #[cfg(target_os = "android")]
+    pub unsafe fn handle_android_event(&mut self, event: *const std::os::raw::c_void) -> bool {
+        self.inner.handle_android_event(event)
+    }
+
+    /// Gives backend access to application's Java VM, for example from
+    /// `android_activity::AndroidApp::vm_as_ptr()` or `ANativeActivity::vm`. Backend then
+    /// enumerates connected gamepads with `InputDevice.getDeviceIds()` and `getDevice()`, reads
+    /// their names, UUIDs, and axes, and plays force feedback on their `Vibrator`. Because
+    /// `InputDeviceListener` requires Java code, connected and removed devices are found by
+    /// enumerating devices again once per second in `next_event()`, so
+    /// [`android_device_added()`](#method.android_device_added) and
+    /// [`android_device_removed()`](#method.android_device_removed) don't have to be called.
+    /// Input events still have to be forwarded to
+    /// [`handle_android_event()`](#method.handle_android_event).
+    ///
+    /// Force feedback uses `VibrationEffect`, which requires API level 26, and application needs
+    /// `android.permission.VIBRATE`.
+    ///
+    /// # Safety
+    ///
+    /// `vm` must be null or point to `JavaVM` that stays valid as long as `Gilrs` and its
+    /// `FfDevice`s exist.
+    #[cfg(target_os = "android")]
+    pub unsafe fn android_set_java_vm(&mut self, vm: *mut std::os::raw::c_void) {
+        self.inner.android_set_java_vm(vm)
+    }
+
+    /// Adds gamepad. Should be called from `InputManager.InputDeviceListener.onInputDeviceAdded`
+    /// and for already connected devices returned by `InputManager.getInputDeviceIds`, with
+    /// values of `InputDevice.getName()`, `getVendorId()` and `getProductId()`. `axes` are
+    /// `MotionRange.getAxis()` of ranges returned by `InputDevice.getMotionRanges()`, in any
+    /// order. Emits `Connected` event. Returns ID of the gamepad.
+    #[cfg(target_os = "android")]
+    pub fn android_device_added(
+        &mut self,
+        device_id: i32,
+        name: &str,
+        vendor_id: u16,
+        product_id: u16,
+        axes: &[i32],
+    ) -> usize {
+        self.inner
+            .android_device_added(device_id, name, vendor_id, product_id, axes)
+    }
+
+    /// Removes gamepad. Should be called from
+    /// `InputManager.InputDeviceListener.onInputDeviceRemoved`. Emits `Disconnected` event.
+    #[cfg(target_os = "android")]
+    pub fn android_device_removed(&mut self, device_id: i32) {
+        self.inner.android_device_removed(device_id)
+    }
+}
+
+/// State of gamepad's buttons and axes read from device. See `Gamepad::current_state()`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RawState {
+    /// Buttons and whether they are pressed.
+    pub buttons: Vec<(EvCode, bool)>,
+    /// Axes and their raw values.
+    pub axes: Vec<(EvCode, i32)>,
 }
 
 /// Provides information about gamepad.
@@ -154,6 +493,22 @@ impl Gamepad {
         self.inner.is_connected()
     }
 
+    /// Returns manufacturer string of device, for example "Sony Interactive Entertainment".
+    ///
+    /// On Linux this is USB manufacturer string reported by udev, or vendor name from udev's
+    /// hardware database if device doesn't have one. On macOS and FreeBSD it's read from device
+    /// descriptor. Returns `None` on other platforms or if device doesn't provide it.
+    pub fn manufacturer_string(&self) -> Option<&str> {
+        self.inner.manufacturer_string()
+    }
+
+    /// Returns product string of device, for example "Wireless Controller". Unlike `name()`,
+    /// this is never name assigned by driver or OS. Availability is the same as for
+    /// `manufacturer_string()`.
+    pub fn product_string(&self) -> Option<&str> {
+        self.inner.product_string()
+    }
+
     /// Returns UUID that represents gamepad model.
     ///
     /// Returned UUID should be the same as SLD2 uses. If platform does not provide any method to
@@ -170,11 +525,137 @@ impl Gamepad {
         self.inner.power_info()
     }
 
+    /// Returns battery level in percent. `Charged` is reported as 100%. Returns `None` for wired
+    /// devices and if battery level is unknown.
+    pub fn battery_percentage(&self) -> Option<u8> {
+        match self.power_info() {
+            PowerInfo::Discharging(lvl) | PowerInfo::Charging(lvl) => Some(lvl),
+            PowerInfo::Charged => Some(100),
+            PowerInfo::Wired | PowerInfo::Unknown => None,
+        }
+    }
+
+    /// Returns true if device is powered by battery, which isn't charging.
+    pub fn is_on_battery(&self) -> bool {
+        match self.power_info() {
+            PowerInfo::Discharging(_) => true,
+            _ => false,
+        }
+    }
+
     /// Returns true if force feedback is supported by device,
     pub fn is_ff_supported(&self) -> bool {
         self.inner.is_ff_supported()
     }
 
+    /// Returns how many input reports per second device sends, or `None` if platform does not
+    /// provide this information.
+    pub fn input_report_rate(&self) -> Option<u32> {
+        self.inner.input_report_rate()
+    }
+
+    /// Returns true if gamepad has touchpad whose events are reported. Currently only touchpads
+    /// of gamepads with separate touchpad device on Linux (like DualShock 4 and DualSense) are
+    /// supported. On other platforms this always returns false.
+    ///
+    /// Windows doesn't support touchpads at all: XInput has no touchpad data, so this returns
+    /// false even for gamepads that have one.
+    pub fn has_touchpad(&self) -> bool {
+        self.inner.has_touchpad()
+    }
+
+    /// Returns path to device file or platform specific device identifier that can be used to
+    /// open the same device with other libraries. On Linux this is path to evdev device (e.g.
+    /// `/dev/input/event5`), on FreeBSD path to uhid device (e.g. `/dev/uhid0`) and on macOS path
+    /// of IOService in I/O Registry. Returns `None` if there is no such path on current platform.
+    pub fn os_path(&self) -> Option<&str> {
+        self.inner.os_path()
+    }
+
+    /// Returns zero-based player index suggested by platform, or `None` if platform does not
+    /// provide it. On Windows this is XInput user index. On Linux this is player shown by
+    /// gamepad's LEDs, which some drivers set when gamepad connects, or number of joydev device
+    /// (`/dev/input/jsN`).
+    pub fn suggested_player_index(&self) -> Option<u8> {
+        self.inner.suggested_player_index()
+    }
+
+    /// Returns description of physical port to which gamepad is connected, if platform provides
+    /// it. On Linux this is `phys` of input device, e.g. `usb-0000:00:14.0-2/input0`. Gamepads
+    /// connected over Bluetooth report address of adapter.
+    pub fn port_path(&self) -> Option<&str> {
+        self.inner.port_path()
+    }
+
+    /// Returns serial number of device, if platform provides it. On Linux this is `uniq` of
+    /// input device, which is usually empty for USB gamepads and address of gamepad for Bluetooth
+    /// ones. Other platforms don't provide serial numbers and always return `None`.
+    pub fn serial_number(&self) -> Option<&str> {
+        self.inner.serial_number()
+    }
+
+    /// Powers off wireless gamepad. Gamepad disconnects and `Disconnected` event is emitted as
+    /// usual.
+    ///
+    /// Currently no platform supports it and `PowerOffError::NotSupported` is returned for all
+    /// connected gamepads. On Linux BlueZ only allows to disconnect devices over D-Bus, which
+    /// is not implemented.
+    pub fn power_off(&self) -> Result<(), PowerOffError> {
+        self.inner.power_off()
+    }
+
+    /// Shows player number on gamepad's player indicator LEDs, like quadrant LEDs of Xbox 360
+    /// controllers or player lights of Switch controllers. `player` has to be in range 1–4,
+    /// `None` turns indicator off.
+    ///
+    /// | Platform | Support                                                                   |
+    /// |----------|---------------------------------------------------------------------------|
+    /// | Linux    | `xpad` LED and `*:player-N` LEDs (`hid-nintendo`, `hid-playstation`).     |
+    /// | Windows  | Not supported, XInput sets quadrant LEDs by controller slot on its own.   |
+    /// | macOS    | Does nothing and returns `Ok(())`.                                        |
+    /// | Others   | Not supported.                                                            |
+    ///
+    /// On Linux user needs write access to `brightness` file of LEDs in `/sys/class/leds`,
+    /// usually granted with udev rule.
+    ///
+    /// On Windows `LedError::NotSupported` is always returned. XInput has no API for LEDs and
+    /// XInput gamepads aren't HID devices, so they can't be controlled with raw HID reports
+    /// either.
+    pub fn set_player_indicator(&self, player: Option<u8>) -> Result<(), LedError> {
+        match player {
+            Some(player) if !(1..=4).contains(&player) => Err(LedError::InvalidPlayer),
+            _ if !self.is_connected() => Err(LedError::Disconnected),
+            _ => self.inner.set_player_indicator(player),
+        }
+    }
+
+    /// Opens HID device of this gamepad to read and write raw reports. Useful for devices that
+    /// gilrs can't fully interpret. Gamepad still emits events as usual.
+    ///
+    /// On Linux this opens hidraw node of the device, so user needs read and write access to
+    /// `/dev/hidraw*` (usually granted with udev rule). Devices that don't use HID drivers, like
+    /// gamepads handled by `xpad`, don't have hidraw node. On macOS the device is opened
+    /// non-exclusively and since macOS 10.15 application needs "Input Monitoring" permission.
+    ///
+    /// Windows is not supported and error is always returned there, as on other platforms.
+    /// Gamepads are read only with XInput, which doesn't expose HID device of gamepad, and
+    /// gilrs doesn't enumerate HID devices that are not XInput gamepads (button boxes of
+    /// simulators, custom HID devices) at all, so they never appear as `Gamepad`.
+    #[cfg(feature = "raw-hid")]
+    pub fn raw_reports(&self) -> io::Result<RawReports> {
+        self.inner.raw_reports().map(|inner| RawReports { inner })
+    }
+
+    /// Reads current state of all buttons and axes directly from device, independently of events
+    /// that were already read. Use it to find out which buttons are held after events were not
+    /// processed for some time.
+    ///
+    /// Currently only supported on Linux and Windows. Returns `None` on other platforms, for
+    /// disconnected gamepads and if reading state failed.
+    pub fn current_state(&self) -> Option<RawState> {
+        self.inner.current_state()
+    }
+
     /// Creates `FfDevice` corresponding to this gamepad.
     pub fn ff_device(&self) -> Option<FfDevice> {
         self.inner.ff_device().map(|inner| FfDevice { inner })
@@ -215,9 +696,41 @@ use serde::{Deserialize, Serialize};
 pub struct EvCode(platform::EvCode);
 
 impl EvCode {
+    /// Number of codes that can be created with `EvCode::synthetic()`.
+    pub const SYNTHETIC_COUNT: u8 = 64;
+
+    /// Returns code that is never reported by gamepad, for elements created by higher level
+    /// libraries, like buttons bound to half of axis. Codes with different `index` are different.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not lower than `SYNTHETIC_COUNT`.
+    pub fn synthetic(index: u8) -> Self {
+        assert!(
+            index < Self::SYNTHETIC_COUNT,
+            "synthetic code index {} out of range",
+            index
+        );
+        EvCode(platform::EvCode::synthetic(index))
+    }
+
     pub fn into_u32(self) -> u32 {
         self.0.into_u32()
     }
+
+    /// Creates code from raw event type and number, as reported in `EvCodeInfo::kind` and
+    /// `EvCodeInfo::number`. Returns `None` if values are out of range of codes used on current
+    /// platform.
+    pub fn from_raw(kind: u32, number: u32) -> Option<Self> {
+        platform::EvCode::from_raw(kind, number).map(EvCode)
+    }
+
+    /// Returns platform specific details about this code, like raw event type and number or name
+    /// of constant used by the OS for it. Unlike `Display`, the result can be understood without
+    /// knowing on which platform it was printed, which makes it useful in logs and bug reports.
+    pub fn debug_info(self) -> EvCodeInfo {
+        self.0.debug_info()
+    }
 }
 
 impl Display for EvCode {
@@ -226,6 +739,59 @@ impl Display for EvCode {
     }
 }
 
+/// Details about `EvCode`, returned by `EvCode::debug_info()`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct EvCodeInfo {
+    /// Name of platform, e.g. "Linux" or "macOS".
+    pub platform: &'static str,
+    /// Event type on Linux, HID usage page on macOS and 1 for key codes or 3 for motion axes on
+    /// Android. 0 on other platforms.
+    pub kind: u32,
+    /// Event code on Linux, HID usage on macOS, key code or axis on Android and index of element
+    /// on other platforms.
+    pub number: u32,
+    /// Name of constant for this code, e.g. "BTN_SOUTH" on Linux, if it's known.
+    pub name: Option<&'static str>,
+}
+
+impl Display for EvCodeInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "{}/{}", name, self.number),
+            None if self.kind != 0 => write!(f, "{:#04x}/{:#04x}", self.kind, self.number),
+            None => write!(f, "{}", self.number),
+        }
+    }
+}
+
+/// Counts alive `Gilrs` instances and refuses to create new one if platform doesn't support more
+/// than one of them.
+#[derive(Debug)]
+struct InstanceGuard {
+    counter: &'static AtomicUsize,
+}
+
+impl InstanceGuard {
+    fn acquire(counter: &'static AtomicUsize, allow_multiple: bool) -> Option<Self> {
+        if allow_multiple {
+            counter.fetch_add(1, Ordering::SeqCst);
+        } else if counter
+            .compare_exchange(0, 1, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return None;
+        }
+
+        Some(InstanceGuard { counter })
+    }
+}
+
+impl Drop for InstanceGuard {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 /// Error type which can be returned when creating `Gilrs`.
 ///
 /// Private version of `Error` that use `platform::Gilrs`.
@@ -262,10 +828,14 @@ impl error::Error for PlatformError {
 
 /// Error type which can be returned when creating `Gilrs`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Gilrs does not support current platform, but you can use dummy context from this error if
     /// gamepad input is not essential.
     NotImplemented(Gilrs),
+    /// Another `Gilrs` is alive and current platform doesn't support multiple instances. See
+    /// `SUPPORTS_MULTIPLE_INSTANCES`.
+    AlreadyInUse,
     /// Platform specific error.
     Other(Box<dyn error::Error + Send + Sync + 'static>),
 }
@@ -274,11 +844,32 @@ impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             Error::NotImplemented(_) => f.write_str("Gilrs does not support current platform."),
+            Error::AlreadyInUse => f.write_str(
+                "Another Gilrs instance is alive and current platform doesn't support more than \
+                 one.",
+            ),
             Error::Other(ref e) => e.fmt(f),
         }
     }
 }
 
+impl Error {
+    /// Returns numeric error code reported by operating system (errno, `HRESULT` or Win32 error
+    /// code), if this error was caused by one.
+    pub fn os_error(&self) -> Option<i32> {
+        self.platform_error_kind().map(|kind| kind.code())
+    }
+
+    /// Returns OS error code together with tag describing what failed, if this error was caused
+    /// by OS error. See `PlatformErrorKind::find()`.
+    pub fn platform_error_kind(&self) -> Option<PlatformErrorKind> {
+        match self {
+            Error::Other(e) => PlatformErrorKind::find(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
@@ -288,6 +879,172 @@ impl error::Error for Error {
     }
 }
 
+/// Numeric error code reported by operating system and stable tag naming operation that failed,
+/// for example `("udev_new", 12)`.
+///
+/// Backends return it from `source()` of their errors, use `PlatformErrorKind::find()` or
+/// `Error::os_error()` to retrieve it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PlatformErrorKind {
+    tag: &'static str,
+    code: i32,
+}
+
+impl PlatformErrorKind {
+    pub fn new(tag: &'static str, code: i32) -> Self {
+        PlatformErrorKind { tag, code }
+    }
+
+    /// Creates `PlatformErrorKind` from current value of errno (or `GetLastError()` on Windows).
+    #[allow(dead_code)]
+    pub(crate) fn last_os_error(tag: &'static str) -> Self {
+        Self::from_io(tag, &io::Error::last_os_error())
+    }
+
+    /// Creates `PlatformErrorKind` from `io::Error`. Code is 0 if `err` does not come from OS.
+    #[allow(dead_code)]
+    pub(crate) fn from_io(tag: &'static str, err: &io::Error) -> Self {
+        Self::new(tag, err.raw_os_error().unwrap_or(0))
+    }
+
+    /// Stable name of operation that failed. Tags don't change between releases, so they can be
+    /// used for telemetry.
+    pub fn tag(&self) -> &'static str {
+        self.tag
+    }
+
+    /// Numeric error code, its meaning depends on platform.
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    /// Walks chain of `err` and its sources and returns first OS error found. `io::Error`s that
+    /// carry raw OS error are reported with `"io"` tag.
+    pub fn find(err: &(dyn error::Error + 'static)) -> Option<Self> {
+        let mut err = err;
+        loop {
+            if let Some(kind) = err.downcast_ref::<PlatformErrorKind>() {
+                return Some(*kind);
+            }
+
+            if let Some(code) = err
+                .downcast_ref::<io::Error>()
+                .and_then(io::Error::raw_os_error)
+            {
+                return Some(PlatformErrorKind::new("io", code));
+            }
+
+            err = err.source()?;
+        }
+    }
+}
+
+impl Display for PlatformErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed (os error {})", self.tag, self.code)
+    }
+}
+
+impl error::Error for PlatformErrorKind {}
+
+/// Error returned by `Gamepad::power_off()`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PowerOffError {
+    /// Platform or gamepad doesn't support powering off.
+    NotSupported,
+    /// Gamepad is not connected.
+    Disconnected,
+    /// Platform specific error.
+    Other(Box<dyn error::Error + Send + Sync + 'static>),
+}
+
+impl Display for PowerOffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PowerOffError::NotSupported => f.write_str("Gamepad can't be powered off."),
+            PowerOffError::Disconnected => f.write_str("Gamepad is not connected."),
+            PowerOffError::Other(ref e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for PowerOffError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PowerOffError::Other(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned by `Gamepad::set_player_indicator()`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LedError {
+    /// Platform or gamepad doesn't have player indicator that can be controlled.
+    NotSupported,
+    /// Player number is not in range 1–4.
+    InvalidPlayer,
+    /// Gamepad is not connected.
+    Disconnected,
+    /// Platform specific error.
+    Other(Box<dyn error::Error + Send + Sync + 'static>),
+}
+
+impl Display for LedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            LedError::NotSupported => f.write_str("Gamepad's player indicator can't be set."),
+            LedError::InvalidPlayer => f.write_str("Player number must be in range 1-4."),
+            LedError::Disconnected => f.write_str("Gamepad is not connected."),
+            LedError::Other(ref e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for LedError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            LedError::Other(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned by `Gilrs::set_polling_rate()`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PollingRateError {
+    /// Platform doesn't support changing polling rate.
+    NotSupported,
+    /// Rate is 0.
+    InvalidRate,
+    /// Platform specific error.
+    Other(Box<dyn error::Error + Send + Sync + 'static>),
+}
+
+impl Display for PollingRateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PollingRateError::NotSupported => {
+                f.write_str("Polling rate can't be changed on this platform.")
+            }
+            PollingRateError::InvalidRate => f.write_str("Polling rate must be greater than 0."),
+            PollingRateError::Other(ref e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for PollingRateError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PollingRateError::Other(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
 /// Provides the most common mappings of physical location of gamepad elements to their EvCodes.
 /// Some (or most) gamepads may use different mappings.
 pub mod native_ev_codes {
@@ -328,3 +1085,246 @@ pub mod native_ev_codes {
     pub const BTN_DPAD_LEFT: EvCode = EvCode(nec::BTN_DPAD_LEFT);
     pub const BTN_DPAD_RIGHT: EvCode = EvCode(nec::BTN_DPAD_RIGHT);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(min: i32, max: i32, deadzone: u32) -> AxisInfo {
+        AxisInfo {
+            min,
+            max,
+            deadzone: Some(deadzone),
+        }
+    }
+
+    #[test]
+    fn deadzone_normalized() {
+        let stick = info(-32768, 32767, 4000);
+        assert_eq!(stick.deadzone_normalized(false), Some(8000.0 / 65535.0));
+        assert_eq!(stick.deadzone_normalized(true), Some(4000.0 / 65535.0));
+
+        let trigger = info(0, 255, 51);
+        assert_eq!(trigger.deadzone_normalized(true), Some(0.2));
+        assert_eq!(trigger.deadzone_normalized(false), Some(0.4));
+
+        assert_eq!(info(10, 10, 5).deadzone_normalized(true), Some(0.0));
+        assert_eq!(info(10, 10, 5).deadzone_normalized(false), Some(0.0));
+        assert_eq!(info(10, 0, 5).deadzone_normalized(false), Some(0.0));
+
+        let unknown = AxisInfo {
+            deadzone: None,
+            ..trigger
+        };
+        assert_eq!(unknown.deadzone_normalized(true), None);
+        let degenerate = AxisInfo {
+            deadzone: None,
+            ..info(10, 10, 0)
+        };
+        assert_eq!(degenerate.deadzone_normalized(true), Some(0.0));
+    }
+
+    #[test]
+    fn instance_guard() {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        // Create, drop and create again.
+        let first = InstanceGuard::acquire(&COUNTER, false).unwrap();
+        assert!(InstanceGuard::acquire(&COUNTER, false).is_none());
+        drop(first);
+        let single = InstanceGuard::acquire(&COUNTER, false).unwrap();
+        drop(single);
+        assert_eq!(COUNTER.load(Ordering::SeqCst), 0);
+
+        // Concurrent instances.
+        let guards: Vec<_> = (0..3)
+            .map(|_| InstanceGuard::acquire(&COUNTER, true).unwrap())
+            .collect();
+        assert_eq!(COUNTER.load(Ordering::SeqCst), 3);
+        assert!(InstanceGuard::acquire(&COUNTER, false).is_none());
+        drop(guards);
+        assert_eq!(COUNTER.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn multiple_instances() {
+        let first = match new_or_skip() {
+            Some(gilrs) => gilrs,
+            None => return,
+        };
+        let second = Gilrs::new().unwrap();
+        assert_eq!(first.last_gamepad_hint(), second.last_gamepad_hint());
+        drop(first);
+        drop(second);
+
+        assert!(Gilrs::new().is_ok());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn event_source_fds() {
+        let mut gilrs = match new_or_skip() {
+            Some(gilrs) => gilrs,
+            None => return,
+        };
+        let monitor = gilrs.event_source_fd().unwrap();
+        assert!(monitor >= 0);
+        assert!(!gilrs.device_fds().contains(&monitor));
+
+        while gilrs.next_event().is_some() {}
+        let connected = (0..gilrs.last_gamepad_hint())
+            .filter_map(|id| gilrs.gamepad(id))
+            .filter(|gamepad| gamepad.is_connected())
+            .count();
+        assert!(gilrs.device_fds().len() >= connected);
+        assert!(gilrs.device_fds().iter().all(|&fd| fd >= 0));
+    }
+
+    /// Creates `Gilrs` or returns `None` if backend can't be initialized on this machine, for
+    /// example in containers without udev.
+    #[cfg(target_os = "linux")]
+    fn new_or_skip() -> Option<Gilrs> {
+        match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e @ Error::NotImplemented(_)) | Err(e @ Error::Other(_)) => {
+                eprintln!("Skipping test, backend is not available: {}", e);
+                None
+            }
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[derive(Debug)]
+    struct OpenError(PlatformErrorKind);
+
+    impl Display for OpenError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Failed to open device: {}", self.0)
+        }
+    }
+
+    impl error::Error for OpenError {
+        fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn os_error() {
+        use std::error::Error as _;
+
+        let kind = PlatformErrorKind::new("open", 13);
+        let err = Error::Other(Box::new(OpenError(kind)));
+        assert_eq!(err.os_error(), Some(13));
+        assert_eq!(err.platform_error_kind().unwrap().tag(), "open");
+        assert_eq!(
+            err.to_string(),
+            "Failed to open device: open failed (os error 13)"
+        );
+
+        let source = err.source().unwrap();
+        assert!(source.is::<OpenError>());
+        let source = source.source().unwrap();
+        assert_eq!(source.downcast_ref::<PlatformErrorKind>(), Some(&kind));
+        assert!(source.source().is_none());
+
+        let io = Error::Other(Box::new(io::Error::from_raw_os_error(2)));
+        assert_eq!(
+            io.platform_error_kind(),
+            Some(PlatformErrorKind::new("io", 2))
+        );
+
+        let other = Error::Other("no code".into());
+        assert_eq!(other.os_error(), None);
+    }
+
+    #[test]
+    fn ev_code_from_raw() {
+        for &code in &[native_ev_codes::BTN_SOUTH, native_ev_codes::AXIS_DPADY] {
+            let info = code.debug_info();
+            assert_eq!(EvCode::from_raw(info.kind, info.number), Some(code));
+        }
+        // HID backends accept any page and usage.
+        #[cfg(not(any(target_os = "macos", target_os = "freebsd")))]
+        assert_eq!(EvCode::from_raw(0x10_0000, 0x10_0000), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn ev_code_debug_info() {
+        let south = native_ev_codes::BTN_SOUTH.debug_info();
+        assert_eq!(
+            south,
+            EvCodeInfo {
+                platform: "Linux",
+                kind: 0x01,
+                number: 0x130,
+                name: Some("BTN_SOUTH"),
+            }
+        );
+        assert_eq!(south.to_string(), "BTN_SOUTH/304");
+        assert_eq!(native_ev_codes::BTN_LT.debug_info().name, Some("BTN_TL"));
+        assert_eq!(
+            native_ev_codes::AXIS_DPADY.debug_info().to_string(),
+            "ABS_HAT0Y/17"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn ev_code_debug_info() {
+        let x = native_ev_codes::AXIS_LSTICKX.debug_info();
+        assert_eq!(x.platform, "macOS");
+        assert_eq!(x.to_string(), "GD_X/48");
+
+        let south = native_ev_codes::BTN_SOUTH.debug_info();
+        assert_eq!(south.name, None);
+        assert_eq!(south.to_string(), "0x09/0x01");
+    }
+
+    #[test]
+    #[cfg(target_os = "freebsd")]
+    fn ev_code_debug_info() {
+        let rx = native_ev_codes::AXIS_RSTICKX.debug_info();
+        assert_eq!(rx.platform, "FreeBSD");
+        assert_eq!(rx.to_string(), "GD_RX/51");
+        assert_eq!(
+            native_ev_codes::BTN_SOUTH.debug_info().to_string(),
+            "0x09/0x01"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "android")]
+    fn ev_code_debug_info() {
+        let south = native_ev_codes::BTN_SOUTH.debug_info();
+        assert_eq!(south.platform, "Android");
+        assert_eq!(south.to_string(), "AKEYCODE_BUTTON_A/96");
+        assert_eq!(
+            native_ev_codes::AXIS_LT2.debug_info().to_string(),
+            "AMOTION_EVENT_AXIS_LTRIGGER/17"
+        );
+    }
+
+    #[test]
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "freebsd",
+        target_os = "android"
+    )))]
+    fn ev_code_debug_info() {
+        let south = native_ev_codes::BTN_SOUTH.debug_info();
+        assert_eq!(south.kind, 0);
+        assert_eq!(south.to_string(), "BTN_SOUTH/12");
+        assert_eq!(
+            native_ev_codes::AXIS_LT2.debug_info().to_string(),
+            "AXIS_LT2/11"
+        );
+        assert_eq!(
+            native_ev_codes::BTN_DPAD_RIGHT.debug_info().to_string(),
+            "BTN_DPAD_RIGHT/30"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::jni::{jvalue, GlobalRef};
+
+use std::cmp;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Represents gamepad. Reexported as FfDevice
+///
+/// Vibration is played with `android.os.Vibrator` of input device, which is accessed through
+/// JNI. Vibrator has only one motor, so it vibrates with magnitude of the stronger one.
+/// `VibrationEffect` requires API level 26 and application has to have `VIBRATE` permission.
+#[derive(Debug)]
+pub struct Device {
+    vibrator: Arc<GlobalRef>,
+    // Amplitude of last vibration, 0 if vibrator was stopped.
+    amplitude: i32,
+    // When last vibration ends.
+    end: Instant,
+}
+
+impl Device {
+    pub(crate) fn new(vibrator: Arc<GlobalRef>) -> Self {
+        Device {
+            vibrator,
+            amplitude: 0,
+            end: Instant::now(),
+        }
+    }
+
+    /// Returns number of effects that can be played at the same time. Vibrator doesn't have
+    /// effect slots, effects are mixed before vibration is started, so this is the same as on
+    /// Windows.
+    pub fn query_max_effects(&self) -> usize {
+        16
+    }
+
+    /// Global reference to vibrator stays valid after device is removed, vibrating it just has
+    /// no effect.
+    pub fn is_connected(&self) -> bool {
+        true
+    }
+
+    /// Sets magnitude for strong and weak ff motors.
+    pub fn set_ff_state(&mut self, strong: u16, weak: u16, min_duration: Duration) {
+        // `VibrationEffect` accepts amplitude in range 1..=255.
+        let amplitude = (u32::from(cmp::max(strong, weak)) * 255 + 65534) / 65535;
+        let amplitude = amplitude as i32;
+        let now = Instant::now();
+
+        // Starting vibration again restarts it, so don't do it on every tick if it still plays
+        // for long enough.
+        if amplitude == self.amplitude && (amplitude == 0 || now + min_duration / 2 < self.end) {
+            return;
+        }
+
+        let result = if amplitude == 0 {
+            self.cancel()
+        } else {
+            self.vibrate(amplitude, min_duration)
+        };
+        if result.is_none() {
+            error!("Failed to set vibration of input device to {}", amplitude);
+        }
+
+        // Failed vibration is not retried until magnitude changes, so error is not logged on
+        // every tick.
+        self.amplitude = amplitude;
+        self.end = now + min_duration;
+    }
+
+    fn vibrate(&self, amplitude: i32, duration: Duration) -> Option<()> {
+        let millis = cmp::max(duration.as_millis(), 1) as i64;
+        let env = self.vibrator.vm().env()?;
+
+        env.with_frame(|env| {
+            let effect_class = env.class("android/os/VibrationEffect")?;
+            let create_one_shot = env.static_method(
+                effect_class,
+                "createOneShot",
+                "(JI)Landroid/os/VibrationEffect;",
+            )?;
+            let effect = env.call_static_object(
+                effect_class,
+                create_one_shot,
+                &[jvalue { j: millis }, jvalue { i: amplitude }],
+            )?;
+
+            let class = env.class("android/os/Vibrator")?;
+            let vibrate = env.method(class, "vibrate", "(Landroid/os/VibrationEffect;)V")?;
+            env.call_void(self.vibrator.as_obj(), vibrate, &[jvalue { l: effect }])
+        })
+    }
+
+    fn cancel(&self) -> Option<()> {
+        let env = self.vibrator.vm().env()?;
+
+        env.with_frame(|env| {
+            let class = env.class("android/os/Vibrator")?;
+            let cancel = env.method(class, "cancel", "()V")?;
+            env.call_void(self.vibrator.as_obj(), cancel, &[])
+        })
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        if self.amplitude != 0 {
+            let _ = self.cancel();
+        }
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Subset of NDK's `android/input.h` used by the backend.
+
+#![allow(non_camel_case_types, dead_code)]
+
+use std::os::raw::c_int;
+
+pub enum AInputEvent {}
+
+pub const AINPUT_EVENT_TYPE_KEY: c_int = 1;
+pub const AINPUT_EVENT_TYPE_MOTION: c_int = 2;
+
+pub const AKEY_EVENT_ACTION_DOWN: c_int = 0;
+pub const AKEY_EVENT_ACTION_UP: c_int = 1;
+
+pub const AINPUT_SOURCE_CLASS_JOYSTICK: c_int = 0x0000_0010;
+pub const AINPUT_SOURCE_DPAD: c_int = 0x0000_0201;
+pub const AINPUT_SOURCE_GAMEPAD: c_int = 0x0000_0401;
+pub const AINPUT_SOURCE_JOYSTICK: c_int = 0x0100_0010;
+
+pub const AKEYCODE_BACK: c_int = 4;
+pub const AKEYCODE_DPAD_UP: c_int = 19;
+pub const AKEYCODE_DPAD_DOWN: c_int = 20;
+pub const AKEYCODE_DPAD_LEFT: c_int = 21;
+pub const AKEYCODE_DPAD_RIGHT: c_int = 22;
+pub const AKEYCODE_DPAD_CENTER: c_int = 23;
+pub const AKEYCODE_MENU: c_int = 82;
+pub const AKEYCODE_BUTTON_A: c_int = 96;
+pub const AKEYCODE_BUTTON_B: c_int = 97;
+pub const AKEYCODE_BUTTON_C: c_int = 98;
+pub const AKEYCODE_BUTTON_X: c_int = 99;
+pub const AKEYCODE_BUTTON_Y: c_int = 100;
+pub const AKEYCODE_BUTTON_Z: c_int = 101;
+pub const AKEYCODE_BUTTON_L1: c_int = 102;
+pub const AKEYCODE_BUTTON_R1: c_int = 103;
+pub const AKEYCODE_BUTTON_L2: c_int = 104;
+pub const AKEYCODE_BUTTON_R2: c_int = 105;
+pub const AKEYCODE_BUTTON_THUMBL: c_int = 106;
+pub const AKEYCODE_BUTTON_THUMBR: c_int = 107;
+pub const AKEYCODE_BUTTON_START: c_int = 108;
+pub const AKEYCODE_BUTTON_SELECT: c_int = 109;
+pub const AKEYCODE_BUTTON_MODE: c_int = 110;
+pub const AKEYCODE_BUTTON_1: c_int = 188;
+pub const AKEYCODE_BUTTON_16: c_int = 203;
+
+pub const AMOTION_EVENT_AXIS_X: c_int = 0;
+pub const AMOTION_EVENT_AXIS_Y: c_int = 1;
+pub const AMOTION_EVENT_AXIS_Z: c_int = 11;
+pub const AMOTION_EVENT_AXIS_RX: c_int = 12;
+pub const AMOTION_EVENT_AXIS_RY: c_int = 13;
+pub const AMOTION_EVENT_AXIS_RZ: c_int = 14;
+pub const AMOTION_EVENT_AXIS_HAT_X: c_int = 15;
+pub const AMOTION_EVENT_AXIS_HAT_Y: c_int = 16;
+pub const AMOTION_EVENT_AXIS_LTRIGGER: c_int = 17;
+pub const AMOTION_EVENT_AXIS_RTRIGGER: c_int = 18;
+pub const AMOTION_EVENT_AXIS_GAS: c_int = 22;
+pub const AMOTION_EVENT_AXIS_BRAKE: c_int = 23;
+pub const AMOTION_EVENT_AXIS_GENERIC_1: c_int = 32;
+pub const AMOTION_EVENT_AXIS_GENERIC_2: c_int = 33;
+
+#[link(name = "android")]
+extern "C" {
+    pub fn AInputEvent_getType(event: *const AInputEvent) -> c_int;
+    pub fn AInputEvent_getDeviceId(event: *const AInputEvent) -> c_int;
+    pub fn AInputEvent_getSource(event: *const AInputEvent) -> c_int;
+    pub fn AKeyEvent_getAction(key_event: *const AInputEvent) -> c_int;
+    pub fn AKeyEvent_getKeyCode(key_event: *const AInputEvent) -> c_int;
+    pub fn AKeyEvent_getRepeatCount(key_event: *const AInputEvent) -> c_int;
+    pub fn AMotionEvent_getAxisValue(
+        motion_event: *const AInputEvent,
+        axis: c_int,
+        pointer_index: usize,
+    ) -> f32;
+}
@@ -0,0 +1,887 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::ffi::{self, AInputEvent};
+use super::jni::{jclass, jobject, jvalue, Env, GlobalRef, Vm};
+use super::FfDevice;
+use crate::{
+    AxisInfo, Event, EventType, LedError, PlatformError, PollingRateError, PowerInfo,
+    PowerOffError, RawState,
+};
+use uuid::Uuid;
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::os::raw::{c_int, c_void};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const HOTPLUG_SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// NDK doesn't have API for listing input devices. Gamepads are enumerated through JNI after
+/// application passes its Java VM to `android_set_java_vm()`, otherwise application has to
+/// forward `InputManager.InputDeviceListener` callbacks. Input events are always forwarded by
+/// application.
+#[derive(Debug)]
+pub struct Gilrs {
+    gamepads: Vec<Gamepad>,
+    events: VecDeque<Event>,
+    vm: Option<Vm>,
+    // Input devices that are not gamepads, so they are not queried on every scan. Android never
+    // reuses device IDs.
+    non_gamepads: Vec<i32>,
+    last_scan: Instant,
+}
+
+impl Gilrs {
+    pub(crate) fn new() -> Result<Self, PlatformError> {
+        Ok(Gilrs {
+            gamepads: Vec::new(),
+            events: VecDeque::new(),
+            vm: None,
+            non_gamepads: Vec::new(),
+            last_scan: Instant::now(),
+        })
+    }
+
+    pub(crate) fn next_event(&mut self) -> Option<Event> {
+        if let Some(event) = self.events.pop_front() {
+            return Some(event);
+        }
+
+        // `InputDeviceListener` can't be implemented without Java code, so devices are
+        // enumerated again once per second instead.
+        if self.vm.is_some() && self.last_scan.elapsed() >= HOTPLUG_SCAN_INTERVAL {
+            self.rescan_gamepads();
+        }
+
+        self.events.pop_front()
+    }
+
+    pub fn gamepad(&self, id: usize) -> Option<&Gamepad> {
+        self.gamepads.get(id)
+    }
+
+    /// Returns index greater than index of last connected gamepad.
+    pub fn last_gamepad_hint(&self) -> usize {
+        self.gamepads.len()
+    }
+
+    /// Enumerates input devices through JNI. Without Java VM devices are added and removed only by
+    /// application, so there is nothing to rescan.
+    pub(crate) fn rescan_gamepads(&mut self) -> usize {
+        let vm = match self.vm {
+            Some(vm) => vm,
+            None => return 0,
+        };
+        self.last_scan = Instant::now();
+
+        let device_ids = match device_ids(vm) {
+            Some(ids) => ids,
+            None => {
+                error!("Failed to enumerate input devices");
+                return 0;
+            }
+        };
+        let mut changes = 0;
+
+        // Handle removed devices first, so reconnected gamepads can reuse their old ID.
+        let removed: Vec<_> = self
+            .gamepads
+            .iter()
+            .filter(|gp| gp.connected && !device_ids.contains(&gp.device_id))
+            .map(|gp| gp.device_id)
+            .collect();
+        for device_id in removed {
+            self.android_device_removed(device_id);
+            changes += 1;
+        }
+
+        for device_id in device_ids {
+            if self.find_device(device_id).is_some() || self.non_gamepads.contains(&device_id) {
+                continue;
+            }
+
+            match query_device(vm, device_id) {
+                Some(gamepad) => {
+                    self.add_gamepad(gamepad);
+                    changes += 1;
+                }
+                None => self.non_gamepads.push(device_id),
+            }
+        }
+
+        changes
+    }
+
+    pub(crate) fn set_polling_rate(&mut self, _hz: u32) -> Result<(), PollingRateError> {
+        Err(PollingRateError::NotSupported)
+    }
+
+    pub(crate) unsafe fn handle_android_event(&mut self, event: *const c_void) -> bool {
+        let event = event as *const AInputEvent;
+        if event.is_null() || !is_gamepad_source(ffi::AInputEvent_getSource(event)) {
+            return false;
+        }
+
+        let device_id = ffi::AInputEvent_getDeviceId(event);
+        let id = match self.find_device(device_id) {
+            Some(id) => id,
+            None => self.add_unknown(device_id),
+        };
+
+        match ffi::AInputEvent_getType(event) {
+            ffi::AINPUT_EVENT_TYPE_KEY => {
+                let code = match key_code(ffi::AKeyEvent_getKeyCode(event)) {
+                    Some(code) => code,
+                    None => return false,
+                };
+                let pressed = match ffi::AKeyEvent_getAction(event) {
+                    ffi::AKEY_EVENT_ACTION_DOWN => true,
+                    ffi::AKEY_EVENT_ACTION_UP => false,
+                    _ => return false,
+                };
+                // Gilrs doesn't report key repeats, but they still belong to the gamepad.
+                if ffi::AKeyEvent_getRepeatCount(event) == 0 {
+                    if let Some(ev) = self.gamepads[id].set_button(code, pressed) {
+                        self.events.push_back(Event::new(id, ev));
+                    }
+                }
+
+                true
+            }
+            ffi::AINPUT_EVENT_TYPE_MOTION => {
+                let gamepad = &mut self.gamepads[id];
+                for (idx, &code) in gamepad.axes.iter().enumerate() {
+                    let value = ffi::AMotionEvent_getAxisValue(event, c_int::from(code.code), 0);
+                    let value = axis_value(code, value);
+                    if gamepad.axes_state[idx] != value {
+                        gamepad.axes_state[idx] = value;
+                        self.events.push_back(Event::new(
+                            id,
+                            EventType::AxisValueChanged(value, crate::EvCode(code)),
+                        ));
+                    }
+                }
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) unsafe fn android_set_java_vm(&mut self, vm: *mut c_void) {
+        self.vm = Vm::from_ptr(vm);
+        self.rescan_gamepads();
+    }
+
+    pub(crate) fn android_device_added(
+        &mut self,
+        device_id: i32,
+        name: &str,
+        vendor_id: u16,
+        product_id: u16,
+        axes: &[i32],
+    ) -> usize {
+        let mut gamepad = Gamepad::new(device_id, name, vendor_id, product_id, axes);
+        gamepad.vibrator = self
+            .vm
+            .and_then(|vm| query_device(vm, device_id))
+            .and_then(|gp| gp.vibrator);
+
+        self.add_gamepad(gamepad)
+    }
+
+    fn add_gamepad(&mut self, gamepad: Gamepad) -> usize {
+        let device_id = gamepad.device_id;
+
+        if let Some(id) = self.find_device(device_id) {
+            // Gamepad added from its first event gets real name, UUID and axes. Repeated
+            // `Connected` event makes gilrs read them again.
+            if self.gamepads[id].is_placeholder {
+                self.gamepads[id] = gamepad;
+                self.events.push_back(Event::new(id, EventType::Connected));
+            }
+
+            return id;
+        }
+
+        // Android never reuses device IDs, but reusing slot of disconnected gamepad with the same
+        // UUID and name keeps ID stable when gamepad reconnects.
+        let slot = self
+            .gamepads
+            .iter()
+            .position(|gp| !gp.connected && gp.uuid == gamepad.uuid && gp.name == gamepad.name);
+        let id = match slot {
+            Some(id) => {
+                self.gamepads[id] = gamepad;
+                id
+            }
+            None => {
+                self.gamepads.push(gamepad);
+                self.gamepads.len() - 1
+            }
+        };
+        self.events.push_back(Event::new(id, EventType::Connected));
+
+        id
+    }
+
+    pub(crate) fn android_device_removed(&mut self, device_id: i32) {
+        if let Some(id) = self.find_device(device_id) {
+            self.gamepads[id].connected = false;
+            self.events
+                .push_back(Event::new(id, EventType::Disconnected));
+        }
+    }
+
+    /// Adds device that sent event before it was reported by application or found by scan. If
+    /// device can't be queried through JNI, it gets placeholder name and axes present on typical
+    /// gamepad.
+    fn add_unknown(&mut self, device_id: i32) -> usize {
+        if let Some(gamepad) = self.vm.and_then(|vm| query_device(vm, device_id)) {
+            return self.add_gamepad(gamepad);
+        }
+
+        let name = format!("Android input device {}", device_id);
+        let mut gamepad = Gamepad::new(device_id, &name, 0, 0, &PLACEHOLDER_AXES);
+        gamepad.is_placeholder = true;
+
+        self.add_gamepad(gamepad)
+    }
+
+    fn find_device(&self, device_id: i32) -> Option<usize> {
+        self.gamepads
+            .iter()
+            .position(|gp| gp.connected && gp.device_id == device_id)
+    }
+}
+
+/// Returns IDs of all input devices, from `InputDevice.getDeviceIds()`.
+fn device_ids(vm: Vm) -> Option<Vec<i32>> {
+    vm.env()?.with_frame(|env| {
+        let class = env.class("android/view/InputDevice")?;
+        let get_device_ids = env.static_method(class, "getDeviceIds", "()[I")?;
+        let ids = env.call_static_object(class, get_device_ids, &[])?;
+
+        env.int_array(ids)
+    })
+}
+
+/// Reads name, vendor and product ID, motion ranges and vibrator of input device with
+/// `InputDevice.getDevice()`. Returns `None` if device is not gamepad or joystick.
+fn query_device(vm: Vm, device_id: i32) -> Option<Gamepad> {
+    vm.env()?.with_frame(|env| {
+        let class = env.class("android/view/InputDevice")?;
+        let get_device = env.static_method(class, "getDevice", "(I)Landroid/view/InputDevice;")?;
+        let device = env.call_static_object(class, get_device, &[jvalue { i: device_id }])?;
+        let call_int = |name: &str| env.call_int(device, env.method(class, name, "()I")?, &[]);
+
+        if !is_gamepad_source(call_int("getSources")?) {
+            return None;
+        }
+
+        let get_name = env.method(class, "getName", "()Ljava/lang/String;")?;
+        let name = env.string(env.call_object(device, get_name, &[])?)?;
+        // Not available before API level 19.
+        let vendor_id = call_int("getVendorId").unwrap_or(0);
+        let product_id = call_int("getProductId").unwrap_or(0);
+        let axes = motion_ranges(env, class, device)?;
+
+        let mut gamepad =
+            Gamepad::new(device_id, &name, vendor_id as u16, product_id as u16, &axes);
+        gamepad.vibrator = vibrator(vm, env, class, device);
+
+        Some(gamepad)
+    })
+}
+
+/// Returns axes of device's motion ranges that belong to joystick. Touchpads of some gamepads
+/// are part of the same device, but their ranges have different source.
+fn motion_ranges(env: &Env, class: jclass, device: jobject) -> Option<Vec<c_int>> {
+    let get_motion_ranges = env.method(class, "getMotionRanges", "()Ljava/util/List;")?;
+    let ranges = env.call_object(device, get_motion_ranges, &[])?;
+    let list = env.class("java/util/List")?;
+    let size = env.method(list, "size", "()I")?;
+    let get = env.method(list, "get", "(I)Ljava/lang/Object;")?;
+    let range_class = env.class("android/view/InputDevice$MotionRange")?;
+    let get_axis = env.method(range_class, "getAxis", "()I")?;
+    let get_source = env.method(range_class, "getSource", "()I")?;
+
+    let mut axes = Vec::new();
+    for idx in 0..env.call_int(ranges, size, &[])? {
+        let range = env.call_object(ranges, get, &[jvalue { i: idx }])?;
+        let source = env.call_int(range, get_source, &[])?;
+        if source & ffi::AINPUT_SOURCE_CLASS_JOYSTICK != 0 {
+            axes.push(env.call_int(range, get_axis, &[])?);
+        }
+    }
+
+    Some(axes)
+}
+
+/// Returns `InputDevice.getVibrator()` if device has vibrator.
+fn vibrator(vm: Vm, env: &Env, class: jclass, device: jobject) -> Option<Arc<GlobalRef>> {
+    let get_vibrator = env.method(class, "getVibrator", "()Landroid/os/Vibrator;")?;
+    let vibrator = env.call_object(device, get_vibrator, &[])?;
+    let vibrator_class = env.class("android/os/Vibrator")?;
+    let has_vibrator = env.method(vibrator_class, "hasVibrator", "()Z")?;
+
+    if env.call_bool(vibrator, has_vibrator, &[])? {
+        GlobalRef::new(vm, env, vibrator).map(Arc::new)
+    } else {
+        None
+    }
+}
+
+/// Axes assumed for devices added with `add_unknown()`.
+const PLACEHOLDER_AXES: [c_int; 12] = [
+    ffi::AMOTION_EVENT_AXIS_X,
+    ffi::AMOTION_EVENT_AXIS_Y,
+    ffi::AMOTION_EVENT_AXIS_Z,
+    ffi::AMOTION_EVENT_AXIS_RX,
+    ffi::AMOTION_EVENT_AXIS_RY,
+    ffi::AMOTION_EVENT_AXIS_RZ,
+    ffi::AMOTION_EVENT_AXIS_HAT_X,
+    ffi::AMOTION_EVENT_AXIS_HAT_Y,
+    ffi::AMOTION_EVENT_AXIS_LTRIGGER,
+    ffi::AMOTION_EVENT_AXIS_RTRIGGER,
+    ffi::AMOTION_EVENT_AXIS_GAS,
+    ffi::AMOTION_EVENT_AXIS_BRAKE,
+];
+
+/// Returns axes in order used by SDL's Android backend, so `aN` in SDL mappings for Android
+/// refers to the same axis. SDL sorts motion ranges of device by axis ID and reports hats
+/// separately, gilrs puts them after other axes, as `AXIS_DPADX` and `AXIS_DPADY`.
+fn axes_from_motion_ranges(ranges: &[c_int]) -> Vec<EvCode> {
+    let mut ranges: Vec<_> = ranges
+        .iter()
+        .cloned()
+        .filter(|&axis| axis >= 0 && axis <= c_int::from(std::u16::MAX))
+        .collect();
+    ranges.sort_by_key(|&axis| {
+        let is_hat = axis == ffi::AMOTION_EVENT_AXIS_HAT_X || axis == ffi::AMOTION_EVENT_AXIS_HAT_Y;
+        (is_hat, axis)
+    });
+    ranges.dedup();
+
+    ranges.into_iter().map(EvCode::axis).collect()
+}
+
+fn is_gamepad_source(source: c_int) -> bool {
+    source & ffi::AINPUT_SOURCE_GAMEPAD == ffi::AINPUT_SOURCE_GAMEPAD
+        || source & ffi::AINPUT_SOURCE_JOYSTICK == ffi::AINPUT_SOURCE_JOYSTICK
+}
+
+/// Returns button for Android key code. `AKEYCODE_BACK` and `AKEYCODE_MENU` are reported as
+/// select and start, like SDL does.
+fn key_code(keycode: c_int) -> Option<EvCode> {
+    let keycode = match keycode {
+        ffi::AKEYCODE_BACK => ffi::AKEYCODE_BUTTON_SELECT,
+        ffi::AKEYCODE_MENU => ffi::AKEYCODE_BUTTON_START,
+        keycode => keycode,
+    };
+
+    native_ev_codes::BUTTONS
+        .iter()
+        .find(|code| c_int::from(code.code) == keycode)
+        .cloned()
+}
+
+/// Converts axis value reported by Android, which is in [-1.0, 1.0] range (or [0.0, 1.0] for
+/// triggers), to range of `axis_info()`.
+fn axis_value(code: EvCode, value: f32) -> i32 {
+    (value * axis_info(code).max as f32).round() as i32
+}
+
+fn axis_info(code: EvCode) -> &'static AxisInfo {
+    static STICK: AxisInfo = AxisInfo {
+        min: -32767,
+        max: 32767,
+        deadzone: None,
+    };
+    static TRIGGER: AxisInfo = AxisInfo {
+        min: 0,
+        max: 32767,
+        deadzone: None,
+    };
+    static HAT: AxisInfo = AxisInfo {
+        min: -1,
+        max: 1,
+        deadzone: None,
+    };
+
+    match c_int::from(code.code) {
+        ffi::AMOTION_EVENT_AXIS_HAT_X | ffi::AMOTION_EVENT_AXIS_HAT_Y => &HAT,
+        ffi::AMOTION_EVENT_AXIS_LTRIGGER
+        | ffi::AMOTION_EVENT_AXIS_RTRIGGER
+        | ffi::AMOTION_EVENT_AXIS_GAS
+        | ffi::AMOTION_EVENT_AXIS_BRAKE => &TRIGGER,
+        _ => &STICK,
+    }
+}
+
+#[derive(Debug)]
+pub struct Gamepad {
+    device_id: i32,
+    name: String,
+    uuid: Uuid,
+    connected: bool,
+    is_placeholder: bool,
+    axes: Vec<EvCode>,
+    buttons_state: [bool; 36],
+    axes_state: Vec<i32>,
+    vibrator: Option<Arc<GlobalRef>>,
+}
+
+impl Gamepad {
+    fn new(device_id: i32, name: &str, vendor_id: u16, product_id: u16, axes: &[i32]) -> Self {
+        let axes = axes_from_motion_ranges(axes);
+        Gamepad {
+            device_id,
+            name: name.to_owned(),
+            uuid: create_uuid(vendor_id, product_id),
+            connected: true,
+            is_placeholder: false,
+            axes_state: vec![0; axes.len()],
+            axes,
+            buttons_state: [false; 36],
+            vibrator: None,
+        }
+    }
+
+    fn set_button(&mut self, code: EvCode, pressed: bool) -> Option<EventType> {
+        let idx = native_ev_codes::BUTTONS
+            .iter()
+            .position(|&btn| btn == code)?;
+        if self.buttons_state[idx] == pressed {
+            return None;
+        }
+
+        self.buttons_state[idx] = pressed;
+        if pressed {
+            Some(EventType::ButtonPressed(crate::EvCode(code)))
+        } else {
+            Some(EventType::ButtonReleased(crate::EvCode(code)))
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn manufacturer_string(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn product_string(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn power_info(&self) -> PowerInfo {
+        PowerInfo::Unknown
+    }
+
+    /// Vibrator of input device is only available through JNI, so it's only found if
+    /// application passed its Java VM to `android_set_java_vm()`.
+    pub fn is_ff_supported(&self) -> bool {
+        self.vibrator.is_some()
+    }
+
+    pub fn power_off(&self) -> Result<(), PowerOffError> {
+        Err(PowerOffError::NotSupported)
+    }
+
+    pub fn set_player_indicator(&self, _player: Option<u8>) -> Result<(), LedError> {
+        Err(LedError::NotSupported)
+    }
+
+    pub fn input_report_rate(&self) -> Option<u32> {
+        None
+    }
+
+    pub fn has_touchpad(&self) -> bool {
+        false
+    }
+
+    pub fn current_state(&self) -> Option<RawState> {
+        if !self.connected {
+            return None;
+        }
+
+        Some(RawState {
+            buttons: native_ev_codes::BUTTONS
+                .iter()
+                .map(|&code| crate::EvCode(code))
+                .zip(self.buttons_state.iter().cloned())
+                .collect(),
+            axes: self
+                .axes
+                .iter()
+                .map(|&code| crate::EvCode(code))
+                .zip(self.axes_state.iter().cloned())
+                .collect(),
+        })
+    }
+
+    pub fn os_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn suggested_player_index(&self) -> Option<u8> {
+        None
+    }
+
+    pub fn port_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<&str> {
+        None
+    }
+
+    #[cfg(feature = "raw-hid")]
+    pub fn raw_reports(&self) -> std::io::Result<super::RawReports> {
+        Err(super::raw_hid::unsupported())
+    }
+
+    pub fn ff_device(&self) -> Option<FfDevice> {
+        self.vibrator.clone().map(FfDevice::new)
+    }
+
+    pub fn buttons(&self) -> &[EvCode] {
+        &native_ev_codes::BUTTONS
+    }
+
+    pub fn axes(&self) -> &[EvCode] {
+        &self.axes
+    }
+
+    pub(crate) fn axis_info(&self, nec: EvCode) -> Option<&AxisInfo> {
+        if self.axes.contains(&nec) {
+            Some(axis_info(nec))
+        } else {
+            None
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+}
+
+/// Creates UUID in the same format as SDL's Android GUID (bus, vendor and product), but with
+/// button mask left empty, because it's not known to backend.
+fn create_uuid(vendor_id: u16, product_id: u16) -> Uuid {
+    if vendor_id == 0 && product_id == 0 {
+        return Uuid::nil();
+    }
+
+    // Android doesn't report bus, SDL always uses Bluetooth.
+    let bus = 0x05u32.to_be();
+    let vendor = vendor_id.to_be();
+    let product = product_id.to_be();
+    Uuid::from_fields(
+        bus,
+        vendor,
+        0,
+        &[(product >> 8) as u8, product as u8, 0, 0, 0, 0, 0, 0],
+    )
+    .unwrap()
+}
+
+const KIND_KEY: u16 = 1;
+const KIND_AXIS: u16 = 3;
+const KIND_SYNTHETIC: u16 = 0xffff;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+/// Android key code or motion axis.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct EvCode {
+    kind: u16,
+    code: u16,
+}
+
+impl EvCode {
+    const fn key(code: c_int) -> Self {
+        EvCode {
+            kind: KIND_KEY,
+            code: code as u16,
+        }
+    }
+
+    const fn axis(code: c_int) -> Self {
+        EvCode {
+            kind: KIND_AXIS,
+            code: code as u16,
+        }
+    }
+
+    pub fn from_raw(kind: u32, number: u32) -> Option<Self> {
+        Some(EvCode {
+            kind: u16::try_from(kind).ok()?,
+            code: u16::try_from(number).ok()?,
+        })
+    }
+
+    pub fn synthetic(index: u8) -> Self {
+        EvCode {
+            kind: KIND_SYNTHETIC,
+            code: u16::from(index),
+        }
+    }
+
+    pub fn into_u32(self) -> u32 {
+        u32::from(self.kind) << 16 | u32::from(self.code)
+    }
+
+    pub fn debug_info(self) -> crate::EvCodeInfo {
+        crate::EvCodeInfo {
+            platform: "Android",
+            kind: u32::from(self.kind),
+            number: u32::from(self.code),
+            name: self.name(),
+        }
+    }
+
+    fn name(self) -> Option<&'static str> {
+        let code = c_int::from(self.code);
+        let name = match self.kind {
+            KIND_KEY => match code {
+                ffi::AKEYCODE_DPAD_UP => "AKEYCODE_DPAD_UP",
+                ffi::AKEYCODE_DPAD_DOWN => "AKEYCODE_DPAD_DOWN",
+                ffi::AKEYCODE_DPAD_LEFT => "AKEYCODE_DPAD_LEFT",
+                ffi::AKEYCODE_DPAD_RIGHT => "AKEYCODE_DPAD_RIGHT",
+                ffi::AKEYCODE_DPAD_CENTER => "AKEYCODE_DPAD_CENTER",
+                ffi::AKEYCODE_BUTTON_A => "AKEYCODE_BUTTON_A",
+                ffi::AKEYCODE_BUTTON_B => "AKEYCODE_BUTTON_B",
+                ffi::AKEYCODE_BUTTON_C => "AKEYCODE_BUTTON_C",
+                ffi::AKEYCODE_BUTTON_X => "AKEYCODE_BUTTON_X",
+                ffi::AKEYCODE_BUTTON_Y => "AKEYCODE_BUTTON_Y",
+                ffi::AKEYCODE_BUTTON_Z => "AKEYCODE_BUTTON_Z",
+                ffi::AKEYCODE_BUTTON_L1 => "AKEYCODE_BUTTON_L1",
+                ffi::AKEYCODE_BUTTON_R1 => "AKEYCODE_BUTTON_R1",
+                ffi::AKEYCODE_BUTTON_L2 => "AKEYCODE_BUTTON_L2",
+                ffi::AKEYCODE_BUTTON_R2 => "AKEYCODE_BUTTON_R2",
+                ffi::AKEYCODE_BUTTON_THUMBL => "AKEYCODE_BUTTON_THUMBL",
+                ffi::AKEYCODE_BUTTON_THUMBR => "AKEYCODE_BUTTON_THUMBR",
+                ffi::AKEYCODE_BUTTON_START => "AKEYCODE_BUTTON_START",
+                ffi::AKEYCODE_BUTTON_SELECT => "AKEYCODE_BUTTON_SELECT",
+                ffi::AKEYCODE_BUTTON_MODE => "AKEYCODE_BUTTON_MODE",
+                _ => return None,
+            },
+            KIND_AXIS => match code {
+                ffi::AMOTION_EVENT_AXIS_X => "AMOTION_EVENT_AXIS_X",
+                ffi::AMOTION_EVENT_AXIS_Y => "AMOTION_EVENT_AXIS_Y",
+                ffi::AMOTION_EVENT_AXIS_Z => "AMOTION_EVENT_AXIS_Z",
+                ffi::AMOTION_EVENT_AXIS_RX => "AMOTION_EVENT_AXIS_RX",
+                ffi::AMOTION_EVENT_AXIS_RY => "AMOTION_EVENT_AXIS_RY",
+                ffi::AMOTION_EVENT_AXIS_RZ => "AMOTION_EVENT_AXIS_RZ",
+                ffi::AMOTION_EVENT_AXIS_HAT_X => "AMOTION_EVENT_AXIS_HAT_X",
+                ffi::AMOTION_EVENT_AXIS_HAT_Y => "AMOTION_EVENT_AXIS_HAT_Y",
+                ffi::AMOTION_EVENT_AXIS_LTRIGGER => "AMOTION_EVENT_AXIS_LTRIGGER",
+                ffi::AMOTION_EVENT_AXIS_RTRIGGER => "AMOTION_EVENT_AXIS_RTRIGGER",
+                ffi::AMOTION_EVENT_AXIS_GAS => "AMOTION_EVENT_AXIS_GAS",
+                ffi::AMOTION_EVENT_AXIS_BRAKE => "AMOTION_EVENT_AXIS_BRAKE",
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        Some(name)
+    }
+}
+
+impl Display for EvCode {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let kind = match self.kind {
+            KIND_KEY => "KEY",
+            KIND_AXIS => "AXIS",
+            _ => "UNKNOWN",
+        };
+
+        write!(f, "{}({})", kind, self.code)
+    }
+}
+
+pub mod native_ev_codes {
+    use super::super::ffi;
+    use super::EvCode;
+
+    pub const AXIS_LSTICKX: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_X);
+    pub const AXIS_LSTICKY: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_Y);
+    pub const AXIS_LEFTZ: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_RX);
+    pub const AXIS_RSTICKX: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_Z);
+    pub const AXIS_RSTICKY: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_RZ);
+    pub const AXIS_RIGHTZ: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_RY);
+    pub const AXIS_DPADX: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_HAT_X);
+    pub const AXIS_DPADY: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_HAT_Y);
+    // Android doesn't have analog shoulder buttons, these axes are never reported.
+    pub const AXIS_RT: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_GENERIC_2);
+    pub const AXIS_LT: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_GENERIC_1);
+    pub const AXIS_RT2: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_RTRIGGER);
+    pub const AXIS_LT2: EvCode = EvCode::axis(ffi::AMOTION_EVENT_AXIS_LTRIGGER);
+
+    pub const BTN_SOUTH: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_A);
+    pub const BTN_EAST: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_B);
+    pub const BTN_C: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_C);
+    pub const BTN_NORTH: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_Y);
+    pub const BTN_WEST: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_X);
+    pub const BTN_Z: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_Z);
+    pub const BTN_LT: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_L1);
+    pub const BTN_RT: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_R1);
+    pub const BTN_LT2: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_L2);
+    pub const BTN_RT2: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_R2);
+    pub const BTN_SELECT: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_SELECT);
+    pub const BTN_START: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_START);
+    pub const BTN_MODE: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_MODE);
+    pub const BTN_LTHUMB: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_THUMBL);
+    pub const BTN_RTHUMB: EvCode = EvCode::key(ffi::AKEYCODE_BUTTON_THUMBR);
+
+    pub const BTN_DPAD_UP: EvCode = EvCode::key(ffi::AKEYCODE_DPAD_UP);
+    pub const BTN_DPAD_DOWN: EvCode = EvCode::key(ffi::AKEYCODE_DPAD_DOWN);
+    pub const BTN_DPAD_LEFT: EvCode = EvCode::key(ffi::AKEYCODE_DPAD_LEFT);
+    pub const BTN_DPAD_RIGHT: EvCode = EvCode::key(ffi::AKEYCODE_DPAD_RIGHT);
+
+    const BTN_DPAD_CENTER: EvCode = EvCode::key(ffi::AKEYCODE_DPAD_CENTER);
+
+    /// Buttons in order used by SDL's Android backend, so `bN` in SDL mappings for Android
+    /// refers to the same button. Followed by `AKEYCODE_BUTTON_1` to `AKEYCODE_BUTTON_16`.
+    pub(super) static BUTTONS: [EvCode; 36] = [
+        BTN_SOUTH,
+        BTN_EAST,
+        BTN_WEST,
+        BTN_NORTH,
+        BTN_SELECT,
+        BTN_MODE,
+        BTN_START,
+        BTN_LTHUMB,
+        BTN_RTHUMB,
+        BTN_LT,
+        BTN_RT,
+        BTN_DPAD_UP,
+        BTN_DPAD_DOWN,
+        BTN_DPAD_LEFT,
+        BTN_DPAD_RIGHT,
+        BTN_LT2,
+        BTN_RT2,
+        BTN_C,
+        BTN_Z,
+        BTN_DPAD_CENTER,
+        EvCode::key(ffi::AKEYCODE_BUTTON_1),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 1),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 2),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 3),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 4),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 5),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 6),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 7),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 8),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 9),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 10),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 11),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 12),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 13),
+        EvCode::key(ffi::AKEYCODE_BUTTON_1 + 14),
+        EvCode::key(ffi::AKEYCODE_BUTTON_16),
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ffi;
+    use super::EvCode;
+    use super::{axes_from_motion_ranges, axis_value, create_uuid, key_code, native_ev_codes};
+    use uuid::Uuid;
+
+    #[test]
+    fn key_codes() {
+        assert_eq!(
+            key_code(ffi::AKEYCODE_BUTTON_A),
+            Some(native_ev_codes::BTN_SOUTH)
+        );
+        assert_eq!(
+            key_code(ffi::AKEYCODE_BUTTON_Y),
+            Some(native_ev_codes::BTN_NORTH)
+        );
+        assert_eq!(
+            key_code(ffi::AKEYCODE_DPAD_LEFT),
+            Some(native_ev_codes::BTN_DPAD_LEFT)
+        );
+        assert_eq!(
+            key_code(ffi::AKEYCODE_BACK),
+            Some(native_ev_codes::BTN_SELECT)
+        );
+        assert_eq!(
+            key_code(ffi::AKEYCODE_MENU),
+            Some(native_ev_codes::BTN_START)
+        );
+        assert_eq!(
+            key_code(ffi::AKEYCODE_BUTTON_16),
+            Some(EvCode::key(ffi::AKEYCODE_BUTTON_16))
+        );
+        assert_eq!(key_code(ffi::AKEYCODE_BUTTON_16 + 1), None);
+        assert_eq!(key_code(0), None);
+    }
+
+    #[test]
+    fn axis_values() {
+        assert_eq!(axis_value(native_ev_codes::AXIS_LSTICKX, 1.0), 32767);
+        assert_eq!(axis_value(native_ev_codes::AXIS_LSTICKX, -1.0), -32767);
+        assert_eq!(axis_value(native_ev_codes::AXIS_RSTICKY, 0.0), 0);
+        assert_eq!(axis_value(native_ev_codes::AXIS_LT2, 0.5), 16384);
+        assert_eq!(axis_value(native_ev_codes::AXIS_RT2, 1.0), 32767);
+        assert_eq!(axis_value(native_ev_codes::AXIS_DPADX, -1.0), -1);
+        assert_eq!(axis_value(native_ev_codes::AXIS_DPADY, 1.0), 1);
+    }
+
+    #[test]
+    fn uuid() {
+        assert_eq!(create_uuid(0, 0), Uuid::nil());
+        // Same as SDL's GUID for Xbox One S controller connected over Bluetooth.
+        assert_eq!(
+            create_uuid(0x045e, 0x02e0).as_bytes(),
+            &[0x05, 0, 0, 0, 0x5e, 0x04, 0, 0, 0xe0, 0x02, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn axes_order() {
+        let ranges = [
+            ffi::AMOTION_EVENT_AXIS_HAT_Y,
+            ffi::AMOTION_EVENT_AXIS_RZ,
+            ffi::AMOTION_EVENT_AXIS_X,
+            ffi::AMOTION_EVENT_AXIS_HAT_X,
+            ffi::AMOTION_EVENT_AXIS_LTRIGGER,
+            ffi::AMOTION_EVENT_AXIS_Y,
+            ffi::AMOTION_EVENT_AXIS_Z,
+            ffi::AMOTION_EVENT_AXIS_X,
+            -1,
+        ];
+
+        assert_eq!(
+            axes_from_motion_ranges(&ranges),
+            [
+                native_ev_codes::AXIS_LSTICKX,
+                native_ev_codes::AXIS_LSTICKY,
+                native_ev_codes::AXIS_RSTICKX,
+                native_ev_codes::AXIS_RSTICKY,
+                native_ev_codes::AXIS_LT2,
+                native_ev_codes::AXIS_DPADX,
+                native_ev_codes::AXIS_DPADY,
+            ]
+        );
+    }
+}
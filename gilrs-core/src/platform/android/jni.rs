@@ -0,0 +1,332 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Subset of JNI used to enumerate input devices and control their vibrators. Only functions
+//! used by the backend are declared, other entries of function tables are padding.
+
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::ffi::{CStr, CString};
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+
+pub type jint = i32;
+pub type jlong = i64;
+pub type jboolean = u8;
+pub type jsize = jint;
+
+pub enum _jobject {}
+pub type jobject = *mut _jobject;
+pub type jclass = jobject;
+
+pub enum _jmethodID {}
+pub type jmethodID = *mut _jmethodID;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union jvalue {
+    pub z: jboolean,
+    pub i: jint,
+    pub j: jlong,
+    pub l: jobject,
+}
+
+const JNI_OK: jint = 0;
+const JNI_EDETACHED: jint = -2;
+const JNI_VERSION_1_6: jint = 0x0001_0006;
+
+type JNIEnv = *const JNINativeInterface;
+type JavaVM = *const JNIInvokeInterface;
+
+#[repr(C)]
+struct JNINativeInterface {
+    _reserved: [*const c_void; 6],
+    FindClass: unsafe extern "C" fn(*mut JNIEnv, *const c_char) -> jclass,
+    _pad7: [*const c_void; 10],
+    ExceptionClear: unsafe extern "C" fn(*mut JNIEnv),
+    _pad18: [*const c_void; 1],
+    PushLocalFrame: unsafe extern "C" fn(*mut JNIEnv, jint) -> jint,
+    PopLocalFrame: unsafe extern "C" fn(*mut JNIEnv, jobject) -> jobject,
+    NewGlobalRef: unsafe extern "C" fn(*mut JNIEnv, jobject) -> jobject,
+    DeleteGlobalRef: unsafe extern "C" fn(*mut JNIEnv, jobject),
+    _pad23: [*const c_void; 10],
+    GetMethodID:
+        unsafe extern "C" fn(*mut JNIEnv, jclass, *const c_char, *const c_char) -> jmethodID,
+    _pad34: [*const c_void; 2],
+    CallObjectMethodA:
+        unsafe extern "C" fn(*mut JNIEnv, jobject, jmethodID, *const jvalue) -> jobject,
+    _pad37: [*const c_void; 2],
+    CallBooleanMethodA:
+        unsafe extern "C" fn(*mut JNIEnv, jobject, jmethodID, *const jvalue) -> jboolean,
+    _pad40: [*const c_void; 11],
+    CallIntMethodA: unsafe extern "C" fn(*mut JNIEnv, jobject, jmethodID, *const jvalue) -> jint,
+    _pad52: [*const c_void; 11],
+    CallVoidMethodA: unsafe extern "C" fn(*mut JNIEnv, jobject, jmethodID, *const jvalue),
+    _pad64: [*const c_void; 49],
+    GetStaticMethodID:
+        unsafe extern "C" fn(*mut JNIEnv, jclass, *const c_char, *const c_char) -> jmethodID,
+    _pad114: [*const c_void; 2],
+    CallStaticObjectMethodA:
+        unsafe extern "C" fn(*mut JNIEnv, jclass, jmethodID, *const jvalue) -> jobject,
+    _pad117: [*const c_void; 52],
+    GetStringUTFChars: unsafe extern "C" fn(*mut JNIEnv, jobject, *mut jboolean) -> *const c_char,
+    ReleaseStringUTFChars: unsafe extern "C" fn(*mut JNIEnv, jobject, *const c_char),
+    GetArrayLength: unsafe extern "C" fn(*mut JNIEnv, jobject) -> jsize,
+    _pad172: [*const c_void; 31],
+    GetIntArrayRegion: unsafe extern "C" fn(*mut JNIEnv, jobject, jsize, jsize, *mut jint),
+    _pad204: [*const c_void; 24],
+    ExceptionCheck: unsafe extern "C" fn(*mut JNIEnv) -> jboolean,
+}
+
+#[repr(C)]
+struct JNIInvokeInterface {
+    _reserved: [*const c_void; 6],
+    GetEnv: unsafe extern "C" fn(*mut JavaVM, *mut *mut c_void, jint) -> jint,
+    AttachCurrentThreadAsDaemon:
+        unsafe extern "C" fn(*mut JavaVM, *mut *mut JNIEnv, *mut c_void) -> jint,
+}
+
+/// Java VM of application. It can be used from any thread.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Vm(*mut JavaVM);
+
+unsafe impl Send for Vm {}
+unsafe impl Sync for Vm {}
+
+impl Vm {
+    /// # Safety
+    ///
+    /// `vm` must be null or point to `JavaVM` that lives as long as the returned value.
+    pub unsafe fn from_ptr(vm: *mut c_void) -> Option<Self> {
+        if vm.is_null() {
+            None
+        } else {
+            Some(Vm(vm as *mut JavaVM))
+        }
+    }
+
+    /// Returns JNI environment of current thread. Threads that are not attached to VM are
+    /// attached as daemons, so they don't have to be detached before they exit.
+    pub fn env(self) -> Option<Env> {
+        let mut env = ptr::null_mut();
+
+        unsafe {
+            let fns = &**self.0;
+            match (fns.GetEnv)(self.0, &mut env, JNI_VERSION_1_6) {
+                JNI_OK => (),
+                JNI_EDETACHED => {
+                    let mut attached = ptr::null_mut();
+                    let err =
+                        (fns.AttachCurrentThreadAsDaemon)(self.0, &mut attached, ptr::null_mut());
+                    if err != JNI_OK {
+                        error!("Failed to attach thread to Java VM: {}", err);
+                        return None;
+                    }
+                    env = attached as *mut c_void;
+                }
+                err => {
+                    error!("Failed to get JNI environment: {}", err);
+                    return None;
+                }
+            }
+        }
+
+        Some(Env(env as *mut JNIEnv))
+    }
+}
+
+/// JNI environment of current thread. All functions return `None` if Java exception was thrown,
+/// the exception is cleared.
+pub struct Env(*mut JNIEnv);
+
+impl Env {
+    fn fns(&self) -> &JNINativeInterface {
+        unsafe { &**self.0 }
+    }
+
+    /// Calls `f` in new frame of local references, so references created by it are freed when it
+    /// returns. Threads attached by `Vm::env()` never return to Java, so without it local
+    /// references would never be freed.
+    pub fn with_frame<T, F: FnOnce(&Env) -> Option<T>>(&self, f: F) -> Option<T> {
+        unsafe {
+            if (self.fns().PushLocalFrame)(self.0, 16) != JNI_OK {
+                self.check();
+                return None;
+            }
+
+            let result = f(self);
+            (self.fns().PopLocalFrame)(self.0, ptr::null_mut());
+
+            result
+        }
+    }
+
+    /// Returns `None` and clears exception if it was thrown by last call.
+    fn check(&self) -> Option<()> {
+        unsafe {
+            if (self.fns().ExceptionCheck)(self.0) != 0 {
+                (self.fns().ExceptionClear)(self.0);
+                None
+            } else {
+                Some(())
+            }
+        }
+    }
+
+    /// Returns `None` for null `obj` or if exception was thrown.
+    fn check_obj(&self, obj: jobject) -> Option<jobject> {
+        self.check()?;
+
+        if obj.is_null() {
+            None
+        } else {
+            Some(obj)
+        }
+    }
+
+    pub fn class(&self, name: &str) -> Option<jclass> {
+        let name = CString::new(name).ok()?;
+        let class = unsafe { (self.fns().FindClass)(self.0, name.as_ptr()) };
+
+        self.check_obj(class)
+    }
+
+    pub fn method(&self, class: jclass, name: &str, sig: &str) -> Option<jmethodID> {
+        let name = CString::new(name).ok()?;
+        let sig = CString::new(sig).ok()?;
+        let method =
+            unsafe { (self.fns().GetMethodID)(self.0, class, name.as_ptr(), sig.as_ptr()) };
+        self.check()?;
+
+        if method.is_null() {
+            None
+        } else {
+            Some(method)
+        }
+    }
+
+    pub fn static_method(&self, class: jclass, name: &str, sig: &str) -> Option<jmethodID> {
+        let name = CString::new(name).ok()?;
+        let sig = CString::new(sig).ok()?;
+        let method =
+            unsafe { (self.fns().GetStaticMethodID)(self.0, class, name.as_ptr(), sig.as_ptr()) };
+        self.check()?;
+
+        if method.is_null() {
+            None
+        } else {
+            Some(method)
+        }
+    }
+
+    /// Returns `None` if method returned null.
+    pub fn call_object(&self, obj: jobject, method: jmethodID, args: &[jvalue]) -> Option<jobject> {
+        let ret = unsafe { (self.fns().CallObjectMethodA)(self.0, obj, method, args.as_ptr()) };
+
+        self.check_obj(ret)
+    }
+
+    /// Returns `None` if method returned null.
+    pub fn call_static_object(
+        &self,
+        class: jclass,
+        method: jmethodID,
+        args: &[jvalue],
+    ) -> Option<jobject> {
+        let ret =
+            unsafe { (self.fns().CallStaticObjectMethodA)(self.0, class, method, args.as_ptr()) };
+
+        self.check_obj(ret)
+    }
+
+    pub fn call_bool(&self, obj: jobject, method: jmethodID, args: &[jvalue]) -> Option<bool> {
+        let ret = unsafe { (self.fns().CallBooleanMethodA)(self.0, obj, method, args.as_ptr()) };
+        self.check()?;
+
+        Some(ret != 0)
+    }
+
+    pub fn call_int(&self, obj: jobject, method: jmethodID, args: &[jvalue]) -> Option<jint> {
+        let ret = unsafe { (self.fns().CallIntMethodA)(self.0, obj, method, args.as_ptr()) };
+        self.check()?;
+
+        Some(ret)
+    }
+
+    pub fn call_void(&self, obj: jobject, method: jmethodID, args: &[jvalue]) -> Option<()> {
+        unsafe { (self.fns().CallVoidMethodA)(self.0, obj, method, args.as_ptr()) };
+
+        self.check()
+    }
+
+    /// Converts `java.lang.String` to `String`.
+    pub fn string(&self, s: jobject) -> Option<String> {
+        unsafe {
+            let chars = (self.fns().GetStringUTFChars)(self.0, s, ptr::null_mut());
+            self.check_obj(chars as jobject)?;
+            // Modified UTF-8 differs from UTF-8 only in encoding of NUL and supplementary
+            // characters, these are replaced.
+            let string = CStr::from_ptr(chars).to_string_lossy().into_owned();
+            (self.fns().ReleaseStringUTFChars)(self.0, s, chars);
+
+            Some(string)
+        }
+    }
+
+    /// Converts `int[]` to `Vec`.
+    pub fn int_array(&self, array: jobject) -> Option<Vec<jint>> {
+        unsafe {
+            let len = (self.fns().GetArrayLength)(self.0, array);
+            self.check()?;
+            let mut vec = vec![0; len as usize];
+            (self.fns().GetIntArrayRegion)(self.0, array, 0, len, vec.as_mut_ptr());
+            self.check()?;
+
+            Some(vec)
+        }
+    }
+}
+
+/// Global reference to Java object. Unlike local references, it's valid on all threads until it's
+/// dropped.
+pub struct GlobalRef {
+    vm: Vm,
+    obj: jobject,
+}
+
+unsafe impl Send for GlobalRef {}
+unsafe impl Sync for GlobalRef {}
+
+impl GlobalRef {
+    pub fn new(vm: Vm, env: &Env, obj: jobject) -> Option<Self> {
+        let obj = unsafe { (env.fns().NewGlobalRef)(env.0, obj) };
+        env.check_obj(obj).map(|obj| GlobalRef { vm, obj })
+    }
+
+    pub fn vm(&self) -> Vm {
+        self.vm
+    }
+
+    pub fn as_obj(&self) -> jobject {
+        self.obj
+    }
+}
+
+impl Drop for GlobalRef {
+    fn drop(&mut self) {
+        if let Some(env) = self.vm.env() {
+            unsafe { (env.fns().DeleteGlobalRef)(env.0, self.obj) };
+        }
+    }
+}
+
+impl Debug for GlobalRef {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "GlobalRef({:p})", self.obj)
+    }
+}
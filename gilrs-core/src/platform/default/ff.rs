@@ -12,6 +12,16 @@ use std::time::Duration;
 pub struct Device;
 
 impl Device {
+    /// Returns number of effects that can be played at the same time.
+    pub fn query_max_effects(&self) -> usize {
+        0
+    }
+
+    /// Device doesn't hold any OS resource, so it never becomes invalid.
+    pub fn is_connected(&self) -> bool {
+        true
+    }
+
     /// Sets magnitude for strong and weak ff motors.
     pub fn set_ff_state(&mut self, strong: u16, weak: u16, min_duration: Duration) {}
 }
@@ -7,9 +7,10 @@
 #![allow(unused_variables)]
 
 use super::FfDevice;
-use crate::{AxisInfo, Event, PlatformError, PowerInfo};
+use crate::{AxisInfo, Event, LedError, PlatformError, PollingRateError, PowerInfo, PowerOffError};
 use uuid::Uuid;
 
+use std::convert::TryFrom;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
 #[derive(Debug)]
@@ -32,6 +33,14 @@ impl Gilrs {
     pub fn last_gamepad_hint(&self) -> usize {
         0
     }
+
+    pub(crate) fn rescan_gamepads(&mut self) -> usize {
+        0
+    }
+
+    pub(crate) fn set_polling_rate(&mut self, hz: u32) -> Result<(), PollingRateError> {
+        Err(PollingRateError::NotSupported)
+    }
 }
 
 #[derive(Debug)]
@@ -44,6 +53,14 @@ impl Gamepad {
         ""
     }
 
+    pub fn manufacturer_string(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn product_string(&self) -> Option<&str> {
+        None
+    }
+
     pub fn uuid(&self) -> Uuid {
         Uuid::nil()
     }
@@ -56,6 +73,47 @@ impl Gamepad {
         false
     }
 
+    pub fn power_off(&self) -> Result<(), PowerOffError> {
+        Err(PowerOffError::NotSupported)
+    }
+
+    pub fn set_player_indicator(&self, player: Option<u8>) -> Result<(), LedError> {
+        Err(LedError::NotSupported)
+    }
+
+    pub fn input_report_rate(&self) -> Option<u32> {
+        None
+    }
+
+    pub fn has_touchpad(&self) -> bool {
+        false
+    }
+
+    pub fn current_state(&self) -> Option<crate::RawState> {
+        None
+    }
+
+    pub fn os_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn suggested_player_index(&self) -> Option<u8> {
+        None
+    }
+
+    pub fn port_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<&str> {
+        None
+    }
+
+    #[cfg(feature = "raw-hid")]
+    pub fn raw_reports(&self) -> std::io::Result<super::RawReports> {
+        Err(super::raw_hid::unsupported())
+    }
+
     /// Creates Ffdevice corresponding to this gamepad.
     pub fn ff_device(&self) -> Option<FfDevice> {
         Some(FfDevice)
@@ -86,9 +144,30 @@ use serde::{Deserialize, Serialize};
 pub struct EvCode(u16);
 
 impl EvCode {
+    pub fn from_raw(kind: u32, number: u32) -> Option<Self> {
+        if kind != 0 {
+            return None;
+        }
+
+        u16::try_from(number).ok().map(EvCode)
+    }
+
+    pub fn synthetic(index: u8) -> Self {
+        EvCode(0xffc0 | u16::from(index))
+    }
+
     pub fn into_u32(self) -> u32 {
         self.0 as u32
     }
+
+    pub fn debug_info(self) -> crate::EvCodeInfo {
+        crate::EvCodeInfo {
+            platform: "Unknown",
+            kind: 0,
+            number: self.into_u32(),
+            name: crate::utils::native_ev_code_name(self.into_u32()),
+        }
+    }
 }
 
 impl Display for EvCode {
@@ -0,0 +1,28 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::time::Duration;
+
+/// Force feedback is not supported by uhid(4) devices, `Gamepad::ff_device()` always returns
+/// `None` on FreeBSD.
+#[derive(Debug)]
+pub struct Device;
+
+impl Device {
+    /// Returns number of effects that can be played at the same time.
+    pub fn query_max_effects(&self) -> usize {
+        0
+    }
+
+    /// Device doesn't hold any OS resource, so it never becomes invalid.
+    pub fn is_connected(&self) -> bool {
+        true
+    }
+
+    /// Sets magnitude for strong and weak ff motors.
+    pub fn set_ff_state(&mut self, _strong: u16, _weak: u16, _min_duration: Duration) {}
+}
@@ -0,0 +1,780 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::ff::Device as FfDevice;
+use super::ioctl;
+use crate::hid::{self, ReportDescriptor};
+use crate::utils;
+use crate::{
+    AxisInfo, Event, EventType, LedError, PlatformError, PlatformErrorKind, PollingRateError,
+    PowerInfo, PowerOffError,
+};
+
+use libc as c;
+use uuid::Uuid;
+
+use std::collections::{HashMap, VecDeque};
+use std::error;
+use std::ffi::CString;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs;
+use std::io;
+use std::mem;
+use std::ptr;
+use std::time::{Duration, SystemTime};
+
+/// FreeBSD doesn't have udev and devd(8) notifications would require talking to running daemon,
+/// so new and removed devices are found by scanning `/dev` in this interval.
+const HOTPLUG_SCAN_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_REPORT_DESC_SIZE: usize = 4096;
+const MAX_REPORT_SIZE: usize = 1024;
+const BUS_USB: u32 = 0x03;
+
+#[derive(Debug)]
+pub struct Gilrs {
+    gamepads: Vec<Gamepad>,
+    kqueue: c::c_int,
+    // Events read from devices and generated by `rescan_gamepads()`.
+    pending_events: VecDeque<Event>,
+    // uhid devices that are not gamepads, so we don't try to open them on every scan.
+    non_gamepads: Vec<String>,
+    last_scan: SystemTime,
+}
+
+impl Gilrs {
+    pub(crate) fn new() -> Result<Self, PlatformError> {
+        let kqueue = unsafe { c::kqueue() };
+        if kqueue < 0 {
+            return Err(PlatformError::Other(Box::new(Error::Kqueue(
+                PlatformErrorKind::last_os_error("kqueue"),
+            ))));
+        }
+
+        let mut gilrs = Gilrs {
+            gamepads: Vec::new(),
+            kqueue,
+            pending_events: VecDeque::new(),
+            non_gamepads: Vec::new(),
+            last_scan: utils::time_now(),
+        };
+
+        for path in uhid_devices() {
+            match Gamepad::open(&path) {
+                Some(gamepad) => {
+                    gilrs.add_gamepad(gamepad);
+                }
+                None => gilrs.non_gamepads.push(path),
+            }
+        }
+
+        Ok(gilrs)
+    }
+
+    pub(crate) fn next_event(&mut self) -> Option<Event> {
+        if let Some(event) = self.pending_events.pop_front() {
+            return Some(event);
+        }
+
+        let scan_needed = utils::time_now()
+            .duration_since(self.last_scan)
+            .map(|elapsed| elapsed >= HOTPLUG_SCAN_INTERVAL)
+            .unwrap_or(true);
+        if scan_needed {
+            self.rescan_gamepads();
+        }
+
+        if self.pending_events.is_empty() {
+            self.poll();
+        }
+
+        self.pending_events.pop_front()
+    }
+
+    pub fn gamepad(&self, id: usize) -> Option<&Gamepad> {
+        self.gamepads.get(id)
+    }
+
+    pub fn last_gamepad_hint(&self) -> usize {
+        self.gamepads.len()
+    }
+
+    pub(crate) fn rescan_gamepads(&mut self) -> usize {
+        self.last_scan = utils::time_now();
+
+        let paths = uhid_devices();
+        let mut changes = 0;
+
+        // Handle removed devices first, so reconnected gamepads can reuse their old ID.
+        for (id, gamepad) in self.gamepads.iter_mut().enumerate() {
+            if gamepad.is_connected && !paths.contains(&gamepad.path) {
+                info!("Gamepad {} ({}) not found.", gamepad.path, gamepad.name);
+                gamepad.disconnect();
+                self.pending_events
+                    .push_back(Event::new(id, EventType::Disconnected));
+                changes += 1;
+            }
+        }
+        self.non_gamepads.retain(|path| paths.contains(path));
+
+        for path in paths {
+            if self.non_gamepads.contains(&path)
+                || self
+                    .gamepads
+                    .iter()
+                    .any(|gp| gp.is_connected && gp.path == path)
+            {
+                continue;
+            }
+
+            match Gamepad::open(&path) {
+                Some(gamepad) => {
+                    let id = self.add_gamepad(gamepad);
+                    self.pending_events
+                        .push_back(Event::new(id, EventType::Connected));
+                    changes += 1;
+                }
+                None => self.non_gamepads.push(path),
+            }
+        }
+
+        changes
+    }
+
+    // Events are delivered as they arrive, there is no polling.
+    pub(crate) fn set_polling_rate(&mut self, _hz: u32) -> Result<(), PollingRateError> {
+        Err(PollingRateError::NotSupported)
+    }
+
+    /// Adds gamepad, starts watching it for input reports and returns its ID.
+    fn add_gamepad(&mut self, gamepad: Gamepad) -> usize {
+        let id = match self
+            .gamepads
+            .iter()
+            .position(|gp| gp.uuid == gamepad.uuid && !gp.is_connected)
+        {
+            Some(id) => {
+                self.gamepads[id] = gamepad;
+                id
+            }
+            None => {
+                self.gamepads.push(gamepad);
+                self.gamepads.len() - 1
+            }
+        };
+
+        let gamepad = &self.gamepads[id];
+        let mut change: c::kevent = unsafe { mem::zeroed() };
+        change.ident = gamepad.fd as c::uintptr_t;
+        change.filter = c::EVFILT_READ;
+        change.flags = c::EV_ADD;
+        change.udata = id as *mut c::c_void;
+
+        if unsafe { c::kevent(self.kqueue, &change, 1, ptr::null_mut(), 0, ptr::null()) } < 0 {
+            error!(
+                "Failed to watch {} for input reports: {}",
+                gamepad.path,
+                io::Error::last_os_error()
+            );
+        }
+
+        id
+    }
+
+    /// Reads input reports from all gamepads that have them and converts them to events.
+    fn poll(&mut self) {
+        let mut ready: [c::kevent; 16] = unsafe { mem::zeroed() };
+        let timeout = c::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+
+        let n = unsafe {
+            c::kevent(
+                self.kqueue,
+                ptr::null(),
+                0,
+                ready.as_mut_ptr(),
+                ready.len() as c::c_int,
+                &timeout,
+            )
+        };
+        if n < 0 {
+            error!("Failed to poll gamepads: {}", io::Error::last_os_error());
+            return;
+        }
+
+        for ev in &ready[..n as usize] {
+            let id = ev.udata as usize;
+            let gamepad = match self.gamepads.get_mut(id) {
+                Some(gp) if gp.is_connected && gp.fd as c::uintptr_t == ev.ident => gp,
+                _ => continue,
+            };
+
+            let readable = gamepad.read_reports(id, &mut self.pending_events);
+            if !readable || ev.flags & c::EV_EOF != 0 {
+                info!("Gamepad {} ({}) disconnected.", gamepad.path, gamepad.name);
+                gamepad.disconnect();
+                self.pending_events
+                    .push_back(Event::new(id, EventType::Disconnected));
+            }
+        }
+    }
+}
+
+impl Drop for Gilrs {
+    fn drop(&mut self) {
+        unsafe {
+            c::close(self.kqueue);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Gamepad {
+    name: String,
+    manufacturer: Option<String>,
+    product: Option<String>,
+    uuid: Uuid,
+    path: String,
+    fd: c::c_int,
+    descriptor: ReportDescriptor,
+    // Last value of every field in `descriptor.inputs`.
+    values: Vec<Option<i32>>,
+    buttons: Vec<EvCode>,
+    axes: Vec<EvCode>,
+    axes_info: HashMap<EvCode, AxisInfo>,
+    is_connected: bool,
+}
+
+impl Gamepad {
+    fn open(path: &str) -> Option<Gamepad> {
+        let cpath = CString::new(path).ok()?;
+        let fd = unsafe { c::open(cpath.as_ptr(), c::O_RDONLY | c::O_NONBLOCK | c::O_CLOEXEC) };
+        if fd < 0 {
+            debug!("Failed to open {}: {}", path, io::Error::last_os_error());
+            return None;
+        }
+
+        let gamepad = Gamepad::from_fd(fd, path);
+        if gamepad.is_none() {
+            unsafe {
+                c::close(fd);
+            }
+        }
+
+        gamepad
+    }
+
+    fn from_fd(fd: c::c_int, path: &str) -> Option<Gamepad> {
+        let mut buf = vec![0; MAX_REPORT_DESC_SIZE];
+        let len = match unsafe { ioctl::usb_get_report_desc(fd, &mut buf) } {
+            Ok(len) => len,
+            Err(e) => {
+                debug!("Failed to get report descriptor of {}: {}", path, e);
+                return None;
+            }
+        };
+
+        let descriptor = match ReportDescriptor::parse(&buf[..len]) {
+            Ok(descriptor) => descriptor,
+            Err(e) => {
+                warn!("Failed to parse report descriptor of {}: {}", path, e);
+                return None;
+            }
+        };
+
+        if !descriptor.is_gamepad() {
+            debug!("{} is not a gamepad: {:?}", path, descriptor.application);
+            return None;
+        }
+
+        let (name, manufacturer, product, uuid) = match unsafe { ioctl::usb_get_deviceinfo(fd) } {
+            Ok(info) => {
+                let manufacturer = c_string(&info.udi_vendor);
+                let product = c_string(&info.udi_product);
+                let name = product.clone().unwrap_or_default();
+                (name, manufacturer, product, create_uuid(&info))
+            }
+            Err(e) => {
+                warn!("Failed to get device info of {}: {}", path, e);
+                (path.to_owned(), None, None, Uuid::nil())
+            }
+        };
+
+        let mut buttons = Vec::new();
+        let mut axes = Vec::new();
+        let mut axes_info = HashMap::new();
+
+        for field in &descriptor.inputs {
+            if field.is_button() {
+                buttons.push(EvCode::new(field.page, field.usage));
+            } else if field.is_axis() {
+                let code = EvCode::new(field.page, field.usage);
+                axes.push(code);
+                axes_info.insert(
+                    code,
+                    AxisInfo {
+                        min: field.logical_min,
+                        max: field.logical_max,
+                        deadzone: None,
+                    },
+                );
+            } else if field.is_hat() {
+                // Hat switches are translated into two axes.
+                for &code in &[native_ev_codes::AXIS_DPADX, native_ev_codes::AXIS_DPADY] {
+                    axes.push(code);
+                    axes_info.insert(
+                        code,
+                        AxisInfo {
+                            min: -1,
+                            max: 1,
+                            deadzone: None,
+                        },
+                    );
+                }
+            }
+        }
+
+        buttons.sort();
+        buttons.dedup();
+        axes.sort();
+        axes.dedup();
+
+        info!("Found {} ({}) at {}", name, uuid, path);
+
+        Some(Gamepad {
+            name,
+            manufacturer,
+            product,
+            uuid,
+            path: path.to_owned(),
+            fd,
+            values: vec![None; descriptor.inputs.len()],
+            descriptor,
+            buttons,
+            axes,
+            axes_info,
+            is_connected: true,
+        })
+    }
+
+    /// Reads all available input reports. Returns false if device can't be read anymore.
+    fn read_reports(&mut self, id: usize, events: &mut VecDeque<Event>) -> bool {
+        let mut buf = [0u8; MAX_REPORT_SIZE];
+
+        loop {
+            let n = unsafe { c::read(self.fd, buf.as_mut_ptr() as *mut c::c_void, buf.len()) };
+
+            if n < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::WouldBlock {
+                    return true;
+                }
+
+                debug!("Failed to read from {}: {}", self.path, err);
+                return false;
+            } else if n == 0 {
+                return false;
+            }
+
+            self.process_report(id, &buf[..n as usize], events);
+        }
+    }
+
+    fn process_report(&mut self, id: usize, report: &[u8], events: &mut VecDeque<Event>) {
+        let time = utils::time_now();
+        let (report_id, report) = match self.descriptor.split_report(report) {
+            Some(split) => split,
+            None => return,
+        };
+
+        for (field, last) in self.descriptor.inputs.iter().zip(self.values.iter_mut()) {
+            if field.report_id != report_id {
+                continue;
+            }
+
+            let value = match field.read(report) {
+                Some(value) => value,
+                None => continue,
+            };
+
+            // Don't report released buttons in the first report.
+            let unchanged = match *last {
+                Some(last) => last == value,
+                None => field.is_button() && value == 0,
+            };
+            *last = Some(value);
+            if unchanged {
+                continue;
+            }
+
+            let code = crate::EvCode(EvCode::new(field.page, field.usage));
+            let mut push = |event| events.push_back(Event { id, event, time });
+
+            if field.is_button() {
+                if value != 0 {
+                    push(EventType::ButtonPressed(code));
+                } else {
+                    push(EventType::ButtonReleased(code));
+                }
+            } else if field.is_axis() {
+                push(EventType::AxisValueChanged(value, code));
+            } else if field.is_hat() {
+                let (x, y) = field.hat_to_axes(value);
+                push(EventType::AxisValueChanged(
+                    x,
+                    crate::EvCode(native_ev_codes::AXIS_DPADX),
+                ));
+                push(EventType::AxisValueChanged(
+                    y,
+                    crate::EvCode(native_ev_codes::AXIS_DPADY),
+                ));
+            }
+        }
+    }
+
+    fn disconnect(&mut self) {
+        if self.is_connected {
+            unsafe {
+                // Closing descriptor also removes it from kqueue.
+                c::close(self.fd);
+            }
+            self.is_connected = false;
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn manufacturer_string(&self) -> Option<&str> {
+        self.manufacturer.as_deref()
+    }
+
+    pub fn product_string(&self) -> Option<&str> {
+        self.product.as_deref()
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    /// uhid(4) only handles USB devices.
+    pub fn power_info(&self) -> PowerInfo {
+        PowerInfo::Wired
+    }
+
+    pub fn is_ff_supported(&self) -> bool {
+        false
+    }
+
+    pub fn power_off(&self) -> Result<(), PowerOffError> {
+        if self.is_connected {
+            Err(PowerOffError::NotSupported)
+        } else {
+            Err(PowerOffError::Disconnected)
+        }
+    }
+
+    pub fn set_player_indicator(&self, _player: Option<u8>) -> Result<(), LedError> {
+        Err(LedError::NotSupported)
+    }
+
+    pub fn input_report_rate(&self) -> Option<u32> {
+        None
+    }
+
+    pub fn has_touchpad(&self) -> bool {
+        false
+    }
+
+    pub fn current_state(&self) -> Option<crate::RawState> {
+        None
+    }
+
+    pub fn os_path(&self) -> Option<&str> {
+        Some(&self.path)
+    }
+
+    pub fn suggested_player_index(&self) -> Option<u8> {
+        None
+    }
+
+    pub fn port_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<&str> {
+        None
+    }
+
+    #[cfg(feature = "raw-hid")]
+    pub fn raw_reports(&self) -> std::io::Result<super::RawReports> {
+        // uhid device is already opened by `Gilrs` and can't be opened again.
+        Err(super::raw_hid::unsupported())
+    }
+
+    pub fn ff_device(&self) -> Option<FfDevice> {
+        None
+    }
+
+    pub fn buttons(&self) -> &[EvCode] {
+        &self.buttons
+    }
+
+    pub fn axes(&self) -> &[EvCode] {
+        &self.axes
+    }
+
+    pub(crate) fn axis_info(&self, nec: EvCode) -> Option<&AxisInfo> {
+        self.axes_info.get(&nec)
+    }
+}
+
+impl Drop for Gamepad {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+/// Returns paths of all uhid(4) devices, sorted by unit number.
+fn uhid_devices() -> Vec<String> {
+    let entries = match fs::read_dir("/dev") {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("Failed to list /dev: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut units: Vec<u32> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let name = name.to_str()?;
+            if name.starts_with("uhid") {
+                name[4..].parse().ok()
+            } else {
+                None
+            }
+        })
+        .collect();
+    units.sort();
+
+    units
+        .into_iter()
+        .map(|unit| format!("/dev/uhid{}", unit))
+        .collect()
+}
+
+/// Converts NUL terminated string from `usb_device_info` to `String`, or `None` if it's empty.
+fn c_string(buf: &[c::c_char]) -> Option<String> {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    let bytes: Vec<u8> = buf[..len].iter().map(|&c| c as u8).collect();
+    let s = String::from_utf8_lossy(&bytes);
+    let s = s.trim();
+
+    if s.is_empty() {
+        None
+    } else {
+        Some(s.to_owned())
+    }
+}
+
+/// Creates SDL compatible UUID from USB vendor and product ID and device release number.
+fn create_uuid(info: &ioctl::usb_device_info) -> Uuid {
+    let bus = BUS_USB.to_be();
+    let vendor = info.udi_vendor_no.to_be();
+    let product = info.udi_product_no.to_be();
+    let version = info.udi_release_no.to_be();
+    Uuid::from_fields(
+        bus,
+        vendor,
+        0,
+        &[
+            (product >> 8) as u8,
+            product as u8,
+            0,
+            0,
+            (version >> 8) as u8,
+            version as u8,
+            0,
+            0,
+        ],
+    )
+    .unwrap()
+}
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct EvCode {
+    page: u32,
+    usage: u32,
+}
+
+impl EvCode {
+    fn new(page: u16, usage: u16) -> Self {
+        EvCode {
+            page: u32::from(page),
+            usage: u32::from(usage),
+        }
+    }
+
+    pub fn from_raw(kind: u32, number: u32) -> Option<Self> {
+        Some(EvCode {
+            page: kind,
+            usage: number,
+        })
+    }
+
+    /// HID usage pages are 16-bit, so synthetic codes use page outside of their range.
+    pub fn synthetic(index: u8) -> Self {
+        EvCode {
+            page: 0x1_0000,
+            usage: u32::from(index),
+        }
+    }
+
+    pub fn into_u32(self) -> u32 {
+        self.page << 16 | self.usage
+    }
+
+    pub fn debug_info(self) -> crate::EvCodeInfo {
+        let name = match (self.page as u16, self.usage as u16) {
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_X) => Some("GD_X"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_Y) => Some("GD_Y"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_Z) => Some("GD_Z"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_RX) => Some("GD_RX"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_RY) => Some("GD_RY"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_RZ) => Some("GD_RZ"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_SLIDER) => Some("GD_SLIDER"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_DIAL) => Some("GD_DIAL"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_WHEEL) => Some("GD_WHEEL"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_HATSWITCH) => Some("GD_HATSWITCH"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_DPAD_UP) => Some("GD_DPAD_UP"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_DPAD_DOWN) => Some("GD_DPAD_DOWN"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_DPAD_RIGHT) => Some("GD_DPAD_RIGHT"),
+            (hid::PAGE_GENERIC_DESKTOP, hid::USAGE_GD_DPAD_LEFT) => Some("GD_DPAD_LEFT"),
+            (hid::PAGE_SIMULATION, hid::USAGE_SIM_ACCELERATOR) => Some("SIM_ACCELERATOR"),
+            (hid::PAGE_SIMULATION, hid::USAGE_SIM_BRAKE) => Some("SIM_BRAKE"),
+            _ => None,
+        };
+
+        crate::EvCodeInfo {
+            platform: "FreeBSD",
+            kind: self.page,
+            number: self.usage,
+            name,
+        }
+    }
+}
+
+impl Display for EvCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.page as u16 {
+            hid::PAGE_GENERIC_DESKTOP => f.write_str("GENERIC_DESKTOP")?,
+            hid::PAGE_SIMULATION => f.write_str("SIMULATION")?,
+            hid::PAGE_BUTTON => f.write_str("BUTTON")?,
+            page => f.write_fmt(format_args!("PAGE_{}", page))?,
+        }
+
+        f.write_fmt(format_args!("({})", self.usage))
+    }
+}
+
+#[derive(Debug)]
+enum Error {
+    Kqueue(PlatformErrorKind),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Error::Kqueue(kind) => f.write_fmt(format_args!(
+                "Failed to create kqueue (os error {})",
+                kind.code()
+            )),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Kqueue(kind) => Some(kind),
+        }
+    }
+}
+
+/// Codes are HID usages. Buttons are numbered in the same order as Linux assigns `BTN_GAMEPAD`
+/// codes to buttons of HID gamepads, axes use the same usages as on Linux too.
+pub mod native_ev_codes {
+    use super::EvCode;
+    use crate::hid::*;
+
+    const fn gd(usage: u16) -> EvCode {
+        EvCode {
+            page: PAGE_GENERIC_DESKTOP as u32,
+            usage: usage as u32,
+        }
+    }
+
+    const fn button(n: u16) -> EvCode {
+        EvCode {
+            page: PAGE_BUTTON as u32,
+            usage: n as u32,
+        }
+    }
+
+    pub const AXIS_LSTICKX: EvCode = gd(USAGE_GD_X);
+    pub const AXIS_LSTICKY: EvCode = gd(USAGE_GD_Y);
+    pub const AXIS_LEFTZ: EvCode = gd(USAGE_GD_Z);
+    pub const AXIS_RSTICKX: EvCode = gd(USAGE_GD_RX);
+    pub const AXIS_RSTICKY: EvCode = gd(USAGE_GD_RY);
+    pub const AXIS_RIGHTZ: EvCode = gd(USAGE_GD_RZ);
+    pub const AXIS_DPADX: EvCode = gd(USAGE_GD_HATSWITCH);
+    // There is no usage for second axis of hat switch, the next one is assumed.
+    pub const AXIS_DPADY: EvCode = gd(USAGE_GD_HATSWITCH + 1);
+    pub const AXIS_RT: EvCode = gd(USAGE_GD_SLIDER);
+    pub const AXIS_LT: EvCode = gd(USAGE_GD_DIAL);
+    pub const AXIS_RT2: EvCode = EvCode {
+        page: PAGE_SIMULATION as u32,
+        usage: USAGE_SIM_ACCELERATOR as u32,
+    };
+    pub const AXIS_LT2: EvCode = EvCode {
+        page: PAGE_SIMULATION as u32,
+        usage: USAGE_SIM_BRAKE as u32,
+    };
+
+    pub const BTN_SOUTH: EvCode = button(1);
+    pub const BTN_EAST: EvCode = button(2);
+    pub const BTN_C: EvCode = button(3);
+    pub const BTN_NORTH: EvCode = button(4);
+    pub const BTN_WEST: EvCode = button(5);
+    pub const BTN_Z: EvCode = button(6);
+    pub const BTN_LT: EvCode = button(7);
+    pub const BTN_RT: EvCode = button(8);
+    pub const BTN_LT2: EvCode = button(9);
+    pub const BTN_RT2: EvCode = button(10);
+    pub const BTN_SELECT: EvCode = button(11);
+    pub const BTN_START: EvCode = button(12);
+    pub const BTN_MODE: EvCode = button(13);
+    pub const BTN_LTHUMB: EvCode = button(14);
+    pub const BTN_RTHUMB: EvCode = button(15);
+
+    pub const BTN_DPAD_UP: EvCode = gd(USAGE_GD_DPAD_UP);
+    pub const BTN_DPAD_DOWN: EvCode = gd(USAGE_GD_DPAD_DOWN);
+    pub const BTN_DPAD_LEFT: EvCode = gd(USAGE_GD_DPAD_LEFT);
+    pub const BTN_DPAD_RIGHT: EvCode = gd(USAGE_GD_DPAD_RIGHT);
+}
@@ -0,0 +1,103 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+// Definitions from dev/usb/usb_ioctl.h and sys/ioccom.h.
+
+use libc as c;
+use std::io;
+use std::mem::{self, MaybeUninit};
+
+const IOCPARM_MASK: c::c_ulong = 0x1fff;
+const IOC_OUT: c::c_ulong = 0x4000_0000;
+const IOC_IN: c::c_ulong = 0x8000_0000;
+const IOC_INOUT: c::c_ulong = IOC_IN | IOC_OUT;
+
+const fn ioc(inout: c::c_ulong, group: u8, num: u8, len: usize) -> c::c_ulong {
+    inout
+        | ((len as c::c_ulong & IOCPARM_MASK) << 16)
+        | (group as c::c_ulong) << 8
+        | num as c::c_ulong
+}
+
+const USB_GET_REPORT_DESC: c::c_ulong =
+    ioc(IOC_INOUT, b'U', 21, mem::size_of::<usb_gen_descriptor>());
+const USB_GET_DEVICEINFO: c::c_ulong = ioc(IOC_OUT, b'U', 112, mem::size_of::<usb_device_info>());
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct usb_gen_descriptor {
+    ugd_data: *mut c::c_void,
+    ugd_lang_id: u16,
+    ugd_maxlen: u16,
+    ugd_actlen: u16,
+    ugd_offset: u16,
+    ugd_config_index: u8,
+    ugd_string_index: u8,
+    ugd_iface_index: u8,
+    ugd_altif_index: u8,
+    ugd_endpt_index: u8,
+    ugd_report_type: u8,
+    reserved: [u8; 8],
+}
+
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct usb_device_info {
+    pub udi_product_no: u16,
+    pub udi_vendor_no: u16,
+    pub udi_release_no: u16,
+    pub udi_power: u16,
+    pub udi_bus: u8,
+    pub udi_addr: u8,
+    pub udi_index: u8,
+    pub udi_class: u8,
+    pub udi_subclass: u8,
+    pub udi_protocol: u8,
+    pub udi_config_no: u8,
+    pub udi_config_index: u8,
+    pub udi_speed: u8,
+    pub udi_mode: u8,
+    pub udi_nports: u8,
+    pub udi_hubaddr: u8,
+    pub udi_hubindex: u8,
+    pub udi_hubport: u8,
+    pub udi_power_mode: u8,
+    pub udi_suspended: u8,
+    pub udi_reserved: [u8; 16],
+    pub udi_product: [c::c_char; 128],
+    pub udi_vendor: [c::c_char; 128],
+    pub udi_serial: [c::c_char; 64],
+    pub udi_release: [c::c_char; 8],
+}
+
+/// Reads HID report descriptor into `buf` and returns its length.
+pub unsafe fn usb_get_report_desc(fd: c::c_int, buf: &mut [u8]) -> io::Result<usize> {
+    let mut desc: usb_gen_descriptor = mem::zeroed();
+    desc.ugd_data = buf.as_mut_ptr() as *mut c::c_void;
+    desc.ugd_maxlen = buf.len().min(0xffff) as u16;
+
+    if c::ioctl(
+        fd,
+        USB_GET_REPORT_DESC,
+        &mut desc as *mut usb_gen_descriptor,
+    ) < 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(usize::from(desc.ugd_actlen).min(buf.len()))
+}
+
+pub unsafe fn usb_get_deviceinfo(fd: c::c_int) -> io::Result<usb_device_info> {
+    let mut info = MaybeUninit::<usb_device_info>::uninit();
+
+    if c::ioctl(fd, USB_GET_DEVICEINFO, info.as_mut_ptr()) < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(info.assume_init())
+}
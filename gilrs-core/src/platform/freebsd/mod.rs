@@ -0,0 +1,22 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+mod ff;
+mod gamepad;
+mod ioctl;
+#[cfg(feature = "raw-hid")]
+#[path = "../raw_hid_unsupported.rs"]
+mod raw_hid;
+
+pub use self::ff::Device as FfDevice;
+pub use self::gamepad::{native_ev_codes, EvCode, Gamepad, Gilrs};
+#[cfg(feature = "raw-hid")]
+pub use self::raw_hid::RawReports;
+
+pub const IS_Y_AXIS_REVERSED: bool = true;
+pub const HAS_KERNEL_TIMESTAMPS: bool = false;
+// uhid(4) devices can be opened only once.
+pub const SUPPORTS_MULTIPLE_INSTANCES: bool = false;
@@ -45,6 +45,30 @@ impl Device {
         }
     }
 
+    /// Returns number of effects that can be uploaded to device at the same time.
+    pub fn query_max_effects(&self) -> usize {
+        let mut max_effects = 0;
+
+        match unsafe { ioctl::eviocgeffects(self.file.as_raw_fd(), &mut max_effects) } {
+            Ok(_) => max_effects.max(0) as usize,
+            Err(err) => {
+                error!(
+                    "Failed to query max number of effects of gamepad {:?}: {}",
+                    self.file, err
+                );
+                DEFAULT_MAX_EFFECTS
+            }
+        }
+    }
+
+    /// Returns false if device was removed since it was opened. Such device can't play effects
+    /// anymore, even if gamepad was connected again under the same path.
+    pub fn is_connected(&self) -> bool {
+        let mut max_effects = 0;
+
+        unsafe { ioctl::eviocgeffects(self.file.as_raw_fd(), &mut max_effects) }.is_ok()
+    }
+
     pub fn set_ff_state(&mut self, strong: u16, weak: u16, min_duration: Duration) {
         let duration = min_duration.as_secs() * 1000 + u64::from(min_duration.subsec_millis());
         let duration = if duration > u64::from(U16_MAX) {
@@ -118,5 +142,7 @@ impl Drop for Device {
     }
 }
 
+// Limit used by most drivers.
+const DEFAULT_MAX_EFFECTS: usize = 16;
 const EV_FF: u16 = 0x15;
 const FF_RUMBLE: u16 = 0x50;
@@ -8,21 +8,29 @@
 use super::ff::Device as FfDevice;
 use super::ioctl;
 use super::ioctl::{input_absinfo, input_event};
+use super::touchpad::{self, PendingTouchpad, Touchpad};
 use super::udev::*;
 use crate::utils;
-use crate::{AxisInfo, Event, EventType};
-use crate::{PlatformError, PowerInfo};
+use crate::{AxisInfo, Event, EventType, RawState};
+use crate::{
+    LedError, PlatformError, PlatformErrorKind, PollingRateError, PowerInfo, PowerOffError,
+};
 
 use libc as c;
 use uuid::Uuid;
 use vec_map::VecMap;
 
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
 use std::error;
 use std::ffi::CStr;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::mem::{self, MaybeUninit};
 use std::ops::Index;
 use std::os::raw::c_char;
+use std::os::unix::io::RawFd;
+use std::path::{Path, PathBuf};
 use std::str;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -31,6 +39,14 @@ pub struct Gilrs {
     gamepads: Vec<Gamepad>,
     monitor: Monitor,
     event_counter: usize,
+    // Touchpads whose gamepad is not connected yet.
+    touchpads: Vec<PendingTouchpad>,
+    // Events generated by `rescan_gamepads()`.
+    pending_events: VecDeque<Event>,
+    // Descriptors of connected gamepads and their touchpads, see `update_device_fds()`.
+    device_fds: Vec<RawFd>,
+    // Hotplug events read ahead by `is_removal_queued()`.
+    hotplug_queue: VecDeque<Device>,
 }
 
 impl Gilrs {
@@ -50,34 +66,62 @@ impl Gilrs {
             }
         };
 
+        let mut touchpads = Vec::new();
+        if let Some(en) = udev.enumerate() {
+            unsafe { en.add_match_property(cstr_new(b"ID_INPUT_TOUCHPAD\0"), cstr_new(b"1\0")) }
+            en.scan_devices();
+
+            for dev in en.iter() {
+                if let Some(dev) = Device::from_syspath(&udev, &dev) {
+                    touchpads.extend(PendingTouchpad::new(&dev));
+                }
+            }
+        }
+
         unsafe { en.add_match_property(cstr_new(b"ID_INPUT_JOYSTICK\0"), cstr_new(b"1\0")) }
         en.scan_devices();
 
         for dev in en.iter() {
             if let Some(dev) = Device::from_syspath(&udev, &dev) {
-                if let Some(gamepad) = Gamepad::open(&dev) {
+                if let Some(mut gamepad) = Gamepad::open(&dev) {
+                    gamepad.attach_touchpad(&mut touchpads);
                     gamepads.push(gamepad);
                 }
             }
         }
+        // Udev returns devices in order of directory entries, which can change between boots.
+        gamepads.sort_by(|a, b| stable_order(a.order_key(), b.order_key()));
 
         let monitor = match Monitor::new(&udev) {
             Some(m) => m,
             None => return Err(PlatformError::Other(Box::new(Error::UdevMonitor))),
         };
 
-        Ok(Gilrs {
+        let mut gilrs = Gilrs {
             gamepads,
             monitor,
             event_counter: 0,
-        })
+            touchpads,
+            pending_events: VecDeque::new(),
+            device_fds: Vec::new(),
+            hotplug_queue: VecDeque::new(),
+        };
+        gilrs.update_device_fds();
+
+        Ok(gilrs)
     }
 
     pub(crate) fn next_event(&mut self) -> Option<Event> {
-        if let Some(event) = self.handle_hotplug() {
+        if let Some(event) = self.pending_events.pop_front() {
             return Some(event);
         }
 
+        let hotplug_event = self.handle_hotplug();
+        self.update_device_fds();
+        if hotplug_event.is_some() {
+            return hotplug_event;
+        }
+
         loop {
             let gamepad = match self.gamepads.get_mut(self.event_counter) {
                 Some(gp) => gp,
@@ -116,11 +160,142 @@ impl Gilrs {
         self.gamepads.len()
     }
 
-    fn handle_hotplug(&mut self) -> Option<Event> {
-        while self.monitor.hotplug_available() {
-            let dev = self.monitor.device();
+    pub(crate) fn rescan_gamepads(&mut self) -> usize {
+        let udev = match Udev::new() {
+            Some(udev) => udev,
+            None => {
+                error!("Failed to rescan gamepads: {}", Error::UdevCtx);
+                return 0;
+            }
+        };
+        let en = match udev.enumerate() {
+            Some(en) => en,
+            None => {
+                error!("Failed to rescan gamepads: {}", Error::UdevEnumerate);
+                return 0;
+            }
+        };
+
+        unsafe { en.add_match_property(cstr_new(b"ID_INPUT_JOYSTICK\0"), cstr_new(b"1\0")) }
+        en.scan_devices();
+
+        let syspaths: Vec<_> = en.iter().collect();
+        let devices: Vec<_> = syspaths
+            .iter()
+            .filter_map(|syspath| Device::from_syspath(&udev, syspath))
+            .filter(|dev| dev.devnode().is_some())
+            .collect();
+        let mut changes = 0;
+
+        // Handle removed devices first, so reconnected gamepads can reuse their old ID.
+        for (id, gamepad) in self.gamepads.iter_mut().enumerate() {
+            if gamepad.is_connected
+                && !devices
+                    .iter()
+                    .filter_map(Device::devnode)
+                    .any(|devnode| is_eq_cstr_str(devnode, &gamepad.devpath))
+            {
+                info!("Gamepad {} ({}) not found.", gamepad.devpath, gamepad.name);
+                gamepad.disconnect();
+                self.pending_events
+                    .push_back(Event::new(id, EventType::Disconnected));
+                changes += 1;
+            } else if let Some(ref input) = gamepad.suspended_input {
+                // Input devices are enumerated too, they just don't have device node.
+                if !syspaths.iter().any(|path| path.to_str() == Ok(input)) {
+                    info!("Suspended gamepad {} not found.", gamepad.name);
+                    gamepad.suspended_input = None;
+                    self.pending_events
+                        .push_back(Event::new(id, EventType::Disconnected));
+                    changes += 1;
+                }
+            }
+        }
+
+        for dev in &devices {
+            if let Some(ev) = self.add_gamepad(dev) {
+                self.pending_events.push_back(ev);
+                changes += 1;
+            }
+        }
+        self.update_device_fds();
+
+        changes
+    }
+
+    pub fn event_source_fd(&self) -> Option<RawFd> {
+        self.monitor.fd()
+    }
+
+    pub fn device_fds(&self) -> &[RawFd] {
+        &self.device_fds
+    }
+
+    fn update_device_fds(&mut self) {
+        self.device_fds.clear();
+        for gamepad in self.gamepads.iter().filter(|gp| gp.is_connected) {
+            self.device_fds.push(gamepad.fd);
+            self.device_fds
+                .extend(gamepad.touchpad.as_ref().map(Touchpad::fd));
+        }
+    }
+
+    // Events are delivered as they arrive, there is no polling.
+    pub(crate) fn set_polling_rate(&mut self, _hz: u32) -> Result<(), PollingRateError> {
+        Err(PollingRateError::NotSupported)
+    }
+
+    /// Opens gamepad and returns its ID, or `None` if device is not a gamepad or is already
+    /// connected.
+    fn add_gamepad(&mut self, dev: &Device) -> Option<Event> {
+        if let Some(devnode) = dev.devnode() {
+            if self
+                .gamepads
+                .iter()
+                .any(|gp| gp.is_connected && is_eq_cstr_str(devnode, &gp.devpath))
+            {
+                return None;
+            }
+        }
 
+        let mut gamepad = Gamepad::open(dev)?;
+        gamepad.attach_touchpad(&mut self.touchpads);
+
+        // Prefer suspended gamepad, so it's resumed instead of other disconnected gamepad with the
+        // same UUID being reconnected.
+        let reused = self
+            .gamepads
+            .iter()
+            .position(|gp| gp.uuid() == gamepad.uuid && gp.suspended_input.is_some())
+            .or_else(|| {
+                self.gamepads
+                    .iter()
+                    .position(|gp| gp.uuid() == gamepad.uuid && !gp.is_connected)
+            });
+
+        if let Some(id) = reused {
+            let event = if self.gamepads[id].suspended_input.is_some() {
+                info!("Gamepad {} ({}) resumed.", gamepad.devpath, gamepad.name);
+                EventType::Resumed
+            } else {
+                EventType::Connected
+            };
+            self.gamepads[id] = gamepad;
+            Some(Event::new(id, event))
+        } else {
+            self.gamepads.push(gamepad);
+            Some(Event::new(self.gamepads.len() - 1, EventType::Connected))
+        }
+    }
+
+    fn handle_hotplug(&mut self) -> Option<Event> {
+        while let Some(dev) = self.next_hotplug_device() {
             unsafe {
+                if dev.property_value(cstr_new(b"ID_INPUT_TOUCHPAD\0")) == Some(cstr_new(b"1\0")) {
+                    self.handle_touchpad_hotplug(&dev);
+                    continue;
+                }
+
                 if let Some(val) = dev.property_value(cstr_new(b"ID_INPUT_JOYSTICK\0")) {
                     if val != cstr_new(b"1\0") {
                         continue;
@@ -135,35 +310,143 @@ impl Gilrs {
                 };
 
                 if action == cstr_new(b"add\0") {
-                    if let Some(gamepad) = Gamepad::open(&dev) {
-                        if let Some(id) = self
-                            .gamepads
-                            .iter()
-                            .position(|gp| gp.uuid() == gamepad.uuid && !gp.is_connected)
-                        {
-                            self.gamepads[id] = gamepad;
-                            return Some(Event::new(id, EventType::Connected));
-                        } else {
-                            self.gamepads.push(gamepad);
-                            return Some(Event::new(self.gamepads.len() - 1, EventType::Connected));
-                        }
+                    if let Some(ev) = self.add_gamepad(&dev) {
+                        return Some(ev);
                     }
                 } else if action == cstr_new(b"remove\0") {
-                    if let Some(devnode) = dev.devnode() {
-                        if let Some(id) = self
-                            .gamepads
-                            .iter()
-                            .position(|gp| is_eq_cstr_str(devnode, &gp.devpath) && gp.is_connected)
-                        {
-                            self.gamepads[id].disconnect();
-                            return Some(Event::new(id, EventType::Disconnected));
-                        } else {
-                            debug!("Could not find disconnected gamepad {:?}", devnode);
-                        }
+                    if let Some(ev) = self.remove_gamepad(&dev) {
+                        return Some(ev);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn next_hotplug_device(&mut self) -> Option<Device> {
+        if let Some(dev) = self.hotplug_queue.pop_front() {
+            Some(dev)
+        } else if self.monitor.hotplug_available() {
+            Some(self.monitor.device())
+        } else {
+            None
+        }
+    }
+
+    /// Handles removal of gamepad's event device (`eventN`) or its parent input device
+    /// (`inputN`).
+    ///
+    /// When device is unplugged or its driver unbound, kernel removes both of them, event device
+    /// first. If only event device is removed, physical device is still there and gamepad is
+    /// suspended. It's disconnected when input device is removed later.
+    ///
+    /// udev may deliver removal of input device after removal of event device is handled, so
+    /// gamepad is only suspended if input device is also still present in sysfs. On unplug kernel
+    /// removes it right after event device, before udev processes removal of event device.
+    fn remove_gamepad(&mut self, dev: &Device) -> Option<Event> {
+        let syspath = dev.syspath().to_str().ok()?;
+
+        let devnode = match dev.devnode() {
+            Some(devnode) => devnode,
+            None => {
+                let id = self
+                    .gamepads
+                    .iter()
+                    .position(|gp| gp.suspended_input.as_deref() == Some(syspath))?;
+                let gamepad = &mut self.gamepads[id];
+                info!("Suspended gamepad {} removed.", gamepad.name);
+                gamepad.suspended_input = None;
+
+                return Some(Event::new(id, EventType::Disconnected));
+            }
+        };
+
+        let id = match self
+            .gamepads
+            .iter()
+            .position(|gp| gp.is_connected && is_eq_cstr_str(devnode, &gp.devpath))
+        {
+            Some(id) => id,
+            None => {
+                debug!("Could not find disconnected gamepad {:?}", devnode);
+                return None;
+            }
+        };
+
+        let input = input_device_path(syspath).map(str::to_owned);
+        let is_input_removed = match input {
+            Some(ref input) => self.is_removal_queued(input) || !Path::new(input).exists(),
+            None => true,
+        };
+
+        let gamepad = &mut self.gamepads[id];
+        gamepad.disconnect();
+
+        if is_input_removed {
+            Some(Event::new(id, EventType::Disconnected))
+        } else {
+            info!(
+                "Gamepad {} suspended, input device still exists.",
+                gamepad.name
+            );
+            gamepad.suspended_input = input;
+            Some(Event::new(id, EventType::Suspended))
+        }
+    }
+
+    /// Reads hotplug events that are already available and returns true if one of them is
+    /// removal of device with `syspath`. Events read here are handled later, so removal of input
+    /// device doesn't emit second `Disconnected`.
+    fn is_removal_queued(&mut self, syspath: &str) -> bool {
+        while self.monitor.hotplug_available() {
+            self.hotplug_queue.push_back(self.monitor.device());
+        }
+
+        self.hotplug_queue.iter().any(|dev| {
+            dev.action() == Some(unsafe { cstr_new(b"remove\0") })
+                && dev.syspath().to_str() == Ok(syspath)
+        })
+    }
+
+    fn handle_touchpad_hotplug(&mut self, dev: &Device) {
+        let action = match dev.action() {
+            Some(a) => a,
+            None => return,
+        };
+
+        if action == unsafe { cstr_new(b"add\0") } {
+            if let Some(pending) = PendingTouchpad::new(dev) {
+                self.touchpads.push(pending);
+                for gamepad in self.gamepads.iter_mut().filter(|gp| gp.is_connected) {
+                    gamepad.attach_touchpad(&mut self.touchpads);
+                }
+            }
+        } else if action == unsafe { cstr_new(b"remove\0") } {
+            if let Some(devnode) = dev.devnode() {
+                self.touchpads
+                    .retain(|pending| pending.devnode.as_c_str() != devnode);
+                for gamepad in &mut self.gamepads {
+                    if gamepad.touchpad.as_ref().map(Touchpad::devnode) == Some(devnode) {
+                        info!(
+                            "Touchpad {:?} of gamepad {} removed.",
+                            devnode, gamepad.name
+                        );
+                        gamepad.touchpad = None;
                     }
                 }
             }
         }
+    }
+}
+
+/// Returns sysfs path of input device (`inputN`) that is parent of event device with `syspath`.
+fn input_device_path(syspath: &str) -> Option<&str> {
+    let input = &syspath[..syspath.rfind('/')?];
+    let name = &input[input.rfind('/')? + 1..];
+    if name.starts_with("input") && name.len() > 5 && name[5..].bytes().all(|b| b.is_ascii_digit())
+    {
+        Some(input)
+    } else {
         None
     }
 }
@@ -241,16 +524,42 @@ pub struct Gamepad {
     bt_capacity_fd: i32,
     // TODO: path or RefCell<File>
     bt_status_fd: i32,
+    report_rate: Option<u32>,
     axes_values: VecMap<i32>,
     buttons_values: VecMap<bool>,
     events: Vec<input_event>,
     axes: Vec<EvCode>,
     buttons: Vec<EvCode>,
     is_connected: bool,
+    // Syspath of input device (`inputN`) of suspended gamepad. Its event device was removed, but
+    // input device still exists.
+    suspended_input: Option<String>,
+    uniq: Option<String>,
+    touchpad: Option<Touchpad>,
+    manufacturer: Option<String>,
+    product: Option<String>,
+    player_leds: PlayerLeds,
+    port_path: Option<String>,
+    // Number of joydev node (`/dev/input/jsN`) of the same input device.
+    js_number: Option<u8>,
+}
+
+/// LEDs that can show player number, found in sysfs when gamepad is opened.
+#[derive(Debug)]
+enum PlayerLeds {
+    None,
+    /// LED of `xpad` driver. Its brightness selects one of predefined patterns.
+    Xpad(PathBuf),
+    /// LEDs named `<device>:<color>:player-<n>` and their numbers, used by `hid-nintendo` and
+    /// `hid-playstation`.
+    Separate(Vec<(u8, PathBuf)>),
 }
 
 impl Gamepad {
     fn open(dev: &Device) -> Option<Gamepad> {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
         let path = match dev.devnode() {
             Some(path) => path,
             None => return None,
@@ -263,14 +572,22 @@ impl Gamepad {
 
         let fd = unsafe { c::open(path.as_ptr(), c::O_RDWR | c::O_NONBLOCK) };
         if fd < 0 {
-            error!("Failed to open {:?}", path);
+            error!(
+                "Failed to open {:?}: {}",
+                path,
+                PlatformErrorKind::last_os_error("open")
+            );
             return None;
         }
 
         let uuid = match Self::create_uuid(fd) {
             Some(uuid) => uuid,
             None => {
-                error!("Failed to get id of device {:?}", path);
+                error!(
+                    "Failed to get id of device {:?}: {}",
+                    path,
+                    PlatformErrorKind::last_os_error("ioctl_eviocgid")
+                );
                 unsafe {
                     c::close(fd);
                 }
@@ -286,6 +603,9 @@ impl Gamepad {
         let axesi = AxesInfo::new(fd);
         let ff_supported = Self::test_ff(fd);
         let (cap, status) = Self::battery_fd(&dev);
+        let report_rate = Self::report_rate(dev);
+        let player_leds = Self::player_leds(dev);
+        let syspath = Path::new(OsStr::from_bytes(dev.syspath().to_bytes()));
 
         let mut gamepad = Gamepad {
             fd,
@@ -296,12 +616,33 @@ impl Gamepad {
             uuid,
             bt_capacity_fd: cap,
             bt_status_fd: status,
+            report_rate,
             axes_values: VecMap::new(),
             buttons_values: VecMap::new(),
             events: Vec::new(),
             axes: Vec::new(),
             buttons: Vec::new(),
             is_connected: true,
+            suspended_input: None,
+            uniq: touchpad::uniq(dev),
+            touchpad: None,
+            manufacturer: unsafe {
+                udev_string(
+                    dev,
+                    cstr_new(b"ID_VENDOR_ENC\0"),
+                    cstr_new(b"ID_VENDOR_FROM_DATABASE\0"),
+                )
+            },
+            product: unsafe {
+                udev_string(
+                    dev,
+                    cstr_new(b"ID_MODEL_ENC\0"),
+                    cstr_new(b"ID_MODEL_FROM_DATABASE\0"),
+                )
+            },
+            player_leds,
+            port_path: read_phys(syspath),
+            js_number: js_number(syspath),
         };
 
         gamepad.collect_axes_and_buttons();
@@ -328,6 +669,25 @@ impl Gamepad {
         Some(gamepad)
     }
 
+    /// Opens touchpad from `pending` that has the same `uniq` as gamepad.
+    fn attach_touchpad(&mut self, pending: &mut Vec<PendingTouchpad>) {
+        let uniq = match (&self.uniq, &self.touchpad) {
+            (Some(uniq), None) => uniq,
+            _ => return,
+        };
+
+        if let Some(idx) = pending.iter().position(|touchpad| &touchpad.uniq == uniq) {
+            let touchpad = pending.remove(idx);
+            self.touchpad = Touchpad::open(&touchpad);
+            if self.touchpad.is_some() {
+                info!(
+                    "Touchpad {:?} attached to gamepad {}.",
+                    touchpad.devnode, self.devpath
+                );
+            }
+        }
+    }
+
     fn collect_axes_and_buttons(&mut self) {
         let mut key_bits = [0u8; (KEY_MAX / 8) as usize + 1];
         let mut abs_bits = [0u8; (ABS_MAX / 8) as usize + 1];
@@ -468,6 +828,90 @@ impl Gamepad {
         (-1, -1)
     }
 
+    fn report_rate(dev: &Device) -> Option<u32> {
+        use std::ffi::OsStr;
+        use std::fs;
+        use std::os::unix::ffi::OsStrExt;
+        use std::path::Path;
+
+        let syspath = Path::new(OsStr::from_bytes(dev.syspath().to_bytes()));
+        // Depending on driver, "device/device" is either USB interface or HID device, which is
+        // child of USB interface. Interrupt IN endpoint of interface tells us polling interval.
+        let devroot = syspath.join("device/device");
+        for dir in [devroot.clone(), devroot.join("..")].iter() {
+            let read_dir = match fs::read_dir(dir) {
+                Ok(read_dir) => read_dir,
+                Err(_) => continue,
+            };
+
+            for entry in read_dir.filter_map(Result::ok) {
+                if !entry.file_name().to_string_lossy().starts_with("ep_") {
+                    continue;
+                }
+
+                let path = entry.path();
+                let is_in = fs::read_to_string(path.join("direction"))
+                    .map(|d| d.trim() == "in")
+                    .unwrap_or(false);
+                let is_interrupt = fs::read_to_string(path.join("type"))
+                    .map(|t| t.trim() == "Interrupt")
+                    .unwrap_or(false);
+
+                if is_in && is_interrupt {
+                    if let Ok(interval) = fs::read_to_string(path.join("interval")) {
+                        return parse_interval(interval.trim());
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn player_leds(dev: &Device) -> PlayerLeds {
+        use std::ffi::OsStr;
+        use std::fs;
+        use std::os::unix::ffi::OsStrExt;
+
+        let syspath = Path::new(OsStr::from_bytes(dev.syspath().to_bytes()));
+        // HID drivers register LEDs on HID device, which is "device/device". `xpad` registers
+        // them on USB device, which is parent of USB interface.
+        let devroot = syspath.join("device/device");
+        for dir in [devroot.join("leds"), devroot.join("../leds")].iter() {
+            let read_dir = match fs::read_dir(dir) {
+                Ok(read_dir) => read_dir,
+                Err(_) => continue,
+            };
+
+            let mut leds = Vec::new();
+            for entry in read_dir.filter_map(Result::ok) {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if name.starts_with("xpad") {
+                    return PlayerLeds::Xpad(entry.path());
+                }
+
+                if let Some(player) = player_led_number(&name) {
+                    leds.push((player, entry.path()));
+                }
+            }
+
+            if !leds.is_empty() {
+                leds.sort();
+                return PlayerLeds::Separate(leds);
+            }
+        }
+
+        PlayerLeds::None
+    }
+
+    fn order_key(&self) -> (Option<&str>, Option<&str>, &str) {
+        (
+            self.port_path.as_deref(),
+            self.uniq.as_deref(),
+            &self.devpath,
+        )
+    }
+
     fn event(&mut self) -> Option<(EventType, SystemTime)> {
         let mut skip = false;
         // Skip all unknown events and return Option on first know event or when there is no more
@@ -475,7 +919,7 @@ impl Gamepad {
         loop {
             let event = match self.next_event() {
                 Some(e) => e,
-                None => return None,
+                None => return self.touchpad.as_mut().and_then(Touchpad::event),
             };
 
             if skip {
@@ -608,6 +1052,8 @@ impl Gamepad {
         self.fd = -2;
         self.devpath.clear();
         self.is_connected = false;
+        self.suspended_input = None;
+        self.touchpad = None;
     }
 
     pub fn is_connected(&self) -> bool {
@@ -670,14 +1116,150 @@ impl Gamepad {
         self.ff_supported
     }
 
+    pub fn power_off(&self) -> Result<(), PowerOffError> {
+        // BlueZ only allows to disconnect devices over D-Bus and drivers don't have sysfs
+        // interface for it.
+        if self.is_connected {
+            Err(PowerOffError::NotSupported)
+        } else {
+            Err(PowerOffError::Disconnected)
+        }
+    }
+
+    pub fn set_player_indicator(&self, player: Option<u8>) -> Result<(), LedError> {
+        use std::fs;
+
+        let set_brightness = |led: &Path, brightness: u8| {
+            fs::write(led.join("brightness"), brightness.to_string())
+                .map_err(|e| LedError::Other(Box::new(e)))
+        };
+
+        match self.player_leds {
+            PlayerLeds::None => Err(LedError::NotSupported),
+            PlayerLeds::Xpad(ref led) => set_brightness(led, xpad_led_pattern(player)),
+            PlayerLeds::Separate(ref leds) => {
+                for &(num, ref led) in leds {
+                    set_brightness(led, (Some(num) == player) as u8)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns player shown by LEDs, which `xpad`, `hid-nintendo` and `hid-playstation` set when
+    /// gamepad connects, or number of joydev node.
+    pub fn suggested_player_index(&self) -> Option<u8> {
+        use std::fs;
+
+        let brightness = |led: &Path| -> Option<u8> {
+            fs::read_to_string(led.join("brightness"))
+                .ok()
+                .and_then(|b| b.trim().parse().ok())
+        };
+
+        let player = match self.player_leds {
+            PlayerLeds::None => None,
+            PlayerLeds::Xpad(ref led) => brightness(led).and_then(xpad_led_player),
+            PlayerLeds::Separate(ref leds) => {
+                let mut lit = leds
+                    .iter()
+                    .filter(|(_, led)| brightness(led).unwrap_or(0) > 0);
+                match (lit.next(), lit.next()) {
+                    (Some(&(num, _)), None) => Some(num),
+                    _ => None,
+                }
+            }
+        };
+
+        player
+            .filter(|&player| player > 0)
+            .map(|player| player - 1)
+            .or(self.js_number)
+    }
+
+    pub fn port_path(&self) -> Option<&str> {
+        self.port_path.as_deref()
+    }
+
+    pub fn serial_number(&self) -> Option<&str> {
+        self.uniq.as_deref()
+    }
+
+    pub fn input_report_rate(&self) -> Option<u32> {
+        self.report_rate
+    }
+
+    pub fn has_touchpad(&self) -> bool {
+        self.touchpad.is_some()
+    }
+
+    pub fn current_state(&self) -> Option<RawState> {
+        if self.fd < 0 {
+            return None;
+        }
+
+        let mut buf = [0u8; KEY_MAX as usize / 8 + 1];
+        if let Err(e) = unsafe { ioctl::eviocgkey(self.fd, &mut buf) } {
+            debug!("Failed to read state of buttons: {}", e);
+            return None;
+        }
+
+        let buttons = self
+            .buttons
+            .iter()
+            .map(|&btn| (crate::EvCode(btn), utils::test_bit(btn.code, &buf)))
+            .collect();
+
+        let mut axes = Vec::with_capacity(self.axes.len());
+        let mut absinfo = input_absinfo::default();
+        for &axis in &self.axes {
+            if unsafe { ioctl::eviocgabs(self.fd, u32::from(axis.code), &mut absinfo) } < 0 {
+                debug!(
+                    "Failed to read state of axis: {}",
+                    PlatformErrorKind::last_os_error("ioctl_eviocgabs")
+                );
+                return None;
+            }
+            axes.push((crate::EvCode(axis), absinfo.value));
+        }
+
+        Some(RawState { buttons, axes })
+    }
+
+    pub fn os_path(&self) -> Option<&str> {
+        if self.devpath.is_empty() {
+            None
+        } else {
+            Some(&self.devpath)
+        }
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    pub fn manufacturer_string(&self) -> Option<&str> {
+        self.manufacturer.as_deref()
+    }
+
+    pub fn product_string(&self) -> Option<&str> {
+        self.product.as_deref()
+    }
+
     pub fn uuid(&self) -> Uuid {
         self.uuid
     }
 
+    #[cfg(feature = "raw-hid")]
+    pub fn raw_reports(&self) -> std::io::Result<super::RawReports> {
+        if !self.is_connected {
+            return Err(std::io::ErrorKind::NotConnected.into());
+        }
+
+        super::RawReports::open(&self.devpath)
+    }
+
     pub fn ff_device(&self) -> Option<FfDevice> {
         if self.is_ff_supported() {
             FfDevice::new(&self.devpath).ok()
@@ -748,10 +1330,181 @@ fn create_uuid(iid: ioctl::input_id) -> Uuid {
     .unwrap()
 }
 
+/// Converts endpoint interval from sysfs (for example "8ms" or "125us") to number of reports per
+/// second.
+fn parse_interval(interval: &str) -> Option<u32> {
+    let unit_idx = interval.find(|c: char| !c.is_ascii_digit())?;
+    let value: u32 = interval[..unit_idx].parse().ok()?;
+    let us = match &interval[unit_idx..] {
+        "us" => value,
+        "ms" => value.checked_mul(1000)?,
+        _ => return None,
+    };
+
+    1_000_000u32.checked_div(us).filter(|&rate| rate > 0)
+}
+
+/// Returns player number of LED named `<device>:<color>:player-<n>`.
+fn player_led_number(name: &str) -> Option<u8> {
+    let idx = name.rfind(":player-")?;
+    name[idx + ":player-".len()..].parse().ok()
+}
+
+/// Returns brightness of `xpad` LED that shows `player`. Values 6–9 turn on quadrant of player
+/// 1–4, 0 turns all quadrants off.
+fn xpad_led_pattern(player: Option<u8>) -> u8 {
+    player.map(|player| player + 5).unwrap_or(0)
+}
+
+/// Returns player whose quadrant is turned on by `xpad` LED `brightness`. Values 2–5 blink the
+/// quadrant before turning it on, `xpad` uses them when gamepad connects.
+fn xpad_led_player(brightness: u8) -> Option<u8> {
+    match brightness {
+        2..=5 => Some(brightness - 1),
+        6..=9 => Some(brightness - 5),
+        _ => None,
+    }
+}
+
+/// Returns `phys` of input device that owns evdev node at `syspath`, for example
+/// `usb-0000:00:14.0-2/input0`. It describes port to which device is connected.
+fn read_phys(syspath: &Path) -> Option<String> {
+    let phys = std::fs::read_to_string(syspath.join("device/phys")).ok()?;
+    let phys = phys.trim();
+
+    if phys.is_empty() {
+        None
+    } else {
+        Some(phys.to_owned())
+    }
+}
+
+/// Returns number of joydev node that belongs to the same input device as evdev node at
+/// `syspath`.
+fn js_number(syspath: &Path) -> Option<u8> {
+    std::fs::read_dir(syspath.join("device"))
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if name.get(..2) == Some("js") {
+                name[2..].parse().ok()
+            } else {
+                None
+            }
+        })
+        .min()
+}
+
+/// Orders gamepads by `phys`, then `uniq` (Bluetooth gamepads share `phys` of adapter) and device
+/// node. Gamepads without `phys` are last. Numbers are compared by value, so port 10 is after
+/// port 2, and ports of hub are after port to which hub is connected.
+fn stable_order<'a>(
+    a: (Option<&'a str>, Option<&'a str>, &'a str),
+    b: (Option<&'a str>, Option<&'a str>, &'a str),
+) -> Ordering {
+    // Split "usb-0000:00:14.0-2.3/input0" to port and interface, so "2.3" isn't before "2".
+    let split = |s: &'a str| {
+        let mut parts = s.splitn(2, '/');
+        (parts.next().unwrap_or(""), parts.next().unwrap_or(""))
+    };
+    let optional = |a: Option<&'a str>, b: Option<&'a str>| match (a, b) {
+        (Some(a), Some(b)) => {
+            let (a, b) = (split(a), split(b));
+            natural_cmp(a.0, b.0).then_with(|| natural_cmp(a.1, b.1))
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    };
+
+    optional(a.0, b.0)
+        .then_with(|| optional(a.1, b.1))
+        .then_with(|| natural_cmp(a.2, b.2))
+}
+
+/// Compares strings like `str::cmp()`, but runs of digits are compared as numbers.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    fn split_number(s: &[u8]) -> (&[u8], &[u8]) {
+        let end = s
+            .iter()
+            .position(|c| !c.is_ascii_digit())
+            .unwrap_or(s.len());
+        let start = s[..end].iter().position(|&c| c != b'0').unwrap_or(end);
+
+        (&s[start..end], &s[end..])
+    }
+
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (num_a, rest_a) = split_number(a);
+                let (num_b, rest_b) = split_number(b);
+                let ord = num_a.len().cmp(&num_b.len()).then(num_a.cmp(num_b));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                a = rest_a;
+                b = rest_b;
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(y);
+                }
+                a = &a[1..];
+                b = &b[1..];
+            }
+        }
+    }
+}
+
 unsafe fn cstr_new(bytes: &[u8]) -> &CStr {
     CStr::from_bytes_with_nul_unchecked(bytes)
 }
 
+/// Returns string descriptor of USB device from udev property `enc_key` or, if device doesn't have
+/// it, name from hardware database stored in `db_key`.
+fn udev_string(dev: &Device, enc_key: &CStr, db_key: &CStr) -> Option<String> {
+    dev.property_value(enc_key)
+        .map(|val| decode_udev_enc(&val.to_string_lossy()))
+        .filter(|val| !val.trim().is_empty())
+        .or_else(|| {
+            dev.property_value(db_key)
+                .map(|val| val.to_string_lossy().into_owned())
+        })
+        .map(|val| val.trim().to_owned())
+        .filter(|val| !val.is_empty())
+}
+
+/// Decodes value of udev's `*_ENC` property, which has special characters escaped as `\xHH`.
+fn decode_udev_enc(val: &str) -> String {
+    let bytes = val.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'x') {
+            if let Some(byte) = val
+                .get(i + 2..i + 4)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 #[cfg(feature = "serde-serialize")]
 use serde::{Deserialize, Serialize};
 
@@ -767,9 +1520,68 @@ impl EvCode {
         EvCode { kind, code }
     }
 
+    pub fn from_raw(kind: u32, number: u32) -> Option<Self> {
+        Some(EvCode {
+            kind: u16::try_from(kind).ok()?,
+            code: u16::try_from(number).ok()?,
+        })
+    }
+
+    pub fn synthetic(index: u8) -> Self {
+        EvCode::new(EV_SYNTHETIC, u16::from(index))
+    }
+
     pub fn into_u32(self) -> u32 {
         u32::from(self.kind) << 16 | u32::from(self.code)
     }
+
+    pub fn debug_info(self) -> crate::EvCodeInfo {
+        crate::EvCodeInfo {
+            platform: "Linux",
+            kind: u32::from(self.kind),
+            number: u32::from(self.code),
+            name: self.name(),
+        }
+    }
+
+    fn name(self) -> Option<&'static str> {
+        let name = match (self.kind, self.code) {
+            (EV_KEY, BTN_SOUTH) => "BTN_SOUTH",
+            (EV_KEY, BTN_EAST) => "BTN_EAST",
+            (EV_KEY, BTN_C) => "BTN_C",
+            (EV_KEY, BTN_NORTH) => "BTN_NORTH",
+            (EV_KEY, BTN_WEST) => "BTN_WEST",
+            (EV_KEY, BTN_Z) => "BTN_Z",
+            (EV_KEY, BTN_TL) => "BTN_TL",
+            (EV_KEY, BTN_TR) => "BTN_TR",
+            (EV_KEY, BTN_TL2) => "BTN_TL2",
+            (EV_KEY, BTN_TR2) => "BTN_TR2",
+            (EV_KEY, BTN_SELECT) => "BTN_SELECT",
+            (EV_KEY, BTN_START) => "BTN_START",
+            (EV_KEY, BTN_MODE) => "BTN_MODE",
+            (EV_KEY, BTN_THUMBL) => "BTN_THUMBL",
+            (EV_KEY, BTN_THUMBR) => "BTN_THUMBR",
+            (EV_KEY, BTN_DPAD_UP) => "BTN_DPAD_UP",
+            (EV_KEY, BTN_DPAD_DOWN) => "BTN_DPAD_DOWN",
+            (EV_KEY, BTN_DPAD_LEFT) => "BTN_DPAD_LEFT",
+            (EV_KEY, BTN_DPAD_RIGHT) => "BTN_DPAD_RIGHT",
+            (EV_ABS, ABS_X) => "ABS_X",
+            (EV_ABS, ABS_Y) => "ABS_Y",
+            (EV_ABS, ABS_Z) => "ABS_Z",
+            (EV_ABS, ABS_RX) => "ABS_RX",
+            (EV_ABS, ABS_RY) => "ABS_RY",
+            (EV_ABS, ABS_RZ) => "ABS_RZ",
+            (EV_ABS, ABS_HAT0X) => "ABS_HAT0X",
+            (EV_ABS, ABS_HAT0Y) => "ABS_HAT0Y",
+            (EV_ABS, ABS_HAT1X) => "ABS_HAT1X",
+            (EV_ABS, ABS_HAT1Y) => "ABS_HAT1Y",
+            (EV_ABS, ABS_HAT2X) => "ABS_HAT2X",
+            (EV_ABS, ABS_HAT2Y) => "ABS_HAT2Y",
+            _ => return None,
+        };
+
+        Some(name)
+    }
 }
 
 impl From<input_event> for crate::EvCode {
@@ -797,6 +1609,7 @@ impl Display for EvCode {
     }
 }
 
+// libudev doesn't document errno when it returns NULL, so these errors don't carry OS error code.
 #[derive(Debug, Copy, Clone)]
 #[allow(clippy::enum_variant_names)]
 enum Error {
@@ -810,7 +1623,7 @@ impl Display for Error {
         match *self {
             Error::UdevCtx => f.write_str("Failed to create udev context"),
             Error::UdevEnumerate => f.write_str("Failed to create udev enumerate object"),
-            Error::UdevMonitor => f.write_str("Failed to create udev monitor."),
+            Error::UdevMonitor => f.write_str("Failed to create udev monitor"),
         }
     }
 }
@@ -825,6 +1638,8 @@ const EV_KEY: u16 = 0x01;
 const EV_ABS: u16 = 0x03;
 const ABS_MAX: u16 = 0x3f;
 const EV_FF: u16 = 0x15;
+// Greater than EV_MAX, so kernel never reports it.
+const EV_SYNTHETIC: u16 = 0xffff;
 
 const SYN_REPORT: u16 = 0x00;
 const SYN_DROPPED: u16 = 0x03;
@@ -1007,7 +1822,11 @@ pub mod native_ev_codes {
 #[cfg(test)]
 mod tests {
     use super::super::ioctl;
-    use super::create_uuid;
+    use super::{
+        create_uuid, decode_udev_enc, input_device_path, js_number, natural_cmp, parse_interval,
+        player_led_number, read_phys, stable_order, xpad_led_pattern, xpad_led_player, Error,
+        EvCode, EV_ABS, EV_KEY,
+    };
     use uuid::Uuid;
 
     #[test]
@@ -1021,4 +1840,146 @@ mod tests {
         });
         assert_eq!(x, y);
     }
+
+    #[test]
+    fn endpoint_interval() {
+        assert_eq!(parse_interval("8ms"), Some(125));
+        assert_eq!(parse_interval("4ms"), Some(250));
+        assert_eq!(parse_interval("125us"), Some(8000));
+        assert_eq!(parse_interval("0ms"), None);
+        assert_eq!(parse_interval("8"), None);
+    }
+
+    #[test]
+    fn player_leds() {
+        assert_eq!(
+            player_led_number("0005:057E:2009.0001:green:player-1"),
+            Some(1)
+        );
+        assert_eq!(player_led_number("input12::white:player-4"), Some(4));
+        assert_eq!(player_led_number("0005:057E:2009.0001:home"), None);
+        assert_eq!(player_led_number("input12:rgb:indicator"), None);
+
+        assert_eq!(xpad_led_pattern(None), 0);
+        assert_eq!(xpad_led_pattern(Some(1)), 6);
+        assert_eq!(xpad_led_pattern(Some(4)), 9);
+
+        assert_eq!(xpad_led_player(xpad_led_pattern(Some(3))), Some(3));
+        assert_eq!(xpad_led_player(2), Some(1));
+        assert_eq!(xpad_led_player(0), None);
+        assert_eq!(xpad_led_player(10), None);
+    }
+
+    #[test]
+    fn natural_order() {
+        use std::cmp::Ordering;
+
+        assert_eq!(natural_cmp("usb-1-2", "usb-1-10"), Ordering::Less);
+        assert_eq!(natural_cmp("usb-1-2.3", "usb-1-2"), Ordering::Greater);
+        assert_eq!(natural_cmp("event02", "event2"), Ordering::Equal);
+        assert_eq!(natural_cmp("a", "b"), Ordering::Less);
+    }
+
+    #[test]
+    fn stable_enumeration_order() {
+        use std::fs;
+
+        let root = std::env::temp_dir().join(format!("gilrs-sysfs-{}", std::process::id()));
+        let devices = [
+            ("event3", Some("usb-0000:00:14.0-10/input0\n"), "js0"),
+            ("event4", None, "js1"),
+            ("event5", Some("usb-0000:00:14.0-2/input0"), "js3"),
+            ("event6", Some("usb-0000:00:14.0-2.3/input0"), "js2"),
+            ("event7", Some(""), "js4"),
+        ];
+        for &(event, phys, js) in devices.iter() {
+            let device = root.join(event).join("device");
+            fs::create_dir_all(device.join(js)).unwrap();
+            if let Some(phys) = phys {
+                fs::write(device.join("phys"), phys).unwrap();
+            }
+        }
+
+        let mut gamepads = devices
+            .iter()
+            .map(|&(event, _, _)| (read_phys(&root.join(event)), event))
+            .collect::<Vec<_>>();
+        gamepads.sort_by(|&(ref pa, a), &(ref pb, b)| {
+            stable_order((pa.as_deref(), None, a), (pb.as_deref(), None, b))
+        });
+        let js = gamepads
+            .iter()
+            .map(|&(_, event)| js_number(&root.join(event)))
+            .collect::<Vec<_>>();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(gamepads[0].0.as_deref(), Some("usb-0000:00:14.0-2/input0"));
+        assert_eq!(gamepads[2].0.as_deref(), Some("usb-0000:00:14.0-10/input0"));
+        assert_eq!(
+            gamepads.iter().map(|g| g.1).collect::<Vec<_>>(),
+            ["event5", "event6", "event3", "event4", "event7"]
+        );
+        assert_eq!(js, [Some(3), Some(2), Some(0), Some(1), Some(4)]);
+
+        // Bluetooth gamepads have the same `phys`.
+        let adapter = Some("00:1a:7d:da:71:13");
+        assert_eq!(
+            stable_order(
+                (adapter, Some("a4:ae:12:00:00:02"), "/dev/input/event3"),
+                (adapter, Some("a4:ae:12:00:00:01"), "/dev/input/event4")
+            ),
+            std::cmp::Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn unknown_ev_code_debug_info() {
+        let info = EvCode::new(EV_KEY, 0x2c0).debug_info();
+        assert_eq!(info.name, None);
+        assert_eq!(info.to_string(), "0x01/0x2c0");
+
+        let info = EvCode::new(EV_ABS, 0x10).debug_info();
+        assert_eq!(info.to_string(), "ABS_HAT0X/16");
+    }
+
+    #[test]
+    fn input_device() {
+        let hid = "/sys/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2:1.0/0003:045E:028E.0001";
+        let input = format!("{}/input/input23", hid);
+        assert_eq!(
+            input_device_path(&format!("{}/event5", input)),
+            Some(&input[..])
+        );
+        assert_eq!(
+            input_device_path("/sys/devices/virtual/input/input7/event3"),
+            Some("/sys/devices/virtual/input/input7")
+        );
+        assert_eq!(input_device_path(&input), None);
+        assert_eq!(input_device_path("event3"), None);
+    }
+
+    #[test]
+    fn udev_error() {
+        use std::error::Error as _;
+
+        let err = Error::UdevCtx;
+        assert_eq!(err.to_string(), "Failed to create udev context");
+        assert!(err.source().is_none());
+
+        // errno is not meaningful after libudev failure.
+        let err = crate::Error::Other(Box::new(err));
+        assert_eq!(err.os_error(), None);
+    }
+
+    #[test]
+    fn udev_enc() {
+        assert_eq!(
+            decode_udev_enc("Sony\\x20Interactive\\x20Entertainment"),
+            "Sony Interactive Entertainment"
+        );
+        assert_eq!(decode_udev_enc("Pad\\x2fV2"), "Pad/V2");
+        // Invalid escapes are kept.
+        assert_eq!(decode_udev_enc("a\\xZZb\\x2"), "a\\xZZb\\x2");
+        assert_eq!(decode_udev_enc("Caf\\xc3\\xa9"), "Café");
+    }
 }
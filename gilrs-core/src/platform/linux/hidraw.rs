@@ -0,0 +1,183 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Access to raw HID reports through hidraw node of HID device that created evdev device.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub struct RawReports {
+    file: File,
+}
+
+impl RawReports {
+    pub fn open(devpath: &str) -> io::Result<Self> {
+        let path = hidraw_path(devpath)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK | libc::O_CLOEXEC)
+            .open(&path)?;
+
+        Ok(RawReports { file })
+    }
+
+    pub fn next_report(&mut self, buf: &mut [u8]) -> Option<usize> {
+        match self.file.read(buf) {
+            Ok(len) => Some(len),
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => None,
+            Err(e) => {
+                debug!("Failed to read HID report: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn write_report(&mut self, report: &[u8]) -> io::Result<usize> {
+        self.file.write(report)
+    }
+}
+
+/// Returns path to hidraw node of HID device that is parent of evdev device `devpath`.
+fn hidraw_path(devpath: &str) -> io::Result<PathBuf> {
+    let not_hid = || io::Error::new(ErrorKind::NotFound, "device doesn't have hidraw node");
+
+    let name = Path::new(devpath).file_name().ok_or_else(not_hid)?;
+    // `device` of evdev node is input device and its parent is HID device. Devices that don't use
+    // HID drivers (like xpad) don't have `hidraw` directory there.
+    let dir = Path::new("/sys/class/input")
+        .join(name)
+        .join("device/device/hidraw");
+
+    match fs::read_dir(dir).map_err(|_| not_hid())?.next() {
+        Some(entry) => Ok(Path::new("/dev").join(entry?.file_name())),
+        None => Err(not_hid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const UHID_DESTROY: u32 = 1;
+    const UHID_OUTPUT: u32 = 6;
+    const UHID_CREATE2: u32 = 11;
+    const UHID_INPUT2: u32 = 12;
+    // sizeof(struct uhid_event)
+    const UHID_EVENT_SIZE: usize = 4380;
+    const BUS_USB: u16 = 0x03;
+
+    // Gamepad with 8 buttons and 8 LEDs in output report.
+    const REPORT_DESCRIPTOR: [u8; 31] = [
+        0x05, 0x01, 0x09, 0x05, 0xa1, 0x01, 0x05, 0x09, 0x19, 0x01, 0x29, 0x08, 0x15, 0x00, 0x25,
+        0x01, 0x75, 0x01, 0x95, 0x08, 0x81, 0x02, 0x05, 0x08, 0x19, 0x01, 0x29, 0x08, 0x91, 0x02,
+        0xc0,
+    ];
+
+    fn uhid_event(type_: u32) -> Vec<u8> {
+        let mut ev = vec![0; UHID_EVENT_SIZE];
+        ev[..4].copy_from_slice(&type_.to_ne_bytes());
+        ev
+    }
+
+    fn find_evdev(name: &str) -> Option<String> {
+        for entry in fs::read_dir("/sys/class/input").ok()? {
+            let entry = entry.ok()?;
+            let node = entry.file_name().to_string_lossy().into_owned();
+            if !node.starts_with("event") {
+                continue;
+            }
+            let dev_name = fs::read_to_string(entry.path().join("device/name")).unwrap_or_default();
+            if dev_name.trim_end() == name {
+                return Some(format!("/dev/input/{}", node));
+            }
+        }
+
+        None
+    }
+
+    // Creates virtual HID device with uhid, which requires write access to /dev/uhid. Test is
+    // skipped if it's not available.
+    #[test]
+    fn uhid_loopback() {
+        let mut uhid = match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/uhid")
+        {
+            Ok(uhid) => uhid,
+            Err(e) => {
+                eprintln!("Skipping uhid_loopback, can't open /dev/uhid: {}", e);
+                return;
+            }
+        };
+
+        let name = format!("gilrs raw-hid test {}", std::process::id());
+        let mut create = uhid_event(UHID_CREATE2);
+        create[4..4 + name.len()].copy_from_slice(name.as_bytes());
+        create[260..262].copy_from_slice(&(REPORT_DESCRIPTOR.len() as u16).to_ne_bytes());
+        create[262..264].copy_from_slice(&BUS_USB.to_ne_bytes());
+        create[264..268].copy_from_slice(&0x1234u32.to_ne_bytes());
+        create[268..272].copy_from_slice(&0x5678u32.to_ne_bytes());
+        create[280..280 + REPORT_DESCRIPTOR.len()].copy_from_slice(&REPORT_DESCRIPTOR);
+        uhid.write_all(&create).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let devpath = loop {
+            if let Some(devpath) = find_evdev(&name) {
+                break devpath;
+            }
+            if Instant::now() > deadline {
+                eprintln!("Skipping uhid_loopback, evdev device was not created");
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        let mut reports = RawReports::open(&devpath).unwrap();
+        let mut buf = [0; 64];
+        assert_eq!(reports.next_report(&mut buf), None);
+
+        let mut input = uhid_event(UHID_INPUT2);
+        input[4..6].copy_from_slice(&1u16.to_ne_bytes());
+        input[6] = 0x05;
+        uhid.write_all(&input).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let len = loop {
+            if let Some(len) = reports.next_report(&mut buf) {
+                break len;
+            }
+            assert!(Instant::now() < deadline, "input report was not received");
+            thread::sleep(Duration::from_millis(1));
+        };
+        assert_eq!(&buf[..len], &[0x05]);
+
+        // Report ID 0, LEDs 1 and 3
+        assert_eq!(reports.write_report(&[0x00, 0x05]).unwrap(), 2);
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let mut ev = vec![0; UHID_EVENT_SIZE];
+        loop {
+            if uhid.read(&mut ev).is_ok() && ev[..4] == UHID_OUTPUT.to_ne_bytes() {
+                break;
+            }
+            assert!(Instant::now() < deadline, "output report was not received");
+            thread::sleep(Duration::from_millis(1));
+        }
+        let size = u16::from_ne_bytes([ev[4100], ev[4101]]) as usize;
+        assert_eq!(ev[4 + size - 1], 0x05);
+
+        uhid.write_all(&uhid_event(UHID_DESTROY)).unwrap();
+    }
+}
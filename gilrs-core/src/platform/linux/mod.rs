@@ -7,10 +7,20 @@
 // Copyright 2016 GilRs Developers
 mod ff;
 mod gamepad;
+#[cfg(feature = "raw-hid")]
+mod hidraw;
+// Recent libc reexports C types from `core::ffi`, which is newer than MSRV, and ioctl macros of
+// nix use them. Older libc, which has to be used with MSRV, defines the same types itself.
+#[allow(clippy::incompatible_msrv)]
 mod ioctl;
+mod touchpad;
 mod udev;
 
 pub use self::ff::Device as FfDevice;
 pub use self::gamepad::{native_ev_codes, EvCode, Gamepad, Gilrs};
+#[cfg(feature = "raw-hid")]
+pub use self::hidraw::RawReports;
 
 pub const IS_Y_AXIS_REVERSED: bool = true;
+pub const HAS_KERNEL_TIMESTAMPS: bool = true;
+pub const SUPPORTS_MULTIPLE_INSTANCES: bool = true;
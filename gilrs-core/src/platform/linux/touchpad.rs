@@ -0,0 +1,329 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Touchpads of gamepads like DualShock 4 and DualSense. Kernel exposes them as separate evdev
+//! devices with the same `uniq` (usually MAC address) as the gamepad.
+
+use super::ioctl::{self, input_absinfo, input_event};
+use super::udev::Device;
+use crate::EventType;
+
+use libc as c;
+
+use std::collections::VecDeque;
+use std::ffi::{CStr, CString, OsStr};
+use std::fs;
+use std::mem::{self, MaybeUninit};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Touchpad that was found, but its gamepad is not connected yet.
+#[derive(Debug)]
+pub(crate) struct PendingTouchpad {
+    pub devnode: CString,
+    pub uniq: String,
+}
+
+impl PendingTouchpad {
+    /// Returns `None` if device is not touchpad that can belong to a gamepad.
+    pub fn new(dev: &Device) -> Option<Self> {
+        let devnode = dev.devnode()?;
+
+        // Touchpads also have mouse interface.
+        if !devnode.to_bytes().starts_with(b"/dev/input/event") {
+            return None;
+        }
+
+        Some(PendingTouchpad {
+            devnode: devnode.to_owned(),
+            uniq: uniq(dev)?,
+        })
+    }
+}
+
+/// Reads `uniq` of input device from sysfs. Returns `None` if it's empty.
+pub(crate) fn uniq(dev: &Device) -> Option<String> {
+    let syspath = Path::new(OsStr::from_bytes(dev.syspath().to_bytes()));
+    // "device" is symlink to inputXX.
+    let uniq = fs::read_to_string(syspath.join("device/uniq")).ok()?;
+    let uniq = uniq.trim();
+
+    if uniq.is_empty() {
+        None
+    } else {
+        Some(uniq.to_owned())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Touchpad {
+    fd: i32,
+    devnode: CString,
+    state: TouchpadState,
+    events: VecDeque<(EventType, SystemTime)>,
+    skip: bool,
+}
+
+impl Touchpad {
+    pub fn open(pending: &PendingTouchpad) -> Option<Self> {
+        let fd = unsafe { c::open(pending.devnode.as_ptr(), c::O_RDONLY | c::O_NONBLOCK) };
+        if fd < 0 {
+            error!("Failed to open touchpad {:?}", pending.devnode);
+            return None;
+        }
+
+        let absinfo = |code: u16| unsafe {
+            let mut info = input_absinfo::default();
+            ioctl::eviocgabs(fd, u32::from(code), &mut info);
+            info
+        };
+        let state = TouchpadState::new(
+            absinfo(ABS_MT_POSITION_X),
+            absinfo(ABS_MT_POSITION_Y),
+            absinfo(ABS_MT_SLOT).maximum,
+        );
+
+        debug!("Touchpad {:?}: {:?}", pending.devnode, state);
+
+        Some(Touchpad {
+            fd,
+            devnode: pending.devnode.clone(),
+            state,
+            events: VecDeque::new(),
+            skip: false,
+        })
+    }
+
+    pub fn devnode(&self) -> &CStr {
+        &self.devnode
+    }
+
+    pub fn fd(&self) -> i32 {
+        self.fd
+    }
+
+    pub fn event(&mut self) -> Option<(EventType, SystemTime)> {
+        while self.events.is_empty() {
+            let mut event_buf: [MaybeUninit<input_event>; 12] =
+                unsafe { MaybeUninit::uninit().assume_init() };
+            let size = mem::size_of::<input_event>();
+            let n = unsafe {
+                c::read(
+                    self.fd,
+                    event_buf.as_mut_ptr() as *mut c::c_void,
+                    size * event_buf.len(),
+                )
+            };
+
+            if n <= 0 {
+                // Nothing to read (non-blocking IO)
+                return None;
+            } else if n % size as isize != 0 {
+                error!("Unexpected read of size {}", n);
+                return None;
+            }
+
+            for ev in event_buf[..n as usize / size].iter() {
+                let ev = unsafe { ev.assume_init() };
+
+                // Events are lost after SYN_DROPPED, ignore everything up to next report.
+                if self.skip {
+                    self.skip = !(ev.type_ == EV_SYN && ev.code == SYN_REPORT);
+                } else if ev.type_ == EV_SYN && ev.code == SYN_DROPPED {
+                    self.skip = true;
+                } else {
+                    self.state.process(&ev, &mut self.events);
+                }
+            }
+        }
+
+        self.events.pop_front()
+    }
+}
+
+impl Drop for Touchpad {
+    fn drop(&mut self) {
+        unsafe {
+            c::close(self.fd);
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Slot {
+    x: i32,
+    y: i32,
+    is_touching: bool,
+    changed: bool,
+}
+
+/// Multi-touch state, see "Protocol Example B" in kernel's multi-touch-protocol.rst.
+#[derive(Debug)]
+struct TouchpadState {
+    x: (i32, i32),
+    y: (i32, i32),
+    slot: usize,
+    slots: Vec<Slot>,
+}
+
+impl TouchpadState {
+    fn new(x: input_absinfo, y: input_absinfo, max_slot: i32) -> Self {
+        TouchpadState {
+            x: (x.minimum, x.maximum),
+            y: (y.minimum, y.maximum),
+            slot: 0,
+            slots: vec![Slot::default(); max_slot.max(0) as usize + 1],
+        }
+    }
+
+    fn process(&mut self, ev: &input_event, out: &mut VecDeque<(EventType, SystemTime)>) {
+        match (ev.type_, ev.code) {
+            (EV_ABS, ABS_MT_SLOT) => self.slot = ev.value.max(0) as usize,
+            (EV_ABS, code) => {
+                // Kernel should never select slot out of range, but don't trust it.
+                let slot = match self.slots.get_mut(self.slot) {
+                    Some(slot) => slot,
+                    None => return,
+                };
+
+                match code {
+                    ABS_MT_TRACKING_ID => slot.is_touching = ev.value >= 0,
+                    ABS_MT_POSITION_X => slot.x = ev.value,
+                    ABS_MT_POSITION_Y => slot.y = ev.value,
+                    _ => return,
+                }
+                slot.changed = true;
+            }
+            (EV_SYN, SYN_REPORT) => {
+                let time = UNIX_EPOCH
+                    + Duration::new(ev.time.tv_sec as u64, ev.time.tv_usec as u32 * 1000);
+
+                for (finger, slot) in self.slots.iter_mut().enumerate() {
+                    if !slot.changed {
+                        continue;
+                    }
+
+                    slot.changed = false;
+                    let ev = EventType::TouchpadTouch {
+                        finger: finger as u8,
+                        x: normalize(slot.x, self.x),
+                        y: normalize(slot.y, self.y),
+                        pressed: slot.is_touching,
+                    };
+                    out.push_back((ev, time));
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Maps `val` from `(min, max)` range to [0.0, 1.0].
+fn normalize(val: i32, (min, max): (i32, i32)) -> f32 {
+    if max <= min || val <= min {
+        0.0
+    } else if val >= max {
+        1.0
+    } else {
+        ((f64::from(val) - f64::from(min)) / (f64::from(max) - f64::from(min))) as f32
+    }
+}
+
+const EV_SYN: u16 = 0x00;
+const EV_ABS: u16 = 0x03;
+const SYN_REPORT: u16 = 0x00;
+const SYN_DROPPED: u16 = 0x03;
+const ABS_MT_SLOT: u16 = 0x2f;
+const ABS_MT_POSITION_X: u16 = 0x35;
+const ABS_MT_POSITION_Y: u16 = 0x36;
+const ABS_MT_TRACKING_ID: u16 = 0x39;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ev(type_: u16, code: u16, value: i32) -> input_event {
+        input_event {
+            type_,
+            code,
+            value,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn multi_touch() {
+        let info = |maximum| input_absinfo {
+            maximum,
+            ..Default::default()
+        };
+        let mut state = TouchpadState::new(info(2000), info(1000), 1);
+        let mut out = VecDeque::new();
+        let mut process = |events: &[input_event]| {
+            for e in events {
+                state.process(e, &mut out);
+            }
+            out.drain(..).map(|(ev, _)| ev).collect::<Vec<_>>()
+        };
+        let touch = |finger, x, y, pressed| EventType::TouchpadTouch {
+            finger,
+            x,
+            y,
+            pressed,
+        };
+
+        assert_eq!(
+            process(&[
+                ev(EV_ABS, ABS_MT_SLOT, 0),
+                ev(EV_ABS, ABS_MT_TRACKING_ID, 10),
+                ev(EV_ABS, ABS_MT_POSITION_X, 0),
+                ev(EV_ABS, ABS_MT_POSITION_Y, 1000),
+                ev(EV_SYN, SYN_REPORT, 0),
+            ]),
+            &[touch(0, 0.0, 1.0, true)]
+        );
+        // First finger moves and second one touches in the same report.
+        assert_eq!(
+            process(&[
+                ev(EV_ABS, ABS_MT_POSITION_X, 2000),
+                ev(EV_ABS, ABS_MT_SLOT, 1),
+                ev(EV_ABS, ABS_MT_TRACKING_ID, 11),
+                ev(EV_ABS, ABS_MT_POSITION_X, 1000),
+                ev(EV_ABS, ABS_MT_POSITION_Y, 0),
+                ev(EV_SYN, SYN_REPORT, 0),
+            ]),
+            &[touch(0, 1.0, 1.0, true), touch(1, 0.5, 0.0, true)]
+        );
+        // Second finger is lifted, slot stays selected.
+        assert_eq!(
+            process(&[
+                ev(EV_ABS, ABS_MT_TRACKING_ID, -1),
+                ev(EV_SYN, SYN_REPORT, 0)
+            ]),
+            &[touch(1, 0.5, 0.0, false)]
+        );
+        // Slot out of range and unrelated axes are ignored.
+        assert!(process(&[
+            ev(EV_ABS, ABS_MT_SLOT, 5),
+            ev(EV_ABS, ABS_MT_POSITION_X, 5),
+            ev(EV_ABS, ABS_MT_SLOT, 0),
+            ev(EV_ABS, 0x00, 5),
+            ev(EV_SYN, SYN_REPORT, 0),
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn normalization() {
+        assert_eq!(normalize(-5, (0, 100)), 0.0);
+        assert_eq!(normalize(50, (0, 100)), 0.5);
+        assert_eq!(normalize(150, (0, 100)), 1.0);
+        assert_eq!(normalize(0, (-100, 100)), 0.5);
+        assert_eq!(normalize(10, (0, 0)), 0.0);
+    }
+}
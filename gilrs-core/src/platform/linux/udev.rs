@@ -8,7 +8,7 @@
 use libc as c;
 use libudev_sys as ud;
 use std::ffi::{CStr, CString};
-use std::os::raw::c_char;
+use std::os::raw::{c_char, c_int};
 use std::ptr;
 
 #[derive(Debug)]
@@ -98,6 +98,7 @@ impl Iterator for DeviceIterator {
     }
 }
 
+#[derive(Debug)]
 pub struct Device(*mut ud::udev_device);
 
 impl Device {
@@ -236,6 +237,15 @@ impl Monitor {
     pub fn device(&self) -> Device {
         Device(unsafe { ud::udev_monitor_receive_device(self.0) })
     }
+
+    pub fn fd(&self) -> Option<c_int> {
+        let fd = unsafe { ud::udev_monitor_get_fd(self.0) };
+        if fd < 0 {
+            None
+        } else {
+            Some(fd)
+        }
+    }
 }
 
 impl Drop for Monitor {
@@ -12,6 +12,17 @@ use std::time::Duration;
 pub struct Device;
 
 impl Device {
+    /// Returns number of effects that can be played at the same time. Force feedback is not
+    /// implemented on macOS yet, so this is conservative default.
+    pub fn query_max_effects(&self) -> usize {
+        4
+    }
+
+    /// Device doesn't hold any OS resource, so it never becomes invalid.
+    pub fn is_connected(&self) -> bool {
+        true
+    }
+
     /// Sets magnitude for strong and weak ff motors.
     pub fn set_ff_state(&mut self, _strong: u16, _weak: u16, _min_duration: Duration) {}
 }
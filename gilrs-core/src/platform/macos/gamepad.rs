@@ -7,7 +7,10 @@
 
 use super::io_kit::*;
 use super::FfDevice;
-use crate::{AxisInfo, Event, EventType, PlatformError, PowerInfo};
+use crate::{
+    AxisInfo, Event, EventType, LedError, PlatformError, PlatformErrorKind, PollingRateError,
+    PowerInfo, PowerOffError,
+};
 use uuid::Uuid;
 
 use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoop};
@@ -19,6 +22,7 @@ use io_kit_sys::hid::usage_tables::{
 use io_kit_sys::ret::IOReturn;
 use vec_map::VecMap;
 
+use std::error;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::os::raw::c_void;
 use std::sync::mpsc::{self, Receiver, Sender};
@@ -30,20 +34,33 @@ pub struct Gilrs {
     gamepads: Vec<Gamepad>,
     device_infos: Arc<Mutex<Vec<DeviceInfo>>>,
     rx: Receiver<(Event, Option<IOHIDDevice>)>,
+    // Used by `rescan_gamepads()` to send events the same way as callbacks.
+    tx: Sender<(Event, Option<IOHIDDevice>)>,
+    // Report interval set by `set_polling_rate()`, applied to newly connected devices.
+    report_interval: Option<u32>,
+    // Lists devices for `rescan_gamepads()` and `set_polling_rate()`. Manager of event thread
+    // can't be shared, because it's scheduled on that thread's run loop.
+    manager: IOHIDManager,
 }
 
 impl Gilrs {
     pub(crate) fn new() -> Result<Self, PlatformError> {
         let gamepads = Vec::new();
         let device_infos = Arc::new(Mutex::new(Vec::new()));
+        // Also checks that HID manager can be opened, event thread can only log its errors.
+        let manager = IOHIDManager::new()
+            .map_err(|kind| PlatformError::Other(Box::new(Error::from(kind))))?;
 
         let (tx, rx) = mpsc::channel();
-        Self::spawn_thread(tx, device_infos.clone());
+        Self::spawn_thread(tx.clone(), device_infos.clone());
 
         Ok(Gilrs {
             gamepads,
             device_infos,
             rx,
+            tx,
+            report_interval: None,
+            manager,
         })
     }
 
@@ -53,9 +70,9 @@ impl Gilrs {
     ) {
         thread::spawn(move || unsafe {
             let mut manager = match IOHIDManager::new() {
-                Some(manager) => manager,
-                None => {
-                    error!("Failed to create IOHIDManager object");
+                Ok(manager) => manager,
+                Err(kind) => {
+                    error!("{}", Error::from(kind));
                     return;
                 }
             };
@@ -81,6 +98,12 @@ impl Gilrs {
         match self.rx.try_recv().ok() {
             Some((event, Some(device))) => {
                 if event.event == EventType::Connected {
+                    if let Some(interval) = self.report_interval {
+                        if !device.set_report_interval(interval) {
+                            warn!("Failed to set report interval of device {}", event.id);
+                        }
+                    }
+
                     if self.gamepads.get(event.id).is_some() {
                         self.gamepads[event.id].is_connected = true;
                     } else {
@@ -126,20 +149,107 @@ impl Gilrs {
         self.gamepads.get(id)
     }
 
+    pub(crate) fn set_polling_rate(&mut self, hz: u32) -> Result<(), PollingRateError> {
+        let devices = self.manager.get_devices();
+
+        let interval = (1_000_000 / hz).max(1);
+        self.report_interval = Some(interval);
+
+        for device in devices {
+            if !device.set_report_interval(interval) {
+                warn!("Failed to set report interval of device");
+                continue;
+            }
+
+            let entry_id = device
+                .get_service()
+                .and_then(|service| service.get_registry_entry_id());
+            for gamepad in self.gamepads.iter_mut() {
+                if Some(gamepad.entry_id) == entry_id {
+                    gamepad.report_rate = Some(1_000_000 / interval);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Returns index greater than index of last connected gamepad.
     pub fn last_gamepad_hint(&self) -> usize {
         self.gamepads.len()
     }
+
+    pub(crate) fn rescan_gamepads(&mut self) -> usize {
+        let devices = self.manager.get_devices();
+
+        let mut device_infos = self.device_infos.lock().unwrap();
+        let mut entry_ids = Vec::new();
+        let mut changes = 0;
+
+        for device in devices {
+            let entry_id = match device
+                .get_service()
+                .and_then(|service| service.get_registry_entry_id())
+            {
+                Some(entry_id) => entry_id,
+                None => {
+                    error!("Failed to get entry id of device");
+                    continue;
+                }
+            };
+            entry_ids.push(entry_id);
+
+            if device_infos
+                .iter()
+                .any(|info| info.entry_id == entry_id && info.is_connected)
+            {
+                continue;
+            }
+
+            let location_id = match device.get_location_id() {
+                Some(location_id) => location_id,
+                None => {
+                    error!("Failed to get location id of device");
+                    continue;
+                }
+            };
+
+            device_infos.push(DeviceInfo {
+                entry_id,
+                location_id,
+                is_connected: true,
+            });
+            let event = Event::new(device_infos.len() - 1, EventType::Connected);
+            let _ = self.tx.send((event, Some(device)));
+            changes += 1;
+        }
+
+        for (id, info) in device_infos.iter_mut().enumerate() {
+            if info.is_connected && !entry_ids.contains(&info.entry_id) {
+                info.is_connected = false;
+                let _ = self
+                    .tx
+                    .send((Event::new(id, EventType::Disconnected), None));
+                changes += 1;
+            }
+        }
+
+        changes
+    }
 }
 
 #[derive(Debug)]
 pub struct Gamepad {
     name: String,
+    manufacturer: Option<String>,
+    product: Option<String>,
     uuid: Uuid,
     entry_id: u64,
     location_id: u32,
     page: u32,
     usage: u32,
+    report_rate: Option<u32>,
+    os_path: Option<String>,
     axes_info: VecMap<AxisInfo>,
     axes: Vec<EvCode>,
     buttons: Vec<EvCode>,
@@ -164,6 +274,8 @@ impl Gamepad {
             }
         };
 
+        let os_path = io_service.get_registry_entry_path();
+
         let location_id = match device.get_location_id() {
             Some(location_id) => location_id,
             None => {
@@ -215,13 +327,22 @@ impl Gamepad {
             None => Uuid::nil(),
         };
 
+        let report_rate = device
+            .get_report_interval()
+            .map(|interval| 1_000_000 / interval)
+            .filter(|&rate| rate > 0);
+
         let mut gamepad = Gamepad {
             name,
+            manufacturer: device.get_manufacturer(),
+            product: device.get_name(),
             uuid,
             entry_id,
             location_id,
             page,
             usage,
+            report_rate,
+            os_path,
             axes_info: VecMap::with_capacity(8),
             axes: Vec::with_capacity(8),
             buttons: Vec::with_capacity(16),
@@ -291,6 +412,14 @@ impl Gamepad {
         &self.name
     }
 
+    pub fn manufacturer_string(&self) -> Option<&str> {
+        self.manufacturer.as_deref()
+    }
+
+    pub fn product_string(&self) -> Option<&str> {
+        self.product.as_deref()
+    }
+
     pub fn uuid(&self) -> Uuid {
         self.uuid
     }
@@ -303,6 +432,52 @@ impl Gamepad {
         false
     }
 
+    pub fn power_off(&self) -> Result<(), PowerOffError> {
+        Err(PowerOffError::NotSupported)
+    }
+
+    // IOKit doesn't expose player LEDs, but setting them isn't considered an error here.
+    pub fn set_player_indicator(&self, _player: Option<u8>) -> Result<(), LedError> {
+        Ok(())
+    }
+
+    pub fn input_report_rate(&self) -> Option<u32> {
+        self.report_rate
+    }
+
+    pub fn has_touchpad(&self) -> bool {
+        false
+    }
+
+    pub fn current_state(&self) -> Option<crate::RawState> {
+        None
+    }
+
+    pub fn os_path(&self) -> Option<&str> {
+        self.os_path.as_ref().map(|s| s.as_str())
+    }
+
+    pub fn suggested_player_index(&self) -> Option<u8> {
+        None
+    }
+
+    pub fn port_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<&str> {
+        None
+    }
+
+    #[cfg(feature = "raw-hid")]
+    pub fn raw_reports(&self) -> std::io::Result<super::RawReports> {
+        if !self.is_connected {
+            return Err(std::io::ErrorKind::NotConnected.into());
+        }
+
+        super::RawReports::open(self.entry_id)
+    }
+
     /// Creates Ffdevice corresponding to this gamepad.
     pub fn ff_device(&self) -> Option<FfDevice> {
         Some(FfDevice)
@@ -402,6 +577,44 @@ struct DeviceInfo {
     location_id: u32,
     is_connected: bool,
 }
+
+// IOHIDManagerCreate() returns NULL without reason, so only opening manager carries OS error code.
+#[derive(Debug)]
+enum Error {
+    ManagerCreate,
+    ManagerOpen(PlatformErrorKind),
+}
+
+impl From<Option<PlatformErrorKind>> for Error {
+    fn from(kind: Option<PlatformErrorKind>) -> Self {
+        match kind {
+            Some(kind) => Error::ManagerOpen(kind),
+            None => Error::ManagerCreate,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match *self {
+            Error::ManagerCreate => f.write_str("Failed to create IOHIDManager object"),
+            Error::ManagerOpen(kind) => write!(
+                f,
+                "Failed to open IOHIDManager object (os error {})",
+                kind.code()
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::ManagerCreate => None,
+            Error::ManagerOpen(kind) => Some(kind),
+        }
+    }
+}
 #[cfg(feature = "serde-serialize")]
 use serde::{Deserialize, Serialize};
 
@@ -417,9 +630,44 @@ impl EvCode {
         EvCode { page, usage }
     }
 
+    pub fn from_raw(kind: u32, number: u32) -> Option<Self> {
+        Some(EvCode {
+            page: kind,
+            usage: number,
+        })
+    }
+
+    /// HID usage pages are 16-bit, so synthetic codes use page outside of their range.
+    pub fn synthetic(index: u8) -> Self {
+        EvCode {
+            page: 0x1_0000,
+            usage: u32::from(index),
+        }
+    }
+
     pub fn into_u32(self) -> u32 {
         self.page << 16 | self.usage
     }
+
+    pub fn debug_info(self) -> crate::EvCodeInfo {
+        let name = match (self.page, self.usage) {
+            (PAGE_GENERIC_DESKTOP, USAGE_AXIS_LSTICKX) => Some("GD_X"),
+            (PAGE_GENERIC_DESKTOP, USAGE_AXIS_LSTICKY) => Some("GD_Y"),
+            (PAGE_GENERIC_DESKTOP, USAGE_AXIS_RT2) => Some("GD_Z"),
+            (PAGE_GENERIC_DESKTOP, USAGE_AXIS_RSTICKX) => Some("GD_RX"),
+            (PAGE_GENERIC_DESKTOP, USAGE_AXIS_RSTICKY) => Some("GD_RY"),
+            (PAGE_GENERIC_DESKTOP, USAGE_AXIS_LT2) => Some("GD_RZ"),
+            (PAGE_GENERIC_DESKTOP, USAGE_AXIS_DPADX) => Some("GD_HATSWITCH"),
+            _ => None,
+        };
+
+        crate::EvCodeInfo {
+            platform: "macOS",
+            kind: self.page,
+            number: self.usage,
+            name,
+        }
+    }
 }
 
 impl From<IOHIDElement> for crate::EvCode {
@@ -37,6 +37,8 @@ use io_kit_sys::ret::{kIOReturnSuccess, IOReturn};
 use io_kit_sys::types::{io_service_t, IO_OBJECT_NULL};
 use io_kit_sys::{IOObjectRelease, IOObjectRetain, IORegistryEntryGetRegistryEntryID};
 
+use crate::PlatformErrorKind;
+
 use std::ffi::CStr;
 use std::os::raw::{c_char, c_void};
 use std::ptr;
@@ -47,6 +49,10 @@ pub struct IOHIDManager(IOHIDManagerRef);
 
 pub type CFMutableArrayRef = *mut __CFArray;
 
+const kIOHIDReportIntervalKey: *const c_char =
+    b"ReportInterval\0" as *const [u8; 15] as *const c_char;
+const kIOServicePlane: *const c_char = b"IOService\0" as *const [u8; 10] as *const c_char;
+
 extern "C" {
     pub fn CFArrayCreateMutable(
         allocator: CFAllocatorRef,
@@ -54,16 +60,24 @@ extern "C" {
         callBacks: *const CFArrayCallBacks,
     ) -> CFMutableArrayRef;
     pub fn CFArrayAppendValue(theArray: CFMutableArrayRef, value: *const c_void);
+    // `path` must point to `io_string_t` (`[c_char; 512]`).
+    pub fn IORegistryEntryGetPath(
+        entry: io_service_t,
+        plane: *const c_char,
+        path: *mut c_char,
+    ) -> IOReturn;
 }
 
 impl_TCFType!(IOHIDManager, IOHIDManagerRef, IOHIDManagerGetTypeID);
 
 impl IOHIDManager {
-    pub fn new() -> Option<Self> {
+    /// Creates manager that matches gamepads and opens it. Returns `Err(None)` if manager
+    /// couldn't be created, `IOHIDManagerCreate()` doesn't report why.
+    pub fn new() -> Result<Self, Option<PlatformErrorKind>> {
         let manager = unsafe { IOHIDManagerCreate(kCFAllocatorDefault, kIOHIDOptionsTypeNone) };
 
         if manager.is_null() {
-            return None;
+            return Err(None);
         }
 
         let matchers = CFArray::from_CFTypes(&[
@@ -78,10 +92,10 @@ impl IOHIDManager {
         let ret = unsafe { IOHIDManagerOpen(manager, kIOHIDOptionsTypeNone) };
 
         if ret == kIOReturnSuccess {
-            Some(IOHIDManager(manager))
+            Ok(IOHIDManager(manager))
         } else {
             unsafe { CFRelease(manager as _) };
-            None
+            Err(Some(PlatformErrorKind::new("IOHIDManagerOpen", ret)))
         }
     }
 
@@ -168,6 +182,9 @@ impl IOHIDManager {
     }
 }
 
+// Manager can be moved to other thread as long as it's not scheduled on run loop of current one.
+unsafe impl Send for IOHIDManager {}
+
 impl Drop for IOHIDManager {
     fn drop(&mut self) {
         unsafe { CFRelease(self.as_CFTypeRef()) }
@@ -196,6 +213,11 @@ impl IOHIDDevice {
         }
     }
 
+    pub fn get_manufacturer(&self) -> Option<String> {
+        self.get_string_property(kIOHIDManufacturerKey)
+            .map(|manufacturer| manufacturer.to_string())
+    }
+
     pub fn get_location_id(&self) -> Option<u32> {
         match self.get_number_property(kIOHIDLocationIDKey) {
             Some(location_id) => match location_id.to_i32() {
@@ -278,6 +300,34 @@ impl IOHIDDevice {
         }
     }
 
+    /// Returns interval between input reports in microseconds.
+    pub fn get_report_interval(&self) -> Option<u32> {
+        match self.get_number_property(kIOHIDReportIntervalKey) {
+            Some(interval) => match interval.to_i32() {
+                Some(interval) if interval > 0 => Some(interval as u32),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    /// Sets interval between input reports in microseconds. Returns `false` if device rejected
+    /// it.
+    pub fn set_report_interval(&self, interval: u32) -> bool {
+        let key = unsafe {
+            CFStringCreateWithCString(
+                kCFAllocatorDefault,
+                kIOHIDReportIntervalKey,
+                kCFStringEncodingUTF8,
+            )
+        };
+        let value = CFNumber::from(interval as i32);
+        let ret = unsafe { IOHIDDeviceSetProperty(self.0, key, value.as_CFTypeRef()) };
+        unsafe { CFRelease(key as _) };
+
+        ret != 0
+    }
+
     pub fn get_service(&self) -> Option<IOService> {
         unsafe { IOService::new(IOHIDDeviceGetService(self.0)) }
     }
@@ -538,6 +588,23 @@ impl IOService {
             }
         }
     }
+
+    pub fn get_registry_entry_path(&self) -> Option<String> {
+        unsafe {
+            IOObjectRetain(self.0);
+
+            let mut path = [0 as c_char; 512];
+            let result = IORegistryEntryGetPath(self.0, kIOServicePlane, path.as_mut_ptr());
+
+            IOObjectRelease(self.0);
+
+            if result == kIOReturnSuccess {
+                Some(CStr::from_ptr(path.as_ptr()).to_string_lossy().into_owned())
+            } else {
+                None
+            }
+        }
+    }
 }
 
 impl Drop for IOService {
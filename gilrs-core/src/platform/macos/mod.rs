@@ -7,9 +7,15 @@
 mod ff;
 mod gamepad;
 mod io_kit;
+#[cfg(feature = "raw-hid")]
+mod raw_hid;
 
 pub use self::ff::Device as FfDevice;
 pub use self::gamepad::{native_ev_codes, EvCode, Gamepad, Gilrs};
+#[cfg(feature = "raw-hid")]
+pub use self::raw_hid::RawReports;
 
 // True, if Y axis of sticks points downwards.
 pub const IS_Y_AXIS_REVERSED: bool = true;
+pub const HAS_KERNEL_TIMESTAMPS: bool = false;
+pub const SUPPORTS_MULTIPLE_INSTANCES: bool = true;
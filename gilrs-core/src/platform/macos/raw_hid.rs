@@ -0,0 +1,245 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+#![allow(non_upper_case_globals)]
+#![allow(non_snake_case)]
+
+//! Access to raw HID reports with separate, non-exclusively opened `IOHIDDevice`.
+
+use core_foundation::base::{kCFAllocatorDefault, CFAllocatorRef, CFIndex, CFRelease};
+use core_foundation::dictionary::{CFDictionaryRef, CFMutableDictionaryRef};
+use core_foundation::runloop::{
+    kCFRunLoopDefaultMode, CFRunLoopGetCurrent, CFRunLoopRef, CFRunLoopRunInMode,
+};
+use core_foundation::string::CFStringRef;
+use io_kit_sys::hid::base::IOHIDDeviceRef;
+use io_kit_sys::ret::{kIOReturnSuccess, IOReturn};
+use io_kit_sys::types::{io_service_t, IO_OBJECT_NULL};
+use io_kit_sys::IOObjectRelease;
+
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind};
+use std::os::raw::c_void;
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+const kIOMasterPortDefault: u32 = 0;
+const kIOHIDOptionsTypeNone: u32 = 0;
+const kIOHIDReportTypeOutput: u32 = 1;
+const kIOReturnNotPermitted: IOReturn = 0xe000_02e2_u32 as IOReturn;
+
+const MAX_REPORT_SIZE: usize = 4096;
+// Oldest reports are dropped if application doesn't read them.
+const MAX_QUEUED_REPORTS: usize = 256;
+
+type IOHIDReportCallback = extern "C" fn(
+    context: *mut c_void,
+    result: IOReturn,
+    sender: *mut c_void,
+    report_type: u32,
+    report_id: u32,
+    report: *mut u8,
+    report_length: CFIndex,
+);
+
+extern "C" {
+    fn IORegistryEntryIDMatching(entryID: u64) -> CFMutableDictionaryRef;
+    fn IOServiceGetMatchingService(masterPort: u32, matching: CFDictionaryRef) -> io_service_t;
+    fn IOHIDDeviceCreate(allocator: CFAllocatorRef, service: io_service_t) -> IOHIDDeviceRef;
+    fn IOHIDDeviceOpen(device: IOHIDDeviceRef, options: u32) -> IOReturn;
+    fn IOHIDDeviceClose(device: IOHIDDeviceRef, options: u32) -> IOReturn;
+    fn IOHIDDeviceRegisterInputReportCallback(
+        device: IOHIDDeviceRef,
+        report: *mut u8,
+        reportLength: CFIndex,
+        callback: IOHIDReportCallback,
+        context: *mut c_void,
+    );
+    fn IOHIDDeviceScheduleWithRunLoop(
+        device: IOHIDDeviceRef,
+        runLoop: CFRunLoopRef,
+        runLoopMode: CFStringRef,
+    );
+    fn IOHIDDeviceUnscheduleFromRunLoop(
+        device: IOHIDDeviceRef,
+        runLoop: CFRunLoopRef,
+        runLoopMode: CFStringRef,
+    );
+    fn IOHIDDeviceSetReport(
+        device: IOHIDDeviceRef,
+        reportType: u32,
+        reportID: CFIndex,
+        report: *const u8,
+        reportLength: CFIndex,
+    ) -> IOReturn;
+}
+
+type Queue = Arc<Mutex<VecDeque<Vec<u8>>>>;
+
+struct Context {
+    queue: Queue,
+    buf: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct RawReports {
+    device: IOHIDDeviceRef,
+    queue: Queue,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+// IOHIDDevice functions used outside of run loop thread are thread safe.
+unsafe impl Send for RawReports {}
+
+impl RawReports {
+    pub fn open(entry_id: u64) -> io::Result<Self> {
+        let device = unsafe {
+            let service = IOServiceGetMatchingService(
+                kIOMasterPortDefault,
+                IORegistryEntryIDMatching(entry_id) as CFDictionaryRef,
+            );
+            if service == IO_OBJECT_NULL {
+                return Err(io::Error::new(ErrorKind::NotFound, "device not found"));
+            }
+
+            let device = IOHIDDeviceCreate(kCFAllocatorDefault, service);
+            IOObjectRelease(service);
+            device
+        };
+
+        if device.is_null() {
+            return Err(io::Error::new(
+                ErrorKind::Other,
+                "failed to create IOHIDDevice",
+            ));
+        }
+
+        let ret = unsafe { IOHIDDeviceOpen(device, kIOHIDOptionsTypeNone) };
+        if ret != kIOReturnSuccess {
+            unsafe { CFRelease(device as _) };
+            return Err(io_error(ret));
+        }
+
+        let queue = Arc::new(Mutex::new(VecDeque::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let queue = queue.clone();
+            let stop = stop.clone();
+            // Raw pointers are not `Send`.
+            let device = device as usize;
+
+            thread::spawn(move || unsafe {
+                let device = device as IOHIDDeviceRef;
+                let mut context = Box::new(Context {
+                    queue,
+                    buf: vec![0; MAX_REPORT_SIZE],
+                });
+                let buf = context.buf.as_mut_ptr();
+                IOHIDDeviceRegisterInputReportCallback(
+                    device,
+                    buf,
+                    MAX_REPORT_SIZE as CFIndex,
+                    input_report_cb,
+                    &mut *context as *mut Context as *mut c_void,
+                );
+
+                let run_loop = CFRunLoopGetCurrent();
+                IOHIDDeviceScheduleWithRunLoop(device, run_loop, kCFRunLoopDefaultMode);
+                while !stop.load(Ordering::Relaxed) {
+                    CFRunLoopRunInMode(kCFRunLoopDefaultMode, 0.1, 0);
+                }
+                IOHIDDeviceUnscheduleFromRunLoop(device, run_loop, kCFRunLoopDefaultMode);
+            })
+        };
+
+        Ok(RawReports {
+            device,
+            queue,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn next_report(&mut self, buf: &mut [u8]) -> Option<usize> {
+        let report = self.queue.lock().unwrap().pop_front()?;
+        let len = report.len().min(buf.len());
+        buf[..len].copy_from_slice(&report[..len]);
+
+        Some(len)
+    }
+
+    pub fn write_report(&mut self, report: &[u8]) -> io::Result<usize> {
+        let report_id = match report.first() {
+            Some(&id) => id,
+            None => return Ok(0),
+        };
+        // Report ID is not sent for devices that don't use numbered reports.
+        let data = if report_id == 0 { &report[1..] } else { report };
+
+        let ret = unsafe {
+            IOHIDDeviceSetReport(
+                self.device,
+                kIOHIDReportTypeOutput,
+                report_id as CFIndex,
+                data.as_ptr(),
+                data.len() as CFIndex,
+            )
+        };
+
+        if ret == kIOReturnSuccess {
+            Ok(report.len())
+        } else {
+            Err(io_error(ret))
+        }
+    }
+}
+
+impl Drop for RawReports {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        unsafe {
+            IOHIDDeviceClose(self.device, kIOHIDOptionsTypeNone);
+            CFRelease(self.device as _);
+        }
+    }
+}
+
+fn io_error(ret: IOReturn) -> io::Error {
+    if ret == kIOReturnNotPermitted {
+        io::Error::new(
+            ErrorKind::PermissionDenied,
+            "Input Monitoring permission is required",
+        )
+    } else {
+        io::Error::new(ErrorKind::Other, format!("IOKit error {:#x}", ret))
+    }
+}
+
+extern "C" fn input_report_cb(
+    context: *mut c_void,
+    _result: IOReturn,
+    _sender: *mut c_void,
+    _report_type: u32,
+    _report_id: u32,
+    report: *mut u8,
+    report_length: CFIndex,
+) {
+    let context = unsafe { &*(context as *const Context) };
+    let report = unsafe { slice::from_raw_parts(report, report_length as usize) };
+
+    let mut queue = context.queue.lock().unwrap();
+    if queue.len() >= MAX_QUEUED_REPORTS {
+        queue.pop_front();
+    }
+    queue.push_back(report.to_vec());
+}
@@ -33,14 +33,24 @@ mod platform;
 #[path = "windows/mod.rs"]
 mod platform;
 
+#[cfg(target_os = "freebsd")]
+#[path = "freebsd/mod.rs"]
+mod platform;
+
 #[cfg(target_arch = "wasm32")]
 #[path = "wasm/mod.rs"]
 mod platform;
 
+#[cfg(target_os = "android")]
+#[path = "android/mod.rs"]
+mod platform;
+
 #[cfg(all(
     not(target_os = "linux"),
     not(target_os = "macos"),
     not(target_os = "windows"),
+    not(target_os = "freebsd"),
+    not(target_os = "android"),
     not(target_arch = "wasm32")
 ))]
 #[path = "default/mod.rs"]
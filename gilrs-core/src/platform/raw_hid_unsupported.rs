@@ -0,0 +1,31 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `RawReports` for platforms that can't access HID device of gamepad.
+
+use std::io;
+
+/// Can't be created, `Gamepad::raw_reports()` always returns `unsupported()`.
+#[derive(Debug)]
+pub enum RawReports {}
+
+impl RawReports {
+    pub fn next_report(&mut self, _buf: &mut [u8]) -> Option<usize> {
+        match *self {}
+    }
+
+    pub fn write_report(&mut self, _report: &[u8]) -> io::Result<usize> {
+        match *self {}
+    }
+}
+
+pub fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        "raw HID reports are not supported on this platform",
+    )
+}
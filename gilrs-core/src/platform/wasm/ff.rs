@@ -11,5 +11,14 @@ use std::time::Duration;
 pub struct Device;
 
 impl Device {
+    pub fn query_max_effects(&self) -> usize {
+        0
+    }
+
+    /// Device doesn't hold any OS resource, so it never becomes invalid.
+    pub fn is_connected(&self) -> bool {
+        true
+    }
+
     pub fn set_ff_state(&mut self, _strong: u16, _weak: u16, _min_duration: Duration) {}
 }
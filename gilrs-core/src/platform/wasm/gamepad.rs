@@ -6,10 +6,13 @@
 // copied, modified, or distributed except according to those terms.
 
 use super::FfDevice;
-use crate::{AxisInfo, Event, EventType, PlatformError, PowerInfo};
+use crate::{
+    AxisInfo, Event, EventType, LedError, PlatformError, PollingRateError, PowerInfo, PowerOffError,
+};
 use uuid::Uuid;
 
 use std::collections::VecDeque;
+use std::convert::TryFrom;
 #[cfg(not(feature = "wasm-bindgen"))]
 use stdweb::web::{Gamepad as WebGamepad, GamepadMappingType};
 #[cfg(feature = "wasm-bindgen")]
@@ -139,6 +142,16 @@ impl Gilrs {
     pub fn last_gamepad_hint(&self) -> usize {
         self.gamepads.len()
     }
+
+    /// Gamepads are enumerated on every call to `next_event()`, so there is nothing to do.
+    pub(crate) fn rescan_gamepads(&mut self) -> usize {
+        0
+    }
+
+    // Browser updates gamepad state on its own, it's read on every call to `next_event()`.
+    pub(crate) fn set_polling_rate(&mut self, _hz: u32) -> Result<(), PollingRateError> {
+        Err(PollingRateError::NotSupported)
+    }
 }
 
 #[derive(Debug)]
@@ -240,6 +253,14 @@ impl Gamepad {
         &self.name
     }
 
+    pub fn manufacturer_string(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn product_string(&self) -> Option<&str> {
+        None
+    }
+
     pub fn uuid(&self) -> Uuid {
         self.uuid
     }
@@ -256,6 +277,47 @@ impl Gamepad {
         false
     }
 
+    pub fn power_off(&self) -> Result<(), PowerOffError> {
+        Err(PowerOffError::NotSupported)
+    }
+
+    pub fn set_player_indicator(&self, _player: Option<u8>) -> Result<(), LedError> {
+        Err(LedError::NotSupported)
+    }
+
+    pub fn input_report_rate(&self) -> Option<u32> {
+        None
+    }
+
+    pub fn has_touchpad(&self) -> bool {
+        false
+    }
+
+    pub fn current_state(&self) -> Option<crate::RawState> {
+        None
+    }
+
+    pub fn os_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn suggested_player_index(&self) -> Option<u8> {
+        None
+    }
+
+    pub fn port_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<&str> {
+        None
+    }
+
+    #[cfg(feature = "raw-hid")]
+    pub fn raw_reports(&self) -> std::io::Result<super::RawReports> {
+        Err(super::raw_hid::unsupported())
+    }
+
     pub fn ff_device(&self) -> Option<FfDevice> {
         None
     }
@@ -298,9 +360,32 @@ use serde::{Deserialize, Serialize};
 pub struct EvCode(u8);
 
 impl EvCode {
+    pub fn from_raw(kind: u32, number: u32) -> Option<Self> {
+        if kind != 0 {
+            return None;
+        }
+
+        u8::try_from(number).ok().map(EvCode)
+    }
+
+    pub fn synthetic(index: u8) -> Self {
+        // Codes of gamepads without standard mapping start at 31, so they can only collide with
+        // this if gamepad has more than 160 buttons and axes.
+        EvCode(0xc0 | index)
+    }
+
     pub fn into_u32(self) -> u32 {
         self.0 as u32
     }
+
+    pub fn debug_info(self) -> crate::EvCodeInfo {
+        crate::EvCodeInfo {
+            platform: "Web",
+            kind: 0,
+            number: self.into_u32(),
+            name: crate::utils::native_ev_code_name(self.into_u32()),
+        }
+    }
 }
 
 impl Display for EvCode {
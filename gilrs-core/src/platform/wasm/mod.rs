@@ -1,7 +1,14 @@
 mod ff;
 mod gamepad;
+#[cfg(feature = "raw-hid")]
+#[path = "../raw_hid_unsupported.rs"]
+mod raw_hid;
 
 pub use self::ff::Device as FfDevice;
 pub use self::gamepad::{native_ev_codes, EvCode, Gamepad, Gilrs};
+#[cfg(feature = "raw-hid")]
+pub use self::raw_hid::RawReports;
 
 pub const IS_Y_AXIS_REVERSED: bool = true;
+pub const HAS_KERNEL_TIMESTAMPS: bool = false;
+pub const SUPPORTS_MULTIPLE_INSTANCES: bool = true;
@@ -8,6 +8,9 @@
 use rusty_xinput::{self, XInputUsageError};
 use std::time::Duration;
 
+// The same as limit used by most Linux drivers, so programs behave the same on both platforms.
+const MAX_EFFECTS: usize = 16;
+
 #[derive(Debug)]
 pub struct Device {
     id: u32,
@@ -18,6 +21,17 @@ impl Device {
         Device { id }
     }
 
+    /// XInput doesn't have effect slots, it exposes only one vibration state (speed of both
+    /// motors) and effects are combined before they are sent to device.
+    pub fn query_max_effects(&self) -> usize {
+        MAX_EFFECTS
+    }
+
+    /// XInput addresses gamepad by user index, so device stays valid after gamepad reconnects.
+    pub fn is_connected(&self) -> bool {
+        true
+    }
+
     pub fn set_ff_state(&mut self, strong: u16, weak: u16, _min_duration: Duration) {
         match rusty_xinput::xinput_set_state(self.id, strong, weak) {
             Ok(()) => (),
@@ -6,12 +6,21 @@
 // copied, modified, or distributed except according to those terms.
 
 use super::FfDevice;
-use crate::{AxisInfo, Event, EventType, PlatformError, PowerInfo};
+use crate::native_ev_codes as nec;
+use crate::{
+    AxisInfo, Event, EventType, LedError, PlatformError, PlatformErrorKind, PollingRateError,
+    PowerInfo, PowerOffError, RawState,
+};
 
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
 use std::error::Error as StdError;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::{mem, thread, u16, u32};
 
 use rusty_xinput::XInputLoadingFailure;
@@ -27,14 +36,44 @@ use winapi::um::xinput::{
 
 // Chosen by dice roll ;)
 const EVENT_THREAD_SLEEP_TIME: u64 = 10;
-const ITERATIONS_TO_CHECK_IF_CONNECTED: u64 = 100;
+// Disconnected gamepads are checked less often, independently of polling rate.
+const CONNECTION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 const MAX_XINPUT_CONTROLLERS: usize = 4;
+// Not defined in XInput headers, guide button is reported only by XInputGetStateEx.
+const XINPUT_GAMEPAD_GUIDE: u16 = 0x0400;
+/// XInput button masks and codes with which buttons are reported.
+const BUTTON_MASKS: [(u16, crate::EvCode); 15] = [
+    (XINPUT_GAMEPAD_DPAD_UP, nec::BTN_DPAD_UP),
+    (XINPUT_GAMEPAD_DPAD_DOWN, nec::BTN_DPAD_DOWN),
+    (XINPUT_GAMEPAD_DPAD_LEFT, nec::BTN_DPAD_LEFT),
+    (XINPUT_GAMEPAD_DPAD_RIGHT, nec::BTN_DPAD_RIGHT),
+    (XINPUT_GAMEPAD_START, nec::BTN_START),
+    (XINPUT_GAMEPAD_GUIDE, nec::BTN_MODE),
+    (XINPUT_GAMEPAD_BACK, nec::BTN_SELECT),
+    (XINPUT_GAMEPAD_LEFT_THUMB, nec::BTN_LTHUMB),
+    (XINPUT_GAMEPAD_RIGHT_THUMB, nec::BTN_RTHUMB),
+    (XINPUT_GAMEPAD_LEFT_SHOULDER, nec::BTN_LT),
+    (XINPUT_GAMEPAD_RIGHT_SHOULDER, nec::BTN_RT),
+    (XINPUT_GAMEPAD_A, nec::BTN_SOUTH),
+    (XINPUT_GAMEPAD_B, nec::BTN_EAST),
+    (XINPUT_GAMEPAD_X, nec::BTN_WEST),
+    (XINPUT_GAMEPAD_Y, nec::BTN_NORTH),
+];
+
+// Battery level changes slowly and XInputGetBatteryInformation may block for a while on wireless
+// devices, so don't query it on every call.
+const POWER_INFO_CACHE_TIME: Duration = Duration::from_secs(2);
 
 #[derive(Debug)]
 pub struct Gilrs {
     gamepads: [Gamepad; MAX_XINPUT_CONTROLLERS],
     rx: Receiver<Event>,
+    // Events generated by `rescan_gamepads()`.
+    pending_events: VecDeque<Event>,
+    guide_supported: bool,
+    // Time between reads of XInput state in microseconds, shared with event thread.
+    poll_interval: Arc<AtomicU64>,
 }
 
 impl Gilrs {
@@ -43,37 +82,109 @@ impl Gilrs {
             Ok(()) => (),
             Err(XInputLoadingFailure::AlreadyLoading)
             | Err(XInputLoadingFailure::AlreadyActive) => (),
-            Err(e) => return Err(PlatformError::Other(Box::new(Error::FailedToLoadDll(e)))),
+            Err(e) => {
+                let kind = PlatformErrorKind::last_os_error("xinput_load");
+                return Err(PlatformError::Other(Box::new(Error::FailedToLoadDll(
+                    e, kind,
+                ))));
+            }
         }
 
         let mut gamepads: [Gamepad; MAX_XINPUT_CONTROLLERS] = Default::default();
         let mut connected: [bool; MAX_XINPUT_CONTROLLERS] = Default::default();
+        let guide_supported = is_guide_supported();
 
         // Iterate through each controller ID and set connected state
         for id in 0..MAX_XINPUT_CONTROLLERS {
-            gamepads[id] = Gamepad::new(id as u32);
+            gamepads[id] = Gamepad::new(id as u32, guide_supported);
             connected[id] = gamepads[id].is_connected;
         }
 
         let (tx, rx) = mpsc::channel();
-        Self::spawn_thread(tx, connected);
+        let poll_interval = Arc::new(AtomicU64::new(EVENT_THREAD_SLEEP_TIME * 1000));
+        Self::spawn_thread(tx, connected, guide_supported, poll_interval.clone());
 
         // Coerce gamepads vector to slice
-        Ok(Gilrs { gamepads, rx })
+        Ok(Gilrs {
+            gamepads,
+            rx,
+            pending_events: VecDeque::new(),
+            guide_supported,
+            poll_interval,
+        })
     }
 
     pub(crate) fn next_event(&mut self) -> Option<Event> {
-        let ev = self.rx.try_recv().ok();
+        if let Some(ev) = self.pending_events.pop_front() {
+            return Some(ev);
+        }
+
+        loop {
+            let ev = self.rx.try_recv().ok()?;
 
-        if let Some(ev) = ev {
             match ev.event {
-                EventType::Connected => self.gamepads[ev.id].is_connected = true,
-                EventType::Disconnected => self.gamepads[ev.id].is_connected = false,
+                // Change was already reported by `rescan_gamepads()`.
+                EventType::Connected if self.gamepads[ev.id].is_connected => continue,
+                EventType::Disconnected if !self.gamepads[ev.id].is_connected => continue,
+                EventType::Connected => {
+                    self.gamepads[ev.id].is_connected = true;
+                    self.gamepads[ev.id].power_info.set(None);
+                }
+                EventType::Disconnected => {
+                    self.gamepads[ev.id].is_connected = false;
+                    self.gamepads[ev.id].power_info.set(None);
+                }
                 _ => (),
             }
+
+            return Some(ev);
         }
+    }
+
+    pub(crate) fn rescan_gamepads(&mut self) -> usize {
+        let mut changes = 0;
+
+        for (id, gamepad) in self.gamepads.iter_mut().enumerate() {
+            let state = if self.guide_supported {
+                rusty_xinput::xinput_get_state_ex(id as u32)
+            } else {
+                rusty_xinput::xinput_get_state(id as u32)
+            };
+            let is_connected = match state {
+                Ok(_) => true,
+                Err(XInputUsageError::DeviceNotConnected) => false,
+                Err(e) => {
+                    error!(
+                        "Failed to get gamepad state: {:?}, {}",
+                        e,
+                        xinput_error("xinput_get_state", &e)
+                    );
+                    continue;
+                }
+            };
 
-        ev
+            if is_connected != gamepad.is_connected {
+                gamepad.is_connected = is_connected;
+                gamepad.power_info.set(None);
+
+                let event = if is_connected {
+                    EventType::Connected
+                } else {
+                    EventType::Disconnected
+                };
+                self.pending_events.push_back(Event::new(id, event));
+                changes += 1;
+            }
+        }
+
+        changes
+    }
+
+    pub(crate) fn set_polling_rate(&mut self, hz: u32) -> Result<(), PollingRateError> {
+        let interval = (1_000_000 / u64::from(hz)).max(1);
+        self.poll_interval.store(interval, Ordering::Relaxed);
+
+        Ok(())
     }
 
     pub fn gamepad(&self, id: usize) -> Option<&Gamepad> {
@@ -84,20 +195,36 @@ impl Gilrs {
         self.gamepads.len()
     }
 
-    fn spawn_thread(tx: Sender<Event>, connected: [bool; MAX_XINPUT_CONTROLLERS]) {
+    fn spawn_thread(
+        tx: Sender<Event>,
+        connected: [bool; MAX_XINPUT_CONTROLLERS],
+        guide_supported: bool,
+        poll_interval: Arc<AtomicU64>,
+    ) {
         thread::spawn(move || unsafe {
             // Issue #70 fix - Maintain a prev_state per controller id. Otherwise the loop will compare the prev_state of a different controller.
             let mut prev_states: [XState; MAX_XINPUT_CONTROLLERS] =
                 [mem::zeroed::<XState>(); MAX_XINPUT_CONTROLLERS];
             let mut connected = connected;
-            let mut counter = 0;
+            let mut last_connection_check: Option<Instant> = None;
 
             loop {
+                let check_connection = last_connection_check
+                    .map(|time| time.elapsed() >= CONNECTION_CHECK_INTERVAL)
+                    .unwrap_or(true);
+                if check_connection {
+                    last_connection_check = Some(Instant::now());
+                }
+
                 for id in 0..MAX_XINPUT_CONTROLLERS {
-                    if *connected.get_unchecked(id)
-                        || counter % ITERATIONS_TO_CHECK_IF_CONNECTED == 0
-                    {
-                        match rusty_xinput::xinput_get_state(id as u32) {
+                    if *connected.get_unchecked(id) || check_connection {
+                        let state = if guide_supported {
+                            rusty_xinput::xinput_get_state_ex(id as u32)
+                        } else {
+                            rusty_xinput::xinput_get_state(id as u32)
+                        };
+
+                        match state {
                             Ok(XInputState { raw: state }) => {
                                 if !connected[id] {
                                     connected[id] = true;
@@ -119,13 +246,16 @@ impl Gilrs {
                                 let _ = tx.send(Event::new(id, EventType::Disconnected));
                             }
                             Err(XInputUsageError::DeviceNotConnected) => (),
-                            Err(e) => error!("Failed to get gamepad state: {:?}", e),
+                            Err(e) => error!(
+                                "Failed to get gamepad state: {:?}, {}",
+                                e,
+                                xinput_error("xinput_get_state", &e)
+                            ),
                         }
                     }
                 }
 
-                counter = counter.wrapping_add(1);
-                thread::sleep(Duration::from_millis(EVENT_THREAD_SLEEP_TIME));
+                thread::sleep(Duration::from_micros(poll_interval.load(Ordering::Relaxed)));
             }
         });
     }
@@ -245,6 +375,18 @@ impl Gilrs {
                 )),
             };
         }
+        if !is_mask_eq(g.wButtons, pg.wButtons, XINPUT_GAMEPAD_GUIDE) {
+            let _ = match g.wButtons & XINPUT_GAMEPAD_GUIDE != 0 {
+                true => tx.send(Event::new(
+                    id,
+                    EventType::ButtonPressed(crate::native_ev_codes::BTN_MODE),
+                )),
+                false => tx.send(Event::new(
+                    id,
+                    EventType::ButtonReleased(crate::native_ev_codes::BTN_MODE),
+                )),
+            };
+        }
         if !is_mask_eq(g.wButtons, pg.wButtons, XINPUT_GAMEPAD_BACK) {
             let _ = match g.wButtons & XINPUT_GAMEPAD_BACK != 0 {
                 true => tx.send(Event::new(
@@ -361,10 +503,12 @@ pub struct Gamepad {
     uuid: Uuid,
     id: u32,
     is_connected: bool,
+    guide_supported: bool,
+    power_info: Cell<Option<(Instant, PowerInfo)>>,
 }
 
 impl Gamepad {
-    fn new(id: u32) -> Gamepad {
+    fn new(id: u32, guide_supported: bool) -> Gamepad {
         let is_connected = {
             if rusty_xinput::xinput_get_state(id).is_ok() {
                 true
@@ -377,6 +521,8 @@ impl Gamepad {
             uuid: Uuid::nil(),
             id,
             is_connected,
+            guide_supported,
+            power_info: Cell::new(None),
         };
 
         gamepad
@@ -386,6 +532,15 @@ impl Gamepad {
         "Xbox Controller"
     }
 
+    // XInput does not tell which device is behind given user index, so HID strings can't be read.
+    pub fn manufacturer_string(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn product_string(&self) -> Option<&str> {
+        None
+    }
+
     pub fn uuid(&self) -> Uuid {
         self.uuid
     }
@@ -395,47 +550,127 @@ impl Gamepad {
     }
 
     pub fn power_info(&self) -> PowerInfo {
-        match rusty_xinput::xinput_get_gamepad_battery_information(self.id) {
-            Ok(binfo) => match binfo.battery_type {
-                BatteryType::WIRED => PowerInfo::Wired,
-                BatteryType::ALKALINE | BatteryType::NIMH => {
-                    let lvl = match binfo.battery_level {
-                        BatteryLevel::EMPTY => 0,
-                        BatteryLevel::LOW => 33,
-                        BatteryLevel::MEDIUM => 67,
-                        BatteryLevel::FULL => 100,
-                        lvl => {
-                            trace!("Unexpected battery level: {}", lvl.0);
-
-                            100
-                        }
-                    };
-                    if lvl == 100 {
-                        PowerInfo::Charged
-                    } else {
-                        PowerInfo::Discharging(lvl)
-                    }
-                }
-                _ => PowerInfo::Unknown,
-            },
+        if let Some((time, info)) = self.power_info.get() {
+            if time.elapsed() < POWER_INFO_CACHE_TIME {
+                return info;
+            }
+        }
+
+        let info = match rusty_xinput::xinput_get_gamepad_battery_information(self.id) {
+            Ok(binfo) => xinput_power_info(binfo.battery_type, binfo.battery_level),
             Err(e) => {
-                debug!("Failed to get battery info: {:?}", e);
+                debug!(
+                    "Failed to get battery info: {:?}, {}",
+                    e,
+                    xinput_error("xinput_get_battery_information", &e)
+                );
 
                 PowerInfo::Unknown
             }
-        }
+        };
+        self.power_info.set(Some((Instant::now(), info)));
+
+        info
     }
 
     pub fn is_ff_supported(&self) -> bool {
         true
     }
 
+    // XInput doesn't have public API for powering off controllers.
+    pub fn power_off(&self) -> Result<(), PowerOffError> {
+        Err(PowerOffError::NotSupported)
+    }
+
+    // XInput assigns quadrant LEDs by controller slot and has no API for changing them. Devices
+    // handled by XInput driver don't have HID interface, so there is no raw HID fallback.
+    pub fn set_player_indicator(&self, _player: Option<u8>) -> Result<(), LedError> {
+        Err(LedError::NotSupported)
+    }
+
+    pub fn input_report_rate(&self) -> Option<u32> {
+        // XInput devices always report at 125 Hz.
+        Some(125)
+    }
+
+    // XInput doesn't report touchpads.
+    pub fn has_touchpad(&self) -> bool {
+        false
+    }
+
+    pub fn current_state(&self) -> Option<RawState> {
+        let state = if self.guide_supported {
+            rusty_xinput::xinput_get_state_ex(self.id)
+        } else {
+            rusty_xinput::xinput_get_state(self.id)
+        };
+        let g = match state {
+            Ok(XInputState { raw }) => raw.Gamepad,
+            Err(e) => {
+                debug!(
+                    "Failed to get gamepad state: {:?}, {}",
+                    e,
+                    xinput_error("xinput_get_state", &e)
+                );
+                return None;
+            }
+        };
+
+        let buttons = BUTTON_MASKS
+            .iter()
+            .filter(|&&(mask, _)| self.guide_supported || mask != XINPUT_GAMEPAD_GUIDE)
+            .map(|&(mask, code)| (code, g.wButtons & mask != 0))
+            .collect();
+        let axes = vec![
+            (nec::AXIS_LSTICKX, i32::from(g.sThumbLX)),
+            (nec::AXIS_LSTICKY, i32::from(g.sThumbLY)),
+            (nec::AXIS_RSTICKX, i32::from(g.sThumbRX)),
+            (nec::AXIS_RSTICKY, i32::from(g.sThumbRY)),
+            (nec::AXIS_LT2, i32::from(g.bLeftTrigger)),
+            (nec::AXIS_RT2, i32::from(g.bRightTrigger)),
+        ];
+
+        Some(RawState { buttons, axes })
+    }
+
+    pub fn os_path(&self) -> Option<&str> {
+        // XInput does not tell which device is behind given user index, so there is no reliable
+        // way to match it with device instance ID from SetupAPI.
+        None
+    }
+
+    pub fn suggested_player_index(&self) -> Option<u8> {
+        // User index is also the quadrant lit on the Xbox button.
+        Some(self.id as u8)
+    }
+
+    pub fn port_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn serial_number(&self) -> Option<&str> {
+        None
+    }
+
+    // Not supported. XInput does not tell which device is behind given user index and devices
+    // handled by XInput driver don't have HID interface that could be opened with `HidD_*`
+    // functions and `ReadFile()`. Other HID devices are not enumerated at all, so there is no
+    // gamepad whose reports could be read.
+    #[cfg(feature = "raw-hid")]
+    pub fn raw_reports(&self) -> std::io::Result<super::RawReports> {
+        Err(super::raw_hid::unsupported())
+    }
+
     pub fn ff_device(&self) -> Option<FfDevice> {
         Some(FfDevice::new(self.id))
     }
 
     pub fn buttons(&self) -> &[EvCode] {
-        &native_ev_codes::BUTTONS
+        if self.guide_supported {
+            &native_ev_codes::BUTTONS
+        } else {
+            &native_ev_codes::BUTTONS_WITHOUT_MODE
+        }
     }
 
     pub fn axes(&self) -> &[EvCode] {
@@ -449,6 +684,63 @@ impl Gamepad {
     }
 }
 
+/// Win32 error code returned by XInput functions for disconnected controllers.
+const ERROR_DEVICE_NOT_CONNECTED: i32 = 1167;
+
+/// Converts error returned by XInput function to `PlatformErrorKind` with Win32 error code. Errors
+/// that don't come from XInput itself have code 0.
+fn xinput_error(tag: &'static str, err: &XInputUsageError) -> PlatformErrorKind {
+    let code = match *err {
+        XInputUsageError::DeviceNotConnected => ERROR_DEVICE_NOT_CONNECTED,
+        XInputUsageError::UnknownError(code) => code as i32,
+        _ => 0,
+    };
+
+    PlatformErrorKind::new(tag, code)
+}
+
+/// Checks if XInputGetStateEx is available. It is exported only by ordinal, so rusty_xinput
+/// resolves it when loading DLL and reports error if it's missing (e.g. in xinput9_1_0.dll).
+fn is_guide_supported() -> bool {
+    match rusty_xinput::xinput_get_state_ex(0) {
+        Ok(_) | Err(XInputUsageError::DeviceNotConnected) => true,
+        Err(e) => {
+            debug!(
+                "XInputGetStateEx is not available, guide button will not be reported: {:?}",
+                e
+            );
+
+            false
+        }
+    }
+}
+
+/// Translates XInput's coarse battery level to `PowerInfo`.
+fn xinput_power_info(battery_type: BatteryType, battery_level: BatteryLevel) -> PowerInfo {
+    match battery_type {
+        BatteryType::WIRED => PowerInfo::Wired,
+        BatteryType::ALKALINE | BatteryType::NIMH => {
+            let lvl = match battery_level {
+                BatteryLevel::EMPTY => 0,
+                BatteryLevel::LOW => 33,
+                BatteryLevel::MEDIUM => 67,
+                BatteryLevel::FULL => 100,
+                lvl => {
+                    trace!("Unexpected battery level: {}", lvl.0);
+
+                    100
+                }
+            };
+            if lvl == 100 {
+                PowerInfo::Charged
+            } else {
+                PowerInfo::Discharging(lvl)
+            }
+        }
+        _ => PowerInfo::Unknown,
+    }
+}
+
 #[inline(always)]
 fn is_mask_eq(l: u16, r: u16, mask: u16) -> bool {
     (l & mask != 0) == (r & mask != 0)
@@ -462,9 +754,31 @@ use serde::{Deserialize, Serialize};
 pub struct EvCode(u8);
 
 impl EvCode {
+    pub fn from_raw(kind: u32, number: u32) -> Option<Self> {
+        if kind != 0 {
+            return None;
+        }
+
+        u8::try_from(number).ok().map(EvCode)
+    }
+
+    pub fn synthetic(index: u8) -> Self {
+        // Native codes are lower than 32.
+        EvCode(0xc0 | index)
+    }
+
     pub fn into_u32(self) -> u32 {
         self.0 as u32
     }
+
+    pub fn debug_info(self) -> crate::EvCodeInfo {
+        crate::EvCodeInfo {
+            platform: "Windows",
+            kind: 0,
+            number: self.into_u32(),
+            name: crate::utils::native_ev_code_name(self.into_u32()),
+        }
+    }
 }
 
 impl Display for EvCode {
@@ -475,17 +789,25 @@ impl Display for EvCode {
 
 #[derive(Debug)]
 enum Error {
-    FailedToLoadDll(XInputLoadingFailure),
+    FailedToLoadDll(XInputLoadingFailure, PlatformErrorKind),
 }
 
-impl StdError for Error {}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::FailedToLoadDll(_, kind) => Some(kind),
+        }
+    }
+}
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         match self {
-            Error::FailedToLoadDll(e) => {
-                f.write_fmt(format_args!("Failed to load XInput DLL {:?}", e))
-            }
+            Error::FailedToLoadDll(e, kind) => f.write_fmt(format_args!(
+                "Failed to load XInput DLL {:?} (os error {})",
+                e,
+                kind.code()
+            )),
         }
     }
 }
@@ -554,6 +876,23 @@ pub mod native_ev_codes {
         BTN_DPAD_RIGHT,
     ];
 
+    pub(super) static BUTTONS_WITHOUT_MODE: [EvCode; 14] = [
+        BTN_SOUTH,
+        BTN_EAST,
+        BTN_NORTH,
+        BTN_WEST,
+        BTN_LT,
+        BTN_RT,
+        BTN_SELECT,
+        BTN_START,
+        BTN_LTHUMB,
+        BTN_RTHUMB,
+        BTN_DPAD_UP,
+        BTN_DPAD_DOWN,
+        BTN_DPAD_LEFT,
+        BTN_DPAD_RIGHT,
+    ];
+
     pub(super) static AXES: [EvCode; 6] = [
         AXIS_LSTICKX,
         AXIS_LSTICKY,
@@ -614,3 +953,45 @@ pub mod native_ev_codes {
         }),
     ];
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{xinput_error, xinput_power_info, ERROR_DEVICE_NOT_CONNECTED};
+    use crate::{PlatformErrorKind, PowerInfo};
+    use rusty_xinput::{BatteryLevel, BatteryType, XInputUsageError};
+
+    #[test]
+    fn xinput_error_codes() {
+        assert_eq!(
+            xinput_error("xinput_get_state", &XInputUsageError::DeviceNotConnected),
+            PlatformErrorKind::new("xinput_get_state", ERROR_DEVICE_NOT_CONNECTED)
+        );
+        assert_eq!(
+            xinput_error("xinput_get_state", &XInputUsageError::UnknownError(5)).code(),
+            5
+        );
+    }
+
+    #[test]
+    fn xinput_battery_levels() {
+        let cases = [
+            (BatteryLevel::EMPTY, PowerInfo::Discharging(0)),
+            (BatteryLevel::LOW, PowerInfo::Discharging(33)),
+            (BatteryLevel::MEDIUM, PowerInfo::Discharging(67)),
+            (BatteryLevel::FULL, PowerInfo::Charged),
+        ];
+
+        for &(level, expected) in cases.iter() {
+            assert_eq!(xinput_power_info(BatteryType::ALKALINE, level), expected);
+            assert_eq!(xinput_power_info(BatteryType::NIMH, level), expected);
+            assert_eq!(
+                xinput_power_info(BatteryType::WIRED, level),
+                PowerInfo::Wired
+            );
+            assert_eq!(
+                xinput_power_info(BatteryType::DISCONNECTED, level),
+                PowerInfo::Unknown
+            );
+        }
+    }
+}
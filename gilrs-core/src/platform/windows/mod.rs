@@ -6,9 +6,16 @@
 // copied, modified, or distributed except according to those terms.
 mod ff;
 mod gamepad;
+#[cfg(feature = "raw-hid")]
+#[path = "../raw_hid_unsupported.rs"]
+mod raw_hid;
 
 pub use self::ff::Device as FfDevice;
 pub use self::gamepad::{native_ev_codes, EvCode, Gamepad, Gilrs};
+#[cfg(feature = "raw-hid")]
+pub use self::raw_hid::RawReports;
 
 pub const NAME: &'static str = "Windows";
 pub const IS_Y_AXIS_REVERSED: bool = false;
+pub const HAS_KERNEL_TIMESTAMPS: bool = false;
+pub const SUPPORTS_MULTIPLE_INSTANCES: bool = true;
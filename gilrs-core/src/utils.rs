@@ -22,3 +22,46 @@ pub fn time_now() -> SystemTime {
     let offset = Duration::from_millis(Date::now() as u64);
     SystemTime::UNIX_EPOCH + offset
 }
+
+/// Names of `native_ev_codes` constants on platforms where `EvCode` is index of element.
+#[allow(dead_code)]
+static NATIVE_EV_CODE_NAMES: [&str; 31] = [
+    "AXIS_LSTICKX",
+    "AXIS_LSTICKY",
+    "AXIS_LEFTZ",
+    "AXIS_RSTICKX",
+    "AXIS_RSTICKY",
+    "AXIS_RIGHTZ",
+    "AXIS_DPADX",
+    "AXIS_DPADY",
+    "AXIS_RT",
+    "AXIS_LT",
+    "AXIS_RT2",
+    "AXIS_LT2",
+    "BTN_SOUTH",
+    "BTN_EAST",
+    "BTN_C",
+    "BTN_NORTH",
+    "BTN_WEST",
+    "BTN_Z",
+    "BTN_LT",
+    "BTN_RT",
+    "BTN_LT2",
+    "BTN_RT2",
+    "BTN_SELECT",
+    "BTN_START",
+    "BTN_MODE",
+    "BTN_LTHUMB",
+    "BTN_RTHUMB",
+    "BTN_DPAD_UP",
+    "BTN_DPAD_DOWN",
+    "BTN_DPAD_LEFT",
+    "BTN_DPAD_RIGHT",
+];
+
+/// Returns name of element with given index on platforms that don't have their own codes
+/// (Windows, web and unsupported platforms).
+#[allow(dead_code)]
+pub(crate) fn native_ev_code_name(index: u32) -> Option<&'static str> {
+    NATIVE_EV_CODE_NAMES.get(index as usize).cloned()
+}
@@ -0,0 +1,66 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Measures `Gilrs::snapshot()` with four gamepads. Run with
+//! `cargo bench --features test_utils --bench snapshot`.
+
+use gilrs::test_utils::{native_ev_codes as nec, AxisInfo, TestBackend};
+use gilrs::GilrsBuilder;
+
+use std::time::Instant;
+
+const ITERATIONS: u32 = 100_000;
+
+fn main() {
+    let stick = AxisInfo {
+        min: -32768,
+        max: 32767,
+        deadzone: None,
+    };
+    let buttons = [
+        nec::BTN_SOUTH,
+        nec::BTN_EAST,
+        nec::BTN_NORTH,
+        nec::BTN_WEST,
+        nec::BTN_LT,
+        nec::BTN_RT,
+        nec::BTN_SELECT,
+        nec::BTN_START,
+        nec::BTN_MODE,
+        nec::BTN_LTHUMB,
+        nec::BTN_RTHUMB,
+    ];
+    let axes = [
+        (nec::AXIS_LSTICKX, stick),
+        (nec::AXIS_LSTICKY, stick),
+        (nec::AXIS_RSTICKX, stick),
+        (nec::AXIS_RSTICKY, stick),
+    ];
+
+    let mut backend = TestBackend::new();
+    for i in 0..4 {
+        backend.add_device(&format!("Gamepad {}", i), [i; 16], &buttons, &axes);
+    }
+    let mut gilrs = GilrsBuilder::new().build_with_backend(backend).unwrap();
+    while gilrs.next_event().is_some() {}
+
+    // Used so that snapshots are not optimized out.
+    let mut elements = 0;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let snapshot = gilrs.snapshot();
+        elements += snapshot.gamepads[3].buttons.len() + snapshot.gamepads[3].axes.len();
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "snapshot: {:?} per call ({} iterations, {} elements)",
+        elapsed / ITERATIONS,
+        ITERATIONS,
+        elements
+    );
+}
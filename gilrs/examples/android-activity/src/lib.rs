@@ -0,0 +1,79 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use android_activity::input::{InputEvent, InputStatus};
+use android_activity::{AndroidApp, MainEvent, PollEvent};
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Replay, Ticks};
+use gilrs::{Button, EventType, GamepadId, Gilrs};
+use log::LevelFilter;
+use std::time::Duration;
+
+#[no_mangle]
+fn android_main(app: AndroidApp) {
+    android_logger::init_once(android_logger::Config::default().with_max_level(LevelFilter::Info));
+
+    let mut gilrs = Gilrs::new().unwrap();
+    // Enumerates gamepads and enables force feedback. VM lives as long as the application.
+    unsafe { gilrs.android_set_java_vm(app.vm_as_ptr()) };
+
+    // Effect stops playing when it's dropped.
+    let mut effect = None;
+    let mut quit = false;
+    while !quit {
+        app.poll_events(Some(Duration::from_millis(16)), |event| {
+            if let PollEvent::Main(MainEvent::Destroy) = event {
+                quit = true;
+            }
+        });
+
+        app.input_events(|event| {
+            let raw = match event {
+                InputEvent::KeyEvent(ev) => ev.ptr().as_ptr() as *const _,
+                InputEvent::MotionEvent(ev) => ev.ptr().as_ptr() as *const _,
+                _ => return InputStatus::Unhandled,
+            };
+            // Event is valid until callback returns.
+            if unsafe { gilrs.handle_android_event(raw) } {
+                InputStatus::Handled
+            } else {
+                InputStatus::Unhandled
+            }
+        });
+
+        while let Some(ev) = gilrs.next_event() {
+            log::info!("{}", ev);
+
+            if let EventType::ButtonPressed(Button::South, _) = ev.event {
+                if gilrs.gamepad(ev.id).is_ff_supported() {
+                    effect = rumble(&mut gilrs, ev.id);
+                }
+            }
+        }
+    }
+}
+
+fn rumble(gilrs: &mut Gilrs, id: GamepadId) -> Option<Effect> {
+    let effect = EffectBuilder::new()
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Strong { magnitude: 60_000 },
+            scheduling: Replay {
+                play_for: Ticks::from_ms(200),
+                ..Default::default()
+            },
+            envelope: Default::default(),
+        })
+        .gamepads(&[id])
+        .finish(gilrs);
+
+    match effect.and_then(|effect| effect.play().map(|_| effect)) {
+        Ok(effect) => Some(effect),
+        Err(err) => {
+            log::error!("Failed to play effect: {}", err);
+            None
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use gilrs::ev::curve::CurveTable;
+use gilrs::{Axis, EventType, Gilrs};
+
+use std::thread;
+use std::time::Duration;
+
+// Worn stick: nothing is reported for small movements and then value jumps to 0.4.
+fn distort(x: f32) -> f32 {
+    if x.abs() < 0.05 {
+        0.0
+    } else {
+        x.signum() * (0.4 + 0.6 * x.abs())
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    // Reported 0.43 (the jump) is corrected to 0.05, from there curve is linear.
+    let curve = CurveTable::from_points(&[
+        (-1.0, -1.0),
+        (-0.43, -0.05),
+        (0.0, 0.0),
+        (0.43, 0.05),
+        (1.0, 1.0),
+    ])
+    .unwrap();
+
+    println!("{:>8} {:>8} {:>8}", "real", "reported", "fixed");
+    for i in -10..=10 {
+        let real = i as f32 / 10.0;
+        let reported = distort(real);
+        println!(
+            "{:8.2} {:8.2} {:8.2}",
+            real,
+            reported,
+            curve.apply(reported)
+        );
+    }
+
+    let mut gilrs = Gilrs::new().unwrap();
+    let gamepads: Vec<_> = gilrs
+        .gamepads()
+        .map(|(id, gamepad)| (id, gamepad.axis_code(Axis::LeftStickX)))
+        .collect();
+    for (id, code) in gamepads {
+        if let Some(code) = code {
+            gilrs.set_axis_curve(id, code, curve.clone()).unwrap();
+        }
+    }
+
+    println!("Move left stick of connected gamepad to see corrected values.");
+    loop {
+        while let Some(ev) = gilrs.next_event() {
+            if let EventType::AxisChanged(Axis::LeftStickX, val, _) = ev.event {
+                println!("{:?}: {:.3}", ev.id, val);
+            }
+        }
+
+        thread::sleep(Duration::from_millis(33));
+    }
+}
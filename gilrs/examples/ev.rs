@@ -33,7 +33,7 @@ fn main() {
     loop {
         while let Some(ev) = gilrs.next_event().filter_ev(&repeat_filter, &mut gilrs) {
             gilrs.update(&ev);
-            println!("{:?}", ev);
+            println!("{}", ev);
         }
 
         if gilrs.counter() % 250 == 0 {
@@ -0,0 +1,35 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use gilrs::ff::Clip;
+use gilrs::Gilrs;
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    env_logger::init();
+    let mut gilrs = Gilrs::new().unwrap();
+    let support_ff = gilrs
+        .gamepads()
+        .filter_map(|(id, gp)| if gp.is_ff_supported() { Some(id) } else { None })
+        .collect::<Vec<_>>();
+
+    // Two beats followed by a pause.
+    let heartbeat: Clip = "0.1:1.0,0.4;0.1:0,0;0.15:0.6,1.0;0.65:0,0".parse().unwrap();
+
+    for _ in 0..5 {
+        let effects = support_ff
+            .iter()
+            .map(|&id| heartbeat.play_on(&mut gilrs, id).unwrap())
+            .collect::<Vec<_>>();
+
+        thread::sleep(heartbeat.duration());
+        drop(effects);
+    }
+
+    thread::sleep(Duration::from_millis(100));
+}
@@ -0,0 +1,63 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use gilrs::GilrsBuilder;
+
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn main() {
+    env_logger::init();
+
+    let mut gilrs = match GilrsBuilder::new().with_latency_metrics(true).build() {
+        Ok(g) => g,
+        Err(gilrs::Error::NotImplemented(g)) => {
+            eprintln!("Current platform is not supported");
+
+            g
+        }
+        Err(e) => {
+            eprintln!("Failed to create gilrs context: {}", e);
+            process::exit(-1);
+        }
+    };
+
+    let mut last_print = Instant::now();
+    loop {
+        while gilrs.next_event().is_some() {}
+
+        if last_print.elapsed() >= Duration::from_secs(1) {
+            last_print = Instant::now();
+
+            for (id, gamepad) in gilrs.gamepads() {
+                let stats = match gilrs.latency_stats(id) {
+                    Some(stats) => stats,
+                    None => continue,
+                };
+
+                println!(
+                    "{}({}): {} events, min {:?}, mean {:?}, p95 {:?}, max {:?}{}",
+                    id,
+                    gamepad.name(),
+                    stats.samples,
+                    stats.min,
+                    stats.mean,
+                    stats.p95,
+                    stats.max,
+                    if stats.queueing_only {
+                        " (queueing only)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+
+        thread::sleep(Duration::from_millis(16));
+    }
+}
@@ -0,0 +1,373 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Source of raw events used by `Gilrs`. This is either platform backend from `gilrs-core` or,
+//! with `test_utils` feature, scriptable `TestBackend`.
+
+#[cfg(all(feature = "raw-ff", any(test, feature = "test_utils")))]
+use crate::test_utils::TestFfDevice;
+#[cfg(any(test, feature = "test_utils"))]
+use crate::test_utils::{TestBackend, TestGamepad};
+use crate::utils;
+
+use gilrs_core::{
+    AxisInfo, EvCode, Event as RawEvent, FfDevice, LedError, PollingRateError, PowerInfo,
+    PowerOffError, RawState,
+};
+
+#[cfg(feature = "raw-ff")]
+use std::time::Duration;
+use std::time::SystemTime;
+
+#[derive(Debug)]
+pub(crate) enum Backend {
+    // Boxed, so `TestBackend` doesn't pay for size of platform backend and the other way around.
+    Platform(Box<gilrs_core::Gilrs>),
+    #[cfg(any(test, feature = "test_utils"))]
+    Test(TestBackend),
+}
+
+impl Backend {
+    pub fn next_event(&mut self) -> Option<RawEvent> {
+        match self {
+            Backend::Platform(gilrs) => gilrs.next_event(),
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(backend) => backend.next_event(),
+        }
+    }
+
+    pub fn gamepad(&self, id: usize) -> Option<RawGamepad<'_>> {
+        match self {
+            Backend::Platform(gilrs) => gilrs.gamepad(id).map(RawGamepad::Platform),
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(backend) => backend.gamepad(id).map(RawGamepad::Test),
+        }
+    }
+
+    pub fn last_gamepad_hint(&self) -> usize {
+        match self {
+            Backend::Platform(gilrs) => gilrs.last_gamepad_hint(),
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(backend) => backend.last_gamepad_hint(),
+        }
+    }
+
+    pub fn rescan_gamepads(&mut self) -> usize {
+        match self {
+            Backend::Platform(gilrs) => gilrs.rescan_gamepads(),
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(_) => 0,
+        }
+    }
+
+    pub fn set_polling_rate(&mut self, hz: u32) -> Result<(), PollingRateError> {
+        match self {
+            Backend::Platform(gilrs) => gilrs.set_polling_rate(hz),
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(_) => Err(PollingRateError::NotSupported),
+        }
+    }
+
+    /// Descriptors that become readable when backend has new events, or `None` if backend
+    /// doesn't have them and must be polled periodically.
+    #[cfg(all(feature = "async", target_os = "linux"))]
+    pub fn event_fds(&self) -> Option<Vec<std::os::unix::io::RawFd>> {
+        match self {
+            Backend::Platform(gilrs) => {
+                let mut fds = gilrs.device_fds().to_vec();
+                fds.extend(gilrs.event_source_fd());
+                Some(fds)
+            }
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(_) => None,
+        }
+    }
+
+    #[cfg(target_os = "android")]
+    pub unsafe fn handle_android_event(&mut self, event: *const std::os::raw::c_void) -> bool {
+        match self {
+            Backend::Platform(gilrs) => gilrs.handle_android_event(event),
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(_) => false,
+        }
+    }
+
+    #[cfg(target_os = "android")]
+    pub unsafe fn android_set_java_vm(&mut self, vm: *mut std::os::raw::c_void) {
+        match self {
+            Backend::Platform(gilrs) => gilrs.android_set_java_vm(vm),
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(_) => (),
+        }
+    }
+
+    #[cfg(target_os = "android")]
+    pub fn android_device_added(
+        &mut self,
+        device_id: i32,
+        name: &str,
+        vendor: u16,
+        product: u16,
+        axes: &[i32],
+    ) {
+        match self {
+            Backend::Platform(gilrs) => {
+                gilrs.android_device_added(device_id, name, vendor, product, axes);
+            }
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(_) => (),
+        }
+    }
+
+    #[cfg(target_os = "android")]
+    pub fn android_device_removed(&mut self, device_id: i32) {
+        match self {
+            Backend::Platform(gilrs) => gilrs.android_device_removed(device_id),
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(_) => (),
+        }
+    }
+
+    /// Current time as seen by backend. `TestBackend` has its own clock.
+    pub fn time_now(&self) -> SystemTime {
+        match self {
+            Backend::Platform(_) => utils::time_now(),
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(backend) => backend.time(),
+        }
+    }
+
+    /// True if time of events is set by kernel. `TestBackend` sets it when event is pushed.
+    pub fn has_kernel_timestamps(&self) -> bool {
+        match self {
+            Backend::Platform(_) => gilrs_core::HAS_KERNEL_TIMESTAMPS,
+            #[cfg(any(test, feature = "test_utils"))]
+            Backend::Test(_) => false,
+        }
+    }
+}
+
+/// Gamepad owned by one of backends.
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum RawGamepad<'a> {
+    Platform(&'a gilrs_core::Gamepad),
+    #[cfg(any(test, feature = "test_utils"))]
+    Test(&'a TestGamepad),
+}
+
+impl<'a> RawGamepad<'a> {
+    pub fn name(self) -> &'a str {
+        match self {
+            RawGamepad::Platform(gp) => gp.name(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.name(),
+        }
+    }
+
+    pub fn uuid(self) -> [u8; 16] {
+        match self {
+            RawGamepad::Platform(gp) => gp.uuid(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.uuid(),
+        }
+    }
+
+    pub fn is_connected(self) -> bool {
+        match self {
+            RawGamepad::Platform(gp) => gp.is_connected(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.is_connected(),
+        }
+    }
+
+    pub fn power_info(self) -> PowerInfo {
+        match self {
+            RawGamepad::Platform(gp) => gp.power_info(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.power_info(),
+        }
+    }
+
+    pub fn power_off(self) -> Result<(), PowerOffError> {
+        match self {
+            RawGamepad::Platform(gp) => gp.power_off(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.power_off(),
+        }
+    }
+
+    pub fn set_player_indicator(self, player: Option<u8>) -> Result<(), LedError> {
+        match self {
+            RawGamepad::Platform(gp) => gp.set_player_indicator(player),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.set_player_indicator(player),
+        }
+    }
+
+    pub fn is_ff_supported(self) -> bool {
+        match self {
+            RawGamepad::Platform(gp) => gp.is_ff_supported(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.is_ff_supported(),
+        }
+    }
+
+    pub fn has_touchpad(self) -> bool {
+        match self {
+            RawGamepad::Platform(gp) => gp.has_touchpad(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.has_touchpad(),
+        }
+    }
+
+    pub fn input_report_rate(self) -> Option<u32> {
+        match self {
+            RawGamepad::Platform(gp) => gp.input_report_rate(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(_) => None,
+        }
+    }
+
+    pub fn manufacturer_string(self) -> Option<&'a str> {
+        match self {
+            RawGamepad::Platform(gp) => gp.manufacturer_string(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(_) => None,
+        }
+    }
+
+    pub fn product_string(self) -> Option<&'a str> {
+        match self {
+            RawGamepad::Platform(gp) => gp.product_string(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(_) => None,
+        }
+    }
+
+    pub fn os_path(self) -> Option<&'a str> {
+        match self {
+            RawGamepad::Platform(gp) => gp.os_path(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(_) => None,
+        }
+    }
+
+    pub fn port_path(self) -> Option<&'a str> {
+        match self {
+            RawGamepad::Platform(gp) => gp.port_path(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(_) => None,
+        }
+    }
+
+    pub fn serial_number(self) -> Option<&'a str> {
+        match self {
+            RawGamepad::Platform(gp) => gp.serial_number(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.serial_number(),
+        }
+    }
+
+    pub fn suggested_player_index(self) -> Option<u8> {
+        match self {
+            RawGamepad::Platform(gp) => gp.suggested_player_index(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(_) => None,
+        }
+    }
+
+    #[cfg(feature = "raw-hid")]
+    pub fn raw_reports(self) -> std::io::Result<gilrs_core::RawReports> {
+        match self {
+            RawGamepad::Platform(gp) => gp.raw_reports(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "TestBackend doesn't have raw HID reports",
+            )),
+        }
+    }
+
+    /// `IS_Y_AXIS_REVERSED` of backend that owns gamepad. `TestBackend` can simulate both values.
+    pub fn is_y_axis_reversed(self) -> bool {
+        match self {
+            RawGamepad::Platform(_) => gilrs_core::IS_Y_AXIS_REVERSED,
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.is_y_axis_reversed(),
+        }
+    }
+
+    pub fn current_state(self) -> Option<RawState> {
+        match self {
+            RawGamepad::Platform(gp) => gp.current_state(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.current_state(),
+        }
+    }
+
+    pub fn ff_device(self) -> Option<FfDevice> {
+        match self {
+            RawGamepad::Platform(gp) => gp.ff_device(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(_) => None,
+        }
+    }
+
+    /// Returns device for `RawFfHandle`. Unlike `ff_device()`, test gamepads have one.
+    #[cfg(feature = "raw-ff")]
+    pub fn raw_ff_device(self) -> Option<RawFfDevice> {
+        match self {
+            RawGamepad::Platform(gp) => gp.ff_device().map(RawFfDevice::Platform),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.ff_device().map(RawFfDevice::Test),
+        }
+    }
+
+    pub fn buttons(self) -> &'a [EvCode] {
+        match self {
+            RawGamepad::Platform(gp) => gp.buttons(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.buttons(),
+        }
+    }
+
+    pub fn axes(self) -> &'a [EvCode] {
+        match self {
+            RawGamepad::Platform(gp) => gp.axes(),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.axes(),
+        }
+    }
+
+    pub fn axis_info(self, nec: EvCode) -> Option<&'a AxisInfo> {
+        match self {
+            RawGamepad::Platform(gp) => gp.axis_info(nec),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawGamepad::Test(gp) => gp.axis_info(nec),
+        }
+    }
+}
+
+/// Force feedback device controlled by `RawFfHandle`.
+#[cfg(feature = "raw-ff")]
+#[derive(Debug)]
+pub(crate) enum RawFfDevice {
+    Platform(FfDevice),
+    #[cfg(any(test, feature = "test_utils"))]
+    Test(TestFfDevice),
+}
+
+#[cfg(feature = "raw-ff")]
+impl RawFfDevice {
+    pub fn set_ff_state(&mut self, strong: u16, weak: u16, min_duration: Duration) {
+        match self {
+            RawFfDevice::Platform(device) => device.set_ff_state(strong, weak, min_duration),
+            #[cfg(any(test, feature = "test_utils"))]
+            RawFfDevice::Test(device) => device.set_ff_state(strong, weak, min_duration),
+        }
+    }
+}
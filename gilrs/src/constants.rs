@@ -38,3 +38,5 @@ pub const AXIS_RSTICKY: u16 = 5;
 pub const AXIS_RIGHTZ: u16 = 6;
 pub const AXIS_DPADX: u16 = 7;
 pub const AXIS_DPADY: u16 = 8;
+pub const AXIS_LT2: u16 = 9;
+pub const AXIS_RT2: u16 = 10;
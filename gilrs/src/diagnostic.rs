@@ -0,0 +1,415 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Interactive test of gamepad's buttons and axes.
+//!
+//! [`run_interactive_test()`](fn.run_interactive_test.html) asks user to press every mapped
+//! button and move every mapped axis to both ends, one by one, and returns
+//! [`TestReport`](struct.TestReport.html) with elements that didn't work as expected. Instructions
+//! are passed to callback, so they can be shown by any frontend.
+//!
+//! ```no_run
+//! use gilrs::diagnostic;
+//! use gilrs::Gilrs;
+//!
+//! let mut gilrs = Gilrs::new().unwrap();
+//! if let Some((id, _)) = gilrs.gamepads().next() {
+//!     let report = diagnostic::run_interactive_test(&mut gilrs, id, |prompt| {
+//!         println!("[{}/{}] {}", prompt.step + 1, prompt.steps, prompt);
+//!     });
+//!     println!("{:?}", report);
+//! }
+//! ```
+
+// Only `run_interactive_test()` uses other items and it's not available on wasm.
+#![cfg_attr(target_arch = "wasm32", allow(dead_code))]
+
+use crate::ev::{Axis, Button, Code, Event, EventType};
+use crate::gamepad::{GamepadId, Gilrs};
+
+use std::fmt::{self, Display};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Value that axis has to reach in both directions.
+const AXIS_EXTREME: f32 = 0.95;
+/// Time after which step is considered failed.
+const STEP_TIMEOUT: Duration = Duration::from_secs(10);
+#[cfg(not(target_arch = "wasm32"))]
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Element tested in one step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PromptKind {
+    /// User should press and release button.
+    Button(Button),
+    /// User should move axis to both ends.
+    Axis(Axis),
+}
+
+/// Instruction for user, passed to callback of `run_interactive_test()` at beginning of every
+/// step. `Display` implementation returns message in English.
+#[derive(Clone, Debug)]
+pub struct TestPrompt {
+    /// Tested element.
+    pub kind: PromptKind,
+    /// Index of this step, starting from 0.
+    pub step: usize,
+    /// Number of all steps.
+    pub steps: usize,
+    /// Time after which step fails if element is not tested.
+    pub timeout: Duration,
+    skip: SkipHandle,
+}
+
+impl TestPrompt {
+    /// Returns handle that can be used to skip this step, for example because gamepad doesn't
+    /// have such element.
+    pub fn skip_handle(&self) -> SkipHandle {
+        self.skip.clone()
+    }
+}
+
+impl Display for TestPrompt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            PromptKind::Button(btn) => write!(f, "Press and release {:?} button", btn),
+            PromptKind::Axis(axis) => write!(f, "Move {:?} axis to both ends", axis),
+        }
+    }
+}
+
+/// Skips step of interactive test. It can be sent to other threads, for example to UI thread.
+#[derive(Clone, Debug, Default)]
+pub struct SkipHandle(Arc<AtomicBool>);
+
+impl SkipHandle {
+    /// Ends step as if it timed out. Events that weren't processed yet are used by next steps, so
+    /// skipping step from prompt callback doesn't consume any events.
+    pub fn skip(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    fn is_skipped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Axis that was moved, but didn't reach both ends. See `TestReport::partial_axes`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AxisRange {
+    /// Tested axis.
+    pub axis: Axis,
+    /// Lowest observed value.
+    pub min: f32,
+    /// Highest observed value.
+    pub max: f32,
+}
+
+/// Result of `run_interactive_test()`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TestReport {
+    /// Buttons that weren't pressed before their step ended.
+    pub missing_buttons: Vec<Button>,
+    /// Axes that didn't move before their step ended.
+    pub missing_axes: Vec<Axis>,
+    /// Buttons that were pressed, but not released the same number of times.
+    pub unclean_buttons: Vec<Button>,
+    /// Axes that moved, but didn't reach at least ±0.95.
+    pub partial_axes: Vec<AxisRange>,
+    /// Codes of buttons and axes that emitted events during test, but aren't mapped.
+    pub unmapped_codes: Vec<Code>,
+}
+
+impl TestReport {
+    /// Returns `true` if all elements worked as expected.
+    pub fn is_ok(&self) -> bool {
+        self.missing_buttons.is_empty()
+            && self.missing_axes.is_empty()
+            && self.unclean_buttons.is_empty()
+            && self.partial_axes.is_empty()
+            && self.unmapped_codes.is_empty()
+    }
+}
+
+/// Walks user through pressing every mapped button and moving every mapped axis of gamepad `id`
+/// and returns report with elements that didn't work. `prompt` is called at beginning of every
+/// step. Function blocks until the test is finished.
+///
+/// Step ends when user tests its element, when it is skipped with `SkipHandle` or when no event
+/// finishes it for 10 seconds. Events that are already queued are used by the test, events of
+/// other gamepads are discarded. If gamepad disconnects, elements that weren't tested are
+/// reported as missing.
+///
+/// D-pad mapped to axes is not tested, because default filters report it as buttons.
+///
+/// Not available on wasm, where events are not generated while this function blocks.
+///
+/// # Panics
+///
+/// Panics if gamepad with given ID was never observed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_interactive_test<F: FnMut(TestPrompt)>(
+    gilrs: &mut Gilrs,
+    id: GamepadId,
+    mut prompt: F,
+) -> TestReport {
+    let steps = test_steps(gilrs, id);
+    let mut report = TestReport::default();
+    let mut step_start = gilrs.time_now();
+    let mut pending: Option<Event> = None;
+    let mut disconnected = false;
+
+    for (idx, &kind) in steps.iter().enumerate() {
+        let mut check = StepCheck::new(kind);
+        if disconnected {
+            check.finish(&mut report);
+            continue;
+        }
+
+        let skip = SkipHandle::default();
+        prompt(TestPrompt {
+            kind,
+            step: idx,
+            steps: steps.len(),
+            timeout: STEP_TIMEOUT,
+            skip: skip.clone(),
+        });
+
+        // Next step starts when this one ends.
+        let deadline = step_start + STEP_TIMEOUT;
+        step_start = deadline;
+
+        loop {
+            if skip.is_skipped() {
+                step_start = gilrs.time_now();
+                break;
+            }
+
+            let ev = match pending.take().or_else(|| gilrs.next_event()) {
+                Some(ev) => ev,
+                None if gilrs.time_now() >= deadline => {
+                    step_start = gilrs.time_now();
+                    break;
+                }
+                None => {
+                    std::thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+            };
+
+            if ev.id != id {
+                continue;
+            }
+
+            if ev.time >= deadline {
+                // Event belongs to next step.
+                pending = Some(ev);
+                break;
+            }
+
+            if let EventType::Disconnected = ev.event {
+                disconnected = true;
+                break;
+            }
+
+            record_unmapped(&ev.event, &mut report);
+            check.handle(&ev.event);
+            if check.is_done() {
+                step_start = ev.time;
+                break;
+            }
+        }
+
+        check.finish(&mut report);
+    }
+
+    report
+}
+
+fn test_steps(gilrs: &Gilrs, id: GamepadId) -> Vec<PromptKind> {
+    let mapping = gilrs.gamepad(id).effective_mapping();
+    let buttons = Button::all()
+        .iter()
+        .filter(|btn| mapping.buttons.contains_key(btn))
+        .map(|&btn| PromptKind::Button(btn));
+    let axes = Axis::all()
+        .iter()
+        .filter(|&&axis| axis != Axis::DPadX && axis != Axis::DPadY)
+        .filter(|axis| mapping.axes.contains_key(axis))
+        .map(|&axis| PromptKind::Axis(axis));
+
+    buttons.chain(axes).collect()
+}
+
+fn record_unmapped(ev: &EventType, report: &mut TestReport) {
+    let code = match *ev {
+        EventType::ButtonPressed(Button::Unknown, code)
+        | EventType::ButtonReleased(Button::Unknown, code)
+        | EventType::ButtonChanged(Button::Unknown, _, code)
+        | EventType::AxisChanged(Axis::Unknown, _, code) => code,
+        _ => return,
+    };
+
+    if !report.unmapped_codes.contains(&code) {
+        report.unmapped_codes.push(code);
+    }
+}
+
+/// Tracks events of element tested in one step.
+#[derive(Debug)]
+struct StepCheck {
+    kind: PromptKind,
+    presses: u32,
+    releases: u32,
+    min: f32,
+    max: f32,
+    moved: bool,
+}
+
+impl StepCheck {
+    fn new(kind: PromptKind) -> Self {
+        StepCheck {
+            kind,
+            presses: 0,
+            releases: 0,
+            min: 0.0,
+            max: 0.0,
+            moved: false,
+        }
+    }
+
+    fn handle(&mut self, ev: &EventType) {
+        match (self.kind, ev) {
+            (PromptKind::Button(btn), &EventType::ButtonPressed(b, _)) if b == btn => {
+                self.presses += 1;
+            }
+            // Release of button that was held before step started is ignored.
+            (PromptKind::Button(btn), &EventType::ButtonReleased(b, _))
+                if b == btn && self.presses > self.releases =>
+            {
+                self.releases += 1;
+            }
+            (PromptKind::Axis(axis), &EventType::AxisChanged(a, val, _)) if a == axis => {
+                self.moved = true;
+                self.min = self.min.min(val);
+                self.max = self.max.max(val);
+            }
+            _ => (),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        match self.kind {
+            PromptKind::Button(_) => self.presses > 0 && self.presses == self.releases,
+            PromptKind::Axis(_) => self.min <= -AXIS_EXTREME && self.max >= AXIS_EXTREME,
+        }
+    }
+
+    fn finish(&self, report: &mut TestReport) {
+        match self.kind {
+            PromptKind::Button(btn) if self.presses == 0 => report.missing_buttons.push(btn),
+            PromptKind::Button(btn) if self.presses != self.releases => {
+                report.unclean_buttons.push(btn)
+            }
+            PromptKind::Axis(axis) if !self.moved => report.missing_axes.push(axis),
+            PromptKind::Axis(axis) if !self.is_done() => report.partial_axes.push(AxisRange {
+                axis,
+                min: self.min,
+                max: self.max,
+            }),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{gilrs_with_gamepad, native_ev_codes as nec, RawEventType};
+    use crate::GilrsBuilder;
+
+    #[test]
+    fn complete_run() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        for &btn in &[nec::BTN_SOUTH, nec::BTN_EAST, nec::BTN_NORTH, nec::BTN_WEST] {
+            backend.push(id, RawEventType::ButtonPressed(btn));
+            backend.push(id, RawEventType::ButtonReleased(btn));
+        }
+        backend.push(id, RawEventType::AxisValueChanged(255, nec::AXIS_RT2));
+        backend.push(id, RawEventType::AxisValueChanged(0, nec::AXIS_RT2));
+        for &axis in &[nec::AXIS_LSTICKX, nec::AXIS_LSTICKY] {
+            backend.push(id, RawEventType::AxisValueChanged(-32768, axis));
+            backend.push(id, RawEventType::AxisValueChanged(32767, axis));
+            backend.push(id, RawEventType::AxisValueChanged(0, axis));
+        }
+
+        let mut prompts = Vec::new();
+        let report = run_interactive_test(&mut gilrs, id, |prompt| prompts.push(prompt));
+        assert!(report.is_ok(), "{:?}", report);
+
+        let kinds: Vec<_> = prompts.iter().map(|prompt| prompt.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                PromptKind::Button(Button::South),
+                PromptKind::Button(Button::East),
+                PromptKind::Button(Button::North),
+                PromptKind::Button(Button::West),
+                PromptKind::Button(Button::RightTrigger2),
+                PromptKind::Axis(Axis::LeftStickX),
+                PromptKind::Axis(Axis::LeftStickY),
+            ]
+        );
+        assert_eq!(prompts[0].to_string(), "Press and release South button");
+        assert_eq!(prompts[6].steps, 7);
+    }
+
+    #[test]
+    fn incomplete_run() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.push(id, RawEventType::ButtonReleased(nec::BTN_SOUTH));
+        // East is never released, step times out when next event arrives.
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_EAST));
+        backend.advance_time(STEP_TIMEOUT);
+        // North is skipped.
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_WEST));
+        backend.push(id, RawEventType::ButtonReleased(nec::BTN_WEST));
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_C));
+        backend.push(id, RawEventType::AxisValueChanged(255, nec::AXIS_RT2));
+        backend.push(id, RawEventType::AxisValueChanged(0, nec::AXIS_RT2));
+        // Left stick X only reaches half of its range and times out.
+        backend.push(
+            id,
+            RawEventType::AxisValueChanged(-32768, nec::AXIS_LSTICKX),
+        );
+        backend.push(id, RawEventType::AxisValueChanged(16384, nec::AXIS_LSTICKX));
+        backend.advance_time(STEP_TIMEOUT * 2);
+
+        let report = run_interactive_test(&mut gilrs, id, |prompt| match prompt.kind {
+            PromptKind::Button(Button::North) | PromptKind::Axis(Axis::LeftStickY) => {
+                prompt.skip_handle().skip()
+            }
+            _ => (),
+        });
+
+        assert!(!report.is_ok());
+        assert_eq!(report.missing_buttons, [Button::North]);
+        assert_eq!(report.unclean_buttons, [Button::East]);
+        assert_eq!(report.missing_axes, [Axis::LeftStickY]);
+        assert_eq!(report.partial_axes.len(), 1);
+        assert_eq!(report.partial_axes[0].axis, Axis::LeftStickX);
+        assert!(report.partial_axes[0].min < -0.99);
+        assert!(report.partial_axes[0].max > 0.0);
+        assert!(report.partial_axes[0].max < AXIS_EXTREME);
+        assert_eq!(report.unmapped_codes, [Code(nec::BTN_C)]);
+    }
+}
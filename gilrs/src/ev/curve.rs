@@ -0,0 +1,273 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Response curves for axes.
+//!
+//! Some devices need nonlinear correction of their axes, for example worn stick that jumps from
+//! 0.0 straight to 0.4. `CurveTable` describes such correction as piecewise-linear function. Use
+//! [`Gilrs::set_axis_curve()`](../../struct.Gilrs.html#method.set_axis_curve) to apply it to
+//! values of `AxisChanged` and `ButtonChanged` events.
+//!
+//! # Example
+//!
+//! ```
+//! use gilrs::ev::curve::CurveTable;
+//!
+//! // Stick that reports 0.4 when it's barely moved.
+//! let points = [(-1.0, -1.0), (-0.4, -0.1), (0.0, 0.0), (0.4, 0.1), (1.0, 1.0)];
+//! let curve = CurveTable::from_points(&points).unwrap();
+//!
+//! assert_eq!(curve.apply(0.4), 0.1);
+//! assert!((curve.apply(0.7) - 0.55).abs() < 1e-6);
+//! assert_eq!(curve.apply(-1.0), -1.0);
+//! ```
+
+use crate::utils;
+use crate::GamepadId;
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Number of segments used to approximate `CurveTable::expo()`.
+const EXPO_SEGMENTS: usize = 32;
+
+/// Piecewise-linear mapping of axis values.
+///
+/// Curve is defined by control points `(input, output)` sorted by input. Values between control
+/// points are linearly interpolated and values outside of defined range are clamped to output of
+/// first or last point. If outputs of control points are monotonic, so is the curve.
+///
+/// Curve does not have to cover whole [-1.0, 1.0] range. Curve defined only for [0.0, 1.0] can be
+/// used for triggers and other unipolar axes, whose values are never negative.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CurveTable {
+    points: Vec<(f32, f32)>,
+}
+
+impl CurveTable {
+    /// Creates curve that doesn't change values.
+    pub fn linear() -> Self {
+        CurveTable {
+            // Middle point keeps interpolation exact.
+            points: vec![(-1.0, -1.0), (0.0, 0.0), (1.0, 1.0)],
+        }
+    }
+
+    /// Creates exponential curve `(1 - k) * x + k * x³`, commonly used to make small stick
+    /// movements more precise. `k` is clamped to [0.0, 1.0], 0.0 is linear curve.
+    pub fn expo(k: f32) -> Self {
+        let k = if k.is_nan() {
+            0.0
+        } else {
+            utils::clamp(k, 0.0, 1.0)
+        };
+        let points = (0..=EXPO_SEGMENTS)
+            .map(|i| {
+                let x = i as f32 / EXPO_SEGMENTS as f32 * 2.0 - 1.0;
+                (x, (1.0 - k) * x + k * x * x * x)
+            })
+            .collect();
+
+        CurveTable { points }
+    }
+
+    /// Creates curve from control points.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if there are less than two points, if any coordinate is outside of
+    /// [-1.0, 1.0] range, or if inputs are not strictly increasing.
+    pub fn from_points(points: &[(f32, f32)]) -> Result<Self, CurveError> {
+        if points.len() < 2 {
+            return Err(CurveError::NotEnoughPoints);
+        }
+
+        let in_range = |v: f32| (-1.0..=1.0).contains(&v);
+        for (idx, &(input, output)) in points.iter().enumerate() {
+            if !in_range(input) || !in_range(output) {
+                return Err(CurveError::OutOfRange(idx));
+            }
+        }
+
+        for (idx, pair) in points.windows(2).enumerate() {
+            if pair[0].0 >= pair[1].0 {
+                return Err(CurveError::NotSorted(idx + 1));
+            }
+        }
+
+        Ok(CurveTable {
+            points: points.to_vec(),
+        })
+    }
+
+    /// Returns control points of curve.
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    /// Returns value of curve for `input`.
+    pub fn apply(&self, input: f32) -> f32 {
+        let first = self.points[0];
+        let last = self.points[self.points.len() - 1];
+
+        if input.is_nan() {
+            input
+        } else if input <= first.0 {
+            first.1
+        } else if input >= last.0 {
+            last.1
+        } else {
+            // First point with input greater than `input`. It's never first one, because of
+            // checks above.
+            let idx = self.points.iter().position(|p| p.0 > input).unwrap();
+            let (x0, y0) = self.points[idx - 1];
+            let (x1, y1) = self.points[idx];
+
+            (y0 * (x1 - input) + y1 * (input - x0)) / (x1 - x0)
+        }
+    }
+}
+
+impl Default for CurveTable {
+    fn default() -> Self {
+        Self::linear()
+    }
+}
+
+/// The error type for `CurveTable::from_points()` and `Gilrs::set_axis_curve()`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CurveError {
+    /// Curve needs at least two control points.
+    NotEnoughPoints,
+    /// Control point with given index has coordinate outside of [-1.0, 1.0] range.
+    OutOfRange(usize),
+    /// Control point with given index has input that is not greater than input of previous one.
+    NotSorted(usize),
+    /// Gamepad with given ID was never observed.
+    UnknownGamepad(GamepadId),
+}
+
+impl Error for CurveError {}
+
+impl Display for CurveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            CurveError::NotEnoughPoints => f.write_str("curve needs at least two control points"),
+            CurveError::OutOfRange(idx) => write!(
+                f,
+                "control point {} has coordinate outside of [-1.0, 1.0] range",
+                idx
+            ),
+            CurveError::NotSorted(idx) => write!(
+                f,
+                "control point {} has input that is not greater than input of previous point",
+                idx
+            ),
+            CurveError::UnknownGamepad(id) => write!(f, "gamepad {} was never observed", id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32;
+
+    #[test]
+    fn linear() {
+        let curve = CurveTable::linear();
+        for &x in [-1.0, -0.5, 0.0, 0.3, 1.0].iter() {
+            assert_eq!(curve.apply(x), x);
+        }
+        assert_eq!(CurveTable::expo(0.0).apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn interpolation() {
+        let curve =
+            CurveTable::from_points(&[(-0.5, -1.0), (0.0, 0.0), (0.5, 0.2), (1.0, 1.0)]).unwrap();
+
+        // Exactly at control points
+        assert_eq!(curve.apply(-0.5), -1.0);
+        assert_eq!(curve.apply(0.0), 0.0);
+        assert_eq!(curve.apply(0.5), 0.2);
+        assert_eq!(curve.apply(1.0), 1.0);
+        // Between
+        assert_eq!(curve.apply(-0.25), -0.5);
+        assert!((curve.apply(0.25) - 0.1).abs() < 1e-6);
+        assert!((curve.apply(0.75) - 0.6).abs() < 1e-6);
+        // Clamped outside of defined range
+        assert_eq!(curve.apply(-0.75), -1.0);
+        assert_eq!(curve.apply(-1.0), -1.0);
+        assert_eq!(curve.apply(2.0), 1.0);
+        assert!(curve.apply(f32::NAN).is_nan());
+    }
+
+    #[test]
+    fn unipolar() {
+        let curve = CurveTable::from_points(&[(0.1, 0.0), (1.0, 1.0)]).unwrap();
+
+        assert_eq!(curve.apply(0.0), 0.0);
+        assert_eq!(curve.apply(0.1), 0.0);
+        assert!((curve.apply(0.55) - 0.5).abs() < 1e-6);
+        assert_eq!(curve.apply(1.0), 1.0);
+    }
+
+    #[test]
+    fn monotonic() {
+        let curves = [
+            CurveTable::expo(0.7),
+            CurveTable::expo(1.0),
+            CurveTable::from_points(&[(-1.0, -1.0), (-0.2, -0.19), (0.2, 0.19), (1.0, 1.0)])
+                .unwrap(),
+        ];
+
+        for curve in curves.iter() {
+            let mut last = curve.apply(-1.0);
+            for i in -1000..=1000 {
+                let val = curve.apply(i as f32 / 1000.0);
+                assert!(val >= last);
+                last = val;
+            }
+            assert_eq!(curve.apply(-1.0), -1.0);
+            assert_eq!(curve.apply(0.0), 0.0);
+            assert_eq!(curve.apply(1.0), 1.0);
+        }
+
+        // Out of range k is clamped
+        assert_eq!(CurveTable::expo(5.0), CurveTable::expo(1.0));
+        assert_eq!(CurveTable::expo(-5.0), CurveTable::expo(0.0));
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(
+            CurveTable::from_points(&[]),
+            Err(CurveError::NotEnoughPoints)
+        );
+        assert_eq!(
+            CurveTable::from_points(&[(0.0, 0.0)]),
+            Err(CurveError::NotEnoughPoints)
+        );
+        assert_eq!(
+            CurveTable::from_points(&[(0.0, 0.0), (1.0, 1.5)]),
+            Err(CurveError::OutOfRange(1))
+        );
+        assert_eq!(
+            CurveTable::from_points(&[(f32::NAN, 0.0), (1.0, 1.0)]),
+            Err(CurveError::OutOfRange(0))
+        );
+        assert_eq!(
+            CurveTable::from_points(&[(0.0, 0.0), (0.5, 0.5), (0.5, 1.0)]),
+            Err(CurveError::NotSorted(2))
+        );
+        assert_eq!(
+            CurveTable::from_points(&[(0.5, 0.0), (0.0, 1.0)]),
+            Err(CurveError::NotSorted(1))
+        );
+    }
+}
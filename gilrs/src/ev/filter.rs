@@ -72,12 +72,23 @@
 //!
 //! `FilterFn` is also implemented for all `Fn(Option<Event>, &Gilrs) -> Option<Event>`, so above
 //! example could be simplified to passing closure to `filter()` function.
+//!
+//! # Batches
+//!
+//! Some filters need to see events that come after current one before they can decide what to do
+//! with it. Such filters override `FilterFn::filter_batch()`, which gets all pending events at
+//! once. By default it calls `filter()` on every event. See
+//! [`LookaheadDebounce`](struct.LookaheadDebounce.html) for example.
 
-use crate::ev::{Axis, AxisOrBtn, Button, Code, Event, EventType};
-use crate::gamepad::{Gamepad, Gilrs};
+use crate::ev::{Axis, AxisOrBtn, Button, Code, Event, EventSource, EventType, FilterId};
+use crate::gamepad::{Gamepad, GamepadId, Gilrs};
 use crate::utils;
 
-use std::time::Duration;
+use fnv::FnvHashMap;
+
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, SystemTime};
 
 /// Discard axis events that changed less than `threshold`.
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -116,6 +127,94 @@ impl FilterFn for Jitter {
     }
 }
 
+/// Discard axis events that changed less than `threshold` since last emitted value, unless value
+/// changed sign or reached 0.0, -1.0 or 1.0.
+///
+/// Use it after `deadzone` to stop stick resting at the edge of dead zone from generating endless
+/// stream of events alternating between 0.0 and small values. Last emitted value is read from
+/// gamepad's state, so state has to be updated only with events returned from this filter (see
+/// `GilrsBuilder::set_update_state()`).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Hysteresis {
+    pub threshold: f32,
+}
+
+impl Hysteresis {
+    /// Creates new `Hysteresis` filter with threshold set to 0.05.
+    pub fn new() -> Self {
+        Hysteresis { threshold: 0.05 }
+    }
+}
+
+impl Default for Hysteresis {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FilterFn for Hysteresis {
+    fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        match ev {
+            Some(Event {
+                event: EventType::AxisChanged(_, val, nec),
+                id,
+                ..
+            }) => {
+                let last = gilrs.gamepad(id).state().value(nec);
+                let sign_changed = val * last < 0.0;
+                let is_extreme = val == 0.0 || val.abs() >= 1.0;
+
+                if sign_changed
+                    || (is_extreme && val != last)
+                    || (val - last).abs() >= self.threshold
+                {
+                    ev
+                } else {
+                    Some(Event::new(id, EventType::Dropped))
+                }
+            }
+            _ => ev,
+        }
+    }
+}
+
+/// Negates values of `AxisChanged` events for axes in `axes`.
+///
+/// This is meant for user preferences, like inverted Y axis. It doesn't depend on platform, values
+/// are already converted to convention described in [`Axis`](../enum.Axis.html) when they reach
+/// filters. To invert axes before default filters, use `GilrsBuilder::with_inverted_axes()`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct InvertAxis {
+    pub axes: HashSet<Axis>,
+}
+
+impl InvertAxis {
+    /// Creates new `InvertAxis` filter that inverts `axes`.
+    pub fn new(axes: &[Axis]) -> Self {
+        InvertAxis {
+            axes: axes.iter().cloned().collect(),
+        }
+    }
+
+    pub(crate) fn invert(&self, ev: Option<Event>) -> Option<Event> {
+        ev.map(|mut ev| {
+            if let EventType::AxisChanged(axis, ref mut val, _) = ev.event {
+                if self.axes.contains(&axis) {
+                    *val = -*val;
+                }
+            }
+
+            ev
+        })
+    }
+}
+
+impl FilterFn for InvertAxis {
+    fn filter(&self, ev: Option<Event>, _gilrs: &mut Gilrs) -> Option<Event> {
+        self.invert(ev)
+    }
+}
+
 fn apply_deadzone(x: f32, y: f32, threshold: f32) -> (f32, f32) {
     let magnitude = utils::clamp((x * x + y * y).sqrt(), 0.0, 1.0);
     if magnitude <= threshold {
@@ -133,16 +232,20 @@ pub fn deadzone(ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
             event: EventType::AxisChanged(axis, val, nec),
             id,
             time,
+            seq,
+            source,
         }) => {
             let threshold = match gilrs.gamepad(id).deadzone(nec) {
                 Some(t) => t,
                 None => return ev,
             };
 
-            if let Some((_, other_code)) = axis
+            // Sticks and d-pad use circular dead zone.
+            let other_code = axis
                 .second_axis()
-                .and_then(|axis| gilrs.gamepad(id).axis_code(axis).map(|code| (axis, code)))
-            {
+                .and_then(|other| gilrs.gamepad(id).axis_code(other));
+
+            if let Some(other_code) = other_code {
                 let other_val = gilrs.gamepad(id).state().value(other_code);
                 let val = apply_deadzone(val, other_val, threshold);
 
@@ -153,6 +256,8 @@ pub fn deadzone(ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
                         id,
                         time,
                         event: EventType::AxisChanged(axis, val.0, nec),
+                        seq,
+                        source,
                     }
                 })
             } else {
@@ -165,6 +270,8 @@ pub fn deadzone(ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
                         id,
                         time,
                         event: EventType::AxisChanged(axis, val, nec),
+                        seq,
+                        source,
                     }
                 })
             }
@@ -173,6 +280,8 @@ pub fn deadzone(ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
             event: EventType::ButtonChanged(btn, val, nec),
             id,
             time,
+            seq,
+            source,
         }) => {
             let gp = &gilrs.gamepad(id);
             let threshold = match gp.deadzone(nec) {
@@ -188,6 +297,8 @@ pub fn deadzone(ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
                     id,
                     time,
                     event: EventType::ButtonChanged(btn, val, nec),
+                    seq,
+                    source,
                 }
             })
         }
@@ -224,17 +335,23 @@ pub fn axis_dpad_to_button(ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event
             event: EventType::AxisChanged(Axis::DPadX, val, _),
             id,
             time,
+            seq,
+            ..
         }) if can_map(&gilrs.gamepad(id)) => Some(if val == 1.0 {
             Event {
                 id,
                 time,
                 event: EventType::ButtonPressed(Button::DPadRight, Code(necs::BTN_DPAD_RIGHT)),
+                seq,
+                source: EventSource::Synthetic(FilterId::AxisDpadToButton),
             }
         } else if val == -1.0 {
             Event {
                 id,
                 time,
                 event: EventType::ButtonPressed(Button::DPadLeft, Code(necs::BTN_DPAD_LEFT)),
+                seq,
+                source: EventSource::Synthetic(FilterId::AxisDpadToButton),
             }
         } else if gilrs
             .gamepad(id)
@@ -245,29 +362,39 @@ pub fn axis_dpad_to_button(ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event
                 id,
                 time,
                 event: EventType::ButtonReleased(Button::DPadRight, Code(necs::BTN_DPAD_RIGHT)),
+                seq,
+                source: EventSource::Synthetic(FilterId::AxisDpadToButton),
             }
         } else {
             Event {
                 id,
                 time,
                 event: EventType::ButtonReleased(Button::DPadLeft, Code(necs::BTN_DPAD_LEFT)),
+                seq,
+                source: EventSource::Synthetic(FilterId::AxisDpadToButton),
             }
         }),
         Some(Event {
             event: EventType::AxisChanged(Axis::DPadY, val, _),
             id,
             time,
+            seq,
+            ..
         }) if can_map(&gilrs.gamepad(id)) => Some(if val == 1.0 {
             Event {
                 id,
                 time,
                 event: EventType::ButtonPressed(Button::DPadUp, Code(necs::BTN_DPAD_UP)),
+                seq,
+                source: EventSource::Synthetic(FilterId::AxisDpadToButton),
             }
         } else if val == -1.0 {
             Event {
                 id,
                 time,
                 event: EventType::ButtonPressed(Button::DPadDown, Code(necs::BTN_DPAD_DOWN)),
+                seq,
+                source: EventSource::Synthetic(FilterId::AxisDpadToButton),
             }
         } else if gilrs
             .gamepad(id)
@@ -278,12 +405,16 @@ pub fn axis_dpad_to_button(ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event
                 id,
                 time,
                 event: EventType::ButtonReleased(Button::DPadUp, Code(necs::BTN_DPAD_UP)),
+                seq,
+                source: EventSource::Synthetic(FilterId::AxisDpadToButton),
             }
         } else {
             Event {
                 id,
                 time,
                 event: EventType::ButtonReleased(Button::DPadDown, Code(necs::BTN_DPAD_DOWN)),
+                seq,
+                source: EventSource::Synthetic(FilterId::AxisDpadToButton),
             }
         }),
         _ => ev,
@@ -313,50 +444,765 @@ impl Default for Repeat {
     }
 }
 
+impl Repeat {
+    fn next_repeated(&self, gilrs: &Gilrs) -> Option<Event> {
+        let now = gilrs.time_now();
+        for (id, gamepad) in gilrs.gamepads() {
+            for (nec, btn_data) in gamepad.state().buttons() {
+                match (
+                    btn_data.is_pressed(),
+                    btn_data.is_repeating(),
+                    now.duration_since(btn_data.timestamp()),
+                ) {
+                    (true, false, Ok(dur)) if dur >= self.after => {
+                        let btn_name = match gamepad.axis_or_btn_name(nec) {
+                            Some(AxisOrBtn::Btn(b)) => b,
+                            _ => Button::Unknown,
+                        };
+
+                        return Some(Event {
+                            id,
+                            event: EventType::ButtonRepeated(btn_name, nec),
+                            time: btn_data.timestamp() + self.after,
+                            seq: 0,
+                            source: EventSource::Synthetic(FilterId::Repeat),
+                        });
+                    }
+                    (true, true, Ok(dur)) if dur >= self.every => {
+                        let btn_name = match gamepad.axis_or_btn_name(nec) {
+                            Some(AxisOrBtn::Btn(b)) => b,
+                            _ => Button::Unknown,
+                        };
+
+                        return Some(Event {
+                            id,
+                            event: EventType::ButtonRepeated(btn_name, nec),
+                            time: btn_data.timestamp() + self.every,
+                            seq: 0,
+                            source: EventSource::Synthetic(FilterId::Repeat),
+                        });
+                    }
+                    _ => (),
+                }
+            }
+        }
+        None
+    }
+}
+
 impl FilterFn for Repeat {
     fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
         match ev {
             Some(ev) => Some(ev),
+            // Repeated events never leave `next_event()`, so they have to be numbered here.
+            None => self.next_repeated(gilrs).map(|mut ev| {
+                ev.seq = gilrs.next_seq();
+                ev
+            }),
+        }
+    }
+}
+
+/// Detects long presses and double presses.
+///
+/// `ButtonHeld` is emitted once when button has been held for at least `hold`. Its duration is
+/// always equal to `hold`. `ButtonDoublePressed` is emitted when button is pressed again less than
+/// `double_press` after previous press (time is measured between `ButtonPressed` events). Press
+/// that completes double press doesn't start a new one, so three quick presses emit only one
+/// `ButtonDoublePressed`.
+///
+/// All events are passed through unchanged and new events are returned when this filter is called
+/// with `None`, so like `Repeat`, it has to be called even if there are no new events. Timestamp
+/// of new event is the moment when its condition was met.
+#[derive(Clone, Debug)]
+pub struct Taps {
+    pub hold: Duration,
+    pub double_press: Duration,
+    buttons: RefCell<FnvHashMap<(GamepadId, Code), TapState>>,
+    pending: RefCell<VecDeque<Event>>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct TapState {
+    button: Button,
+    pressed_at: Option<SystemTime>,
+    // Press that can start double press.
+    last_press: Option<SystemTime>,
+    held_emitted: bool,
+}
+
+impl Taps {
+    /// Creates new `Taps` filter with `hold` set to 1s and `double_press` set to 300ms.
+    pub fn new() -> Self {
+        Taps {
+            hold: Duration::from_secs(1),
+            double_press: Duration::from_millis(300),
+            buttons: RefCell::new(FnvHashMap::default()),
+            pending: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    fn next_tap(&self, gilrs: &Gilrs) -> Option<Event> {
+        if let Some(ev) = self.pending.borrow_mut().pop_front() {
+            return Some(ev);
+        }
+
+        let now = gilrs.time_now();
+        let mut buttons = self.buttons.borrow_mut();
+        let held = buttons
+            .iter_mut()
+            .filter_map(|(&(id, code), state)| {
+                let deadline = state.pressed_at? + self.hold;
+                if state.held_emitted || now < deadline {
+                    None
+                } else {
+                    Some((deadline, id, code, state))
+                }
+            })
+            .min_by_key(|&(deadline, ..)| deadline);
+
+        held.map(|(time, id, code, state)| {
+            state.held_emitted = true;
+
+            Event {
+                id,
+                time,
+                event: EventType::ButtonHeld(state.button, self.hold, code),
+                seq: 0,
+                source: EventSource::Synthetic(FilterId::Taps),
+            }
+        })
+    }
+}
+
+impl Default for Taps {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FilterFn for Taps {
+    fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        let ev = match ev {
+            Some(ev) => ev,
+            // Same as with `Repeat`, new events have to be numbered here.
             None => {
-                let now = utils::time_now();
-                for (id, gamepad) in gilrs.gamepads() {
-                    for (nec, btn_data) in gamepad.state().buttons() {
-                        match (
-                            btn_data.is_pressed(),
-                            btn_data.is_repeating(),
-                            now.duration_since(btn_data.timestamp()),
-                        ) {
-                            (true, false, Ok(dur)) if dur >= self.after => {
-                                let btn_name = match gamepad.axis_or_btn_name(nec) {
-                                    Some(AxisOrBtn::Btn(b)) => b,
-                                    _ => Button::Unknown,
-                                };
-
-                                return Some(Event {
-                                    id,
-                                    event: EventType::ButtonRepeated(btn_name, nec),
-                                    time: btn_data.timestamp() + self.after,
-                                });
-                            }
-                            (true, true, Ok(dur)) if dur >= self.every => {
-                                let btn_name = match gamepad.axis_or_btn_name(nec) {
-                                    Some(AxisOrBtn::Btn(b)) => b,
-                                    _ => Button::Unknown,
-                                };
-
-                                return Some(Event {
-                                    id,
-                                    event: EventType::ButtonRepeated(btn_name, nec),
-                                    time: btn_data.timestamp() + self.every,
-                                });
-                            }
-                            _ => (),
-                        }
+                return self.next_tap(gilrs).map(|mut ev| {
+                    ev.seq = gilrs.next_seq();
+                    ev
+                })
+            }
+        };
+
+        let mut buttons = self.buttons.borrow_mut();
+        match ev.event {
+            EventType::ButtonPressed(button, code) => {
+                let state = buttons.entry((ev.id, code)).or_insert(TapState {
+                    button,
+                    pressed_at: None,
+                    last_press: None,
+                    held_emitted: false,
+                });
+                let is_double = state
+                    .last_press
+                    .and_then(|last| ev.time.duration_since(last).ok())
+                    .map(|dur| dur < self.double_press)
+                    .unwrap_or(false);
+
+                if is_double {
+                    state.last_press = None;
+                    self.pending.borrow_mut().push_back(Event {
+                        id: ev.id,
+                        time: ev.time,
+                        event: EventType::ButtonDoublePressed(button, code),
+                        seq: 0,
+                        source: EventSource::Synthetic(FilterId::Taps),
+                    });
+                } else {
+                    state.last_press = Some(ev.time);
+                }
+
+                state.button = button;
+                state.pressed_at = Some(ev.time);
+                state.held_emitted = false;
+            }
+            EventType::ButtonReleased(_, code) => {
+                if let Some(state) = buttons.get_mut(&(ev.id, code)) {
+                    state.pressed_at = None;
+                }
+            }
+            EventType::Disconnected | EventType::ConnectionLost | EventType::Suspended => {
+                buttons.retain(|&(id, _), _| id != ev.id);
+            }
+            _ => (),
+        }
+
+        Some(ev)
+    }
+}
+
+/// Suppresses button bounce – `ButtonPressed` followed by `ButtonReleased` (or the other way
+/// around) within `window`.
+///
+/// Every `ButtonPressed` and `ButtonReleased` event is held for `window`. If opposite event for
+/// the same button arrives before that, both events are dropped. Otherwise held event is returned
+/// unchanged (with its original timestamp and sequence number) when this filter is called with
+/// `None` after `window` passes, or when next event for the same button arrives. Decisions are
+/// based on timestamps of events, `ButtonChanged` events are not affected.
+///
+/// Because this filter holds events, it has to be called even if there are no new events, like
+/// `Repeat`.
+#[derive(Clone, Debug)]
+pub struct ButtonDebounce {
+    pub window: Duration,
+    pending: RefCell<FnvHashMap<(GamepadId, Code), Event>>,
+}
+
+impl ButtonDebounce {
+    /// Creates new `ButtonDebounce` filter with given `window`.
+    pub fn new(window: Duration) -> Self {
+        ButtonDebounce {
+            window,
+            pending: RefCell::new(FnvHashMap::default()),
+        }
+    }
+
+    fn is_expired(&self, held: &Event, now: SystemTime) -> bool {
+        now.duration_since(held.time)
+            .map(|dur| dur >= self.window)
+            .unwrap_or(false)
+    }
+}
+
+/// Returns code of button and whether it was pressed for `ButtonPressed` and `ButtonReleased`
+/// events.
+fn button_state(ev: &EventType) -> Option<(Code, bool)> {
+    match *ev {
+        EventType::ButtonPressed(_, code) => Some((code, true)),
+        EventType::ButtonReleased(_, code) => Some((code, false)),
+        _ => None,
+    }
+}
+
+impl FilterFn for ButtonDebounce {
+    fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        let mut pending = self.pending.borrow_mut();
+
+        let ev = match ev {
+            Some(ev) => ev,
+            None => {
+                let now = gilrs.time_now();
+                let key = pending
+                    .iter()
+                    .filter(|(_, held)| self.is_expired(held, now))
+                    .min_by_key(|(_, held)| held.time)
+                    .map(|(key, _)| *key);
+
+                return key.and_then(|key| pending.remove(&key));
+            }
+        };
+
+        let (code, is_press) = match button_state(&ev.event) {
+            Some(state) => state,
+            None => return Some(ev),
+        };
+        let key = (ev.id, code);
+        let dropped = Event::new(ev.id, EventType::Dropped);
+
+        match pending.remove(&key) {
+            Some(held) => {
+                if button_state(&held.event) == Some((code, is_press)) {
+                    // Repeated event, keep the first one.
+                    pending.insert(key, held);
+                    Some(dropped)
+                } else if !self.is_expired(&held, ev.time) {
+                    // Bounce, drop both events.
+                    Some(dropped)
+                } else {
+                    pending.insert(key, ev);
+                    Some(held)
+                }
+            }
+            None => {
+                pending.insert(key, ev);
+                Some(dropped)
+            }
+        }
+    }
+}
+
+/// Suppresses button bounce – `ButtonPressed` followed by `ButtonReleased` (or the other way
+/// around) within `window` – by looking at following events from the same batch.
+///
+/// Unlike `ButtonDebounce`, this filter never holds events, so it doesn't add any latency, but it
+/// only detects bounce if both events are passed to the same call of `filter_batch()`. If
+/// opposite event for the same button follows in less than `window`, both events are dropped.
+/// `filter()` returns events unchanged.
+///
+/// ```
+/// use gilrs::ev::filter::{FilterFn, LookaheadDebounce};
+/// use gilrs::GilrsBuilder;
+/// use std::time::Duration;
+///
+/// // State is updated after filtering, so `next_event()` must not update it too.
+/// let mut gilrs = GilrsBuilder::new().set_update_state(false).build().unwrap();
+/// let debounce = LookaheadDebounce::new(Duration::from_millis(10));
+///
+/// let mut batch = Vec::new();
+/// while let Some(ev) = gilrs.next_event() {
+///     batch.push(Some(ev));
+/// }
+///
+/// for ev in debounce.filter_batch(batch, &mut gilrs).into_iter().flatten() {
+///     gilrs.update(&ev);
+/// }
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct LookaheadDebounce {
+    pub window: Duration,
+}
+
+impl LookaheadDebounce {
+    /// Creates new `LookaheadDebounce` filter with given `window`.
+    pub fn new(window: Duration) -> Self {
+        LookaheadDebounce { window }
+    }
+}
+
+impl FilterFn for LookaheadDebounce {
+    fn filter(&self, ev: Option<Event>, _gilrs: &mut Gilrs) -> Option<Event> {
+        ev
+    }
+
+    fn filter_batch(
+        &self,
+        mut events: Vec<Option<Event>>,
+        _gilrs: &mut Gilrs,
+    ) -> Vec<Option<Event>> {
+        for idx in 0..events.len() {
+            let (id, time, code, is_press) = match events[idx] {
+                Some(ref ev) => match button_state(&ev.event) {
+                    Some((code, is_press)) => (ev.id, ev.time, code, is_press),
+                    None => continue,
+                },
+                None => continue,
+            };
+
+            // Only next event for the same button matters.
+            let next = events[idx + 1..]
+                .iter()
+                .position(|other| match *other {
+                    Some(ref other) => {
+                        other.id == id && button_state(&other.event).map(|(c, _)| c) == Some(code)
                     }
+                    None => false,
+                })
+                .map(|pos| idx + 1 + pos);
+
+            if let Some(next) = next {
+                let is_bounce = events[next]
+                    .as_ref()
+                    .map(|other| {
+                        button_state(&other.event) == Some((code, !is_press))
+                            && other
+                                .time
+                                .duration_since(time)
+                                .map(|dur| dur < self.window)
+                                .unwrap_or(true)
+                    })
+                    .unwrap_or(false);
+
+                if is_bounce {
+                    events[idx] = Some(Event::new(id, EventType::Dropped));
+                    events[next] = Some(Event::new(id, EventType::Dropped));
                 }
-                None
             }
         }
+
+        events
+    }
+}
+
+/// Limits number of `AxisChanged` events for every axis of every gamepad.
+///
+/// After event for given axis is passed through, following events for that axis are dropped
+/// until `1 / rate` seconds pass. The last of dropped events is held and returned (with its
+/// original timestamp and sequence number) when this filter is called with `None` after window
+/// ends, so the final value is never lost. Because this filter holds events, it has to be called
+/// even if there are no new events, like `Repeat`. If newer event for the same axis arrives after
+/// window ended, it's returned instead of held one.
+///
+/// `default_rate` is maximum number of events per second, `per_code` overrides it for specific
+/// axes. Rate 0 disables limiting. Only `AxisChanged` events are limited.
+///
+/// ```
+/// use gilrs::ev::filter::RateLimit;
+/// use gilrs::{Filter, GilrsBuilder};
+///
+/// let mut gilrs = GilrsBuilder::new().build().unwrap();
+/// let rate_limit = RateLimit::new(60);
+///
+/// while let Some(ev) = gilrs.next_event().filter_ev(&rate_limit, &mut gilrs) {
+///     println!("{:?}", ev);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    pub default_rate: u32,
+    pub per_code: FnvHashMap<Code, u32>,
+    axes: RefCell<FnvHashMap<(GamepadId, Code), RateWindow>>,
+}
+
+#[derive(Clone, Debug)]
+struct RateWindow {
+    start: SystemTime,
+    interval: Duration,
+    held: Option<Event>,
+}
+
+impl RateWindow {
+    fn end(&self) -> SystemTime {
+        self.start + self.interval
+    }
+}
+
+impl RateLimit {
+    /// Creates new `RateLimit` filter that passes at most `default_rate` events per second for
+    /// every axis.
+    pub fn new(default_rate: u32) -> Self {
+        RateLimit {
+            default_rate,
+            per_code: FnvHashMap::default(),
+            axes: RefCell::new(FnvHashMap::default()),
+        }
+    }
+
+    /// Sets maximum number of events per second for axis with `code`.
+    pub fn with_rate(mut self, code: Code, rate: u32) -> Self {
+        self.per_code.insert(code, rate);
+
+        self
+    }
+
+    fn interval(&self, code: Code) -> Option<Duration> {
+        let rate = self
+            .per_code
+            .get(&code)
+            .cloned()
+            .unwrap_or(self.default_rate);
+
+        if rate == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(1) / rate)
+        }
+    }
+}
+
+impl FilterFn for RateLimit {
+    fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        let mut axes = self.axes.borrow_mut();
+
+        let ev = match ev {
+            Some(ev) => ev,
+            None => {
+                let now = gilrs.time_now();
+                let window = axes
+                    .values_mut()
+                    .filter(|window| window.held.is_some() && window.end() <= now)
+                    .min_by_key(|window| window.end())?;
+
+                // Held event starts new window, so rate stays the same.
+                window.start = window.end();
+                return window.held.take();
+            }
+        };
+
+        let code = match ev.event {
+            EventType::AxisChanged(_, _, code) => code,
+            EventType::Disconnected | EventType::ConnectionLost | EventType::Suspended => {
+                axes.retain(|&(id, _), _| id != ev.id);
+                return Some(ev);
+            }
+            _ => return Some(ev),
+        };
+        let interval = match self.interval(code) {
+            Some(interval) => interval,
+            None => return Some(ev),
+        };
+
+        let key = (ev.id, code);
+        match axes.get_mut(&key) {
+            Some(window) if ev.time < window.end() => {
+                let id = ev.id;
+                window.held = Some(ev);
+                Some(Event::new(id, EventType::Dropped))
+            }
+            _ => {
+                axes.insert(
+                    key,
+                    RateWindow {
+                        start: ev.time,
+                        interval,
+                        held: None,
+                    },
+                );
+                Some(ev)
+            }
+        }
+    }
+}
+
+/// One of eight directions of stick, used by [`Snap`](struct.Snap.html).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+/// Directions in counter-clockwise order, starting from `Right`. Index multiplied by 45 is angle
+/// of direction in degrees.
+static DIRECTIONS: [Direction8; 8] = [
+    Direction8::Right,
+    Direction8::UpRight,
+    Direction8::Up,
+    Direction8::UpLeft,
+    Direction8::Left,
+    Direction8::DownLeft,
+    Direction8::Down,
+    Direction8::DownRight,
+];
+
+impl Direction8 {
+    /// Returns unit vector `(x, y)` pointing in this direction. Positive Y is up, like values of
+    /// `Axis::LeftStickY`.
+    pub fn vector(self) -> (f32, f32) {
+        let angle = self.angle().to_radians();
+        let (x, y) = (angle.cos(), angle.sin());
+
+        // Make sure that canonical directions have exact 0.0 and ±1.0.
+        let round = |v: f32| if v.abs() < 1e-6 { 0.0 } else { v };
+        (round(x), round(y))
+    }
+
+    /// Returns true for `Up`, `Right`, `Down` and `Left`.
+    pub fn is_cardinal(self) -> bool {
+        match self {
+            Direction8::Up | Direction8::Right | Direction8::Down | Direction8::Left => true,
+            _ => false,
+        }
+    }
+
+    fn index(self) -> usize {
+        DIRECTIONS.iter().position(|&dir| dir == self).unwrap()
+    }
+
+    /// Angle in degrees, counter-clockwise from `Right`.
+    fn angle(self) -> f32 {
+        self.index() as f32 * 45.0
+    }
+}
+
+/// Number of directions that `Snap` filter quantizes stick to.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SnapMode {
+    /// Up, right, down and left.
+    FourWay,
+    /// Cardinal directions and diagonals.
+    EightWay,
+}
+
+impl SnapMode {
+    /// Size of one sector in degrees.
+    fn sector(self) -> f32 {
+        match self {
+            SnapMode::FourWay => 90.0,
+            SnapMode::EightWay => 45.0,
+        }
+    }
+}
+
+/// Default radius of center region of `Snap`.
+pub(crate) const SNAP_CENTER: f32 = 0.5;
+
+/// Returns direction of stick at `(x, y)`, or `None` if it's in center region of `center` radius.
+///
+/// If `prev` is given, stick stays in that direction until it moves more than `hysteresis` degrees
+/// past the sector boundary.
+pub(crate) fn snap_direction(
+    mode: SnapMode,
+    (x, y): (f32, f32),
+    center: f32,
+    hysteresis: f32,
+    prev: Option<Direction8>,
+) -> Option<Direction8> {
+    if (x * x + y * y).sqrt() < center {
+        return None;
+    }
+
+    let mut angle = y.atan2(x).to_degrees();
+    if angle < 0.0 {
+        angle += 360.0;
+    }
+
+    let sector = mode.sector();
+    if let Some(prev) = prev {
+        let valid = mode == SnapMode::EightWay || prev.is_cardinal();
+        let dist = (angle - prev.angle()).abs() % 360.0;
+        if valid && dist.min(360.0 - dist) <= sector / 2.0 + hysteresis {
+            return Some(prev);
+        }
+    }
+
+    let sectors = (360.0 / sector) as usize;
+    let idx = (angle / sector).round() as usize % sectors;
+    Some(DIRECTIONS[idx * (sector / 45.0) as usize])
+}
+
+/// Quantizes stick input to 4 or 8 directions, for arcade style controls.
+///
+/// `AxisChanged` events of enabled sticks are rewritten so stick's (x, y) is unit vector of one of
+/// directions (see [`Direction8::vector()`](enum.Direction8.html#method.vector)), or (0.0, 0.0)
+/// if stick is closer to center than `center`. Direction depends on both axes, so when it changes,
+/// event for the other axis of stick is emitted too. Events that don't change snapped value are
+/// dropped.
+///
+/// To avoid flicker when stick rests near the boundary of two sectors, direction changes only
+/// after stick moves `hysteresis` degrees past the boundary.
+///
+/// Filter remembers raw values of axes and emitted directions, so it should see all axis events
+/// and its events should not be fed back to it. Because it may emit two events for one input
+/// event, it has to be called even if there are no new events, like `Repeat`.
+#[derive(Clone, Debug)]
+pub struct Snap {
+    pub mode: SnapMode,
+    /// Hysteresis in degrees.
+    pub hysteresis: f32,
+    /// Radius of center region.
+    pub center: f32,
+    pub left_stick: bool,
+    pub right_stick: bool,
+    // Raw values of stick axes.
+    raw: RefCell<FnvHashMap<(GamepadId, Code), f32>>,
+    // Last emitted direction of stick identified by code of its X axis.
+    directions: RefCell<FnvHashMap<(GamepadId, Code), Option<Direction8>>>,
+    pending: RefCell<VecDeque<Event>>,
+}
+
+impl Snap {
+    /// Creates new `Snap` filter for both sticks with hysteresis set to 5° and center set to 0.5.
+    pub fn new(mode: SnapMode) -> Self {
+        Snap {
+            mode,
+            hysteresis: 5.0,
+            center: SNAP_CENTER,
+            left_stick: true,
+            right_stick: true,
+            raw: RefCell::new(FnvHashMap::default()),
+            directions: RefCell::new(FnvHashMap::default()),
+            pending: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    fn is_enabled(&self, axis: Axis) -> bool {
+        match axis.stick_pair() {
+            Some((Axis::LeftStickX, _)) => self.left_stick,
+            Some((Axis::RightStickX, _)) => self.right_stick,
+            _ => false,
+        }
+    }
+
+    /// Appends events that replace `ev` to `out`.
+    fn process(&self, ev: Event, gilrs: &mut Gilrs, out: &mut VecDeque<Event>) {
+        let (axis, val, nec) = match ev.event {
+            EventType::AxisChanged(axis, val, nec) if self.is_enabled(axis) => (axis, val, nec),
+            EventType::Disconnected => {
+                self.raw.borrow_mut().retain(|&(id, _), _| id != ev.id);
+                self.directions
+                    .borrow_mut()
+                    .retain(|&(id, _), _| id != ev.id);
+                out.push_back(ev);
+                return;
+            }
+            _ => {
+                out.push_back(ev);
+                return;
+            }
+        };
+
+        let (x_axis, y_axis) = axis.stick_pair().unwrap();
+        let gamepad = gilrs.gamepad(ev.id);
+        let (x_code, y_code) = match (gamepad.axis_code(x_axis), gamepad.axis_code(y_axis)) {
+            (Some(x), Some(y)) => (x, y),
+            _ => {
+                out.push_back(ev);
+                return;
+            }
+        };
+
+        let mut raw = self.raw.borrow_mut();
+        raw.insert((ev.id, nec), val);
+        let mut value = |code| {
+            *raw.entry((ev.id, code))
+                .or_insert_with(|| gamepad.state().value(code))
+        };
+        let pos = (value(x_code), value(y_code));
+
+        let mut directions = self.directions.borrow_mut();
+        let prev = directions.get(&(ev.id, x_code)).cloned().unwrap_or(None);
+        let dir = snap_direction(self.mode, pos, self.center, self.hysteresis, prev);
+        directions.insert((ev.id, x_code), dir);
+
+        let vector = |dir: Option<Direction8>| dir.map(Direction8::vector).unwrap_or((0.0, 0.0));
+        let (prev_vec, new_vec) = (vector(prev), vector(dir));
+        let (this, other) = if axis == x_axis {
+            (
+                (new_vec.0, prev_vec.0),
+                (y_axis, y_code, new_vec.1, prev_vec.1),
+            )
+        } else {
+            (
+                (new_vec.1, prev_vec.1),
+                (x_axis, x_code, new_vec.0, prev_vec.0),
+            )
+        };
+
+        out.push_back(if this.0 != this.1 {
+            Event {
+                event: EventType::AxisChanged(axis, this.0, nec),
+                ..ev
+            }
+        } else {
+            Event::new(ev.id, EventType::Dropped)
+        });
+
+        let (other_axis, other_code, other_new, other_prev) = other;
+        if other_new != other_prev {
+            out.push_back(Event {
+                id: ev.id,
+                event: EventType::AxisChanged(other_axis, other_new, other_code),
+                time: ev.time,
+                seq: gilrs.next_seq(),
+                source: EventSource::Synthetic(FilterId::Snap),
+            });
+        }
+    }
+}
+
+impl FilterFn for Snap {
+    fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        let mut pending = self.pending.borrow_mut();
+        if let Some(ev) = ev {
+            self.process(ev, gilrs, &mut pending);
+        }
+
+        pending.pop_front()
     }
 }
 
@@ -372,6 +1218,17 @@ pub trait Filter {
 /// See module level documentation for more info.
 pub trait FilterFn {
     fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event>;
+
+    /// Filters multiple events at once. Filters that need to look at following events override
+    /// this function, default implementation calls `filter()` on every event in order.
+    ///
+    /// Same as with `filter()`, `Some(event)` must not be changed into `None`.
+    fn filter_batch(&self, events: Vec<Option<Event>>, gilrs: &mut Gilrs) -> Vec<Option<Event>> {
+        events
+            .into_iter()
+            .map(|ev| self.filter(ev, gilrs))
+            .collect()
+    }
 }
 
 impl<F> FilterFn for F
@@ -385,7 +1242,7 @@ where
 
 impl Filter for Option<Event> {
     fn filter_ev<F: FilterFn>(&self, filter: &F, gilrs: &mut Gilrs) -> Option<Event> {
-        let e = filter.filter(*self, gilrs);
+        let e = filter.filter(self.clone(), gilrs);
         debug_assert!(
             !(self.is_some() && e.is_none()),
             "Filter changed Some(event) into None. See ev::filter documentation for more info."
@@ -397,7 +1254,7 @@ impl Filter for Option<Event> {
 
 impl Filter for Event {
     fn filter_ev<F: FilterFn>(&self, filter: &F, gilrs: &mut Gilrs) -> Option<Event> {
-        let e = filter.filter(Some(*self), gilrs);
+        let e = filter.filter(Some(self.clone()), gilrs);
         debug_assert!(
             !e.is_none(),
             "Filter changed Some(event) into None. See ev::filter documentation for more info."
@@ -406,3 +1263,583 @@ impl Filter for Event {
         e
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{gilrs_with_gamepad, AxisInfo, RawEventType};
+    use crate::GilrsBuilder;
+    use gilrs_core::native_ev_codes as nec;
+
+    #[test]
+    fn deadzone_and_jitter() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let code = Code(nec::AXIS_LSTICKX);
+        let backend = gilrs.test_backend().unwrap();
+        // Inside deadzone, nothing changes
+        backend.push(id, RawEventType::AxisValueChanged(1000, nec::AXIS_LSTICKX));
+        backend.push(id, RawEventType::AxisValueChanged(32767, nec::AXIS_LSTICKX));
+        // Jitter
+        backend.push(id, RawEventType::AxisValueChanged(32700, nec::AXIS_LSTICKX));
+        backend.push(id, RawEventType::AxisValueChanged(0, nec::AXIS_LSTICKX));
+
+        assert_eq!(
+            gilrs.next_event().unwrap().event,
+            EventType::AxisChanged(Axis::LeftStickX, 1.0, code)
+        );
+        assert_eq!(
+            gilrs.next_event().unwrap().event,
+            EventType::AxisChanged(Axis::LeftStickX, 0.0, code)
+        );
+        assert!(gilrs.next_event().is_none());
+    }
+
+    #[test]
+    fn dpad_circular_deadzone() {
+        let axis = AxisInfo {
+            min: -32768,
+            max: 32767,
+            deadzone: Some(10000),
+        };
+        let builder = GilrsBuilder::new().with_default_filters(false);
+        let (mut gilrs, _) = gilrs_with_gamepad(builder);
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device(
+            "Analog d-pad",
+            [1; 16],
+            &[],
+            &[(nec::AXIS_DPADX, axis), (nec::AXIS_DPADY, axis)],
+        );
+        backend.push(id, RawEventType::AxisValueChanged(16384, nec::AXIS_DPADY));
+        // Outside dead zone only together with Y axis.
+        backend.push(id, RawEventType::AxisValueChanged(6554, nec::AXIS_DPADX));
+        gilrs.next_event();
+        gilrs.next_event();
+
+        match gilrs.next_event().filter_ev(&deadzone, &mut gilrs) {
+            Some(Event {
+                event: EventType::AxisChanged(Axis::DPadX, val, _),
+                ..
+            }) => assert!(val > 0.0 && val < 0.2),
+            e => panic!("Unexpected event {:?}", e),
+        }
+    }
+
+    #[test]
+    fn invert_axis() {
+        let events = |builder: GilrsBuilder| {
+            let (mut gilrs, id) = gilrs_with_gamepad(builder);
+            let backend = gilrs.test_backend().unwrap();
+            backend.push(id, RawEventType::AxisValueChanged(20000, nec::AXIS_LSTICKX));
+            backend.push(id, RawEventType::AxisValueChanged(20000, nec::AXIS_LSTICKY));
+
+            let mut emitted = Vec::new();
+            while let Some(ev) = gilrs.next_event() {
+                emitted.push(ev.event);
+            }
+            let state = gilrs.gamepad(id).value(Axis::LeftStickY);
+            (emitted, state)
+        };
+
+        let (normal, normal_y) = events(GilrsBuilder::new());
+        let (inverted, inverted_y) =
+            events(GilrsBuilder::new().with_inverted_axes(&[Axis::LeftStickY]));
+        assert_ne!(normal_y, 0.0);
+        assert_eq!(inverted_y, -normal_y);
+        assert_eq!(normal[0], inverted[0]);
+        match (&normal[1], &inverted[1]) {
+            (
+                &EventType::AxisChanged(Axis::LeftStickY, normal, _),
+                &EventType::AxisChanged(Axis::LeftStickY, inverted, _),
+            ) => assert_eq!(inverted, -normal),
+            e => panic!("Unexpected events {:?}", e),
+        }
+
+        // Used as regular filter
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new().with_default_filters(false));
+        let invert = InvertAxis::new(&[Axis::LeftStickX]);
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::AxisValueChanged(32767, nec::AXIS_LSTICKX));
+        assert_eq!(
+            gilrs
+                .next_event()
+                .filter_ev(&invert, &mut gilrs)
+                .unwrap()
+                .event,
+            EventType::AxisChanged(Axis::LeftStickX, -1.0, Code(nec::AXIS_LSTICKX))
+        );
+    }
+
+    #[test]
+    fn snap_boundaries() {
+        let at = |angle: f32| {
+            let angle = angle.to_radians();
+            (angle.cos(), angle.sin())
+        };
+
+        for &mode in &[SnapMode::FourWay, SnapMode::EightWay] {
+            let sector = mode.sector();
+            let hysteresis = 5.0;
+            for k in 0..(360.0 / sector) as usize {
+                let boundary = k as f32 * sector + sector / 2.0;
+                let start = boundary - sector / 2.0;
+                let steps: Vec<f32> = (0..=(sector * 2.0) as usize)
+                    .map(|i| start + i as f32 / 2.0)
+                    .collect();
+
+                let mut dir = snap_direction(mode, at(start), 0.5, hysteresis, None);
+                let first = dir;
+                let mut transitions = Vec::new();
+                for &angle in steps.iter().chain(steps.iter().rev()) {
+                    let new = snap_direction(mode, at(angle), 0.5, hysteresis, dir);
+                    if new != dir {
+                        transitions.push(angle);
+                        dir = new;
+                    }
+                }
+
+                // One transition each way, only after moving past hysteresis.
+                assert_eq!(transitions.len(), 2, "{:?} {}", mode, boundary);
+                assert!(transitions[0] >= boundary + hysteresis);
+                assert!(transitions[1] <= boundary - hysteresis);
+                assert_eq!(dir, first);
+            }
+        }
+
+        assert_eq!(
+            snap_direction(SnapMode::EightWay, (0.3, 0.3), 0.5, 5.0, None),
+            None
+        );
+        assert_eq!(
+            snap_direction(
+                SnapMode::FourWay,
+                (0.7, 0.7),
+                0.5,
+                0.0,
+                Some(Direction8::UpRight)
+            ),
+            Some(Direction8::Up)
+        );
+        assert_eq!(Direction8::Up.vector(), (0.0, 1.0));
+        assert_eq!(Direction8::Left.vector(), (-1.0, 0.0));
+        let (x, y) = Direction8::DownRight.vector();
+        let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((x - diagonal).abs() < 1e-6 && (y + diagonal).abs() < 1e-6);
+    }
+
+    #[test]
+    fn snap_filter() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new().with_default_filters(false));
+        gilrs.test_backend().unwrap().set_y_axis_reversed(false);
+        let snap = Snap::new(SnapMode::EightWay);
+
+        // Stick near boundary of Right and UpRight (22.5°), jittering by 3°.
+        let angles = [
+            0.0f32, 20.0, 25.0, 19.5, 25.5, 30.0, 20.0, 25.0, 19.0, 16.0, 20.0,
+        ];
+        let mut emitted = (0.0, 0.0);
+        let mut directions = vec![];
+        for &angle in &angles {
+            let (x, y) = (angle.to_radians().cos(), angle.to_radians().sin());
+            let backend = gilrs.test_backend().unwrap();
+            backend.push(
+                id,
+                RawEventType::AxisValueChanged((x * 30000.0) as i32, nec::AXIS_LSTICKX),
+            );
+            backend.push(
+                id,
+                RawEventType::AxisValueChanged((y * 30000.0) as i32, nec::AXIS_LSTICKY),
+            );
+
+            while let Some(ev) = gilrs.next_event().filter_ev(&snap, &mut gilrs) {
+                match ev.event {
+                    EventType::AxisChanged(Axis::LeftStickX, val, _) => emitted.0 = val,
+                    EventType::AxisChanged(Axis::LeftStickY, val, _) => emitted.1 = val,
+                    EventType::Dropped => (),
+                    _ => panic!("Unexpected event {:?}", ev),
+                }
+            }
+
+            if directions.last() != Some(&emitted) {
+                directions.push(emitted);
+            }
+        }
+
+        let right = Direction8::Right.vector();
+        let up_right = Direction8::UpRight.vector();
+        assert_eq!(directions, vec![right, up_right, right]);
+        assert_eq!(
+            gilrs
+                .gamepad(id)
+                .snapped_direction(Axis::LeftStickY, SnapMode::FourWay),
+            Some(Direction8::Right)
+        );
+    }
+
+    #[test]
+    fn hysteresis() {
+        let builder = GilrsBuilder::new()
+            .with_default_filters(false)
+            .set_update_state(false);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        let hysteresis = Hysteresis::new();
+
+        let events = |gilrs: &mut Gilrs, values: &[i32]| {
+            let backend = gilrs.test_backend().unwrap();
+            for &val in values {
+                backend.push(id, RawEventType::AxisValueChanged(val, nec::AXIS_LSTICKX));
+            }
+
+            let mut emitted = Vec::new();
+            while let Some(ev) = gilrs
+                .next_event()
+                .filter_ev(&deadzone, gilrs)
+                .filter_ev(&hysteresis, gilrs)
+            {
+                if !ev.is_dropped() {
+                    gilrs.update(&ev);
+                    emitted.push(ev.event);
+                }
+            }
+            emitted
+        };
+
+        // Noise around edge of dead zone (~3277)
+        let noise: Vec<_> = (0..100).map(|i| 3277 + (i % 2 * 2 - 1) * 150).collect();
+        assert!(events(&mut gilrs, &noise).is_empty());
+
+        let code = Code(nec::AXIS_LSTICKX);
+        let emitted = events(&mut gilrs, &[32767, 32700, 32767, -16384]);
+        assert_eq!(emitted.len(), 2);
+        assert_eq!(
+            emitted[0],
+            EventType::AxisChanged(Axis::LeftStickX, 1.0, code)
+        );
+        match emitted[1] {
+            EventType::AxisChanged(Axis::LeftStickX, val, _) => {
+                assert!(val < -0.4);
+                assert_eq!(gilrs.gamepad(id).state().value(code), val);
+            }
+            ref e => panic!("Unexpected event {:?}", e),
+        }
+
+        let noise: Vec<_> = noise.iter().map(|val| -val).collect();
+        assert_eq!(
+            events(&mut gilrs, &noise),
+            &[EventType::AxisChanged(Axis::LeftStickX, 0.0, code)]
+        );
+    }
+
+    #[test]
+    fn repeat() {
+        let builder = GilrsBuilder::new().with_default_filters(false);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        let repeat = Repeat::new();
+        let start = gilrs.test_backend().unwrap().time();
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        while let Some(ev) = gilrs.next_event().filter_ev(&repeat, &mut gilrs) {
+            gilrs.update(&ev);
+        }
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.advance_time(Duration::from_millis(400));
+        assert!(None.filter_ev(&repeat, &mut gilrs).is_none());
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.advance_time(Duration::from_millis(150));
+        let ev = None.filter_ev(&repeat, &mut gilrs).unwrap();
+        assert_eq!(
+            ev.event,
+            EventType::ButtonRepeated(Button::South, Code(nec::BTN_SOUTH))
+        );
+        assert_eq!(ev.time, start + repeat.after);
+        gilrs.update(&ev);
+
+        // Repeated every 30ms, counted from previous repeat
+        let ev = None.filter_ev(&repeat, &mut gilrs).unwrap();
+        assert_eq!(ev.time, start + repeat.after + repeat.every);
+    }
+
+    #[test]
+    fn button_debounce() {
+        let builder = GilrsBuilder::new().with_default_filters(false);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        let debounce = ButtonDebounce::new(Duration::from_millis(10));
+        let code = Code(nec::BTN_SOUTH);
+
+        // Moves clock by `step`, pushes events separated by `step` and returns emitted button
+        // events.
+        let events = |gilrs: &mut Gilrs, raw: &[RawEventType], step: u64| {
+            let backend = gilrs.test_backend().unwrap();
+            backend.advance_time(Duration::from_millis(step));
+            for ev in raw {
+                let backend = gilrs.test_backend().unwrap();
+                backend.push(id, *ev);
+                backend.advance_time(Duration::from_millis(step));
+            }
+
+            let mut emitted = Vec::new();
+            while let Some(ev) = gilrs.next_event().filter_ev(&debounce, gilrs) {
+                match ev.event {
+                    EventType::ButtonPressed(..) | EventType::ButtonReleased(..) => {
+                        emitted.push(ev.event)
+                    }
+                    _ => (),
+                }
+            }
+            emitted
+        };
+        let press = RawEventType::ButtonPressed(nec::BTN_SOUTH);
+        let release = RawEventType::ButtonReleased(nec::BTN_SOUTH);
+
+        // Bounce
+        assert!(events(&mut gilrs, &[press, release], 2).is_empty());
+        // Held until window passes
+        assert!(events(&mut gilrs, &[press], 5).is_empty());
+        assert_eq!(
+            events(&mut gilrs, &[], 5),
+            &[EventType::ButtonPressed(Button::South, code)]
+        );
+        // Bounce on release
+        assert!(events(&mut gilrs, &[release, press], 1).is_empty());
+        assert!(events(&mut gilrs, &[], 20).is_empty());
+        // Next event for the same button releases held one
+        assert_eq!(
+            events(&mut gilrs, &[release, press, release], 20),
+            &[
+                EventType::ButtonReleased(Button::South, code),
+                EventType::ButtonPressed(Button::South, code),
+                EventType::ButtonReleased(Button::South, code),
+            ]
+        );
+    }
+
+    #[test]
+    fn lookahead_debounce() {
+        let builder = GilrsBuilder::new().with_default_filters(false);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        let debounce = LookaheadDebounce::new(Duration::from_millis(10));
+        let code = Code(nec::BTN_SOUTH);
+
+        // Pushes events separated by `step` and returns emitted button events.
+        let events = |gilrs: &mut Gilrs, raw: &[RawEventType], step: u64| {
+            for ev in raw {
+                let backend = gilrs.test_backend().unwrap();
+                backend.push(id, *ev);
+                backend.advance_time(Duration::from_millis(step));
+            }
+
+            let mut batch = Vec::new();
+            while let Some(ev) = gilrs.next_event() {
+                batch.push(Some(ev));
+            }
+            batch.push(None);
+
+            let filtered = debounce.filter_batch(batch, gilrs);
+            assert_eq!(filtered.last(), Some(&None));
+            filtered
+                .into_iter()
+                .flatten()
+                .filter_map(|ev| match ev.event {
+                    EventType::ButtonPressed(..) | EventType::ButtonReleased(..) => Some(ev.event),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let press = RawEventType::ButtonPressed(nec::BTN_SOUTH);
+        let release = RawEventType::ButtonReleased(nec::BTN_SOUTH);
+        let east = RawEventType::ButtonPressed(nec::BTN_EAST);
+
+        // Bounce, events for other buttons don't interfere
+        assert_eq!(
+            events(&mut gilrs, &[press, east, release, press], 2),
+            &[
+                EventType::ButtonPressed(Button::East, Code(nec::BTN_EAST)),
+                EventType::ButtonPressed(Button::South, code),
+            ]
+        );
+        // Slower than window
+        assert_eq!(
+            events(&mut gilrs, &[release, press], 20),
+            &[
+                EventType::ButtonReleased(Button::South, code),
+                EventType::ButtonPressed(Button::South, code),
+            ]
+        );
+        // Nothing to look ahead
+        assert_eq!(
+            events(&mut gilrs, &[release], 1),
+            &[EventType::ButtonReleased(Button::South, code)]
+        );
+    }
+
+    #[test]
+    fn default_filter_batch() {
+        let builder = GilrsBuilder::new().with_default_filters(false);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        let ev = gilrs.next_event();
+        let drop_all =
+            |ev: Option<Event>, _: &mut Gilrs| ev.map(|ev| Event::new(ev.id, EventType::Dropped));
+
+        let filtered = drop_all.filter_batch(vec![ev, None], &mut gilrs);
+        assert_eq!(filtered.len(), 2);
+        assert!(filtered[0].as_ref().unwrap().is_dropped());
+        assert_eq!(filtered[1], None);
+    }
+
+    #[test]
+    fn taps() {
+        let builder = GilrsBuilder::new().with_default_filters(false);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        let taps = Taps::new();
+        let code = Code(nec::BTN_SOUTH);
+        let press = RawEventType::ButtonPressed(nec::BTN_SOUTH);
+        let release = RawEventType::ButtonReleased(nec::BTN_SOUTH);
+
+        // Pushes events separated by `step` ms and returns events emitted by filter.
+        let events = |gilrs: &mut Gilrs, raw: &[RawEventType], step: u64| {
+            for ev in raw {
+                let backend = gilrs.test_backend().unwrap();
+                backend.push(id, *ev);
+                backend.advance_time(Duration::from_millis(step));
+            }
+
+            let mut emitted = Vec::new();
+            while let Some(ev) = gilrs.next_event().filter_ev(&taps, gilrs) {
+                match ev.event {
+                    EventType::ButtonHeld(..) | EventType::ButtonDoublePressed(..) => {
+                        emitted.push((ev.event, ev.time))
+                    }
+                    _ => (),
+                }
+            }
+            emitted
+        };
+        let advance = |gilrs: &mut Gilrs, ms: u64| {
+            let backend = gilrs.test_backend().unwrap();
+            backend.advance_time(Duration::from_millis(ms));
+        };
+
+        // Hold, fired exactly once when threshold is reached
+        let start = gilrs.test_backend().unwrap().time();
+        assert!(events(&mut gilrs, &[press], 999).is_empty());
+        advance(&mut gilrs, 1);
+        assert_eq!(
+            events(&mut gilrs, &[], 0),
+            &[(
+                EventType::ButtonHeld(Button::South, taps.hold, code),
+                start + taps.hold
+            )]
+        );
+        advance(&mut gilrs, 1000);
+        assert!(events(&mut gilrs, &[release], 0).is_empty());
+
+        // Press after long pause isn't double press
+        assert!(events(&mut gilrs, &[press, release], 150).is_empty());
+        advance(&mut gilrs, 1000);
+        assert!(events(&mut gilrs, &[press, release], 100).is_empty());
+
+        // Second press exactly at the end of window
+        advance(&mut gilrs, 100);
+        assert!(events(&mut gilrs, &[press, release], 150).is_empty());
+
+        // Second press just before end of window, third one starts new double press
+        let start = gilrs.test_backend().unwrap().time();
+        assert!(events(&mut gilrs, &[press], 1).is_empty());
+        assert!(events(&mut gilrs, &[release], 298).is_empty());
+        assert_eq!(
+            events(&mut gilrs, &[press], 1),
+            &[(
+                EventType::ButtonDoublePressed(Button::South, code),
+                start + Duration::from_millis(299)
+            )]
+        );
+        assert!(events(&mut gilrs, &[release, press, release], 1).is_empty());
+        assert_eq!(
+            events(&mut gilrs, &[press], 1)[0].0,
+            EventType::ButtonDoublePressed(Button::South, code)
+        );
+    }
+
+    #[test]
+    fn rate_limit() {
+        let builder = GilrsBuilder::new().with_default_filters(false);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        let rate_limit = RateLimit::new(50);
+        let stick = Code(nec::AXIS_LSTICKX);
+
+        // 1 kHz stream for 100 ms, interleaved with button presses.
+        let mut axis_events = Vec::new();
+        let mut buttons = 0;
+        for i in 0..100 {
+            let backend = gilrs.test_backend().unwrap();
+            backend.push(
+                id,
+                RawEventType::AxisValueChanged(i * 300, nec::AXIS_LSTICKX),
+            );
+            if i % 10 == 0 {
+                backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+            }
+            backend.advance_time(Duration::from_millis(1));
+
+            while let Some(ev) = gilrs.next_event().filter_ev(&rate_limit, &mut gilrs) {
+                match ev.event {
+                    EventType::AxisChanged(_, val, code) => {
+                        assert_eq!(code, stick);
+                        axis_events.push(val);
+                    }
+                    EventType::ButtonPressed(..) => buttons += 1,
+                    _ => (),
+                }
+            }
+        }
+
+        // First event and then the last one from every 20 ms window, which always reflects
+        // current state.
+        assert_eq!(buttons, 10);
+        assert_eq!(axis_events.len(), 6);
+        let value = gilrs.gamepad(id).value(Axis::LeftStickX);
+        assert_eq!(axis_events.last(), Some(&value));
+
+        // Held value is emitted after window ends.
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::AxisValueChanged(-1000, nec::AXIS_LSTICKX));
+        assert!(gilrs
+            .next_event()
+            .filter_ev(&rate_limit, &mut gilrs)
+            .unwrap()
+            .is_dropped());
+        assert!(None.filter_ev(&rate_limit, &mut gilrs).is_none());
+        gilrs
+            .test_backend()
+            .unwrap()
+            .advance_time(Duration::from_millis(20));
+        let value = gilrs.gamepad(id).value(Axis::LeftStickX);
+        assert_eq!(
+            None.filter_ev(&rate_limit, &mut gilrs).unwrap().event,
+            EventType::AxisChanged(Axis::LeftStickX, value, stick)
+        );
+        assert!(None.filter_ev(&rate_limit, &mut gilrs).is_none());
+
+        // Codes with rate 0 are not limited.
+        let rate_limit = RateLimit::new(50).with_rate(stick, 0);
+        let backend = gilrs.test_backend().unwrap();
+        for i in 0..3 {
+            backend.push(
+                id,
+                RawEventType::AxisValueChanged(i * 1000, nec::AXIS_LSTICKX),
+            );
+        }
+        let mut count = 0;
+        while let Some(ev) = gilrs.next_event().filter_ev(&rate_limit, &mut gilrs) {
+            if let EventType::AxisChanged(..) = ev.event {
+                count += 1;
+            }
+        }
+        assert_eq!(count, 3);
+    }
+}
@@ -7,15 +7,25 @@
 
 //! Gamepad state and other event related functionality.
 
+pub mod curve;
 pub mod filter;
+#[cfg(feature = "sdl2-compat")]
+mod sdl;
 pub mod state;
 
 use std::{
+    convert::TryFrom,
     fmt::{Display, Formatter, Result as FmtResult},
-    time::SystemTime,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use crate::{constants::*, gamepad::GamepadId, utils};
+use crate::{
+    constants::*,
+    gamepad::{Gamepad, GamepadId, GamepadInfo, Gilrs, MappingSource},
+    mapping, utils,
+};
+pub use gilrs_core::EvCodeInfo;
+use uuid::Uuid;
 
 #[cfg(feature = "serde-serialize")]
 use serde::{Deserialize, Serialize};
@@ -23,19 +33,24 @@ use serde::{Deserialize, Serialize};
 /// Platform specific event code.
 ///
 /// This type represents single gamepads's element like specific axis or button.
-/// It can't be directly created, but you can get it from events or using
-/// `Gamepad`'s methods [`button_code`](crate::Gamepad::button_code) and
-/// [`axis_code`](crate::Gamepad::axis_code). If `serde-serialize` feature is
-/// enabled, `Code` can be serialized and deserialized, but keep in mind that
-/// layout **is** platform-specific. So it's not possible to serialize `Code` on
-/// Linux and deserialize it on Windows. This also apply to `Display` implementation.
+/// You can get it from events or using `Gamepad`'s methods
+/// [`button_code`](crate::Gamepad::button_code) and
+/// [`axis_code`](crate::Gamepad::axis_code). It can also be converted from and to
+/// `gilrs_core::EvCode`, which is useful when `gilrs-core` is used together with `gilrs`.
+/// If `serde-serialize` feature is enabled, `Code` can be serialized and deserialized, but keep
+/// in mind that layout **is** platform-specific. So it's not possible to serialize `Code` on
+/// Linux and deserialize it on Windows.
+///
+/// `Display` prints name of the code used by the OS, if it's known, and its raw number, e.g.
+/// `BTN_SOUTH/304` on Linux. Use [`debug_info()`](Code::debug_info) to get these as separate
+/// values.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct Code(pub(crate) gilrs_core::EvCode);
 
 impl Display for Code {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        self.0.fmt(f)
+        self.debug_info().fmt(f)
     }
 }
 
@@ -43,10 +58,33 @@ impl Code {
     pub fn into_u32(&self) -> u32 {
         self.0.into_u32()
     }
+
+    /// Returns platform, raw type and number of this code and name of constant used by the OS for
+    /// it, if it's known.
+    pub fn debug_info(&self) -> EvCodeInfo {
+        self.0.debug_info()
+    }
+
+    /// Returns `gilrs_core::EvCode` wrapped by this code.
+    pub fn into_ev_code(self) -> gilrs_core::EvCode {
+        self.0
+    }
+}
+
+impl From<gilrs_core::EvCode> for Code {
+    fn from(nec: gilrs_core::EvCode) -> Self {
+        Code(nec)
+    }
+}
+
+impl From<Code> for gilrs_core::EvCode {
+    fn from(code: Code) -> Self {
+        code.0
+    }
 }
 
 /// Holds information about gamepad event.
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct Event {
     /// Id of gamepad.
@@ -55,19 +93,73 @@ pub struct Event {
     pub event: EventType,
     /// Time when event was emitted.
     pub time: SystemTime,
+    /// Sequence number of event.
+    ///
+    /// It is assigned by `Gilrs` when event leaves `Gilrs::next_event()`, after default filters,
+    /// so every returned event, including companion events like `ButtonChanged` emitted after
+    /// `ButtonPressed`, has number greater by one than previous event. Events added with
+    /// `Gilrs::insert_event()` also get fresh number when they are returned. Numbers are
+    /// per-`Gilrs` instance, start from 1 and are never reused.
+    ///
+    /// Unlike `time`, sequence number can be used to reconstruct exact order of events from
+    /// multiple gamepads. Events that were not returned by `Gilrs` have sequence number 0.
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    pub seq: u64,
+    /// Where event came from.
+    ///
+    /// Source is compared by `PartialEq`, so event read from gamepad is not equal to otherwise
+    /// identical event created with `Event::new()`.
+    #[cfg_attr(feature = "serde-serialize", serde(default = "hardware_source"))]
+    pub source: EventSource,
 }
 
 impl Event {
-    /// Creates new event with current time.
+    /// Creates new event with current time, sequence number 0 and `EventSource::Inserted`
+    /// source. Custom filters that create events can tag them by changing `source` to
+    /// `EventSource::Synthetic(FilterId::Custom(_))`.
     pub fn new(id: GamepadId, event: EventType) -> Self {
         Event {
             id,
             event,
             time: utils::time_now(),
+            seq: 0,
+            source: EventSource::Inserted,
+        }
+    }
+
+    /// Returns true if event was read from gamepad, not created by gilrs, filter or application.
+    pub fn is_hardware(&self) -> bool {
+        self.source == EventSource::Hardware
+    }
+
+    /// Returns true if event was emitted by given button, axis or code.
+    ///
+    /// ```
+    /// use gilrs::{Axis, Button, Gilrs};
+    ///
+    /// let mut gilrs = Gilrs::new().unwrap();
+    /// while let Some(ev) = gilrs.next_event() {
+    ///     if ev.is(Button::South) {
+    ///         // South button was pressed, released, …
+    ///     } else if ev.is(Axis::LeftStickX) {
+    ///         let x = ev.event.value().unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn is<S: Into<ElementSelector>>(&self, element: S) -> bool {
+        let (el, _, code) = self.event.element();
+        match element.into() {
+            ElementSelector::Button(btn) => el == Some(AxisOrBtn::Btn(btn)),
+            ElementSelector::Axis(axis) => el == Some(AxisOrBtn::Axis(axis)),
+            ElementSelector::Code(c) => code == Some(c),
         }
     }
 
     /// Returns `Event` with `EventType::Dropped`.
+    ///
+    /// Use it in custom filters to discard an event. The returned event keeps its `id` and `time`,
+    /// so filters further down the chain can still inspect them. See
+    /// [`EventType::Dropped`](enum.EventType.html#variant.Dropped) for more info.
     pub fn drop(mut self) -> Event {
         self.event = EventType::Dropped;
 
@@ -75,12 +167,117 @@ impl Event {
     }
 
     /// Returns true if event is `Dropped` and should be ignored.
+    ///
+    /// `Gilrs::next_event()` already skips dropped events when default filters are enabled. If
+    /// you apply your own filters, check this after the last one.
     pub fn is_dropped(&self) -> bool {
         self.event == EventType::Dropped
     }
+
+    /// Returns ID of gamepad that emitted this event. Same as `id` field.
+    pub fn gamepad_id(&self) -> GamepadId {
+        self.id
+    }
+
+    /// Returns gamepad that emitted this event or `None` if `gilrs` doesn't know gamepad with
+    /// such ID. Returned gamepad may be disconnected, for example if this is `Disconnected` event.
+    pub fn gamepad<'g>(&self, gilrs: &'g Gilrs) -> Option<Gamepad<'g>> {
+        gilrs.gamepad_checked(self.id)
+    }
+
+    /// Returns gamepad that emitted this event if it's still connected.
+    pub fn connected_gamepad<'g>(&self, gilrs: &'g Gilrs) -> Option<Gamepad<'g>> {
+        gilrs.connected_gamepad(self.id)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Origin of event. See [`Event::source`](struct.Event.html#structfield.source).
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum EventSource {
+    /// Event was created from event read from platform backend. Filters that only change value
+    /// of event, like `deadzone`, keep this source.
+    Hardware,
+    /// Event was created by gilrs or by filter, not directly from event read from backend.
+    Synthetic(FilterId),
+    /// Event was created with `Event::new()`, for example by application and added with
+    /// `Gilrs::insert_event()`.
+    Inserted,
+}
+
+/// Identifies what created synthetic event, see `EventSource::Synthetic`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum FilterId {
+    /// Event created by `Gilrs` itself: `ButtonChanged` emitted together with `ButtonPressed`
+    /// or `ButtonReleased`, events from `release_all()` and `update_from_state()`, and
+    /// connection events that don't correspond to any event from backend.
+    Gilrs,
+    /// Dpad button event converted from dpad axis by `filter::axis_dpad_to_button`.
+    AxisDpadToButton,
+    /// `ButtonRepeated` event from `filter::Repeat`.
+    Repeat,
+    /// `ButtonDoublePressed` or `ButtonHeld` event from `filter::Taps`.
+    Taps,
+    /// Event for the other axis of stick from `filter::Snap`.
+    Snap,
+    /// Filter implemented outside of gilrs. Number is chosen by filter's author.
+    Custom(u32),
+}
+
+/// Events serialized before `source` was added were read from gamepads.
+#[cfg(feature = "serde-serialize")]
+fn hardware_source() -> EventSource {
+    EventSource::Hardware
+}
+
+/// Prints ID of gamepad, type of event and its data, including platform specific code, e.g.
+/// `pad 0: ButtonPressed South (BTN_SOUTH/304)`. Useful in logs and bug reports.
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "pad {}: ", self.id)?;
+
+        match self.event {
+            EventType::ButtonPressed(btn, code) => write!(f, "ButtonPressed {:?} ({})", btn, code),
+            EventType::ButtonRepeated(btn, code) => {
+                write!(f, "ButtonRepeated {:?} ({})", btn, code)
+            }
+            EventType::ButtonReleased(btn, code) => {
+                write!(f, "ButtonReleased {:?} ({})", btn, code)
+            }
+            EventType::ButtonHeld(btn, duration, code) => {
+                write!(f, "ButtonHeld {:?} {:?} ({})", btn, duration, code)
+            }
+            EventType::ButtonDoublePressed(btn, code) => {
+                write!(f, "ButtonDoublePressed {:?} ({})", btn, code)
+            }
+            EventType::ButtonChanged(btn, value, code) => {
+                write!(f, "ButtonChanged {:?} {} ({})", btn, value, code)
+            }
+            EventType::AxisChanged(axis, value, code) => {
+                write!(f, "AxisChanged {:?} {} ({})", axis, value, code)
+            }
+            EventType::Connected(ref info) => write!(f, "Connected {:?}", info.name),
+            EventType::TouchpadTouch {
+                finger,
+                x,
+                y,
+                pressed,
+            } => write!(
+                f,
+                "TouchpadTouch finger {} at ({}, {}) {}",
+                finger,
+                x,
+                y,
+                if pressed { "pressed" } else { "released" }
+            ),
+            ref ev => write!(f, "{:?}", ev),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 /// Gamepad event.
 pub enum EventType {
@@ -90,24 +287,286 @@ pub enum EventType {
     ButtonRepeated(Button, Code),
     /// Previously pressed button has been released.
     ButtonReleased(Button, Code),
+    /// Button has been held for given duration. This event can be generated by
+    /// [`ev::Taps`](filter/struct.Taps.html) event filter.
+    ButtonHeld(Button, Duration, Code),
+    /// Button has been pressed twice in short time. This event can be generated by
+    /// [`ev::Taps`](filter/struct.Taps.html) event filter.
+    ButtonDoublePressed(Button, Code),
     /// Value of button has changed. Value can be in range [0.0, 1.0].
+    ///
+    /// For buttons that are bound to an axis (analog triggers or pressure sensitive face
+    /// buttons), value is the real pressure reported by device. `ButtonPressed` and
+    /// `ButtonReleased` events for such buttons are generated based on thresholds set with
+    /// [`GilrsBuilder::set_axis_to_btn()`](../struct.GilrsBuilder.html#method.set_axis_to_btn).
     ButtonChanged(Button, f32, Code),
     /// Value of axis has changed. Value can be in range [-1.0, 1.0].
     AxisChanged(Axis, f32, Code),
     /// Gamepad has been connected. If gamepad's UUID doesn't match one of disconnected gamepads,
     /// newly connected gamepad will get new ID.
-    Connected,
+    ///
+    /// `GamepadInfo` is a snapshot of gamepad's properties at the time of connection, so it's not
+    /// necessary to call `Gilrs::gamepad()` to learn what was connected.
+    Connected(GamepadInfo),
     /// Gamepad has been disconnected. Disconnected gamepad will not generate any new events.
     Disconnected,
+    /// Gamepad has been disconnected, but it may soon reconnect. Emitted instead of
+    /// `Disconnected` if grace period was set with
+    /// [`GilrsBuilder::reconnect_grace()`](../struct.GilrsBuilder.html#method.reconnect_grace).
+    ///
+    /// Gamepad's state is reset and it's treated as disconnected. If the same device connects
+    /// again before grace period ends, `ConnectionRestored` is emitted with the same ID.
+    /// Otherwise `Disconnected` is emitted when grace period ends.
+    ConnectionLost,
+    /// Gamepad that emitted `ConnectionLost` reconnected in time. It's emitted instead of
+    /// `Connected`.
+    ConnectionRestored,
+    /// Gamepad still exists, but it stopped delivering events, for example because system
+    /// suspended it. Gamepad's state is reset, its status is `Status::Suspended` and it's not
+    /// connected until `Resumed` is emitted with the same ID. If gamepad is removed while
+    /// suspended, `Disconnected` is emitted instead.
+    ///
+    /// Currently only Linux reports it, when gamepad's event device is removed while its parent
+    /// input device is still present. It's not supported on Windows.
+    Suspended,
+    /// Gamepad that emitted `Suspended` delivers events again.
+    Resumed,
+    /// Finger touched, moved on or was lifted from gamepad's touchpad.
+    ///
+    /// `finger` identifies contact for as long as it touches the touchpad, `x` and `y` are in
+    /// [0.0, 1.0] range with (0.0, 0.0) in top left corner. When finger is lifted, `pressed` is
+    /// false and coordinates are the last known position. Only gamepads for which
+    /// [`Gamepad::has_touchpad()`](../struct.Gamepad.html#method.has_touchpad) returns true emit
+    /// this event.
+    TouchpadTouch {
+        finger: u8,
+        x: f32,
+        y: f32,
+        pressed: bool,
+    },
     /// There was an `Event`, but it was dropped by one of filters. You should ignore it.
+    ///
+    /// Custom filters should return `Dropped` when they want to discard an event, but must not
+    /// use it for events that they did not receive. In particular, filter must never turn `None`
+    /// into `Dropped` (that would make the event loop spin forever) nor `Some(event)` into `None`
+    /// (that would end the event loop before all events were processed). Filters should also
+    /// pass through `Connected` and `Disconnected` events, because dropping them makes the
+    /// application miss changes in the set of available gamepads.
+    ///
+    /// `Gilrs::next_event()` skips dropped events when default filters are enabled.
     Dropped,
 }
 
+impl EventType {
+    /// Returns `EventType::Dropped`.
+    ///
+    /// Shorthand for custom filters, e.g. `Event::new(id, EventType::drop())`.
+    pub fn drop() -> EventType {
+        EventType::Dropped
+    }
+
+    /// Returns button of `Button*` events.
+    pub fn button(&self) -> Option<Button> {
+        match self.element().0 {
+            Some(AxisOrBtn::Btn(btn)) => Some(btn),
+            _ => None,
+        }
+    }
+
+    /// Returns axis of `AxisChanged` event.
+    pub fn axis(&self) -> Option<Axis> {
+        match self.element().0 {
+            Some(AxisOrBtn::Axis(axis)) => Some(axis),
+            _ => None,
+        }
+    }
+
+    /// Returns code of element that emitted `Button*` or `AxisChanged` event.
+    pub fn code(&self) -> Option<Code> {
+        self.element().2
+    }
+
+    /// Returns value of `ButtonChanged` or `AxisChanged` event.
+    pub fn value(&self) -> Option<f32> {
+        self.element().1
+    }
+
+    /// Returns true for `ButtonPressed`, `ButtonRepeated`, `ButtonReleased`, `ButtonHeld`,
+    /// `ButtonDoublePressed` and `ButtonChanged`.
+    pub fn is_button_event(&self) -> bool {
+        self.button().is_some()
+    }
+
+    /// Returns true for `AxisChanged`.
+    pub fn is_axis_event(&self) -> bool {
+        self.axis().is_some()
+    }
+
+    // Match has no wildcard, so new variants can't be forgotten by methods above.
+    fn element(&self) -> (Option<AxisOrBtn>, Option<f32>, Option<Code>) {
+        match *self {
+            EventType::ButtonPressed(btn, code)
+            | EventType::ButtonRepeated(btn, code)
+            | EventType::ButtonReleased(btn, code)
+            | EventType::ButtonHeld(btn, _, code)
+            | EventType::ButtonDoublePressed(btn, code) => {
+                (Some(AxisOrBtn::Btn(btn)), None, Some(code))
+            }
+            EventType::ButtonChanged(btn, val, code) => {
+                (Some(AxisOrBtn::Btn(btn)), Some(val), Some(code))
+            }
+            EventType::AxisChanged(axis, val, code) => {
+                (Some(AxisOrBtn::Axis(axis)), Some(val), Some(code))
+            }
+            EventType::Connected(_)
+            | EventType::Disconnected
+            | EventType::ConnectionLost
+            | EventType::ConnectionRestored
+            | EventType::Suspended
+            | EventType::Resumed
+            | EventType::TouchpadTouch { .. }
+            | EventType::Dropped => (None, None, None),
+        }
+    }
+}
+
+/// Plain-old-data version of `Event` for use across FFI boundary.
+///
+/// Fields that don't apply to event's type are set to 0. Information about connected gamepad is
+/// not included, use `Gilrs::gamepad()` to get it.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FfiEvent {
+    /// Id of gamepad.
+    pub id: u32,
+    /// Type of event.
+    pub event_type: FfiEventType,
+    /// `Button` or `Axis` converted to `u16`.
+    pub element: u16,
+    /// Value of `Code::into_u32()` for button and axis events, finger for `TouchpadTouch`.
+    pub code: u32,
+    /// Value of button or axis. For `TouchpadTouch` it's 1.0 if finger is pressed and 0.0
+    /// otherwise. For `ButtonHeld` it's duration in seconds.
+    pub value: f32,
+    /// X coordinate of `TouchpadTouch`.
+    pub touch_x: f32,
+    /// Y coordinate of `TouchpadTouch`.
+    pub touch_y: f32,
+    /// Time when event was emitted in microseconds since UNIX epoch.
+    pub time_us: u64,
+}
+
+/// Type of `FfiEvent`. Corresponds to variants of `EventType`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum FfiEventType {
+    ButtonPressed = 0,
+    ButtonRepeated = 1,
+    ButtonReleased = 2,
+    ButtonChanged = 3,
+    AxisChanged = 4,
+    Connected = 5,
+    Disconnected = 6,
+    TouchpadTouch = 7,
+    Dropped = 8,
+    ConnectionLost = 9,
+    ConnectionRestored = 10,
+    ButtonHeld = 11,
+    ButtonDoublePressed = 12,
+    Suspended = 13,
+    Resumed = 14,
+}
+
+impl From<Event> for FfiEvent {
+    fn from(ev: Event) -> Self {
+        let mut ffi = FfiEvent {
+            id: ev.id.0 as u32,
+            event_type: FfiEventType::Dropped,
+            element: 0,
+            code: 0,
+            value: 0.0,
+            touch_x: 0.0,
+            touch_y: 0.0,
+            time_us: ev
+                .time
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() * 1_000_000 + u64::from(d.subsec_micros()))
+                .unwrap_or(0),
+        };
+
+        match ev.event {
+            EventType::ButtonPressed(btn, code) => {
+                ffi.event_type = FfiEventType::ButtonPressed;
+                ffi.element = btn as u16;
+                ffi.code = code.into_u32();
+            }
+            EventType::ButtonRepeated(btn, code) => {
+                ffi.event_type = FfiEventType::ButtonRepeated;
+                ffi.element = btn as u16;
+                ffi.code = code.into_u32();
+            }
+            EventType::ButtonReleased(btn, code) => {
+                ffi.event_type = FfiEventType::ButtonReleased;
+                ffi.element = btn as u16;
+                ffi.code = code.into_u32();
+            }
+            EventType::ButtonHeld(btn, duration, code) => {
+                ffi.event_type = FfiEventType::ButtonHeld;
+                ffi.element = btn as u16;
+                ffi.code = code.into_u32();
+                ffi.value = duration.as_secs_f32();
+            }
+            EventType::ButtonDoublePressed(btn, code) => {
+                ffi.event_type = FfiEventType::ButtonDoublePressed;
+                ffi.element = btn as u16;
+                ffi.code = code.into_u32();
+            }
+            EventType::ButtonChanged(btn, value, code) => {
+                ffi.event_type = FfiEventType::ButtonChanged;
+                ffi.element = btn as u16;
+                ffi.code = code.into_u32();
+                ffi.value = value;
+            }
+            EventType::AxisChanged(axis, value, code) => {
+                ffi.event_type = FfiEventType::AxisChanged;
+                ffi.element = axis as u16;
+                ffi.code = code.into_u32();
+                ffi.value = value;
+            }
+            EventType::Connected(_) => ffi.event_type = FfiEventType::Connected,
+            EventType::Disconnected => ffi.event_type = FfiEventType::Disconnected,
+            EventType::ConnectionLost => ffi.event_type = FfiEventType::ConnectionLost,
+            EventType::ConnectionRestored => ffi.event_type = FfiEventType::ConnectionRestored,
+            EventType::Suspended => ffi.event_type = FfiEventType::Suspended,
+            EventType::Resumed => ffi.event_type = FfiEventType::Resumed,
+            EventType::TouchpadTouch {
+                finger,
+                x,
+                y,
+                pressed,
+            } => {
+                ffi.event_type = FfiEventType::TouchpadTouch;
+                ffi.code = u32::from(finger);
+                ffi.value = if pressed { 1.0 } else { 0.0 };
+                ffi.touch_x = x;
+                ffi.touch_y = y;
+            }
+            EventType::Dropped => (),
+        }
+
+        ffi
+    }
+}
+
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 /// Gamepad's elements which state can be represented by value from 0.0 to 1.0.
 ///
+/// Buttons can be converted to `u32` and back with `From` and `TryFrom`. Values are in range
+/// `0..Button::COUNT`, `Unknown` is 0. Values of existing variants are stable across versions, so
+/// they can be stored or sent over network.
+///
 /// ![Controller layout](https://gilrs-project.gitlab.io/gilrs/img/controller.svg)
 pub enum Button {
     // Action Pad
@@ -139,6 +598,14 @@ pub enum Button {
 }
 
 impl Button {
+    /// Number of `Button` variants, including `Unknown`.
+    pub const COUNT: usize = 20;
+
+    /// Returns all buttons except `Unknown`, in order of declaration.
+    pub fn all() -> &'static [Button] {
+        &BUTTONS
+    }
+
     pub fn is_action(self) -> bool {
         use crate::Button::*;
         match self {
@@ -206,6 +673,118 @@ impl Button {
         }
         .map(Code)
     }
+
+    /// Returns name of button used in SDL mappings, for example `"a"` for `South` or `"dpup"`
+    /// for `DPadUp`. Returns `None` for `Unknown`.
+    ///
+    /// `C` and `Z` are not part of SDL2, but gilrs accepts `"c"` and `"z"` in mappings.
+    pub fn sdl_name(self) -> Option<&'static str> {
+        match self {
+            Button::South => Some("a"),
+            Button::East => Some("b"),
+            Button::North => Some("y"),
+            Button::West => Some("x"),
+            Button::C => Some("c"),
+            Button::Z => Some("z"),
+            Button::LeftTrigger => Some("leftshoulder"),
+            Button::LeftTrigger2 => Some("lefttrigger"),
+            Button::RightTrigger => Some("rightshoulder"),
+            Button::RightTrigger2 => Some("righttrigger"),
+            Button::Select => Some("back"),
+            Button::Start => Some("start"),
+            Button::Mode => Some("guide"),
+            Button::LeftThumb => Some("leftstick"),
+            Button::RightThumb => Some("rightstick"),
+            Button::DPadUp => Some("dpup"),
+            Button::DPadDown => Some("dpdown"),
+            Button::DPadLeft => Some("dpleft"),
+            Button::DPadRight => Some("dpright"),
+            Button::Unknown => None,
+        }
+    }
+
+    /// Returns label of button on Xbox controllers, for example `"A"` for `South` or `"LB"` for
+    /// `LeftTrigger`. Can be used to show button prompts.
+    pub fn to_xbox_name(self) -> &'static str {
+        match self {
+            Button::South => "A",
+            Button::East => "B",
+            Button::North => "Y",
+            Button::West => "X",
+            Button::C => "C",
+            Button::Z => "Z",
+            Button::LeftTrigger => "LB",
+            Button::LeftTrigger2 => "LT",
+            Button::RightTrigger => "RB",
+            Button::RightTrigger2 => "RT",
+            Button::Select => "View",
+            Button::Start => "Menu",
+            Button::Mode => "Xbox",
+            Button::LeftThumb => "LS",
+            Button::RightThumb => "RS",
+            Button::DPadUp => "D-Pad Up",
+            Button::DPadDown => "D-Pad Down",
+            Button::DPadLeft => "D-Pad Left",
+            Button::DPadRight => "D-Pad Right",
+            Button::Unknown => "Unknown",
+        }
+    }
+
+    /// Returns label of button on PlayStation controllers, for example `"Cross"` for `South` or
+    /// `"L1"` for `LeftTrigger`. Can be used to show button prompts.
+    pub fn to_playstation_name(self) -> &'static str {
+        match self {
+            Button::South => "Cross",
+            Button::East => "Circle",
+            Button::North => "Triangle",
+            Button::West => "Square",
+            Button::C => "C",
+            Button::Z => "Z",
+            Button::LeftTrigger => "L1",
+            Button::LeftTrigger2 => "L2",
+            Button::RightTrigger => "R1",
+            Button::RightTrigger2 => "R2",
+            Button::Select => "Share",
+            Button::Start => "Options",
+            Button::Mode => "PS",
+            Button::LeftThumb => "L3",
+            Button::RightThumb => "R3",
+            Button::DPadUp => "D-Pad Up",
+            Button::DPadDown => "D-Pad Down",
+            Button::DPadLeft => "D-Pad Left",
+            Button::DPadRight => "D-Pad Right",
+            Button::Unknown => "Unknown",
+        }
+    }
+
+    /// Returns which set of button prompts should be shown for gamepad with `uuid` and
+    /// `mapping_source`. Gamepads are recognized by USB vendor ID, except for third party gamepads
+    /// that mimic Switch layout, which are also matched by product ID. Other third party gamepads
+    /// and gamepads with nonstandard UUID get `ButtonPromptHint::Generic`. Gamepads without
+    /// mapping also get `Generic`, because their buttons are not recognized.
+    ///
+    /// See also [`Gamepad::button_prompt_hint()`](../struct.Gamepad.html#method.button_prompt_hint).
+    pub fn prompt_image_hint(uuid: [u8; 16], mapping_source: MappingSource) -> ButtonPromptHint {
+        // Microsoft, PDP, PowerA
+        const XBOX_VENDORS: [u16; 3] = [0x045e, 0x0e6f, 0x24c6];
+        const SONY: u16 = 0x054c;
+
+        let uuid = Uuid::from_bytes(uuid);
+        let vendor = match mapping::vendor_product_id(uuid) {
+            Some((vendor, _)) if mapping_source != MappingSource::None => vendor,
+            _ => return ButtonPromptHint::Generic,
+        };
+
+        if XBOX_VENDORS.contains(&vendor) {
+            ButtonPromptHint::Xbox
+        } else if vendor == SONY {
+            ButtonPromptHint::PlayStation
+        } else if mapping::is_nintendo_layout(uuid) {
+            ButtonPromptHint::Switch
+        } else {
+            ButtonPromptHint::Generic
+        }
+    }
 }
 
 impl Default for Button {
@@ -214,11 +793,56 @@ impl Default for Button {
     }
 }
 
+impl From<Button> for u32 {
+    fn from(btn: Button) -> Self {
+        btn as u32
+    }
+}
+
+impl TryFrom<u32> for Button {
+    type Error = ();
+
+    /// Returns `Err(())` if `val` is not lower than `Button::COUNT`.
+    fn try_from(val: u32) -> Result<Self, ()> {
+        Button::all()
+            .iter()
+            .cloned()
+            .chain(Some(Button::Unknown))
+            .find(|&btn| btn as u32 == val)
+            .ok_or(())
+    }
+}
+
+/// Set of on-screen button prompts that matches gamepad. Returned by
+/// [`Button::prompt_image_hint()`](enum.Button.html#method.prompt_image_hint).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum ButtonPromptHint {
+    /// Xbox controller, use `Button::to_xbox_name()`.
+    Xbox,
+    /// DualShock or DualSense, use `Button::to_playstation_name()`.
+    PlayStation,
+    /// Nintendo Switch controller or gamepad with the same layout.
+    Switch,
+    /// Unknown gamepad, generic prompts (or Xbox ones, which most PC gamepads imitate) should be
+    /// used.
+    Generic,
+}
+
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 /// Gamepad's elements which state can be represented by value from -1.0 to 1.0.
 ///
+/// On all platforms positive values of `LeftStickX`, `RightStickX` and `DPadX` mean right and
+/// positive values of `LeftStickY`, `RightStickY` and `DPadY` mean up. Values of other axes are
+/// reported in direction used by device. Use
+/// [`Gamepad::axis_direction()`](../struct.Gamepad.html#method.axis_direction) to check if gilrs
+/// flipped values reported by device.
+///
+/// Axes can be converted to `u32` and back with `From` and `TryFrom`. Values are in range
+/// `0..Axis::COUNT`, `Unknown` is 0. Values of existing variants are stable across versions.
+///
 /// ![Controller layout](https://gilrs-project.gitlab.io/gilrs/img/controller.svg)
 pub enum Axis {
     LeftStickX = AXIS_LSTICKX,
@@ -229,10 +853,24 @@ pub enum Axis {
     RightZ = AXIS_RIGHTZ,
     DPadX = AXIS_DPADX,
     DPadY = AXIS_DPADY,
+    /// Analog part of left trigger, for gamepads that report it separately from digital "click"
+    /// at the end of travel, like GameCube controller. Most gamepads report analog triggers as
+    /// `Button::LeftTrigger2`.
+    LeftTrigger2 = AXIS_LT2,
+    /// Analog part of right trigger. See `LeftTrigger2`.
+    RightTrigger2 = AXIS_RT2,
     Unknown = AXIS_UNKNOWN,
 }
 
 impl Axis {
+    /// Number of `Axis` variants, including `Unknown`.
+    pub const COUNT: usize = 11;
+
+    /// Returns all axes except `Unknown`, in order of declaration.
+    pub fn all() -> &'static [Axis] {
+        &AXES
+    }
+
     /// Returns true if axis is `LeftStickX`, `LeftStickY`, `RightStickX` or `RightStickY`.
     pub fn is_stick(self) -> bool {
         use crate::Axis::*;
@@ -242,6 +880,54 @@ impl Axis {
         }
     }
 
+    /// Returns true if axis is `LeftZ`, `RightZ`, `LeftTrigger2` or `RightTrigger2`. Most devices
+    /// use these axes for analog triggers, but mapped triggers are usually reported as
+    /// `Button::LeftTrigger2` and `Button::RightTrigger2`.
+    pub fn is_trigger(self) -> bool {
+        use crate::Axis::*;
+        self == LeftZ || self == RightZ || self == LeftTrigger2 || self == RightTrigger2
+    }
+
+    /// Returns X and Y axes of stick that this axis belongs to, or `None` if it's not a stick
+    /// axis.
+    ///
+    /// ```
+    /// use gilrs::Axis;
+    ///
+    /// assert_eq!(
+    ///     Axis::LeftStickY.stick_pair(),
+    ///     Some((Axis::LeftStickX, Axis::LeftStickY))
+    /// );
+    /// assert_eq!(Axis::DPadX.stick_pair(), None);
+    /// ```
+    pub fn stick_pair(self) -> Option<(Axis, Axis)> {
+        use crate::Axis::*;
+        match self {
+            LeftStickX | LeftStickY => Some((LeftStickX, LeftStickY)),
+            RightStickX | RightStickY => Some((RightStickX, RightStickY)),
+            _ => None,
+        }
+    }
+
+    /// Returns name of axis used in SDL mappings, for example `"leftx"` for `LeftStickX`. Returns
+    /// `None` for axes that SDL doesn't have (`DPadX`, `DPadY` and `Unknown`).
+    ///
+    /// `LeftZ`, `RightZ`, `LeftTrigger2` and `RightTrigger2` are not part of SDL2, but gilrs
+    /// accepts `"leftz"`, `"rightz"`, `"lefttrigger2"` and `"righttrigger2"` in mappings.
+    pub fn sdl_name(self) -> Option<&'static str> {
+        match self {
+            Axis::LeftStickX => Some("leftx"),
+            Axis::LeftStickY => Some("lefty"),
+            Axis::LeftZ => Some("leftz"),
+            Axis::RightStickX => Some("rightx"),
+            Axis::RightStickY => Some("righty"),
+            Axis::RightZ => Some("rightz"),
+            Axis::LeftTrigger2 => Some("lefttrigger2"),
+            Axis::RightTrigger2 => Some("righttrigger2"),
+            Axis::DPadX | Axis::DPadY | Axis::Unknown => None,
+        }
+    }
+
     /// Returns the other axis from same element of gamepad, if any.
     ///
     /// | input       | output            |
@@ -267,6 +953,72 @@ impl Axis {
     }
 }
 
+impl From<Axis> for u32 {
+    fn from(axis: Axis) -> Self {
+        axis as u32
+    }
+}
+
+impl TryFrom<u32> for Axis {
+    type Error = ();
+
+    /// Returns `Err(())` if `val` is not lower than `Axis::COUNT`.
+    fn try_from(val: u32) -> Result<Self, ()> {
+        Axis::all()
+            .iter()
+            .cloned()
+            .chain(Some(Axis::Unknown))
+            .find(|&axis| axis as u32 == val)
+            .ok_or(())
+    }
+}
+
+static BUTTONS: [Button; 19] = [
+    Button::South,
+    Button::East,
+    Button::North,
+    Button::West,
+    Button::C,
+    Button::Z,
+    Button::LeftTrigger,
+    Button::LeftTrigger2,
+    Button::RightTrigger,
+    Button::RightTrigger2,
+    Button::Select,
+    Button::Start,
+    Button::Mode,
+    Button::LeftThumb,
+    Button::RightThumb,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+];
+
+static AXES: [Axis; 10] = [
+    Axis::LeftStickX,
+    Axis::LeftStickY,
+    Axis::LeftZ,
+    Axis::RightStickX,
+    Axis::RightStickY,
+    Axis::RightZ,
+    Axis::DPadX,
+    Axis::DPadY,
+    Axis::LeftTrigger2,
+    Axis::RightTrigger2,
+];
+
+/// Direction in which gilrs reports values of an axis, relative to values reported by device. See
+/// [`Gamepad::axis_direction()`](../struct.Gamepad.html#method.axis_direction).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum AxisDirection {
+    /// Values are in the same direction as reported by device.
+    Native,
+    /// Values are negated.
+    Flipped,
+}
+
 /// Represents `Axis` or `Button`.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
@@ -274,3 +1026,215 @@ pub enum AxisOrBtn {
     Axis(Axis),
     Btn(Button),
 }
+
+/// Element of gamepad that can be checked with [`Event::is()`](struct.Event.html#method.is).
+/// Usually created from `Button`, `Axis` or `Code` with `From`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ElementSelector {
+    Button(Button),
+    Axis(Axis),
+    Code(Code),
+}
+
+impl From<Button> for ElementSelector {
+    fn from(btn: Button) -> Self {
+        ElementSelector::Button(btn)
+    }
+}
+
+impl From<Axis> for ElementSelector {
+    fn from(axis: Axis) -> Self {
+        ElementSelector::Axis(axis)
+    }
+}
+
+impl From<Code> for ElementSelector {
+    fn from(code: Code) -> Self {
+        ElementSelector::Code(code)
+    }
+}
+
+impl From<AxisOrBtn> for ElementSelector {
+    fn from(el: AxisOrBtn) -> Self {
+        match el {
+            AxisOrBtn::Axis(axis) => ElementSelector::Axis(axis),
+            AxisOrBtn::Btn(btn) => ElementSelector::Button(btn),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gilrs_core::native_ev_codes as nec;
+
+    #[test]
+    fn event_type_accessors() {
+        let btn = Code(nec::BTN_SOUTH);
+        let axis = Code(nec::AXIS_LSTICKX);
+
+        let button_events = vec![
+            EventType::ButtonPressed(Button::South, btn),
+            EventType::ButtonRepeated(Button::South, btn),
+            EventType::ButtonReleased(Button::South, btn),
+            EventType::ButtonHeld(Button::South, Duration::from_secs(1), btn),
+            EventType::ButtonDoublePressed(Button::South, btn),
+            EventType::ButtonChanged(Button::South, 0.5, btn),
+        ];
+        for event in button_events {
+            assert_eq!(event.button(), Some(Button::South), "{:?}", event);
+            assert_eq!(event.axis(), None, "{:?}", event);
+            assert_eq!(event.code(), Some(btn), "{:?}", event);
+            assert!(event.is_button_event() && !event.is_axis_event());
+
+            let ev = Event::new(GamepadId(0), event);
+            assert!(ev.is(Button::South) && ev.is(btn) && ev.is(AxisOrBtn::Btn(Button::South)));
+            assert!(!ev.is(Button::North) && !ev.is(Axis::LeftStickX) && !ev.is(axis));
+        }
+        assert_eq!(EventType::ButtonPressed(Button::South, btn).value(), None);
+        assert_eq!(
+            EventType::ButtonChanged(Button::South, 0.5, btn).value(),
+            Some(0.5)
+        );
+
+        let event = EventType::AxisChanged(Axis::LeftStickX, -0.5, axis);
+        assert_eq!(event.button(), None);
+        assert_eq!(event.axis(), Some(Axis::LeftStickX));
+        assert_eq!(event.code(), Some(axis));
+        assert_eq!(event.value(), Some(-0.5));
+        assert!(!event.is_button_event() && event.is_axis_event());
+        let ev = Event::new(GamepadId(0), event);
+        assert!(ev.is(Axis::LeftStickX) && ev.is(axis));
+        assert!(!ev.is(Axis::LeftStickY) && !ev.is(Button::South) && !ev.is(btn));
+
+        let info = GamepadInfo {
+            name: "Gamepad".to_owned(),
+            uuid: [0; 16],
+            mapping_source: MappingSource::None,
+            is_ff_supported: false,
+        };
+        let other_events = vec![
+            EventType::Connected(info),
+            EventType::Disconnected,
+            EventType::ConnectionLost,
+            EventType::ConnectionRestored,
+            EventType::Suspended,
+            EventType::Resumed,
+            EventType::TouchpadTouch {
+                finger: 0,
+                x: 0.5,
+                y: 0.5,
+                pressed: true,
+            },
+            EventType::Dropped,
+        ];
+        for event in other_events {
+            assert_eq!(event.button(), None, "{:?}", event);
+            assert_eq!(event.axis(), None, "{:?}", event);
+            assert_eq!(event.code(), None, "{:?}", event);
+            assert_eq!(event.value(), None, "{:?}", event);
+            assert!(!event.is_button_event() && !event.is_axis_event());
+
+            let ev = Event::new(GamepadId(0), event);
+            assert!(!ev.is(Button::South) && !ev.is(Axis::LeftStickX) && !ev.is(btn));
+        }
+    }
+
+    #[test]
+    fn button_prompt_names() {
+        assert_eq!(Button::South.to_xbox_name(), "A");
+        assert_eq!(Button::West.to_xbox_name(), "X");
+        assert_eq!(Button::RightTrigger2.to_xbox_name(), "RT");
+        assert_eq!(Button::South.to_playstation_name(), "Cross");
+        assert_eq!(Button::North.to_playstation_name(), "Triangle");
+        assert_eq!(Button::LeftThumb.to_playstation_name(), "L3");
+
+        for (i, a) in Button::all().iter().enumerate() {
+            for b in &Button::all()[i + 1..] {
+                assert_ne!(a.to_xbox_name(), b.to_xbox_name());
+                assert_ne!(a.to_playstation_name(), b.to_playstation_name());
+            }
+        }
+    }
+
+    #[test]
+    fn button_prompt_hint() {
+        fn usb_uuid(vendor: u16, product: u16) -> [u8; 16] {
+            let (v, p) = (vendor.to_le_bytes(), product.to_le_bytes());
+            [
+                3, 0, 0, 0, v[0], v[1], 0, 0, p[0], p[1], 0, 0, 0x10, 0x01, 0, 0,
+            ]
+        }
+        let hint = |vendor, product| {
+            Button::prompt_image_hint(usb_uuid(vendor, product), MappingSource::SdlMappings)
+        };
+
+        assert_eq!(hint(0x045e, 0x028e), ButtonPromptHint::Xbox);
+        assert_eq!(hint(0x054c, 0x0ce6), ButtonPromptHint::PlayStation);
+        assert_eq!(hint(0x057e, 0x2009), ButtonPromptHint::Switch);
+        assert_eq!(hint(0x2dc8, 0x6001), ButtonPromptHint::Switch);
+        assert_eq!(hint(0x2dc8, 0x3106), ButtonPromptHint::Generic);
+        assert_eq!(hint(0x0079, 0x0006), ButtonPromptHint::Generic);
+        assert_eq!(
+            Button::prompt_image_hint(usb_uuid(0x045e, 0x028e), MappingSource::None),
+            ButtonPromptHint::Generic
+        );
+        assert_eq!(
+            Button::prompt_image_hint([0; 16], MappingSource::Driver),
+            ButtonPromptHint::Generic
+        );
+    }
+
+    #[test]
+    fn event_display() {
+        let id = GamepadId(0);
+        let south = Code(nec::BTN_SOUTH);
+        let ev = Event::new(id, EventType::ButtonPressed(Button::South, south));
+        assert_eq!(
+            ev.to_string(),
+            format!("pad 0: ButtonPressed South ({})", south.debug_info())
+        );
+
+        let ev = Event::new(
+            id,
+            EventType::AxisChanged(Axis::LeftStickX, 0.5, Code(nec::AXIS_LSTICKX)),
+        );
+        assert!(ev
+            .to_string()
+            .starts_with("pad 0: AxisChanged LeftStickX 0.5 ("));
+        assert_eq!(
+            Event::new(id, EventType::Disconnected).to_string(),
+            "pad 0: Disconnected"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn event_display_linux() {
+        let ev = Event::new(
+            GamepadId(1),
+            EventType::ButtonReleased(Button::East, Code(nec::BTN_EAST)),
+        );
+        assert_eq!(ev.to_string(), "pad 1: ButtonReleased East (BTN_EAST/305)");
+        assert_eq!(Code(nec::AXIS_RSTICKX).to_string(), "ABS_RX/3");
+    }
+
+    #[test]
+    fn u32_round_trip() {
+        for &btn in Button::all().iter().chain(&[Button::Unknown]) {
+            assert!((u32::from(btn) as usize) < Button::COUNT);
+            assert_eq!(Button::try_from(u32::from(btn)), Ok(btn));
+        }
+        for &axis in Axis::all().iter().chain(&[Axis::Unknown]) {
+            assert!((u32::from(axis) as usize) < Axis::COUNT);
+            assert_eq!(Axis::try_from(u32::from(axis)), Ok(axis));
+        }
+
+        assert_eq!(Button::COUNT, Button::all().len() + 1);
+        assert_eq!(Axis::COUNT, Axis::all().len() + 1);
+        assert_eq!(u32::from(Button::South), 1);
+        assert_eq!(u32::from(Axis::LeftStickX), 1);
+        assert_eq!(Button::try_from(Button::COUNT as u32), Err(()));
+        assert_eq!(Axis::try_from(Axis::COUNT as u32), Err(()));
+    }
+}
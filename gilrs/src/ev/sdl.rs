@@ -0,0 +1,187 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Conversions between `Button`/`Axis` and values of SDL2's `SDL_GameControllerButton` and
+//! `SDL_GameControllerAxis`.
+
+use super::{Axis, Button};
+
+/// `Button` for every `SDL_CONTROLLER_BUTTON_*` value, in SDL's order. `MISC1`, paddles and
+/// touchpad button don't have `Button` variant.
+static SDL_BUTTONS: [Option<Button>; 21] = [
+    Some(Button::South),        // A
+    Some(Button::East),         // B
+    Some(Button::West),         // X
+    Some(Button::North),        // Y
+    Some(Button::Select),       // BACK
+    Some(Button::Mode),         // GUIDE
+    Some(Button::Start),        // START
+    Some(Button::LeftThumb),    // LEFTSTICK
+    Some(Button::RightThumb),   // RIGHTSTICK
+    Some(Button::LeftTrigger),  // LEFTSHOULDER
+    Some(Button::RightTrigger), // RIGHTSHOULDER
+    Some(Button::DPadUp),       // DPAD_UP
+    Some(Button::DPadDown),     // DPAD_DOWN
+    Some(Button::DPadLeft),     // DPAD_LEFT
+    Some(Button::DPadRight),    // DPAD_RIGHT
+    None,                       // MISC1
+    None,                       // PADDLE1
+    None,                       // PADDLE2
+    None,                       // PADDLE3
+    None,                       // PADDLE4
+    None,                       // TOUCHPAD
+];
+
+/// `Axis` for every `SDL_CONTROLLER_AXIS_*` value, in SDL's order.
+static SDL_AXES: [Axis; 6] = [
+    Axis::LeftStickX,    // LEFTX
+    Axis::LeftStickY,    // LEFTY
+    Axis::RightStickX,   // RIGHTX
+    Axis::RightStickY,   // RIGHTY
+    Axis::LeftTrigger2,  // TRIGGERLEFT
+    Axis::RightTrigger2, // TRIGGERRIGHT
+];
+
+impl Button {
+    /// Returns value of `SDL_GameControllerButton` for this button. Returns `None` for buttons
+    /// that SDL doesn't have (`C`, `Z` and `Unknown`) and for `LeftTrigger2` and
+    /// `RightTrigger2`, which SDL reports as axes `TRIGGERLEFT` and `TRIGGERRIGHT`.
+    pub fn to_sdl_index(self) -> Option<u8> {
+        SDL_BUTTONS
+            .iter()
+            .position(|&btn| btn == Some(self))
+            .map(|idx| idx as u8)
+    }
+
+    /// Returns button for `SDL_GameControllerButton` value. Returns `None` for `MISC1`, paddles
+    /// and touchpad button, which don't have `Button` variant, and for invalid values.
+    pub fn from_sdl_index(idx: u8) -> Option<Button> {
+        SDL_BUTTONS.get(idx as usize).cloned().flatten()
+    }
+}
+
+impl Axis {
+    /// Returns value of `SDL_GameControllerAxis` for this axis. Returns `None` for axes that SDL
+    /// doesn't have (`LeftZ`, `RightZ`, `DPadX`, `DPadY` and `Unknown`).
+    ///
+    /// Note that positive values of SDL's Y axes mean down, while gilrs uses up.
+    pub fn to_sdl_index(self) -> Option<u8> {
+        SDL_AXES
+            .iter()
+            .position(|&axis| axis == self)
+            .map(|idx| idx as u8)
+    }
+
+    /// Returns axis for `SDL_GameControllerAxis` value, or `None` if value is invalid.
+    pub fn from_sdl_index(idx: u8) -> Option<Axis> {
+        SDL_AXES.get(idx as usize).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Names used by SDL in mappings, in order of `SDL_GameControllerButton`.
+    const SDL_BUTTON_NAMES: [&str; 21] = [
+        "a",
+        "b",
+        "x",
+        "y",
+        "back",
+        "guide",
+        "start",
+        "leftstick",
+        "rightstick",
+        "leftshoulder",
+        "rightshoulder",
+        "dpup",
+        "dpdown",
+        "dpleft",
+        "dpright",
+        "misc1",
+        "paddle1",
+        "paddle2",
+        "paddle3",
+        "paddle4",
+        "touchpad",
+    ];
+
+    #[test]
+    fn buttons() {
+        for (idx, &name) in SDL_BUTTON_NAMES.iter().enumerate() {
+            let btn = Button::from_sdl_index(idx as u8);
+            match btn {
+                Some(btn) => {
+                    assert_eq!(btn.sdl_name(), Some(name));
+                    assert_eq!(btn.to_sdl_index(), Some(idx as u8));
+                }
+                None => assert!(Button::all().iter().all(|btn| btn.sdl_name() != Some(name))),
+            }
+        }
+
+        for idx in SDL_BUTTON_NAMES.len()..=255 {
+            assert_eq!(Button::from_sdl_index(idx as u8), None);
+        }
+
+        for &btn in Button::all().iter().chain(&[Button::Unknown]) {
+            match btn.to_sdl_index() {
+                Some(idx) => assert_eq!(Button::from_sdl_index(idx), Some(btn)),
+                None => assert!(
+                    [
+                        Button::C,
+                        Button::Z,
+                        Button::LeftTrigger2,
+                        Button::RightTrigger2,
+                        Button::Unknown
+                    ]
+                    .contains(&btn),
+                    "{:?} should have SDL index",
+                    btn
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn axes() {
+        let names = [
+            (Axis::LeftStickX, "leftx"),
+            (Axis::LeftStickY, "lefty"),
+            (Axis::RightStickX, "rightx"),
+            (Axis::RightStickY, "righty"),
+            (Axis::LeftTrigger2, "lefttrigger2"),
+            (Axis::RightTrigger2, "righttrigger2"),
+        ];
+        for (idx, &(axis, name)) in names.iter().enumerate() {
+            assert_eq!(Axis::from_sdl_index(idx as u8), Some(axis));
+            assert_eq!(axis.sdl_name(), Some(name));
+        }
+
+        for idx in names.len()..=255 {
+            assert_eq!(Axis::from_sdl_index(idx as u8), None);
+        }
+
+        for &axis in Axis::all().iter().chain(&[Axis::Unknown]) {
+            match axis.to_sdl_index() {
+                Some(idx) => assert_eq!(Axis::from_sdl_index(idx), Some(axis)),
+                None => assert!(
+                    [
+                        Axis::LeftZ,
+                        Axis::RightZ,
+                        Axis::DPadX,
+                        Axis::DPadY,
+                        Axis::Unknown
+                    ]
+                    .contains(&axis),
+                    "{:?} should have SDL index",
+                    axis
+                ),
+            }
+        }
+    }
+}
@@ -11,7 +11,7 @@ use fnv::FnvHashMap;
 
 use std::collections::hash_map;
 use std::iter::Iterator;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 /// Cached gamepad state.
 #[derive(Clone, Debug)]
@@ -49,6 +49,27 @@ impl GamepadState {
             .unwrap_or(0.0)
     }
 
+    /// Returns value of button `btn`, for example how far analog trigger is pressed. If `btn` is
+    /// not tracked as button, but as axis, returns value of that axis. Returns 0.0 when there is
+    /// no information about `btn`.
+    ///
+    /// Unlike `value()`, this method prefers button data if the same `Code` was reported both as
+    /// button and as axis.
+    pub fn button_value(&self, btn: Code) -> f32 {
+        self.element_value(btn)
+    }
+
+    /// Returns value of `el`, checking buttons first and then axes, or 0.0 when there is no
+    /// information about it. Useful for generic input display widgets that don't distinguish
+    /// between buttons and axes.
+    pub fn element_value(&self, el: Code) -> f32 {
+        self.buttons
+            .get(&el)
+            .map(|s| s.value())
+            .or_else(|| self.axes.get(&el).map(|s| s.value()))
+            .unwrap_or(0.0)
+    }
+
     /// Iterate over buttons data.
     pub fn buttons(&self) -> ButtonDataIter<'_> {
         ButtonDataIter(self.buttons.iter())
@@ -59,6 +80,15 @@ impl GamepadState {
         AxisDataIter(self.axes.iter())
     }
 
+    /// Returns the highest counter value stored in any of buttons or axes, that is value of
+    /// counter when the last event was processed. Returns 0 if there was no events.
+    pub fn last_event_counter(&self) -> u64 {
+        let buttons = self.buttons.values().map(|data| data.counter());
+        let axes = self.axes.values().map(|data| data.counter());
+
+        buttons.chain(axes).max().unwrap_or(0)
+    }
+
     /// Returns button state and when it changed.
     pub fn button_data(&self, btn: Code) -> Option<&ButtonData> {
         self.buttons.get(&btn)
@@ -69,6 +99,33 @@ impl GamepadState {
         self.axes.get(&axis)
     }
 
+    /// Returns how long `btn` was held during its last completed press. See
+    /// `ButtonData::last_press_duration()`.
+    pub fn last_press_duration(&self, btn: Code) -> Option<Duration> {
+        self.buttons
+            .get(&btn)
+            .and_then(ButtonData::last_press_duration)
+    }
+
+    /// Returns how many times `btn` was pressed, or 0 if there is no information about it. See
+    /// `ButtonData::presses_total()`.
+    pub fn presses_total(&self, btn: Code) -> u32 {
+        self.buttons
+            .get(&btn)
+            .map(ButtonData::presses_total)
+            .unwrap_or(0)
+    }
+
+    /// Forgets durations and number of presses of all buttons, for example after gamepad
+    /// disconnected.
+    pub(crate) fn clear_press_history(&mut self) {
+        for data in self.buttons.values_mut() {
+            data.pressed_at = None;
+            data.last_press_duration = None;
+            data.presses_total = 0;
+        }
+    }
+
     pub(crate) fn set_btn_pressed(
         &mut self,
         btn: Code,
@@ -76,6 +133,7 @@ impl GamepadState {
         counter: u64,
         timestamp: SystemTime,
     ) {
+        let was_pressed = self.is_pressed(btn);
         let data = self.buttons.entry(btn).or_insert_with(|| {
             ButtonData::new(
                 if pressed { 1.0 } else { 0.0 },
@@ -89,6 +147,22 @@ impl GamepadState {
         data.is_repeating = false;
         data.counter = counter;
         data.last_event_ts = timestamp;
+        if pressed {
+            data.pressed_counter = Some(counter);
+        } else {
+            data.released_counter = Some(counter);
+        }
+
+        if pressed && !was_pressed {
+            data.pressed_at = Some(timestamp);
+            data.presses_total = data.presses_total.saturating_add(1);
+        } else if !pressed && was_pressed {
+            // Clock could go backwards between events.
+            data.last_press_duration = data
+                .pressed_at
+                .take()
+                .map(|at| timestamp.duration_since(at).unwrap_or_default());
+        }
     }
 
     pub(crate) fn set_btn_repeating(&mut self, btn: Code, counter: u64, timestamp: SystemTime) {
@@ -152,6 +226,13 @@ pub struct ButtonData {
     value: f32,
     is_pressed: bool,
     is_repeating: bool,
+    // Counters of last `ButtonPressed` and `ButtonReleased` events.
+    pressed_counter: Option<u64>,
+    released_counter: Option<u64>,
+    // Time of `ButtonPressed` event of press that is still in progress.
+    pressed_at: Option<SystemTime>,
+    last_press_duration: Option<Duration>,
+    presses_total: u32,
 }
 
 impl ButtonData {
@@ -168,6 +249,11 @@ impl ButtonData {
             value,
             is_pressed: pressed,
             is_repeating: repeating,
+            pressed_counter: None,
+            released_counter: None,
+            pressed_at: None,
+            last_press_duration: None,
+            presses_total: 0,
         }
     }
 
@@ -177,6 +263,9 @@ impl ButtonData {
     }
 
     /// Returns value of button.
+    ///
+    /// For analog buttons this is the last reported pressure, digital buttons are either 0.0 or
+    /// 1.0.
     pub fn value(&self) -> f32 {
         self.value
     }
@@ -191,10 +280,39 @@ impl ButtonData {
         self.counter
     }
 
+    /// Returns `true` if button was pressed when counter had value `counter`, usually
+    /// `Gilrs::counter()` for the current frame. Unlike `counter()`, this is not affected by
+    /// `ButtonRepeated` events or changes of analog value, so repeats don't count as presses.
+    ///
+    /// If button was pressed and released in the same frame, both `is_just_pressed()` and
+    /// `is_just_released()` return `true`, even though `is_pressed()` returns `false`.
+    pub fn is_just_pressed(&self, counter: u64) -> bool {
+        self.pressed_counter == Some(counter)
+    }
+
+    /// Returns `true` if button was released when counter had value `counter`. See
+    /// `is_just_pressed()`.
+    pub fn is_just_released(&self, counter: u64) -> bool {
+        self.released_counter == Some(counter)
+    }
+
     /// Returns when button state last changed.
     pub fn timestamp(&self) -> SystemTime {
         self.last_event_ts
     }
+
+    /// Returns time between `ButtonPressed` and `ButtonReleased` events of the last completed
+    /// press, or `None` if button was never released after being pressed. Press that is still in
+    /// progress doesn't change it. Useful for telling apart taps from holds.
+    pub fn last_press_duration(&self) -> Option<Duration> {
+        self.last_press_duration
+    }
+
+    /// Returns number of times button was pressed since gamepad connected. `ButtonRepeated`
+    /// events are not counted.
+    pub fn presses_total(&self) -> u32 {
+        self.presses_total
+    }
 }
 
 /// Information about axis stored in `State`.
@@ -228,3 +346,83 @@ impl AxisData {
         self.last_event_ts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gilrs_core::native_ev_codes as nec;
+    use std::time::UNIX_EPOCH;
+
+    fn at(ms: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn press_duration() {
+        let btn = Code(nec::BTN_SOUTH);
+        let mut state = GamepadState::new();
+        assert_eq!(state.last_press_duration(btn), None);
+        assert_eq!(state.presses_total(btn), 0);
+
+        state.set_btn_pressed(btn, true, 1, at(100));
+        state.set_btn_repeating(btn, 2, at(300));
+        assert_eq!(state.last_press_duration(btn), None);
+        assert_eq!(state.presses_total(btn), 1);
+
+        state.set_btn_pressed(btn, false, 3, at(350));
+        assert_eq!(
+            state.last_press_duration(btn),
+            Some(Duration::from_millis(250))
+        );
+
+        // Tap. Analog value changes and duplicate releases don't affect press history.
+        state.set_btn_pressed(btn, true, 4, at(1000));
+        state.set_btn_value(btn, 0.5, 4, at(1010));
+        assert_eq!(
+            state.last_press_duration(btn),
+            Some(Duration::from_millis(250))
+        );
+        state.set_btn_pressed(btn, false, 5, at(1040));
+        state.set_btn_pressed(btn, false, 6, at(2000));
+        assert_eq!(
+            state.last_press_duration(btn),
+            Some(Duration::from_millis(40))
+        );
+        assert_eq!(state.presses_total(btn), 2);
+
+        // Clock went backwards.
+        state.set_btn_pressed(btn, true, 7, at(5000));
+        state.set_btn_pressed(btn, false, 8, at(4000));
+        assert_eq!(
+            state.last_press_duration(btn),
+            Some(Duration::from_millis(0))
+        );
+
+        state.clear_press_history();
+        assert_eq!(state.last_press_duration(btn), None);
+        assert_eq!(state.presses_total(btn), 0);
+        assert!(!state.is_pressed(btn));
+    }
+
+    #[test]
+    fn element_value() {
+        let trigger = Code(nec::BTN_LT2);
+        let stick = Code(nec::AXIS_LSTICKX);
+        let mut state = GamepadState::new();
+        assert_eq!(state.button_value(trigger), 0.0);
+        assert_eq!(state.element_value(stick), 0.0);
+
+        state.set_btn_value(trigger, 0.75, 1, at(0));
+        state.update_axis(stick, AxisData::new(-0.5, 2, at(0)));
+        assert_eq!(state.button_value(trigger), 0.75);
+        assert_eq!(state.button_value(stick), -0.5);
+        assert_eq!(state.element_value(trigger), 0.75);
+        assert_eq!(state.element_value(stick), -0.5);
+
+        // Buttons have priority over axes.
+        state.update_axis(trigger, AxisData::new(0.25, 3, at(0)));
+        assert_eq!(state.element_value(trigger), 0.75);
+        assert_eq!(state.button_value(trigger), 0.75);
+        assert_eq!(state.value(trigger), 0.25);
+    }
+}
@@ -7,7 +7,7 @@
 
 use std::ops::Mul;
 
-use super::time::Ticks;
+use super::time::{Ticks, DEFAULT_TICK_DURATION};
 
 /// Kind of [`BaseEffect`](struct.BaseEffect.html).
 ///
@@ -21,7 +21,7 @@ pub enum BaseEffectType {
 }
 
 impl BaseEffectType {
-    fn magnitude(&self) -> u16 {
+    pub(super) fn magnitude(&self) -> u16 {
         match *self {
             BaseEffectType::Weak { magnitude } => magnitude,
             BaseEffectType::Strong { magnitude } => magnitude,
@@ -144,7 +144,7 @@ impl Default for Replay {
     fn default() -> Self {
         Replay {
             after: Ticks(0),
-            play_for: Ticks(1),
+            play_for: Ticks::from(DEFAULT_TICK_DURATION),
             with_delay: Ticks(0),
         }
     }
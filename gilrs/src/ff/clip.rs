@@ -0,0 +1,370 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use std::u16;
+
+use super::base_effect::{BaseEffect, BaseEffectType, Replay};
+use super::time::{Repeat, Ticks};
+use super::{Effect, EffectBuilder, Error};
+use crate::gamepad::{GamepadId, Gilrs};
+use crate::utils;
+
+/// One part of [`Clip`](struct.Clip.html). Magnitudes are in \[0.0, 1.0\] range.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ClipSegment {
+    /// How long segment should be played.
+    pub duration: Duration,
+    /// Magnitude of strong motor.
+    pub strong: f32,
+    /// Magnitude of weak motor.
+    pub weak: f32,
+}
+
+/// Vibration pattern described as data.
+///
+/// Clip is a sequence of segments, each with duration and magnitude of strong and weak motor. It
+/// can be created with [`ClipBuilder`](struct.ClipBuilder.html) or parsed from string in
+/// `duration:strong,weak;duration:strong,weak;…` format, where duration is in seconds and
+/// magnitudes are in \[0.0, 1.0\] range.
+///
+/// # Timing
+///
+/// Force feedback server works in ticks, so end of each segment, measured from the start of clip,
+/// is rounded to the nearest tick and the next segment starts there. Segment that would be shorter
+/// than one tick after rounding is extended to exactly one tick. This delays only the start of
+/// the next segment, its end is still rounded from the start of clip, so errors don't accumulate.
+/// Segments are never merged, so each of them is always played with its own magnitude.
+///
+/// ```
+/// use gilrs::ff::Clip;
+///
+/// let heartbeat: Clip = "0.1:1.0,0.5;0.05:0,0;0.2:0.3,0.6".parse().unwrap();
+/// assert_eq!(heartbeat.segments().len(), 3);
+/// ```
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Clip {
+    segments: Vec<ClipSegment>,
+}
+
+impl Clip {
+    /// Returns all segments of clip.
+    pub fn segments(&self) -> &[ClipSegment] {
+        &self.segments
+    }
+
+    /// Returns sum of durations of all segments. Actual playback may be slightly shorter or longer,
+    /// see [timing](#timing).
+    pub fn duration(&self) -> Duration {
+        self.segments
+            .iter()
+            .fold(Duration::from_secs(0), |acc, s| acc + s.duration)
+    }
+
+    /// Creates one-shot effect from clip and plays it on gamepad with `id`.
+    ///
+    /// Playback is stopped when all handles to returned effect are dropped, so keep it alive at
+    /// least for `duration()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Disconnected(id)` or `Error::FfNotSupported(id)` if gamepad is not connected
+    /// or doesn't support force feedback.
+    pub fn play_on(&self, gilrs: &mut Gilrs, id: GamepadId) -> Result<Effect, Error> {
+        let (base_effects, total) = self.schedule(gilrs.ff_tick_duration());
+
+        let mut builder = EffectBuilder::new();
+        for effect in base_effects {
+            builder.add_effect(effect);
+        }
+        let effect = builder
+            .gamepads(&[id])
+            .repeat(Repeat::For(total))
+            .finish(gilrs)?;
+        effect.play()?;
+
+        Ok(effect)
+    }
+
+    /// Converts segments to base effects, with start and end of each segment rounded to multiple
+    /// of `tick_duration`. Returns them with total duration of clip.
+    fn schedule(&self, tick_duration: Duration) -> (Vec<BaseEffect>, Ticks) {
+        let tick = Ticks::from(tick_duration).0.max(1);
+        let mut effects = Vec::with_capacity(self.segments.len() * 2);
+        let mut elapsed_ms = 0;
+        // In ticks.
+        let mut end = 0;
+
+        for segment in &self.segments {
+            elapsed_ms +=
+                segment.duration.as_secs() * 1000 + u64::from(segment.duration.subsec_millis());
+            let start = end;
+            let rounded_end = (elapsed_ms + tick / 2) / tick;
+            end = rounded_end.max(start + 1);
+
+            let scheduling = Replay {
+                after: Ticks(start * tick),
+                play_for: Ticks((end - start) * tick),
+                with_delay: Ticks(0),
+            };
+            let strong = to_magnitude(segment.strong);
+            let weak = to_magnitude(segment.weak);
+
+            if strong > 0 {
+                effects.push(BaseEffect {
+                    kind: BaseEffectType::Strong { magnitude: strong },
+                    scheduling,
+                    envelope: Default::default(),
+                });
+            }
+            if weak > 0 {
+                effects.push(BaseEffect {
+                    kind: BaseEffectType::Weak { magnitude: weak },
+                    scheduling,
+                    envelope: Default::default(),
+                });
+            }
+        }
+
+        // Make sure that no segment will be repeated before clip ends.
+        for effect in &mut effects {
+            effect.scheduling.with_delay = Ticks(end * tick) - effect.scheduling.play_for;
+        }
+
+        (effects, Ticks(end * tick))
+    }
+}
+
+fn to_magnitude(val: f32) -> u16 {
+    (utils::clamp(val, 0.0, 1.0) * f32::from(u16::MAX)) as u16
+}
+
+impl FromStr for Clip {
+    type Err = ParseClipError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut builder = ClipBuilder::new();
+
+        for (idx, segment) in s.trim().split(';').enumerate() {
+            let mut split = segment.splitn(2, ':');
+            let duration = split.next().map(str::trim).unwrap_or("");
+            let magnitudes = split.next().ok_or(ParseClipError::InvalidSegment(idx))?;
+
+            let duration = match duration.parse::<f64>() {
+                Ok(d) if d.is_finite() && d >= 0.0 => {
+                    Duration::from_micros((d * 1e6).round() as u64)
+                }
+                _ => return Err(ParseClipError::InvalidDuration(idx)),
+            };
+
+            let mut magnitudes = magnitudes.split(',').map(|m| m.trim().parse::<f32>());
+            let (strong, weak) = match (magnitudes.next(), magnitudes.next(), magnitudes.next()) {
+                (Some(Ok(strong)), Some(Ok(weak)), None) => (strong, weak),
+                (Some(_), Some(_), None) => return Err(ParseClipError::InvalidMagnitude(idx)),
+                _ => return Err(ParseClipError::InvalidSegment(idx)),
+            };
+
+            if !(0.0..=1.0).contains(&strong) || !(0.0..=1.0).contains(&weak) {
+                return Err(ParseClipError::InvalidMagnitude(idx));
+            }
+
+            builder.segment(duration, strong, weak);
+        }
+
+        Ok(builder.finish())
+    }
+}
+
+/// Creates new [`Clip`](struct.Clip.html).
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ClipBuilder {
+    segments: Vec<ClipSegment>,
+}
+
+impl ClipBuilder {
+    /// Creates builder without any segments.
+    pub fn new() -> Self {
+        ClipBuilder {
+            segments: Vec::new(),
+        }
+    }
+
+    /// Adds segment at the end of clip. `strong` and `weak` will be clamped to \[0.0, 1.0\].
+    pub fn segment(&mut self, duration: Duration, strong: f32, weak: f32) -> &mut Self {
+        self.segments.push(ClipSegment {
+            duration,
+            strong: utils::clamp(strong, 0.0, 1.0),
+            weak: utils::clamp(weak, 0.0, 1.0),
+        });
+        self
+    }
+
+    /// Adds pause at the end of clip.
+    pub fn pause(&mut self, duration: Duration) -> &mut Self {
+        self.segment(duration, 0.0, 0.0)
+    }
+
+    /// Creates clip.
+    pub fn finish(&mut self) -> Clip {
+        Clip {
+            segments: self.segments.clone(),
+        }
+    }
+}
+
+/// Error that can be returned when parsing [`Clip`](struct.Clip.html). Each variant contains
+/// index of invalid segment.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum ParseClipError {
+    /// Segment is not in `duration:strong,weak` format.
+    InvalidSegment(usize),
+    /// Duration is not a non-negative number.
+    InvalidDuration(usize),
+    /// Magnitude is not a number in \[0.0, 1.0\] range.
+    InvalidMagnitude(usize),
+}
+
+impl StdError for ParseClipError {}
+
+impl fmt::Display for ParseClipError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseClipError::InvalidSegment(idx) => fmt.write_fmt(format_args!(
+                "segment {} is not in `duration:strong,weak` format",
+                idx
+            )),
+            ParseClipError::InvalidDuration(idx) => {
+                fmt.write_fmt(format_args!("segment {} has invalid duration", idx))
+            }
+            ParseClipError::InvalidMagnitude(idx) => {
+                fmt.write_fmt(format_args!("segment {} has invalid magnitude", idx))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ff::DEFAULT_TICK_DURATION;
+
+    /// Returns duration of `ticks` default ticks.
+    fn t(ticks: u64) -> Ticks {
+        Ticks(ticks * 50)
+    }
+
+    #[test]
+    fn parse() {
+        let clip: Clip = "0.1:1.0,0.5; 0.05:0,0;0.2:0.3,0.6".parse().unwrap();
+        assert_eq!(
+            clip.segments(),
+            &[
+                ClipSegment {
+                    duration: Duration::from_millis(100),
+                    strong: 1.0,
+                    weak: 0.5
+                },
+                ClipSegment {
+                    duration: Duration::from_millis(50),
+                    strong: 0.0,
+                    weak: 0.0
+                },
+                ClipSegment {
+                    duration: Duration::from_millis(200),
+                    strong: 0.3,
+                    weak: 0.6
+                },
+            ]
+        );
+        assert_eq!(clip.duration(), Duration::from_millis(350));
+
+        let err = |s: &str| s.parse::<Clip>().unwrap_err();
+        assert_eq!(err(""), ParseClipError::InvalidSegment(0));
+        assert_eq!(err("0.1:1.0,0.5;"), ParseClipError::InvalidSegment(1));
+        assert_eq!(err("0.1:1.0"), ParseClipError::InvalidSegment(0));
+        assert_eq!(err("0.1:1.0,0.5,0.2"), ParseClipError::InvalidSegment(0));
+        assert_eq!(err("-0.1:1.0,0.5"), ParseClipError::InvalidDuration(0));
+        assert_eq!(err("0.1:1.0,0.5;x:1,1"), ParseClipError::InvalidDuration(1));
+        assert_eq!(err("0.1:1.5,0.5"), ParseClipError::InvalidMagnitude(0));
+        assert_eq!(err("0.1:1.0,a"), ParseClipError::InvalidMagnitude(0));
+    }
+
+    #[test]
+    fn schedule() {
+        let clip = ClipBuilder::new()
+            .segment(Duration::from_millis(100), 1.0, 0.5)
+            .pause(Duration::from_millis(50))
+            .segment(Duration::from_millis(200), 0.0, 1.0)
+            .finish();
+        let (effects, total) = clip.schedule(DEFAULT_TICK_DURATION);
+
+        assert_eq!(total, t(7));
+        assert_eq!(effects.len(), 3);
+        assert_eq!(
+            effects[0].kind,
+            BaseEffectType::Strong {
+                magnitude: u16::MAX
+            }
+        );
+        assert_eq!(
+            effects[1].kind,
+            BaseEffectType::Weak {
+                magnitude: u16::MAX / 2
+            }
+        );
+        assert_eq!(
+            effects[2].kind,
+            BaseEffectType::Weak {
+                magnitude: u16::MAX
+            }
+        );
+
+        let replay = |after, play_for| Replay {
+            after: t(after),
+            play_for: t(play_for),
+            with_delay: t(7 - play_for),
+        };
+        assert_eq!(effects[0].scheduling, replay(0, 2));
+        assert_eq!(effects[1].scheduling, replay(0, 2));
+        assert_eq!(effects[2].scheduling, replay(3, 4));
+
+        for i in 0..2 {
+            assert_eq!(effects[0].magnitude_at(t(i)).magnitude(), u16::MAX);
+            assert_eq!(effects[2].magnitude_at(t(i)).magnitude(), 0);
+        }
+        assert_eq!(effects[0].magnitude_at(t(2)).magnitude(), 0);
+        assert_eq!(effects[2].magnitude_at(t(2)).magnitude(), 0);
+        for i in 3..7 {
+            assert_eq!(effects[0].magnitude_at(t(i)).magnitude(), 0);
+            assert_eq!(effects[2].magnitude_at(t(i)).magnitude(), u16::MAX);
+        }
+    }
+
+    #[test]
+    fn schedule_short_segments() {
+        // Each segment is shorter than one tick.
+        let clip: Clip = "0.01:1,0;0.01:0,1;0.01:1,0".parse().unwrap();
+        let (effects, total) = clip.schedule(DEFAULT_TICK_DURATION);
+
+        assert_eq!(total, t(3));
+        let afters: Vec<_> = effects.iter().map(|e| e.scheduling.after).collect();
+        assert_eq!(afters, vec![t(0), t(1), t(2)]);
+        assert!(effects.iter().all(|e| e.scheduling.play_for == t(1)));
+
+        // Short segment between long ones doesn't shift them more than needed.
+        let clip: Clip = "0.1:1,0;0.01:0,1;0.09:1,0".parse().unwrap();
+        let (effects, total) = clip.schedule(DEFAULT_TICK_DURATION);
+
+        assert_eq!(total, t(4));
+        let afters: Vec<_> = effects.iter().map(|e| e.scheduling.after).collect();
+        assert_eq!(afters, vec![t(0), t(2), t(3)]);
+    }
+}
@@ -16,6 +16,8 @@
 //! [`DistanceModel`](enum.DistanceModel.html). Final strength of effect is based on saturating sum
 //! (to `u16::MAX`) of all base effects and time from the start of playback, attenuation from
 //! distance between effect source and listener (represented by gamepad) and effect's gain.
+//! Combined magnitude can be further attenuated by distance between listener and gamepad itself,
+//! see [`Gilrs::set_ff_attenuation()`](../struct.Gilrs.html#method.set_ff_attenuation).
 //!
 //! See also [`Gilrs::set_listener_position()`](../struct.Gilrs.html#method.set_listener_position)
 //! and [`Gamepad::is_ff_supported()`](../struct.Gamepad.html#method.is_ff_supported).
@@ -52,20 +54,30 @@
 //!
 //! See [`examples/ff_pos.rs`](https://gitlab.com/gilrs-project/gilrs/blob/v0.8.0/examples/ff_pos.rs) for
 //! more advanced example.
+//!
+//! Simple vibration patterns can also be described as data with [`Clip`](struct.Clip.html).
 mod base_effect;
+mod clip;
 mod effect_source;
+#[cfg(feature = "raw-ff")]
+mod raw;
 pub(crate) mod server;
 mod time;
 
 pub use self::base_effect::{BaseEffect, BaseEffectType, Envelope, Replay};
+pub use self::clip::{Clip, ClipBuilder, ClipSegment, ParseClipError};
 pub use self::effect_source::{DistanceModel, DistanceModelError};
-#[allow(unused_imports)]
-pub(crate) use self::time::TICK_DURATION;
+#[cfg(feature = "raw-ff")]
+pub use self::raw::RawFfHandle;
 pub use self::time::{Repeat, Ticks};
+pub(crate) use self::time::{DEFAULT_TICK_DURATION, MAX_TICK_DURATION, MIN_TICK_DURATION};
 
 use std::error::Error as StdError;
 use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{SendError, Sender};
+use std::sync::Arc;
+use std::time::Duration;
 use std::{f32, fmt};
 
 use self::effect_source::EffectSource;
@@ -125,6 +137,14 @@ impl Effect {
         Ok(())
     }
 
+    /// Plays effect on all associated gamepads. Effect is not stopped when last handle is dropped,
+    /// but only after it ends, so it must not repeat infinitely.
+    pub(crate) fn play_detached(&self) -> Result<(), Error> {
+        self.tx.send(Message::PlayDetached { id: self.id })?;
+
+        Ok(())
+    }
+
     pub fn stop(&self) -> Result<(), Error> {
         self.tx.send(Message::Stop { id: self.id })?;
 
@@ -226,7 +246,53 @@ impl Effect {
     }
 }
 
+/// Returns new ID of effect. IDs are never reused.
+pub(crate) fn next_effect_id(next_id: &AtomicUsize) -> usize {
+    // TODO: reuse free ids
+    let id = next_id.fetch_add(1, Ordering::Relaxed);
+    if id.checked_add(1).is_none() {
+        panic!("Failed to assign ID to new effect");
+    }
+
+    id
+}
+
+/// What is needed to create effect without `Gilrs`.
+#[derive(Clone, Debug)]
+struct FfContext {
+    tx: Sender<Message>,
+    next_id: Arc<AtomicUsize>,
+    // Error for gamepad that was used to create builder.
+    gamepad_error: Option<Error>,
+}
+
+impl PartialEq for FfContext {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.next_id, &other.next_id) && self.gamepad_error == other.gamepad_error
+    }
+}
+
 /// Creates new [`Effect`](struct.Effect.html).
+///
+/// Simple rumble can be created with
+/// [`Gamepad::create_ff_effect()`](../struct.Gamepad.html#method.create_ff_effect) without
+/// importing anything from this module:
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # let mut gilrs = gilrs::Gilrs::new().unwrap();
+/// # let id = gilrs.gamepads().next().unwrap().0;
+/// let effect = gilrs
+///     .gamepad(id)
+///     .create_ff_effect()
+///     .with_rumble(60_000, 20_000)
+///     .with_duration(Duration::from_millis(200))
+///     .with_delay(Duration::from_millis(100))
+///     .with_repeat(3)
+///     .build()
+///     .unwrap();
+/// effect.play().unwrap();
+/// ```
 #[derive(Clone, PartialEq, Debug)]
 pub struct EffectBuilder {
     base_effects: Vec<BaseEffect>,
@@ -235,6 +301,10 @@ pub struct EffectBuilder {
     dist_model: DistanceModel,
     position: [f32; 3],
     gain: f32,
+    rumble: Option<(u16, u16)>,
+    rumble_replay: Replay,
+    rumble_count: Option<u16>,
+    context: Option<FfContext>,
 }
 
 impl EffectBuilder {
@@ -249,9 +319,68 @@ impl EffectBuilder {
             dist_model: DistanceModel::None,
             position: [0.0, 0.0, 0.0],
             gain: 1.0,
+            rumble: None,
+            rumble_replay: Replay::default(),
+            rumble_count: None,
+            context: None,
         }
     }
 
+    /// Creates builder for effect played on `gamepad`. See `Gamepad::create_ff_effect()`.
+    pub(crate) fn for_gamepad(
+        gamepad: &Gamepad<'_>,
+        tx: Sender<Message>,
+        next_id: Arc<AtomicUsize>,
+    ) -> Self {
+        let id = gamepad.id();
+        let gamepad_error = if !gamepad.is_connected() {
+            Some(Error::Disconnected(id))
+        } else if !gamepad.is_ff_supported() {
+            Some(Error::FfNotSupported(id))
+        } else {
+            None
+        };
+
+        let mut builder = EffectBuilder::new();
+        builder.devices.insert(id.0, ());
+        builder.context = Some(FfContext {
+            tx,
+            next_id,
+            gamepad_error,
+        });
+
+        builder
+    }
+
+    /// Plays rumble with `strong` (low frequency) and `weak` (high frequency) motor magnitudes.
+    /// It's played in addition to base effects added with `add_effect()`.
+    ///
+    /// By default rumble lasts until effect is stopped. Use `with_duration()`, `with_delay()`
+    /// and `with_repeat()` to change it.
+    pub fn with_rumble(&mut self, strong: u16, weak: u16) -> &mut Self {
+        self.rumble = Some((strong, weak));
+        self
+    }
+
+    /// Changes how long rumble is played.
+    pub fn with_duration(&mut self, duration: Duration) -> &mut Self {
+        self.rumble_replay.play_for = duration.into();
+        self
+    }
+
+    /// Changes pause between repetitions of rumble.
+    pub fn with_delay(&mut self, delay: Duration) -> &mut Self {
+        self.rumble_replay.with_delay = delay.into();
+        self
+    }
+
+    /// Plays rumble `count` times and then stops the effect. If rumble is set, this overrides
+    /// `repeat()`.
+    pub fn with_repeat(&mut self, count: u16) -> &mut Self {
+        self.rumble_count = Some(count);
+        self
+    }
+
     /// Adds new [`BaseEffect`](struct.BaseEffect.html).
     pub fn add_effect(&mut self, effect: BaseEffect) -> &mut Self {
         self.base_effects.push(effect);
@@ -321,16 +450,65 @@ impl EffectBuilder {
 
         self.dist_model.validate()?;
 
+        let id = gilrs.next_ff_id();
+        self.create(id, gilrs.ff_sender())
+    }
+
+    /// Validates all parameters and creates new effect. Unlike `finish()`, this doesn't need
+    /// `Gilrs`, but it can be used only with builder returned by `Gamepad::create_ff_effect()`.
+    /// Gamepads added to such builder with `gamepads()` are not validated.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Disconnected(id)` or `Error::FfNotSupported(id)` if gamepad that created
+    /// this builder is disconnected or doesn't support force feedback.
+    ///
+    /// Returns `Error::InvalidDistanceModel` if `model` is not valid.
+    ///
+    /// Returns `Error::NotGamepadBuilder` if builder was not created with
+    /// `Gamepad::create_ff_effect()`.
+    pub fn build(&mut self) -> Result<Effect, Error> {
+        let context = self.context.clone().ok_or(Error::NotGamepadBuilder)?;
+        if let Some(err) = context.gamepad_error {
+            return Err(err);
+        }
+
+        self.dist_model.validate()?;
+
+        let id = next_effect_id(&context.next_id);
+        self.create(id, &context.tx)
+    }
+
+    fn create(&self, id: usize, tx: &Sender<Message>) -> Result<Effect, Error> {
+        let mut base_effects = self.base_effects.clone();
+        let mut repeat = self.repeat;
+
+        if let Some((strong, weak)) = self.rumble {
+            let scheduling = self.rumble_replay;
+            base_effects.push(BaseEffect {
+                kind: BaseEffectType::Strong { magnitude: strong },
+                scheduling,
+                envelope: Default::default(),
+            });
+            base_effects.push(BaseEffect {
+                kind: BaseEffectType::Weak { magnitude: weak },
+                scheduling,
+                envelope: Default::default(),
+            });
+
+            if let Some(count) = self.rumble_count {
+                repeat = Repeat::For(scheduling.dur() * u32::from(count));
+            }
+        }
+
         let effect = EffectSource::new(
-            self.base_effects.clone(),
+            base_effects,
             self.devices.clone(),
-            self.repeat,
+            repeat,
             self.dist_model,
             self.position,
             self.gain,
         );
-        let id = gilrs.next_ff_id();
-        let tx = gilrs.ff_sender();
         tx.send(Message::Create {
             id,
             effect: Box::new(effect),
@@ -351,6 +529,9 @@ pub enum Error {
     InvalidDistanceModel(DistanceModelError),
     /// The other end of channel was dropped.
     SendFailed,
+    /// `EffectBuilder::build()` was called on builder that wasn't created with
+    /// `Gamepad::create_ff_effect()`. Use `EffectBuilder::finish()` for such builders.
+    NotGamepadBuilder,
     /// Unexpected error has occurred
     Other,
 }
@@ -381,6 +562,9 @@ impl fmt::Display for Error {
             }
             Error::InvalidDistanceModel(_) => "distance model is invalid",
             Error::SendFailed => "receiving end of a channel is disconnected.",
+            Error::NotGamepadBuilder => {
+                "effect builder was not created with Gamepad::create_ff_effect()."
+            }
             Error::Other => "unespected error has occurred.",
         };
 
@@ -449,4 +633,102 @@ mod tests {
         assert_eq!(replay.at(Ticks(60)), 0.0);
         assert_eq!(replay.at(Ticks(70)), 0.0);
     }
+
+    #[test]
+    fn ticks_from_duration() {
+        assert_eq!(Ticks::from(Duration::from_secs(0)), Ticks::from_ms(0));
+        assert_eq!(Ticks::from(Duration::from_nanos(1)), Ticks::from_ms(1));
+        assert_eq!(Ticks::from(Duration::from_micros(999)), Ticks::from_ms(1));
+        assert_eq!(Ticks::from(Duration::from_millis(1)), Ticks::from_ms(1));
+        assert_eq!(Ticks::from(Duration::from_micros(1001)), Ticks::from_ms(2));
+        assert_eq!(Ticks::from(Duration::new(2, 1)), Ticks::from_ms(2001));
+    }
+
+    #[test]
+    fn tick_duration_independent() {
+        let effect = BaseEffect {
+            kind: BaseEffectType::Strong { magnitude: 60_000 },
+            scheduling: Replay {
+                play_for: Ticks::from_ms(200),
+                with_delay: Ticks::from_ms(800),
+                ..Default::default()
+            },
+            envelope: Envelope {
+                attack_length: Ticks::from_ms(100),
+                attack_level: 0.0,
+                ..Default::default()
+            },
+        };
+
+        for &ms in &[1, 4, 5, 10, 25, 50] {
+            let tick_duration = Duration::from_millis(ms);
+            let mut tick = Ticks::default();
+            let mut playing = 0;
+            while tick < Ticks::from_ms(1000) {
+                let magnitude = effect.magnitude_at(tick).magnitude();
+                if tick == Ticks::from_ms(50) {
+                    assert_eq!(magnitude, 30_000, "{} ms tick", ms);
+                }
+                if magnitude > 0 {
+                    playing += 1;
+                }
+                tick += Ticks::from(tick_duration);
+            }
+
+            // First sample is silent because of attack.
+            assert_eq!(
+                Duration::from_millis(ms) * (playing + 1),
+                Duration::from_millis(200),
+                "{} ms tick",
+                ms
+            );
+        }
+    }
+
+    #[test]
+    fn build_rumble() {
+        use crate::test_utils::gilrs_with_gamepad;
+        use crate::GilrsBuilder;
+        use std::sync::mpsc;
+
+        // Test gamepads don't support force feedback.
+        let (gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let result = gilrs
+            .gamepad(id)
+            .create_ff_effect()
+            .with_rumble(1, 1)
+            .build();
+        assert_eq!(result.err(), Some(Error::FfNotSupported(id)));
+        assert_eq!(
+            EffectBuilder::new().build().err(),
+            Some(Error::NotGamepadBuilder)
+        );
+
+        let (tx, rx) = mpsc::channel();
+        let mut builder = EffectBuilder::new();
+        builder.devices.insert(2, ());
+        builder.context = Some(FfContext {
+            tx,
+            next_id: Arc::new(AtomicUsize::new(5)),
+            gamepad_error: None,
+        });
+        let effect = builder
+            .with_rumble(60_000, 30_000)
+            .with_duration(Duration::from_millis(200))
+            .with_delay(Duration::from_millis(100))
+            .with_repeat(3)
+            .build()
+            .unwrap();
+        assert_eq!(effect.id, 5);
+
+        match rx.try_recv().unwrap() {
+            Message::Create { id, effect } => {
+                assert_eq!(id, 5);
+                assert_eq!(effect.repeat, Repeat::For(Ticks::from_ms(900)));
+                assert!(effect.devices.contains_key(2));
+            }
+            m => panic!("Unexpected message {:?}", m),
+        }
+        assert_eq!(builder.build().unwrap().id, 6);
+    }
 }
@@ -0,0 +1,119 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::server::Message;
+use crate::backend::RawFfDevice;
+
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+/// How long motors keep running after last call to `set_magnitudes()`.
+const RAW_FF_DURATION: Duration = Duration::from_secs(1);
+
+/// Direct access to gamepad's rumble motors for applications that mix force feedback
+/// themselves. Use [`Gamepad::ff_device_raw()`](../struct.Gamepad.html#method.ff_device_raw) to
+/// get it.
+///
+/// While at least one `RawFfHandle` exists, gilrs doesn't play any [`Effect`](struct.Effect.html)
+/// on its gamepad, so effects don't interfere with values set through the handle. When last
+/// handle is dropped, motors are stopped and gilrs plays effects on the gamepad again.
+#[derive(Debug)]
+pub struct RawFfHandle {
+    device: RawFfDevice,
+    id: usize,
+    tx: Sender<Message>,
+}
+
+impl RawFfHandle {
+    pub(crate) fn new(device: RawFfDevice, id: usize, tx: Sender<Message>) -> Self {
+        let _ = tx.send(Message::Detach { id });
+
+        RawFfHandle { device, id, tx }
+    }
+
+    /// Sets magnitudes of strong (low frequency) and weak (high frequency) motor. Motors stop
+    /// one second after last call, so this should be called regularly, for example every frame.
+    pub fn set_magnitudes(&mut self, strong: u16, weak: u16) {
+        self.device.set_ff_state(strong, weak, RAW_FF_DURATION);
+    }
+}
+
+impl Drop for RawFfHandle {
+    fn drop(&mut self) {
+        self.device.set_ff_state(0, 0, Duration::from_millis(0));
+        let _ = self.tx.send(Message::Reattach { id: self.id });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::gilrs_with_gamepad;
+    use crate::GilrsBuilder;
+
+    use std::sync::mpsc;
+
+    #[test]
+    fn ff_device_raw() {
+        let (mut gilrs, no_ff) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device("Rumble Gamepad", [1; 16], &[], &[]);
+        backend.set_ff_supported(id, true);
+        gilrs.next_event();
+
+        assert!(gilrs.gamepad(no_ff).ff_device_raw().is_none());
+
+        let mut first = gilrs.gamepad(id).ff_device_raw().unwrap();
+        let mut second = gilrs.gamepad(id).ff_device_raw().unwrap();
+        assert_eq!(first.id, id.0);
+        assert_eq!(second.id, id.0);
+
+        first.set_magnitudes(40_000, 1_000);
+        assert_eq!(
+            gilrs.test_backend().unwrap().ff_magnitudes(id),
+            (40_000, 1_000)
+        );
+        second.set_magnitudes(0, 20_000);
+        assert_eq!(gilrs.test_backend().unwrap().ff_magnitudes(id), (0, 20_000));
+        assert_eq!(gilrs.test_backend().unwrap().ff_magnitudes(no_ff), (0, 0));
+
+        // Dropped handle stops motors.
+        drop(first);
+        assert_eq!(gilrs.test_backend().unwrap().ff_magnitudes(id), (0, 0));
+        drop(second);
+
+        gilrs.test_backend().unwrap().remove_device(id);
+        gilrs.next_event();
+        assert!(gilrs.gamepad(id).ff_device_raw().is_none());
+    }
+
+    #[test]
+    fn handle_detaches_device() {
+        let (mut gilrs, _) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device("Rumble Gamepad", [1; 16], &[], &[]);
+        backend.set_ff_supported(id, true);
+        let device = RawFfDevice::Test(backend.gamepad(id.0).unwrap().ff_device().unwrap());
+
+        let (tx, rx) = mpsc::channel();
+        let mut handle = RawFfHandle::new(device, id.0, tx);
+        match rx.try_recv() {
+            Ok(Message::Detach { id: detached }) => assert_eq!(detached, id.0),
+            msg => panic!("expected Detach, got {:?}", msg),
+        }
+
+        handle.set_magnitudes(1, 2);
+        assert!(rx.try_recv().is_err());
+
+        drop(handle);
+        match rx.try_recv() {
+            Ok(Message::Reattach { id: reattached }) => assert_eq!(reattached, id.0),
+            msg => panic!("expected Reattach, got {:?}", msg),
+        }
+        assert_eq!(gilrs.test_backend().unwrap().ff_magnitudes(id), (0, 0));
+    }
+}
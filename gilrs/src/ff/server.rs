@@ -6,7 +6,7 @@
 // copied, modified, or distributed except according to those terms.
 
 use super::effect_source::{DistanceModel, EffectSource, EffectState, Magnitude};
-use super::time::{Repeat, Ticks, TICK_DURATION};
+use super::time::{Repeat, Ticks};
 
 use std::ops::{Deref, DerefMut};
 use std::sync::mpsc::{self, Receiver, Sender};
@@ -33,6 +33,10 @@ pub(crate) enum Message {
     Play {
         id: usize,
     },
+    /// Plays effect and keeps it alive until it ends, even if all its handles are dropped.
+    PlayDetached {
+        id: usize,
+    },
     Stop {
         id: usize,
     },
@@ -43,6 +47,12 @@ pub(crate) enum Message {
     Close {
         id: usize,
     },
+    /// Gamepad was reported as connected again without being disconnected. `device` replaces the
+    /// old one only if it no longer works, settings of gamepad are kept.
+    Reopen {
+        id: usize,
+        device: FfDevice,
+    },
     SetListenerPosition {
         id: usize,
         position: [f32; 3],
@@ -71,6 +81,31 @@ pub(crate) enum Message {
         id: usize,
         gain: f32,
     },
+    /// Magnitudes played on gamepad are multiplied by `factor` to compensate for low battery.
+    SetCompensation {
+        id: usize,
+        factor: f32,
+    },
+    /// Position of gamepad used for attenuation, distinct from its listener position.
+    SetGamepadPosition {
+        id: usize,
+        position: [f32; 3],
+    },
+    /// Falloff constant of attenuation based on distance between gamepad and its listener.
+    SetAttenuation {
+        id: usize,
+        k: f32,
+    },
+    /// `RawFfHandle` for gamepad was created, effects must not be played on it.
+    #[cfg(feature = "raw-ff")]
+    Detach {
+        id: usize,
+    },
+    /// `RawFfHandle` for gamepad was dropped.
+    #[cfg(feature = "raw-ff")]
+    Reattach {
+        id: usize,
+    },
 }
 
 impl Message {
@@ -79,7 +114,10 @@ impl Message {
         use self::Message::*;
 
         match self {
-            &SetListenerPosition { .. } | &HandleCloned { .. } | &HandleDropped { .. } => true,
+            &SetListenerPosition { .. }
+            | &SetGamepadPosition { .. }
+            | &HandleCloned { .. }
+            | &HandleDropped { .. } => true,
             _ => false,
         }
     }
@@ -90,12 +128,19 @@ struct Device {
     inner: FfDevice,
     position: [f32; 3],
     gain: f32,
+    /// Battery compensation factor, 1.0 if gamepad doesn't need it.
+    compensation: f32,
+    gamepad_position: [f32; 3],
+    /// Falloff constant of `spatial_attenuation()`, 0.0 disables attenuation.
+    attenuation: f32,
 }
 
 struct Effect {
     source: EffectSource,
-    /// Number of created effect's handles.
+    /// Number of created effect's handles, plus one if effect is detached.
     count: usize,
+    /// Effect is kept alive by server until it ends.
+    detached: bool,
 }
 
 impl Effect {
@@ -112,7 +157,11 @@ impl Effect {
 
 impl From<EffectSource> for Effect {
     fn from(source: EffectSource) -> Self {
-        Effect { source, count: 1 }
+        Effect {
+            source,
+            count: 1,
+            detached: false,
+        }
     }
 }
 
@@ -136,15 +185,23 @@ impl From<FfDevice> for Device {
             inner,
             position: [0.0, 0.0, 0.0],
             gain: 1.0,
+            compensation: 1.0,
+            gamepad_position: [0.0, 0.0, 0.0],
+            attenuation: 0.0,
         }
     }
 }
 
-pub(crate) fn run(rx: Receiver<Message>) {
+pub(crate) fn run(rx: Receiver<Message>, tick_duration: Duration) {
     let mut effects = VecMap::<Effect>::new();
     let mut devices = VecMap::<Device>::new();
-    let sleep_dur = Duration::from_millis(TICK_DURATION.into());
+    // Number of `RawFfHandle`s for each gamepad.
+    let mut raw_handles = VecMap::<usize>::new();
+    let sleep_dur = tick_duration;
     let mut tick = Ticks(0);
+    // Tick is computed from total time, so durations that aren't whole milliseconds don't
+    // accumulate rounding error.
+    let mut elapsed = Duration::from_secs(0);
 
     loop {
         let t1 = Instant::now();
@@ -155,147 +212,474 @@ pub(crate) fn run(rx: Receiver<Message>) {
                 debug!("New ff event: {:?}", ev);
             }
 
-            match ev {
-                Message::Create { id, effect } => {
-                    effects.insert(id, (*effect).into());
-                }
-                Message::Play { id } => {
-                    if let Some(effect) = effects.get_mut(id) {
-                        effect.source.state = EffectState::Playing { since: tick }
-                    } else {
-                        error!("{:?} with wrong ID", ev);
-                    }
-                }
-                Message::Stop { id } => {
-                    if let Some(effect) = effects.get_mut(id) {
-                        effect.source.state = EffectState::Stopped
-                    } else {
-                        error!("{:?} with wrong ID", ev);
-                    }
-                }
-                Message::Open { id, device } => {
-                    devices.insert(id, device.into());
-                }
-                Message::Close { id } => {
-                    devices.remove(id);
-                }
-                Message::SetListenerPosition { id, position } => {
-                    if let Some(device) = devices.get_mut(id) {
-                        device.position = position;
-                    } else {
-                        error!("{:?} with wrong ID", ev);
-                    }
-                }
-                Message::HandleCloned { id } => {
-                    if let Some(effect) = effects.get_mut(id) {
-                        effect.inc();
-                    } else {
-                        error!("{:?} with wrong ID", ev);
-                    }
-                }
-                Message::HandleDropped { id } => {
-                    let mut drop = false;
-                    if let Some(effect) = effects.get_mut(id) {
-                        if effect.dec() == 0 {
-                            drop = true;
-                        }
-                    } else {
-                        error!("{:?} with wrong ID", ev);
-                    }
-
-                    if drop {
-                        effects.remove(id);
-                    }
-                }
-                Message::SetGamepads { id, gamepads } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.devices = gamepads;
-                    } else {
-                        error!("Invalid effect id {} when changing gamepads.", id);
-                    }
-                }
-                Message::AddGamepad { id, gamepad_id } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.devices.insert(gamepad_id.0, ());
-                    } else {
-                        error!("Invalid effect id {} when changing gamepads.", id);
-                    }
-                }
-                Message::SetRepeat { id, repeat } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.repeat = repeat;
-                    } else {
-                        error!("Invalid effect id {} when changing repeat mode.", id);
-                    }
-                }
-                Message::SetDistanceModel { id, model } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.distance_model = model;
-                    } else {
-                        error!("Invalid effect id {} when changing distance model.", id);
-                    }
-                }
-                Message::SetPosition { id, position } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.position = position;
-                    } else {
-                        error!("Invalid effect id {}.", id);
-                    }
-                }
-                Message::SetGain { id, gain } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.gain = gain;
-                    } else {
-                        error!("Invalid effect id {} when changing effect gain.", id);
-                    }
-                }
-            }
+            handle_message(ev, &mut effects, &mut devices, &mut raw_handles, tick);
         }
 
-        combine_and_play(&mut effects, &mut devices, tick);
+        combine_and_play(
+            &mut effects,
+            &mut devices,
+            &raw_handles,
+            tick,
+            tick_duration,
+        );
+        release_finished(&mut effects, tick);
 
         let dur = Instant::now().duration_since(t1);
         if dur > sleep_dur {
             // TODO: Should we add dur - sleep_dur to next iteration's dur?
             warn!(
-                "One iteration of a force feedback loop took more than {}ms!",
-                TICK_DURATION
+                "One iteration of a force feedback loop took more than {:?}!",
+                sleep_dur
             );
         } else {
             thread::sleep(sleep_dur - dur);
         }
-        tick.inc();
+        elapsed += tick_duration;
+        tick = Ticks::from(elapsed);
+    }
+}
+
+// `raw_handles` are changed only by messages from `RawFfHandle`.
+#[cfg_attr(not(feature = "raw-ff"), allow(unused_variables))]
+fn handle_message(
+    ev: Message,
+    effects: &mut VecMap<Effect>,
+    devices: &mut VecMap<Device>,
+    raw_handles: &mut VecMap<usize>,
+    tick: Ticks,
+) {
+    match ev {
+        Message::Create { id, effect } => {
+            effects.insert(id, (*effect).into());
+        }
+        Message::Play { id } => {
+            if let Some(effect) = effects.get_mut(id) {
+                effect.source.state = EffectState::Playing { since: tick }
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        Message::PlayDetached { id } => {
+            match effects.get_mut(id) {
+                Some(effect) if !effect.detached => {
+                    effect.detached = true;
+                    effect.inc();
+                }
+                Some(_) => (),
+                None => {
+                    error!("{:?} with wrong ID", ev);
+                    return;
+                }
+            }
+
+            handle_message(Message::Play { id }, effects, devices, raw_handles, tick);
+        }
+        Message::Stop { id } => {
+            if let Some(effect) = effects.get_mut(id) {
+                effect.source.state = EffectState::Stopped
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        Message::Open { id, device } => {
+            devices.insert(id, device.into());
+        }
+        Message::Close { id } => {
+            devices.remove(id);
+        }
+        Message::Reopen { id, device } => match devices.get_mut(id) {
+            Some(old) if old.inner.is_connected() => (),
+            Some(old) => {
+                debug!(
+                    "Force feedback device of gamepad {} was removed, using new one",
+                    id
+                );
+                old.inner = device;
+            }
+            None => {
+                devices.insert(id, device.into());
+            }
+        },
+        Message::SetListenerPosition { id, position } => {
+            if let Some(device) = devices.get_mut(id) {
+                device.position = position;
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        Message::HandleCloned { id } => {
+            if let Some(effect) = effects.get_mut(id) {
+                effect.inc();
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        Message::HandleDropped { id } => {
+            let mut drop = false;
+            if let Some(effect) = effects.get_mut(id) {
+                if effect.dec() == 0 {
+                    drop = true;
+                }
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+
+            if drop {
+                effects.remove(id);
+            }
+        }
+        Message::SetGamepads { id, gamepads } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.devices = gamepads;
+            } else {
+                error!("Invalid effect id {} when changing gamepads.", id);
+            }
+        }
+        Message::AddGamepad { id, gamepad_id } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.devices.insert(gamepad_id.0, ());
+            } else {
+                error!("Invalid effect id {} when changing gamepads.", id);
+            }
+        }
+        Message::SetRepeat { id, repeat } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.repeat = repeat;
+            } else {
+                error!("Invalid effect id {} when changing repeat mode.", id);
+            }
+        }
+        Message::SetDistanceModel { id, model } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.distance_model = model;
+            } else {
+                error!("Invalid effect id {} when changing distance model.", id);
+            }
+        }
+        Message::SetPosition { id, position } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.position = position;
+            } else {
+                error!("Invalid effect id {}.", id);
+            }
+        }
+        Message::SetGain { id, gain } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.gain = gain;
+            } else {
+                error!("Invalid effect id {} when changing effect gain.", id);
+            }
+        }
+        Message::SetCompensation { id, factor } => {
+            if let Some(device) = devices.get_mut(id) {
+                device.compensation = factor;
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        Message::SetGamepadPosition { id, position } => {
+            if let Some(device) = devices.get_mut(id) {
+                device.gamepad_position = position;
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        Message::SetAttenuation { id, k } => {
+            if let Some(device) = devices.get_mut(id) {
+                device.attenuation = k;
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        #[cfg(feature = "raw-ff")]
+        Message::Detach { id } => {
+            *raw_handles.entry(id).or_insert(0) += 1;
+
+            // Stop effects that are still playing, raw handle controls gamepad from now.
+            if let Some(device) = devices.get_mut(id) {
+                device.inner.set_ff_state(0, 0, Duration::from_millis(0));
+            }
+        }
+        #[cfg(feature = "raw-ff")]
+        Message::Reattach { id } => match raw_handles.get_mut(id) {
+            Some(count) if *count > 1 => *count -= 1,
+            Some(_) => {
+                raw_handles.remove(id);
+            }
+            None => error!("{:?} without Detach", ev),
+        },
     }
 }
 
-pub(crate) fn init() -> Sender<Message> {
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+pub(crate) fn init(tick_duration: Duration) -> Sender<Message> {
     let (tx, _rx) = mpsc::channel();
 
     // Wasm doesn't support threads and force feedback
     #[cfg(not(target_arch = "wasm32"))]
-    thread::spawn(move || run(_rx));
+    thread::spawn(move || run(_rx, tick_duration));
 
     tx
 }
 
-fn combine_and_play(effects: &mut VecMap<Effect>, devices: &mut VecMap<Device>, tick: Ticks) {
+fn combine_and_play(
+    effects: &mut VecMap<Effect>,
+    devices: &mut VecMap<Device>,
+    raw_handles: &VecMap<usize>,
+    tick: Ticks,
+    tick_duration: Duration,
+) {
     for (dev_id, dev) in devices {
+        if raw_handles.contains_key(dev_id) {
+            continue;
+        }
+
         let mut magnitude = Magnitude::zero();
-        for (_, ref mut effect) in effects.iter_mut() {
-            if effect.devices.contains_key(dev_id) {
-                magnitude += effect.combine_base_effects(tick, dev.position);
-            }
+        for effect in effects
+            .values_mut()
+            .filter(|effect| is_playing_on(effect, dev_id))
+        {
+            magnitude += effect.combine_base_effects(tick, dev.position);
         }
+        let attenuation = spatial_attenuation(dev.position, dev.gamepad_position, dev.attenuation);
+        // Saturates at u16::MAX.
+        let magnitude = magnitude * (dev.compensation * attenuation);
         trace!(
             "({:?}) Setting ff state of {:?} to {:?}",
             tick,
             dev,
             magnitude
         );
-        dev.inner.set_ff_state(
-            magnitude.strong,
-            magnitude.weak,
-            Duration::from_millis(u64::from(TICK_DURATION) * 2),
+        dev.inner
+            .set_ff_state(magnitude.strong, magnitude.weak, tick_duration * 2);
+    }
+}
+
+/// Drops server's reference to detached effects that ended and removes them if they don't have
+/// any handles left.
+fn release_finished(effects: &mut VecMap<Effect>, tick: Ticks) {
+    effects.retain(|_, effect| {
+        if !effect.detached || !is_finished(effect, tick) {
+            return true;
+        }
+
+        effect.detached = false;
+        effect.dec() > 0
+    });
+}
+
+/// Returns true if `effect` is stopped or it played for its whole duration. Effects are stopped
+/// in `combine_base_effects()`, but only if they are played on some device.
+fn is_finished(effect: &Effect, tick: Ticks) -> bool {
+    match (effect.state, effect.repeat) {
+        (EffectState::Stopped, _) => true,
+        (EffectState::Playing { since }, Repeat::For(max_dur)) => tick - since > max_dur,
+        (EffectState::Playing { .. }, Repeat::Infinitely) => false,
+    }
+}
+
+/// Largest boost applied by `battery_boost()`, for completely discharged battery.
+const MAX_BATTERY_BOOST: f32 = 0.5;
+
+/// Returns factor by which ff magnitudes are multiplied for gamepad with battery at `level`
+/// percent. Factor is 1.0 at `threshold` and above, and grows linearly to
+/// `1.0 + MAX_BATTERY_BOOST` at 0%.
+pub(crate) fn battery_boost(level: u8, threshold: u8) -> f32 {
+    if level >= threshold {
+        return 1.0;
+    }
+
+    let missing = f32::from(threshold - level) / f32::from(threshold);
+    1.0 + MAX_BATTERY_BOOST * missing
+}
+
+/// Returns factor by which ff magnitudes are multiplied for gamepad at `gamepad` position when
+/// its listener is at `listener`: `1.0 / (1.0 + k * distance²)`.
+pub(crate) fn spatial_attenuation(listener: [f32; 3], gamepad: [f32; 3], k: f32) -> f32 {
+    let dist_sq: f32 = listener
+        .iter()
+        .zip(gamepad.iter())
+        .map(|(l, g)| (l - g) * (l - g))
+        .sum();
+
+    1.0 / (1.0 + k * dist_sq)
+}
+
+/// Returns true if `effect` is playing on device with `dev_id`.
+fn is_playing_on(effect: &Effect, dev_id: usize) -> bool {
+    let is_playing = match effect.state {
+        EffectState::Playing { .. } => true,
+        EffectState::Stopped => false,
+    };
+
+    is_playing && effect.devices.contains_key(dev_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ff::{Effect as EffectHandle, Error};
+
+    fn process(rx: &Receiver<Message>, effects: &mut VecMap<Effect>) {
+        let mut devices = VecMap::new();
+        while let Ok(ev) = rx.try_recv() {
+            handle_message(ev, effects, &mut devices, &mut VecMap::new(), Ticks(0));
+        }
+    }
+
+    #[test]
+    fn live_effect_updates() {
+        let (tx, rx) = mpsc::channel();
+        let mut effects = VecMap::new();
+        let source = EffectSource::new(
+            Vec::new(),
+            VecMap::new(),
+            Repeat::Infinitely,
+            DistanceModel::None,
+            [0.0, 0.0, 0.0],
+            1.0,
+        );
+        tx.send(Message::Create {
+            id: 0,
+            effect: Box::new(source),
+        })
+        .unwrap();
+        let effect = EffectHandle { id: 0, tx };
+
+        let model = DistanceModel::Linear {
+            ref_distance: 1.0,
+            rolloff_factor: 0.5,
+            max_distance: 10.0,
+        };
+        effect.set_distance_model(model).unwrap();
+        effect.set_position([1.0, 2.0, 3.0]).unwrap();
+        process(&rx, &mut effects);
+
+        assert_eq!(effects[0].distance_model, model);
+        assert_eq!(effects[0].position, [1.0, 2.0, 3.0]);
+
+        // Invalid models are rejected before reaching server
+        let invalid = DistanceModel::Inverse {
+            ref_distance: 0.0,
+            rolloff_factor: 1.0,
+        };
+        match effect.set_distance_model(invalid) {
+            Err(Error::InvalidDistanceModel(_)) => (),
+            e => panic!("Unexpected result: {:?}", e),
+        }
+        effect.set_position([4.0, 5.0, 6.0]).unwrap();
+        process(&rx, &mut effects);
+
+        assert_eq!(effects[0].distance_model, model);
+        assert_eq!(effects[0].position, [4.0, 5.0, 6.0]);
+
+        drop(effect);
+        process(&rx, &mut effects);
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn detached_effect() {
+        let mut effects = VecMap::new();
+        let source = EffectSource::new(
+            Vec::new(),
+            VecMap::new(),
+            Repeat::For(Ticks(3)),
+            DistanceModel::None,
+            [0.0, 0.0, 0.0],
+            1.0,
+        );
+        effects.insert(0, Effect::from(source));
+
+        let (mut devices, mut raw_handles) = (VecMap::new(), VecMap::new());
+        let play = Message::PlayDetached { id: 0 };
+        handle_message(play, &mut effects, &mut devices, &mut raw_handles, Ticks(1));
+        let drop = Message::HandleDropped { id: 0 };
+        handle_message(drop, &mut effects, &mut devices, &mut raw_handles, Ticks(1));
+
+        // Effect outlives its handle until it ends.
+        release_finished(&mut effects, Ticks(4));
+        assert!(effects.contains_key(0));
+        release_finished(&mut effects, Ticks(5));
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn battery_boost_curve() {
+        assert_eq!(battery_boost(50, 30), 1.0);
+        assert_eq!(battery_boost(30, 30), 1.0);
+        assert_eq!(battery_boost(15, 30), 1.0 + MAX_BATTERY_BOOST / 2.0);
+        assert_eq!(battery_boost(0, 30), 1.0 + MAX_BATTERY_BOOST);
+        assert!(battery_boost(10, 30) > battery_boost(20, 30));
+
+        // Threshold 0 disables compensation.
+        assert_eq!(battery_boost(0, 0), 1.0);
+
+        let magnitude = Magnitude {
+            strong: 60_000,
+            weak: 1000,
+        } * battery_boost(0, 30);
+        assert_eq!(magnitude.strong, std::u16::MAX);
+        assert_eq!(magnitude.weak, 1500);
+    }
+
+    #[test]
+    fn spatial_attenuation_falloff() {
+        let origin = [0.0, 0.0, 0.0];
+        assert_eq!(spatial_attenuation(origin, [3.0, 4.0, 0.0], 0.0), 1.0);
+        assert_eq!(spatial_attenuation(origin, origin, 2.0), 1.0);
+        assert_eq!(spatial_attenuation(origin, [3.0, 4.0, 0.0], 0.04), 0.5);
+        assert_eq!(
+            spatial_attenuation([1.0, 1.0, 1.0], [1.0, 1.0, 2.0], 1.0),
+            0.5
+        );
+
+        // Only ratio of positions and `k` matters.
+        assert_eq!(
+            spatial_attenuation(origin, [30.0, 40.0, 0.0], 0.0004),
+            spatial_attenuation(origin, [3.0, 4.0, 0.0], 0.04)
         );
+
+        let magnitude = Magnitude {
+            strong: 60_000,
+            weak: 1000,
+        } * spatial_attenuation(origin, [0.0, 2.0, 0.0], 0.25);
+        assert_eq!(magnitude.strong, 30_000);
+        assert_eq!(magnitude.weak, 500);
+    }
+
+    #[cfg(feature = "raw-ff")]
+    #[test]
+    fn raw_handles() {
+        fn process(raw_handles: &mut VecMap<usize>, messages: Vec<Message>) {
+            for msg in messages {
+                let (mut effects, mut devices) = (VecMap::new(), VecMap::new());
+                handle_message(msg, &mut effects, &mut devices, raw_handles, Ticks(0));
+            }
+        }
+
+        // Two handles for gamepad 1, one for gamepad 2
+        let mut raw_handles = VecMap::new();
+        process(
+            &mut raw_handles,
+            vec![
+                Message::Detach { id: 1 },
+                Message::Detach { id: 1 },
+                Message::Detach { id: 2 },
+            ],
+        );
+        assert_eq!(raw_handles.get(1), Some(&2));
+        assert_eq!(raw_handles.get(2), Some(&1));
+
+        // Server takes gamepad back only after last handle is dropped.
+        process(&mut raw_handles, vec![Message::Reattach { id: 1 }]);
+        assert!(raw_handles.contains_key(1));
+        process(&mut raw_handles, vec![Message::Reattach { id: 1 }]);
+        assert!(!raw_handles.contains_key(1));
+        assert!(raw_handles.contains_key(2));
+
+        // Unbalanced reattach is ignored.
+        process(&mut raw_handles, vec![Message::Reattach { id: 1 }]);
+        assert!(!raw_handles.contains_key(1));
     }
 }
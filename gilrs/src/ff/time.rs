@@ -8,15 +8,20 @@
 use std::ops::{Add, AddAssign, Mul, MulAssign, Rem, Sub, SubAssign};
 use std::time::Duration;
 
-use crate::utils;
-
-pub(crate) const TICK_DURATION: u32 = 50;
+/// Default time between updates of force feedback effects. See
+/// [`GilrsBuilder::ff_tick_duration()`](../struct.GilrsBuilder.html#method.ff_tick_duration).
+pub(crate) const DEFAULT_TICK_DURATION: Duration = Duration::from_millis(50);
+pub(crate) const MIN_TICK_DURATION: Duration = Duration::from_millis(1);
+pub(crate) const MAX_TICK_DURATION: Duration = Duration::from_millis(100);
 
 /// Represents duration.
 ///
 /// This type is only useful as input parameter for other functions in force feedback module. To
-/// create it, use `from_ms()` method. Keep in mind that `Ticks` **is not precise** representation
-/// of time.
+/// create it, use `from_ms()` method or convert `Duration`, which is rounded up to whole
+/// milliseconds, so only zero duration becomes zero ticks. `Ticks` has millisecond precision, but
+/// force feedback server updates effects only once per tick (see
+/// [`GilrsBuilder::ff_tick_duration()`](../struct.GilrsBuilder.html#method.ff_tick_duration)), so
+/// effects can be played up to one tick longer.
 ///
 /// # Example
 ///
@@ -24,22 +29,17 @@ pub(crate) const TICK_DURATION: u32 = 50;
 /// use gilrs::ff::Ticks;
 /// use std::time::Duration;
 ///
-/// let t1 = Ticks::from_ms(110);
-/// let t2 = Ticks::from(Duration::from_millis(130));
+/// let t1 = Ticks::from_ms(131);
+/// let t2 = Ticks::from(Duration::from_micros(130_400));
 ///
-/// /// `Ticks` is not precise.
 /// assert_eq!(t1, t2);
 /// ```
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
-pub struct Ticks(pub(super) u32);
+pub struct Ticks(pub(super) u64);
 
 impl Ticks {
     pub fn from_ms(dur: u32) -> Self {
-        Ticks(utils::ceil_div(dur, TICK_DURATION))
-    }
-
-    pub(super) fn inc(&mut self) {
-        self.0 += 1
+        Ticks(u64::from(dur))
     }
 
     pub(super) fn checked_sub(self, rhs: Ticks) -> Option<Ticks> {
@@ -49,7 +49,13 @@ impl Ticks {
 
 impl From<Duration> for Ticks {
     fn from(dur: Duration) -> Self {
-        Ticks::from_ms(dur.as_secs() as u32 * 1000 + dur.subsec_millis())
+        let millis = dur
+            .as_secs()
+            .saturating_mul(1000)
+            .saturating_add(u64::from(dur.subsec_millis()));
+        let is_partial = dur.subsec_millis() * 1_000_000 < dur.subsec_nanos();
+
+        Ticks(millis.saturating_add(u64::from(is_partial)))
     }
 }
 
@@ -85,13 +91,13 @@ impl Mul<u32> for Ticks {
     type Output = Ticks;
 
     fn mul(self, rhs: u32) -> Self::Output {
-        Ticks(self.0 * rhs)
+        Ticks(self.0 * u64::from(rhs))
     }
 }
 
 impl MulAssign<u32> for Ticks {
     fn mul_assign(&mut self, rhs: u32) {
-        self.0 *= rhs;
+        self.0 *= u64::from(rhs);
     }
 }
 
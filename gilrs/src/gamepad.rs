@@ -6,44 +6,78 @@
 // copied, modified, or distributed except according to those terms.
 
 use crate::{
+    backend::{Backend, RawGamepad},
     ev::{
+        curve::{CurveError, CurveTable},
+        filter::{self, Direction8, InvertAxis, SnapMode},
         state::{AxisData, ButtonData, GamepadState},
-        Axis, AxisOrBtn, Button, Code, Event, EventType,
+        Axis, AxisDirection, AxisOrBtn, Button, ButtonPromptHint, Code, Event, EventSource,
+        EventType, FilterId,
     },
     ff::{
+        self,
         server::{self, Message},
-        Error as FfError,
+        EffectBuilder, Error as FfError,
     },
-    mapping::{Mapping, MappingData, MappingDb},
+    latency::{LatencyHistogram, LatencyStats},
+    mapping::{
+        self, AxisRange, DeviceFilter, EffectiveMapping, HalfAxes, Mapping, MappingData, MappingDb,
+    },
+    snapshot::{CompactState, GamepadSnapshot, InputSnapshot},
     utils, MappingError,
 };
 
 use gilrs_core::{
-    self, AxisInfo, Error as PlatformError, Event as RawEvent, EventType as RawEventType,
+    self, native_ev_codes, AxisInfo, Error as PlatformError, Event as RawEvent,
+    EventType as RawEventType, PlatformErrorKind, RawState,
 };
 
+use fnv::FnvHashMap;
+use log::{Level, LevelFilter};
 use uuid::Uuid;
 
 use std::{
+    cell::Cell,
     collections::VecDeque,
     error,
     fmt::{self, Display},
-    sync::mpsc::Sender,
+    io, mem,
+    path::Path,
+    sync::{atomic::AtomicUsize, mpsc::Sender, Arc},
+    time::{Duration, Instant, SystemTime},
 };
 
-pub use gilrs_core::PowerInfo;
+#[cfg(feature = "raw-ff")]
+use crate::ff::RawFfHandle;
+#[cfg(feature = "async")]
+use crate::stream::EventStream;
+#[cfg(any(test, feature = "test_utils"))]
+use crate::test_utils::TestBackend;
+#[cfg(feature = "raw-hid")]
+use gilrs_core::RawReports;
+#[cfg(feature = "async")]
+use std::os::raw::c_int;
+
+pub use gilrs_core::{LedError, PollingRateError, PowerInfo, PowerOffError, Status};
 
 #[cfg(feature = "serde-serialize")]
 use serde::{Deserialize, Serialize};
 
 const DEFAULT_DEADZONE: f32 = 0.1;
+const DEFAULT_FF_COMPENSATION_THRESHOLD: u8 = 30;
+
+// Duration of `Gamepad::rumble_test()`.
+const RUMBLE_TEST_DURATION: Duration = Duration::from_millis(300);
+// How often power info is read when ff battery compensation is enabled.
+const FF_COMPENSATION_INTERVAL: Duration = Duration::from_secs(1);
+// How often gamepads are checked for idle timeout.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Main object responsible of managing gamepads.
 ///
 /// In order to get gamepad handle, use `gamepad()`, or `connected_gamepad()`. The main difference
 /// between these two is that `gamepad()` will also return handle to gamepad that is currently
-/// disconnected. However, both functions will return `None` if gamepad with given id has never
-/// existed.
+/// disconnected or was never observed. Use `status()` to check in which state gamepad is.
 ///
 /// # Event loop
 ///
@@ -114,32 +148,82 @@ const DEFAULT_DEADZONE: f32 = 0.1;
 ///             // go left
 ///         }
 ///
-///         match gamepad.button_data(Button::South) {
-///             Some(d) if d.is_pressed() && d.counter() == gilrs.counter() => {
-///                 // jump only if button was observed to be pressed in this iteration
-///             }
-///             _ => ()
+///         if gamepad.is_just_pressed(Button::South, gilrs.counter()) {
+///             // jump only if button was observed to be pressed in this iteration
 ///         }
 ///     }
 ///
+///     // Or the same without looking up `Gamepad`
+///     if player_one.map_or(false, |id| gilrs.just_pressed(id, Button::South)) {
+///         // jump
+///     }
+///
 ///     // Increase counter
 ///     gilrs.inc();
 /// #   break;
 /// }
+/// ```
+///
+/// # Multiple instances
 ///
+/// More than one `Gilrs` can be alive at the same time, for example when both application and a
+/// plugin it loads use gilrs. Each instance opens gamepads on its own and receives all events,
+/// filters and mappings are not shared. Caveats:
+///
+/// * Each instance plays force feedback independently, so effects played by different instances on
+///   the same gamepad overwrite each other instead of being combined.
+/// * On FreeBSD, gamepads can be opened only once, so `GilrsBuilder::build()` returns
+///   `Error::AlreadyInUse` while another instance is alive. See
+///   `gilrs_core::SUPPORTS_MULTIPLE_INSTANCES`.
 #[derive(Debug)]
 pub struct Gilrs {
-    inner: gilrs_core::Gilrs,
-    next_id: usize,
+    inner: Backend,
+    // Shared with `GamepadData`, so `Gamepad` can create effects.
+    next_ff_id: Arc<AtomicUsize>,
     tx: Sender<Message>,
     counter: u64,
+    last_seq: u64,
     mappings: MappingDb,
+    strict_uuid_matching: bool,
+    auto_mapping: bool,
     default_filters: bool,
+    inverted_axes: InvertAxis,
+    axis_info_overrides: FnvHashMap<([u8; 16], Code), AxisInfo>,
+    axis_info_overrides_all: FnvHashMap<Code, AxisInfo>,
     events: VecDeque<Event>,
+    // Events from `release_all()`, returned before other events.
+    synthesized_events: VecDeque<Event>,
+    // Events from `resync()`, processed before events from backend.
+    resync_events: VecDeque<RawEvent>,
     axis_to_btn_pressed: f32,
     axis_to_btn_released: f32,
     update_state: bool,
+    analog_buttons: bool,
+    unchanged_axis_epsilon: Option<f32>,
+    reconnect_grace: Option<Duration>,
+    button_layout: ButtonLayout,
+    event_log_level: Option<Level>,
+    state_logging: bool,
+    device_filter: DeviceFilter,
+    idle_timeout: Option<Duration>,
+    last_idle_check: Option<SystemTime>,
+    latency_metrics: bool,
+    #[cfg(feature = "latency-tracking")]
+    event_latency: LatencyHistogram,
+    ff_tick_duration: Duration,
+    ff_battery_compensation: bool,
+    ff_compensation_threshold: u8,
+    last_compensation_update: Option<SystemTime>,
+    ff_attenuation: f32,
+    update_hooks: UpdateHooks,
     gamepads_data: Vec<GamepadData>,
+    // Backend's IDs of gamepads, see `IdMap`.
+    ids: IdMap,
+    // Used by `gamepad()` for gamepads that backend reported before `gamepads_data` was synced.
+    // Its ID is never used, `Gamepad` keeps its own.
+    not_observed_data: GamepadData,
+    added_this_frame: Vec<GamepadId>,
+    removed_this_frame: Vec<GamepadId>,
 }
 
 impl Gilrs {
@@ -149,17 +233,111 @@ impl Gilrs {
         GilrsBuilder::new().build()
     }
 
+    /// Creates new `Gilrs` with settings from `options`. This is equivalent to
+    /// `GilrsBuilder::from(options).build()`.
+    #[allow(clippy::result_large_err)]
+    pub fn new_with_options(options: GilrsOptions) -> Result<Self, Error> {
+        GilrsBuilder::from(options).build()
+    }
+
     /// Returns next pending event. If there is no pending event, `None` is
     /// returned. This function will not block current thread and should be safe
     /// to call in async context.
     pub fn next_event(&mut self) -> Option<Event> {
+        loop {
+            // Events from `release_all()` have already updated state, so they skip filters.
+            let (mut ev, synthesized) = match self.synthesized_events.pop_front() {
+                Some(ev) => (ev, true),
+                None => {
+                    let mut ev = self.next_filtered_event()?;
+                    self.apply_curve(&mut ev);
+                    if self.is_unchanged_axis_event(&ev) {
+                        continue;
+                    }
+                    self.track_activity(&ev);
+                    (ev, false)
+                }
+            };
+
+            // Suppressed events still update state, but they are not returned.
+            let suppressed = self.is_suppressed_event(&ev);
+            if !suppressed {
+                ev.seq = self.next_seq();
+                if self.latency_metrics || cfg!(feature = "latency-tracking") {
+                    self.record_latency(&ev);
+                }
+
+                if let Some(level) = self.event_log_level {
+                    log!(level, "Event: {:?}", ev);
+                }
+            }
+
+            if self.update_state && !synthesized {
+                self.update(&ev);
+            }
+
+            if !suppressed {
+                return Some(ev);
+            }
+        }
+    }
+
+    /// Calls `handler` for every pending event, until `next_event()` returns `None`. This is
+    /// equivalent to:
+    ///
+    /// ```
+    /// # let mut gilrs = gilrs::Gilrs::new().unwrap();
+    /// # let mut handler = |_| ();
+    /// while let Some(ev) = gilrs.next_event() {
+    ///     handler(ev);
+    /// }
+    /// ```
+    ///
+    /// It's useful when `Gilrs` is only available as `&mut Gilrs`, for example in systems of ECS
+    /// frameworks. If handler needs to examine gamepads' state, use `process_events_with_state()`.
+    pub fn process_events(&mut self, mut handler: impl FnMut(Event)) {
+        while let Some(ev) = self.next_event() {
+            handler(ev);
+        }
+    }
+
+    /// Like `process_events()`, but `handler` also gets reference to `Gilrs`, so it can examine
+    /// state of gamepads. Gamepad state is already updated with event passed to `handler`.
+    ///
+    /// ```
+    /// use gilrs::{Button, EventType};
+    /// # let mut gilrs = gilrs::Gilrs::new().unwrap();
+    ///
+    /// gilrs.process_events_with_state(|ev, gilrs| {
+    ///     if let EventType::ButtonPressed(Button::South, _) = ev.event {
+    ///         if gilrs.gamepad(ev.id).is_pressed(Button::LeftTrigger) {
+    ///             println!("Combo on {}!", ev.id);
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// Plain `while let` loop can also use `gilrs` in its body, because `next_event()` returns
+    /// owned `Event`. This method is for handlers that are closures or functions created
+    /// elsewhere, which can't capture `&Gilrs` while it's mutably borrowed.
+    pub fn process_events_with_state(&mut self, mut handler: impl FnMut(Event, &Self)) {
+        while let Some(ev) = self.next_event() {
+            handler(ev, self);
+        }
+    }
+
+    /// Returns next event after default filters, if they are enabled. Axes are inverted before
+    /// default filters, so they see the same values as gamepad's state.
+    fn next_filtered_event(&mut self) -> Option<Event> {
         use crate::ev::filter::{axis_dpad_to_button, deadzone, Filter, Jitter};
 
-        let ev = if self.default_filters {
+        if self.default_filters {
             let jitter_filter = Jitter::new();
             loop {
+                let ev = self.next_event_priv();
                 let ev = self
-                    .next_event_priv()
+                    .inverted_axes
+                    .invert(ev)
                     .filter_ev(&axis_dpad_to_button, self)
                     .filter_ev(&jitter_filter, self)
                     .filter_ev(&deadzone, self);
@@ -171,16 +349,159 @@ impl Gilrs {
                 }
             }
         } else {
-            self.next_event_priv()
+            let ev = self.next_event_priv();
+            self.inverted_axes.invert(ev)
+        }
+    }
+
+    /// Records time between creation of `ev` and now in its gamepad's latency histogram and, with
+    /// `latency-tracking` feature, in histogram of all events. Only input events are measured.
+    fn record_latency(&mut self, ev: &Event) {
+        match ev.event {
+            EventType::Connected(_)
+            | EventType::Disconnected
+            | EventType::ConnectionLost
+            | EventType::ConnectionRestored
+            | EventType::Suspended
+            | EventType::Resumed
+            | EventType::Dropped => return,
+            _ => (),
+        }
+
+        // Time of event can be in future if clock was changed.
+        let latency = match self.time_now().duration_since(ev.time) {
+            Ok(latency) => latency,
+            Err(_) => return,
+        };
+
+        #[cfg(feature = "latency-tracking")]
+        self.event_latency.record(latency);
+
+        if self.latency_metrics {
+            if let Some(data) = self.gamepads_data.get_mut(ev.id.0) {
+                data.latency.record(latency);
+            }
+        }
+    }
+
+    /// Returns true if `ev` is `AxisChanged` event with value that differs from axis' value in
+    /// gamepad's state by at most epsilon set with `GilrsBuilder::suppress_unchanged_axes()`.
+    /// First event of every axis after `release_all()` is never suppressed.
+    fn is_unchanged_axis_event(&mut self, ev: &Event) -> bool {
+        // Without automatic updates state doesn't contain last returned values.
+        let epsilon = match self.unchanged_axis_epsilon {
+            Some(epsilon) if self.update_state => epsilon,
+            _ => return false,
+        };
+
+        let (value, code) = match ev.event {
+            EventType::AxisChanged(_, value, code) => (value, code),
+            _ => return false,
+        };
+
+        let data = match self.gamepads_data.get_mut(ev.id.0) {
+            Some(data) => data,
+            None => return false,
         };
 
-        if self.update_state {
-            if let Some(ref ev) = ev {
-                self.update(ev);
+        if let Some(idx) = data.released_axes.iter().position(|&c| c == code) {
+            data.released_axes.swap_remove(idx);
+            return false;
+        }
+
+        match data.state.axis_data(code) {
+            Some(axis) => (value - axis.value()).abs() <= epsilon,
+            None => false,
+        }
+    }
+
+    /// Returns true if `ev` is input event of suppressed gamepad.
+    fn is_suppressed_event(&self, ev: &Event) -> bool {
+        match self.gamepads_data.get(ev.id.0) {
+            Some(data) if data.suppressed => (),
+            _ => return false,
+        }
+
+        match ev.event {
+            EventType::Connected(_)
+            | EventType::Disconnected
+            | EventType::ConnectionLost
+            | EventType::ConnectionRestored
+            | EventType::Suspended
+            | EventType::Resumed => false,
+            _ => true,
+        }
+    }
+
+    /// Appends all pending events to `buf` and returns number of appended events.
+    ///
+    /// Events are processed exactly as in `next_event()` (filters, curves and state updates), so
+    /// this is equivalent to calling `next_event()` until it returns `None`. It's intended for
+    /// applications that access gilrs through FFI, where one call per event is expensive. Events
+    /// can be converted to [`FfiEvent`](ev/struct.FfiEvent.html).
+    pub fn poll_events_into(&mut self, buf: &mut Vec<Event>) -> usize {
+        let len = buf.len();
+        while let Some(ev) = self.next_event() {
+            buf.push(ev);
+        }
+
+        buf.len() - len
+    }
+
+    /// Processes all pending events and returns summary of what happened.
+    ///
+    /// This is equivalent to calling `next_event()` until it returns `None` and discarding
+    /// events, so filters are applied and state is updated as usual. It's intended for
+    /// applications that only redraw when something changed and read input from gamepad state.
+    /// Dropped events are not counted as changes. Use `pump_with()` to also handle events.
+    pub fn pump(&mut self) -> PumpResult {
+        self.pump_with(|_| ())
+    }
+
+    /// Like `pump()`, but also calls `handler` for every event that is not dropped, in order in
+    /// which they were processed.
+    pub fn pump_with(&mut self, mut handler: impl FnMut(Event)) -> PumpResult {
+        let mut result = PumpResult::default();
+
+        while let Some(ev) = self.next_event() {
+            if ev.is_dropped() {
+                continue;
+            }
+
+            result.changed = true;
+            if !result.active_gamepads.contains(&ev.id) {
+                result.active_gamepads.push(ev.id);
             }
+
+            match ev.event {
+                EventType::Connected(_) => result.connected += 1,
+                EventType::Disconnected => result.disconnected += 1,
+                _ => (),
+            }
+
+            handler(ev);
         }
 
-        ev
+        result
+    }
+
+    fn apply_curve(&self, ev: &mut Event) {
+        let data = match self.gamepads_data.get(ev.id.0) {
+            Some(data) => data,
+            None => return,
+        };
+
+        let (el, val, nec) = match ev.event {
+            EventType::AxisChanged(axis, ref mut val, nec) => (AxisOrBtn::Axis(axis), val, nec),
+            EventType::ButtonChanged(btn, ref mut val, nec) => (AxisOrBtn::Btn(btn), val, nec),
+            _ => return,
+        };
+
+        // Curve set with `set_axis_curve()` overrides one from mapping.
+        let curve = data.curves.get(&nec).or_else(|| data.mapping.curve(el));
+        if let Some(curve) = curve {
+            *val = curve.apply(*val);
+        }
     }
 
     /// Returns next pending event.
@@ -188,10 +509,25 @@ impl Gilrs {
         if let Some(ev) = self.events.pop_front() {
             Some(ev)
         } else {
-            match self.inner.next_event() {
+            let raw = loop {
+                let raw = self
+                    .resync_events
+                    .pop_front()
+                    .or_else(|| self.inner.next_event());
+                self.sync_gamepads_data();
+                match raw {
+                    Some(RawEvent { id, .. }) if self.is_ignored(id) => {
+                        trace!("Ignoring event from ignored gamepad {}", id)
+                    }
+                    raw => break raw,
+                }
+            };
+
+            match raw {
                 Some(RawEvent { id, event, time }) => {
                     trace!("Original event: {:?}", RawEvent { id, event, time });
-                    let id = GamepadId(id);
+                    let mut id = self.ids.id(id);
+                    let mut source = EventSource::Hardware;
 
                     let event = match event {
                         RawEventType::ButtonPressed(nec) => {
@@ -202,16 +538,24 @@ impl Gilrs {
                                         id,
                                         time,
                                         event: EventType::ButtonChanged(b, 1.0, nec),
+                                        seq: 0,
+                                        source: EventSource::Synthetic(FilterId::Gilrs),
                                     });
 
                                     EventType::ButtonPressed(b, nec)
                                 }
-                                Some(AxisOrBtn::Axis(a)) => EventType::AxisChanged(a, 1.0, nec),
+                                Some(AxisOrBtn::Axis(a)) => EventType::AxisChanged(
+                                    a,
+                                    self.button_axis_value(id, nec, a),
+                                    nec,
+                                ),
                                 None => {
                                     self.events.push_back(Event {
                                         id,
                                         time,
                                         event: EventType::ButtonChanged(Button::Unknown, 1.0, nec),
+                                        seq: 0,
+                                        source: EventSource::Synthetic(FilterId::Gilrs),
                                     });
 
                                     EventType::ButtonPressed(Button::Unknown, nec)
@@ -226,6 +570,8 @@ impl Gilrs {
                                         id,
                                         time,
                                         event: EventType::ButtonChanged(b, 0.0, nec),
+                                        seq: 0,
+                                        source: EventSource::Synthetic(FilterId::Gilrs),
                                     });
 
                                     EventType::ButtonReleased(b, nec)
@@ -236,6 +582,8 @@ impl Gilrs {
                                         id,
                                         time,
                                         event: EventType::ButtonChanged(Button::Unknown, 0.0, nec),
+                                        seq: 0,
+                                        source: EventSource::Synthetic(FilterId::Gilrs),
                                     });
 
                                     EventType::ButtonReleased(Button::Unknown, nec)
@@ -244,84 +592,459 @@ impl Gilrs {
                         }
                         RawEventType::AxisValueChanged(val, nec) => {
                             // Let's trust at least our backend code
-                            let axis_info = *self.gamepad(id).inner.axis_info(nec).unwrap();
+                            let axis_info = self.gamepad(id).axis_info(Code(nec)).unwrap();
+                            let y_reversed = self.gamepad(id).inner.is_y_axis_reversed();
+                            let val = axis_info.clamp(val);
                             let nec = Code(nec);
 
                             match self.gamepad(id).axis_or_btn_name(nec) {
                                 Some(AxisOrBtn::Btn(b)) => {
                                     let val = btn_value(&axis_info, val);
+                                    let is_analog = self.analog_buttons
+                                        && i64::from(axis_info.max) - i64::from(axis_info.min) > 1;
 
                                     if val >= self.axis_to_btn_pressed
                                         && !self.gamepad(id).state().is_pressed(nec)
                                     {
+                                        let val = if is_analog { val } else { 1.0 };
                                         self.events.push_back(Event {
                                             id,
                                             time,
                                             event: EventType::ButtonChanged(b, val, nec),
+                                            seq: 0,
+                                            source: EventSource::Synthetic(FilterId::Gilrs),
                                         });
 
                                         EventType::ButtonPressed(b, nec)
                                     } else if val <= self.axis_to_btn_released
                                         && self.gamepad(id).state().is_pressed(nec)
                                     {
+                                        let val = if is_analog { val } else { 0.0 };
                                         self.events.push_back(Event {
                                             id,
                                             time,
                                             event: EventType::ButtonChanged(b, val, nec),
+                                            seq: 0,
+                                            source: EventSource::Synthetic(FilterId::Gilrs),
                                         });
 
                                         EventType::ButtonReleased(b, nec)
-                                    } else {
+                                    } else if is_analog
+                                        && self.gamepad(id).state().value(nec) != val
+                                    {
                                         EventType::ButtonChanged(b, val, nec)
+                                    } else {
+                                        EventType::Dropped
                                     }
                                 }
-                                Some(AxisOrBtn::Axis(a)) => {
-                                    EventType::AxisChanged(a, axis_value(&axis_info, val, a), nec)
-                                }
-                                None => EventType::AxisChanged(
-                                    Axis::Unknown,
-                                    axis_value(&axis_info, val, Axis::Unknown),
+                                Some(AxisOrBtn::Axis(a)) => EventType::AxisChanged(
+                                    a,
+                                    axis_value(&axis_info, val, a, y_reversed),
                                     nec,
                                 ),
+                                None => match self.gamepad(id).data.mapping.half_axes(&nec.0) {
+                                    Some(half_axes) => {
+                                        let is_analog = self.analog_buttons
+                                            && i64::from(axis_info.max) - i64::from(axis_info.min)
+                                                > 1;
+                                        let val = axis_value(&axis_info, val, Axis::Unknown, false);
+
+                                        self.half_axes_event(id, time, half_axes, val, is_analog)
+                                    }
+                                    None => EventType::AxisChanged(
+                                        Axis::Unknown,
+                                        axis_value(&axis_info, val, Axis::Unknown, y_reversed),
+                                        nec,
+                                    ),
+                                },
                             }
                         }
                         RawEventType::Connected => {
-                            if id.0 == self.gamepads_data.len() {
-                                self.gamepads_data.push(GamepadData::new(
-                                    id,
-                                    self.tx.clone(),
-                                    self.inner.gamepad(id.0).unwrap(),
-                                    &self.mappings,
-                                ));
-                            } else if id.0 < self.gamepads_data.len() {
-                                self.gamepads_data[id.0] = GamepadData::new(
-                                    id,
-                                    self.tx.clone(),
-                                    self.inner.gamepad(id.0).unwrap(),
-                                    &self.mappings,
-                                );
+                            id = self.restored_id(id, time);
+                            let lost = self
+                                .gamepads_data
+                                .get(id.0)
+                                .and_then(|data| data.connection_lost.clone());
+                            let is_connected = self
+                                .gamepads_data
+                                .get(id.0)
+                                .map(|data| data.status == Status::Connected)
+                                .unwrap_or(false);
+                            let info = if is_connected {
+                                self.refresh_gamepad_data(id)
                             } else {
-                                error!(
-                                    "Platform implementation error: got Connected event with id \
-                                     {}, when expected id {}",
-                                    id.0,
-                                    self.gamepads_data.len()
-                                );
+                                self.reopen_gamepad_data(id)
+                            };
+
+                            let is_restored = lost.map(|lost| {
+                                time <= lost.deadline
+                                    && self
+                                        .inner
+                                        .gamepad(self.ids.raw(id))
+                                        .map(|gamepad| lost.is_same_device(gamepad))
+                                        .unwrap_or(false)
+                            });
+
+                            match is_restored {
+                                Some(true) => EventType::ConnectionRestored,
+                                Some(false) => {
+                                    // Different device or reconnected too late, previous one has
+                                    // to be reported as disconnected first.
+                                    self.events.push_back(Event {
+                                        id,
+                                        time,
+                                        event: EventType::Connected(info),
+                                        seq: 0,
+                                        source: EventSource::Hardware,
+                                    });
+                                    source = EventSource::Synthetic(FilterId::Gilrs);
+
+                                    EventType::Disconnected
+                                }
+                                None => EventType::Connected(info),
+                            }
+                        }
+                        RawEventType::Suspended => {
+                            let _ = self.tx.send(Message::Close { id: id.0 });
+                            if let Some(data) = self.gamepads_data.get_mut(id.0) {
+                                data.status = Status::Suspended;
+                                data.state = GamepadState::new();
                             }
 
-                            EventType::Connected
+                            EventType::Suspended
+                        }
+                        RawEventType::Resumed => {
+                            // Platform opened device again, so force feedback has to be opened too.
+                            self.reopen_gamepad_data(id);
+
+                            EventType::Resumed
                         }
                         RawEventType::Disconnected => {
+                            let gamepad = self.inner.gamepad(self.ids.raw(id));
+                            let uuid = gamepad.map(|gp| gp.uuid());
+                            let serial_number =
+                                gamepad.and_then(|gp| gp.serial_number()).map(str::to_owned);
                             let _ = self.tx.send(Message::Close { id: id.0 });
 
-                            EventType::Disconnected
+                            match self.gamepads_data.get_mut(id.0) {
+                                Some(data) => {
+                                    data.set_connected(false);
+                                    data.state.clear_press_history();
+
+                                    match self.reconnect_grace {
+                                        Some(grace) => {
+                                            data.state = GamepadState::new();
+                                            data.connection_lost = Some(LostConnection {
+                                                deadline: time + grace,
+                                                uuid: uuid.unwrap_or([0; 16]),
+                                                serial_number,
+                                            });
+
+                                            EventType::ConnectionLost
+                                        }
+                                        None => EventType::Disconnected,
+                                    }
+                                }
+                                None => EventType::Disconnected,
+                            }
                         }
+                        RawEventType::TouchpadTouch {
+                            finger,
+                            x,
+                            y,
+                            pressed,
+                        } => EventType::TouchpadTouch {
+                            finger,
+                            x,
+                            y,
+                            pressed,
+                        },
                     };
 
-                    Some(Event { id, event, time })
+                    Some(Event {
+                        id,
+                        event,
+                        time,
+                        seq: 0,
+                        source,
+                    })
+                }
+                None => {
+                    let ev = self.expire_lost_connection();
+                    if ev.is_none() {
+                        self.power_off_idle_gamepads();
+                        self.update_ff_compensation(false);
+                        self.sync_ff_attenuation();
+                    }
+
+                    ev
+                }
+            }
+        }
+    }
+
+    /// Returns value of `axis` when button with `nec` bound to it is pressed. Buttons bound to
+    /// negative half of axis, like `-lefty:b2` in SDL mapping, move it in negative direction of
+    /// device's axis.
+    fn button_axis_value(&self, id: GamepadId, nec: Code, axis: Axis) -> f32 {
+        let gamepad = self.gamepad(id);
+        let val = match gamepad.data.mapping.button_range(&nec.0) {
+            AxisRange::Full => return 1.0,
+            AxisRange::LowerHalf => -1.0,
+            AxisRange::UpperHalf => 1.0,
+        };
+
+        if is_axis_flipped(axis, gamepad.inner.is_y_axis_reversed()) {
+            -val
+        } else {
+            val
+        }
+    }
+
+    /// Returns event of elements bound to halves of axis, like `lefttrigger:-a2` in SDL mapping.
+    /// `val` is value of axis in direction reported by device. Value of each half is in range
+    /// [0.0, 1.0] and buttons are pressed and released with the same thresholds as buttons bound
+    /// to whole axis. If events of more than one element are emitted, the rest is queued.
+    fn half_axes_event(
+        &mut self,
+        id: GamepadId,
+        time: SystemTime,
+        half_axes: HalfAxes,
+        val: f32,
+        is_analog: bool,
+    ) -> EventType {
+        let companion = EventSource::Synthetic(FilterId::Gilrs);
+        let mut events = Vec::new();
+
+        for &(half, val) in &[(half_axes.negative, -val), (half_axes.positive, val)] {
+            let (el, code) = match half {
+                Some((el, code)) => (el, Code(code)),
+                None => continue,
+            };
+            let val = utils::clamp(val, 0.0, 1.0);
+            let state = &self.gamepads_data[id.0].state;
+
+            match el {
+                AxisOrBtn::Btn(b) => {
+                    let pressed = state.is_pressed(code);
+                    if val >= self.axis_to_btn_pressed && !pressed {
+                        let val = if is_analog { val } else { 1.0 };
+                        events.push((EventType::ButtonPressed(b, code), EventSource::Hardware));
+                        events.push((EventType::ButtonChanged(b, val, code), companion));
+                    } else if val <= self.axis_to_btn_released && pressed {
+                        let val = if is_analog { val } else { 0.0 };
+                        events.push((EventType::ButtonReleased(b, code), EventSource::Hardware));
+                        events.push((EventType::ButtonChanged(b, val, code), companion));
+                    } else if is_analog && state.value(code) != val {
+                        events.push((
+                            EventType::ButtonChanged(b, val, code),
+                            EventSource::Hardware,
+                        ));
+                    }
+                }
+                AxisOrBtn::Axis(a) => {
+                    if state.value(code) != val {
+                        events.push((EventType::AxisChanged(a, val, code), EventSource::Hardware));
+                    }
+                }
+            }
+        }
+
+        let mut events = events.into_iter().map(|(event, source)| Event {
+            id,
+            event,
+            time,
+            seq: 0,
+            source,
+        });
+        let first = events.next();
+        self.events.extend(events);
+
+        first.map(|ev| ev.event).unwrap_or(EventType::Dropped)
+    }
+
+    /// Returns ID of gamepad restored by device that backend reported as connected with `id`.
+    /// Backends don't always reuse slot of reconnected device, so if gamepad with `id` didn't
+    /// lose connection to the same device, all gamepads that did are searched. Backend's
+    /// gamepad of matching one is swapped with `id`, so it keeps its ID.
+    fn restored_id(&mut self, id: GamepadId, time: SystemTime) -> GamepadId {
+        let gamepad = match self.inner.gamepad(self.ids.raw(id)) {
+            Some(gamepad) => gamepad,
+            None => return id,
+        };
+        let is_lost = |data: &GamepadData| {
+            data.status != Status::Connected
+                && data
+                    .connection_lost
+                    .as_ref()
+                    .map(|lost| time <= lost.deadline && lost.is_same_device(gamepad))
+                    .unwrap_or(false)
+        };
+
+        match self.gamepads_data.get(id.0) {
+            Some(data) if data.status == Status::Connected || is_lost(data) => return id,
+            _ => (),
+        }
+
+        let lost_id = match self.gamepads_data.iter().position(is_lost) {
+            Some(idx) => GamepadId(idx),
+            None => return id,
+        };
+        debug!(
+            "Gamepad {} reconnected as {}, restoring its ID",
+            lost_id, id
+        );
+        self.ids.swap(id, lost_id);
+
+        lost_id
+    }
+
+    /// Returns `Disconnected` event for gamepad whose grace period for reconnecting has ended.
+    fn expire_lost_connection(&mut self) -> Option<Event> {
+        let now = self.time_now();
+        let (deadline, idx) = self
+            .gamepads_data
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, data)| {
+                data.connection_lost
+                    .as_ref()
+                    .map(|lost| (lost.deadline, idx))
+            })
+            .filter(|&(deadline, _)| deadline <= now)
+            .min()?;
+
+        self.gamepads_data[idx].connection_lost = None;
+
+        Some(Event {
+            id: GamepadId(idx),
+            event: EventType::Disconnected,
+            time: deadline,
+            seq: 0,
+            source: EventSource::Synthetic(FilterId::Gilrs),
+        })
+    }
+
+    fn track_activity(&mut self, ev: &Event) {
+        if ev.is_dropped() {
+            return;
+        }
+
+        if let Some(data) = self.gamepads_data.get_mut(ev.id.0) {
+            data.last_activity = Some(ev.time);
+            data.idle_power_off = false;
+        }
+    }
+
+    /// Powers off wireless gamepads that were idle for longer than `idle_timeout`. Gamepads are
+    /// checked at most once per `IDLE_CHECK_INTERVAL`.
+    fn power_off_idle_gamepads(&mut self) {
+        let timeout = match self.idle_timeout {
+            Some(timeout) => timeout,
+            None => return,
+        };
+        let now = self.time_now();
+
+        let is_due = self
+            .last_idle_check
+            .and_then(|last| now.duration_since(last).ok())
+            .map(|elapsed| elapsed >= IDLE_CHECK_INTERVAL)
+            .unwrap_or(true);
+        if !is_due {
+            return;
+        }
+        self.last_idle_check = Some(now);
+
+        for (id, data) in self.gamepads_data.iter_mut().enumerate() {
+            let gamepad = match self.inner.gamepad(self.ids.raw(GamepadId(id))) {
+                Some(gamepad) if gamepad.is_connected() && !data.idle_power_off => gamepad,
+                _ => continue,
+            };
+
+            // Gamepad may not have sent any event since idle timeout was set.
+            let last_activity = *data.last_activity.get_or_insert(now);
+            let is_idle = now
+                .duration_since(last_activity)
+                .map(|idle| idle >= timeout)
+                .unwrap_or(false);
+
+            if !is_idle {
+                continue;
+            }
+
+            // Never power off wired gamepads. Charging gamepads are connected with cable too.
+            if let PowerInfo::Discharging(_) = gamepad.power_info() {
+                // Don't try again until gamepad is used.
+                data.idle_power_off = true;
+
+                match gamepad.power_off() {
+                    Ok(()) => info!("Powered off idle gamepad {}", id),
+                    Err(PowerOffError::NotSupported) => {
+                        debug!("Idle gamepad {} can't be powered off", id)
+                    }
+                    Err(e) => warn!("Failed to power off idle gamepad {}: {}", id, e),
+                }
+            }
+        }
+    }
+
+    /// Updates battery compensation factor of all gamepads and sends it to ff server. Power info is
+    /// read at most once per `FF_COMPENSATION_INTERVAL`, unless `force` is true.
+    fn update_ff_compensation(&mut self, force: bool) {
+        let now = self.time_now();
+        if !force {
+            if !self.ff_battery_compensation {
+                return;
+            }
+
+            let is_due = self
+                .last_compensation_update
+                .and_then(|last| now.duration_since(last).ok())
+                .map(|elapsed| elapsed >= FF_COMPENSATION_INTERVAL)
+                .unwrap_or(true);
+            if !is_due {
+                return;
+            }
+        }
+        self.last_compensation_update = Some(now);
+
+        for (id, data) in self.gamepads_data.iter_mut().enumerate() {
+            let power_info = match self.inner.gamepad(self.ids.raw(GamepadId(id))) {
+                Some(gamepad) if self.ff_battery_compensation && gamepad.is_connected() => {
+                    gamepad.power_info()
+                }
+                _ => PowerInfo::Unknown,
+            };
+            let factor = match power_info {
+                PowerInfo::Discharging(level) => {
+                    server::battery_boost(level, self.ff_compensation_threshold)
                 }
-                None => None,
+                _ => 1.0,
+            };
+
+            if factor == data.ff_compensation {
+                continue;
+            }
+
+            data.ff_compensation = factor;
+            // Only gamepads with opened ff device are known to server.
+            if data.max_ff_effects.is_some() {
+                let _ = data.tx.send(Message::SetCompensation { id, factor });
+            }
+        }
+    }
+
+    /// Sends attenuation constant to ff server for gamepads that don't have it yet, for example
+    /// because they were reconnected.
+    fn sync_ff_attenuation(&mut self) {
+        let k = self.ff_attenuation;
+        for (id, data) in self.gamepads_data.iter_mut().enumerate() {
+            if data.ff_attenuation == k || data.max_ff_effects.is_none() {
+                continue;
             }
+
+            data.ff_attenuation = k;
+            let _ = data.tx.send(Message::SetAttenuation { id, k });
         }
     }
 
@@ -334,6 +1057,16 @@ impl Gilrs {
 
         let counter = self.counter;
 
+        match event.event {
+            Connected(_) if !self.added_this_frame.contains(&event.id) => {
+                self.added_this_frame.push(event.id)
+            }
+            Disconnected if !self.removed_this_frame.contains(&event.id) => {
+                self.removed_this_frame.push(event.id)
+            }
+            _ => (),
+        }
+
         let data = match self.gamepads_data.get_mut(event.id.0) {
             Some(d) => d,
             None => return,
@@ -356,7 +1089,53 @@ impl Gilrs {
                 data.state
                     .update_axis(nec, AxisData::new(value, counter, event.time));
             }
-            Disconnected | Connected | Dropped => (),
+            ButtonHeld(..)
+            | ButtonDoublePressed(..)
+            | Disconnected
+            | Connected(_)
+            | ConnectionLost
+            | ConnectionRestored
+            | Suspended
+            | Resumed
+            | TouchpadTouch { .. }
+            | Dropped => (),
+        }
+
+        match event.event {
+            ButtonPressed(..)
+            | ButtonReleased(..)
+            | ButtonRepeated(..)
+            | ButtonChanged(..)
+            | AxisChanged(..)
+            | TouchpadTouch { .. } => data.last_event_instant = utils::instant_now(),
+            _ => (),
+        }
+
+        if let (Some(level), true) = (self.event_log_level, self.state_logging) {
+            match event.event {
+                ButtonPressed(_, nec)
+                | ButtonReleased(_, nec)
+                | ButtonRepeated(_, nec)
+                | ButtonChanged(_, _, nec) => log!(
+                    level,
+                    "Gamepad {} button {} state: {:?}",
+                    event.id,
+                    nec,
+                    data.state.button_data(nec)
+                ),
+                AxisChanged(_, _, nec) => log!(
+                    level,
+                    "Gamepad {} axis {} state: {:?}",
+                    event.id,
+                    nec,
+                    data.state.axis_data(nec)
+                ),
+                _ => (),
+            }
+        }
+
+        for hook in &self.update_hooks.0 {
+            hook(event, &data.state);
         }
     }
 
@@ -364,6 +1143,9 @@ impl Gilrs {
     /// determine when last event happened. You probably want to use this function in your update
     /// loop after processing events.
     pub fn inc(&mut self) {
+        self.added_this_frame.clear();
+        self.removed_this_frame.clear();
+
         // Counter is 62bit. See `ButtonData`.
         if self.counter == 0x3FFF_FFFF_FFFF_FFFF {
             self.counter = 0;
@@ -383,58 +1165,458 @@ impl Gilrs {
         self.counter = 0;
     }
 
-    fn finish_gamepads_creation(&mut self) {
-        let tx = self.tx.clone();
-        for id in 0..self.inner.last_gamepad_hint() {
-            let gamepad = self.inner.gamepad(id).unwrap();
-            self.gamepads_data.push(GamepadData::new(
-                GamepadId(id),
-                tx.clone(),
-                gamepad,
-                &self.mappings,
-            ))
+    /// Returns `true` if `btn` of gamepad `id` was pressed since last call to `inc()`. Repeats
+    /// from `ButtonRepeated` don't count. See `ButtonData::is_just_pressed()`.
+    pub fn just_pressed(&self, id: GamepadId, btn: Button) -> bool {
+        match self.gamepads_data.get(id.0) {
+            Some(data) => data
+                .button_data(btn)
+                .map(|data| data.is_just_pressed(self.counter))
+                .unwrap_or(false),
+            None => false,
         }
     }
 
-    /// Returns handle to gamepad with given ID. Unlike `connected_gamepad()`, this function will
-    /// also return handle to gamepad that is currently disconnected.
-    ///
-    /// ```
-    /// # let mut gilrs = gilrs::Gilrs::new().unwrap();
-    /// use gilrs::{Button, EventType};
-    ///
-    /// loop {
-    ///     while let Some(ev) = gilrs.next_event() {
-    ///         // unwrap() should never panic because we use id from event
-    ///         let is_up_pressed = gilrs.gamepad(ev.id).is_pressed(Button::DPadUp);
-    ///
-    ///         match ev.event {
-    ///             EventType::ButtonPressed(Button::South, _) if is_up_pressed => {
-    ///                 // do something…
-    ///             }
-    ///             _ => (),
-    ///         }
-    ///     }
-    ///     # break;
-    /// }
-    /// ```
-    pub fn gamepad(&self, id: GamepadId) -> Gamepad {
-        Gamepad {
-            inner: self.inner.gamepad(id.0).unwrap(),
-            data: &self.gamepads_data[id.0],
+    /// Returns `true` if `btn` of gamepad `id` was released since last call to `inc()`.
+    pub fn just_released(&self, id: GamepadId, btn: Button) -> bool {
+        match self.gamepads_data.get(id.0) {
+            Some(data) => data
+                .button_data(btn)
+                .map(|data| data.is_just_released(self.counter))
+                .unwrap_or(false),
+            None => false,
         }
     }
 
-    /// Returns a reference to connected gamepad or `None`.
-    pub fn connected_gamepad(&self, id: GamepadId) -> Option<Gamepad<'_>> {
-        // Make sure that it will not panic even with invalid GamepadId, so ConnectedGamepadIterator
-        // will always work.
-        if let Some(data) = self.gamepads_data.get(id.0) {
-            let inner = self.inner.gamepad(id.0).unwrap();
+    /// Returns IDs of gamepads that were connected since last call to `inc()`. Gamepad is
+    /// reported once even if it was connected multiple times.
+    ///
+    /// List is filled by `update()`, so if automatic state updates are disabled, it only contains
+    /// gamepads from events that were passed to `update()`.
+    pub fn gamepad_added_events(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.added_this_frame.iter().cloned()
+    }
 
-            if inner.is_connected() {
-                Some(Gamepad { inner, data })
-            } else {
+    /// Returns IDs of gamepads that were disconnected since last call to `inc()`. See
+    /// [`gamepad_added_events()`](#method.gamepad_added_events) for details.
+    pub fn gamepad_removed_events(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.removed_this_frame.iter().cloned()
+    }
+
+    /// Converts `Gilrs` to stream of events that can be used in async code. See
+    /// [`stream`](stream/index.html) module for more info.
+    #[cfg(feature = "async")]
+    pub fn into_event_stream(self) -> EventStream {
+        EventStream::new(self)
+    }
+
+    /// Descriptors that become readable when `next_event()` may return something, or `None` if
+    /// backend has to be polled periodically.
+    #[cfg(feature = "async")]
+    pub(crate) fn event_fds(&self) -> Option<Vec<c_int>> {
+        #[cfg(target_os = "linux")]
+        {
+            self.inner.event_fds()
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            None
+        }
+    }
+
+    /// Returns sequence number of last event returned by `next_event()`, or 0 if no event was
+    /// returned yet. See [`Event::seq`](struct.Event.html#structfield.seq).
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq
+    }
+
+    pub(crate) fn next_seq(&mut self) -> u64 {
+        // 64 bits are enough to never overflow in practice; debug builds will panic if it does.
+        self.last_seq += 1;
+        self.last_seq
+    }
+
+    /// Returns how many times counter was increased since the last input from gamepad, which in
+    /// frame synchronous games is number of frames that gamepad has been idle. If there was no
+    /// input from gamepad, current value of counter is returned.
+    ///
+    /// Returns 0 if counter was reset or wrapped around after the last input.
+    pub fn frames_since_last_input(&self, id: GamepadId) -> u64 {
+        let last = self
+            .gamepads_data
+            .get(id.0)
+            .map(|data| data.state.last_event_counter())
+            .unwrap_or(0);
+
+        self.counter.saturating_sub(last)
+    }
+
+    /// Sets how long wireless gamepad can stay idle before it's powered off with
+    /// [`Gamepad::power_off()`](struct.Gamepad.html#method.power_off). `None`, the default,
+    /// disables it.
+    ///
+    /// Gamepad is idle when `next_event()` doesn't return any events for it, so input removed by
+    /// filters (like stick jitter) doesn't count. Only gamepads whose power info is
+    /// `PowerInfo::Discharging` are powered off, wired gamepads are never affected. Idle time is
+    /// checked when `next_event()` returns `None`, at most once per second, so gamepad can be
+    /// powered off up to a second late. Powered off gamepad disconnects and `Disconnected` event
+    /// is emitted as usual. If gamepad can't be powered off, error is logged and it's not tried
+    /// again until gamepad is used.
+    ///
+    /// Because `Gamepad::power_off()` is not supported by any platform yet, this currently has
+    /// no effect outside of `TestBackend`.
+    pub fn set_idle_timeout(&mut self, timeout: Option<Duration>) {
+        self.idle_timeout = timeout;
+    }
+
+    /// Returns statistics of latency of events from gamepad with `id`, that is time between
+    /// creation of event and moment when `next_event()` returned it. Returns `None` if latency
+    /// metrics are disabled (see `GilrsBuilder::with_latency_metrics()`) or if no event was
+    /// measured yet.
+    ///
+    /// On platforms that provide kernel timestamps (Linux) this includes time event spent in
+    /// kernel and in application before it called `next_event()`. On other platforms it only
+    /// includes time spent queued in gilrs, which is indicated by `LatencyStats::queueing_only`.
+    pub fn latency_stats(&self, id: GamepadId) -> Option<LatencyStats> {
+        if !self.latency_metrics {
+            return None;
+        }
+
+        let queueing_only = !self.inner.has_kernel_timestamps();
+        self.gamepads_data
+            .get(id.0)
+            .and_then(|data| data.latency.stats(queueing_only))
+    }
+
+    /// Clears latency statistics of all gamepads.
+    pub fn reset_latency_stats(&mut self) {
+        for data in &mut self.gamepads_data {
+            data.latency.reset();
+        }
+    }
+
+    /// Returns histogram of latency of input events from all gamepads, that is time between
+    /// creation of event and moment when `next_event()` returned it.
+    ///
+    /// This measures processing latency: time event spent in kernel (on platforms with kernel
+    /// timestamps, see `LatencyStats::queueing_only`), in gilrs, and in application before it
+    /// called `next_event()`. It doesn't include latency of gamepad itself, its connection or
+    /// USB/Bluetooth stack, so it's not end-to-end input latency.
+    #[cfg(feature = "latency-tracking")]
+    pub fn event_latency_histogram(&self) -> &LatencyHistogram {
+        &self.event_latency
+    }
+
+    /// Returns approximate latency below which are `pct` (0.0 to 1.0) of events returned by
+    /// `next_event()`, or `None` if no event was measured yet. See
+    /// [`event_latency_histogram()`](#method.event_latency_histogram) for what is measured.
+    #[cfg(feature = "latency-tracking")]
+    pub fn event_latency_percentile(&self, pct: f64) -> Option<Duration> {
+        self.event_latency.percentile(pct)
+    }
+
+    /// Clears histogram returned by `event_latency_histogram()`.
+    #[cfg(feature = "latency-tracking")]
+    pub fn reset_latency_histogram(&mut self) {
+        self.event_latency.reset();
+    }
+
+    /// Enables or disables compensation of weaker force feedback on low battery. Disabled by
+    /// default.
+    ///
+    /// Many wireless gamepads rumble noticeably weaker when their battery is low. When
+    /// compensation is enabled and gamepad's power info is `PowerInfo::Discharging(level)` with
+    /// `level` below threshold (see `set_ff_battery_compensation_threshold()`), magnitudes of all
+    /// effects played on it are boosted, up to 1.5 times for empty battery. Power info is checked
+    /// about once per second when `next_event()` returns `None`. Current factor can be read with
+    /// [`Gamepad::ff_battery_compensation()`](struct.Gamepad.html#method.ff_battery_compensation).
+    pub fn set_ff_battery_compensation(&mut self, enabled: bool) {
+        self.ff_battery_compensation = enabled;
+        self.update_ff_compensation(true);
+    }
+
+    /// Sets battery level, in percent, below which force feedback is boosted when battery
+    /// compensation is enabled. Default is 30.
+    pub fn set_ff_battery_compensation_threshold(&mut self, threshold: u8) {
+        self.ff_compensation_threshold = threshold;
+        self.update_ff_compensation(true);
+    }
+
+    /// Sets falloff constant `k` of force feedback attenuation based on distance between gamepad
+    /// and its listener. Magnitudes of effects played on gamepad are multiplied by
+    /// `1.0 / (1.0 + k * distance²)`, where `distance` is distance between position set with
+    /// [`set_gamepad_position()`](#method.set_gamepad_position) and
+    /// [`Gamepad::set_listener_position()`](struct.Gamepad.html#method.set_listener_position).
+    ///
+    /// Positions are in arbitrary units, only ratio between distance and `k` matters. Default is
+    /// 0.0, which disables attenuation. Negative values are treated as 0.0.
+    pub fn set_ff_attenuation(&mut self, k: f32) {
+        self.ff_attenuation = k.max(0.0);
+        self.sync_ff_attenuation();
+    }
+
+    /// Returns how often force feedback server updates effects. See
+    /// [`GilrsBuilder::ff_tick_duration()`](struct.GilrsBuilder.html#method.ff_tick_duration).
+    pub fn ff_tick_duration(&self) -> Duration {
+        self.ff_tick_duration
+    }
+
+    /// Changes position of gamepad used by force feedback attenuation, see
+    /// [`set_ff_attenuation()`](#method.set_ff_attenuation). Unlike listener position, it doesn't
+    /// affect effects' distance models. Position is reset to `[0.0, 0.0, 0.0]` when gamepad is
+    /// reconnected.
+    pub fn set_gamepad_position<Vec3: Into<[f32; 3]>>(
+        &mut self,
+        id: GamepadId,
+        position: Vec3,
+    ) -> Result<(), FfError> {
+        let gamepad = self.gamepad(id);
+        if !gamepad.is_connected() {
+            Err(FfError::Disconnected(id))
+        } else if !gamepad.is_ff_supported() {
+            Err(FfError::FfNotSupported(id))
+        } else {
+            let position = position.into();
+            self.tx
+                .send(Message::SetGamepadPosition { id: id.0, position })?;
+            self.gamepads_data[id.0].gamepad_position.set(position);
+            Ok(())
+        }
+    }
+
+    /// Makes sure that there is `GamepadData` for every ID lower than `last_gamepad_hint()`, so
+    /// `gamepad()` will not panic for IDs that backend knows about.
+    fn sync_gamepads_data(&mut self) {
+        for id in self.gamepads_data.len()..self.inner.last_gamepad_hint() {
+            self.gamepads_data.push(GamepadData::not_observed(
+                GamepadId(id),
+                self.tx.clone(),
+                self.next_ff_id.clone(),
+            ));
+        }
+    }
+
+    fn is_ignored(&self, id: usize) -> bool {
+        self.inner
+            .gamepad(id)
+            .map(|gamepad| self.device_filter.is_ignored(gamepad.uuid()))
+            .unwrap_or(false)
+    }
+
+    fn finish_gamepads_creation(&mut self) {
+        let tx = self.tx.clone();
+        for id in 0..self.inner.last_gamepad_hint() {
+            let gamepad = self.inner.gamepad(id).unwrap();
+            if self.device_filter.is_ignored(gamepad.uuid()) {
+                debug!("Ignoring gamepad {} ({})", id, gamepad.name());
+                self.gamepads_data.push(GamepadData::not_observed(
+                    GamepadId(id),
+                    tx.clone(),
+                    self.next_ff_id.clone(),
+                ));
+                continue;
+            }
+
+            let mut data = GamepadData::new(
+                GamepadId(id),
+                tx.clone(),
+                self.next_ff_id.clone(),
+                gamepad,
+                &self.mappings,
+                self.mapping_options(),
+            );
+            data.axis_overrides = self.axis_overrides(gamepad.uuid());
+            self.gamepads_data.push(data);
+        }
+    }
+
+    /// Replaces `GamepadData` of gamepad that was (re)connected or resumed, keeping settings that
+    /// survive reconnection. Returns info about gamepad.
+    fn reopen_gamepad_data(&mut self, id: GamepadId) -> GamepadInfo {
+        if id.0 >= self.gamepads_data.len() {
+            error!(
+                "Platform implementation error: got Connected event with id {}, when expected id \
+                 lower than {}",
+                id.0,
+                self.gamepads_data.len()
+            );
+
+            return GamepadInfo {
+                name: String::new(),
+                uuid: [0; 16],
+                mapping_source: MappingSource::None,
+                is_ff_supported: false,
+            };
+        }
+
+        let overrides = self.axis_overrides(self.inner.gamepad(self.ids.raw(id)).unwrap().uuid());
+        let mut data = GamepadData::new(
+            id,
+            self.tx.clone(),
+            self.next_ff_id.clone(),
+            self.inner.gamepad(self.ids.raw(id)).unwrap(),
+            &self.mappings,
+            self.mapping_options(),
+        );
+        data.set_connected(true);
+        data.axis_overrides = overrides;
+        data.curves = mem::take(&mut self.gamepads_data[id.0].curves);
+        data.suppressed = self.gamepads_data[id.0].suppressed;
+        data.latency = self.gamepads_data[id.0].latency.clone();
+        self.gamepads_data[id.0] = data;
+
+        self.gamepad(id).info()
+    }
+
+    /// Handles `Connected` event for gamepad that was never reported as disconnected. Some drivers
+    /// emit it after waking up from suspend. State, user settings and force feedback effects are
+    /// kept, only mapping is found again if UUID changed and force feedback device is replaced if
+    /// it stopped working.
+    fn refresh_gamepad_data(&mut self, id: GamepadId) -> GamepadInfo {
+        let options = self.mapping_options();
+        let gamepad = self.inner.gamepad(self.ids.raw(id)).unwrap();
+        let uuid = gamepad.uuid();
+        let overrides = self.axis_overrides(uuid);
+        let data = &mut self.gamepads_data[id.0];
+
+        if uuid == data.uuid {
+            info!(
+                "Gamepad {} reported Connected while already connected, keeping its data",
+                id
+            );
+        } else {
+            debug!(
+                "Gamepad {} reported Connected with different UUID {}, finding new mapping",
+                id,
+                Uuid::from_bytes(uuid)
+            );
+            data.update_mapping(gamepad, &self.mappings, options);
+            data.axis_overrides = overrides;
+            data.uuid = uuid;
+        }
+        data.report_rate = gamepad.input_report_rate().filter(|&rate| rate > 0);
+
+        // Old device handle may be stale if driver recreated the device. Server checks it and uses
+        // the new one only if needed, so settings and playing effects are kept.
+        if gamepad.is_ff_supported() {
+            if let Some(device) = gamepad.ff_device() {
+                data.max_ff_effects = Some(device.query_max_effects());
+                let _ = self.tx.send(Message::Reopen { id: id.0, device });
+            }
+        }
+
+        self.gamepad(id).info()
+    }
+
+    fn mapping_options(&self) -> MappingOptions {
+        MappingOptions {
+            strict: self.strict_uuid_matching,
+            layout: self.button_layout,
+            auto_mapping: self.auto_mapping,
+        }
+    }
+
+    /// Returns axis info overrides that apply to gamepad with `uuid`. Overrides for specific UUID
+    /// take precedence over overrides for all gamepads.
+    fn axis_overrides(&self, uuid: [u8; 16]) -> FnvHashMap<Code, AxisInfo> {
+        let mut overrides = self.axis_info_overrides_all.clone();
+        overrides.extend(
+            self.axis_info_overrides
+                .iter()
+                .filter(|&(&(override_uuid, _), _)| override_uuid == uuid)
+                .map(|(&(_, code), &info)| (code, info)),
+        );
+
+        overrides
+    }
+
+    /// Returns handle to gamepad with given ID. Unlike `connected_gamepad()`, this function will
+    /// also return handle to gamepad that is currently disconnected or was never observed (see
+    /// [`Gamepad::status()`](struct.Gamepad.html#method.status)).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is not lower than backend's `last_gamepad_hint()`. This can't happen with
+    /// IDs obtained from events or `gamepads()`. Gamepads that backend already knows about, but
+    /// which were not reported by `next_event()` yet, have status `Status::NotObserved`.
+    ///
+    /// ```
+    /// # let mut gilrs = gilrs::Gilrs::new().unwrap();
+    /// use gilrs::{Button, EventType};
+    ///
+    /// loop {
+    ///     while let Some(ev) = gilrs.next_event() {
+    ///         // unwrap() should never panic because we use id from event
+    ///         let is_up_pressed = gilrs.gamepad(ev.id).is_pressed(Button::DPadUp);
+    ///
+    ///         match ev.event {
+    ///             EventType::ButtonPressed(Button::South, _) if is_up_pressed => {
+    ///                 // do something…
+    ///             }
+    ///             _ => (),
+    ///         }
+    ///     }
+    ///     # break;
+    /// }
+    /// ```
+    pub fn gamepad(&self, id: GamepadId) -> Gamepad {
+        Gamepad {
+            id,
+            inner: self.inner.gamepad(self.ids.raw(id)).unwrap(),
+            data: self
+                .gamepads_data
+                .get(id.0)
+                .unwrap_or(&self.not_observed_data),
+        }
+    }
+
+    /// Like `gamepad()`, but returns `None` instead of panicking if `id` is invalid.
+    pub(crate) fn gamepad_checked(&self, id: GamepadId) -> Option<Gamepad<'_>> {
+        let data = self.gamepads_data.get(id.0)?;
+        let inner = self.inner.gamepad(self.ids.raw(id))?;
+
+        Some(Gamepad { id, inner, data })
+    }
+
+    /// Returns ID of gamepad that emitted `ev` as `usize`.
+    pub fn gamepad_id_from_event(&self, ev: &Event) -> usize {
+        ev.id.into()
+    }
+
+    /// Returns connection status of gamepad with given ID. Unlike `gamepad()`, this function never
+    /// panics – `Status::NotObserved` is returned for unknown IDs.
+    pub fn status(&self, id: GamepadId) -> Status {
+        self.gamepads_data
+            .get(id.0)
+            .map(|data| data.status)
+            .unwrap_or(Status::NotObserved)
+    }
+
+    /// Returns zero-based player index that platform or gamepad itself assigned to gamepad, or
+    /// `None` if there is none or `id` is invalid.
+    ///
+    /// | Platform | Source                                                                      |
+    /// |----------|-----------------------------------------------------------------------------|
+    /// | Linux    | Player shown by LEDs (set by `xpad` and `hid-nintendo`), or `jsN` number.   |
+    /// | Windows  | XInput user index.                                                          |
+    /// | Others   | Not supported.                                                              |
+    ///
+    /// On Linux gamepads connected when `Gilrs` is created are also ordered by port they are
+    /// connected to, so IDs of identical gamepads don't change between runs.
+    pub fn suggested_player_index(&self, id: GamepadId) -> Option<u8> {
+        self.gamepad_checked(id)?.inner.suggested_player_index()
+    }
+
+    /// Returns a reference to connected gamepad or `None`.
+    pub fn connected_gamepad(&self, id: GamepadId) -> Option<Gamepad<'_>> {
+        // Make sure that it will not panic even with invalid GamepadId, so ConnectedGamepadIterator
+        // will always work.
+        if let Some(data) = self.gamepads_data.get(id.0) {
+            let inner = self.inner.gamepad(self.ids.raw(id)).unwrap();
+
+            if inner.is_connected() && !self.device_filter.is_ignored(inner.uuid()) {
+                Some(Gamepad { id, inner, data })
+            } else {
                 None
             }
         } else {
@@ -456,117 +1638,634 @@ impl Gilrs {
         ConnectedGamepadsIterator(self, 0)
     }
 
-    /// Adds `ev` at the end of internal event queue. It can later be retrieved with `next_event()`.
-    pub fn insert_event(&mut self, ev: Event) {
-        self.events.push_back(ev);
+    /// Returns first connected gamepad, in order of IDs, for which `predicate` returns `true`.
+    /// See also [`GamepadIteratorExt`](trait.GamepadIteratorExt.html) for common filters.
+    ///
+    /// ```
+    /// # let gilrs = gilrs::Gilrs::new().unwrap();
+    /// if let Some((id, gamepad)) = gilrs.find_gamepad(|gp| gp.is_ff_supported()) {
+    ///     println!("Player 1 is {} ({})", gamepad.name(), id);
+    /// }
+    /// ```
+    pub fn find_gamepad<F>(&self, mut predicate: F) -> Option<(GamepadId, Gamepad<'_>)>
+    where
+        F: FnMut(&Gamepad<'_>) -> bool,
+    {
+        self.gamepads().find(|(_, gamepad)| predicate(gamepad))
     }
 
-    pub(crate) fn ff_sender(&self) -> &Sender<Message> {
-        &self.tx
+    /// Returns copy of cached state of all connected gamepads.
+    ///
+    /// This is useful when input is passed to scripting language, where one call per button is
+    /// expensive, or when application wants to compare state with previous frame. See
+    /// [`InputSnapshot`](struct.InputSnapshot.html).
+    pub fn snapshot(&self) -> InputSnapshot {
+        InputSnapshot {
+            gamepads: self
+                .gamepads()
+                .map(|(_, gamepad)| GamepadSnapshot::new(&gamepad))
+                .collect(),
+        }
     }
 
-    /// Sets gamepad's mapping and returns SDL2 representation of them. Returned mappings may not be
-    /// compatible with SDL2 - if it is important, use
-    /// [`set_mapping_strict()`](#method.set_mapping_strict).
+    /// Forces backend to enumerate connected devices again. Use it if you suspect that some
+    /// hotplug events were missed, for example after application was suspended. Returns number
+    /// of `Connected` and `Disconnected` events that will be returned by `next_event()` for
+    /// gamepads that appeared or disappeared.
     ///
-    /// The `name` argument can be a string slice with custom gamepad name or `None`. If `None`,
-    /// gamepad name reported by driver will be used.
+    /// `TestBackend` reports changes immediately, so with it this function always returns 0.
+    pub fn rescan_gamepads(&mut self) -> usize {
+        let changed = self.inner.rescan_gamepads();
+        self.sync_gamepads_data();
+
+        changed
+    }
+
+    /// Sets how often backend polls gamepads, in Hz. Useful for applications that want to save
+    /// power and don't need every input report.
     ///
-    /// # Errors
+    /// Supported on Windows, where XInput is polled at 100 Hz by default, and on macOS, where it
+    /// sets report interval of devices. On other platforms, and with `TestBackend`, it returns
+    /// `PollingRateError::NotSupported`. Returns `PollingRateError::InvalidRate` if `hz` is 0.
+    pub fn set_polling_rate(&mut self, hz: u32) -> Result<(), PollingRateError> {
+        if hz == 0 {
+            return Err(PollingRateError::InvalidRate);
+        }
+
+        self.inner.set_polling_rate(hz)
+    }
+
+    /// Forwards `AInputEvent` received by application to backend. Returns true if event came from
+    /// gamepad and shouldn't be handled by application again. See
+    /// [`gilrs_core::Gilrs::handle_android_event()`][core] and "Platform specific notes" in
+    /// crate documentation.
     ///
-    /// This function return error if `name` contains comma, `mapping` have axis and button entry
-    /// for same element (for example `Axis::LetfTrigger` and `Button::LeftTrigger`) or gamepad does
-    /// not have any element with `EvCode` used in mapping. `Button::Unknown` and
-    /// `Axis::Unknown` are not allowd as keys to `mapping` – in this case,
-    /// `MappingError::UnknownElement` is returned.
+    /// Always returns false with `TestBackend`.
     ///
-    /// Error is also returned if this function is not implemented or gamepad is not connected.
+    /// [core]: ../gilrs_core/struct.Gilrs.html#method.handle_android_event
     ///
-    /// # Example
+    /// # Safety
     ///
-    /// ```
-    /// use gilrs::{Mapping, Button};
+    /// `event` must be null or point to valid `AInputEvent`.
+    #[cfg(target_os = "android")]
+    pub unsafe fn handle_android_event(&mut self, event: *const std::os::raw::c_void) -> bool {
+        self.inner.handle_android_event(event)
+    }
+
+    /// Gives backend access to application's Java VM, so it can enumerate gamepads and play force
+    /// feedback on their vibrators through JNI. See
+    /// [`gilrs_core::Gilrs::android_set_java_vm()`][core_vm] and "Platform specific notes" in
+    /// crate documentation. `Connected` events of gamepads that were found are returned by
+    /// `next_event()`.
     ///
-    /// # let mut gilrs = gilrs::Gilrs::new().unwrap();
-    /// let mut data = Mapping::new();
-    /// // …
+    /// Does nothing with `TestBackend`.
     ///
-    /// // or `match gilrs.set_mapping(0, &data, None) {`
-    /// match gilrs.set_mapping(0, &data, "Custom name") {
-    ///     Ok(sdl) => println!("SDL2 mapping: {}", sdl),
-    ///     Err(e) => println!("Failed to set mapping: {}", e),
-    /// };
-    /// ```
+    /// [core_vm]: ../gilrs_core/struct.Gilrs.html#method.android_set_java_vm
     ///
-    /// See also `examples/mapping.rs`.
-    pub fn set_mapping<'b, O: Into<Option<&'b str>>>(
-        &mut self,
-        gamepad_id: usize,
-        mapping: &MappingData,
-        name: O,
-    ) -> Result<String, MappingError> {
-        if let Some(gamepad) = self.inner.gamepad(gamepad_id) {
-            if gamepad.is_connected() {
-                return Err(MappingError::NotConnected);
-            }
+    /// # Safety
+    ///
+    /// `vm` must be null or point to `JavaVM` that stays valid as long as `Gilrs` exists.
+    #[cfg(target_os = "android")]
+    pub unsafe fn android_set_java_vm(&mut self, vm: *mut std::os::raw::c_void) {
+        self.inner.android_set_java_vm(vm);
+        self.sync_gamepads_data();
+    }
 
-            let name = match name.into() {
-                Some(s) => s,
-                None => gamepad.name(),
-            };
+    /// Reports gamepad added by `InputManager.InputDeviceListener`. `axes` are IDs of device's
+    /// motion ranges, see [`gilrs_core::Gilrs::android_device_added()`][core_added]. `Connected`
+    /// event is returned by next call to `next_event()`.
+    ///
+    /// [core_added]: ../gilrs_core/struct.Gilrs.html#method.android_device_added
+    #[cfg(target_os = "android")]
+    pub fn android_device_added(
+        &mut self,
+        device_id: i32,
+        name: &str,
+        vendor_id: u16,
+        product_id: u16,
+        axes: &[i32],
+    ) {
+        self.inner
+            .android_device_added(device_id, name, vendor_id, product_id, axes);
+        self.sync_gamepads_data();
+    }
 
-            let (mapping, s) = Mapping::from_data(
-                mapping,
-                gamepad.buttons(),
-                gamepad.axes(),
-                name,
-                Uuid::from_bytes(gamepad.uuid()),
-            )?;
+    /// Reports gamepad removed by `InputManager.InputDeviceListener`.
+    #[cfg(target_os = "android")]
+    pub fn android_device_removed(&mut self, device_id: i32) {
+        self.inner.android_device_removed(device_id)
+    }
 
-            // We checked if gamepad is connected, so it should never panic
-            let data = &mut self.gamepads_data[gamepad_id];
-            data.mapping = mapping;
+    /// Returns multi-line, human readable description of all gamepads that were ever observed,
+    /// including disconnected ones. It lists their names, UUIDs, mapping source, status, and all
+    /// buttons and axes reported by device together with element they are mapped to.
+    ///
+    /// Output is intended to be attached to bug reports. Its format is not stable.
+    pub fn debug_dump(&self) -> String {
+        let mut out = String::new();
 
-            Ok(s)
-        } else {
-            Err(MappingError::NotConnected)
+        for id in 0..self.gamepads_data.len() {
+            let gamepad = self.gamepad(GamepadId(id));
+            // Writing to String never fails.
+            let _ = gamepad.write_debug_dump(&mut out);
         }
+
+        out
     }
 
-    /// Similar to [`set_mapping()`](#method.set_mapping) but returned string should be compatible
-    /// with SDL2.
+    /// Sets response curve for axis or button with given `code`. Curve is applied to values of
+    /// `AxisChanged` and `ButtonChanged` events after default filters (including deadzone), so
+    /// curve's input 0.0 corresponds to edge of dead zone. Use `Gamepad::axis_code()` or
+    /// `Gamepad::button_code()` to find `code` of mapped element.
+    ///
+    /// Mappings can also set curves with `axis` field, for example `axis:leftx=expo(0.3)` or
+    /// `axis:lefttrigger=0/0;0.4/0.1;1/1` (control points are separated with `;`). Curve set
+    /// with this function overrides one from mapping.
+    ///
+    /// Curve stays in effect when gamepad is reconnected. Set `CurveTable::linear()` to disable
+    /// it.
     ///
     /// # Errors
     ///
-    /// Returns `MappingError::NotSdl2Compatible` if `mapping` have an entry for `Button::{C, Z}`
-    /// or `Axis::{LeftZ, RightZ}`.
-    pub fn set_mapping_strict<'b, O: Into<Option<&'b str>>>(
+    /// Returns `CurveError::UnknownGamepad` if gamepad with given ID was never observed.
+    pub fn set_axis_curve(
         &mut self,
-        gamepad_id: usize,
-        mapping: &MappingData,
-        name: O,
-    ) -> Result<String, MappingError> {
-        if mapping.button(Button::C).is_some()
-            || mapping.button(Button::Z).is_some()
-            || mapping.axis(Axis::LeftZ).is_some()
-            || mapping.axis(Axis::RightZ).is_some()
-        {
-            Err(MappingError::NotSdl2Compatible)
-        } else {
-            self.set_mapping(gamepad_id, mapping, name)
+        id: GamepadId,
+        code: Code,
+        curve: CurveTable,
+    ) -> Result<(), CurveError> {
+        let data = self
+            .gamepads_data
+            .get_mut(id.0)
+            .ok_or(CurveError::UnknownGamepad(id))?;
+        data.curves.insert(code, curve);
+
+        Ok(())
+    }
+
+    /// Stops returning input events of gamepad with given ID from `next_event()`, for example to
+    /// ignore gamepad while steering wheel is used. Gamepad's state is still updated and
+    /// connection events (`Connected`, `Disconnected`, `ConnectionLost`, `ConnectionRestored`,
+    /// `Suspended` and `Resumed`) are still returned. Gamepad stays suppressed when it's
+    /// reconnected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if gamepad with given ID was never observed.
+    pub fn suppress_gamepad(&mut self, id: GamepadId) {
+        self.gamepads_data[id.0].suppressed = true;
+    }
+
+    /// Reverts `suppress_gamepad()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if gamepad with given ID was never observed.
+    pub fn unsuppress_gamepad(&mut self, id: GamepadId) {
+        self.gamepads_data[id.0].suppressed = false;
+    }
+
+    /// Adds `ev` at the end of internal event queue. It can later be retrieved with `next_event()`,
+    /// which will assign it new sequence number.
+    ///
+    /// `EventSource::Synthetic` source set by caller is kept, for example by custom filter that
+    /// inserts events it created. Events with `EventSource::Hardware` are not coming from device
+    /// anymore, so their source is changed to `EventSource::Inserted`.
+    pub fn insert_event(&mut self, mut ev: Event) {
+        if ev.source == EventSource::Hardware {
+            ev.source = EventSource::Inserted;
         }
+        self.events.push_back(ev);
     }
 
-    pub(crate) fn next_ff_id(&mut self) -> usize {
-        // TODO: reuse free ids
-        let id = self.next_id;
-        self.next_id = match self.next_id.checked_add(1) {
-            Some(x) => x,
-            None => panic!("Failed to assign ID to new effect"),
+    /// Releases all pressed buttons and resets all axes of gamepad with `id`, or of all gamepads if
+    /// `id` is `None`. Use it when application stops reading events for a while, for example when
+    /// its window loses focus, so buttons released in the meantime don't appear stuck.
+    ///
+    /// For every button and axis with non-zero value, `ButtonReleased`, `ButtonChanged` or
+    /// `AxisChanged` event is queued. Gamepad's state is updated immediately, even if automatic
+    /// state updates are disabled. Queued events are returned by `next_event()` before other
+    /// events and they are not processed by filters. Use [`resync()`](#method.resync) to find
+    /// out which buttons are really held.
+    pub fn release_all(&mut self, id: Option<GamepadId>) {
+        let ids: Vec<_> = match id {
+            Some(id) => vec![id],
+            None => (0..self.gamepads_data.len()).map(GamepadId).collect(),
         };
-        id
-    }
-}
+        let time = self.time_now();
+
+        for id in ids {
+            if let Some(data) = self.gamepads_data.get_mut(id.0) {
+                data.released_axes = data.state.axes().map(|(code, _)| code).collect();
+            }
+
+            for event in self.release_events(id) {
+                let ev = Event {
+                    id,
+                    event,
+                    time,
+                    seq: 0,
+                    source: EventSource::Synthetic(FilterId::Gilrs),
+                };
+                self.update(&ev);
+                self.synthesized_events.push_back(ev);
+            }
+        }
+    }
+
+    /// Returns events that set all buttons and axes of gamepad to 0.0.
+    fn release_events(&self, id: GamepadId) -> Vec<EventType> {
+        let gamepad = match self.gamepad_checked(id) {
+            Some(gamepad) => gamepad,
+            None => return Vec::new(),
+        };
+
+        let mut events = Vec::new();
+        for (code, data) in gamepad.state().buttons() {
+            let btn = btn_name(&gamepad, code);
+            if data.is_pressed() {
+                events.push(EventType::ButtonReleased(btn, code));
+            }
+            if data.value() != 0.0 {
+                events.push(EventType::ButtonChanged(btn, 0.0, code));
+            }
+        }
+
+        for (code, data) in gamepad.state().axes() {
+            if data.value() != 0.0 {
+                let axis = match gamepad.axis_or_btn_name(code) {
+                    Some(AxisOrBtn::Axis(axis)) => axis,
+                    _ => Axis::Unknown,
+                };
+                events.push(EventType::AxisChanged(axis, 0.0, code));
+            }
+        }
+
+        events
+    }
+
+    /// Replaces state of gamepad with `state`, for example authoritative state received over
+    /// network, and sets counter to `counter`. For every button and axis that differs between old
+    /// and new state, `ButtonPressed`, `ButtonReleased`, `ButtonChanged` or `AxisChanged` event
+    /// with time `time` is queued.
+    ///
+    /// Like events from [`release_all()`](#method.release_all), queued events are returned by
+    /// `next_event()` before other events and they are not processed by filters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if gamepad with given ID was never observed.
+    pub fn update_from_state(
+        &mut self,
+        id: GamepadId,
+        state: GamepadState,
+        counter: u64,
+        time: SystemTime,
+    ) {
+        let events = self.state_diff(id, &state);
+        self.gamepads_data[id.0].state = state;
+        self.counter = counter;
+
+        for event in events {
+            self.synthesized_events.push_back(Event {
+                id,
+                event,
+                time,
+                seq: 0,
+                source: EventSource::Synthetic(FilterId::Gilrs),
+            });
+        }
+    }
+
+    /// Returns events that change state of gamepad to `new`.
+    fn state_diff(&self, id: GamepadId, new: &GamepadState) -> Vec<EventType> {
+        let gamepad = self.gamepad(id);
+        let old = gamepad.state();
+
+        let mut buttons: Vec<_> = old.buttons().map(|(code, _)| code).collect();
+        buttons.extend(
+            new.buttons()
+                .map(|(code, _)| code)
+                .filter(|code| old.button_data(*code).is_none()),
+        );
+        let mut axes: Vec<_> = old.axes().map(|(code, _)| code).collect();
+        axes.extend(
+            new.axes()
+                .map(|(code, _)| code)
+                .filter(|code| old.axis_data(*code).is_none()),
+        );
+
+        let mut events = Vec::new();
+        for code in buttons {
+            let btn = btn_name(&gamepad, code);
+            match (old.is_pressed(code), new.is_pressed(code)) {
+                (false, true) => events.push(EventType::ButtonPressed(btn, code)),
+                (true, false) => events.push(EventType::ButtonReleased(btn, code)),
+                _ => (),
+            }
+
+            let value = |state: &GamepadState| state.button_data(code).map(|data| data.value());
+            let new_value = value(new).unwrap_or(0.0);
+            if value(old).unwrap_or(0.0) != new_value {
+                events.push(EventType::ButtonChanged(btn, new_value, code));
+            }
+        }
+
+        for code in axes {
+            let value = |state: &GamepadState| state.axis_data(code).map(|data| data.value());
+            let new_value = value(new).unwrap_or(0.0);
+            if value(old).unwrap_or(0.0) != new_value {
+                let axis = match gamepad.axis_or_btn_name(code) {
+                    Some(AxisOrBtn::Axis(axis)) => axis,
+                    _ => Axis::Unknown,
+                };
+                events.push(EventType::AxisChanged(axis, new_value, code));
+            }
+        }
+
+        events
+    }
+
+    /// Reads current state of gamepad's buttons and axes from device and queues events for
+    /// everything that differs from gamepad's state. Call it after
+    /// [`release_all()`](#method.release_all), when application starts reading events again, so
+    /// buttons that are held are pressed again. Events are processed like any other events read
+    /// from device.
+    ///
+    /// Returns `false` if gamepad is not connected or if state can't be read. Currently this is
+    /// supported only on Linux and Windows.
+    pub fn resync(&mut self, id: GamepadId) -> bool {
+        let raw = match self.inner.gamepad(self.ids.raw(id)) {
+            Some(gamepad) if gamepad.is_connected() => gamepad.current_state(),
+            _ => None,
+        };
+        let raw = match raw {
+            Some(raw) => raw,
+            None => return false,
+        };
+
+        let time = self.time_now();
+        for event in self.resync_diff(id, &raw) {
+            self.resync_events.push_back(RawEvent {
+                id: self.ids.raw(id),
+                event,
+                time,
+            });
+        }
+
+        true
+    }
+
+    /// Returns raw events that change state of gamepad to `raw`.
+    fn resync_diff(&self, id: GamepadId, raw: &RawState) -> Vec<RawEventType> {
+        let gamepad = match self.gamepad_checked(id) {
+            Some(gamepad) => gamepad,
+            None => return Vec::new(),
+        };
+        let state = gamepad.state();
+        let mut events = Vec::new();
+
+        for &(nec, pressed) in &raw.buttons {
+            if state.is_pressed(Code(nec)) != pressed {
+                events.push(if pressed {
+                    RawEventType::ButtonPressed(nec)
+                } else {
+                    RawEventType::ButtonReleased(nec)
+                });
+            }
+        }
+
+        let y_reversed = gamepad.inner.is_y_axis_reversed();
+        for &(nec, val) in &raw.axes {
+            let code = Code(nec);
+            let info = match gamepad.axis_info(code) {
+                Some(info) => info,
+                None => continue,
+            };
+            let clamped = info.clamp(val);
+            let value = match gamepad.axis_or_btn_name(code) {
+                Some(AxisOrBtn::Btn(_)) => btn_value(&info, clamped),
+                Some(AxisOrBtn::Axis(axis)) => axis_value(&info, clamped, axis, y_reversed),
+                None => axis_value(&info, clamped, Axis::Unknown, y_reversed),
+            };
+
+            // Filters decide if small differences, like stick resting in dead zone, are reported.
+            if value != state.value(code) {
+                events.push(RawEventType::AxisValueChanged(val, nec));
+            }
+        }
+
+        events
+    }
+
+    /// Returns maximum number of force feedback effects that gamepad reports it can play at the
+    /// same time. Returns `None` if gamepad is not connected or doesn't support force feedback.
+    ///
+    /// This value is informational only. Gilrs mixes effects itself and sends only combined
+    /// vibration to device, so number of effects played on gamepad is not limited. On Linux it's
+    /// number of effect slots reported by kernel, on Windows it's 16 (limit of most Linux drivers)
+    /// and on macOS 4.
+    pub fn max_ff_effects(&self, id: GamepadId) -> Option<usize> {
+        self.connected_gamepad(id)
+            .and_then(|gamepad| gamepad.data.max_ff_effects)
+    }
+
+    pub(crate) fn ff_sender(&self) -> &Sender<Message> {
+        &self.tx
+    }
+
+    /// Sets gamepad's mapping and returns SDL2 representation of them. Returned mappings may not be
+    /// compatible with SDL2 - if it is important, use
+    /// [`set_mapping_strict()`](#method.set_mapping_strict).
+    ///
+    /// The `name` argument can be a string slice with custom gamepad name or `None`. If `None`,
+    /// gamepad name reported by driver will be used.
+    ///
+    /// # Errors
+    ///
+    /// This function return error if `name` contains comma, `mapping` have axis and button entry
+    /// for same element (for example `Axis::LetfTrigger` and `Button::LeftTrigger`) or gamepad does
+    /// not have any element with `EvCode` used in mapping. `Button::Unknown` and
+    /// `Axis::Unknown` are not allowd as keys to `mapping` – in this case,
+    /// `MappingError::UnknownElement` is returned.
+    ///
+    /// Error is also returned if this function is not implemented or gamepad is not connected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gilrs::{Mapping, Button};
+    ///
+    /// # let mut gilrs = gilrs::Gilrs::new().unwrap();
+    /// let mut data = Mapping::new();
+    /// // …
+    ///
+    /// // or `match gilrs.set_mapping(0, &data, None) {`
+    /// match gilrs.set_mapping(0, &data, "Custom name") {
+    ///     Ok(sdl) => println!("SDL2 mapping: {}", sdl),
+    ///     Err(e) => println!("Failed to set mapping: {}", e),
+    /// };
+    /// ```
+    ///
+    /// See also `examples/mapping.rs`.
+    pub fn set_mapping<'b, O: Into<Option<&'b str>>>(
+        &mut self,
+        gamepad_id: usize,
+        mapping: &MappingData,
+        name: O,
+    ) -> Result<String, MappingError> {
+        if let Some(gamepad) = self.inner.gamepad(self.ids.raw(GamepadId(gamepad_id))) {
+            if gamepad.is_connected() {
+                return Err(MappingError::NotConnected);
+            }
+
+            let name = match name.into() {
+                Some(s) => s,
+                None => gamepad.name(),
+            };
+
+            // `mapping` uses the same layout as events, but SDL mappings are always by label.
+            let uuid = Uuid::from_bytes(gamepad.uuid());
+            let swap =
+                self.button_layout == ButtonLayout::ByPosition && mapping::is_nintendo_layout(uuid);
+            let swapped_data;
+            let mapping = if swap {
+                swapped_data = mapping.with_swapped_face_buttons();
+                &swapped_data
+            } else {
+                mapping
+            };
+
+            let (mut mapping, s) =
+                Mapping::from_data(mapping, gamepad.buttons(), gamepad.axes(), name, uuid)?;
+            if swap {
+                mapping.swap_face_buttons();
+            }
+
+            // We checked if gamepad is connected, so it should never panic
+            let data = &mut self.gamepads_data[gamepad_id];
+            data.mapping = mapping;
+            data.mapping_source = MappingSource::SdlMappings;
+
+            Ok(s)
+        } else {
+            Err(MappingError::NotConnected)
+        }
+    }
+
+    /// Similar to [`set_mapping()`](#method.set_mapping) but returned string should be compatible
+    /// with SDL2.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MappingError::NotSdl2Compatible` if `mapping` have an entry for `Button::{C, Z}`
+    /// or `Axis::{LeftZ, RightZ, LeftTrigger2, RightTrigger2}`.
+    pub fn set_mapping_strict<'b, O: Into<Option<&'b str>>>(
+        &mut self,
+        gamepad_id: usize,
+        mapping: &MappingData,
+        name: O,
+    ) -> Result<String, MappingError> {
+        if mapping.button(Button::C).is_some()
+            || mapping.button(Button::Z).is_some()
+            || mapping.axis(Axis::LeftZ).is_some()
+            || mapping.axis(Axis::RightZ).is_some()
+            || mapping.axis(Axis::LeftTrigger2).is_some()
+            || mapping.axis(Axis::RightTrigger2).is_some()
+        {
+            Err(MappingError::NotSdl2Compatible)
+        } else {
+            self.set_mapping(gamepad_id, mapping, name)
+        }
+    }
+
+    /// Adds mappings from file and updates mappings of connected gamepads. Mappings set with
+    /// `set_mapping()` are replaced if file contains mapping for the same gamepad model.
+    ///
+    /// See [`GilrsBuilder::add_mappings_from_file()`](struct.GilrsBuilder.html#method.add_mappings_from_file)
+    /// for details about file format.
+    pub fn reload_mappings_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        self.mappings.add_file_mappings(path)?;
+
+        let options = self.mapping_options();
+        for data in &mut self.gamepads_data {
+            let gamepad = match self.inner.gamepad(self.ids.raw(data.id)) {
+                Some(gp) if gp.is_connected() => gp,
+                _ => continue,
+            };
+
+            let uuid = Uuid::from_bytes(gamepad.uuid());
+            if db_mapping(&self.mappings, uuid, options.strict).is_some() {
+                data.update_mapping(gamepad, &self.mappings, options);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns database of SDL mappings.
+    pub fn mapping_db(&self) -> &MappingDb {
+        &self.mappings
+    }
+
+    /// Replaces database of SDL mappings and updates mappings of connected gamepads.
+    ///
+    /// `Connected` event is emitted for every connected gamepad whose mapping from database
+    /// changed, so application can refresh information about it. Mappings set with
+    /// `set_mapping()` are kept if database lookup for gamepad gives the same result as before.
+    pub fn set_mapping_db(&mut self, db: MappingDb) {
+        let old = mem::replace(&mut self.mappings, db);
+        let options = self.mapping_options();
+        let strict = options.strict;
+        let time = self.time_now();
+
+        for data in &mut self.gamepads_data {
+            let gamepad = match self.inner.gamepad(self.ids.raw(data.id)) {
+                Some(gp) if gp.is_connected() && data.status == Status::Connected => gp,
+                _ => continue,
+            };
+
+            let uuid = Uuid::from_bytes(gamepad.uuid());
+            if db_mapping(&old, uuid, strict) == db_mapping(&self.mappings, uuid, strict) {
+                continue;
+            }
+
+            data.update_mapping(gamepad, &self.mappings, options);
+            let info = Gamepad {
+                id: data.id,
+                data: &*data,
+                inner: gamepad,
+            }
+            .info();
+            self.events.push_back(Event {
+                id: data.id,
+                event: EventType::Connected(info),
+                time,
+                seq: 0,
+                source: EventSource::Synthetic(FilterId::Gilrs),
+            });
+        }
+    }
+
+    /// Returns `TestBackend` used by this `Gilrs` or `None` if it was created with platform
+    /// backend.
+    #[cfg(any(test, feature = "test_utils"))]
+    pub fn test_backend(&mut self) -> Option<&mut TestBackend> {
+        match self.inner {
+            Backend::Test(ref mut backend) => Some(backend),
+            _ => None,
+        }
+    }
+
+    /// Returns current time. With `TestBackend` this is time of backend's clock.
+    pub(crate) fn time_now(&self) -> SystemTime {
+        self.inner.time_now()
+    }
+
+    pub(crate) fn next_ff_id(&mut self) -> usize {
+        ff::next_effect_id(&self.next_ff_id)
+    }
+}
 
 /// Allow to create `Gilrs ` with customized behaviour.
 pub struct GilrsBuilder {
@@ -575,8 +2274,24 @@ pub struct GilrsBuilder {
     axis_to_btn_pressed: f32,
     axis_to_btn_released: f32,
     update_state: bool,
+    analog_buttons: bool,
+    unchanged_axis_epsilon: Option<f32>,
     env_mappings: bool,
     included_mappings: bool,
+    strict_uuid_matching: bool,
+    auto_mapping: bool,
+    reconnect_grace: Option<Duration>,
+    button_layout: ButtonLayout,
+    event_logging: LevelFilter,
+    state_logging: bool,
+    ignore_devices: Vec<(u16, u16)>,
+    env_ignore_list: bool,
+    inverted_axes: Vec<Axis>,
+    latency_metrics: bool,
+    ff_tick_duration: Duration,
+    update_hooks: Vec<UpdateHook>,
+    axis_info_overrides: FnvHashMap<([u8; 16], Code), AxisInfo>,
+    axis_info_overrides_all: FnvHashMap<Code, AxisInfo>,
 }
 
 impl GilrsBuilder {
@@ -588,524 +2303,3990 @@ impl GilrsBuilder {
             axis_to_btn_pressed: 0.75,
             axis_to_btn_released: 0.65,
             update_state: true,
+            analog_buttons: true,
+            unchanged_axis_epsilon: Some(0.0),
             env_mappings: true,
             included_mappings: true,
+            strict_uuid_matching: false,
+            auto_mapping: cfg!(target_os = "linux"),
+            reconnect_grace: None,
+            button_layout: ButtonLayout::ByLabel,
+            event_logging: LevelFilter::Off,
+            state_logging: false,
+            ignore_devices: Vec::new(),
+            env_ignore_list: true,
+            inverted_axes: Vec::new(),
+            latency_metrics: false,
+            ff_tick_duration: ff::DEFAULT_TICK_DURATION,
+            update_hooks: Vec::new(),
+            axis_info_overrides: FnvHashMap::default(),
+            axis_info_overrides_all: FnvHashMap::default(),
         }
     }
 
-    /// If `true`, use [`axis_dpad_to_button`](ev/filter/fn.axis_dpad_to_button.html),
-    /// [`Jitter`](ev/filter/struct.Jitter.html) and [`deadzone`](ev/filter/fn.deadzone.html)
-    /// filters with default parameters. Defaults to `true`.
-    pub fn with_default_filters(mut self, default_filters: bool) -> Self {
-        self.default_filters = default_filters;
+    /// If `true`, use [`axis_dpad_to_button`](ev/filter/fn.axis_dpad_to_button.html),
+    /// [`Jitter`](ev/filter/struct.Jitter.html) and [`deadzone`](ev/filter/fn.deadzone.html)
+    /// filters with default parameters. Defaults to `true`.
+    pub fn with_default_filters(mut self, default_filters: bool) -> Self {
+        self.default_filters = default_filters;
+
+        self
+    }
+
+    /// Adds SDL mappings. They take precedence over mappings from environment and included
+    /// mappings.
+    pub fn add_mappings(mut self, mappings: &str) -> Self {
+        self.mappings.insert(mappings);
+
+        self
+    }
+
+    /// Adds SDL mappings from file. Each line should contain one mapping; empty lines and lines
+    /// starting with `#` are ignored. Invalid mappings are logged and skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if file can't be read.
+    pub fn add_mappings_from_file<P: AsRef<Path>>(mut self, path: P) -> io::Result<Self> {
+        self.mappings.add_file_mappings(path)?;
+
+        Ok(self)
+    }
+
+    /// If true, will add SDL mappings from `SDL_GAMECONTROLLERCONFIG` environment variable and
+    /// from file pointed by `SDL_GAMECONTROLLERCONFIG_FILE`. Defaults to true.
+    ///
+    /// Mappings added with `add_mappings()` take precedence over mappings from environment, which
+    /// take precedence over included mappings.
+    pub fn add_env_mappings(mut self, env_mappings: bool) -> Self {
+        self.env_mappings = env_mappings;
+
+        self
+    }
+
+    /// If true, will add SDL mappings included from
+    /// https://github.com/gabomdq/SDL_GameControllerDB. Defaults to true.
+    pub fn add_included_mappings(mut self, included_mappings: bool) -> Self {
+        self.included_mappings = included_mappings;
+
+        self
+    }
+
+    /// If `false`, gamepad that doesn't have mapping for its exact UUID will use mapping for
+    /// device with the same bus, vendor and product ID, but different version. This helps with
+    /// controllers whose firmware update changed version. Exact matches are always preferred.
+    /// Defaults to `false`.
+    pub fn strict_uuid_matching(mut self, strict: bool) -> Self {
+        self.strict_uuid_matching = strict;
+
+        self
+    }
+
+    /// If `true`, gamepad without SDL mapping, whose driver doesn't follow kernel's gamepad
+    /// specification, gets provisional mapping built from codes reported by driver. `ABS_Z` and
+    /// `ABS_RZ` become triggers. Joystick buttons (`BTN_TRIGGER`, `BTN_THUMB`, ..., `BTN_BASE6`)
+    /// used by generic USB gamepads are assigned in order to `South`, `East`, `West`, `North`,
+    /// shoulder buttons, triggers (if gamepad doesn't have `ABS_Z` and `ABS_RZ`), `Select`,
+    /// `Start`, stick buttons and `Mode`. Other codes keep their meaning, for example `BTN_START` becomes `Button::Start`, `ABS_RX`
+    /// `Axis::RightStickX` and `ABS_HAT0X` `Axis::DPadX`. Such gamepads report
+    /// `MappingSource::Heuristic`. Controllers whose driver labels elements differently will be
+    /// only partially usable, but without this most of their events would be `Unknown`. If
+    /// `false`, these gamepads have `MappingSource::None`.
+    ///
+    /// Gamepads whose driver follows the specification (reports `BTN_GAMEPAD`) use layout
+    /// provided by driver and report `MappingSource::Driver` regardless of this setting.
+    ///
+    /// This only matters on Linux, other platforms' drivers provide unified layout on their own.
+    /// Defaults to `true` on Linux and `false` elsewhere.
+    pub fn enable_auto_mapping(mut self, enabled: bool) -> Self {
+        self.auto_mapping = enabled;
+
+        self
+    }
+
+    /// Sets values on which `ButtonPressed` and `ButtonReleased` events will be emitted. `build()`
+    /// will return error if `pressed ≤ released` or if one of values is outside [0.0, 1.0].
+    ///
+    /// Defaults to 0.75 for `pressed` and 0.65 for `released`.
+    pub fn set_axis_to_btn(mut self, pressed: f32, released: f32) -> Self {
+        self.axis_to_btn_pressed = pressed;
+        self.axis_to_btn_released = released;
+
+        self
+    }
+
+    /// Disable or enable automatic state updates. You should use this if you use custom filters;
+    /// in this case you have to update state manually anyway.
+    pub fn set_update_state(mut self, enabled: bool) -> Self {
+        self.update_state = enabled;
+
+        self
+    }
+
+    /// If `true`, buttons that device reports as axes (analog triggers, pressure sensitive face
+    /// buttons) emit `ButtonChanged` events with their real value. If `false`, such buttons are
+    /// treated as digital: `ButtonChanged` is emitted only together with `ButtonPressed` (with
+    /// value 1.0) and `ButtonReleased` (with value 0.0). Defaults to `true`.
+    ///
+    /// Buttons are considered analog only if their axis range is larger than 1. Other buttons
+    /// reported as axes are always treated as digital. Analog buttons emit `ButtonChanged` only
+    /// when their value changes.
+    pub fn with_analog_buttons(mut self, enabled: bool) -> Self {
+        self.analog_buttons = enabled;
+
+        self
+    }
+
+    /// Drops `AxisChanged` events with value that differs from last value of the same axis by at
+    /// most `epsilon`. Some devices report values that flicker by 1 LSB, which after
+    /// normalization, deadzone and curves often gives exactly the same value. Values are compared
+    /// after mapping, filters and curves, with value stored in gamepad's state, so this has no
+    /// effect if automatic state updates are disabled.
+    ///
+    /// First event of axis after gamepad connects or after `Gilrs::release_all()` is always
+    /// returned. Button events, including the ones generated from axes, are not affected.
+    ///
+    /// Defaults to `Some(0.0)`, which drops only events with exactly the same value. `None`
+    /// disables suppression.
+    pub fn suppress_unchanged_axes(mut self, epsilon: Option<f32>) -> Self {
+        self.unchanged_axis_epsilon = epsilon;
+
+        self
+    }
+
+    /// Sets how long disconnected gamepad can take to reconnect and keep its ID and bindings.
+    /// Wireless gamepads sometimes lose connection for a few seconds.
+    ///
+    /// When gamepad disconnects, `ConnectionLost` is emitted instead of `Disconnected`. If the
+    /// same device reconnects before `grace` passes, `ConnectionRestored` is emitted instead of
+    /// `Connected`. Otherwise `Disconnected` is emitted when grace period ends. Reconnected
+    /// gamepad keeps its ID even if backend reports it as a new device. Disabled by default.
+    ///
+    /// Devices are matched by serial number (see `gilrs_core::Gamepad::serial_number()`), or by
+    /// UUID if serial number is not available. Serial numbers are currently provided only on
+    /// Linux, so on other platforms two identical gamepads that lose connection at the same time
+    /// can swap their IDs.
+    pub fn reconnect_grace(mut self, grace: Duration) -> Self {
+        self.reconnect_grace = Some(grace);
+
+        self
+    }
+
+    /// Sets how face buttons of gamepads with Nintendo layout (A on the right, B on the bottom)
+    /// are mapped. `ButtonLayout::ByLabel`, the default, follows SDL mappings, so `Button::South`
+    /// is the button labeled "A", which is on the east side of gamepad.
+    /// `ButtonLayout::ByPosition` swaps `South` with `East` and `West` with `North`, so buttons
+    /// match positions on Xbox gamepad.
+    ///
+    /// This only affects gamepads using SDL mappings. Gamepads are recognized by USB vendor and
+    /// product ID.
+    pub fn button_layout(mut self, layout: ButtonLayout) -> Self {
+        self.button_layout = layout;
+
+        self
+    }
+
+    /// Logs every event returned by `Gilrs::next_event()` with given level. This is independent
+    /// of internal `trace!` messages, so you can see events without enabling trace level for the
+    /// whole crate. Messages still have to pass `log`'s global and logger's filters.
+    ///
+    /// `LevelFilter::Off`, the default, disables event logging.
+    pub fn with_event_logging(mut self, level: LevelFilter) -> Self {
+        self.event_logging = level;
+
+        self
+    }
+
+    /// If `true`, `Gilrs::update()` also logs new state of changed buttons and axes. Messages use
+    /// level set by `with_event_logging()`, so this has no effect when event logging is
+    /// disabled. Default is `false`.
+    pub fn with_state_logging(mut self, enabled: bool) -> Self {
+        self.state_logging = enabled;
+
+        self
+    }
+
+    /// Ignores gamepads with given USB vendor and product IDs. Ignored gamepads don't emit any
+    /// events and are not returned by `Gilrs::gamepads()`. Gamepads are identified by their UUID,
+    /// so this has no effect on gamepads with UUID that doesn't contain vendor and product ID.
+    ///
+    /// Devices are ignored in addition to devices from environment variables, see
+    /// `respect_env_ignore_list()`.
+    pub fn ignore_devices(mut self, devices: Vec<(u16, u16)>) -> Self {
+        self.ignore_devices.extend(devices);
+
+        self
+    }
+
+    /// If true, will ignore gamepads listed in `SDL_GAMECONTROLLER_IGNORE_DEVICES` environment
+    /// variable and, if `SDL_GAMECONTROLLER_IGNORE_DEVICES_EXCEPT` is set, all gamepads that are
+    /// not listed there. Both variables contain comma separated list of `0xVVVV/0xPPPP`
+    /// entries, the same as in SDL. Defaults to true.
+    pub fn respect_env_ignore_list(mut self, respect: bool) -> Self {
+        self.env_ignore_list = respect;
+
+        self
+    }
+
+    /// Negates values of `axes`, for example to invert Y axis for players that prefer it. Axes are
+    /// inverted with [`InvertAxis`](ev/filter/struct.InvertAxis.html) filter before default
+    /// filters, even if they are disabled, so gamepad's state also contains inverted values.
+    ///
+    /// This is independent of platform, see [`Axis`](enum.Axis.html) for convention used by gilrs.
+    pub fn with_inverted_axes(mut self, axes: &[Axis]) -> Self {
+        self.inverted_axes.extend_from_slice(axes);
+
+        self
+    }
+
+    /// If `true`, latency of every event returned by `next_event()` is recorded in per-gamepad
+    /// histogram. See `Gilrs::latency_stats()`. Defaults to `false`.
+    pub fn with_latency_metrics(mut self, enabled: bool) -> Self {
+        self.latency_metrics = enabled;
+
+        self
+    }
+
+    /// Sets how often force feedback server updates effects. Shorter ticks make envelopes and
+    /// clips smoother at the cost of more frequent writes to device. `build()` will return error if
+    /// `duration` is outside [1 ms, 100 ms].
+    ///
+    /// Effect durations don't depend on tick duration, but they are rounded to whole ticks.
+    /// Defaults to 50 ms.
+    pub fn ff_tick_duration(mut self, duration: Duration) -> Self {
+        self.ff_tick_duration = duration;
+
+        self
+    }
+
+    /// Adds function that is called by `Gilrs::update()` after gamepad's state was updated with
+    /// event. Hooks are called in order in which they were added, with event and new state of
+    /// gamepad that emitted it. Useful for collecting statistics without wrapping `update()`.
+    ///
+    /// Hooks are called synchronously from `update()` (and `next_event()` if automatic state
+    /// updates are enabled), so they should be fast. They must not call back into `Gilrs`.
+    pub fn with_update_hook(mut self, hook: UpdateHook) -> Self {
+        self.update_hooks.push(hook);
+
+        self
+    }
+
+    /// Replaces range and deadzone of axis with `code` reported by gamepad with `uuid`. Use it for
+    /// devices that report incorrect range, for example 0..65535 instead of -32768..32767.
+    ///
+    /// Override is used when converting raw values and for deadzone, see also
+    /// `Gamepad::axis_info()`. It takes precedence over override set with
+    /// `with_axis_info_override_all()`.
+    pub fn with_axis_info_override(mut self, uuid: [u8; 16], code: Code, info: AxisInfo) -> Self {
+        self.axis_info_overrides.insert((uuid, code), info);
+
+        self
+    }
+
+    /// Replaces range and deadzone of axis with `code` for all gamepads that have such axis. See
+    /// `with_axis_info_override()`.
+    pub fn with_axis_info_override_all(mut self, code: Code, info: AxisInfo) -> Self {
+        self.axis_info_overrides_all.insert(code, info);
+
+        self
+    }
+
+    /// Creates `Gilrs`.
+    pub fn build(self) -> Result<Gilrs, Error> {
+        if !self.is_axis_to_btn_valid() {
+            return Err(Error::InvalidAxisToBtn);
+        }
+
+        if !self.is_ff_tick_duration_valid() {
+            return Err(Error::InvalidFfTickDuration);
+        }
+
+        let mut is_dummy = false;
+        let inner = match gilrs_core::Gilrs::new() {
+            Ok(g) => g,
+            Err(PlatformError::NotImplemented(g)) => {
+                is_dummy = true;
+
+                g
+            }
+            Err(PlatformError::AlreadyInUse) => return Err(Error::AlreadyInUse),
+            Err(PlatformError::Other(e)) => return Err(Error::Other(e)),
+            // `gilrs_core::Error` is not `Send`, so only message can be kept.
+            Err(e) => return Err(Error::Other(e.to_string().into())),
+        };
+
+        let gilrs = self.build_with(Backend::Platform(Box::new(inner)));
+
+        if is_dummy {
+            Err(Error::NotImplemented(gilrs))
+        } else {
+            Ok(gilrs)
+        }
+    }
+
+    /// Creates `Gilrs` that uses `backend` instead of platform backend. See
+    /// [`test_utils`](test_utils/index.html) for example.
+    #[cfg(any(test, feature = "test_utils"))]
+    #[allow(clippy::result_large_err)]
+    pub fn build_with_backend(self, backend: TestBackend) -> Result<Gilrs, Error> {
+        if !self.is_axis_to_btn_valid() {
+            return Err(Error::InvalidAxisToBtn);
+        }
+
+        if !self.is_ff_tick_duration_valid() {
+            return Err(Error::InvalidFfTickDuration);
+        }
+
+        Ok(self.build_with(Backend::Test(backend)))
+    }
+
+    fn is_axis_to_btn_valid(&self) -> bool {
+        !(self.axis_to_btn_pressed <= self.axis_to_btn_released
+            || self.axis_to_btn_pressed < 0.0
+            || self.axis_to_btn_pressed > 1.0
+            || self.axis_to_btn_released < 0.0
+            || self.axis_to_btn_released > 1.0)
+    }
+
+    fn is_ff_tick_duration_valid(&self) -> bool {
+        self.ff_tick_duration >= ff::MIN_TICK_DURATION
+            && self.ff_tick_duration <= ff::MAX_TICK_DURATION
+    }
+
+    fn build_with(self, inner: Backend) -> Gilrs {
+        let mut mappings = MappingDb::new();
+
+        // Order doesn't matter, `MappingDb` keeps mapping from source with highest priority.
+        if self.included_mappings {
+            mappings.add_included_mappings();
+        }
+
+        if self.env_mappings {
+            mappings.add_env_mappings();
+        }
+
+        mappings.merge(self.mappings);
+
+        debug!("Loaded {} mappings.", mappings.len());
+
+        let mut device_filter = if self.env_ignore_list {
+            DeviceFilter::from_env()
+        } else {
+            DeviceFilter::default()
+        };
+        device_filter.ignore(self.ignore_devices);
+
+        let tx = server::init(self.ff_tick_duration);
+        let next_ff_id = Arc::new(AtomicUsize::new(0));
+        let not_observed_data =
+            GamepadData::not_observed(GamepadId(0), tx.clone(), next_ff_id.clone());
+
+        let mut gilrs = Gilrs {
+            inner,
+            next_ff_id,
+            tx,
+            counter: 0,
+            last_seq: 0,
+            mappings,
+            strict_uuid_matching: self.strict_uuid_matching,
+            auto_mapping: self.auto_mapping,
+            default_filters: self.default_filters,
+            inverted_axes: InvertAxis::new(&self.inverted_axes),
+            axis_info_overrides: self.axis_info_overrides,
+            axis_info_overrides_all: self.axis_info_overrides_all,
+            events: VecDeque::new(),
+            synthesized_events: VecDeque::new(),
+            resync_events: VecDeque::new(),
+            axis_to_btn_pressed: self.axis_to_btn_pressed,
+            axis_to_btn_released: self.axis_to_btn_released,
+            update_state: self.update_state,
+            analog_buttons: self.analog_buttons,
+            unchanged_axis_epsilon: self.unchanged_axis_epsilon,
+            reconnect_grace: self.reconnect_grace,
+            button_layout: self.button_layout,
+            event_log_level: self.event_logging.to_level(),
+            state_logging: self.state_logging,
+            device_filter,
+            idle_timeout: None,
+            last_idle_check: None,
+            latency_metrics: self.latency_metrics,
+            #[cfg(feature = "latency-tracking")]
+            event_latency: LatencyHistogram::new(),
+            ff_tick_duration: self.ff_tick_duration,
+            ff_battery_compensation: false,
+            ff_compensation_threshold: DEFAULT_FF_COMPENSATION_THRESHOLD,
+            last_compensation_update: None,
+            ff_attenuation: 0.0,
+            update_hooks: UpdateHooks(self.update_hooks),
+            gamepads_data: Vec::new(),
+            ids: IdMap::default(),
+            not_observed_data,
+            added_this_frame: Vec::new(),
+            removed_this_frame: Vec::new(),
+        };
+        gilrs.finish_gamepads_creation();
+
+        gilrs
+    }
+}
+
+impl Default for GilrsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<GilrsOptions> for GilrsBuilder {
+    fn from(options: GilrsOptions) -> Self {
+        let mut builder = GilrsBuilder::new()
+            .add_mappings(&options.mappings)
+            .with_default_filters(options.default_filters)
+            .set_axis_to_btn(options.axis_to_btn_pressed, options.axis_to_btn_released)
+            .set_update_state(options.update_state)
+            .with_analog_buttons(options.analog_buttons)
+            .suppress_unchanged_axes(options.suppress_unchanged_axes)
+            .add_env_mappings(options.env_mappings)
+            .add_included_mappings(options.included_mappings)
+            .strict_uuid_matching(options.strict_uuid_matching)
+            .enable_auto_mapping(options.auto_mapping)
+            .button_layout(options.button_layout)
+            .with_event_logging(options.event_logging)
+            .with_state_logging(options.state_logging)
+            .ignore_devices(options.ignore_devices)
+            .respect_env_ignore_list(options.respect_env_ignore_list)
+            .with_inverted_axes(&options.inverted_axes)
+            .with_latency_metrics(options.latency_metrics);
+
+        if let Some(grace) = options.reconnect_grace {
+            builder = builder.reconnect_grace(grace);
+        }
+
+        builder
+    }
+}
+
+/// Settings of `Gilrs` as plain struct.
+///
+/// This is alternative to [`GilrsBuilder`](struct.GilrsBuilder.html) for applications that load
+/// their configuration from file. With `serde-serialize` feature it can be deserialized directly
+/// and missing fields are set to their default values. Use `Gilrs::new_with_options()` to create
+/// `Gilrs`.
+///
+/// Fields correspond to `GilrsBuilder` methods with the same name and have the same defaults.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-serialize", serde(default))]
+pub struct GilrsOptions {
+    /// Additional SDL mappings, one per line. See `GilrsBuilder::add_mappings()`.
+    pub mappings: String,
+    /// See `GilrsBuilder::with_default_filters()`.
+    pub default_filters: bool,
+    /// See `GilrsBuilder::set_axis_to_btn()`.
+    pub axis_to_btn_pressed: f32,
+    /// See `GilrsBuilder::set_axis_to_btn()`.
+    pub axis_to_btn_released: f32,
+    /// See `GilrsBuilder::set_update_state()`.
+    pub update_state: bool,
+    /// See `GilrsBuilder::with_analog_buttons()`.
+    pub analog_buttons: bool,
+    /// See `GilrsBuilder::suppress_unchanged_axes()`.
+    pub suppress_unchanged_axes: Option<f32>,
+    /// See `GilrsBuilder::add_env_mappings()`.
+    pub env_mappings: bool,
+    /// See `GilrsBuilder::add_included_mappings()`.
+    pub included_mappings: bool,
+    /// See `GilrsBuilder::strict_uuid_matching()`.
+    pub strict_uuid_matching: bool,
+    /// See `GilrsBuilder::enable_auto_mapping()`.
+    pub auto_mapping: bool,
+    /// See `GilrsBuilder::reconnect_grace()`. `None` disables grace period.
+    pub reconnect_grace: Option<Duration>,
+    /// See `GilrsBuilder::button_layout()`.
+    pub button_layout: ButtonLayout,
+    /// See `GilrsBuilder::with_event_logging()`.
+    pub event_logging: LevelFilter,
+    /// See `GilrsBuilder::with_state_logging()`.
+    pub state_logging: bool,
+    /// See `GilrsBuilder::ignore_devices()`.
+    pub ignore_devices: Vec<(u16, u16)>,
+    /// See `GilrsBuilder::respect_env_ignore_list()`.
+    pub respect_env_ignore_list: bool,
+    /// See `GilrsBuilder::with_inverted_axes()`.
+    pub inverted_axes: Vec<Axis>,
+    /// See `GilrsBuilder::with_latency_metrics()`.
+    pub latency_metrics: bool,
+}
+
+impl Default for GilrsOptions {
+    fn default() -> Self {
+        GilrsOptions {
+            mappings: String::new(),
+            default_filters: true,
+            axis_to_btn_pressed: 0.75,
+            axis_to_btn_released: 0.65,
+            update_state: true,
+            analog_buttons: true,
+            suppress_unchanged_axes: Some(0.0),
+            env_mappings: true,
+            included_mappings: true,
+            strict_uuid_matching: false,
+            auto_mapping: cfg!(target_os = "linux"),
+            reconnect_grace: None,
+            button_layout: ButtonLayout::ByLabel,
+            event_logging: LevelFilter::Off,
+            state_logging: false,
+            ignore_devices: Vec::new(),
+            respect_env_ignore_list: true,
+            inverted_axes: Vec::new(),
+            latency_metrics: false,
+        }
+    }
+}
+
+/// Iterator over all connected gamepads.
+#[derive(Clone)]
+pub struct ConnectedGamepadsIterator<'a>(&'a Gilrs, usize);
+
+impl<'a> Iterator for ConnectedGamepadsIterator<'a> {
+    type Item = (GamepadId, Gamepad<'a>);
+
+    fn next(&mut self) -> Option<(GamepadId, Gamepad<'a>)> {
+        loop {
+            if self.1 == self.0.inner.last_gamepad_hint() {
+                return None;
+            }
+
+            if let Some(gp) = self.0.connected_gamepad(GamepadId(self.1)) {
+                let idx = self.1;
+                self.1 += 1;
+                return Some((GamepadId(idx), gp));
+            }
+
+            self.1 += 1;
+        }
+    }
+}
+
+/// Represents handle to game controller.
+///
+/// Using this struct you can access cached gamepad state, information about gamepad such as name
+/// or UUID and manage force feedback effects.
+#[derive(Debug, Copy, Clone)]
+pub struct Gamepad<'a> {
+    id: GamepadId,
+    data: &'a GamepadData,
+    inner: RawGamepad<'a>,
+}
+
+impl<'a> Gamepad<'a> {
+    /// Returns the mapping name if it exists otherwise returns the os provided name.
+    pub fn name(&self) -> &str {
+        if let Some(map_name) = self.map_name() {
+            map_name
+        } else {
+            self.os_name()
+        }
+    }
+
+    /// if `mapping_source()` is `SdlMappings` returns the name of the mapping used by the gamepad.
+    /// Otherwise returns `None`.
+    pub fn map_name(&self) -> Option<&str> {
+        self.data.map_name()
+    }
+
+    /// Returns the name of the gamepad supplied by the OS.
+    pub fn os_name(&self) -> &str {
+        self.inner.name()
+    }
+
+    /// Returns manufacturer string from device's descriptor, for example "Sony Interactive
+    /// Entertainment". Returns `None` if it's not available. Currently works on Linux, macOS and
+    /// FreeBSD.
+    pub fn manufacturer_string(&self) -> Option<&str> {
+        self.inner.manufacturer_string()
+    }
+
+    /// Returns product string from device's descriptor, for example "Wireless Controller". Unlike
+    /// `os_name()`, it's not affected by driver. Returns `None` if it's not available. Currently
+    /// works on Linux, macOS and FreeBSD.
+    pub fn product_string(&self) -> Option<&str> {
+        self.inner.product_string()
+    }
+
+    /// Returns path to the device file used by backend, or `None` if it is not available on
+    /// current platform.
+    ///
+    /// On Linux this is evdev device node, for example `/dev/input/event5`. On macOS it's path of
+    /// device's IOService in I/O Registry. It can be used to open the same device with other
+    /// libraries, for example to control LEDs with hidapi.
+    pub fn os_path(&self) -> Option<&str> {
+        self.inner.os_path()
+    }
+
+    /// Returns description of physical port to which gamepad is connected, or `None` if it is not
+    /// available on current platform. Unlike `os_path()`, it stays the same when gamepad is
+    /// reconnected to the same port or after reboot, so it can be used to assign players to
+    /// seats.
+    ///
+    /// On Linux this is `phys` of input device, for example `usb-0000:00:14.0-2/input0`. Gamepads
+    /// connected over Bluetooth report address of adapter, so they all have the same port path.
+    pub fn port_path(&self) -> Option<&str> {
+        self.inner.port_path()
+    }
+
+    /// Opens gamepad's HID device to read and write raw reports, for example to handle
+    /// elements that gilrs can't interpret or to control LEDs. Gamepad still emits events as
+    /// usual.
+    ///
+    /// Only Linux (through hidraw, user needs access to `/dev/hidraw*`) and macOS (application
+    /// needs "Input Monitoring" permission) are supported. Windows always returns error: gilrs
+    /// reads only XInput gamepads there, which aren't HID devices, and doesn't enumerate other
+    /// HID devices (button boxes of simulators, custom HID devices) at all. See
+    /// [`gilrs_core::Gamepad::raw_reports()`](../gilrs_core/struct.Gamepad.html#method.raw_reports).
+    #[cfg(feature = "raw-hid")]
+    pub fn raw_reports(&self) -> std::io::Result<RawReports> {
+        self.inner.raw_reports()
+    }
+
+    /// Returns gamepad's UUID.
+    ///
+    /// It is recommended to process with the [UUID crate](https://crates.io/crates/uuid).
+    /// Use `Uuid::from_bytes` method to create a `Uuid` from the returned bytes.
+    pub fn uuid(&self) -> [u8; 16] {
+        self.inner.uuid()
+    }
+
+    /// Returns cached gamepad state.
+    pub fn state(&self) -> &GamepadState {
+        &self.data.state
+    }
+
+    /// Returns pressed buttons and values of axes in fixed-size format that can be sent over
+    /// network. See [`CompactState`](struct.CompactState.html).
+    pub fn compact_state(&self) -> CompactState {
+        CompactState::new(self)
+    }
+
+    /// Returns monotonic time when last input event of this gamepad was processed by
+    /// `Gilrs::update()`, or `None` if there was no such event.
+    ///
+    /// Unlike `SystemTime` stored in events and state, `Instant` never goes backward, so it's
+    /// better suited for timing measurements like input prediction. It's time of processing, not
+    /// time when event was generated. Always returns `None` on wasm, which doesn't support
+    /// `Instant`.
+    pub fn last_input_monotonic(&self) -> Option<Instant> {
+        self.data.last_event_instant
+    }
+
+    /// Returns true if gamepad is connected. Suspended gamepads (see `Status::Suspended`) are not
+    /// connected.
+    pub fn is_connected(&self) -> bool {
+        self.data.status != Status::Suspended && self.inner.is_connected()
+    }
+
+    /// Returns gamepad's connection status. Unlike `is_connected()`, this function can tell
+    /// apart gamepad that was disconnected from one that was never observed.
+    ///
+    /// Status is changed when `Connected` or `Disconnected` event (or `ConnectionRestored` and
+    /// `ConnectionLost`, `Resumed` and `Suspended`) is returned from `Gilrs::next_event()`. If
+    /// gamepad is reconnected with a new ID, status of old ID stays `Disconnected`.
+    pub fn status(&self) -> Status {
+        self.data.status
+    }
+
+    /// Returns true if gamepad's input events are suppressed with `Gilrs::suppress_gamepad()`.
+    pub fn is_suppressed(&self) -> bool {
+        self.data.suppressed
+    }
+
+    /// Examines cached gamepad state to check if given button is pressed. Panics if `btn` is
+    /// `Unknown`.
+    ///
+    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
+    /// directly on `State`, because this version have to check which `Code` is mapped to element of
+    /// gamepad.
+    pub fn is_pressed(&self, btn: Button) -> bool {
+        self.data.is_pressed(btn)
+    }
+
+    /// Examines cached gamepad state to check axis's value. Panics if `axis` is `Unknown`.
+    ///
+    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
+    /// directly on `State`, because this version have to check which `Code` is mapped to element of
+    /// gamepad.
+    pub fn value(&self, axis: Axis) -> f32 {
+        self.data.value(axis)
+    }
+
+    /// Returns direction of `stick` quantized to 4 or 8 directions, or `None` if stick is in
+    /// center region (closer to center than 0.5) or `stick` is not a stick axis. Any axis of stick
+    /// can be used.
+    ///
+    /// Direction is computed from cached state, without hysteresis. To quantize events, use
+    /// [`Snap`](ev/filter/struct.Snap.html) filter.
+    pub fn snapped_direction(&self, stick: Axis, mode: SnapMode) -> Option<Direction8> {
+        let (x, y) = stick.stick_pair()?;
+        let pos = (self.value(x), self.value(y));
+
+        filter::snap_direction(mode, pos, filter::SNAP_CENTER, 0.0, None)
+    }
+
+    /// Returns button state and when it changed.
+    ///
+    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
+    /// directly on `State`, because this version have to check which `Code` is mapped to element of
+    /// gamepad.
+    pub fn button_data(&self, btn: Button) -> Option<&ButtonData> {
+        self.data.button_data(btn)
+    }
+
+    /// Returns axis state and when it changed.
+    ///
+    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
+    /// directly on `State`, because this version have to check which `Code` is mapped to element of
+    /// gamepad.
+    pub fn axis_data(&self, axis: Axis) -> Option<&AxisData> {
+        self.data.axis_data(axis)
+    }
+
+    /// Returns `true` if `btn` was pressed when counter had value `counter`. Pass
+    /// `Gilrs::counter()` to check if button was pressed in current frame. See
+    /// `ButtonData::is_just_pressed()`.
+    pub fn is_just_pressed(&self, btn: Button, counter: u64) -> bool {
+        self.button_data(btn)
+            .map(|data| data.is_just_pressed(counter))
+            .unwrap_or(false)
+    }
+
+    /// Returns `true` if `btn` was released when counter had value `counter`. See
+    /// `is_just_pressed()`.
+    pub fn is_just_released(&self, btn: Button, counter: u64) -> bool {
+        self.button_data(btn)
+            .map(|data| data.is_just_released(counter))
+            .unwrap_or(false)
+    }
+
+    /// Returns device's power supply state. See [`PowerInfo`](enum.PowerInfo.html) for details.
+    pub fn power_info(&self) -> PowerInfo {
+        self.inner.power_info()
+    }
+
+    /// Returns battery level in percent. `PowerInfo::Charged` is reported as 100%. Returns `None`
+    /// for wired gamepads and if battery level is unknown.
+    pub fn battery_percentage(&self) -> Option<u8> {
+        match self.power_info() {
+            PowerInfo::Discharging(lvl) | PowerInfo::Charging(lvl) => Some(lvl),
+            PowerInfo::Charged => Some(100),
+            PowerInfo::Wired | PowerInfo::Unknown => None,
+        }
+    }
+
+    /// Returns true if gamepad is powered by battery, which isn't charging
+    /// (`PowerInfo::Discharging`).
+    pub fn is_on_battery(&self) -> bool {
+        match self.power_info() {
+            PowerInfo::Discharging(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Powers off wireless gamepad. Gamepad disconnects and `Disconnected` event is emitted as
+    /// usual.
+    ///
+    /// Currently no platform supports it and `PowerOffError::NotSupported` is returned for all
+    /// connected gamepads. It only works with `TestBackend`. See also
+    /// `Gilrs::set_idle_timeout()`.
+    pub fn power_off(&self) -> Result<(), PowerOffError> {
+        if !self.is_connected() {
+            return Err(PowerOffError::Disconnected);
+        }
+
+        self.inner.power_off()
+    }
+
+    /// Shows player number on gamepad's player indicator LEDs, like quadrant LEDs of Xbox 360
+    /// controllers or player lights of Switch controllers. `player` has to be in range 1–4,
+    /// `None` turns indicator off.
+    ///
+    /// | Platform | Support                                                                   |
+    /// |----------|---------------------------------------------------------------------------|
+    /// | Linux    | Gamepads handled by `xpad`, `hid-nintendo` and `hid-playstation` drivers. |
+    /// | Windows  | Not supported, XInput sets quadrant LEDs by controller slot on its own.   |
+    /// | macOS    | Does nothing and returns `Ok(())`.                                        |
+    /// | Others   | Not supported.                                                            |
+    ///
+    /// On Linux user needs write access to `brightness` file of LEDs in `/sys/class/leds`,
+    /// usually granted with udev rule. Other gamepads return `LedError::NotSupported`.
+    ///
+    /// Windows is not supported, because XInput has no API for LEDs and XInput gamepads aren't
+    /// HID devices, so raw HID reports can't be used either.
+    pub fn set_player_indicator(&self, player: Option<u8>) -> Result<(), LedError> {
+        match player {
+            Some(player) if !(1..=4).contains(&player) => return Err(LedError::InvalidPlayer),
+            _ => (),
+        }
+
+        if !self.is_connected() {
+            return Err(LedError::Disconnected);
+        }
+
+        self.inner.set_player_indicator(player)?;
+        self.data.player_indicator.set(player);
+
+        Ok(())
+    }
+
+    /// Returns which set of on-screen button prompts matches this gamepad. See
+    /// [`Button::prompt_image_hint()`](enum.Button.html#method.prompt_image_hint).
+    pub fn button_prompt_hint(&self) -> ButtonPromptHint {
+        Button::prompt_image_hint(self.uuid(), self.mapping_source())
+    }
+
+    /// Returns player number last set with `set_player_indicator()`. It's reset to `None` when
+    /// gamepad reconnects.
+    pub fn player_indicator(&self) -> Option<u8> {
+        self.data.player_indicator.get()
+    }
+
+    /// Returns source of gamepad mapping. Can be used to filter gamepads which do not provide
+    /// unified controller layout.
+    ///
+    /// ```
+    /// use gilrs::MappingSource;
+    /// # let mut gilrs = gilrs::Gilrs::new().unwrap();
+    ///
+    /// for (_, gamepad) in gilrs.gamepads().filter(
+    ///     |gp| gp.1.mapping_source() != MappingSource::None)
+    /// {
+    ///     println!("{} is ready to use!", gamepad.name());
+    /// }
+    /// ```
+    pub fn mapping_source(&self) -> MappingSource {
+        self.data.mapping_source
+    }
+
+    /// Returns true if force feedback is supported by device.
+    pub fn is_ff_supported(&self) -> bool {
+        self.inner.is_ff_supported()
+    }
+
+    /// Returns [`EffectBuilder`](ff/struct.EffectBuilder.html) for effect played on this
+    /// gamepad. Unlike `EffectBuilder::finish()`, its `build()` doesn't need `Gilrs`.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # let gilrs = gilrs::Gilrs::new().unwrap();
+    /// # let id = gilrs.gamepads().next().unwrap().0;
+    /// let effect = gilrs
+    ///     .gamepad(id)
+    ///     .create_ff_effect()
+    ///     .with_rumble(40_000, 0)
+    ///     .with_duration(Duration::from_millis(300))
+    ///     .build()
+    ///     .unwrap();
+    /// effect.play().unwrap();
+    /// ```
+    pub fn create_ff_effect(&self) -> EffectBuilder {
+        EffectBuilder::for_gamepad(self, self.data.tx.clone(), self.data.next_ff_id.clone())
+    }
+
+    /// Plays short rumble on both motors at full strength, to test if force feedback works. This
+    /// is intended for "test rumble" buttons in setup wizards, so every application uses the same
+    /// pulse. Rumble lasts 300ms and it doesn't have to be kept alive by caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Disconnected` if gamepad is not connected and `Error::FfNotSupported` if
+    /// it doesn't support force feedback.
+    pub fn rumble_test(&self) -> Result<(), FfError> {
+        let effect = self
+            .create_ff_effect()
+            .with_rumble(std::u16::MAX, std::u16::MAX)
+            .with_duration(RUMBLE_TEST_DURATION)
+            .with_repeat(1)
+            .build()?;
+        effect.play_detached()
+    }
+
+    /// Returns handle that sets gamepad's rumble motors directly, bypassing force feedback
+    /// effects. Returns `None` if gamepad is not connected or doesn't support force feedback.
+    ///
+    /// While handle exists, `Effect`s are not played on this gamepad. They are played again after
+    /// all handles are dropped. See [`RawFfHandle`](ff/struct.RawFfHandle.html).
+    #[cfg(feature = "raw-ff")]
+    pub fn ff_device_raw(&self) -> Option<RawFfHandle> {
+        if !self.is_connected() || !self.is_ff_supported() {
+            return None;
+        }
+
+        let device = self.inner.raw_ff_device()?;
+
+        Some(RawFfHandle::new(device, self.id.0, self.data.tx.clone()))
+    }
+
+    /// Returns true if gamepad has touchpad that emits `EventType::TouchpadTouch` events.
+    /// Currently touchpads are only supported on Linux, for gamepads whose driver exposes
+    /// touchpad as separate device (DualShock 4, DualSense). On other platforms this always
+    /// returns false.
+    ///
+    /// On Windows touchpads are not supported at all. Gamepads are read with XInput, which has
+    /// no touchpad data, so this returns false even for DualShock 4 and DualSense and
+    /// `TouchpadTouch` is never emitted.
+    pub fn has_touchpad(&self) -> bool {
+        self.inner.has_touchpad()
+    }
+
+    /// Returns how many input reports per second gamepad sends or `None` if this information is
+    /// not available. Value is read when gamepad is connected.
+    pub fn input_report_rate(&self) -> Option<u32> {
+        self.data.report_rate
+    }
+
+    /// Returns time between two consecutive input reports. See
+    /// [`input_report_rate()`](#method.input_report_rate).
+    pub fn input_report_period(&self) -> Option<Duration> {
+        self.data
+            .report_rate
+            .map(|rate| Duration::from_nanos(1_000_000_000 / u64::from(rate)))
+    }
+
+    /// Change gamepad position used by force feedback effects.
+    pub fn set_listener_position<Vec3: Into<[f32; 3]>>(
+        &self,
+        position: Vec3,
+    ) -> Result<(), FfError> {
+        if !self.is_connected() {
+            Err(FfError::Disconnected(self.id()))
+        } else if !self.is_ff_supported() {
+            Err(FfError::FfNotSupported(self.id()))
+        } else {
+            let position = position.into();
+            self.data.tx.send(Message::SetListenerPosition {
+                id: self.id.0,
+                position,
+            })?;
+            self.data.listener_position.set(position);
+            Ok(())
+        }
+    }
+
+    /// Returns gamepad position used by force feedback effects. Position is reset to
+    /// `[0.0, 0.0, 0.0]` when gamepad is reconnected.
+    pub fn listener_position(&self) -> [f32; 3] {
+        self.data.listener_position.get()
+    }
+
+    /// Returns position of gamepad used by force feedback attenuation. See
+    /// [`Gilrs::set_gamepad_position()`](struct.Gilrs.html#method.set_gamepad_position).
+    pub fn gamepad_position(&self) -> [f32; 3] {
+        self.data.gamepad_position.get()
+    }
+
+    /// Returns factor by which force feedback magnitudes are currently multiplied to compensate
+    /// for low battery, or 1.0 if they are not changed. See
+    /// [`Gilrs::set_ff_battery_compensation()`](struct.Gilrs.html#method.set_ff_battery_compensation).
+    pub fn ff_battery_compensation(&self) -> f32 {
+        self.data.ff_compensation
+    }
+
+    /// Returns `AxisOrBtn` mapped to `Code`.
+    pub fn axis_or_btn_name(&self, ec: Code) -> Option<AxisOrBtn> {
+        self.data.axis_or_btn_name(ec)
+    }
+
+    /// Returns `Code` associated with `btn`.
+    pub fn button_code(&self, btn: Button) -> Option<Code> {
+        self.data.button_code(btn)
+    }
+
+    /// Returns `Code` associated with `axis`.
+    pub fn axis_code(&self, axis: Axis) -> Option<Code> {
+        self.data.axis_code(axis)
+    }
+
+    /// Returns `Flipped` if gilrs negates values of `axis` reported by device to follow
+    /// convention described in [`Axis`](enum.Axis.html). This is the case for Y axes on platforms
+    /// where they point down (see `gilrs_core::IS_Y_AXIS_REVERSED`). Axes that don't have
+    /// convention, like `Axis::Unknown`, are always reported in direction used by device.
+    pub fn axis_direction(&self, axis: Axis) -> AxisDirection {
+        if is_axis_flipped(axis, self.inner.is_y_axis_reversed()) {
+            AxisDirection::Flipped
+        } else {
+            AxisDirection::Native
+        }
+    }
+
+    /// Returns `Code`s of all mapped buttons and axes. Useful for showing on-screen prompts or
+    /// current layout in remapping UI.
+    ///
+    /// ```
+    /// # let gilrs = gilrs::Gilrs::new().unwrap();
+    /// for (_, gamepad) in gilrs.gamepads() {
+    ///     let mapping = gamepad.effective_mapping();
+    ///     println!("{} buttons are mapped", mapping.buttons.len());
+    ///     println!("Not mapped: {:?}", mapping.unmapped_buttons(&gamepad));
+    /// }
+    /// ```
+    pub fn effective_mapping(&self) -> EffectiveMapping {
+        EffectiveMapping {
+            buttons: Button::all()
+                .iter()
+                .filter_map(|&btn| Some((btn, self.button_code(btn)?)))
+                .collect(),
+            axes: Axis::all()
+                .iter()
+                .filter_map(|&axis| Some((axis, self.axis_code(axis)?)))
+                .collect(),
+        }
+    }
+
+    /// Returns copy of current mapping that can be edited and passed to `Gilrs::set_mapping()`.
+    /// Setting unchanged copy gives the same mapping as the current one.
+    ///
+    /// Dpad mapped to hat in SDL mapping is kept as hat. `MappingData::button()` returns the same
+    /// codes for such dpad buttons as `button_code()`, inserting other code for dpad button or
+    /// removing it replaces hat binding of that direction. If more than one code is mapped to the
+    /// same button or axis, only one of them is included.
+    pub fn mapping_data(&self) -> MappingData {
+        self.data.mapping.to_data()
+    }
+
+    /// Returns iterator over `Code`s of all buttons reported by device, including ones that are
+    /// not mapped to any `Button`.
+    pub fn raw_buttons_iter(&self) -> impl Iterator<Item = Code> + 'a {
+        self.inner.buttons().iter().cloned().map(Code)
+    }
+
+    /// Returns iterator over `Code`s of all axes reported by device, including ones that are not
+    /// mapped to any `Axis`.
+    pub fn raw_axes_iter(&self) -> impl Iterator<Item = Code> + 'a {
+        self.inner.axes().iter().cloned().map(Code)
+    }
+
+    /// Returns SDL name of button or axis that `code` is mapped to, for example `"leftshoulder"`
+    /// or `"leftx"`. Returns `None` if `code` is not mapped or if element it's mapped to doesn't
+    /// exist in SDL. See `Button::sdl_name()` and `Axis::sdl_name()`.
+    pub fn button_name_sdl(&self, code: Code) -> Option<&'static str> {
+        match self.axis_or_btn_name(code)? {
+            AxisOrBtn::Btn(btn) if self.data.mapping.face_buttons_swapped() => {
+                mapping::swap_face_button(btn).sdl_name()
+            }
+            AxisOrBtn::Btn(btn) => btn.sdl_name(),
+            AxisOrBtn::Axis(axis) => axis.sdl_name(),
+        }
+    }
+
+    /// Returns area in which axis events should be ignored.
+    ///
+    /// Deadzone is in the same scale as values in events: [0.0, 1.0] for axes mapped to buttons
+    /// (like analog triggers) and [-1.0, 1.0] otherwise.
+    pub fn deadzone(&self, axis: Code) -> Option<f32> {
+        let unipolar = match self.axis_or_btn_name(axis) {
+            Some(AxisOrBtn::Btn(_)) => true,
+            Some(AxisOrBtn::Axis(_)) | None => false,
+        };
+
+        self.axis_info(axis)
+            .map(|i| i.deadzone_normalized(unipolar).unwrap_or(DEFAULT_DEADZONE))
+    }
+
+    /// Returns range and deadzone of axis with `code`, as reported by device or overridden with
+    /// `GilrsBuilder::with_axis_info_override()`. Returns `None` if gamepad doesn't have such axis.
+    pub fn axis_info(&self, code: Code) -> Option<AxisInfo> {
+        let info = *self.inner.axis_info(code.0)?;
+
+        Some(self.data.axis_overrides.get(&code).cloned().unwrap_or(info))
+    }
+
+    /// Returns ID of gamepad.
+    pub fn id(&self) -> GamepadId {
+        self.id
+    }
+
+    /// Returns identifier of physical device that is independent of gamepad's ID. See
+    /// [`PhysicalGamepadId`](struct.PhysicalGamepadId.html) for more info.
+    pub fn physical_id(&self) -> PhysicalGamepadId {
+        let uuid = self.uuid();
+        let os_name = if uuid == [0; 16] {
+            Some(self.os_name().to_owned())
+        } else {
+            None
+        };
+
+        PhysicalGamepadId(uuid, os_name)
+    }
+
+    /// Returns `true` if `other` is probably the same physical device as this gamepad, for
+    /// example the same controller that was disconnected and connected again with new ID.
+    ///
+    /// Gamepads are compared by UUID or by OS name if UUID is not available. This means that two
+    /// identical controllers are indistinguishable.
+    pub fn is_same_physical_device_as(&self, other: &Gamepad<'_>) -> bool {
+        self.physical_id() == other.physical_id()
+    }
+
+    /// Returns snapshot of gamepad's properties. This is the same information that is sent with
+    /// `EventType::Connected`.
+    pub fn info(&self) -> GamepadInfo {
+        GamepadInfo {
+            name: self.name().to_owned(),
+            uuid: self.uuid(),
+            mapping_source: self.mapping_source(),
+            is_ff_supported: self.is_ff_supported(),
+        }
+    }
+
+    pub(crate) fn mapping(&self) -> &Mapping {
+        &self.data.mapping
+    }
+
+    fn write_debug_dump<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        writeln!(out, "Gamepad {}", self.id())?;
+        writeln!(out, "  name: {}", self.name())?;
+        writeln!(out, "  os name: {}", self.os_name())?;
+        if let Some(manufacturer) = self.manufacturer_string() {
+            writeln!(out, "  manufacturer: {}", manufacturer)?;
+        }
+        if let Some(product) = self.product_string() {
+            writeln!(out, "  product: {}", product)?;
+        }
+        writeln!(out, "  uuid: {}", Uuid::from_bytes(self.uuid()).to_simple())?;
+        writeln!(out, "  mapping source: {:?}", self.mapping_source())?;
+        writeln!(out, "  status: {:?}", self.status())?;
+        writeln!(out, "  power info: {:?}", self.power_info())?;
+        writeln!(out, "  force feedback: {}", self.is_ff_supported())?;
+
+        writeln!(out, "  buttons:")?;
+        for code in self.raw_buttons_iter() {
+            match self.axis_or_btn_name(code) {
+                Some(el) => writeln!(out, "    {} -> {:?}", code, el)?,
+                None => writeln!(out, "    {} -> unmapped", code)?,
+            }
+        }
+
+        writeln!(out, "  axes:")?;
+        for code in self.raw_axes_iter() {
+            match self.axis_or_btn_name(code) {
+                Some(el) => write!(out, "    {} -> {:?}", code, el)?,
+                None => write!(out, "    {} -> unmapped", code)?,
+            }
+            if let Some(info) = self.axis_info(code) {
+                write!(out, ", range: {}..={}", info.min, info.max)?;
+                match info.deadzone {
+                    Some(deadzone) => write!(out, ", deadzone: {}", deadzone)?,
+                    None => write!(out, ", deadzone: default")?,
+                }
+            }
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct GamepadData {
+    state: GamepadState,
+    mapping: Mapping,
+    mapping_source: MappingSource,
+    tx: Sender<Message>,
+    next_ff_id: Arc<AtomicUsize>,
+    id: GamepadId,
+    uuid: [u8; 16],
+    report_rate: Option<u32>,
+    status: Status,
+    // Last position sent to ff server, it can't be read back from there.
+    listener_position: Cell<[f32; 3]>,
+    player_indicator: Cell<Option<u8>>,
+    gamepad_position: Cell<[f32; 3]>,
+    // Attenuation constant known to ff server.
+    ff_attenuation: f32,
+    curves: FnvHashMap<Code, CurveTable>,
+    max_ff_effects: Option<usize>,
+    connection_lost: Option<LostConnection>,
+    // Time of last event returned by `next_event()`.
+    last_activity: Option<SystemTime>,
+    // Set after idle gamepad was powered off.
+    idle_power_off: bool,
+    suppressed: bool,
+    // Axes that were reset by `Gilrs::release_all()` and didn't emit event since then.
+    released_axes: Vec<Code>,
+    // Last battery compensation factor sent to ff server.
+    ff_compensation: f32,
+    latency: LatencyHistogram,
+    // Time when `Gilrs::update()` processed last input event.
+    last_event_instant: Option<Instant>,
+    // Axis infos from `GilrsBuilder::with_axis_info_override()`.
+    axis_overrides: FnvHashMap<Code, AxisInfo>,
+}
+
+/// Settings used by `GamepadData::find_mapping()`.
+#[derive(Copy, Clone, Debug)]
+struct MappingOptions {
+    strict: bool,
+    layout: ButtonLayout,
+    auto_mapping: bool,
+}
+
+/// Function called after every state update, see
+/// [`GilrsBuilder::with_update_hook()`](struct.GilrsBuilder.html#method.with_update_hook).
+pub type UpdateHook = Box<dyn Fn(&Event, &GamepadState) + Send + 'static>;
+
+/// Hooks from `GilrsBuilder::with_update_hook()`.
+struct UpdateHooks(Vec<UpdateHook>);
+
+impl fmt::Debug for UpdateHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UpdateHooks({})", self.0.len())
+    }
+}
+
+/// Gamepad that emitted `ConnectionLost` and can still be restored.
+#[derive(Clone, Debug)]
+struct LostConnection {
+    deadline: SystemTime,
+    uuid: [u8; 16],
+    serial_number: Option<String>,
+}
+
+/// Maps IDs of backend's gamepads to `GamepadId`s. They are the same, unless gamepad that lost
+/// connection was restored by device in different slot of backend.
+#[derive(Debug, Default)]
+struct IdMap {
+    // Backend's IDs, indexed by `GamepadId`.
+    raw: Vec<usize>,
+    // `GamepadId`s, indexed by backend's ID.
+    ids: Vec<usize>,
+}
+
+impl IdMap {
+    fn raw(&self, id: GamepadId) -> usize {
+        self.raw.get(id.0).cloned().unwrap_or(id.0)
+    }
+
+    fn id(&self, raw: usize) -> GamepadId {
+        GamepadId(self.ids.get(raw).cloned().unwrap_or(raw))
+    }
+
+    /// Swaps backend's gamepads of `a` and `b`.
+    fn swap(&mut self, a: GamepadId, b: GamepadId) {
+        for idx in self.raw.len()..=a.0.max(b.0) {
+            self.raw.push(idx);
+            self.ids.push(idx);
+        }
+
+        self.raw.swap(a.0, b.0);
+        self.ids[self.raw[a.0]] = a.0;
+        self.ids[self.raw[b.0]] = b.0;
+    }
+}
+
+impl LostConnection {
+    /// Returns true if `gamepad` is the device that lost connection. Serial numbers are compared
+    /// if both devices have one, otherwise UUIDs.
+    fn is_same_device(&self, gamepad: RawGamepad<'_>) -> bool {
+        match (self.serial_number.as_deref(), gamepad.serial_number()) {
+            (Some(lost), Some(serial_number)) => lost == serial_number,
+            _ => self.uuid == gamepad.uuid(),
+        }
+    }
+}
+
+impl GamepadData {
+    fn new(
+        id: GamepadId,
+        tx: Sender<Message>,
+        next_ff_id: Arc<AtomicUsize>,
+        gamepad: RawGamepad<'_>,
+        db: &MappingDb,
+        options: MappingOptions,
+    ) -> Self {
+        let (mapping, mapping_source) = Self::find_mapping(gamepad, db, options);
+        let mut max_ff_effects = None;
+
+        if gamepad.is_ff_supported() && gamepad.is_connected() {
+            if let Some(device) = gamepad.ff_device() {
+                max_ff_effects = Some(device.query_max_effects());
+                let _ = tx.send(Message::Open { id: id.0, device });
+            }
+        }
+
+        GamepadData {
+            state: GamepadState::new(),
+            mapping,
+            mapping_source,
+            tx,
+            next_ff_id,
+            id,
+            uuid: gamepad.uuid(),
+            report_rate: gamepad.input_report_rate().filter(|&rate| rate > 0),
+            status: if gamepad.is_connected() {
+                Status::Connected
+            } else {
+                Status::NotObserved
+            },
+            listener_position: Cell::new([0.0; 3]),
+            player_indicator: Cell::new(None),
+            gamepad_position: Cell::new([0.0; 3]),
+            ff_attenuation: 0.0,
+            curves: FnvHashMap::default(),
+            max_ff_effects,
+            connection_lost: None,
+            last_activity: None,
+            idle_power_off: false,
+            suppressed: false,
+            released_axes: Vec::new(),
+            ff_compensation: 1.0,
+            latency: LatencyHistogram::new(),
+            last_event_instant: None,
+            axis_overrides: FnvHashMap::default(),
+        }
+    }
+
+    fn update_mapping(&mut self, gamepad: RawGamepad<'_>, db: &MappingDb, options: MappingOptions) {
+        let (mapping, source) = Self::find_mapping(gamepad, db, options);
+        self.mapping = mapping;
+        self.mapping_source = source;
+    }
+
+    /// Returns mapping from `db` or default mapping if `db` doesn't have valid mapping for
+    /// gamepad, together with its source. If `strict` is `false`, mapping for different version of
+    /// the same device can be used. Face buttons of mappings from `db` are swapped if `layout`
+    /// requires it.
+    ///
+    /// On Linux default mapping is only reported as `Driver` if driver follows kernel's gamepad
+    /// specification, which requires `BTN_GAMEPAD` (alias of `BTN_SOUTH`). Otherwise it's
+    /// `Heuristic`, or empty mapping is used if `auto_mapping` is `false`.
+    fn find_mapping(
+        gamepad: RawGamepad<'_>,
+        db: &MappingDb,
+        options: MappingOptions,
+    ) -> (Mapping, MappingSource) {
+        let MappingOptions {
+            strict,
+            layout,
+            auto_mapping,
+        } = options;
+        let uuid = Uuid::from_bytes(gamepad.uuid());
+        let mapping = db.get(uuid).or_else(|| {
+            let mapping = db_mapping(db, uuid, strict);
+            if mapping.is_some() {
+                debug!("Using mapping for different version of {}", uuid);
+            }
+            mapping
+        });
+
+        let mapping = mapping.and_then(|s| {
+            Mapping::parse_sdl_mapping(s, gamepad.buttons(), gamepad.axes())
+                .map_err(|e| warn!("Failed to parse mapping for {}: {}", uuid, e))
+                .ok()
+        });
+        let mut mapping = match mapping {
+            Some(mapping) => mapping,
+            None if !cfg!(target_os = "linux") => {
+                return (Mapping::default(gamepad), MappingSource::Driver)
+            }
+            None if gamepad.buttons().contains(&native_ev_codes::BTN_SOUTH) => {
+                return (Mapping::default(gamepad), MappingSource::Driver)
+            }
+            None if auto_mapping => return (Mapping::heuristic(gamepad), MappingSource::Heuristic),
+            None => return (Mapping::new(), MappingSource::None),
+        };
+
+        if layout == ButtonLayout::ByPosition && mapping::is_nintendo_layout(uuid) {
+            mapping.swap_face_buttons();
+        }
+
+        (mapping, MappingSource::SdlMappings)
+    }
+
+    /// Creates data for gamepad that backend knows about, but which was never connected.
+    fn not_observed(id: GamepadId, tx: Sender<Message>, next_ff_id: Arc<AtomicUsize>) -> Self {
+        GamepadData {
+            state: GamepadState::new(),
+            mapping: Mapping::new(),
+            mapping_source: MappingSource::None,
+            tx,
+            next_ff_id,
+            id,
+            uuid: [0; 16],
+            report_rate: None,
+            status: Status::NotObserved,
+            listener_position: Cell::new([0.0; 3]),
+            player_indicator: Cell::new(None),
+            gamepad_position: Cell::new([0.0; 3]),
+            ff_attenuation: 0.0,
+            curves: FnvHashMap::default(),
+            max_ff_effects: None,
+            connection_lost: None,
+            last_activity: None,
+            idle_power_off: false,
+            suppressed: false,
+            released_axes: Vec::new(),
+            ff_compensation: 1.0,
+            latency: LatencyHistogram::new(),
+            last_event_instant: None,
+            axis_overrides: FnvHashMap::default(),
+        }
+    }
+
+    fn set_connected(&mut self, connected: bool) {
+        self.status = if connected {
+            Status::Connected
+        } else {
+            Status::Disconnected
+        };
+    }
+
+    /// if `mapping_source()` is `SdlMappings` returns the name of the mapping used by the gamepad.
+    /// Otherwise returns `None`.
+    ///
+    /// Warning: Mappings are set after event `Connected` is processed therefore this function will
+    /// always return `None` before first calls to `Gilrs::next_event()`.
+    pub fn map_name(&self) -> Option<&str> {
+        if self.mapping_source == MappingSource::SdlMappings {
+            Some(&self.mapping.name())
+        } else {
+            None
+        }
+    }
+
+    /// Examines cached gamepad state to check if given button is pressed. Panics if `btn` is
+    /// `Unknown`.
+    ///
+    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
+    /// directly on `State`, because this version have to check which `Code` is mapped to element of
+    /// gamepad.
+    pub fn is_pressed(&self, btn: Button) -> bool {
+        assert_ne!(btn, Button::Unknown);
+
+        self.button_code(btn)
+            .or_else(|| btn.to_nec())
+            .map(|nec| self.state.is_pressed(nec))
+            .unwrap_or(false)
+    }
+
+    /// Examines cached gamepad state to check axis's value. Panics if `axis` is `Unknown`.
+    ///
+    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
+    /// directly on `State`, because this version have to check which `Code` is mapped to element of
+    /// gamepad.
+    pub fn value(&self, axis: Axis) -> f32 {
+        assert_ne!(axis, Axis::Unknown);
+
+        self.axis_code(axis)
+            .map(|nec| self.state.value(nec))
+            .unwrap_or(0.0)
+    }
+
+    /// Returns button state and when it changed.
+    ///
+    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
+    /// directly on `State`, because this version have to check which `Code` is mapped to element of
+    /// gamepad.
+    pub fn button_data(&self, btn: Button) -> Option<&ButtonData> {
+        self.button_code(btn)
+            .and_then(|nec| self.state.button_data(nec))
+    }
+
+    /// Returns axis state and when it changed.
+    ///
+    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
+    /// directly on `State`, because this version have to check which `Code` is mapped to element of
+    /// gamepad.
+    pub fn axis_data(&self, axis: Axis) -> Option<&AxisData> {
+        self.axis_code(axis)
+            .and_then(|nec| self.state.axis_data(nec))
+    }
+
+    /// Returns `AxisOrBtn` mapped to `Code`.
+    pub fn axis_or_btn_name(&self, ec: Code) -> Option<AxisOrBtn> {
+        self.mapping.map(&ec.0)
+    }
+
+    /// Returns `Code` associated with `btn`.
+    pub fn button_code(&self, btn: Button) -> Option<Code> {
+        self.mapping.map_rev(&AxisOrBtn::Btn(btn)).map(Code)
+    }
+
+    /// Returns `Code` associated with `axis`.
+    pub fn axis_code(&self, axis: Axis) -> Option<Code> {
+        self.mapping.map_rev(&AxisOrBtn::Axis(axis)).map(Code)
+    }
+}
+
+/// Source of gamepad mappings.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum MappingSource {
+    /// Gamepad uses SDL mappings.
+    SdlMappings,
+    /// Gamepad does not use any mappings but driver should provide unified controller layout.
+    Driver,
+    /// Gamepad does not have SDL mapping, so provisional mapping was built from codes reported by
+    /// driver. Some elements may be mapped incorrectly. See
+    /// [`GilrsBuilder::enable_auto_mapping()`](struct.GilrsBuilder.html#method.enable_auto_mapping).
+    Heuristic,
+    /// Gamepad does not use any mappings and most gamepad events will probably be `Button::Unknown`
+    /// or `Axis::Unknown`
+    None,
+}
+
+/// How face buttons of gamepads with Nintendo layout are mapped. See
+/// [`GilrsBuilder::button_layout()`](struct.GilrsBuilder.html#method.button_layout).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum ButtonLayout {
+    /// Buttons are mapped by their labels, `Button::South` is "A" button.
+    ByLabel,
+    /// Buttons are mapped by their positions, `Button::South` is bottom button.
+    ByPosition,
+}
+
+/// Information about gamepad, sent with `EventType::Connected`.
+///
+/// This is a snapshot taken when gamepad was connected. Use `Gilrs::gamepad()` to get current
+/// values.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct GamepadInfo {
+    /// See `Gamepad::name()`.
+    pub name: String,
+    /// See `Gamepad::uuid()`.
+    pub uuid: [u8; 16],
+    /// See `Gamepad::mapping_source()`.
+    pub mapping_source: MappingSource,
+    /// See `Gamepad::is_ff_supported()`.
+    pub is_ff_supported: bool,
+}
+
+/// Summary of events processed by [`Gilrs::pump()`](struct.Gilrs.html#method.pump).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PumpResult {
+    /// `true` if at least one event, that was not dropped, was processed.
+    pub changed: bool,
+    /// Gamepads that emitted at least one event, in order of their first event.
+    pub active_gamepads: Vec<GamepadId>,
+    /// Number of `Connected` events.
+    pub connected: usize,
+    /// Number of `Disconnected` events.
+    pub disconnected: usize,
+}
+
+/// Identifier of physical device.
+///
+/// Unlike `GamepadId`, which may change when gamepad is reconnected, this value is based on
+/// gamepad's UUID (or OS name, if UUID is not available) and can be stored by application to map
+/// player slots across disconnect/reconnect cycles. Use `Gamepad::physical_id()` to obtain it.
+///
+/// Identical controllers (same model and connection type) have the same `PhysicalGamepadId`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct PhysicalGamepadId([u8; 16], Option<String>);
+
+impl PhysicalGamepadId {
+    /// Returns UUID of gamepad.
+    pub fn uuid(&self) -> [u8; 16] {
+        self.0
+    }
+
+    /// Returns OS name of gamepad. This is `Some` only if UUID is nil.
+    pub fn os_name(&self) -> Option<&str> {
+        self.1.as_deref()
+    }
+}
+
+/// Gamepad ID.
+///
+/// It's not possible to create instance of this type directly, but you can obtain one from Gamepad
+/// handle or any event. ID is valid for entire lifetime of `Gilrs` context.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct GamepadId(pub(crate) usize);
+
+impl Into<usize> for GamepadId {
+    fn into(self) -> usize {
+        self.0
+    }
+}
+
+impl Display for GamepadId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Returns mapping for `uuid` from `db`. If `strict` is `false`, mapping for different version of
+/// the same device can be returned.
+fn db_mapping(db: &MappingDb, uuid: Uuid, strict: bool) -> Option<&str> {
+    if strict {
+        db.get(uuid)
+    } else {
+        db.get_fuzzy(uuid)
+    }
+}
+
+/// Returns true if values of `axis` have to be negated to follow convention described in `Axis`.
+/// `y_reversed` is `IS_Y_AXIS_REVERSED` of backend.
+fn is_axis_flipped(axis: Axis, y_reversed: bool) -> bool {
+    y_reversed && (axis == Axis::LeftStickY || axis == Axis::RightStickY || axis == Axis::DPadY)
+}
+
+/// Converts raw axis value to [-1.0, 1.0] range. `y_reversed` is `IS_Y_AXIS_REVERSED` of backend.
+fn axis_value(info: &AxisInfo, val: i32, axis: Axis, y_reversed: bool) -> f32 {
+    let mut range = info.max as f32 - info.min as f32;
+    let mut val = val as f32 - info.min as f32;
+
+    if (info.max - info.min) % 2 == 1 {
+        // Add one to range and val, so value at ceter (like 127/255) will be mapped 0.0
+        range += 1.0;
+        val += 1.0;
+    }
+
+    val = val / range * 2.0 - 1.0;
+
+    if is_axis_flipped(axis, y_reversed) && val != 0.0 {
+        val = -val;
+    }
+
+    utils::clamp(val, -1.0, 1.0)
+}
+
+/// Returns button with `code` in events synthesized from gamepad's state. Buttons emitted by
+/// `axis_dpad_to_button` filter have native D-pad codes, which usually aren't mapped.
+fn btn_name(gamepad: &Gamepad<'_>, code: Code) -> Button {
+    use gilrs_core::native_ev_codes as necs;
+
+    const DPAD: [(gilrs_core::EvCode, Button); 4] = [
+        (necs::BTN_DPAD_UP, Button::DPadUp),
+        (necs::BTN_DPAD_DOWN, Button::DPadDown),
+        (necs::BTN_DPAD_LEFT, Button::DPadLeft),
+        (necs::BTN_DPAD_RIGHT, Button::DPadRight),
+    ];
+
+    match gamepad.axis_or_btn_name(code) {
+        Some(AxisOrBtn::Btn(btn)) => btn,
+        _ => DPAD
+            .iter()
+            .find(|&&(nec, _)| Code(nec) == code)
+            .map(|&(_, btn)| btn)
+            .unwrap_or(Button::Unknown),
+    }
+}
+
+fn btn_value(info: &AxisInfo, val: i32) -> f32 {
+    let range = (info.max - info.min) as f32;
+    let mut val = (val - info.min) as f32;
+    val /= range;
+
+    utils::clamp(val, 0.0, 1.0)
+}
+
+/// Error type which can be returned when creating `Gilrs`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Gilrs does not support current platform, but you can use dummy context from this error if
+    /// gamepad input is not essential.
+    NotImplemented(Gilrs),
+    /// Either `pressed ≤ released` or one of values is outside [0.0, 1.0] range.
+    InvalidAxisToBtn,
+    /// Force feedback tick duration is outside [1 ms, 100 ms] range.
+    InvalidFfTickDuration,
+    /// Another `Gilrs` is alive and current platform doesn't support multiple instances. See
+    /// [`Gilrs`](struct.Gilrs.html#multiple-instances).
+    AlreadyInUse,
+    /// Platform specific error.
+    Other(Box<dyn error::Error + Send + Sync + 'static>),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotImplemented(_) => f.write_str("Gilrs does not support current platform."),
+            Error::InvalidAxisToBtn => f.write_str(
+                "Either `pressed ≤ released` or one of values is outside [0.0, 1.0] range.",
+            ),
+            Error::InvalidFfTickDuration => {
+                f.write_str("Force feedback tick duration is outside [1 ms, 100 ms] range.")
+            }
+            Error::AlreadyInUse => f.write_str(
+                "Another Gilrs instance is alive and current platform doesn't support more than \
+                 one.",
+            ),
+            Error::Other(ref e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error {
+    /// Returns numeric error code reported by operating system (errno, `HRESULT` or Win32 error
+    /// code), if this error was caused by one.
+    pub fn os_error(&self) -> Option<i32> {
+        self.platform_error_kind().map(|kind| kind.code())
+    }
+
+    /// Returns OS error code together with stable tag describing what failed, if this error was
+    /// caused by OS error.
+    pub fn platform_error_kind(&self) -> Option<PlatformErrorKind> {
+        match self {
+            Error::Other(e) => PlatformErrorKind::find(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Other(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{capture_logs, gilrs_with_gamepad, RawEventType};
+    use gilrs_core::native_ev_codes as nec;
+
+    #[test]
+    fn status() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        assert_eq!(gilrs.status(id), Status::Connected);
+
+        gilrs.test_backend().unwrap().remove_device(id);
+        assert_eq!(gilrs.next_event().unwrap().event, EventType::Disconnected);
+        assert_eq!(gilrs.status(id), Status::Disconnected);
+        assert!(gilrs.connected_gamepad(id).is_none());
+
+        // Gamepad reconnected with new ID
+        let backend = gilrs.test_backend().unwrap();
+        let next_id = backend.add_device("Test Gamepad", [0; 16], &[nec::BTN_SOUTH], &[]);
+        assert_eq!(gilrs.status(next_id), Status::NotObserved);
+        // Backend already knows about gamepad, but `next_event()` didn't see it yet.
+        let gamepad = gilrs.gamepad(next_id);
+        assert_eq!(gamepad.id(), next_id);
+        assert_eq!(gamepad.status(), Status::NotObserved);
+
+        let ev = gilrs.next_event().unwrap();
+        let info = GamepadInfo {
+            name: "Test Gamepad".to_owned(),
+            uuid: [0; 16],
+            mapping_source: MappingSource::Driver,
+            is_ff_supported: false,
+        };
+        assert_eq!((ev.id, ev.event), (next_id, EventType::Connected(info)));
+        assert_eq!(gilrs.status(next_id), Status::Connected);
+        assert_eq!(gilrs.status(id), Status::Disconnected);
+        assert_eq!(gilrs.status(GamepadId(next_id.0 + 1)), Status::NotObserved);
+    }
+
+    #[test]
+    fn suspended() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        while gilrs.next_event().is_some() {}
+        assert!(gilrs.gamepad(id).is_pressed(Button::South));
+
+        gilrs.test_backend().unwrap().suspend_device(id);
+        assert_eq!(gilrs.next_event().unwrap().event, EventType::Suspended);
+        assert_eq!(gilrs.status(id), Status::Suspended);
+        assert!(!gilrs.gamepad(id).is_connected());
+        assert!(!gilrs.gamepad(id).is_pressed(Button::South));
+        assert!(gilrs.connected_gamepad(id).is_none());
+
+        gilrs.test_backend().unwrap().resume_device(id);
+        assert_eq!(gilrs.next_event().unwrap().event, EventType::Resumed);
+        assert_eq!(gilrs.status(id), Status::Connected);
+        assert!(gilrs.gamepad(id).is_connected());
+
+        // Suspended gamepad can still be disconnected.
+        let backend = gilrs.test_backend().unwrap();
+        backend.suspend_device(id);
+        backend.remove_device(id);
+        assert_eq!(gilrs.next_event().unwrap().event, EventType::Suspended);
+        assert_eq!(gilrs.next_event().unwrap().event, EventType::Disconnected);
+        assert_eq!(gilrs.status(id), Status::Disconnected);
+    }
+
+    #[test]
+    fn dropped_events_are_skipped() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let pressed = Event::new(
+            id,
+            EventType::ButtonPressed(Button::South, Code(nec::BTN_SOUTH)),
+        );
+
+        // Events dropped by custom filter
+        gilrs.insert_event(pressed.clone().drop());
+        gilrs.insert_event(Event::new(id, EventType::drop()));
+        gilrs.insert_event(pressed.clone());
+
+        let ev = gilrs.next_event().unwrap();
+        assert!(!ev.is_dropped());
+        assert_eq!(ev.event, pressed.event);
+        assert!(gilrs.next_event().is_none());
+    }
+
+    #[test]
+    fn dropped_events_without_default_filters() {
+        let builder = GilrsBuilder::new().with_default_filters(false);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        gilrs.insert_event(Event::new(id, EventType::Dropped));
+
+        assert!(gilrs.next_event().unwrap().is_dropped());
+    }
+
+    #[test]
+    fn analog_button_state() {
+        // Face button bound to axis code
+        let builder =
+            GilrsBuilder::new().add_mappings("00000000000000000000000000000000,Test,a:a2,");
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        let code = Code(nec::AXIS_RT2);
+        let raw = |gilrs: &mut Gilrs, val| {
+            let ev = RawEventType::AxisValueChanged(val, nec::AXIS_RT2);
+            gilrs.test_backend().unwrap().push(id, ev);
+        };
+
+        raw(&mut gilrs, 102);
+        assert_eq!(
+            gilrs.next_event().unwrap().event,
+            EventType::ButtonChanged(Button::South, 0.4, code)
+        );
+        assert!(!gilrs.gamepad(id).is_pressed(Button::South));
+
+        raw(&mut gilrs, 204);
+        assert_eq!(
+            gilrs.next_event().unwrap().event,
+            EventType::ButtonPressed(Button::South, code)
+        );
+        assert_eq!(
+            gilrs.next_event().unwrap().event,
+            EventType::ButtonChanged(Button::South, 0.8, code)
+        );
+        assert!(gilrs.gamepad(id).is_pressed(Button::South));
+        assert_eq!(gilrs.gamepad(id).state().value(code), 0.8);
+
+        // Between release and press thresholds
+        raw(&mut gilrs, 178);
+        gilrs.next_event().unwrap();
+        assert!(gilrs.gamepad(id).is_pressed(Button::South));
+        assert_eq!(gilrs.gamepad(id).state().value(code), 178.0 / 255.0);
+
+        raw(&mut gilrs, 51);
+        assert_eq!(
+            gilrs.next_event().unwrap().event,
+            EventType::ButtonReleased(Button::South, code)
+        );
+        gilrs.next_event().unwrap();
+        assert!(!gilrs.gamepad(id).is_pressed(Button::South));
+        assert_eq!(gilrs.gamepad(id).state().value(code), 0.2);
+    }
+
+    #[test]
+    fn frames_since_last_input() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+
+        for _ in 0..3 {
+            gilrs.inc();
+        }
+        assert_eq!(gilrs.frames_since_last_input(id), 3);
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        while gilrs.next_event().is_some() {}
+        assert_eq!(gilrs.frames_since_last_input(id), 0);
+
+        for _ in 0..5 {
+            gilrs.inc();
+        }
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::AxisValueChanged(16384, nec::AXIS_LSTICKX));
+        while gilrs.next_event().is_some() {}
+        gilrs.inc();
+        assert_eq!(gilrs.frames_since_last_input(id), 1);
+        assert_eq!(gilrs.gamepad(id).state().last_event_counter(), 8);
+    }
+
+    #[test]
+    fn digital_buttons() {
+        let builder = GilrsBuilder::new()
+            .with_default_filters(false)
+            .with_analog_buttons(false);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        let code = Code(nec::AXIS_RT2);
+        let backend = gilrs.test_backend().unwrap();
+        for &val in [102, 204, 178, 230, 51, 25].iter() {
+            backend.push(id, RawEventType::AxisValueChanged(val, nec::AXIS_RT2));
+        }
+
+        let mut events = Vec::new();
+        while let Some(ev) = gilrs.next_event() {
+            if !ev.is_dropped() {
+                events.push(ev.event);
+            }
+        }
+        assert_eq!(
+            events,
+            &[
+                EventType::ButtonPressed(Button::RightTrigger2, code),
+                EventType::ButtonChanged(Button::RightTrigger2, 1.0, code),
+                EventType::ButtonReleased(Button::RightTrigger2, code),
+                EventType::ButtonChanged(Button::RightTrigger2, 0.0, code),
+            ]
+        );
+        assert_eq!(gilrs.gamepad(id).state().value(code), 0.0);
+    }
+
+    #[test]
+    fn non_analog_axis_buttons() {
+        let axis = AxisInfo {
+            min: 0,
+            max: 1,
+            deadzone: None,
+        };
+        let builder = GilrsBuilder::new().with_default_filters(false);
+        let (mut gilrs, _) = gilrs_with_gamepad(builder);
+        let code = Code(nec::AXIS_RT2);
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device("Digital", [1; 16], &[], &[(nec::AXIS_RT2, axis)]);
+        for &val in [1, 1, 0].iter() {
+            backend.push(id, RawEventType::AxisValueChanged(val, nec::AXIS_RT2));
+        }
+
+        let mut events = Vec::new();
+        while let Some(ev) = gilrs.next_event() {
+            if !ev.is_dropped() && ev.id == id {
+                events.push(ev.event);
+            }
+        }
+        assert_eq!(
+            &events[1..],
+            &[
+                EventType::ButtonPressed(Button::RightTrigger2, code),
+                EventType::ButtonChanged(Button::RightTrigger2, 1.0, code),
+                EventType::ButtonReleased(Button::RightTrigger2, code),
+                EventType::ButtonChanged(Button::RightTrigger2, 0.0, code),
+            ]
+        );
+    }
+
+    #[test]
+    fn debug_dump() {
+        let (gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let dump = gilrs.debug_dump();
+
+        assert!(dump.starts_with(&format!("Gamepad {}\n", id)));
+        assert!(dump.contains("  name: Test Gamepad\n"));
+        assert!(dump.contains("  uuid: 00000000000000000000000000000000\n"));
+        assert!(dump.contains("  status: Connected\n"));
+        assert!(dump.contains(&format!("    {} -> Btn(South)\n", Code(nec::BTN_SOUTH))));
+        assert!(dump.contains(&format!(
+            "    {} -> Btn(RightTrigger2), range: 0..=255, deadzone: 0\n",
+            Code(nec::AXIS_RT2)
+        )));
+    }
+
+    #[test]
+    fn suppress_gamepad() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        gilrs.suppress_gamepad(id);
+        assert!(gilrs.gamepad(id).is_suppressed());
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        assert_eq!(gilrs.next_event(), None);
+        assert!(gilrs.gamepad(id).is_pressed(Button::South));
+        assert_eq!(gilrs.last_seq(), 1);
+
+        gilrs.test_backend().unwrap().remove_device(id);
+        assert_eq!(
+            gilrs.next_event().map(|ev| ev.event),
+            Some(EventType::Disconnected)
+        );
+        gilrs.test_backend().unwrap().reconnect_device(id);
+        match gilrs.next_event().map(|ev| ev.event) {
+            Some(EventType::Connected(_)) => (),
+            ev => panic!("Expected Connected, got {:?}", ev),
+        }
+        assert!(gilrs.gamepad(id).is_suppressed());
+
+        gilrs.unsuppress_gamepad(id);
+        assert!(!gilrs.gamepad(id).is_suppressed());
+        gilrs
+            .test_backend()
+            .unwrap()
+            .push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        assert_eq!(
+            gilrs.next_event().map(|ev| ev.event),
+            Some(EventType::ButtonPressed(
+                Button::South,
+                Code(nec::BTN_SOUTH)
+            ))
+        );
+    }
+
+    #[test]
+    fn seq() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        assert_eq!(gilrs.last_seq(), 1);
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.push(id, RawEventType::ButtonReleased(nec::BTN_SOUTH));
+        gilrs.insert_event(Event::new(
+            id,
+            EventType::ButtonPressed(Button::North, Code(nec::BTN_NORTH)),
+        ));
+
+        let seqs: Vec<_> = std::iter::from_fn(|| gilrs.next_event())
+            .map(|ev| ev.seq)
+            .collect();
+        assert_eq!(seqs, &[2, 3, 4, 5, 6]);
+        assert_eq!(gilrs.last_seq(), 6);
+
+        // Long running application.
+        gilrs.last_seq = 1 << 62;
+        gilrs
+            .test_backend()
+            .unwrap()
+            .push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        assert_eq!(gilrs.next_event().map(|ev| ev.seq), Some((1 << 62) + 1));
+        assert_eq!(gilrs.next_event().map(|ev| ev.seq), Some((1 << 62) + 2));
+    }
+
+    #[test]
+    fn options_defaults() {
+        let builder = GilrsBuilder::new();
+        let from_options = GilrsBuilder::from(GilrsOptions::default());
+
+        assert_eq!(builder.mappings.len(), from_options.mappings.len());
+        assert_eq!(builder.default_filters, from_options.default_filters);
+        assert_eq!(
+            builder.axis_to_btn_pressed,
+            from_options.axis_to_btn_pressed
+        );
+        assert_eq!(
+            builder.axis_to_btn_released,
+            from_options.axis_to_btn_released
+        );
+        assert_eq!(builder.update_state, from_options.update_state);
+        assert_eq!(builder.analog_buttons, from_options.analog_buttons);
+        assert_eq!(
+            builder.unchanged_axis_epsilon,
+            from_options.unchanged_axis_epsilon
+        );
+        assert_eq!(builder.env_mappings, from_options.env_mappings);
+        assert_eq!(builder.included_mappings, from_options.included_mappings);
+        assert_eq!(builder.auto_mapping, from_options.auto_mapping);
+        assert_eq!(builder.event_logging, from_options.event_logging);
+        assert_eq!(builder.state_logging, from_options.state_logging);
+        assert_eq!(builder.ignore_devices, from_options.ignore_devices);
+        assert_eq!(builder.env_ignore_list, from_options.env_ignore_list);
+        assert_eq!(builder.inverted_axes, from_options.inverted_axes);
+        assert_eq!(builder.latency_metrics, from_options.latency_metrics);
+    }
+
+    #[test]
+    fn event_logging() {
+        let (gilrs, _) = gilrs_with_gamepad(GilrsBuilder::new());
+        assert_eq!(gilrs.event_log_level, None);
+
+        let builder = GilrsBuilder::new()
+            .with_event_logging(LevelFilter::Debug)
+            .with_state_logging(true);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        assert_eq!(gilrs.event_log_level, Some(Level::Debug));
+
+        gilrs
+            .test_backend()
+            .unwrap()
+            .push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        let mut ev = None;
+        let logs = capture_logs(|| ev = gilrs.next_event());
+        let ev = ev.unwrap();
+        assert_eq!(
+            ev.event,
+            EventType::ButtonPressed(Button::South, Code(nec::BTN_SOUTH))
+        );
+        assert!(gilrs.gamepad(id).is_pressed(Button::South));
+
+        let event_log = format!("Event: {:?}", ev);
+        let state_log = format!(
+            "Gamepad {} button {} state: {:?}",
+            id,
+            Code(nec::BTN_SOUTH),
+            gilrs.gamepad(id).state().button_data(Code(nec::BTN_SOUTH))
+        );
+        let logs = logs
+            .into_iter()
+            .filter(|(_, msg)| msg.starts_with("Event: ") || msg.starts_with("Gamepad "))
+            .collect::<Vec<_>>();
+        assert_eq!(logs, [(Level::Debug, event_log), (Level::Debug, state_log)]);
+
+        // Without event logging nothing is logged.
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new().with_state_logging(true));
+        gilrs
+            .test_backend()
+            .unwrap()
+            .push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        let logs = capture_logs(|| {
+            gilrs.next_event().unwrap();
+        });
+        assert!(
+            !logs
+                .iter()
+                .any(|(_, msg)| msg.starts_with("Event: ") || msg.starts_with("Gamepad ")),
+            "{:?}",
+            logs
+        );
+    }
+
+    #[test]
+    fn axis_curve() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new().with_default_filters(false));
+        let code = Code(nec::AXIS_LSTICKX);
+        let curve =
+            CurveTable::from_points(&[(-1.0, -1.0), (0.0, 0.0), (0.5, 0.1), (1.0, 1.0)]).unwrap();
+        gilrs.set_axis_curve(id, code, curve).unwrap();
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::AxisValueChanged(32767, nec::AXIS_LSTICKX));
+        backend.push(id, RawEventType::AxisValueChanged(16384, nec::AXIS_LSTICKY));
+        backend.push(id, RawEventType::AxisValueChanged(16384, nec::AXIS_LSTICKX));
+
+        let mut values = Vec::new();
+        while let Some(ev) = gilrs.next_event() {
+            if let EventType::AxisChanged(_, val, _) = ev.event {
+                values.push(val);
+            }
+        }
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[0], 1.0);
+        // Other axis is not affected
+        assert!((values[1].abs() - 0.5).abs() < 0.001);
+        assert!((values[2] - 0.1).abs() < 0.001);
+        assert_eq!(gilrs.gamepad(id).state().value(code), values[2]);
+    }
+
+    #[test]
+    fn mapping_axis_curve() {
+        let builder = GilrsBuilder::new()
+            .with_default_filters(false)
+            .add_mappings(
+                "00000000000000000000000000000000,Test,leftx:a0,lefty:a1,\
+             axis:leftx=-1/-1;0/0;0.5/0.1;1/1,",
+            );
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        let value = |gilrs: &mut Gilrs, val, nec| {
+            let backend = gilrs.test_backend().unwrap();
+            backend.push(id, RawEventType::AxisValueChanged(val, nec));
+            match gilrs.next_event().map(|ev| ev.event) {
+                Some(EventType::AxisChanged(_, val, _)) => val,
+                e => panic!("Unexpected event {:?}", e),
+            }
+        };
+
+        assert!((value(&mut gilrs, 16384, nec::AXIS_LSTICKX) - 0.1).abs() < 0.001);
+        assert!((value(&mut gilrs, 16384, nec::AXIS_LSTICKY).abs() - 0.5).abs() < 0.001);
+
+        // Curve set by user overrides one from mapping.
+        let code = gilrs.gamepad(id).axis_code(Axis::LeftStickX).unwrap();
+        gilrs
+            .set_axis_curve(id, code, CurveTable::linear())
+            .unwrap();
+        assert!((value(&mut gilrs, 16000, nec::AXIS_LSTICKX) - 0.488).abs() < 0.001);
+
+        assert_eq!(
+            gilrs.set_axis_curve(GamepadId(5), code, CurveTable::linear()),
+            Err(CurveError::UnknownGamepad(GamepadId(5)))
+        );
+    }
+
+    #[test]
+    fn physical_id() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        backend.remove_device(id);
+        let same = backend.add_device("Test Gamepad", [0; 16], &[], &[]);
+        let other = backend.add_device("Other Gamepad", [0; 16], &[], &[]);
+        let uuid = [1; 16];
+        let with_uuid = backend.add_device("Test Gamepad", uuid, &[], &[]);
+        let with_uuid2 = backend.add_device("Other Gamepad", uuid, &[], &[]);
+        while gilrs.next_event().is_some() {}
+
+        let gp = gilrs.gamepad(id);
+        assert!(gp.is_same_physical_device_as(&gilrs.gamepad(same)));
+        assert!(!gp.is_same_physical_device_as(&gilrs.gamepad(other)));
+        assert!(!gp.is_same_physical_device_as(&gilrs.gamepad(with_uuid)));
+        assert!(gilrs
+            .gamepad(with_uuid)
+            .is_same_physical_device_as(&gilrs.gamepad(with_uuid2)));
+
+        assert_eq!(gp.physical_id().os_name(), Some("Test Gamepad"));
+        let physical_id = gilrs.gamepad(with_uuid).physical_id();
+        assert_eq!(physical_id.uuid(), uuid);
+        assert_eq!(physical_id.os_name(), None);
+    }
+
+    #[test]
+    fn guide_button() {
+        let (mut gilrs, _) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device("Xbox Controller", [0; 16], &[nec::BTN_MODE], &[]);
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_MODE));
+        backend.push(id, RawEventType::ButtonReleased(nec::BTN_MODE));
+
+        let mut events = Vec::new();
+        while let Some(ev) = gilrs.next_event() {
+            events.push(ev.event);
+        }
+        let code = Code(nec::BTN_MODE);
+        assert_eq!(
+            &events[1..],
+            &[
+                EventType::ButtonPressed(Button::Mode, code),
+                EventType::ButtonChanged(Button::Mode, 1.0, code),
+                EventType::ButtonReleased(Button::Mode, code),
+                EventType::ButtonChanged(Button::Mode, 0.0, code),
+            ]
+        );
+        assert_eq!(gilrs.gamepad(id).button_code(Button::Mode), Some(code));
+    }
+
+    #[test]
+    fn touchpad() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        assert!(!gilrs.gamepad(id).has_touchpad());
+
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device("Touchpad", [1; 16], &[nec::BTN_SOUTH], &[]);
+        backend.set_touchpad(id, true);
+        gilrs.next_event();
+        assert!(gilrs.gamepad(id).has_touchpad());
+
+        let touch = RawEventType::TouchpadTouch {
+            finger: 1,
+            x: 0.25,
+            y: 0.75,
+            pressed: true,
+        };
+        gilrs.test_backend().unwrap().push(id, touch);
+
+        assert_eq!(
+            gilrs.next_event().unwrap().event,
+            EventType::TouchpadTouch {
+                finger: 1,
+                x: 0.25,
+                y: 0.75,
+                pressed: true,
+            }
+        );
+        assert!(gilrs.next_event().is_none());
+    }
+
+    #[test]
+    fn added_and_removed_events() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        assert_eq!(gilrs.gamepad_added_events().collect::<Vec<_>>(), &[id]);
+        assert_eq!(gilrs.gamepad_removed_events().count(), 0);
+
+        gilrs.inc();
+        assert_eq!(gilrs.gamepad_added_events().count(), 0);
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.remove_device(id);
+        let id2 = backend.add_device("Second", [0; 16], &[nec::BTN_SOUTH], &[]);
+        while gilrs.next_event().is_some() {}
+        assert_eq!(gilrs.gamepad_added_events().collect::<Vec<_>>(), &[id2]);
+        assert_eq!(gilrs.gamepad_removed_events().collect::<Vec<_>>(), &[id]);
+
+        gilrs.inc();
+        assert_eq!(gilrs.gamepad_added_events().count(), 0);
+        assert_eq!(gilrs.gamepad_removed_events().count(), 0);
+    }
+
+    #[test]
+    fn strict_uuid_matching() {
+        let mapping = "030000005e0400008e02000014010000,Old Firmware,a:b0,";
+        let mut uuid = *Uuid::parse_str(&mapping[..32]).unwrap().as_bytes();
+        uuid[12] = 0x20;
 
-        self
+        for &strict in &[false, true] {
+            let builder = GilrsBuilder::new()
+                .add_included_mappings(false)
+                .add_env_mappings(false)
+                .add_mappings(mapping)
+                .strict_uuid_matching(strict);
+            let (mut gilrs, _) = gilrs_with_gamepad(builder);
+            let backend = gilrs.test_backend().unwrap();
+            let id = backend.add_device("Test Gamepad", uuid, &[nec::BTN_SOUTH], &[]);
+            gilrs.next_event();
+
+            let expected = if strict {
+                MappingSource::Driver
+            } else {
+                MappingSource::SdlMappings
+            };
+            assert_eq!(gilrs.gamepad(id).mapping_source(), expected);
+        }
     }
 
-    /// Adds SDL mappings.
-    pub fn add_mappings(mut self, mappings: &str) -> Self {
-        self.mappings.insert(mappings);
+    #[test]
+    fn poll_events_into() {
+        use crate::ev::{FfiEvent, FfiEventType};
 
-        self
-    }
+        let push_events = |gilrs: &mut Gilrs, id| {
+            let backend = gilrs.test_backend().unwrap();
+            backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+            backend.push(id, RawEventType::AxisValueChanged(1000, nec::AXIS_LSTICKX));
+            backend.push(id, RawEventType::ButtonReleased(nec::BTN_SOUTH));
+            backend.remove_device(id);
+        };
 
-    /// If true, will add SDL mappings from `SDL_GAMECONTROLLERCONFIG` environment variable.
-    /// Defaults to true.
-    pub fn add_env_mappings(mut self, env_mappings: bool) -> Self {
-        self.env_mappings = env_mappings;
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        push_events(&mut gilrs, id);
+        let mut expected = Vec::new();
+        while let Some(ev) = gilrs.next_event() {
+            expected.push(ev);
+        }
 
-        self
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        push_events(&mut gilrs, id);
+        let mut buf = vec![Event::new(id, EventType::Dropped)];
+        assert_eq!(gilrs.poll_events_into(&mut buf), expected.len());
+        assert_eq!(&buf[1..], &expected[..]);
+        assert!(!gilrs.gamepad(id).is_pressed(Button::South));
+        assert_eq!(gilrs.status(id), Status::Disconnected);
+        assert_eq!(gilrs.poll_events_into(&mut buf), 0);
+
+        let ffi = FfiEvent::from(buf[1].clone());
+        assert_eq!(ffi.event_type, FfiEventType::ButtonPressed);
+        assert_eq!(ffi.element, Button::South as u16);
+        assert_eq!(ffi.code, Code(nec::BTN_SOUTH).into_u32());
     }
 
-    /// If true, will add SDL mappings included from
-    /// https://github.com/gabomdq/SDL_GameControllerDB. Defaults to true.
-    pub fn add_included_mappings(mut self, included_mappings: bool) -> Self {
-        self.included_mappings = included_mappings;
+    #[test]
+    fn event_gamepad() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        let ev = gilrs.next_event().unwrap();
+        assert_eq!(ev.gamepad_id(), id);
+        assert_eq!(gilrs.gamepad_id_from_event(&ev), 0);
+        assert_eq!(ev.gamepad(&gilrs).map(|gp| gp.id()), Some(id));
+        assert!(ev.connected_gamepad(&gilrs).is_some());
 
-        self
+        gilrs.test_backend().unwrap().remove_device(id);
+        let ev = loop {
+            let ev = gilrs.next_event().unwrap();
+            if ev.event == EventType::Disconnected {
+                break ev;
+            }
+        };
+        assert!(ev.gamepad(&gilrs).is_some());
+        assert!(ev.connected_gamepad(&gilrs).is_none());
+
+        let ev = Event::new(GamepadId(10), EventType::Disconnected);
+        assert!(ev.gamepad(&gilrs).is_none());
+        assert!(ev.connected_gamepad(&gilrs).is_none());
     }
 
-    /// Sets values on which `ButtonPressed` and `ButtonReleased` events will be emitted. `build()`
-    /// will return error if `pressed ≤ released` or if one of values is outside [0.0, 1.0].
-    ///
-    /// Defaults to 0.75 for `pressed` and 0.65 for `released`.
-    pub fn set_axis_to_btn(mut self, pressed: f32, released: f32) -> Self {
-        self.axis_to_btn_pressed = pressed;
-        self.axis_to_btn_released = released;
+    #[test]
+    fn snapshot() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_EAST));
+        backend.push(
+            id,
+            RawEventType::AxisValueChanged(-16384, nec::AXIS_LSTICKY),
+        );
+        backend.push(id, RawEventType::AxisValueChanged(200, nec::AXIS_RT2));
+        while gilrs.next_event().is_some() {}
 
-        self
+        let snapshot = gilrs.snapshot();
+        assert_eq!(snapshot.gamepads.len(), 1);
+        let gp_snapshot = &snapshot.gamepads[0];
+        let gamepad = gilrs.gamepad(id);
+        assert_eq!(gp_snapshot.id, id);
+        assert_eq!(gp_snapshot.name, gamepad.name());
+        assert_eq!(gp_snapshot.status, Status::Connected);
+        assert_eq!(gp_snapshot.power_info, gamepad.power_info());
+
+        let buttons: Vec<_> = gp_snapshot.buttons.iter().map(|b| b.0).collect();
+        assert_eq!(
+            buttons,
+            [
+                Button::South,
+                Button::East,
+                Button::North,
+                Button::West,
+                Button::RightTrigger2
+            ]
+        );
+        for &(btn, pressed, value) in &gp_snapshot.buttons {
+            assert_eq!(pressed, gamepad.is_pressed(btn));
+            let expected = gamepad.button_data(btn).map(|d| d.value()).unwrap_or(0.0);
+            assert_eq!(value, expected);
+        }
+        assert!(gamepad.is_pressed(Button::East));
+
+        let axes: Vec<_> = gp_snapshot.axes.iter().map(|a| a.0).collect();
+        assert_eq!(axes, [Axis::LeftStickX, Axis::LeftStickY]);
+        for &(axis, value) in &gp_snapshot.axes {
+            assert_eq!(value, gamepad.value(axis));
+        }
+
+        assert_eq!(gilrs.snapshot(), snapshot);
+        gilrs.test_backend().unwrap().remove_device(id);
+        while gilrs.next_event().is_some() {}
+        assert_eq!(gilrs.snapshot(), InputSnapshot::default());
     }
 
-    /// Disable or enable automatic state updates. You should use this if you use custom filters;
-    /// in this case you have to update state manually anyway.
-    pub fn set_update_state(mut self, enabled: bool) -> Self {
-        self.update_state = enabled;
+    #[test]
+    fn pump() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        assert_eq!(gilrs.pump(), PumpResult::default());
 
-        self
+        // Small stick movement is dropped by deadzone filter.
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::AxisValueChanged(10, nec::AXIS_LSTICKX));
+        gilrs.insert_event(Event::new(id, EventType::Dropped));
+        assert!(!gilrs.pump().changed);
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        let next_id = backend.add_device("Test Gamepad", [1; 16], &[nec::BTN_SOUTH], &[]);
+        backend.remove_device(id);
+        let result = gilrs.pump();
+        assert!(result.changed);
+        assert_eq!(result.active_gamepads, [id, next_id]);
+        assert_eq!((result.connected, result.disconnected), (1, 1));
+        assert!(gilrs.gamepad(next_id).is_connected());
+
+        // Handler sees the same events as summary, without dropped ones.
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(next_id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        gilrs.insert_event(Event::new(next_id, EventType::Dropped));
+        let mut events = Vec::new();
+        let result = gilrs.pump_with(|ev| events.push((ev.id, ev.event)));
+        assert_eq!(result.active_gamepads, [next_id]);
+        assert_eq!(
+            events,
+            [
+                (
+                    next_id,
+                    EventType::ButtonPressed(Button::South, Code(nec::BTN_SOUTH))
+                ),
+                (
+                    next_id,
+                    EventType::ButtonChanged(Button::South, 1.0, Code(nec::BTN_SOUTH))
+                ),
+            ]
+        );
+
+        let mut calls = 0;
+        assert_eq!(gilrs.pump_with(|_| calls += 1), PumpResult::default());
+        assert_eq!(calls, 0);
     }
 
-    /// Creates `Gilrs`.
-    pub fn build(mut self) -> Result<Gilrs, Error> {
-        if self.included_mappings {
-            self.mappings.add_included_mappings();
+    #[test]
+    fn player_indicator() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        assert_eq!(gilrs.gamepad(id).player_indicator(), None);
+
+        gilrs.gamepad(id).set_player_indicator(Some(2)).unwrap();
+        assert_eq!(gilrs.gamepad(id).player_indicator(), Some(2));
+        for &player in &[0, 5] {
+            match gilrs.gamepad(id).set_player_indicator(Some(player)) {
+                Err(LedError::InvalidPlayer) => (),
+                res => panic!("unexpected result for {}: {:?}", player, res),
+            }
         }
+        assert_eq!(gilrs.gamepad(id).player_indicator(), Some(2));
 
-        if self.env_mappings {
-            self.mappings.add_env_mappings();
+        gilrs.test_backend().unwrap().remove_device(id);
+        while gilrs.next_event().is_some() {}
+        match gilrs.gamepad(id).set_player_indicator(None) {
+            Err(LedError::Disconnected) => (),
+            res => panic!("unexpected result: {:?}", res),
         }
 
-        debug!("Loaded {} mappings.", self.mappings.len());
+        gilrs.test_backend().unwrap().reconnect_device(id);
+        while gilrs.next_event().is_some() {}
+        assert_eq!(gilrs.gamepad(id).player_indicator(), None);
+    }
+
+    #[test]
+    fn spurious_reconnect() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let mapping = gilrs.gamepad(id).effective_mapping();
+        let backend = gilrs.test_backend().unwrap();
+        backend.set_ff_supported(id, true);
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        while gilrs.next_event().is_some() {}
+        gilrs.suppress_gamepad(id);
+        let effect = crate::ff::EffectBuilder::new()
+            .with_rumble(0x8000, 0)
+            .add_gamepad(&gilrs.gamepad(id))
+            .finish(&mut gilrs)
+            .unwrap();
+        effect.play().unwrap();
 
-        if self.axis_to_btn_pressed <= self.axis_to_btn_released
-            || self.axis_to_btn_pressed < 0.0
-            || self.axis_to_btn_pressed > 1.0
-            || self.axis_to_btn_released < 0.0
-            || self.axis_to_btn_released > 1.0
-        {
-            return Err(Error::InvalidAxisToBtn);
+        // Connected without Disconnected keeps state and settings.
+        gilrs
+            .test_backend()
+            .unwrap()
+            .push(id, RawEventType::Connected);
+        match gilrs.next_event().map(|ev| ev.event) {
+            Some(EventType::Connected(_)) => (),
+            ev => panic!("Expected Connected, got {:?}", ev),
         }
+        assert_eq!(gilrs.status(id), Status::Connected);
+        assert!(gilrs.gamepad(id).is_pressed(Button::South));
+        assert!(gilrs.gamepad(id).is_suppressed());
+        assert_eq!(gilrs.gamepad(id).effective_mapping(), mapping);
 
-        let mut is_dummy = false;
-        let inner = match gilrs_core::Gilrs::new() {
-            Ok(g) => g,
-            Err(PlatformError::NotImplemented(g)) => {
-                is_dummy = true;
+        // Effect created before reconnect can still be played on the gamepad.
+        assert!(gilrs.gamepad(id).is_ff_supported());
+        effect.stop().unwrap();
+        effect.set_gamepads(&[id], &gilrs).unwrap();
+        effect.add_gamepad(&gilrs.gamepad(id)).unwrap();
+        effect.play().unwrap();
 
-                g
+        gilrs.unsuppress_gamepad(id);
+        gilrs
+            .test_backend()
+            .unwrap()
+            .push(id, RawEventType::ButtonReleased(nec::BTN_SOUTH));
+        assert_eq!(
+            gilrs.next_event().map(|ev| ev.event),
+            Some(EventType::ButtonReleased(
+                Button::South,
+                Code(nec::BTN_SOUTH)
+            ))
+        );
+    }
+
+    #[test]
+    fn reconnect_grace() {
+        let grace = Duration::from_secs(3);
+
+        // Restored in time
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new().reconnect_grace(grace));
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.remove_device(id);
+        while let Some(ev) = gilrs.next_event() {
+            if ev.event == EventType::ConnectionLost {
+                break;
             }
-            Err(PlatformError::Other(e)) => return Err(Error::Other(e)),
-        };
+        }
+        assert!(!gilrs.gamepad(id).is_pressed(Button::South));
+        assert_eq!(gilrs.status(id), Status::Disconnected);
 
-        let mut gilrs = Gilrs {
-            inner,
-            next_id: 0,
-            tx: server::init(),
-            counter: 0,
-            mappings: self.mappings,
-            default_filters: self.default_filters,
-            events: VecDeque::new(),
-            axis_to_btn_pressed: self.axis_to_btn_pressed,
-            axis_to_btn_released: self.axis_to_btn_released,
-            update_state: self.update_state,
-            gamepads_data: Vec::new(),
-        };
-        gilrs.finish_gamepads_creation();
+        let backend = gilrs.test_backend().unwrap();
+        backend.advance_time(Duration::from_secs(2));
+        assert!(gilrs.next_event().is_none());
+        gilrs.test_backend().unwrap().reconnect_device(id);
+        let ev = gilrs.next_event().unwrap();
+        assert_eq!((ev.id, ev.event), (id, EventType::ConnectionRestored));
+        assert_eq!(gilrs.status(id), Status::Connected);
+        gilrs.test_backend().unwrap().advance_time(grace);
+        assert!(gilrs.next_event().is_none());
 
-        if is_dummy {
-            Err(Error::NotImplemented(gilrs))
-        } else {
-            Ok(gilrs)
+        // Restored too late, event was not polled during grace period
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new().reconnect_grace(grace));
+        let backend = gilrs.test_backend().unwrap();
+        backend.remove_device(id);
+        backend.advance_time(Duration::from_secs(4));
+        backend.reconnect_device(id);
+        let events: Vec<_> = std::iter::from_fn(|| gilrs.next_event())
+            .map(|ev| ev.event)
+            .collect();
+        assert_eq!(events[0], EventType::ConnectionLost);
+        assert_eq!(events[1], EventType::Disconnected);
+        match events[2] {
+            EventType::Connected(_) => (),
+            ref e => panic!("Unexpected event {:?}", e),
         }
-    }
-}
+        assert_eq!(events.len(), 3);
+        assert_eq!(gilrs.status(id), Status::Connected);
 
-impl Default for GilrsBuilder {
-    fn default() -> Self {
-        Self::new()
+        // Different device connects, original one is disconnected after grace period
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new().reconnect_grace(grace));
+        let backend = gilrs.test_backend().unwrap();
+        backend.remove_device(id);
+        let other = backend.add_device("Other Gamepad", [1; 16], &[nec::BTN_SOUTH], &[]);
+        assert_eq!(gilrs.next_event().unwrap().event, EventType::ConnectionLost);
+        let ev = gilrs.next_event().unwrap();
+        assert_eq!(ev.id, other);
+        assert!(gilrs.next_event().is_none());
+
+        gilrs.test_backend().unwrap().advance_time(grace);
+        let ev = gilrs.next_event().unwrap();
+        assert_eq!((ev.id, ev.event), (id, EventType::Disconnected));
+        assert_eq!(ev.time, std::time::UNIX_EPOCH + grace);
+        assert!(gilrs.next_event().is_none());
+        assert_eq!(gilrs.status(other), Status::Connected);
+
+        // Device with the same UUID, but different serial number reconnects in time
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new().reconnect_grace(grace));
+        let backend = gilrs.test_backend().unwrap();
+        backend.set_serial_number(id, Some("a4:ae:12:00:00:01"));
+        backend.remove_device(id);
+        assert_eq!(gilrs.next_event().unwrap().event, EventType::ConnectionLost);
+        let backend = gilrs.test_backend().unwrap();
+        backend.set_serial_number(id, Some("a4:ae:12:00:00:02"));
+        backend.reconnect_device(id);
+        assert_eq!(gilrs.next_event().unwrap().event, EventType::Disconnected);
+        match gilrs.next_event().unwrap().event {
+            EventType::Connected(_) => (),
+            e => panic!("Unexpected event {:?}", e),
+        }
+
+        // Same serial number restores connection
+        let backend = gilrs.test_backend().unwrap();
+        backend.remove_device(id);
+        backend.reconnect_device(id);
+        assert_eq!(gilrs.next_event().unwrap().event, EventType::ConnectionLost);
+        assert_eq!(
+            gilrs.next_event().unwrap().event,
+            EventType::ConnectionRestored
+        );
     }
-}
 
-/// Iterator over all connected gamepads.
-pub struct ConnectedGamepadsIterator<'a>(&'a Gilrs, usize);
+    #[test]
+    fn reconnect_grace_new_slot() {
+        let grace = Duration::from_secs(3);
+        let uuid = [1; 16];
+        let (mut gilrs, _) = gilrs_with_gamepad(GilrsBuilder::new().reconnect_grace(grace));
+        let backend = gilrs.test_backend().unwrap();
+        let first = backend.add_device("Test Gamepad", uuid, &[nec::BTN_SOUTH], &[]);
+        let second = backend.add_device("Test Gamepad", uuid, &[nec::BTN_SOUTH], &[]);
+        backend.set_serial_number(first, Some("a4:ae:12:00:00:01"));
+        backend.set_serial_number(second, Some("a4:ae:12:00:00:02"));
+        while gilrs.next_event().is_some() {}
 
-impl<'a> Iterator for ConnectedGamepadsIterator<'a> {
-    type Item = (GamepadId, Gamepad<'a>);
+        let backend = gilrs.test_backend().unwrap();
+        backend.remove_device(first);
+        backend.remove_device(second);
+        let events: Vec<_> = std::iter::from_fn(|| gilrs.next_event())
+            .map(|ev| (ev.id, ev.event))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (first, EventType::ConnectionLost),
+                (second, EventType::ConnectionLost)
+            ]
+        );
 
-    fn next(&mut self) -> Option<(GamepadId, Gamepad<'a>)> {
-        loop {
-            if self.1 == self.0.inner.last_gamepad_hint() {
-                return None;
-            }
+        // Backend reports both gamepads in new slots, second one reconnects first.
+        let backend = gilrs.test_backend().unwrap();
+        let new_second = backend.add_device("Test Gamepad", uuid, &[nec::BTN_SOUTH], &[]);
+        backend.set_serial_number(new_second, Some("a4:ae:12:00:00:02"));
+        let new_first = backend.add_device("Test Gamepad", uuid, &[nec::BTN_SOUTH], &[]);
+        backend.set_serial_number(new_first, Some("a4:ae:12:00:00:01"));
+        backend.push(new_first, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        let events: Vec<_> = std::iter::from_fn(|| gilrs.next_event())
+            .filter(|ev| !ev.is_dropped())
+            .map(|ev| (ev.id, ev.event))
+            .collect();
+        assert_eq!(events[0], (second, EventType::ConnectionRestored));
+        assert_eq!(events[1], (first, EventType::ConnectionRestored));
+        assert_eq!(events[2].0, first);
+        assert!(gilrs.gamepad(first).is_pressed(Button::South));
+        assert!(!gilrs.gamepad(second).is_pressed(Button::South));
 
-            if let Some(gp) = self.0.connected_gamepad(GamepadId(self.1)) {
-                let idx = self.1;
-                self.1 += 1;
-                return Some((GamepadId(idx), gp));
-            }
+        // Slots of new devices are not connected gamepads.
+        let ids: Vec<_> = gilrs.gamepads().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec![GamepadId(0), first, second]);
+        assert_eq!(gilrs.status(new_first), Status::NotObserved);
+        assert_eq!(gilrs.status(new_second), Status::NotObserved);
 
-            self.1 += 1;
-        }
+        // Gamepad keeps its ID when it loses connection again.
+        gilrs.test_backend().unwrap().remove_device(new_first);
+        let ev = gilrs.next_event().unwrap();
+        assert_eq!((ev.id, ev.event), (first, EventType::ConnectionLost));
+        gilrs.test_backend().unwrap().advance_time(grace);
+        let ev = gilrs.next_event().unwrap();
+        assert_eq!((ev.id, ev.event), (first, EventType::Disconnected));
+        assert!(gilrs.next_event().is_none());
     }
-}
 
-/// Represents handle to game controller.
-///
-/// Using this struct you can access cached gamepad state, information about gamepad such as name
-/// or UUID and manage force feedback effects.
-#[derive(Debug, Copy, Clone)]
-pub struct Gamepad<'a> {
-    data: &'a GamepadData,
-    inner: &'a gilrs_core::Gamepad,
-}
+    #[test]
+    fn set_mapping_db() {
+        let mapping = "030000005e0400008e02000014010000,New Mapping,a:b0,";
+        let uuid = *Uuid::parse_str(&mapping[..32]).unwrap().as_bytes();
+        let builder = GilrsBuilder::new()
+            .add_included_mappings(false)
+            .add_env_mappings(false);
+        let (mut gilrs, _) = gilrs_with_gamepad(builder);
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device("Test Gamepad", uuid, &[nec::BTN_SOUTH], &[]);
+        gilrs.next_event();
+        assert_eq!(gilrs.gamepad(id).mapping_source(), MappingSource::Driver);
 
-impl<'a> Gamepad<'a> {
-    /// Returns the mapping name if it exists otherwise returns the os provided name.
-    pub fn name(&self) -> &str {
-        if let Some(map_name) = self.map_name() {
-            map_name
-        } else {
-            self.os_name()
+        let mut db = MappingDb::new();
+        db.insert(mapping);
+        gilrs.set_mapping_db(db);
+        assert_eq!(gilrs.mapping_db().len(), 1);
+        let ev = gilrs.next_event().unwrap();
+        match ev.event {
+            EventType::Connected(ref info) => {
+                assert_eq!(ev.id, id);
+                assert_eq!(info.mapping_source, MappingSource::SdlMappings);
+            }
+            e => panic!("Unexpected event {:?}", e),
         }
+        assert_eq!(gilrs.gamepad(id).map_name(), Some("New Mapping"));
+        assert!(gilrs.next_event().is_none());
+
+        // Gamepads whose mapping didn't change are not reported.
+        let mut db = MappingDb::new();
+        db.insert(mapping);
+        gilrs.set_mapping_db(db);
+        assert!(gilrs.next_event().is_none());
+
+        gilrs.set_mapping_db(MappingDb::new());
+        assert_eq!(gilrs.next_event().unwrap().id, id);
+        assert_eq!(gilrs.gamepad(id).mapping_source(), MappingSource::Driver);
     }
 
-    /// if `mapping_source()` is `SdlMappings` returns the name of the mapping used by the gamepad.
-    /// Otherwise returns `None`.
-    pub fn map_name(&self) -> Option<&str> {
-        self.data.map_name()
+    #[test]
+    fn button_name_sdl() {
+        let (gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let gamepad = gilrs.gamepad(id);
+
+        assert_eq!(gamepad.button_name_sdl(Code(nec::BTN_SOUTH)), Some("a"));
+        assert_eq!(
+            gamepad.button_name_sdl(Code(nec::AXIS_LSTICKY)),
+            Some("lefty")
+        );
+        assert_eq!(
+            gamepad.button_name_sdl(Code(nec::AXIS_RT2)),
+            Some("righttrigger")
+        );
+        assert_eq!(gamepad.button_name_sdl(Code(nec::BTN_Z)), None);
+        assert_eq!(Axis::DPadX.sdl_name(), None);
     }
 
-    /// Returns the name of the gamepad supplied by the OS.
-    pub fn os_name(&self) -> &str {
-        self.inner.name()
+    #[test]
+    fn just_pressed() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let btn = Button::South;
+
+        // Held over multiple frames
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        while gilrs.next_event().is_some() {}
+        assert!(gilrs.just_pressed(id, btn));
+        assert!(!gilrs.just_released(id, btn));
+        gilrs.inc();
+        assert!(!gilrs.just_pressed(id, btn));
+        assert!(gilrs.gamepad(id).is_pressed(btn));
+
+        // Repeats are not presses
+        let code = Code(nec::BTN_SOUTH);
+        gilrs.update(&Event::new(id, EventType::ButtonRepeated(btn, code)));
+        assert!(!gilrs.just_pressed(id, btn));
+
+        // Released and pressed in the same frame
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonReleased(nec::BTN_SOUTH));
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.push(id, RawEventType::ButtonReleased(nec::BTN_SOUTH));
+        while gilrs.next_event().is_some() {}
+        let counter = gilrs.counter();
+        let gamepad = gilrs.gamepad(id);
+        assert!(gamepad.is_just_pressed(btn, counter));
+        assert!(gamepad.is_just_released(btn, counter));
+        assert!(!gamepad.is_pressed(btn));
+        assert!(!gamepad.is_just_pressed(Button::North, counter));
+
+        // Counter wraps around
+        gilrs.counter = 0x3FFF_FFFF_FFFF_FFFF;
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        while gilrs.next_event().is_some() {}
+        assert!(gilrs.just_pressed(id, btn));
+        gilrs.inc();
+        assert_eq!(gilrs.counter(), 0);
+        assert!(!gilrs.just_pressed(id, btn));
+        assert!(!gilrs.just_released(id, btn));
     }
 
-    /// Returns gamepad's UUID.
-    ///
-    /// It is recommended to process with the [UUID crate](https://crates.io/crates/uuid).
-    /// Use `Uuid::from_bytes` method to create a `Uuid` from the returned bytes.
-    pub fn uuid(&self) -> [u8; 16] {
-        self.inner.uuid()
+    #[test]
+    fn button_layout() {
+        const SWITCH_PRO: &str = "050000007e0500000920000001800000,Nintendo Switch Pro \
+                                  Controller,a:b0,b:b1,x:b2,y:b3,start:b4,platform:Linux,";
+        let uuid = *Uuid::parse_str(&SWITCH_PRO[..32]).unwrap().as_bytes();
+        let buttons = [
+            nec::BTN_EAST,
+            nec::BTN_SOUTH,
+            nec::BTN_NORTH,
+            nec::BTN_WEST,
+            nec::BTN_START,
+        ];
+
+        for &layout in &[ButtonLayout::ByLabel, ButtonLayout::ByPosition] {
+            let builder = GilrsBuilder::new()
+                .add_included_mappings(false)
+                .add_env_mappings(false)
+                .add_mappings(SWITCH_PRO)
+                .button_layout(layout);
+            let (mut gilrs, _) = gilrs_with_gamepad(builder);
+            let backend = gilrs.test_backend().unwrap();
+            let id = backend.add_device("Switch Pro", uuid, &buttons, &[]);
+            gilrs.next_event();
+            let backend = gilrs.test_backend().unwrap();
+            backend.push(id, RawEventType::ButtonPressed(nec::BTN_EAST));
+            backend.push(id, RawEventType::ButtonPressed(nec::BTN_NORTH));
+
+            let (south, west) = match layout {
+                ButtonLayout::ByLabel => (Button::South, Button::West),
+                ButtonLayout::ByPosition => (Button::East, Button::North),
+            };
+            let mut pressed = Vec::new();
+            while let Some(ev) = gilrs.next_event() {
+                if let EventType::ButtonPressed(btn, code) = ev.event {
+                    pressed.push((btn, code));
+                }
+            }
+            assert_eq!(
+                pressed,
+                [(south, Code(nec::BTN_EAST)), (west, Code(nec::BTN_NORTH))]
+            );
+
+            let gamepad = gilrs.gamepad(id);
+            assert!(gamepad.is_pressed(south));
+            assert!(gamepad.is_pressed(west));
+            assert_eq!(gamepad.button_code(south), Some(Code(nec::BTN_EAST)));
+            assert_eq!(
+                gamepad.button_code(Button::Start),
+                Some(Code(nec::BTN_START))
+            );
+            // SDL names always follow labels
+            assert_eq!(gamepad.button_name_sdl(Code(nec::BTN_EAST)), Some("a"));
+            assert_eq!(gamepad.button_name_sdl(Code(nec::BTN_WEST)), Some("y"));
+        }
+
+        // Other gamepads are not affected
+        let builder = GilrsBuilder::new().button_layout(ButtonLayout::ByPosition);
+        let (gilrs, id) = gilrs_with_gamepad(builder);
+        let gamepad = gilrs.gamepad(id);
+        assert_eq!(
+            gamepad.button_code(Button::South),
+            Some(Code(nec::BTN_SOUTH))
+        );
     }
 
-    /// Returns cached gamepad state.
-    pub fn state(&self) -> &GamepadState {
-        &self.data.state
+    #[test]
+    fn ignore_devices() {
+        let xbox = *Uuid::parse_str("030000005e0400008e02000020200000")
+            .unwrap()
+            .as_bytes();
+        let builder = || {
+            GilrsBuilder::new()
+                .respect_env_ignore_list(false)
+                .ignore_devices(vec![(0x045e, 0x028e)])
+        };
+
+        // Connected after `Gilrs` was created
+        let (mut gilrs, id) = gilrs_with_gamepad(builder());
+        let backend = gilrs.test_backend().unwrap();
+        let ignored = backend.add_device("Xbox 360", xbox, &[nec::BTN_SOUTH], &[]);
+        backend.push(ignored, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+
+        let mut ids = Vec::new();
+        while let Some(ev) = gilrs.next_event() {
+            ids.push(ev.id);
+        }
+        assert!(!ids.is_empty());
+        assert!(ids.iter().all(|&ev_id| ev_id == id));
+        assert!(gilrs.connected_gamepad(ignored).is_none());
+        assert_eq!(gilrs.gamepads().map(|(id, _)| id).collect::<Vec<_>>(), [id]);
+
+        // Connected before `Gilrs` was created
+        let mut backend = TestBackend::new();
+        let ignored = backend.add_device("Xbox 360", xbox, &[nec::BTN_SOUTH], &[]);
+        let mut gilrs = builder().build_with_backend(backend).unwrap();
+        assert_eq!(gilrs.gamepads().count(), 0);
+        assert_eq!(gilrs.gamepad(ignored).status(), Status::NotObserved);
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(ignored, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        assert_eq!(gilrs.next_event(), None);
     }
 
-    /// Returns true if gamepad is connected.
-    pub fn is_connected(&self) -> bool {
-        self.inner.is_connected()
+    #[test]
+    fn effective_mapping() {
+        const MAPPING: &str = "03000000de1000000100000001000000,Half mapped,a:b0,leftx:a0,\
+                               platform:Linux,";
+        let uuid = *Uuid::parse_str(&MAPPING[..32]).unwrap().as_bytes();
+        let stick = AxisInfo {
+            min: -32768,
+            max: 32767,
+            deadzone: None,
+        };
+
+        let builder = GilrsBuilder::new()
+            .add_included_mappings(false)
+            .add_env_mappings(false)
+            .add_mappings(MAPPING);
+        let (mut gilrs, _) = gilrs_with_gamepad(builder);
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device(
+            "Half mapped",
+            uuid,
+            &[nec::BTN_SOUTH, nec::BTN_EAST],
+            &[(nec::AXIS_LSTICKX, stick)],
+        );
+        gilrs.next_event();
+
+        let gamepad = gilrs.gamepad(id);
+        let mapping = gamepad.effective_mapping();
+        let south = gamepad.button_code(Button::South).unwrap();
+        assert_eq!(mapping.buttons.len(), 1);
+        assert_eq!(mapping.buttons[&Button::South], south);
+        assert_eq!(mapping.axes.len(), 1);
+        assert_eq!(mapping.axes[&Axis::LeftStickX], Code(nec::AXIS_LSTICKX));
+
+        let unmapped = mapping.unmapped_buttons(&gamepad);
+        assert_eq!(unmapped.len(), 1);
+        assert_ne!(unmapped[0], south);
     }
 
-    /// Examines cached gamepad state to check if given button is pressed. Panics if `btn` is
-    /// `Unknown`.
-    ///
-    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
-    /// directly on `State`, because this version have to check which `Code` is mapped to element of
-    /// gamepad.
-    pub fn is_pressed(&self, btn: Button) -> bool {
-        self.data.is_pressed(btn)
+    #[test]
+    fn mapping_data() {
+        const MAPPING: &str = "03000000de1000000200000001000000,Hat pad,a:b0,dpup:h0.1,leftx:a0,\
+                               platform:Linux,";
+        let uuid = *Uuid::parse_str(&MAPPING[..32]).unwrap().as_bytes();
+        let axis = AxisInfo {
+            min: -32768,
+            max: 32767,
+            deadzone: None,
+        };
+
+        let builder = GilrsBuilder::new()
+            .add_included_mappings(false)
+            .add_env_mappings(false)
+            .add_mappings(MAPPING);
+        let (mut gilrs, _) = gilrs_with_gamepad(builder);
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device(
+            "Hat pad",
+            uuid,
+            &[nec::BTN_SOUTH],
+            &[(nec::AXIS_LSTICKX, axis), (nec::AXIS_DPADY, axis)],
+        );
+        gilrs.next_event();
+
+        let gamepad = gilrs.gamepad(id);
+        let data = gamepad.mapping_data();
+        assert_eq!(
+            data.button(Button::South),
+            gamepad.button_code(Button::South)
+        );
+        assert_eq!(
+            data.button(Button::DPadUp),
+            gamepad.button_code(Button::DPadUp)
+        );
+        assert_eq!(data.button(Button::DPadDown), None);
+        assert_eq!(data.axis(Axis::LeftStickX), Some(Code(nec::AXIS_LSTICKX)));
     }
 
-    /// Examines cached gamepad state to check axis's value. Panics if `axis` is `Unknown`.
-    ///
-    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
-    /// directly on `State`, because this version have to check which `Code` is mapped to element of
-    /// gamepad.
-    pub fn value(&self, axis: Axis) -> f32 {
-        self.data.value(axis)
+    #[test]
+    fn half_axis_mapping() {
+        // Steering wheel with both pedals on one axis and paddles bound to right stick.
+        const MAPPING: &str = "030000006d0400001ac2000000000000,Wheel,a:b0,leftx:a0,\
+                               lefttrigger:-a1,righttrigger:+a1,-rightx:b1,+rightx:b2,\
+                               platform:Linux,";
+        let uuid = *Uuid::parse_str(&MAPPING[..32]).unwrap().as_bytes();
+        let axis = AxisInfo {
+            min: -32767,
+            max: 32767,
+            deadzone: None,
+        };
+
+        let builder = GilrsBuilder::new()
+            .add_included_mappings(false)
+            .add_env_mappings(false)
+            .add_mappings(MAPPING);
+        let (mut gilrs, _) = gilrs_with_gamepad(builder);
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device(
+            "Wheel",
+            uuid,
+            &[nec::BTN_SOUTH, nec::BTN_EAST, nec::BTN_NORTH],
+            &[(nec::AXIS_LSTICKX, axis), (nec::AXIS_LSTICKY, axis)],
+        );
+        gilrs.next_event();
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(
+            id,
+            RawEventType::AxisValueChanged(-32767, nec::AXIS_LSTICKY),
+        );
+        backend.push(id, RawEventType::AxisValueChanged(32767, nec::AXIS_LSTICKY));
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_EAST));
+        let mut events = Vec::new();
+        while let Some(ev) = gilrs.next_event() {
+            events.push(ev.event);
+        }
+
+        // Pedals have their own codes, because they share axis.
+        let gamepad = gilrs.gamepad(id);
+        let lt2 = gamepad.button_code(Button::LeftTrigger2).unwrap();
+        let rt2 = gamepad.button_code(Button::RightTrigger2).unwrap();
+        assert_ne!(lt2, rt2);
+        assert_eq!(gamepad.axis_or_btn_name(Code(nec::AXIS_LSTICKY)), None);
+        assert_eq!(
+            events,
+            [
+                EventType::ButtonPressed(Button::LeftTrigger2, lt2),
+                EventType::ButtonChanged(Button::LeftTrigger2, 1.0, lt2),
+                EventType::ButtonReleased(Button::LeftTrigger2, lt2),
+                EventType::ButtonChanged(Button::LeftTrigger2, 0.0, lt2),
+                EventType::ButtonPressed(Button::RightTrigger2, rt2),
+                EventType::ButtonChanged(Button::RightTrigger2, 1.0, rt2),
+                EventType::AxisChanged(Axis::RightStickX, -1.0, Code(nec::BTN_EAST)),
+            ]
+        );
+        assert!(!gamepad.is_pressed(Button::LeftTrigger2));
+        assert!(gamepad.is_pressed(Button::RightTrigger2));
+        assert_eq!(gamepad.value(Axis::LeftStickX), 0.0);
     }
 
-    /// Returns button state and when it changed.
-    ///
-    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
-    /// directly on `State`, because this version have to check which `Code` is mapped to element of
-    /// gamepad.
-    pub fn button_data(&self, btn: Button) -> Option<&ButtonData> {
-        self.data.button_data(btn)
+    #[test]
+    fn last_input_monotonic() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        assert_eq!(gilrs.gamepad(id).last_input_monotonic(), None);
+
+        let before = Instant::now();
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        while gilrs.next_event().is_some() {}
+        let first = gilrs.gamepad(id).last_input_monotonic().unwrap();
+        assert!(first >= before);
+
+        // Connection events are not input.
+        gilrs.test_backend().unwrap().remove_device(id);
+        while gilrs.next_event().is_some() {}
+        assert_eq!(gilrs.gamepad(id).last_input_monotonic(), Some(first));
     }
 
-    /// Returns axis state and when it changed.
-    ///
-    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
-    /// directly on `State`, because this version have to check which `Code` is mapped to element of
-    /// gamepad.
-    pub fn axis_data(&self, axis: Axis) -> Option<&AxisData> {
-        self.data.axis_data(axis)
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn multiple_instances() {
+        let first = match GilrsBuilder::new().build() {
+            Ok(gilrs) => gilrs,
+            // Backend can't be initialized on this machine, for example in containers without udev.
+            Err(Error::NotImplemented(_)) | Err(Error::Other(_)) => return,
+            Err(e) => panic!("{}", e),
+        };
+        let second = GilrsBuilder::new().build().unwrap();
+        assert_eq!(first.gamepads().count(), second.gamepads().count());
+        drop(first);
+        drop(second);
+
+        assert!(GilrsBuilder::new().build().is_ok());
     }
 
-    /// Returns device's power supply state. See [`PowerInfo`](enum.PowerInfo.html) for details.
-    pub fn power_info(&self) -> PowerInfo {
-        self.inner.power_info()
+    #[test]
+    fn error_source_chain() {
+        use std::error::Error as _;
+
+        let kind = PlatformErrorKind::new("open", 13);
+        let err = Error::Other(Box::new(kind));
+        assert_eq!(err.os_error(), Some(13));
+        assert_eq!(err.platform_error_kind(), Some(kind));
+        assert_eq!(err.to_string(), "open failed (os error 13)");
+        assert_eq!(
+            err.source().unwrap().downcast_ref::<PlatformErrorKind>(),
+            Some(&kind)
+        );
+
+        let io = Error::Other(Box::new(std::io::Error::from_raw_os_error(2)));
+        assert_eq!(
+            io.platform_error_kind(),
+            Some(PlatformErrorKind::new("io", 2))
+        );
+
+        assert_eq!(Error::InvalidAxisToBtn.os_error(), None);
+        assert!(Error::InvalidAxisToBtn.source().is_none());
     }
 
-    /// Returns source of gamepad mapping. Can be used to filter gamepads which do not provide
-    /// unified controller layout.
-    ///
-    /// ```
-    /// use gilrs::MappingSource;
-    /// # let mut gilrs = gilrs::Gilrs::new().unwrap();
-    ///
-    /// for (_, gamepad) in gilrs.gamepads().filter(
-    ///     |gp| gp.1.mapping_source() != MappingSource::None)
-    /// {
-    ///     println!("{} is ready to use!", gamepad.name());
-    /// }
-    /// ```
-    pub fn mapping_source(&self) -> MappingSource {
-        if self.data.mapping.is_default() {
-            // TODO: check if it's Driver or None
-            MappingSource::Driver
-        } else {
-            MappingSource::SdlMappings
+    #[test]
+    fn ff_tick_duration() {
+        for &ms in &[0, 101] {
+            let res = GilrsBuilder::new()
+                .ff_tick_duration(Duration::from_millis(ms))
+                .build_with_backend(TestBackend::new());
+            match res {
+                Err(Error::InvalidFfTickDuration) => (),
+                _ => panic!("{} ms tick should be rejected", ms),
+            }
         }
-    }
 
-    /// Returns true if force feedback is supported by device.
-    pub fn is_ff_supported(&self) -> bool {
-        self.inner.is_ff_supported()
-    }
+        let gilrs = GilrsBuilder::new()
+            .ff_tick_duration(Duration::from_millis(10))
+            .build_with_backend(TestBackend::new())
+            .unwrap();
+        assert_eq!(gilrs.ff_tick_duration(), Duration::from_millis(10));
 
-    /// Change gamepad position used by force feedback effects.
-    pub fn set_listener_position<Vec3: Into<[f32; 3]>>(
-        &self,
-        position: Vec3,
-    ) -> Result<(), FfError> {
-        if !self.is_connected() {
-            Err(FfError::Disconnected(self.id()))
-        } else if !self.is_ff_supported() {
-            Err(FfError::FfNotSupported(self.id()))
-        } else {
-            self.data.tx.send(Message::SetListenerPosition {
-                id: self.data.id.0,
-                position: position.into(),
-            })?;
-            Ok(())
+        let gilrs = GilrsBuilder::new()
+            .build_with_backend(TestBackend::new())
+            .unwrap();
+        assert_eq!(gilrs.ff_tick_duration(), ff::DEFAULT_TICK_DURATION);
+    }
+
+    #[test]
+    fn set_polling_rate() {
+        let mut gilrs = GilrsBuilder::new()
+            .build_with_backend(TestBackend::new())
+            .unwrap();
+        match gilrs.set_polling_rate(0) {
+            Err(PollingRateError::InvalidRate) => (),
+            res => panic!("unexpected result: {:?}", res),
+        }
+        match gilrs.set_polling_rate(60) {
+            Err(PollingRateError::NotSupported) => (),
+            res => panic!("unexpected result: {:?}", res),
         }
     }
 
-    /// Returns `AxisOrBtn` mapped to `Code`.
-    pub fn axis_or_btn_name(&self, ec: Code) -> Option<AxisOrBtn> {
-        self.data.axis_or_btn_name(ec)
+    #[test]
+    fn axis_info_override() {
+        let unsigned = AxisInfo::builder().range(0, 65535).build();
+        let narrow = AxisInfo::builder().range(-100, 100).deadzone(10).build();
+        let builder = GilrsBuilder::new()
+            .with_default_filters(false)
+            .with_axis_info_override([0; 16], Code(nec::AXIS_LSTICKX), unsigned)
+            .with_axis_info_override_all(Code(nec::AXIS_LSTICKX), narrow)
+            .with_axis_info_override_all(Code(nec::AXIS_LSTICKY), narrow);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+
+        let gamepad = gilrs.gamepad(id);
+        assert_eq!(gamepad.axis_info(Code(nec::AXIS_LSTICKX)), Some(unsigned));
+        assert_eq!(gamepad.axis_info(Code(nec::AXIS_LSTICKY)), Some(narrow));
+        assert_eq!(gamepad.axis_info(Code(nec::AXIS_RT)), None);
+        assert_eq!(gamepad.deadzone(Code(nec::AXIS_LSTICKY)), Some(0.1));
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.set_y_axis_reversed(false);
+        backend.push(id, RawEventType::AxisValueChanged(65535, nec::AXIS_LSTICKX));
+        backend.push(id, RawEventType::AxisValueChanged(-100, nec::AXIS_LSTICKY));
+        while gilrs.next_event().is_some() {}
+        assert_eq!(gilrs.gamepad(id).value(Axis::LeftStickX), 1.0);
+        assert_eq!(gilrs.gamepad(id).value(Axis::LeftStickY), -1.0);
+
+        // Overrides for specific UUID don't apply to other gamepads.
+        let stick = AxisInfo::builder().build();
+        let backend = gilrs.test_backend().unwrap();
+        let other =
+            backend.add_device("Other Gamepad", [1; 16], &[], &[(nec::AXIS_LSTICKX, stick)]);
+        while gilrs.next_event().is_some() {}
+        let gamepad = gilrs.gamepad(other);
+        assert_eq!(gamepad.axis_info(Code(nec::AXIS_LSTICKX)), Some(narrow));
     }
 
-    /// Returns `Code` associated with `btn`.
-    pub fn button_code(&self, btn: Button) -> Option<Code> {
-        self.data.button_code(btn)
+    #[test]
+    fn latency_stats() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        gilrs.next_event();
+        assert_eq!(gilrs.latency_stats(id), None);
+
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new().with_latency_metrics(true));
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.advance_time(Duration::from_millis(3));
+        backend.push(id, RawEventType::ButtonReleased(nec::BTN_SOUTH));
+        backend.advance_time(Duration::from_millis(1));
+        while gilrs.next_event().is_some() {}
+
+        // Pressed, ButtonChanged, Released and ButtonChanged.
+        let stats = gilrs.latency_stats(id).unwrap();
+        assert_eq!(stats.samples, 4);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.max, Duration::from_millis(4));
+        assert!(stats.queueing_only);
+
+        gilrs.reset_latency_stats();
+        assert_eq!(gilrs.latency_stats(id), None);
     }
 
-    /// Returns `Code` associated with `axis`.
-    pub fn axis_code(&self, axis: Axis) -> Option<Code> {
-        self.data.axis_code(axis)
+    #[cfg(feature = "latency-tracking")]
+    #[test]
+    fn event_latency_histogram() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        assert_eq!(gilrs.event_latency_percentile(0.5), None);
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.advance_time(Duration::from_millis(3));
+        backend.push(id, RawEventType::ButtonReleased(nec::BTN_SOUTH));
+        backend.advance_time(Duration::from_millis(1));
+        while gilrs.next_event().is_some() {}
+
+        // Recorded without per-gamepad latency metrics.
+        assert_eq!(gilrs.latency_stats(id), None);
+        let hist = gilrs.event_latency_histogram();
+        assert_eq!(hist.samples(), 4);
+        assert_eq!(hist.min(), Some(Duration::from_millis(1)));
+        assert_eq!(hist.max(), Some(Duration::from_millis(4)));
+        assert_eq!(
+            gilrs.event_latency_percentile(1.0),
+            Some(Duration::from_millis(4))
+        );
+
+        gilrs.reset_latency_histogram();
+        assert_eq!(gilrs.event_latency_histogram().samples(), 0);
+        assert_eq!(gilrs.event_latency_percentile(0.5), None);
     }
 
-    /// Returns area in which axis events should be ignored.
-    pub fn deadzone(&self, axis: Code) -> Option<f32> {
-        self.inner.axis_info(axis.0).map(|i| {
-            let range = i.max as f32 - i.min as f32;
+    #[test]
+    fn rumble_test() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        assert_eq!(
+            gilrs.gamepad(id).rumble_test(),
+            Err(FfError::FfNotSupported(id))
+        );
 
-            if range == 0.0 {
-                0.0
-            } else {
-                i.deadzone
-                    .map(|d| d as f32 / range * 2.0)
-                    .unwrap_or(DEFAULT_DEADZONE)
-            }
-        })
+        gilrs.test_backend().unwrap().remove_device(id);
+        gilrs.next_event();
+        assert_eq!(
+            gilrs.gamepad(id).rumble_test(),
+            Err(FfError::Disconnected(id))
+        );
     }
 
-    /// Returns ID of gamepad.
-    pub fn id(&self) -> GamepadId {
-        self.data.id
+    #[test]
+    fn ff_attenuation() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        gilrs.set_ff_attenuation(0.5);
+        assert_eq!(gilrs.gamepad(id).gamepad_position(), [0.0; 3]);
+
+        // Test gamepad doesn't support force feedback.
+        assert_eq!(
+            gilrs.set_gamepad_position(id, [1.0, 2.0, 3.0]),
+            Err(FfError::FfNotSupported(id))
+        );
+        assert_eq!(gilrs.gamepad(id).gamepad_position(), [0.0; 3]);
+
+        gilrs.test_backend().unwrap().remove_device(id);
+        while gilrs.next_event().is_some() {}
+        assert_eq!(
+            gilrs.set_gamepad_position(id, [1.0, 2.0, 3.0]),
+            Err(FfError::Disconnected(id))
+        );
     }
 
-    pub(crate) fn mapping(&self) -> &Mapping {
-        &self.data.mapping
+    #[test]
+    fn ff_battery_compensation() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        backend.set_power_info(id, PowerInfo::Discharging(10));
+
+        // Disabled by default.
+        assert_eq!(gilrs.next_event(), None);
+        assert_eq!(gilrs.gamepad(id).ff_battery_compensation(), 1.0);
+
+        gilrs.set_ff_battery_compensation(true);
+        let boosted = gilrs.gamepad(id).ff_battery_compensation();
+        assert!(boosted > 1.0);
+
+        // Power info is not read again until interval passes.
+        let backend = gilrs.test_backend().unwrap();
+        backend.set_power_info(id, PowerInfo::Charging(10));
+        assert_eq!(gilrs.next_event(), None);
+        assert_eq!(gilrs.gamepad(id).ff_battery_compensation(), boosted);
+
+        gilrs
+            .test_backend()
+            .unwrap()
+            .advance_time(FF_COMPENSATION_INTERVAL);
+        assert_eq!(gilrs.next_event(), None);
+        assert_eq!(gilrs.gamepad(id).ff_battery_compensation(), 1.0);
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.set_power_info(id, PowerInfo::Discharging(50));
+        gilrs.set_ff_battery_compensation_threshold(60);
+        assert!(gilrs.gamepad(id).ff_battery_compensation() > 1.0);
+
+        gilrs.set_ff_battery_compensation(false);
+        assert_eq!(gilrs.gamepad(id).ff_battery_compensation(), 1.0);
     }
-}
 
-#[derive(Debug)]
-struct GamepadData {
-    state: GamepadState,
-    mapping: Mapping,
-    tx: Sender<Message>,
-    id: GamepadId,
-}
+    #[test]
+    fn y_axis_convention() {
+        let axis = AxisInfo {
+            min: -32768,
+            max: 32767,
+            deadzone: None,
+        };
 
-impl GamepadData {
-    fn new(
-        id: GamepadId,
-        tx: Sender<Message>,
-        gamepad: &gilrs_core::Gamepad,
-        db: &MappingDb,
-    ) -> Self {
-        let mapping = db
-            .get(Uuid::from_bytes(gamepad.uuid()))
-            .and_then(|s| Mapping::parse_sdl_mapping(s, gamepad.buttons(), gamepad.axes()).ok())
-            .unwrap_or_else(|| Mapping::default(gamepad));
+        for &reversed in &[false, true] {
+            let mut backend = TestBackend::new();
+            backend.set_y_axis_reversed(reversed);
+            let id = backend.add_device(
+                "Test Gamepad",
+                [0; 16],
+                &[nec::BTN_SOUTH],
+                &[
+                    (nec::AXIS_LSTICKX, axis),
+                    (nec::AXIS_LSTICKY, axis),
+                    (nec::AXIS_DPADY, axis),
+                ],
+            );
+            let mut gilrs = GilrsBuilder::new()
+                .with_default_filters(false)
+                .add_included_mappings(false)
+                .add_env_mappings(false)
+                .build_with_backend(backend)
+                .unwrap();
+            gilrs.next_event();
 
-        if gamepad.is_ff_supported() && gamepad.is_connected() {
-            if let Some(device) = gamepad.ff_device() {
-                let _ = tx.send(Message::Open { id: id.0, device });
-            }
-        }
+            // Physical "up" is reported as negative value when Y axis is reversed.
+            let up = if reversed { -32768 } else { 32767 };
+            let backend = gilrs.test_backend().unwrap();
+            backend.push(id, RawEventType::AxisValueChanged(up, nec::AXIS_LSTICKY));
+            backend.push(id, RawEventType::AxisValueChanged(up, nec::AXIS_DPADY));
+            backend.push(id, RawEventType::AxisValueChanged(32767, nec::AXIS_LSTICKX));
+            while gilrs.next_event().is_some() {}
 
-        GamepadData {
-            state: GamepadState::new(),
-            mapping,
-            tx,
-            id,
+            let gamepad = gilrs.gamepad(id);
+            assert!(gamepad.value(Axis::LeftStickY) > 0.99);
+            assert!(gamepad.value(Axis::DPadY) > 0.99);
+            assert_eq!(gamepad.value(Axis::LeftStickX), 1.0);
+
+            let flipped = if reversed {
+                AxisDirection::Flipped
+            } else {
+                AxisDirection::Native
+            };
+            assert_eq!(gamepad.axis_direction(Axis::LeftStickY), flipped);
+            assert_eq!(gamepad.axis_direction(Axis::DPadY), flipped);
+            assert_eq!(
+                gamepad.axis_direction(Axis::LeftStickX),
+                AxisDirection::Native
+            );
         }
     }
 
-    /// if `mapping_source()` is `SdlMappings` returns the name of the mapping used by the gamepad.
-    /// Otherwise returns `None`.
-    ///
-    /// Warning: Mappings are set after event `Connected` is processed therefore this function will
-    /// always return `None` before first calls to `Gilrs::next_event()`.
-    pub fn map_name(&self) -> Option<&str> {
-        if self.mapping.is_default() {
-            None
-        } else {
-            Some(&self.mapping.name())
+    #[test]
+    fn idle_timeout() {
+        let builder = GilrsBuilder::new().with_default_filters(false);
+        let (mut gilrs, wired) = gilrs_with_gamepad(builder);
+        let backend = gilrs.test_backend().unwrap();
+        backend.set_power_info(wired, PowerInfo::Wired);
+        let wireless = backend.add_device("Wireless", [0; 16], &[nec::BTN_SOUTH], &[]);
+        backend.set_power_info(wireless, PowerInfo::Discharging(50));
+        while gilrs.next_event().is_some() {}
+
+        let timeout = Duration::from_secs(60);
+        gilrs.set_idle_timeout(Some(timeout));
+        // Idle time is counted from now, because there was no input yet.
+        while gilrs.next_event().is_some() {}
+
+        let step = |gilrs: &mut Gilrs, millis: u64, raw: Option<RawEventType>| {
+            let backend = gilrs.test_backend().unwrap();
+            backend.advance_time(Duration::from_millis(millis));
+            if let Some(raw) = raw {
+                backend.push(wireless, raw);
+            }
+
+            let mut events = Vec::new();
+            while let Some(ev) = gilrs.next_event() {
+                events.push((ev.id, ev.event));
+            }
+            events
+        };
+
+        // Input resets idle time
+        assert!(step(&mut gilrs, 59_000, None).is_empty());
+        let press = RawEventType::ButtonPressed(nec::BTN_SOUTH);
+        step(&mut gilrs, 0, Some(press));
+        assert!(step(&mut gilrs, 59_500, None).is_empty());
+
+        // Timeout passed, but gamepads are checked only once per second
+        assert!(step(&mut gilrs, 700, None).is_empty());
+        assert!(step(&mut gilrs, 0, None).is_empty());
+
+        // Backend disconnects gamepad after it's powered off
+        assert!(step(&mut gilrs, 300, None).is_empty());
+        assert_eq!(
+            step(&mut gilrs, 0, None),
+            [(wireless, EventType::Disconnected)]
+        );
+
+        // Wired gamepad stays connected
+        assert!(step(&mut gilrs, 3_600_000, None).is_empty());
+        assert!(gilrs.gamepad(wired).is_connected());
+
+        assert!(!gilrs.gamepad(wireless).is_connected());
+
+        match gilrs.gamepad(wired).power_off() {
+            Err(PowerOffError::NotSupported) => (),
+            r => panic!("Unexpected result {:?}", r),
+        }
+        match gilrs.gamepad(wireless).power_off() {
+            Err(PowerOffError::Disconnected) => (),
+            r => panic!("Unexpected result {:?}", r),
         }
     }
 
-    /// Examines cached gamepad state to check if given button is pressed. Panics if `btn` is
-    /// `Unknown`.
-    ///
-    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
-    /// directly on `State`, because this version have to check which `Code` is mapped to element of
-    /// gamepad.
-    pub fn is_pressed(&self, btn: Button) -> bool {
-        assert_ne!(btn, Button::Unknown);
+    #[test]
+    fn battery_percentage() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let cases = [
+            (PowerInfo::Discharging(40), Some(40), true),
+            (PowerInfo::Charging(75), Some(75), false),
+            (PowerInfo::Charged, Some(100), false),
+            (PowerInfo::Wired, None, false),
+            (PowerInfo::Unknown, None, false),
+        ];
 
-        self.button_code(btn)
-            .or_else(|| btn.to_nec())
-            .map(|nec| self.state.is_pressed(nec))
-            .unwrap_or(false)
+        for &(power_info, percentage, on_battery) in &cases {
+            gilrs.test_backend().unwrap().set_power_info(id, power_info);
+            let gamepad = gilrs.gamepad(id);
+            assert_eq!(gamepad.battery_percentage(), percentage);
+            assert_eq!(gamepad.is_on_battery(), on_battery);
+        }
     }
 
-    /// Examines cached gamepad state to check axis's value. Panics if `axis` is `Unknown`.
-    ///
-    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
-    /// directly on `State`, because this version have to check which `Code` is mapped to element of
-    /// gamepad.
-    pub fn value(&self, axis: Axis) -> f32 {
-        assert_ne!(axis, Axis::Unknown);
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn event_source() {
+        let hat = AxisInfo {
+            min: -1,
+            max: 1,
+            deadzone: None,
+        };
+        let (mut gilrs, _) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device(
+            "Dpad Gamepad",
+            [3; 16],
+            &[nec::BTN_SOUTH],
+            &[(nec::AXIS_DPADX, hat)],
+        );
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.push(id, RawEventType::AxisValueChanged(1, nec::AXIS_DPADX));
 
-        self.axis_code(axis)
-            .map(|nec| self.state.value(nec))
-            .unwrap_or(0.0)
-    }
+        let south = Code(nec::BTN_SOUTH);
+        let right = Code(nec::BTN_DPAD_RIGHT);
+        let mut next = || {
+            let ev = gilrs.next_event().unwrap();
+            (ev.event, ev.source)
+        };
+        assert_eq!(next().1, EventSource::Hardware);
+        assert_eq!(
+            next(),
+            (
+                EventType::ButtonPressed(Button::South, south),
+                EventSource::Hardware
+            )
+        );
+        assert_eq!(
+            next(),
+            (
+                EventType::ButtonChanged(Button::South, 1.0, south),
+                EventSource::Synthetic(FilterId::Gilrs)
+            )
+        );
+        assert_eq!(
+            next(),
+            (
+                EventType::ButtonPressed(Button::DPadRight, right),
+                EventSource::Synthetic(FilterId::AxisDpadToButton)
+            )
+        );
 
-    /// Returns button state and when it changed.
-    ///
-    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
-    /// directly on `State`, because this version have to check which `Code` is mapped to element of
-    /// gamepad.
-    pub fn button_data(&self, btn: Button) -> Option<&ButtonData> {
-        self.button_code(btn)
-            .and_then(|nec| self.state.button_data(nec))
-    }
+        let mut ev = Event::new(id, EventType::ButtonReleased(Button::South, south));
+        ev.source = EventSource::Hardware;
+        gilrs.insert_event(ev);
+        let ev = gilrs.next_event().unwrap();
+        assert_eq!(ev.source, EventSource::Inserted);
+        assert!(!ev.is_hardware());
 
-    /// Returns axis state and when it changed.
-    ///
-    /// If you know `Code` of the element that you want to examine, it's recommended to use methods
-    /// directly on `State`, because this version have to check which `Code` is mapped to element of
-    /// gamepad.
-    pub fn axis_data(&self, axis: Axis) -> Option<&AxisData> {
-        self.axis_code(axis)
-            .and_then(|nec| self.state.axis_data(nec))
+        let mut ev = Event::new(id, EventType::ButtonReleased(Button::South, south));
+        ev.source = EventSource::Synthetic(FilterId::Custom(7));
+        gilrs.insert_event(ev);
+        let ev = gilrs.next_event().unwrap();
+        assert_eq!(ev.source, EventSource::Synthetic(FilterId::Custom(7)));
     }
 
-    /// Returns `AxisOrBtn` mapped to `Code`.
-    pub fn axis_or_btn_name(&self, ec: Code) -> Option<AxisOrBtn> {
-        self.mapping.map(&ec.0)
-    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn auto_mapping() {
+        let stick = AxisInfo {
+            min: 0,
+            max: 255,
+            deadzone: None,
+        };
+        let hat = AxisInfo {
+            min: -1,
+            max: 1,
+            deadzone: None,
+        };
+        let axes = [
+            (nec::AXIS_LSTICKX, stick),
+            (nec::AXIS_LSTICKY, stick),
+            (nec::AXIS_RSTICKX, stick),
+            (nec::AXIS_RSTICKY, stick),
+            (nec::AXIS_DPADX, hat),
+            (nec::AXIS_DPADY, hat),
+        ];
+        // Generic USB gamepad without SDL mapping, whose driver follows gamepad specification.
+        let native_btns = [
+            nec::BTN_SOUTH,
+            nec::BTN_EAST,
+            nec::BTN_NORTH,
+            nec::BTN_WEST,
+            nec::BTN_LT,
+            nec::BTN_RT,
+            nec::BTN_SELECT,
+            nec::BTN_START,
+        ];
+        // Driver that doesn't report `BTN_GAMEPAD`, labels of its buttons are just a guess.
+        let guessed_btns = [
+            nec::BTN_C,
+            nec::BTN_Z,
+            nec::BTN_LT2,
+            nec::BTN_RT2,
+            nec::BTN_SELECT,
+            nec::BTN_START,
+        ];
+        let native_uuid = [7; 16];
+        let guessed_uuid = [8; 16];
 
-    /// Returns `Code` associated with `btn`.
-    pub fn button_code(&self, btn: Button) -> Option<Code> {
-        self.mapping.map_rev(&AxisOrBtn::Btn(btn)).map(Code)
-    }
+        let connect = |gilrs: &mut Gilrs, uuid, btns: &[gilrs_core::EvCode]| {
+            let backend = gilrs.test_backend().unwrap();
+            let id = backend.add_device("Generic USB Gamepad", uuid, btns, &axes);
+            match gilrs.next_event().unwrap().event {
+                EventType::Connected(info) => (id, info.mapping_source),
+                e => panic!("Unexpected event {:?}", e),
+            }
+        };
 
-    /// Returns `Code` associated with `axis`.
-    pub fn axis_code(&self, axis: Axis) -> Option<Code> {
-        self.mapping.map_rev(&AxisOrBtn::Axis(axis)).map(Code)
+        let (mut gilrs, _) = gilrs_with_gamepad(GilrsBuilder::new());
+        let (native, source) = connect(&mut gilrs, native_uuid, &native_btns);
+        assert_eq!(source, MappingSource::Driver);
+        let gamepad = gilrs.gamepad(native);
+        assert_eq!(gamepad.map_name(), None);
+        assert_eq!(gamepad.button_code(Button::West), Some(Code(nec::BTN_WEST)));
+        assert_eq!(gamepad.axis_code(Axis::DPadX), Some(Code(nec::AXIS_DPADX)));
+        // Elements that gamepad doesn't have are not mapped.
+        assert_eq!(gamepad.button_code(Button::Mode), None);
+
+        let (guessed, source) = connect(&mut gilrs, guessed_uuid, &guessed_btns);
+        assert_eq!(source, MappingSource::Heuristic);
+        let gamepad = gilrs.gamepad(guessed);
+        assert_eq!(gamepad.map_name(), None);
+        assert_eq!(
+            gamepad.button_code(Button::Start),
+            Some(Code(nec::BTN_START))
+        );
+        assert_eq!(
+            gamepad.axis_code(Axis::RightStickY),
+            Some(Code(nec::AXIS_RSTICKY))
+        );
+
+        gilrs
+            .test_backend()
+            .unwrap()
+            .push(guessed, RawEventType::ButtonPressed(nec::BTN_START));
+        assert_eq!(
+            gilrs.next_event().unwrap().event,
+            EventType::ButtonPressed(Button::Start, Code(nec::BTN_START))
+        );
+
+        // Only guessed mapping is disabled.
+        let (mut gilrs, _) = gilrs_with_gamepad(GilrsBuilder::new().enable_auto_mapping(false));
+        let (native, source) = connect(&mut gilrs, native_uuid, &native_btns);
+        assert_eq!(source, MappingSource::Driver);
+        assert_eq!(
+            gilrs.gamepad(native).button_code(Button::West),
+            Some(Code(nec::BTN_WEST))
+        );
+        let (guessed, source) = connect(&mut gilrs, guessed_uuid, &guessed_btns);
+        assert_eq!(source, MappingSource::None);
+        assert_eq!(gilrs.gamepad(guessed).map_name(), None);
+        gilrs
+            .test_backend()
+            .unwrap()
+            .push(guessed, RawEventType::ButtonPressed(nec::BTN_START));
+        assert_eq!(
+            gilrs.next_event().unwrap().event,
+            EventType::ButtonPressed(Button::Unknown, Code(nec::BTN_START))
+        );
+
+        // SDL mappings are still used, even if they are intentionally empty.
+        let mapping = "08080808080808080808080808080808,Empty Mapping,";
+        let (mut gilrs, _) = gilrs_with_gamepad(
+            GilrsBuilder::new()
+                .enable_auto_mapping(false)
+                .add_mappings(mapping),
+        );
+        let (guessed, source) = connect(&mut gilrs, guessed_uuid, &guessed_btns);
+        assert_eq!(source, MappingSource::SdlMappings);
+        assert_eq!(gilrs.gamepad(guessed).map_name(), Some("Empty Mapping"));
+        assert_eq!(gilrs.gamepad(guessed).button_code(Button::Start), None);
     }
-}
 
-/// Source of gamepad mappings.
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum MappingSource {
-    /// Gamepad uses SDL mappings.
-    SdlMappings,
-    /// Gamepad does not use any mappings but driver should provide unified controller layout.
-    Driver,
-    /// Gamepad does not use any mappings and most gamepad events will probably be `Button::Unknown`
-    /// or `Axis::Unknown`
-    None,
-}
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn heuristic_mapping() {
+        use gilrs_core::EvCode;
 
-/// Gamepad ID.
-///
-/// It's not possible to create instance of this type directly, but you can obtain one from Gamepad
-/// handle or any event. ID is valid for entire lifetime of `Gilrs` context.
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
-#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
-pub struct GamepadId(pub(crate) usize);
+        // Capabilities of generic USB gamepad (DragonRise) without BTN_GAMEPAD: BTN_TRIGGER,
+        // BTN_THUMB, BTN_THUMB2, BTN_TOP, BTN_TOP2, BTN_PINKIE and BTN_BASE..BTN_BASE6.
+        let btns = (0x120..=0x12b)
+            .map(|code| EvCode::from_raw(0x01, code).unwrap())
+            .collect::<Vec<_>>();
+        let abs = |code| EvCode::from_raw(0x03, code).unwrap();
+        let info = AxisInfo {
+            min: 0,
+            max: 255,
+            deadzone: None,
+        };
+        let hat = AxisInfo {
+            min: -1,
+            max: 1,
+            deadzone: None,
+        };
+        // ABS_X, ABS_Y, ABS_Z, ABS_RZ, ABS_HAT0X, ABS_HAT0Y
+        let axes = [
+            (abs(0x00), info),
+            (abs(0x01), info),
+            (abs(0x02), info),
+            (abs(0x05), info),
+            (abs(0x10), hat),
+            (abs(0x11), hat),
+        ];
 
-impl Into<usize> for GamepadId {
-    fn into(self) -> usize {
-        self.0
+        let (mut gilrs, _) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device("Generic USB Joystick", [9; 16], &btns, &axes);
+        match gilrs.next_event().unwrap().event {
+            EventType::Connected(info) => assert_eq!(info.mapping_source, MappingSource::Heuristic),
+            e => panic!("Unexpected event {:?}", e),
+        }
+
+        let gamepad = gilrs.gamepad(id);
+        let expected = [
+            Button::South,
+            Button::East,
+            Button::West,
+            Button::North,
+            Button::LeftTrigger,
+            Button::RightTrigger,
+            // Triggers are already bound to ABS_Z and ABS_RZ.
+            Button::Select,
+            Button::Start,
+            Button::LeftThumb,
+            Button::RightThumb,
+            Button::Mode,
+        ];
+        for (&btn, &code) in expected.iter().zip(&btns) {
+            assert_eq!(gamepad.button_code(btn), Some(Code(code)), "{:?}", btn);
+        }
+        assert_eq!(gamepad.axis_or_btn_name(Code(btns[11])), None);
+        assert_eq!(
+            gamepad.button_code(Button::LeftTrigger2),
+            Some(Code(abs(0x02)))
+        );
+        assert_eq!(gamepad.axis_code(Axis::LeftStickX), Some(Code(abs(0x00))));
+        assert_eq!(gamepad.axis_code(Axis::LeftStickY), Some(Code(abs(0x01))));
+        assert_eq!(gamepad.axis_code(Axis::DPadX), Some(Code(abs(0x10))));
+        assert_eq!(gamepad.axis_code(Axis::DPadY), Some(Code(abs(0x11))));
+        assert_eq!(gamepad.axis_code(Axis::LeftZ), None);
+        assert_eq!(gamepad.axis_code(Axis::RightZ), None);
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(btns[7]));
+        backend.push(id, RawEventType::AxisValueChanged(255, abs(0x05)));
+        let mut events = Vec::new();
+        while let Some(ev) = gilrs.next_event() {
+            events.push(ev.event);
+        }
+        assert_eq!(
+            events[0],
+            EventType::ButtonPressed(Button::Start, Code(btns[7]))
+        );
+        assert!(events.contains(&EventType::ButtonPressed(
+            Button::RightTrigger2,
+            Code(abs(0x05))
+        )));
     }
-}
 
-impl Display for GamepadId {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+    #[test]
+    fn process_events() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_EAST));
+        backend.push(id, RawEventType::ButtonReleased(nec::BTN_SOUTH));
+
+        let mut events = Vec::new();
+        gilrs.process_events(|ev| match ev.event {
+            EventType::ButtonChanged(..) => (),
+            event => events.push(event),
+        });
+        assert_eq!(
+            events,
+            [
+                EventType::ButtonPressed(Button::South, Code(nec::BTN_SOUTH)),
+                EventType::ButtonPressed(Button::East, Code(nec::BTN_EAST)),
+                EventType::ButtonReleased(Button::South, Code(nec::BTN_SOUTH)),
+            ]
+        );
+        assert!(gilrs.next_event().is_none());
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonReleased(nec::BTN_EAST));
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_NORTH));
+        let mut pressed = Vec::new();
+        gilrs.process_events_with_state(|ev, gilrs| {
+            if let EventType::ButtonChanged(..) = ev.event {
+                return;
+            }
+            let gamepad = gilrs.gamepad(ev.id);
+            pressed.push((
+                gamepad.is_pressed(Button::East),
+                gamepad.is_pressed(Button::North),
+            ));
+        });
+        assert_eq!(pressed, [(false, false), (false, true)]);
     }
-}
 
-fn axis_value(info: &AxisInfo, val: i32, axis: Axis) -> f32 {
-    let mut range = info.max as f32 - info.min as f32;
-    let mut val = val as f32 - info.min as f32;
+    #[test]
+    fn update_hook() {
+        use std::sync::{atomic::Ordering, Mutex};
 
-    if (info.max - info.min) % 2 == 1 {
-        // Add one to range and val, so value at ceter (like 127/255) will be mapped 0.0
-        range += 1.0;
-        val += 1.0;
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen2 = seen.clone();
+        let count = Arc::new(AtomicUsize::new(0));
+        let count2 = count.clone();
+        let builder = GilrsBuilder::new()
+            .with_update_hook(Box::new(move |ev, state| match ev.event {
+                EventType::ButtonPressed(_, nec) | EventType::ButtonReleased(_, nec) => {
+                    seen2.lock().unwrap().push(state.is_pressed(nec))
+                }
+                _ => (),
+            }))
+            .with_update_hook(Box::new(move |_, _| {
+                count2.fetch_add(1, Ordering::Relaxed);
+            }));
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        // `Connected` event was already handled.
+        assert_eq!(count.swap(0, Ordering::Relaxed), 1);
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.push(id, RawEventType::ButtonReleased(nec::BTN_SOUTH));
+
+        let mut events = 0;
+        while gilrs.next_event().is_some() {
+            events += 1;
+        }
+        assert_eq!(*seen.lock().unwrap(), [true, false]);
+        assert_eq!(count.load(Ordering::Relaxed), events);
     }
 
-    val = val / range * 2.0 - 1.0;
+    #[test]
+    fn release_all() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.push(
+            id,
+            RawEventType::AxisValueChanged(-32768, nec::AXIS_LSTICKX),
+        );
+        backend.push(id, RawEventType::AxisValueChanged(255, nec::AXIS_RT2));
+        while gilrs.next_event().is_some() {}
 
-    if gilrs_core::IS_Y_AXIS_REVERSED
-        && (axis == Axis::LeftStickY || axis == Axis::RightStickY || axis == Axis::DPadY)
-        && val != 0.0
-    {
-        val = -val;
+        gilrs.release_all(Some(id));
+        let gamepad = gilrs.gamepad(id);
+        let state = gamepad.state();
+        assert!(state.buttons().all(|(_, data)| !data.is_pressed()));
+        assert!(state.buttons().all(|(_, data)| data.value() == 0.0));
+        assert!(state.axes().all(|(_, data)| data.value() == 0.0));
+
+        let mut events = Vec::new();
+        while let Some(ev) = gilrs.next_event() {
+            events.push(ev.event);
+        }
+        let south = Code(nec::BTN_SOUTH);
+        let rt2 = Code(nec::AXIS_RT2);
+        assert_eq!(events.len(), 5);
+        assert!(events.contains(&EventType::ButtonReleased(Button::South, south)));
+        assert!(events.contains(&EventType::ButtonChanged(Button::South, 0.0, south)));
+        assert!(events.contains(&EventType::ButtonReleased(Button::RightTrigger2, rt2)));
+        assert!(events.contains(&EventType::ButtonChanged(Button::RightTrigger2, 0.0, rt2)));
+        assert!(events.contains(&EventType::AxisChanged(
+            Axis::LeftStickX,
+            0.0,
+            Code(nec::AXIS_LSTICKX)
+        )));
+
+        gilrs.release_all(None);
+        assert!(gilrs.next_event().is_none());
     }
 
-    utils::clamp(val, -1.0, 1.0)
-}
+    #[test]
+    fn update_from_state() {
+        fn drain(gilrs: &mut Gilrs) -> Vec<(EventType, SystemTime)> {
+            let mut events = Vec::new();
+            while let Some(ev) = gilrs.next_event() {
+                events.push((ev.event, ev.time));
+            }
+            events
+        }
 
-fn btn_value(info: &AxisInfo, val: i32) -> f32 {
-    let range = (info.max - info.min) as f32;
-    let mut val = (val - info.min) as f32;
-    val /= range;
+        let (mut client, client_id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let neutral = client.gamepad(client_id).state().clone();
+        let backend = client.test_backend().unwrap();
+        backend.push(client_id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.push(
+            client_id,
+            RawEventType::AxisValueChanged(-32768, nec::AXIS_LSTICKX),
+        );
+        while client.next_event().is_some() {}
+        let pressed = client.gamepad(client_id).state().clone();
+        let stick = client.gamepad(client_id).value(Axis::LeftStickX);
 
-    utils::clamp(val, 0.0, 1.0)
-}
+        let (mut server, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let time = std::time::UNIX_EPOCH + Duration::from_secs(100);
+        server.update_from_state(id, pressed, 42, time);
+        assert_eq!(server.counter(), 42);
+        assert!(server.gamepad(id).is_pressed(Button::South));
+        assert_eq!(server.gamepad(id).value(Axis::LeftStickX), stick);
 
-/// Error type which can be returned when creating `Gilrs`.
-#[derive(Debug)]
-pub enum Error {
-    /// Gilrs does not support current platform, but you can use dummy context from this error if
-    /// gamepad input is not essential.
-    NotImplemented(Gilrs),
-    /// Either `pressed ≤ released` or one of values is outside [0.0, 1.0] range.
-    InvalidAxisToBtn,
-    /// Platform specific error.
-    Other(Box<dyn error::Error + Send + Sync + 'static>),
-}
+        let south = Code(nec::BTN_SOUTH);
+        let events = drain(&mut server);
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|&(_, t)| t == time));
+        let events: Vec<_> = events.into_iter().map(|(ev, _)| ev).collect();
+        assert!(events.contains(&EventType::ButtonPressed(Button::South, south)));
+        assert!(events.contains(&EventType::ButtonChanged(Button::South, 1.0, south)));
+        assert!(events.contains(&EventType::AxisChanged(
+            Axis::LeftStickX,
+            stick,
+            Code(nec::AXIS_LSTICKX)
+        )));
 
-impl Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Error::NotImplemented(_) => f.write_str("Gilrs does not support current platform."),
-            Error::InvalidAxisToBtn => f.write_str(
-                "Either `pressed ≤ released` or one of values is outside [0.0, 1.0] range.",
-            ),
-            Error::Other(ref e) => e.fmt(f),
+        // Same state again doesn't generate events.
+        let state = server.gamepad(id).state().clone();
+        server.update_from_state(id, state, 43, time);
+        assert!(server.next_event().is_none());
+
+        server.update_from_state(id, neutral, 44, time);
+        let events: Vec<_> = drain(&mut server).into_iter().map(|(ev, _)| ev).collect();
+        assert_eq!(events.len(), 3);
+        assert!(events.contains(&EventType::ButtonReleased(Button::South, south)));
+        assert!(!server.gamepad(id).is_pressed(Button::South));
+    }
+
+    #[test]
+    fn unchanged_axes() {
+        fn axis_events(gilrs: &mut Gilrs, id: GamepadId, values: &[i32]) -> Vec<f32> {
+            let backend = gilrs.test_backend().unwrap();
+            for &val in values {
+                backend.push(id, RawEventType::AxisValueChanged(val, nec::AXIS_LSTICKX));
+            }
+
+            let mut events = Vec::new();
+            while let Some(ev) = gilrs.next_event() {
+                if let EventType::AxisChanged(Axis::LeftStickX, val, _) = ev.event {
+                    events.push(val);
+                }
+            }
+            events
         }
+
+        // Default filters already drop some of these events, test without them.
+        let builder = GilrsBuilder::new().with_default_filters(false);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        assert_eq!(axis_events(&mut gilrs, id, &[16384, 16384, 16384]).len(), 1);
+
+        // First event after `release_all()` is returned even if value didn't change. Axis has
+        // symmetric range, so 0 gives exactly 0.0.
+        let axis = AxisInfo {
+            min: -128,
+            max: 128,
+            deadzone: None,
+        };
+        let backend = gilrs.test_backend().unwrap();
+        let id = backend.add_device("Test Gamepad", [0; 16], &[], &[(nec::AXIS_LSTICKX, axis)]);
+        gilrs.next_event();
+        assert_eq!(axis_events(&mut gilrs, id, &[64, 64]), [0.5]);
+        gilrs.release_all(Some(id));
+        assert_eq!(axis_events(&mut gilrs, id, &[]), [0.0]);
+        assert_eq!(axis_events(&mut gilrs, id, &[0, 0]), [0.0]);
+
+        let builder = GilrsBuilder::new()
+            .with_default_filters(false)
+            .suppress_unchanged_axes(None);
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        assert_eq!(axis_events(&mut gilrs, id, &[16384, 16384, 16384]).len(), 3);
+
+        let builder = GilrsBuilder::new()
+            .with_default_filters(false)
+            .suppress_unchanged_axes(Some(0.1));
+        let (mut gilrs, id) = gilrs_with_gamepad(builder);
+        assert_eq!(axis_events(&mut gilrs, id, &[16384, 17000, 20000]).len(), 2);
     }
-}
 
-impl error::Error for Error {
-    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        match self {
-            Error::Other(e) => Some(e.as_ref()),
-            _ => None,
+    #[test]
+    fn resync() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        assert!(!gilrs.resync(id));
+
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_EAST));
+        while gilrs.next_event().is_some() {}
+
+        let raw = RawState {
+            buttons: vec![
+                (nec::BTN_SOUTH, true),
+                (nec::BTN_EAST, false),
+                (nec::BTN_NORTH, true),
+            ],
+            axes: vec![(nec::AXIS_LSTICKX, 32767), (nec::AXIS_RT2, 0)],
+        };
+        assert_eq!(
+            gilrs.resync_diff(id, &raw),
+            [
+                RawEventType::ButtonReleased(nec::BTN_EAST),
+                RawEventType::ButtonPressed(nec::BTN_NORTH),
+                RawEventType::AxisValueChanged(32767, nec::AXIS_LSTICKX),
+            ]
+        );
+
+        // Buttons held while events were not read are pressed again.
+        gilrs.release_all(Some(id));
+        while gilrs.next_event().is_some() {}
+        let backend = gilrs.test_backend().unwrap();
+        backend.set_device_state(id, Some(raw));
+        assert!(gilrs.resync(id));
+
+        let mut pressed = Vec::new();
+        while let Some(ev) = gilrs.next_event() {
+            if let EventType::ButtonPressed(btn, _) = ev.event {
+                pressed.push(btn);
+            }
         }
+        assert_eq!(pressed, [Button::South, Button::North]);
+        assert!(gilrs.gamepad(id).is_pressed(Button::North));
+        assert_eq!(gilrs.gamepad(id).value(Axis::LeftStickX), 1.0);
     }
 }
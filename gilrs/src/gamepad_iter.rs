@@ -0,0 +1,205 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::gamepad::{Gamepad, GamepadId, MappingSource};
+
+/// Adapters for iterators over gamepads, like the one returned by
+/// [`Gilrs::gamepads()`](struct.Gilrs.html#method.gamepads).
+///
+/// Adapters are named types, so filtered iterator can be stored or returned from function without
+/// boxing.
+///
+/// ```
+/// use gilrs::{GamepadIteratorExt, Gilrs, MappingSource};
+///
+/// # let gilrs = Gilrs::new().unwrap();
+/// let assigned = Vec::new();
+/// let next_player = gilrs
+///     .gamepads()
+///     .with_ff()
+///     .with_mapping_source(MappingSource::SdlMappings)
+///     .excluding(&assigned)
+///     .next();
+/// ```
+pub trait GamepadIteratorExt<'a>: Iterator<Item = (GamepadId, Gamepad<'a>)> + Sized {
+    /// Skips gamepads that don't support force feedback.
+    fn with_ff(self) -> WithFf<Self> {
+        WithFf(self)
+    }
+
+    /// Skips gamepads with mapping source other than `source`.
+    fn with_mapping_source(self, source: MappingSource) -> WithMappingSource<Self> {
+        WithMappingSource(self, source)
+    }
+
+    /// Skips gamepads with ID in `ids`.
+    fn excluding(self, ids: &[GamepadId]) -> Excluding<'_, Self> {
+        Excluding(self, ids)
+    }
+}
+
+impl<'a, I> GamepadIteratorExt<'a> for I where I: Iterator<Item = (GamepadId, Gamepad<'a>)> {}
+
+/// Iterator over gamepads that support force feedback. See
+/// [`GamepadIteratorExt::with_ff()`](trait.GamepadIteratorExt.html#method.with_ff).
+#[derive(Debug, Clone)]
+pub struct WithFf<I>(I);
+
+impl<'a, I> Iterator for WithFf<I>
+where
+    I: Iterator<Item = (GamepadId, Gamepad<'a>)>,
+{
+    type Item = (GamepadId, Gamepad<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.find(|(_, gamepad)| gamepad.is_ff_supported())
+    }
+}
+
+/// Iterator over gamepads with given mapping source. See
+/// [`GamepadIteratorExt::with_mapping_source()`](trait.GamepadIteratorExt.html#method.with_mapping_source).
+#[derive(Debug, Clone)]
+pub struct WithMappingSource<I>(I, MappingSource);
+
+impl<'a, I> Iterator for WithMappingSource<I>
+where
+    I: Iterator<Item = (GamepadId, Gamepad<'a>)>,
+{
+    type Item = (GamepadId, Gamepad<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let source = self.1;
+        self.0
+            .find(|(_, gamepad)| gamepad.mapping_source() == source)
+    }
+}
+
+/// Iterator over gamepads that are not in given list. See
+/// [`GamepadIteratorExt::excluding()`](trait.GamepadIteratorExt.html#method.excluding).
+#[derive(Debug, Clone)]
+pub struct Excluding<'b, I>(I, &'b [GamepadId]);
+
+impl<'a, 'b, I> Iterator for Excluding<'b, I>
+where
+    I: Iterator<Item = (GamepadId, Gamepad<'a>)>,
+{
+    type Item = (GamepadId, Gamepad<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ids = self.1;
+        self.0.find(|(id, _)| !ids.contains(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestBackend;
+    use crate::{Gilrs, GilrsBuilder};
+    use gilrs_core::native_ev_codes as nec;
+    use uuid::Uuid;
+
+    const MAPPING: &str = "030000005e0400008e02000014010000,Mapped,a:b0,";
+
+    struct Population {
+        gilrs: Gilrs,
+        mapped_ff: GamepadId,
+        mapped: GamepadId,
+        unmapped_ff: GamepadId,
+        unmapped: GamepadId,
+        disconnected_ff: GamepadId,
+    }
+
+    fn population() -> Population {
+        let uuid = *Uuid::parse_str(&MAPPING[..32]).unwrap().as_bytes();
+        let buttons = [nec::BTN_SOUTH];
+
+        let mut backend = TestBackend::new();
+        let unmapped = backend.add_device("Unmapped", [0; 16], &buttons, &[]);
+        let mapped_ff = backend.add_device("Mapped FF", uuid, &buttons, &[]);
+        let disconnected_ff = backend.add_device("Disconnected FF", uuid, &buttons, &[]);
+        let unmapped_ff = backend.add_device("Unmapped FF", [0; 16], &buttons, &[]);
+        let mapped = backend.add_device("Mapped", uuid, &buttons, &[]);
+        for &id in &[mapped_ff, unmapped_ff, disconnected_ff] {
+            backend.set_ff_supported(id, true);
+        }
+        backend.remove_device(disconnected_ff);
+
+        let mut gilrs = GilrsBuilder::new()
+            .add_included_mappings(false)
+            .add_env_mappings(false)
+            .add_mappings(MAPPING)
+            .build_with_backend(backend)
+            .unwrap();
+        while gilrs.next_event().is_some() {}
+
+        Population {
+            gilrs,
+            mapped_ff,
+            mapped,
+            unmapped_ff,
+            unmapped,
+            disconnected_ff,
+        }
+    }
+
+    fn ids<'a>(iter: impl Iterator<Item = (GamepadId, Gamepad<'a>)>) -> Vec<GamepadId> {
+        iter.map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn filters() {
+        let p = population();
+        let gamepads = || p.gilrs.gamepads();
+
+        assert_eq!(ids(gamepads().with_ff()), [p.mapped_ff, p.unmapped_ff]);
+        assert_eq!(
+            ids(gamepads().with_mapping_source(MappingSource::SdlMappings)),
+            [p.mapped_ff, p.mapped]
+        );
+        assert_eq!(
+            ids(gamepads().with_mapping_source(MappingSource::Driver)),
+            [p.unmapped, p.unmapped_ff]
+        );
+        assert_eq!(
+            ids(gamepads().excluding(&[p.unmapped, p.mapped_ff])),
+            [p.unmapped_ff, p.mapped]
+        );
+        assert_eq!(
+            ids(gamepads()
+                .with_ff()
+                .with_mapping_source(MappingSource::SdlMappings)),
+            [p.mapped_ff]
+        );
+        assert!(gamepads().with_ff().all(|(id, _)| id != p.disconnected_ff));
+    }
+
+    #[test]
+    fn player_assignment() {
+        let p = population();
+        let mut assigned = Vec::new();
+
+        // Adapters can be stored between calls.
+        let ff_pads: WithFf<_> = p.gilrs.gamepads().with_ff();
+        for _ in 0..3 {
+            match ff_pads.clone().excluding(&assigned).next() {
+                Some((id, _)) => assigned.push(id),
+                None => break,
+            }
+        }
+        assert_eq!(assigned, [p.mapped_ff, p.unmapped_ff]);
+
+        let found = p
+            .gilrs
+            .find_gamepad(|gp| !gp.is_ff_supported() && gp.name() == "Mapped");
+        assert_eq!(found.map(|(id, _)| id), Some(p.mapped));
+        assert!(p
+            .gilrs
+            .find_gamepad(|gp| gp.name() == "Disconnected FF")
+            .is_none());
+    }
+}
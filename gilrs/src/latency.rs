@@ -0,0 +1,235 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Measuring time between creation of events and returning them from `Gilrs::next_event()`.
+
+use std::time::Duration;
+
+/// Number of histogram buckets. Bucket `i` contains samples shorter than `2^i` µs (and not shorter
+/// than `2^(i - 1)` µs), last bucket contains everything longer than ~4 s.
+const BUCKETS: usize = 24;
+/// Number of samples after which weight of all previous samples is halved.
+const ROLLING_WINDOW: u32 = 4096;
+
+/// Summary of latency of events returned by `Gilrs::next_event()` for one gamepad. See
+/// [`Gilrs::latency_stats()`](struct.Gilrs.html#method.latency_stats).
+///
+/// `mean` and `p95` are computed from rolling histogram, so older samples have progressively
+/// lower weight. `p95` is approximate, it's upper bound of histogram bucket, which are powers of
+/// two of microseconds. `min`, `max` and `samples` cover all samples since last reset.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LatencyStats {
+    /// Number of measured events.
+    pub samples: u64,
+    pub min: Duration,
+    pub mean: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+    /// If true, platform doesn't provide kernel timestamps (see
+    /// `gilrs_core::HAS_KERNEL_TIMESTAMPS`) and values only include time that events spent queued
+    /// in gilrs after they were read from OS.
+    pub queueing_only: bool,
+}
+
+/// Histogram of latencies with fixed buckets, recording samples doesn't allocate. See
+/// [`Gilrs::event_latency_histogram()`](struct.Gilrs.html#method.event_latency_histogram).
+///
+/// Buckets are powers of two of microseconds, from 1 µs up to ~4 s, so percentiles are
+/// approximate. Like in `LatencyStats`, every 4096 samples weight of older samples is halved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LatencyHistogram {
+    buckets: [u32; BUCKETS],
+    // Weighted number of samples and their sum, halved together with buckets.
+    count: u32,
+    sum_us: u64,
+    samples: u64,
+    min: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    pub(crate) fn new() -> Self {
+        LatencyHistogram {
+            buckets: [0; BUCKETS],
+            count: 0,
+            sum_us: 0,
+            samples: 0,
+            min: Duration::from_secs(0),
+            max: Duration::from_secs(0),
+        }
+    }
+
+    pub(crate) fn record(&mut self, latency: Duration) {
+        if self.count >= ROLLING_WINDOW {
+            for bucket in self.buckets.iter_mut() {
+                *bucket /= 2;
+            }
+            self.count /= 2;
+            self.sum_us /= 2;
+        }
+
+        let us = duration_us(latency);
+        self.buckets[bucket_index(us)] += 1;
+        self.count += 1;
+        self.sum_us += us;
+
+        if self.samples == 0 || latency < self.min {
+            self.min = latency;
+        }
+        if latency > self.max {
+            self.max = latency;
+        }
+        self.samples += 1;
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = LatencyHistogram::new();
+    }
+
+    /// Returns number of samples recorded since last reset.
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Returns shortest recorded latency or `None` if there are no samples.
+    pub fn min(&self) -> Option<Duration> {
+        if self.samples == 0 {
+            None
+        } else {
+            Some(self.min)
+        }
+    }
+
+    /// Returns longest recorded latency or `None` if there are no samples.
+    pub fn max(&self) -> Option<Duration> {
+        if self.samples == 0 {
+            None
+        } else {
+            Some(self.max)
+        }
+    }
+
+    /// Returns approximate latency below which are `pct` (0.0 to 1.0) of weighted samples.
+    pub fn percentile(&self, pct: f64) -> Option<Duration> {
+        let total: u32 = self.buckets.iter().sum();
+        if total == 0 {
+            return None;
+        }
+
+        let target = (f64::from(total) * pct).ceil().max(1.0) as u32;
+        let mut seen = 0;
+        for (idx, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                let upper = if idx == BUCKETS - 1 {
+                    self.max
+                } else {
+                    Duration::from_micros(1 << idx)
+                };
+
+                return Some(upper.min(self.max).max(self.min));
+            }
+        }
+
+        Some(self.max)
+    }
+
+    pub(crate) fn stats(&self, queueing_only: bool) -> Option<LatencyStats> {
+        if self.samples == 0 || self.count == 0 {
+            return None;
+        }
+
+        Some(LatencyStats {
+            samples: self.samples,
+            min: self.min,
+            mean: Duration::from_micros(self.sum_us / u64::from(self.count)),
+            p95: self.percentile(0.95)?,
+            max: self.max,
+            queueing_only,
+        })
+    }
+}
+
+fn duration_us(dur: Duration) -> u64 {
+    dur.as_secs()
+        .saturating_mul(1_000_000)
+        .saturating_add(u64::from(dur.subsec_micros()))
+}
+
+fn bucket_index(us: u64) -> usize {
+    let bits = (64 - us.leading_zeros()) as usize;
+    if bits < BUCKETS {
+        bits
+    } else {
+        BUCKETS - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn us(us: u64) -> Duration {
+        Duration::from_micros(us)
+    }
+
+    #[test]
+    fn buckets() {
+        assert_eq!(bucket_index(0), 0);
+        assert_eq!(bucket_index(1), 1);
+        assert_eq!(bucket_index(2), 2);
+        assert_eq!(bucket_index(3), 2);
+        assert_eq!(bucket_index(1000), 10);
+        assert_eq!(bucket_index(1 << 40), BUCKETS - 1);
+        assert_eq!(duration_us(Duration::new(2, 5000)), 2_000_005);
+    }
+
+    #[test]
+    fn stats() {
+        let mut hist = LatencyHistogram::new();
+        assert_eq!(hist.stats(false), None);
+
+        for _ in 0..95 {
+            hist.record(us(100));
+        }
+        for _ in 0..5 {
+            hist.record(us(3000));
+        }
+
+        let stats = hist.stats(true).unwrap();
+        assert_eq!(stats.samples, 100);
+        assert_eq!(stats.min, us(100));
+        assert_eq!(stats.max, us(3000));
+        assert_eq!(stats.mean, us(245));
+        // 100 µs falls into bucket with upper bound 128 µs.
+        assert_eq!(stats.p95, us(128));
+        assert_eq!(hist.percentile(1.0), Some(us(3000)));
+        assert!(stats.queueing_only);
+
+        hist.reset();
+        assert_eq!(hist.stats(false), None);
+    }
+
+    #[test]
+    fn rolling() {
+        let mut hist = LatencyHistogram::new();
+        for _ in 0..ROLLING_WINDOW {
+            hist.record(us(10_000));
+        }
+        for _ in 0..ROLLING_WINDOW * 8 {
+            hist.record(us(10));
+        }
+
+        // Old samples still count for min, max and number of samples, but percentiles follow
+        // recent latency.
+        let stats = hist.stats(false).unwrap();
+        assert_eq!(stats.samples, u64::from(ROLLING_WINDOW) * 9);
+        assert_eq!(stats.max, us(10_000));
+        assert_eq!(stats.p95, us(16));
+        assert!(stats.mean < us(100));
+    }
+}
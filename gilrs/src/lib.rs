@@ -37,7 +37,7 @@
 //!
 //! loop {
 //!     // Examine new events
-//!     while let Some(Event { id, event, time }) = gilrs.next_event() {
+//!     while let Some(Event { id, event, time, .. }) = gilrs.next_event() {
 //!         println!("{:?} New event from {}: {:?}", time, id, event);
 //!         active_gamepad = Some(id);
 //!     }
@@ -61,7 +61,7 @@
 //! | Windows (XInput) |   ✓   |      ✓      |        ✓       |
 //! | OS X             |   ✓   |      ✓      |        ✕       |
 //! | Wasm             |   ✓   |      ✓      |       n/a      |
-//! | Android          |   ✕   |      ✕      |        ✕       |
+//! | Android          |   ✓   |      ✓      |        ✓       |
 //!
 //! Controller layout
 //! -----------------
@@ -84,6 +84,19 @@
 //!
 //! - `serde-serialize` - enable deriving of serde's `Serialize` and `Deserialize` for
 //!   various types.
+//! - `async` - enable [`EventStream`](stream/index.html), a `futures_core::Stream` of events.
+//! - `test_utils` - enable [`test_utils`](test_utils/index.html) module with `TestBackend`, a
+//!   scriptable replacement for platform backend.
+//! - `raw-ff` - enable [`Gamepad::ff_device_raw()`](struct.Gamepad.html#method.ff_device_raw)
+//!   that gives direct access to rumble motors.
+//! - `raw-hid` - enable [`Gamepad::raw_reports()`](struct.Gamepad.html#method.raw_reports) that
+//!   reads and writes raw HID reports. Only Linux and macOS are supported. On Windows gilrs
+//!   doesn't enumerate HID devices that aren't XInput gamepads at all.
+//! - `sdl2-compat` - enable conversions between `Button`/`Axis` and SDL2's
+//!   `SDL_GameControllerButton`/`SDL_GameControllerAxis` values. Doesn't depend on SDL.
+//! - `latency-tracking` - enable
+//!   [`Gilrs::event_latency_histogram()`](struct.Gilrs.html#method.event_latency_histogram)
+//!   that measures latency of all events returned by `next_event()`.
 //!
 //! Platform specific notes
 //! ======================
@@ -106,22 +119,85 @@
 //! wasm32-unknown-unknown. For wasm-bindgen, you will need the wasm-bindgen cli or a tool like
 //! [wasm-pack](https://rustwasm.github.io/wasm-pack/installer/).
 //! Unlike other platforms, events are only generated when you call `Gilrs::next_event()`.
+//!
+//! Android
+//! -------
+//!
+//! NDK doesn't provide access to input devices, so application has to forward input events to
+//! `Gilrs::handle_android_event()`. Gamepads are enumerated through JNI after application passes
+//! its Java VM to `Gilrs::android_set_java_vm()`. Connected and removed devices are then found
+//! once per second, because `InputManager.InputDeviceListener` requires Java code, and force
+//! feedback is played on device's `Vibrator` (requires API level 26 and `VIBRATE` permission).
+//! Applications that have their own listener can instead forward its callbacks to
+//! `Gilrs::android_device_added()` and `Gilrs::android_device_removed()`, but force feedback
+//! is not supported then. Buttons and axes are numbered like in SDL's Android backend, so SDL
+//! mappings for Android can be used. Axes are numbered from motion ranges of device.
+//!
+//! With [android-activity](https://crates.io/crates/android-activity) (see
+//! `examples/android-activity` for complete application):
+//!
+//! ```ignore
+//! unsafe { gilrs.android_set_java_vm(app.vm_as_ptr()) };
+//!
+//! app.input_events(|event| {
+//!     let raw = match event {
+//!         InputEvent::KeyEvent(ev) => ev.ptr().as_ptr() as *const _,
+//!         InputEvent::MotionEvent(ev) => ev.ptr().as_ptr() as *const _,
+//!         _ => return InputStatus::Unhandled,
+//!     };
+//!     // Event is valid until callback returns.
+//!     if unsafe { gilrs.handle_android_event(raw) } {
+//!         InputStatus::Handled
+//!     } else {
+//!         InputStatus::Unhandled
+//!     }
+//! });
+//!
+//! while let Some(ev) = gilrs.next_event() {
+//!     // ...
+//! }
+//! ```
 
 #[macro_use]
 extern crate log;
 
+mod backend;
 mod constants;
 mod gamepad;
+mod gamepad_iter;
+mod latency;
 mod mapping;
+mod snapshot;
 mod utils;
 
+pub mod diagnostic;
 pub mod ev;
 pub mod ff;
+#[cfg(feature = "async")]
+pub mod stream;
+#[cfg(any(test, feature = "test_utils"))]
+pub mod test_utils;
 
 pub use crate::ev::filter::Filter;
-pub use crate::ev::{Axis, Button, Event, EventType};
+pub use crate::ev::{
+    Axis, Button, ButtonPromptHint, ElementSelector, Event, EventSource, EventType, FilterId,
+};
 pub use crate::gamepad::{
-    ConnectedGamepadsIterator, Error, Gamepad, GamepadId, Gilrs, GilrsBuilder, MappingSource,
-    PowerInfo,
+    ButtonLayout, ConnectedGamepadsIterator, Error, Gamepad, GamepadId, GamepadInfo, Gilrs,
+    GilrsBuilder, GilrsOptions, LedError, MappingSource, PhysicalGamepadId, PollingRateError,
+    PowerInfo, PowerOffError, PumpResult, Status, UpdateHook,
+};
+pub use crate::gamepad_iter::{Excluding, GamepadIteratorExt, WithFf, WithMappingSource};
+pub use crate::latency::{LatencyHistogram, LatencyStats};
+pub use crate::mapping::{
+    EffectiveMapping, InsertResult, MappingData as Mapping, MappingDb, MappingError,
+    MappingParseIssue, MappingParseIssueKind, MappingSourcePriority,
 };
-pub use crate::mapping::{MappingData as Mapping, MappingError};
+pub use crate::snapshot::{CompactDeserError, CompactState, GamepadSnapshot, InputSnapshot};
+#[cfg(feature = "async")]
+pub use crate::stream::EventStream;
+#[cfg(any(test, feature = "test_utils"))]
+pub use crate::test_utils::TestBackend;
+#[cfg(feature = "raw-hid")]
+pub use gilrs_core::RawReports;
+pub use gilrs_core::{AxisInfo, AxisInfoBuilder, PlatformErrorKind};
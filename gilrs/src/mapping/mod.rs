@@ -8,7 +8,10 @@
 
 mod parser;
 
-use crate::ev::{self, Axis, AxisOrBtn, Button};
+use crate::backend::RawGamepad;
+use crate::ev::curve::CurveTable;
+use crate::ev::{self, Axis, AxisOrBtn, Button, Code};
+use crate::gamepad::Gamepad;
 use gilrs_core::native_ev_codes as nec;
 use gilrs_core::EvCode;
 
@@ -16,11 +19,16 @@ use std::collections::HashMap;
 use std::env;
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::Path;
 
 use fnv::FnvHashMap;
 use uuid::Uuid;
 use vec_map::VecMap;
 
+pub use self::parser::AxisRange;
 use self::parser::{Error as ParserError, ErrorKind as ParserErrorKind, Parser, Token};
 
 /// Platform name used by SDL mappings
@@ -30,13 +38,60 @@ const SDL_PLATFORM_NAME: &str = "Linux";
 const SDL_PLATFORM_NAME: &'static str = "Mac OS X";
 #[cfg(target_os = "windows")]
 const SDL_PLATFORM_NAME: &'static str = "Windows";
+#[cfg(target_os = "freebsd")]
+const SDL_PLATFORM_NAME: &str = "FreeBSD";
+#[cfg(target_os = "android")]
+const SDL_PLATFORM_NAME: &str = "Android";
 #[cfg(all(
     not(target_os = "linux"),
     not(target_os = "macos"),
-    not(target_os = "windows")
+    not(target_os = "windows"),
+    not(target_os = "freebsd"),
+    not(target_os = "android")
 ))]
 const SDL_PLATFORM_NAME: &'static str = "Unknown";
 
+/// Version of mapping format supported by this version of gilrs. Mapping can specify version it
+/// was written for with `version:` field, mappings without it are assumed to use this version.
+const MAPPING_FORMAT_VERSION: u32 = 1;
+
+/// Dpad buttons with their SDL names, directions of hat 0 and codes of axis and button that are
+/// mapped when dpad is bound to hat.
+const DPAD_HAT: [(Button, &str, u8, Axis, EvCode, EvCode); 4] = [
+    (
+        Button::DPadUp,
+        "dpup",
+        1,
+        Axis::DPadY,
+        nec::AXIS_DPADY,
+        nec::BTN_DPAD_UP,
+    ),
+    (
+        Button::DPadRight,
+        "dpright",
+        2,
+        Axis::DPadX,
+        nec::AXIS_DPADX,
+        nec::BTN_DPAD_RIGHT,
+    ),
+    (
+        Button::DPadDown,
+        "dpdown",
+        4,
+        Axis::DPadY,
+        nec::AXIS_DPADY,
+        nec::BTN_DPAD_DOWN,
+    ),
+    (
+        Button::DPadLeft,
+        "dpleft",
+        8,
+        Axis::DPadX,
+        nec::AXIS_DPADX,
+        nec::BTN_DPAD_LEFT,
+    ),
+];
+
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 /// Store mappings from one `EvCode` (`u16`) to another.
@@ -44,22 +99,95 @@ const SDL_PLATFORM_NAME: &'static str = "Unknown";
 /// This struct is internal, `MappingData` is exported in public interface as `Mapping`.
 pub struct Mapping {
     mappings: FnvHashMap<EvCode, AxisOrBtn>,
+    // Elements bound to halves of axes with `+aN` and `-aN`, by code of axis.
+    half_axes: FnvHashMap<EvCode, HalfAxes>,
+    // Buttons bound to one half of axis with `+leftx:bN` and `-leftx:bN`.
+    button_ranges: FnvHashMap<EvCode, AxisRange>,
+    // Response curves set with `axis:leftx=expo(0.3)`.
+    curves: FnvHashMap<AxisOrBtn, CurveTable>,
     name: String,
-    default: bool,
     hats_mapped: u8,
+    face_buttons_swapped: bool,
 }
 
 impl Mapping {
     pub fn new() -> Self {
         Mapping {
             mappings: FnvHashMap::default(),
+            half_axes: FnvHashMap::default(),
+            button_ranges: FnvHashMap::default(),
+            curves: FnvHashMap::default(),
             name: String::new(),
-            default: false,
             hats_mapped: 0,
+            face_buttons_swapped: false,
+        }
+    }
+
+    /// Creates mapping for gamepad whose driver doesn't follow Linux gamepad specification, like
+    /// generic USB gamepads that report joystick buttons (`BTN_TRIGGER`, `BTN_THUMB`, ...,
+    /// `BTN_BASE6`). Such buttons are assigned in order to face buttons, shoulders, triggers,
+    /// `Select`, `Start`, stick buttons and `Mode`, skipping elements that are already bound.
+    /// `ABS_Z` and `ABS_RZ` become triggers, the rest is mapped like in `default()`.
+    pub fn heuristic(gamepad: RawGamepad<'_>) -> Self {
+        // BTN_JOYSTICK..=BTN_DEAD, EV_KEY
+        const JOYSTICK_BTNS: RangeInclusive<u32> = 0x120..=0x12f;
+        const EV_KEY: u32 = 0x01;
+        const ORDER: [Button; 13] = [
+            Button::South,
+            Button::East,
+            Button::West,
+            Button::North,
+            Button::LeftTrigger,
+            Button::RightTrigger,
+            Button::LeftTrigger2,
+            Button::RightTrigger2,
+            Button::Select,
+            Button::Start,
+            Button::LeftThumb,
+            Button::RightThumb,
+            Button::Mode,
+        ];
+
+        let mut mapping = Mapping::default(gamepad);
+
+        for &(code, btn) in &[
+            (nec::AXIS_LEFTZ, Button::LeftTrigger2),
+            (nec::AXIS_RIGHTZ, Button::RightTrigger2),
+        ] {
+            if mapping.mappings.contains_key(&code) {
+                mapping.mappings.insert(code, AxisOrBtn::Btn(btn));
+            }
+        }
+
+        let mut free = ORDER
+            .iter()
+            .filter(|&&btn| {
+                !mapping
+                    .mappings
+                    .values()
+                    .any(|&el| el == AxisOrBtn::Btn(btn))
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+            .into_iter();
+        for &code in gamepad.buttons() {
+            let info = code.debug_info();
+            if info.kind != EV_KEY || !JOYSTICK_BTNS.contains(&info.number) {
+                continue;
+            }
+
+            match free.next() {
+                Some(btn) => {
+                    mapping.mappings.insert(code, AxisOrBtn::Btn(btn));
+                }
+                None => break,
+            }
         }
+
+        mapping
     }
 
-    pub fn default(gamepad: &gilrs_core::Gamepad) -> Self {
+    pub fn default(gamepad: RawGamepad<'_>) -> Self {
         use self::Axis as Ax;
         use self::AxisOrBtn::*;
 
@@ -129,9 +257,9 @@ impl Mapping {
         ];
         let btns = [
             nec::BTN_SOUTH,
+            nec::BTN_EAST,
             nec::BTN_NORTH,
             nec::BTN_WEST,
-            nec::BTN_WEST,
             nec::BTN_C,
             nec::BTN_Z,
             nec::BTN_LT,
@@ -163,9 +291,12 @@ impl Mapping {
 
         Mapping {
             mappings,
+            half_axes: FnvHashMap::default(),
+            button_ranges: FnvHashMap::default(),
+            curves: FnvHashMap::default(),
             name: String::new(),
-            default: true,
             hats_mapped: 0,
+            face_buttons_swapped: false,
         }
     }
 
@@ -173,6 +304,11 @@ impl Mapping {
         &self.name
     }
 
+    /// Returns response curve of `el` set by mapping's `axis` field.
+    pub fn curve(&self, el: AxisOrBtn) -> Option<&CurveTable> {
+        self.curves.get(&el)
+    }
+
     pub fn from_data(
         data: &MappingData,
         buttons: &[EvCode],
@@ -196,6 +332,7 @@ impl Mapping {
                     ev_code,
                     mapped_btn,
                     buttons,
+                    axes,
                     &mut sdl_mappings,
                     &mut mappings,
                 )
@@ -248,17 +385,49 @@ impl Mapping {
                     AXIS_RSTICKY => add_axis("righty", ev_code, Axis::RightStickY)?,
                     AXIS_LEFTZ => add_axis("leftz", ev_code, Axis::LeftZ)?,
                     AXIS_RIGHTZ => add_axis("rightz", ev_code, Axis::RightZ)?,
+                    AXIS_LT2 => add_axis("lefttrigger2", ev_code, Axis::LeftTrigger2)?,
+                    AXIS_RT2 => add_axis("righttrigger2", ev_code, Axis::RightTrigger2)?,
+                    // SDL doesn't have dpad axes, they are handled below.
+                    AXIS_DPADX | AXIS_DPADY => (),
                     AXIS_UNKNOWN => return Err(MappingError::UnknownElement),
                     _ => unreachable!(),
                 }
             }
         }
 
+        for &axis in &[Axis::DPadX, Axis::DPadY] {
+            if let Some(&ev_code) = data.axes.get(axis as usize) {
+                if !axes.contains(&ev_code) {
+                    return Err(MappingError::InvalidCode(ev::Code(ev_code)));
+                }
+                mappings.insert(ev_code, AxisOrBtn::Axis(axis));
+            }
+        }
+
+        // Explicitly mapped dpad button takes precedence over hat.
+        let mut hats_mapped = 0;
+        for &(btn, ident, direction, axis, axis_code, btn_code) in &DPAD_HAT {
+            if data.hats & direction == 0 || data.buttons.contains_key(btn as usize) {
+                continue;
+            }
+            if !axes.contains(&axis_code) {
+                return Err(MappingError::InvalidCode(ev::Code(axis_code)));
+            }
+
+            sdl_mappings.push_str(&format!("{}:h0.{},", ident, direction));
+            mappings.insert(axis_code, AxisOrBtn::Axis(axis));
+            mappings.insert(btn_code, AxisOrBtn::Btn(btn));
+            hats_mapped |= direction;
+        }
+
         let mapping = Mapping {
             mappings,
+            half_axes: FnvHashMap::default(),
+            button_ranges: FnvHashMap::default(),
+            curves: FnvHashMap::default(),
             name: name.to_owned(),
-            default: false,
-            hats_mapped: 0,
+            hats_mapped,
+            face_buttons_swapped: false,
         };
 
         Ok((mapping, sdl_mappings))
@@ -279,30 +448,74 @@ impl Mapping {
                 }
             }
 
-            let token = token?;
+            if let Err(ref e) = token {
+                if e.kind() == &ParserErrorKind::InvalidGuid {
+                    let guid = line.split(',').next().unwrap_or_default();
+                    return Err(MappingError::InvalidGuid(guid.to_owned()).into());
+                }
+            }
+
+            let field = parser.current_field();
+            let token = token.map_err(|e| ParseSdlMappingError::ParseError(e, field.to_owned()))?;
 
             match token {
                 Token::Platform(platform) => {
                     if platform != SDL_PLATFORM_NAME {
-                        warn!("Mappings for different platform – {}", platform);
+                        return Err(MappingError::UnknownPlatform(platform.to_owned()).into());
+                    }
+                }
+                Token::Version(version) => {
+                    if version != MAPPING_FORMAT_VERSION {
+                        return Err(MappingError::VersionMismatch {
+                            expected: MAPPING_FORMAT_VERSION,
+                            got: version,
+                        }
+                        .into());
                     }
                 }
                 Token::Uuid(_) => (),
                 Token::Name(name) => mapping.name = name.to_owned(),
-                Token::AxisMapping { from, to, .. } => {
+                Token::AxisMapping {
+                    from, to, input, ..
+                } => {
                     let axis = axes
                         .get(from as usize)
                         .cloned()
-                        .ok_or(ParseSdlMappingError::InvalidAxis)?;
-                    mapping.mappings.insert(axis, to);
+                        .ok_or_else(|| ParseSdlMappingError::InvalidAxis(field.to_owned()))?;
+
+                    if input == AxisRange::Full {
+                        mapping.mappings.insert(axis, to);
+                    } else {
+                        // Both halves share code of axis, so elements bound to them get their own
+                        // codes, like dpad buttons bound to hat.
+                        let code = half_axis_code(to);
+                        let half_axes = mapping.half_axes.entry(axis).or_default();
+                        if input == AxisRange::LowerHalf {
+                            half_axes.negative = Some((to, code));
+                        } else {
+                            half_axes.positive = Some((to, code));
+                        }
+                        mapping.mappings.insert(code, to);
+                    }
                 }
                 Token::ButtonMapping { from, to } => {
                     let btn = buttons
                         .get(from as usize)
                         .cloned()
-                        .ok_or(ParseSdlMappingError::InvalidButton)?;
+                        .ok_or_else(|| ParseSdlMappingError::InvalidButton(field.to_owned()))?;
                     mapping.mappings.insert(btn, AxisOrBtn::Btn(to));
                 }
+                Token::ButtonAxisMapping { from, to, output } => {
+                    let btn = buttons
+                        .get(from as usize)
+                        .cloned()
+                        .ok_or_else(|| ParseSdlMappingError::InvalidButton(field.to_owned()))?;
+                    mapping.mappings.insert(btn, AxisOrBtn::Axis(to));
+                    mapping.button_ranges.insert(btn, output);
+                }
+                Token::AxisCurve { to, curve } => {
+                    mapping.curves.insert(to, curve);
+                }
                 Token::HatMapping { hat, direction, to } => {
                     if hat != 0 || !to.is_dpad() {
                         warn!(
@@ -322,7 +535,11 @@ impl Mapping {
                             2 => (nec::AXIS_DPADX, nec::BTN_DPAD_RIGHT),
                             8 => (nec::AXIS_DPADX, nec::BTN_DPAD_LEFT),
                             0 => continue, // FIXME: I have no idea what 0 means here
-                            _ => return Err(ParseSdlMappingError::UnknownHatDirection),
+                            _ => {
+                                return Err(ParseSdlMappingError::UnknownHatDirection(
+                                    field.to_owned(),
+                                ))
+                            }
                         };
 
                         let to_axis = match to {
@@ -342,19 +559,24 @@ impl Mapping {
         Ok(mapping)
     }
 
+    // Buttons can also be bound to axis codes, for example pressure sensitive face buttons or
+    // analog triggers. Such buttons will report their real value in `ButtonChanged` events.
     fn add_button(
         ident: &str,
         ev_code: EvCode,
         mapped_btn: Button,
         buttons: &[EvCode],
+        axes: &[EvCode],
         sdl_mappings: &mut String,
         mappings: &mut FnvHashMap<EvCode, AxisOrBtn>,
     ) -> Result<(), MappingError> {
-        let n_btn = buttons
-            .iter()
-            .position(|&x| x == ev_code)
-            .ok_or(MappingError::InvalidCode(ev::Code(ev_code)))?;
-        sdl_mappings.push_str(&format!("{}:b{},", ident, n_btn));
+        if let Some(n_btn) = buttons.iter().position(|&x| x == ev_code) {
+            sdl_mappings.push_str(&format!("{}:b{},", ident, n_btn));
+        } else if let Some(n_axis) = axes.iter().position(|&x| x == ev_code) {
+            sdl_mappings.push_str(&format!("{}:a{},", ident, n_axis));
+        } else {
+            return Err(MappingError::InvalidCode(ev::Code(ev_code)));
+        }
         mappings.insert(ev_code, AxisOrBtn::Btn(mapped_btn));
         Ok(())
     }
@@ -388,8 +610,25 @@ impl Mapping {
         self.mappings.iter().find(|x| x.1 == el).map(|x| *x.0)
     }
 
-    pub fn is_default(&self) -> bool {
-        self.default
+    /// Returns elements bound to halves of axis with `code`.
+    pub fn half_axes(&self, code: &EvCode) -> Option<HalfAxes> {
+        self.half_axes.get(code).cloned()
+    }
+
+    /// Returns half of axis to which button with `code` is bound, `AxisRange::Full` if button
+    /// is bound to whole axis.
+    pub fn button_range(&self, code: &EvCode) -> AxisRange {
+        self.button_ranges
+            .get(code)
+            .cloned()
+            .unwrap_or(AxisRange::Full)
+    }
+
+    fn is_half_axis_element(&self, code: EvCode) -> bool {
+        self.half_axes.values().any(|half_axes| {
+            half_axes.negative.map(|(_, c)| c) == Some(code)
+                || half_axes.positive.map(|(_, c)| c) == Some(code)
+        })
     }
 
     /// Return bit field with mapped hats. Only for mappings created from SDL format this function
@@ -397,25 +636,241 @@ impl Mapping {
     pub fn hats_mapped(&self) -> u8 {
         self.hats_mapped
     }
+
+    /// Converts mapping back to `MappingData`, so `from_data()` creates the same mapping again.
+    ///
+    /// Dpad buttons bound to hat are stored as hat, not as buttons. If more than one code is mapped
+    /// to the same button or axis, only the one with the lowest code is kept. Elements bound to
+    /// halves of axes can't be represented by `MappingData` and are skipped.
+    pub fn to_data(&self) -> MappingData {
+        let mut data = MappingData::new();
+        data.hats = self.hats_mapped;
+
+        let mut entries = self.mappings.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|&(&code, _)| code);
+
+        for (&code, &el) in entries {
+            if self.is_half_axis_element(code) {
+                continue;
+            }
+
+            match el {
+                AxisOrBtn::Btn(Button::Unknown) | AxisOrBtn::Axis(Axis::Unknown) => (),
+                AxisOrBtn::Btn(btn) => {
+                    let from_hat = DPAD_HAT.iter().any(|&(b, _, direction, _, _, btn_code)| {
+                        b == btn && btn_code == code && self.hats_mapped & direction != 0
+                    });
+
+                    if !from_hat && !data.buttons.contains_key(btn as usize) {
+                        data.buttons.insert(btn as usize, code);
+                    }
+                }
+                AxisOrBtn::Axis(axis) => {
+                    if !data.axes.contains_key(axis as usize) {
+                        data.axes.insert(axis as usize, code);
+                    }
+                }
+            }
+        }
+
+        data
+    }
+
+    /// Swaps `South` with `East` and `West` with `North`. Used to map Nintendo-layout gamepads by
+    /// position instead of by label.
+    pub fn swap_face_buttons(&mut self) {
+        let half_elements = self
+            .half_axes
+            .values_mut()
+            .flat_map(|half_axes| half_axes.negative.iter_mut().chain(&mut half_axes.positive))
+            .map(|(el, _)| el);
+        for el in self.mappings.values_mut().chain(half_elements) {
+            if let AxisOrBtn::Btn(ref mut btn) = *el {
+                *btn = swap_face_button(*btn);
+            }
+        }
+        self.face_buttons_swapped = !self.face_buttons_swapped;
+    }
+
+    /// Returns `true` if `swap_face_buttons()` was used, so buttons are mapped by position and
+    /// don't match names in SDL mapping.
+    pub fn face_buttons_swapped(&self) -> bool {
+        self.face_buttons_swapped
+    }
+}
+
+/// Elements bound to negative and positive half of one axis, with codes used in their events.
+#[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct HalfAxes {
+    pub negative: Option<(AxisOrBtn, EvCode)>,
+    pub positive: Option<(AxisOrBtn, EvCode)>,
+}
+
+/// Returns code that is used for events of `el` bound to half of axis. Gamepad never reports
+/// such code, so it can't collide with code of any element of gamepad.
+fn half_axis_code(el: AxisOrBtn) -> EvCode {
+    let index = match el {
+        AxisOrBtn::Btn(btn) => Button::all()
+            .iter()
+            .position(|&b| b == btn)
+            .expect("SDL mapping can't bind unknown button"),
+        AxisOrBtn::Axis(axis) => {
+            Button::COUNT
+                + Axis::all()
+                    .iter()
+                    .position(|&a| a == axis)
+                    .expect("SDL mapping can't bind unknown axis")
+        }
+    };
+
+    EvCode::synthetic(index as u8)
+}
+
+/// Returns button on position of `btn` on gamepad with Nintendo layout.
+pub(crate) fn swap_face_button(btn: Button) -> Button {
+    match btn {
+        Button::South => Button::East,
+        Button::East => Button::South,
+        Button::West => Button::North,
+        Button::North => Button::West,
+        btn => btn,
+    }
+}
+
+/// Returns `true` if gamepad with `uuid` has A/B and X/Y buttons on positions swapped
+/// relatively to Xbox layout, like Nintendo Switch Pro Controller. All Nintendo gamepads are
+/// recognized, 8BitDo ones only by product ID, because some of them use Xbox layout.
+pub(crate) fn is_nintendo_layout(uuid: Uuid) -> bool {
+    const NINTENDO: u16 = 0x057e;
+    const EIGHT_BITDO: u16 = 0x2dc8;
+    // SF30 Pro, SN30 Pro and Pro 2 (USB and Bluetooth), N30 Pro 2 and Zero 2.
+    const EIGHT_BITDO_NINTENDO_LAYOUT: [u16; 8] = [
+        0x6000, 0x6100, 0x6001, 0x6101, 0x6003, 0x6006, 0x9015, 0x9018,
+    ];
+
+    match vendor_product_id(uuid) {
+        Some((NINTENDO, _)) => true,
+        Some((EIGHT_BITDO, product)) => EIGHT_BITDO_NINTENDO_LAYOUT.contains(&product),
+        _ => false,
+    }
+}
+
+/// Returns USB vendor and product ID of gamepad with `uuid`, or `None` if `uuid` doesn't have
+/// standard SDL layout.
+pub(crate) fn vendor_product_id(uuid: Uuid) -> Option<(u16, u16)> {
+    let bytes = uuid_without_version(uuid)?;
+
+    Some((
+        u16::from_le_bytes([bytes[4], bytes[5]]),
+        u16::from_le_bytes([bytes[8], bytes[9]]),
+    ))
+}
+
+/// Decides which gamepads are hidden from application, like SDL's
+/// `SDL_GAMECONTROLLER_IGNORE_DEVICES` and `SDL_GAMECONTROLLER_IGNORE_DEVICES_EXCEPT` hints.
+///
+/// Gamepads are identified by USB vendor and product ID taken from UUID, so gamepads with
+/// nonstandard UUID are never ignored.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DeviceFilter {
+    ignored: Vec<(u16, u16)>,
+    // If not `None`, every device not on this list is ignored.
+    allowed: Option<Vec<(u16, u16)>>,
+}
+
+impl DeviceFilter {
+    /// Creates filter from `SDL_GAMECONTROLLER_IGNORE_DEVICES` and
+    /// `SDL_GAMECONTROLLER_IGNORE_DEVICES_EXCEPT` environment variables.
+    pub fn from_env() -> Self {
+        let ignored = env::var("SDL_GAMECONTROLLER_IGNORE_DEVICES")
+            .map(|list| parse_device_list(&list))
+            .unwrap_or_default();
+        // Same as SDL, empty list doesn't ignore everything.
+        let allowed = env::var("SDL_GAMECONTROLLER_IGNORE_DEVICES_EXCEPT")
+            .ok()
+            .map(|list| parse_device_list(&list))
+            .filter(|list| !list.is_empty());
+
+        DeviceFilter { ignored, allowed }
+    }
+
+    pub fn ignore(&mut self, devices: Vec<(u16, u16)>) {
+        self.ignored.extend(devices);
+    }
+
+    pub fn is_ignored(&self, uuid: [u8; 16]) -> bool {
+        let ids = match vendor_product_id(Uuid::from_bytes(uuid)) {
+            Some(ids) => ids,
+            None => return false,
+        };
+
+        self.ignored.contains(&ids)
+            || self
+                .allowed
+                .as_ref()
+                .map(|allowed| !allowed.contains(&ids))
+                .unwrap_or(false)
+    }
+}
+
+/// Parses comma separated list of devices in format used by SDL hints, for example
+/// `0x045e/0x028e, 0x054c/0x05c4`. Malformed entries are logged and skipped.
+pub(crate) fn parse_device_list(list: &str) -> Vec<(u16, u16)> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let ids = parse_device(entry);
+            if ids.is_none() {
+                warn!("Ignoring malformed entry {:?} in device list", entry);
+            }
+
+            ids
+        })
+        .collect()
+}
+
+fn parse_device(entry: &str) -> Option<(u16, u16)> {
+    let mut ids = entry.splitn(2, '/');
+    let vendor = parse_hex_id(ids.next()?)?;
+    let product = parse_hex_id(ids.next()?)?;
+
+    Some((vendor, product))
+}
+
+fn parse_hex_id(id: &str) -> Option<u16> {
+    let id = id.trim();
+    match (id.get(..2), id.get(2..)) {
+        (Some("0x"), Some(digits)) | (Some("0X"), Some(digits))
+            if digits.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            u16::from_str_radix(digits, 16).ok()
+        }
+        _ => None,
+    }
 }
 
+/// Error returned by `Mapping::parse_sdl_mapping()`. Variants other than `Mapping` hold the
+/// offending field, e.g. `x:b17`.
 #[derive(Clone, PartialEq, Debug)]
 pub enum ParseSdlMappingError {
-    InvalidButton,
-    InvalidAxis,
-    UnknownHatDirection,
-    ParseError(ParserError),
+    InvalidButton(String),
+    InvalidAxis(String),
+    UnknownHatDirection(String),
+    ParseError(ParserError, String),
+    Mapping(MappingError),
 }
 
-impl From<ParserError> for ParseSdlMappingError {
-    fn from(f: ParserError) -> Self {
-        ParseSdlMappingError::ParseError(f)
+impl From<MappingError> for ParseSdlMappingError {
+    fn from(f: MappingError) -> Self {
+        ParseSdlMappingError::Mapping(f)
     }
 }
 
 impl Error for ParseSdlMappingError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
-        if let ParseSdlMappingError::ParseError(ref err) = self {
+        if let ParseSdlMappingError::ParseError(ref err, _) = self {
             Some(err)
         } else {
             None
@@ -425,68 +880,468 @@ impl Error for ParseSdlMappingError {
 
 impl Display for ParseSdlMappingError {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
-        let s = match self {
-            ParseSdlMappingError::InvalidButton => "gamepad doesn't have requested button",
-            ParseSdlMappingError::InvalidAxis => "gamepad doesn't have requested axis",
-            ParseSdlMappingError::UnknownHatDirection => "hat direction wasn't 1, 2, 4 or 8",
-            ParseSdlMappingError::ParseError(_) => "parsing error",
+        let (s, field) = match self {
+            ParseSdlMappingError::InvalidButton(field) => {
+                ("gamepad doesn't have requested button", field)
+            }
+            ParseSdlMappingError::InvalidAxis(field) => {
+                ("gamepad doesn't have requested axis", field)
+            }
+            ParseSdlMappingError::UnknownHatDirection(field) => {
+                ("hat direction wasn't 1, 2, 4 or 8", field)
+            }
+            ParseSdlMappingError::ParseError(err, field) => {
+                return write!(fmt, "{} in \"{}\"", err, field)
+            }
+            ParseSdlMappingError::Mapping(ref err) => return Display::fmt(err, fmt),
         };
 
-        fmt.write_str(s)
+        write!(fmt, "{} in \"{}\"", s, field)
     }
 }
 
-#[derive(Debug)]
+/// Complete layout of gamepad's buttons and axes, returned by
+/// [`Gamepad::effective_mapping()`](struct.Gamepad.html#method.effective_mapping).
+///
+/// It's a copy, so it doesn't change when gamepad's mapping is changed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EffectiveMapping {
+    /// Mapped buttons and their `Code`s.
+    pub buttons: HashMap<Button, Code>,
+    /// Mapped axes and their `Code`s.
+    pub axes: HashMap<Axis, Code>,
+}
+
+impl EffectiveMapping {
+    /// Returns `Code`s of buttons reported by `gamepad` that aren't mapped to any `Button` or
+    /// `Axis`, in order in which device reports them.
+    pub fn unmapped_buttons(&self, gamepad: &Gamepad<'_>) -> Vec<Code> {
+        gamepad
+            .raw_buttons_iter()
+            .filter(|code| {
+                !self.buttons.values().any(|mapped| mapped == code)
+                    && !self.axes.values().any(|mapped| mapped == code)
+            })
+            .collect()
+    }
+}
+
+/// Source of mapping stored in `MappingDb`.
+///
+/// When there are multiple mappings for the same UUID, the one from source with highest priority
+/// is used, regardless of order in which they were added. Variants are ordered from lowest to
+/// highest priority.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MappingSourcePriority {
+    /// Mappings included from SDL_GameControllerDB.
+    Included,
+    /// Mappings from `SDL_GAMECONTROLLERCONFIG` and `SDL_GAMECONTROLLERCONFIG_FILE` environment
+    /// variables.
+    Env,
+    /// Mappings added by user.
+    User,
+}
+
+/// Collection of SDL mappings indexed by gamepad UUID.
+///
+/// Only mappings for current platform are stored. Use
+/// [`merge()`](#method.merge) and [`intersection()`](#method.intersection) to combine mappings from
+/// multiple sources.
+///
+/// Every mapping is tagged with its [`MappingSourcePriority`](enum.MappingSourcePriority.html).
+/// Mapping for given UUID is only replaced by mapping with the same or higher priority.
+#[derive(Debug, Clone, Default)]
 pub struct MappingDb {
-    mappings: HashMap<Uuid, String>,
+    mappings: HashMap<Uuid, (String, MappingSourcePriority)>,
 }
 
 impl MappingDb {
+    /// Creates empty mapping database.
     pub fn new() -> Self {
         MappingDb {
             mappings: HashMap::new(),
         }
     }
 
+    /// Creates mapping database from file in SDL_GameControllerDB format. See
+    /// [`load_sdl_db_file()`](#method.load_sdl_db_file) for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if file can't be read.
+    pub fn from_sdl_db_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut db = MappingDb::new();
+        db.load_sdl_db_file(path)?;
+
+        Ok(db)
+    }
+
+    /// Adds mappings included from https://github.com/gabomdq/SDL_GameControllerDB.
     pub fn add_included_mappings(&mut self) {
-        self.insert(include_str!(
-            "../../SDL_GameControllerDB/gamecontrollerdb.txt"
-        ));
+        self.insert_with_priority(
+            include_str!("../../SDL_GameControllerDB/gamecontrollerdb.txt"),
+            MappingSourcePriority::Included,
+        );
     }
 
+    /// Adds mappings from file pointed by `SDL_GAMECONTROLLERCONFIG_FILE` and from
+    /// `SDL_GAMECONTROLLERCONFIG` environment variables.
     pub fn add_env_mappings(&mut self) {
+        if let Some(path) = env::var_os("SDL_GAMECONTROLLERCONFIG_FILE") {
+            if let Err(e) = self.add_file_mappings_priv(path.as_ref(), MappingSourcePriority::Env) {
+                warn!("Failed to read mappings from {:?}: {}", path, e);
+            }
+        }
+
         if let Ok(mapping) = env::var("SDL_GAMECONTROLLERCONFIG") {
-            self.insert(&mapping);
+            let result = self.insert_with_priority(&mapping, MappingSourcePriority::Env);
+            log_issues("SDL_GAMECONTROLLERCONFIG", &result.errors);
         }
     }
 
-    pub fn insert(&mut self, s: &str) {
-        for mapping in s.lines() {
-            let pat = "platform:";
-            if let Some(offset) = mapping.find(pat).map(|o| o + pat.len()) {
-                let s = &mapping[offset..];
-                let end = s.find(',').unwrap_or_else(|| s.len());
+    /// Adds mappings from file, one per line. Empty lines and lines starting with `#` are ignored.
+    /// Invalid mappings are logged together with their line number and skipped. Mappings have
+    /// `User` priority.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if file can't be read.
+    pub fn add_file_mappings<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        self.load_sdl_db_file(path).map(|_| ())
+    }
 
-                if &s[..end] != SDL_PLATFORM_NAME {
-                    continue;
+    /// Adds mappings from file in SDL_GameControllerDB format and returns number of mappings
+    /// that were stored. Works like [`add_file_mappings()`](#method.add_file_mappings), mappings
+    /// for other platforms and mappings that don't replace existing mapping with higher priority
+    /// are not counted. UTF-8 BOM and CRLF line endings are accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if file can't be read.
+    pub fn load_sdl_db_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<usize> {
+        self.add_file_mappings_priv(path.as_ref(), MappingSourcePriority::User)
+    }
+
+    fn add_file_mappings_priv(
+        &mut self,
+        path: &Path,
+        priority: MappingSourcePriority,
+    ) -> io::Result<usize> {
+        let content = fs::read_to_string(path)?;
+        let content = content.trim_start_matches('\u{feff}');
+        let result = self.insert_with_priority(content, priority);
+        log_issues(&path.display().to_string(), &result.errors);
+
+        Ok(result.added)
+    }
+
+    /// Adds SDL mappings with `User` priority, one per line. Existing mappings for the same UUID
+    /// are replaced. See [`insert_with_priority()`](#method.insert_with_priority) for details.
+    pub fn insert(&mut self, s: &str) -> InsertResult {
+        self.insert_with_priority(s, MappingSourcePriority::User)
+    }
+
+    /// Adds SDL mappings with given priority, one per line. Existing mappings for the same UUID
+    /// are replaced only if they don't have higher priority.
+    ///
+    /// Empty lines, lines starting with `#` and mappings for other platforms are ignored.
+    /// Mappings that are not valid are skipped and reported in returned
+    /// [`InsertResult`](struct.InsertResult.html), together with their line number. Mappings
+    /// with unknown buttons or axes are also reported, but they are stored, because these may be
+    /// supported by newer versions of SDL.
+    pub fn insert_with_priority(
+        &mut self,
+        s: &str,
+        priority: MappingSourcePriority,
+    ) -> InsertResult {
+        let mut result = InsertResult::default();
+
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || !is_for_current_platform(line) {
+                continue;
+            }
+
+            let (uuid, issues) = check_sdl_mapping(line);
+            let valid = issues
+                .iter()
+                .all(|&(kind, _)| kind == MappingParseIssueKind::UnknownKey);
+            result
+                .errors
+                .extend(issues.into_iter().map(|(kind, field)| MappingParseIssue {
+                    line: idx + 1,
+                    uuid: uuid.map(|uuid| *uuid.as_bytes()),
+                    field: field.to_owned(),
+                    kind,
+                }));
+
+            if let (true, Some(uuid)) = (valid, uuid) {
+                if self.insert_entry(uuid, line.to_owned(), priority) {
+                    result.added += 1;
                 }
             }
+        }
 
-            mapping
-                .split(',')
-                .next()
-                .and_then(|s| Uuid::parse_str(s).ok())
-                .and_then(|uuid| self.mappings.insert(uuid, mapping.to_owned()));
+        result
+    }
+
+    /// Stores mapping unless there is mapping with higher priority for the same UUID. Returns
+    /// `true` if mapping was stored.
+    fn insert_entry(
+        &mut self,
+        uuid: Uuid,
+        mapping: String,
+        priority: MappingSourcePriority,
+    ) -> bool {
+        match self.mappings.get(&uuid) {
+            Some(&(_, current)) if current > priority => false,
+            _ => {
+                self.mappings.insert(uuid, (mapping, priority));
+                true
+            }
+        }
+    }
+
+    /// Adds all mappings from `other`. If both databases have mapping for the same UUID, mapping
+    /// with higher priority is used. If priorities are equal, mapping from `other` is used.
+    pub fn merge(&mut self, other: MappingDb) {
+        for (uuid, (mapping, priority)) in other.mappings {
+            self.insert_entry(uuid, mapping, priority);
         }
     }
 
-    pub fn get(&self, uuid: Uuid) -> Option<&str> {
-        self.mappings.get(&uuid).map(String::as_ref)
+    /// Returns database with mappings for UUIDs that are present in both `self` and `other`.
+    /// Mappings are taken from `self`.
+    pub fn intersection(&self, other: &MappingDb) -> MappingDb {
+        let mappings = self
+            .mappings
+            .iter()
+            .filter(|(uuid, _)| other.mappings.contains_key(uuid))
+            .map(|(uuid, mapping)| (*uuid, mapping.clone()))
+            .collect();
+
+        MappingDb { mappings }
+    }
+
+    /// Returns iterator over UUIDs and SDL mappings in unspecified order. UUIDs are returned as
+    /// bytes, the same way as [`Gamepad::uuid()`](struct.Gamepad.html#method.uuid) does.
+    pub fn iter(&self) -> impl Iterator<Item = ([u8; 16], &str)> {
+        self.mappings
+            .iter()
+            .map(|(uuid, (mapping, _))| (*uuid.as_bytes(), mapping.as_str()))
+    }
+
+    pub(crate) fn get(&self, uuid: Uuid) -> Option<&str> {
+        self.mappings
+            .get(&uuid)
+            .map(|(mapping, _)| mapping.as_str())
+    }
+
+    /// Returns mapping for `uuid`. If there is no mapping for exactly the same UUID, returns
+    /// mapping for device with the same bus, vendor and product, but different version. Firmware
+    /// updates often change only version.
+    pub(crate) fn get_fuzzy(&self, uuid: Uuid) -> Option<&str> {
+        if let Some(mapping) = self.get(uuid) {
+            return Some(mapping);
+        }
+
+        let key = uuid_without_version(uuid)?;
+        self.mappings
+            .iter()
+            .filter(|(other, _)| uuid_without_version(**other) == Some(key))
+            // Make choice deterministic if there are multiple versions.
+            .max_by_key(|(other, (_, priority))| (*priority, *other.as_bytes()))
+            .map(|(_, (mapping, _))| mapping.as_str())
+    }
+
+    /// Returns mapping for given UUID together with priority of its source. UUID is expected in
+    /// the same format as returned by [`Gamepad::uuid()`](struct.Gamepad.html#method.uuid).
+    pub fn get_with_source(&self, uuid: [u8; 16]) -> Option<(&str, MappingSourcePriority)> {
+        self.mappings
+            .get(&Uuid::from_bytes(uuid))
+            .map(|(mapping, priority)| (mapping.as_str(), *priority))
     }
 
+    /// Returns number of stored mappings.
     pub fn len(&self) -> usize {
         self.mappings.len()
     }
+
+    /// Returns `true` if there are no mappings.
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+}
+
+/// Returns bytes of `uuid` with version set to 0, or `None` if `uuid` doesn't have standard SDL
+/// layout (little endian bus, vendor, product and version, each followed by two zero bytes).
+fn uuid_without_version(uuid: Uuid) -> Option<[u8; 16]> {
+    let mut bytes = *uuid.as_bytes();
+    let is_standard = [2, 3, 6, 7, 10, 11, 14, 15].iter().all(|&i| bytes[i] == 0)
+        && (bytes[4] != 0 || bytes[5] != 0);
+
+    if is_standard {
+        bytes[12] = 0;
+        bytes[13] = 0;
+        Some(bytes)
+    } else {
+        None
+    }
+}
+
+/// Returns `false` if `line` has `platform:` field for other platform.
+fn is_for_current_platform(line: &str) -> bool {
+    let pat = "platform:";
+    match line.find(pat).map(|o| o + pat.len()) {
+        Some(offset) => {
+            let s = &line[offset..];
+            let end = s.find(',').unwrap_or(s.len());
+
+            &s[..end] == SDL_PLATFORM_NAME
+        }
+        None => true,
+    }
+}
+
+/// Checks if `line` is a syntactically valid SDL mapping. Returns its UUID, if it could be parsed,
+/// and all problems found together with offending fields. Empty values are allowed.
+fn check_sdl_mapping(line: &str) -> (Option<Uuid>, Vec<(MappingParseIssueKind, &str)>) {
+    let mut parser = Parser::new(line);
+    let mut uuid = None;
+    let mut keys = Vec::new();
+    let mut issues = Vec::new();
+
+    while let Some(token) = parser.next_token() {
+        let field = parser.current_field();
+        let kind = match token {
+            Ok(Token::Uuid(u)) => {
+                uuid = Some(u);
+                continue;
+            }
+            Ok(Token::Name(_)) => continue,
+            Ok(token) => {
+                // There can be one `axis` field for every element.
+                let key = match token {
+                    Token::AxisCurve { .. } => field.split('=').next(),
+                    _ => field.split(':').next(),
+                }
+                .unwrap_or_default();
+                if keys.contains(&key) {
+                    MappingParseIssueKind::DuplicateField
+                } else {
+                    keys.push(key);
+                    continue;
+                }
+            }
+            Err(e) => match e.kind() {
+                ParserErrorKind::EmptyValue => continue,
+                ParserErrorKind::UnknownAxis | ParserErrorKind::UnknownButton => {
+                    MappingParseIssueKind::UnknownKey
+                }
+                ParserErrorKind::InvalidValue => MappingParseIssueKind::InvalidValue,
+                ParserErrorKind::InvalidKeyValPair if field.contains(':') => {
+                    MappingParseIssueKind::TrailingGarbage
+                }
+                ParserErrorKind::InvalidKeyValPair => MappingParseIssueKind::MalformedField,
+                // Parser can't continue after these errors.
+                ParserErrorKind::InvalidGuid => {
+                    issues.push((MappingParseIssueKind::InvalidGuid, field));
+                    break;
+                }
+                ParserErrorKind::UnexpectedEnd => {
+                    uuid = Uuid::parse_str(field).ok();
+                    issues.push((MappingParseIssueKind::MissingName, field));
+                    break;
+                }
+                ParserErrorKind::InvalidParserState => break,
+            },
+        };
+
+        issues.push((kind, field));
+    }
+
+    (uuid, issues)
+}
+
+fn log_issues(source: &str, issues: &[MappingParseIssue]) {
+    for issue in issues {
+        if issue.kind == MappingParseIssueKind::UnknownKey {
+            debug!("Unknown element in mapping at {}:{}", source, issue);
+        } else {
+            warn!("Ignoring invalid mapping at {}:{}", source, issue);
+        }
+    }
+}
+
+/// Summary of adding mappings with [`MappingDb::insert()`](struct.MappingDb.html#method.insert)
+/// and [`insert_with_priority()`](struct.MappingDb.html#method.insert_with_priority).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InsertResult {
+    /// Number of mappings that were stored.
+    pub added: usize,
+    /// Problems found in mappings, in order of lines. Mappings with issues other than
+    /// `UnknownKey` were not stored.
+    pub errors: Vec<MappingParseIssue>,
+}
+
+/// Problem with one field of SDL mapping.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MappingParseIssue {
+    /// Line of mapping, starting from 1.
+    pub line: usize,
+    /// UUID of mapping, if it was valid.
+    pub uuid: Option<[u8; 16]>,
+    /// Offending field, for example `leftx:q1`.
+    pub field: String,
+    /// What's wrong with the field.
+    pub kind: MappingParseIssueKind,
+}
+
+impl Error for MappingParseIssue {}
+
+impl Display for MappingParseIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}: {} in \"{}\"", self.line, self.kind, self.field)
+    }
+}
+
+/// Kind of [`MappingParseIssue`](struct.MappingParseIssue.html).
+///
+/// Whether button or axis referenced by mapping exists can be only checked when mapping is
+/// used for gamepad, so it's not reported here.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MappingParseIssueKind {
+    /// Mapping doesn't start with valid GUID.
+    InvalidGuid,
+    /// Mapping ends after GUID.
+    MissingName,
+    /// Field is not in `key:value` format.
+    MalformedField,
+    /// Field has more than one `:`.
+    TrailingGarbage,
+    /// Key is not name of SDL button or axis, `platform` or `version`.
+    UnknownKey,
+    /// Value is not valid element, like `b0`, `-a1~` or `h0.4`, or valid version number.
+    InvalidValue,
+    /// Field with the same key appeared earlier in mapping.
+    DuplicateField,
+}
+
+impl Display for MappingParseIssueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let s = match self {
+            MappingParseIssueKind::InvalidGuid => "GUID is invalid",
+            MappingParseIssueKind::MissingName => "mapping does not have name",
+            MappingParseIssueKind::MalformedField => "expected key value pair",
+            MappingParseIssueKind::TrailingGarbage => "unexpected characters after value",
+            MappingParseIssueKind::UnknownKey => "unknown button or axis",
+            MappingParseIssueKind::InvalidValue => "value is not valid",
+            MappingParseIssueKind::DuplicateField => "duplicated field",
+        };
+
+        f.write_str(s)
+    }
 }
 
 /// Stores data used to map gamepad buttons and axes.
@@ -501,6 +1356,7 @@ impl MappingDb {
 pub struct MappingData {
     buttons: VecMap<EvCode>,
     axes: VecMap<EvCode>,
+    hats: u8,
 }
 
 impl MappingData {
@@ -509,12 +1365,20 @@ impl MappingData {
         MappingData {
             buttons: VecMap::with_capacity(18),
             axes: VecMap::with_capacity(11),
+            hats: 0,
         }
     }
 
     /// Returns `EvCode` associated with button index.
+    ///
+    /// For dpad buttons bound to hat this is the same code as the one returned by
+    /// `Gamepad::button_code()`.
     pub fn button(&self, idx: Button) -> Option<ev::Code> {
-        self.buttons.get(idx as usize).cloned().map(ev::Code)
+        self.buttons
+            .get(idx as usize)
+            .cloned()
+            .or_else(|| self.hat_button_code(idx))
+            .map(ev::Code)
     }
 
     /// Returns `EvCode` associated with axis index.
@@ -532,19 +1396,48 @@ impl MappingData {
         self.axes.insert(to as usize, from.0).map(ev::Code)
     }
 
-    /// Removes button and returns associated `NativEvCode`.
+    /// Removes button and returns associated `NativEvCode`. Dpad button is also unbound from hat.
     pub fn remove_button(&mut self, idx: Button) -> Option<ev::Code> {
-        self.buttons.remove(idx as usize).map(ev::Code)
+        let hat_code = self.hat_button_code(idx);
+        if let Some(&(_, _, direction, _, _, _)) = DPAD_HAT.iter().find(|x| x.0 == idx) {
+            self.hats &= !direction;
+        }
+
+        self.buttons.remove(idx as usize).or(hat_code).map(ev::Code)
     }
 
     /// Removes axis and returns associated `NativEvCode`.
     pub fn remove_axis(&mut self, idx: Axis) -> Option<ev::Code> {
         self.axes.remove(idx as usize).map(ev::Code)
     }
+
+    fn hat_button_code(&self, idx: Button) -> Option<EvCode> {
+        DPAD_HAT
+            .iter()
+            .find(|&&(btn, _, direction, _, _, _)| btn == idx && self.hats & direction != 0)
+            .map(|&(_, _, _, _, _, btn_code)| btn_code)
+    }
+
+    /// Returns copy of mapping with `South` swapped with `East` and `West` swapped with `North`.
+    pub(crate) fn with_swapped_face_buttons(&self) -> Self {
+        let mut data = self.clone();
+        for &(a, b) in &[(Button::South, Button::East), (Button::West, Button::North)] {
+            let code_a = data.buttons.remove(a as usize);
+            let code_b = data.buttons.remove(b as usize);
+            if let Some(code) = code_a {
+                data.buttons.insert(b as usize, code);
+            }
+            if let Some(code) = code_b {
+                data.buttons.insert(a as usize, code);
+            }
+        }
+
+        data
+    }
 }
 
 /// The error type for functions related to gamepad mapping.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum MappingError {
     /// Gamepad does not have element referenced by `EvCode`.
     InvalidCode(ev::Code),
@@ -560,6 +1453,17 @@ pub enum MappingError {
     UnknownElement,
     /// `Mapping` have button or axis that are not present in SDL2.
     NotSdl2Compatible,
+    /// Value of `platform:` field in SDL mapping doesn't match current platform.
+    UnknownPlatform(String),
+    /// Mapping was written for different version of mapping format than supported by gilrs.
+    VersionMismatch {
+        /// Version supported by gilrs.
+        expected: u32,
+        /// Version from `version:` field of mapping.
+        got: u32,
+    },
+    /// Mapping's GUID is not valid.
+    InvalidGuid(String),
 }
 
 impl Error for MappingError {}
@@ -582,6 +1486,24 @@ impl Display for MappingError {
             }
             MappingError::UnknownElement => "Button::Unknown and Axis::Unknown are not allowed",
             MappingError::NotSdl2Compatible => "one of buttons or axes is not compatible with SDL2",
+            MappingError::UnknownPlatform(platform) => {
+                sbuf = format!(
+                    "mapping is for platform \"{}\", not \"{}\"",
+                    platform, SDL_PLATFORM_NAME
+                );
+                sbuf.as_ref()
+            }
+            MappingError::VersionMismatch { expected, got } => {
+                sbuf = format!(
+                    "mapping is for format version {}, but only version {} is supported",
+                    got, expected
+                );
+                sbuf.as_ref()
+            }
+            MappingError::InvalidGuid(guid) => {
+                sbuf = format!("\"{}\" is not valid GUID", guid);
+                sbuf.as_ref()
+            }
         };
 
         f.write_str(s)
@@ -635,11 +1557,223 @@ mod tests {
         nec::AXIS_LT2,
     ];
 
+    #[test]
+    fn nintendo_layout() {
+        let uuid = |vendor: u16, product: u16| {
+            let (v, p) = (vendor.to_le_bytes(), product.to_le_bytes());
+            Uuid::from_bytes([
+                3, 0, 0, 0, v[0], v[1], 0, 0, p[0], p[1], 0, 0, 0x11, 0x01, 0, 0,
+            ])
+        };
+
+        assert!(is_nintendo_layout(uuid(0x057e, 0x2009)));
+        // 8BitDo SN30 Pro and Pro 2 Wired for Xbox
+        assert!(is_nintendo_layout(uuid(0x2dc8, 0x6001)));
+        assert!(!is_nintendo_layout(uuid(0x2dc8, 0x3106)));
+        assert!(!is_nintendo_layout(uuid(0x045e, 0x028e)));
+        assert!(!is_nintendo_layout(Uuid::nil()));
+    }
+
     #[test]
     fn mapping() {
         Mapping::parse_sdl_mapping(TEST_STR, &BUTTONS, &AXES).unwrap();
     }
 
+    #[test]
+    fn analog_buttons() {
+        const ANALOG_STR: &str = "03000000260900008888000000010001,Analog Pad,a:a8,b:b1,leftx:a0,";
+
+        let mapping = Mapping::parse_sdl_mapping(ANALOG_STR, &BUTTONS, &AXES).unwrap();
+        assert_eq!(mapping.map(&AXES[8]), Some(AxisOrBtn::Btn(Button::South)));
+        assert_eq!(mapping.map(&BUTTONS[1]), Some(AxisOrBtn::Btn(Button::East)));
+        assert_eq!(
+            mapping.map_rev(&AxisOrBtn::Btn(Button::South)),
+            Some(AXES[8])
+        );
+
+        let mut data = MappingData::new();
+        data.insert_btn(ev::Code(AXES[8]), Button::South);
+        data.insert_btn(ev::Code(BUTTONS[1]), Button::East);
+        data.insert_axis(ev::Code(AXES[0]), Axis::LeftStickX);
+
+        let (from_data, sdl_mappings) =
+            Mapping::from_data(&data, &BUTTONS, &AXES, "Analog Pad", Uuid::nil()).unwrap();
+        assert!(sdl_mappings.contains("a:a8,"));
+        assert_eq!(from_data.map(&AXES[8]), Some(AxisOrBtn::Btn(Button::South)));
+        assert_eq!(
+            Mapping::parse_sdl_mapping(&sdl_mappings, &BUTTONS, &AXES).unwrap(),
+            from_data
+        );
+    }
+
+    #[test]
+    fn trigger_axes() {
+        const GC_STR: &str = "03000000260900008888000000010001,GameCube,lefttrigger:b0,\
+                              lefttrigger2:a11,righttrigger2:a10,";
+
+        let mapping = Mapping::parse_sdl_mapping(GC_STR, &BUTTONS, &AXES).unwrap();
+        assert_eq!(
+            mapping.map(&BUTTONS[0]),
+            Some(AxisOrBtn::Btn(Button::LeftTrigger2))
+        );
+        assert_eq!(
+            mapping.map(&AXES[11]),
+            Some(AxisOrBtn::Axis(Axis::LeftTrigger2))
+        );
+        assert_eq!(
+            mapping.map(&AXES[10]),
+            Some(AxisOrBtn::Axis(Axis::RightTrigger2))
+        );
+
+        let mut data = MappingData::new();
+        data.insert_btn(ev::Code(BUTTONS[0]), Button::LeftTrigger2);
+        data.insert_axis(ev::Code(AXES[11]), Axis::LeftTrigger2);
+        data.insert_axis(ev::Code(AXES[10]), Axis::RightTrigger2);
+        let (from_data, sdl_mappings) =
+            Mapping::from_data(&data, &BUTTONS, &AXES, "GameCube", Uuid::nil()).unwrap();
+        assert!(sdl_mappings.contains("lefttrigger2:a11,"));
+        assert!(sdl_mappings.contains("righttrigger2:a10,"));
+        assert_eq!(from_data, mapping);
+    }
+
+    #[test]
+    fn half_axes() {
+        // Wheel with pedals on one axis, shifter paddles bound to stick and hat reported as axes.
+        // Button with native code of left trigger 2 is used as back.
+        const WHEEL_STR: &str = "030000006d0400001ac2000000000000,Wheel,a:b0,leftx:a0,\
+                                 lefttrigger:-a1,righttrigger:+a1,-rightx:b4,+rightx:b5,\
+                                 dpleft:-a6,dpright:+a6,dpup:-a7,dpdown:+a7,back:b8,";
+
+        let mapping = Mapping::parse_sdl_mapping(WHEEL_STR, &BUTTONS, &AXES).unwrap();
+        let code = |btn| half_axis_code(AxisOrBtn::Btn(btn));
+        assert_eq!(mapping.map(&AXES[1]), None);
+        assert_eq!(
+            mapping.half_axes(&AXES[1]),
+            Some(HalfAxes {
+                negative: Some((
+                    AxisOrBtn::Btn(Button::LeftTrigger2),
+                    code(Button::LeftTrigger2)
+                )),
+                positive: Some((
+                    AxisOrBtn::Btn(Button::RightTrigger2),
+                    code(Button::RightTrigger2)
+                )),
+            })
+        );
+        assert_eq!(
+            mapping.half_axes(&AXES[6]),
+            Some(HalfAxes {
+                negative: Some((AxisOrBtn::Btn(Button::DPadLeft), code(Button::DPadLeft))),
+                positive: Some((AxisOrBtn::Btn(Button::DPadRight), code(Button::DPadRight))),
+            })
+        );
+        assert_eq!(
+            mapping.map(&code(Button::DPadUp)),
+            Some(AxisOrBtn::Btn(Button::DPadUp))
+        );
+        assert_eq!(
+            mapping.map(&BUTTONS[8]),
+            Some(AxisOrBtn::Btn(Button::Select))
+        );
+        assert_eq!(mapping.half_axes(&AXES[0]), None);
+
+        // Codes of half axes don't collide with each other or with codes of gamepad.
+        let mut codes = BUTTONS
+            .iter()
+            .chain(&AXES)
+            .cloned()
+            .chain(
+                Button::all()
+                    .iter()
+                    .map(|&btn| half_axis_code(AxisOrBtn::Btn(btn))),
+            )
+            .chain(
+                Axis::all()
+                    .iter()
+                    .map(|&axis| half_axis_code(AxisOrBtn::Axis(axis))),
+            )
+            .collect::<Vec<_>>();
+        let len = codes.len();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), len);
+
+        assert_eq!(
+            mapping.map(&BUTTONS[4]),
+            Some(AxisOrBtn::Axis(Axis::RightStickX))
+        );
+        assert_eq!(mapping.button_range(&BUTTONS[4]), AxisRange::LowerHalf);
+        assert_eq!(mapping.button_range(&BUTTONS[5]), AxisRange::UpperHalf);
+        assert_eq!(mapping.button_range(&BUTTONS[0]), AxisRange::Full);
+
+        // Half axes can't be stored in `MappingData`.
+        let data = mapping.to_data();
+        assert_eq!(data.button(Button::LeftTrigger2), None);
+        assert_eq!(data.button(Button::DPadLeft), None);
+        assert_eq!(data.button(Button::South), Some(ev::Code(BUTTONS[0])));
+
+        assert!(Mapping::parse_sdl_mapping(
+            "030000006d0400001ac2000000000000,Wheel,+a:b0,",
+            &BUTTONS,
+            &AXES
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn axis_curves() {
+        const CURVES_STR: &str = "03000000260900008888000000010001,Pad,leftx:a0,lefttrigger:a9,\
+                                  axis:leftx=expo(0.5),axis:lefttrigger=0/0;0.4/0.1;1/1,";
+
+        let mapping = Mapping::parse_sdl_mapping(CURVES_STR, &BUTTONS, &AXES).unwrap();
+        assert_eq!(
+            mapping.curve(AxisOrBtn::Axis(Axis::LeftStickX)),
+            Some(&CurveTable::expo(0.5))
+        );
+        assert_eq!(
+            mapping
+                .curve(AxisOrBtn::Btn(Button::LeftTrigger2))
+                .map(|c| c.points()),
+            Some(&[(0.0, 0.0), (0.4, 0.1), (1.0, 1.0)][..])
+        );
+        assert_eq!(mapping.curve(AxisOrBtn::Axis(Axis::LeftStickY)), None);
+
+        for field in &[
+            "axis:leftx",
+            "axis:leftx=expo(2)",
+            "axis:leftx=expo(x)",
+            "axis:leftx=0/0",
+            "axis:leftx=1/1;0/0",
+            "axis:leftx=0/0;1",
+        ] {
+            let line = format!("03000000260900008888000000010001,Pad,{},", field);
+            match Mapping::parse_sdl_mapping(&line, &BUTTONS, &AXES) {
+                Err(ParseSdlMappingError::ParseError(ref e, ref f))
+                    if e.kind() == &ParserErrorKind::InvalidValue && f == field => {}
+                res => panic!("unexpected result for {}: {:?}", field, res),
+            }
+        }
+        let line = "03000000260900008888000000010001,Pad,axis:misc1=expo(0.5),";
+        match Mapping::parse_sdl_mapping(line, &BUTTONS, &AXES) {
+            Err(ParseSdlMappingError::ParseError(ref e, _))
+                if e.kind() == &ParserErrorKind::UnknownAxis => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        // Every element can have its own curve.
+        let (_, issues) = check_sdl_mapping(
+            "03000000260900008888000000010001,Pad,axis:leftx=expo(0.5),axis:lefty=expo(0.5),\
+             axis:leftx=expo(0.1),",
+        );
+        assert_eq!(
+            issues,
+            [(
+                MappingParseIssueKind::DuplicateField,
+                "axis:leftx=expo(0.1)"
+            )]
+        );
+    }
+
     #[test]
     fn from_data() {
         let uuid = Uuid::nil();
@@ -685,6 +1819,111 @@ mod tests {
         assert_eq!(Err(MappingError::UnknownElement), incorrect_mappings);
     }
 
+    fn assert_round_trip(line: &str) {
+        let mapping = Mapping::parse_sdl_mapping(line, &BUTTONS, &AXES).unwrap();
+        let data = mapping.to_data();
+        let (from_data, sdl_mappings) =
+            Mapping::from_data(&data, &BUTTONS, &AXES, mapping.name(), Uuid::nil()).unwrap();
+        assert_eq!(from_data, mapping);
+        assert_eq!(
+            Mapping::parse_sdl_mapping(&sdl_mappings, &BUTTONS, &AXES).unwrap(),
+            mapping
+        );
+    }
+
+    #[test]
+    fn to_data_round_trip() {
+        // Dpad on hat, triggers on axes
+        assert_round_trip(TEST_STR);
+        // Dpad on buttons, analog triggers
+        assert_round_trip(
+            "03000000260900008888000000010001,Pad,a:b0,b:b1,x:b4,y:b3,dpup:b11,dpdown:b12,\
+             dpleft:b13,dpright:b14,leftx:a0,lefty:a1,lefttrigger2:a11,righttrigger2:a10,",
+        );
+        // Partially mapped hat and trigger bound to both button and axis
+        assert_round_trip(
+            "03000000260900008888000000010001,Pad,a:b0,dpup:h0.1,dpdown:h0.4,dpleft:b13,\
+             lefttrigger:b8,lefttrigger2:a11,",
+        );
+    }
+
+    #[test]
+    fn to_data_hats() {
+        let mapping = Mapping::parse_sdl_mapping(TEST_STR, &BUTTONS, &AXES).unwrap();
+        let mut data = mapping.to_data();
+        assert_eq!(
+            data.button(Button::DPadUp),
+            Some(ev::Code(nec::BTN_DPAD_UP))
+        );
+        assert_eq!(data.axis(Axis::DPadY), Some(ev::Code(nec::AXIS_DPADY)));
+
+        assert_eq!(
+            data.remove_button(Button::DPadUp),
+            Some(ev::Code(nec::BTN_DPAD_UP))
+        );
+        assert_eq!(data.button(Button::DPadUp), None);
+        data.insert_btn(ev::Code(BUTTONS[13]), Button::DPadLeft);
+
+        let (from_data, sdl_mappings) =
+            Mapping::from_data(&data, &BUTTONS, &AXES, "GameCube", Uuid::nil()).unwrap();
+        assert!(!sdl_mappings.contains("dpup"));
+        assert!(sdl_mappings.contains("dpleft:b13,"));
+        assert!(sdl_mappings.contains("dpdown:h0.4,"));
+        assert_eq!(from_data.hats_mapped(), 4 | 2);
+        assert_eq!(
+            from_data.map(&BUTTONS[13]),
+            Some(AxisOrBtn::Btn(Button::DPadLeft))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let parse = |line| Mapping::parse_sdl_mapping(line, &BUTTONS, &AXES);
+
+        let err = parse("03000000260900008888000000010001,Pad,a:b0,platform:Amiga,").unwrap_err();
+        assert_eq!(
+            err,
+            ParseSdlMappingError::Mapping(MappingError::UnknownPlatform("Amiga".to_owned()))
+        );
+        assert!(err.to_string().contains("\"Amiga\""));
+
+        assert!(parse("03000000260900008888000000010001,Pad,a:b0,version:1,").is_ok());
+        assert_eq!(
+            parse("03000000260900008888000000010001,Pad,a:b0,version:2,"),
+            Err(ParseSdlMappingError::Mapping(
+                MappingError::VersionMismatch {
+                    expected: 1,
+                    got: 2
+                }
+            ))
+        );
+
+        assert_eq!(
+            parse("0300000026090000zz,Pad,a:b0,"),
+            Err(ParseSdlMappingError::Mapping(MappingError::InvalidGuid(
+                "0300000026090000zz".to_owned()
+            )))
+        );
+
+        let err = parse("03000000260900008888000000010001,Pad,a:b0,x:b15,").unwrap_err();
+        assert_eq!(err, ParseSdlMappingError::InvalidButton("x:b15".to_owned()));
+        assert!(err.to_string().contains("\"x:b15\""));
+        assert_eq!(
+            parse("03000000260900008888000000010001,Pad,leftx:a12,"),
+            Err(ParseSdlMappingError::InvalidAxis("leftx:a12".to_owned()))
+        );
+        assert_eq!(
+            parse("03000000260900008888000000010001,Pad,dpup:h0.3,"),
+            Err(ParseSdlMappingError::UnknownHatDirection(
+                "dpup:h0.3".to_owned()
+            ))
+        );
+        match parse("03000000260900008888000000010001,Pad,a:b0,leftx:q1,b:b1,") {
+            Err(ParseSdlMappingError::ParseError(_, field)) => assert_eq!(field, "leftx:q1"),
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
     #[test]
     fn with_mappings() {
         let mappings = format!(
@@ -700,4 +1939,291 @@ mod tests {
             db.get(Uuid::parse_str("03000000260900008888000000010001").unwrap())
         );
     }
+
+    #[test]
+    fn merge_and_intersection() {
+        const A: &str = "03000000260900008888000000010001,A,a:b0,";
+        const A2: &str = "03000000260900008888000000010001,A2,a:b1,";
+        const B: &str = "030000005e0400008e02000020200000,B,a:b0,";
+        const C: &str = "030000005e040000d102000001010000,C,a:b0,";
+        let uuid = |s: &str| *Uuid::parse_str(&s[..32]).unwrap().as_bytes();
+
+        let mut db1 = MappingDb::new();
+        db1.insert(&format!("{}\n{}", A, B));
+        let mut db2 = MappingDb::new();
+        db2.insert(&format!("{}\n{}", A2, C));
+
+        let common = db1.intersection(&db2);
+        assert_eq!(common.iter().collect::<Vec<_>>(), vec![(uuid(A), A)]);
+        let common = db2.intersection(&db1);
+        assert_eq!(common.iter().collect::<Vec<_>>(), vec![(uuid(A2), A2)]);
+
+        db1.merge(db2);
+        let mut all = db1.iter().collect::<Vec<_>>();
+        all.sort();
+        let mut expected = vec![(uuid(A2), A2), (uuid(B), B), (uuid(C), C)];
+        expected.sort();
+        assert_eq!(all, expected);
+        assert_eq!(db1.len(), 3);
+        assert!(db1.intersection(&MappingDb::new()).is_empty());
+    }
+
+    #[test]
+    fn mapping_priority() {
+        use self::MappingSourcePriority::*;
+
+        const INCLUDED: &str = "03000000260900008888000000010001,Included,a:b0,";
+        const ENV: &str = "03000000260900008888000000010001,Env,a:b1,";
+        const USER: &str = "03000000260900008888000000010001,User,a:b2,";
+        let uuid = *Uuid::parse_str(&USER[..32]).unwrap().as_bytes();
+        let sources = [(INCLUDED, Included), (ENV, Env), (USER, User)];
+        let orders = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+
+        for order in orders.iter() {
+            let mut db = MappingDb::new();
+            for &idx in order.iter() {
+                db.insert_with_priority(sources[idx].0, sources[idx].1);
+            }
+            assert_eq!(db.get_with_source(uuid), Some((USER, User)));
+            assert_eq!(db.len(), 1);
+
+            // Same result when every source is in separate database.
+            let mut db = MappingDb::new();
+            for &idx in order.iter() {
+                let mut other = MappingDb::new();
+                other.insert_with_priority(sources[idx].0, sources[idx].1);
+                db.merge(other);
+            }
+            assert_eq!(db.get_with_source(uuid), Some((USER, User)));
+        }
+
+        let mut db = MappingDb::new();
+        db.insert_with_priority(ENV, Env);
+        db.insert_with_priority(INCLUDED, Included);
+        assert_eq!(db.get_with_source(uuid), Some((ENV, Env)));
+        assert_eq!(db.get_with_source([0; 16]), None);
+    }
+
+    #[test]
+    fn fuzzy_uuid() {
+        const V1: &str = "030000005e0400008e02000014010000,Version 1,a:b0,";
+        const V2: &str = "030000005e0400008e02000020200000,Version 2,a:b1,";
+        let uuid = |s: &str| Uuid::parse_str(s).unwrap();
+        // Same device, firmware 0x0300
+        let v3 = uuid("030000005e0400008e02000000030000");
+
+        let mut db = MappingDb::new();
+        db.insert(V1);
+        assert_eq!(db.get(v3), None);
+        assert_eq!(db.get_fuzzy(v3), Some(V1));
+
+        // Exact match wins.
+        db.insert(V2);
+        assert_eq!(db.get_fuzzy(uuid(&V2[..32])), Some(V2));
+        assert_eq!(db.get_fuzzy(uuid(&V1[..32])), Some(V1));
+
+        // Different product or bus
+        assert_eq!(db.get_fuzzy(uuid("030000005e0400008f02000000030000")), None);
+        assert_eq!(db.get_fuzzy(uuid("050000005e0400008e02000000030000")), None);
+        // UUID without vendor and product (e.g. created from name) is never fuzzy matched.
+        let mut db = MappingDb::new();
+        db.insert("78696e70757401000000000000000000,XInput,a:b0,");
+        assert_eq!(db.get_fuzzy(uuid("78696e70757402000000000000000000")), None);
+    }
+
+    #[test]
+    fn file_mappings() {
+        use std::io::Write;
+
+        let path = env::temp_dir().join(format!("gilrs-mappings-{}.txt", std::process::id()));
+        let mut file = fs::File::create(&path).unwrap();
+        write!(
+            file,
+            "# Comment\r\n\r\n\
+             03000000260900008888000000010001,GameCube,a:b0,b:b2,\r\n\
+             not a mapping\r\n\
+             030000005e0400008e02000020200000,Invalid,a:b0,b\r\n\
+             030000005e040000d102000001010000,Unknown element,a:b0,misc1:b1,\n"
+        )
+        .unwrap();
+        drop(file);
+
+        let mut db = MappingDb::new();
+        db.add_file_mappings(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(db.len(), 2);
+        assert_eq!(
+            Some("03000000260900008888000000010001,GameCube,a:b0,b:b2,"),
+            db.get(Uuid::parse_str("03000000260900008888000000010001").unwrap())
+        );
+        assert!(db
+            .get(Uuid::parse_str("030000005e040000d102000001010000").unwrap())
+            .is_some());
+        assert!(db.add_file_mappings(&path).is_err());
+    }
+
+    #[test]
+    fn sdl_db_file() {
+        let path = env::temp_dir().join(format!("gilrs-sdl-db-{}.txt", std::process::id()));
+        fs::write(
+            &path,
+            format!(
+                "\u{feff}03000000260900008888000000010001,GameCube,a:b0,b:b2,\r\n\
+                 030000005e0400008e02000020200000,Other platform,a:b0,platform:{},\r\n\
+                 # Comment\r\n\
+                 030000005e040000d102000001010000,Pad,a:b0,\r\n",
+                if SDL_PLATFORM_NAME == "Linux" {
+                    "Windows"
+                } else {
+                    "Linux"
+                }
+            ),
+        )
+        .unwrap();
+
+        let mut db = MappingDb::from_sdl_db_file(&path).unwrap();
+        assert_eq!(db.len(), 2);
+        assert_eq!(
+            Some("03000000260900008888000000010001,GameCube,a:b0,b:b2,"),
+            db.get(Uuid::parse_str("03000000260900008888000000010001").unwrap())
+        );
+        assert_eq!(
+            Some("030000005e040000d102000001010000,Pad,a:b0,"),
+            db.get(Uuid::parse_str("030000005e040000d102000001010000").unwrap())
+        );
+
+        db.insert_with_priority(
+            "030000005e040000d102000001010000,Pad,a:b1,",
+            MappingSourcePriority::User,
+        );
+        db.insert_with_priority(
+            "03000000260900008888000000010001,GameCube,a:b1,",
+            MappingSourcePriority::Included,
+        );
+        assert_eq!(db.load_sdl_db_file(&path).unwrap(), 2);
+        assert_eq!(db.len(), 2);
+
+        fs::remove_file(&path).unwrap();
+        assert!(MappingDb::from_sdl_db_file(&path).is_err());
+    }
+
+    #[test]
+    fn insert_issues() {
+        use self::MappingParseIssueKind::*;
+
+        const UUID: &str = "03000000260900008888000000010001";
+        let uuid = Some(*Uuid::parse_str(UUID).unwrap().as_bytes());
+        let mut db = MappingDb::new();
+        let result = db.insert(&format!(
+            "# Comment\n\
+             {u},Valid,a:b0,\n\
+             0300000026090000zz,Bad GUID,a:b0,\n\
+             {u}\n\
+             {u},Malformed,a:b0,garbage,\n\
+             {u},Trailing,a:b0:1,\n\
+             {u},Unknown key,a:b0,misc1:b1,\n\
+             {u},Invalid value,a:b0,leftx:q1,\n\
+             \n\
+             {u},Duplicate,a:b0,b:b1,a:b2,",
+            u = UUID
+        ));
+
+        assert_eq!(result.added, 2);
+        assert_eq!(db.len(), 1);
+        assert!(db
+            .get(Uuid::parse_str(UUID).unwrap())
+            .unwrap()
+            .contains("Unknown key"));
+
+        let issues: Vec<_> = result
+            .errors
+            .iter()
+            .map(|issue| (issue.line, issue.uuid, issue.field.as_str(), issue.kind))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (3, None, "0300000026090000zz", InvalidGuid),
+                (4, uuid, UUID, MissingName),
+                (5, uuid, "garbage", MalformedField),
+                (6, uuid, "a:b0:1", TrailingGarbage),
+                (7, uuid, "misc1:b1", UnknownKey),
+                (8, uuid, "leftx:q1", InvalidValue),
+                (10, uuid, "a:b2", DuplicateField),
+            ]
+        );
+        assert_eq!(
+            result.errors[4].to_string(),
+            "7: unknown button or axis in \"misc1:b1\""
+        );
+
+        // Mappings for other platforms are not checked.
+        let result = db.insert(&format!("{},Other,a:b0:1,platform:Amiga,", UUID));
+        assert_eq!(result, InsertResult::default());
+    }
+
+    #[test]
+    fn device_list() {
+        assert_eq!(
+            parse_device_list("0x045e/0x028e,0x054C/0X05c4"),
+            [(0x045e, 0x028e), (0x054c, 0x05c4)]
+        );
+        assert_eq!(
+            parse_device_list(" 0x045e / 0x028e , ,0x057e/0x2009, "),
+            [(0x045e, 0x028e), (0x057e, 0x2009)]
+        );
+        assert_eq!(parse_device_list(""), []);
+
+        for malformed in &[
+            "045e/028e",
+            "0x045e",
+            "0x045e/",
+            "0x045e/0x028e/0x0001",
+            "0x/0x028e",
+            "0x1045e/0x028e",
+            "0x+45e/0x028e",
+            "0xgggg/0x028e",
+        ] {
+            let list = format!("{},0x057e/0x2009", malformed);
+            assert_eq!(
+                parse_device_list(&list),
+                [(0x057e, 0x2009)],
+                "{}",
+                malformed
+            );
+        }
+    }
+
+    #[test]
+    fn device_filter() {
+        let xbox = *Uuid::parse_str("030000005e0400008e02000020200000")
+            .unwrap()
+            .as_bytes();
+        let switch = *Uuid::parse_str("050000007e0500000920000001800000")
+            .unwrap()
+            .as_bytes();
+
+        let mut filter = DeviceFilter::default();
+        assert!(!filter.is_ignored(xbox));
+        filter.ignore(vec![(0x045e, 0x028e)]);
+        assert!(filter.is_ignored(xbox));
+        assert!(!filter.is_ignored(switch));
+        assert!(!filter.is_ignored([0; 16]));
+
+        let filter = DeviceFilter {
+            ignored: Vec::new(),
+            allowed: Some(vec![(0x057e, 0x2009)]),
+        };
+        assert!(filter.is_ignored(xbox));
+        assert!(!filter.is_ignored(switch));
+        assert!(!filter.is_ignored([0; 16]));
+    }
 }
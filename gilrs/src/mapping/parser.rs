@@ -9,6 +9,7 @@ use std::fmt::{self, Display};
 
 use uuid::Uuid;
 
+use crate::ev::curve::CurveTable;
 use crate::ev::{Axis, AxisOrBtn, Button};
 
 // Must be sorted!
@@ -56,7 +57,7 @@ static BUTTONS: [Button; 19] = [
 ];
 
 // Must be sorted!
-static AXES_SDL: [&str; 25] = [
+static AXES_SDL: [&str; 27] = [
     "a",
     "b",
     "back",
@@ -69,12 +70,14 @@ static AXES_SDL: [&str; 25] = [
     "leftshoulder",
     "leftstick",
     "lefttrigger",
+    "lefttrigger2",
     "leftx",
     "lefty",
     "leftz",
     "rightshoulder",
     "rightstick",
     "righttrigger",
+    "righttrigger2",
     "rightx",
     "righty",
     "rightz",
@@ -83,7 +86,7 @@ static AXES_SDL: [&str; 25] = [
     "y",
     "z",
 ];
-static AXES: [AxisOrBtn; 25] = [
+static AXES: [AxisOrBtn; 27] = [
     AxisOrBtn::Btn(Button::South),
     AxisOrBtn::Btn(Button::East),
     AxisOrBtn::Btn(Button::Select),
@@ -96,12 +99,14 @@ static AXES: [AxisOrBtn; 25] = [
     AxisOrBtn::Btn(Button::LeftTrigger),
     AxisOrBtn::Btn(Button::LeftThumb),
     AxisOrBtn::Btn(Button::LeftTrigger2),
+    AxisOrBtn::Axis(Axis::LeftTrigger2),
     AxisOrBtn::Axis(Axis::LeftStickX),
     AxisOrBtn::Axis(Axis::LeftStickY),
     AxisOrBtn::Axis(Axis::LeftZ),
     AxisOrBtn::Btn(Button::RightTrigger),
     AxisOrBtn::Btn(Button::RightThumb),
     AxisOrBtn::Btn(Button::RightTrigger2),
+    AxisOrBtn::Axis(Axis::RightTrigger2),
     AxisOrBtn::Axis(Axis::RightStickX),
     AxisOrBtn::Axis(Axis::RightStickY),
     AxisOrBtn::Axis(Axis::RightZ),
@@ -114,6 +119,8 @@ static AXES: [AxisOrBtn; 25] = [
 pub struct Parser<'a> {
     data: &'a str,
     pos: usize,
+    // Start of field returned by last call to `next_token()`.
+    field_start: usize,
     state: State,
 }
 
@@ -122,14 +129,16 @@ impl<'a> Parser<'a> {
         Parser {
             data: mapping,
             pos: 0,
+            field_start: 0,
             state: State::Uuid,
         }
     }
 
-    pub fn next_token(&mut self) -> Option<Result<Token<'_>, Error>> {
+    pub fn next_token(&mut self) -> Option<Result<Token<'a>, Error>> {
         if self.pos >= self.data.len() {
             None
         } else {
+            self.field_start = self.pos;
             Some(match self.state {
                 State::Uuid => self.parse_uuid(),
                 State::Name => self.parse_name(),
@@ -139,7 +148,17 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_uuid(&mut self) -> Result<Token<'_>, Error> {
+    /// Returns text of field parsed by last call to `next_token()`, without trailing comma.
+    pub fn current_field(&self) -> &'a str {
+        let end = self.data[self.field_start..]
+            .find(',')
+            .map(|x| x + self.field_start)
+            .unwrap_or_else(|| self.data.len());
+
+        &self.data[self.field_start..end]
+    }
+
+    fn parse_uuid(&mut self) -> Result<Token<'a>, Error> {
         let next_comma = self.next_comma_or_end();
         let uuid = Uuid::parse_str(&self.data[self.pos..next_comma])
             .map(Token::Uuid)
@@ -159,7 +178,7 @@ impl<'a> Parser<'a> {
         uuid
     }
 
-    fn parse_name(&mut self) -> Result<Token<'_>, Error> {
+    fn parse_name(&mut self) -> Result<Token<'a>, Error> {
         let next_comma = self.next_comma_or_end();
         let name = &self.data[self.pos..next_comma];
 
@@ -169,7 +188,7 @@ impl<'a> Parser<'a> {
         Ok(Token::Name(name))
     }
 
-    fn parse_key_val(&mut self) -> Result<Token<'_>, Error> {
+    fn parse_key_val(&mut self) -> Result<Token<'a>, Error> {
         let next_comma = self.next_comma_or_end();
         let pair = &self.data[self.pos..next_comma];
         let pos = self.pos;
@@ -195,6 +214,17 @@ impl<'a> Parser<'a> {
             return Ok(Token::Platform(value));
         }
 
+        if key == "version" {
+            return value
+                .parse()
+                .map(Token::Version)
+                .map_err(|_| Error::new(ErrorKind::InvalidValue, pos));
+        }
+
+        if key == "axis" {
+            return parse_axis_curve(value, pos);
+        }
+
         let mut input = AxisRange::Full;
         let mut output = AxisRange::Full;
         let mut inverted = false;
@@ -290,6 +320,15 @@ impl<'a> Parser<'a> {
                 output,
                 inverted,
             })
+        } else if let Some(output) = key_range(key) {
+            let idx = AXES_SDL
+                .binary_search(&&key[1..])
+                .map_err(|_| Error::new(ErrorKind::UnknownAxis, pos))?;
+
+            match AXES[idx] {
+                AxisOrBtn::Axis(to) => Ok(Token::ButtonAxisMapping { from, to, output }),
+                AxisOrBtn::Btn(_) => Err(Error::new(ErrorKind::UnknownAxis, pos)),
+            }
         } else {
             let idx = BUTTONS_SDL
                 .binary_search(&key)
@@ -310,9 +349,59 @@ impl<'a> Parser<'a> {
     }
 }
 
+/// Parses value of `axis` field, which sets response curve of element, e.g. `leftx=expo(0.3)` or
+/// `lefttrigger=0/0;0.4/0.1;1/1`.
+fn parse_axis_curve<'a>(value: &str, pos: usize) -> Result<Token<'a>, Error> {
+    let invalid = || Error::new(ErrorKind::InvalidValue, pos);
+
+    let eq_idx = value.find('=').ok_or_else(invalid)?;
+    let (name, curve) = (&value[..eq_idx], &value[(eq_idx + 1)..]);
+    let idx = AXES_SDL
+        .binary_search(&name)
+        .map_err(|_| Error::new(ErrorKind::UnknownAxis, pos))?;
+
+    let curve = if curve.starts_with("expo(") && curve.ends_with(')') {
+        let k: f32 = curve[5..(curve.len() - 1)].parse().map_err(|_| invalid())?;
+        if !(0.0..=1.0).contains(&k) {
+            return Err(invalid());
+        }
+
+        CurveTable::expo(k)
+    } else {
+        let points = curve
+            .split(';')
+            .map(|point| {
+                let mut split = point.split('/');
+                match (split.next(), split.next(), split.next()) {
+                    (Some(x), Some(y), None) => Some((x.parse().ok()?, y.parse().ok()?)),
+                    _ => None,
+                }
+            })
+            .collect::<Option<Vec<(f32, f32)>>>()
+            .ok_or_else(invalid)?;
+
+        CurveTable::from_points(&points).map_err(|_| invalid())?
+    };
+
+    Ok(Token::AxisCurve {
+        to: AXES[idx],
+        curve,
+    })
+}
+
+/// Returns half of axis selected by `+` or `-` prefix of `key`.
+fn key_range(key: &str) -> Option<AxisRange> {
+    match key.get(0..1) {
+        Some("+") => Some(AxisRange::UpperHalf),
+        Some("-") => Some(AxisRange::LowerHalf),
+        _ => None,
+    }
+}
+
 pub enum Token<'a> {
     Uuid(Uuid),
     Platform(&'a str),
+    Version(u32),
     Name(&'a str),
     AxisMapping {
         from: u16,
@@ -325,6 +414,12 @@ pub enum Token<'a> {
         from: u16,
         to: Button,
     },
+    // Button bound to one half of axis, e.g. `-leftx:b4`.
+    ButtonAxisMapping {
+        from: u16,
+        to: Axis,
+        output: AxisRange,
+    },
     // This is just SDL representation, we will convert this to axis mapping later
     HatMapping {
         hat: u16,
@@ -332,9 +427,16 @@ pub enum Token<'a> {
         direction: u16,
         to: Button,
     },
+    // Response curve of axis or analog button, e.g. `axis:leftx=expo(0.3)`.
+    AxisCurve {
+        to: AxisOrBtn,
+        curve: CurveTable,
+    },
 }
 
+/// Part of axis selected with `+` or `-` prefix.
 #[repr(u8)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum AxisRange {
     LowerHalf,
     UpperHalf,
@@ -0,0 +1,395 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Copy of cached state of all connected gamepads and compact state of gamepad.
+
+use crate::ev::{Axis, Button};
+use crate::gamepad::{Gamepad, GamepadId, PowerInfo, Status};
+
+use std::error;
+use std::fmt::{self, Display};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+/// State of all connected gamepads at one point in time, created by
+/// [`Gilrs::snapshot()`](struct.Gilrs.html#method.snapshot).
+///
+/// Snapshot is plain data, so it can be easily passed to scripting languages or compared with
+/// previous one to find what changed.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct InputSnapshot {
+    /// Connected gamepads in order of their IDs.
+    pub gamepads: Vec<GamepadSnapshot>,
+}
+
+/// State of one gamepad. See [`InputSnapshot`](struct.InputSnapshot.html).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct GamepadSnapshot {
+    /// See `Gamepad::id()`.
+    pub id: GamepadId,
+    /// See `Gamepad::name()`.
+    pub name: String,
+    /// See `Gamepad::status()`.
+    pub status: Status,
+    /// See `Gamepad::power_info()`.
+    pub power_info: PowerInfo,
+    /// Mapped buttons with information if they are pressed and their value. Buttons are in order
+    /// of `Button` variants.
+    pub buttons: Vec<(Button, bool, f32)>,
+    /// Mapped axes with their values. Axes are in order of `Axis` variants.
+    pub axes: Vec<(Axis, f32)>,
+}
+
+impl GamepadSnapshot {
+    pub(crate) fn new(gamepad: &Gamepad<'_>) -> Self {
+        let state = gamepad.state();
+
+        let buttons = Button::all()
+            .iter()
+            .filter_map(|&btn| {
+                let code = gamepad.button_code(btn)?;
+                let value = state.button_data(code).map(|data| data.value());
+                Some((btn, state.is_pressed(code), value.unwrap_or(0.0)))
+            })
+            .collect();
+        let axes = Axis::all()
+            .iter()
+            .filter_map(|&axis| Some((axis, state.value(gamepad.axis_code(axis)?))))
+            .collect();
+
+        GamepadSnapshot {
+            id: gamepad.id(),
+            name: gamepad.name().to_owned(),
+            status: gamepad.status(),
+            power_info: gamepad.power_info(),
+            buttons,
+            axes,
+        }
+    }
+}
+
+/// Number of buttons stored in `CompactState`, all `Button` variants except `Unknown`.
+const COMPACT_BUTTONS: usize = Button::COUNT - 1;
+/// Number of axes stored in `CompactState`, all `Axis` variants except `Unknown`.
+const COMPACT_AXES: usize = Axis::COUNT - 1;
+const COMPACT_BUTTON_BYTES: usize = (COMPACT_BUTTONS - 1) / 8 + 1;
+// Fails to compile if buttons don't fit in `CompactState::buttons`.
+const _: [(); 1] = [(); (COMPACT_BUTTONS <= 32) as usize];
+
+/// Pressed buttons and values of axes of one gamepad in fixed-size binary format, intended for
+/// sending input over network, for example in peer-to-peer games. Created by
+/// [`Gamepad::compact_state()`](struct.Gamepad.html#method.compact_state).
+///
+/// Unlike `GamepadState`, which is indexed by platform specific `Code`, compact state uses
+/// mapped `Button`s and `Axis`es, so it can be exchanged between different platforms. Unmapped
+/// buttons are not pressed and unmapped axes have value 0.0. Values of buttons are not stored.
+///
+/// Format has [`LEN`](#associatedconstant.LEN) bytes:
+///
+/// - format version, currently 1,
+/// - one bit for every button, in order of `Button::all()`, starting from the lowest bit of
+///   the first byte, padded with zero bits to whole bytes,
+/// - value of every axis in order of `Axis::all()` as little endian half-precision float.
+///
+/// Axis values are converted to half-precision floats when compact state is created, so
+/// `value()` returns the same value before and after the round trip through bytes.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CompactState {
+    buttons: u32,
+    axes: [u16; COMPACT_AXES],
+}
+
+impl CompactState {
+    /// Size of serialized state in bytes.
+    pub const LEN: usize = 1 + COMPACT_BUTTON_BYTES + 2 * COMPACT_AXES;
+    /// Version of format written by `to_bytes()`.
+    pub const VERSION: u8 = 1;
+
+    pub(crate) fn new(gamepad: &Gamepad<'_>) -> Self {
+        let mut compact = CompactState::default();
+
+        for (idx, &btn) in Button::all().iter().enumerate() {
+            if gamepad.is_pressed(btn) {
+                compact.buttons |= 1 << idx;
+            }
+        }
+
+        for (idx, &axis) in Axis::all().iter().enumerate() {
+            compact.axes[idx] = f32_to_f16(gamepad.value(axis));
+        }
+
+        compact
+    }
+
+    /// Returns `true` if `btn` is pressed. Always returns `false` for `Button::Unknown`.
+    pub fn is_pressed(&self, btn: Button) -> bool {
+        match Button::all().iter().position(|&b| b == btn) {
+            Some(idx) => self.buttons & (1 << idx) != 0,
+            None => false,
+        }
+    }
+
+    /// Returns value of `axis`. Always returns 0.0 for `Axis::Unknown`.
+    pub fn value(&self, axis: Axis) -> f32 {
+        match Axis::all().iter().position(|&a| a == axis) {
+            Some(idx) => f16_to_f32(self.axes[idx]),
+            None => 0.0,
+        }
+    }
+
+    /// Serializes state to bytes.
+    pub fn to_bytes(&self) -> [u8; CompactState::LEN] {
+        let mut bytes = [0; CompactState::LEN];
+        bytes[0] = CompactState::VERSION;
+
+        let buttons = self.buttons.to_le_bytes();
+        bytes[1..=COMPACT_BUTTON_BYTES].copy_from_slice(&buttons[..COMPACT_BUTTON_BYTES]);
+
+        let axes = &mut bytes[1 + COMPACT_BUTTON_BYTES..];
+        for (chunk, value) in axes.chunks_mut(2).zip(self.axes.iter()) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// Deserializes state written by `to_bytes()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if `bytes` doesn't start with supported format version or has other length
+    /// than [`LEN`](#associatedconstant.LEN). Bits of buttons in padding are ignored.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CompactDeserError> {
+        match bytes.first() {
+            Some(&CompactState::VERSION) => (),
+            Some(&version) => return Err(CompactDeserError::UnsupportedVersion(version)),
+            None => return Err(CompactDeserError::InvalidLength(0)),
+        }
+
+        if bytes.len() != CompactState::LEN {
+            return Err(CompactDeserError::InvalidLength(bytes.len()));
+        }
+
+        let mut buttons = [0; 4];
+        buttons[..COMPACT_BUTTON_BYTES].copy_from_slice(&bytes[1..=COMPACT_BUTTON_BYTES]);
+        let mut compact = CompactState {
+            buttons: u32::from_le_bytes(buttons) & ((1 << COMPACT_BUTTONS) - 1),
+            axes: [0; COMPACT_AXES],
+        };
+
+        let axes = &bytes[1 + COMPACT_BUTTON_BYTES..];
+        for (value, chunk) in compact.axes.iter_mut().zip(axes.chunks(2)) {
+            *value = u16::from_le_bytes([chunk[0], chunk[1]]);
+        }
+
+        Ok(compact)
+    }
+}
+
+/// Error returned by [`CompactState::from_bytes()`](struct.CompactState.html#method.from_bytes).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CompactDeserError {
+    /// Data was written with unknown version of format.
+    UnsupportedVersion(u8),
+    /// Data has invalid length.
+    InvalidLength(usize),
+}
+
+impl Display for CompactDeserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CompactDeserError::UnsupportedVersion(version) => {
+                write!(f, "unsupported compact state version {}", version)
+            }
+            CompactDeserError::InvalidLength(len) => write!(
+                f,
+                "compact state has {} bytes, expected {}",
+                len,
+                CompactState::LEN
+            ),
+        }
+    }
+}
+
+impl error::Error for CompactDeserError {}
+
+/// Converts `value` to bits of IEEE 754 half-precision float, rounding to nearest even.
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let man = bits & 0x7f_ffff;
+
+    // Infinity and NaN
+    if exp == 0xff {
+        return sign | 0x7c00 | if man != 0 { 0x200 } else { 0 };
+    }
+
+    let exp = exp - 127 + 15;
+    if exp >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    // Subnormal numbers and zero
+    if exp <= 0 {
+        if exp < -10 {
+            return sign;
+        }
+
+        let man = man | 0x80_0000;
+        let shift = (14 - exp) as u32;
+        let half = 1 << (shift - 1);
+        let rounded = (man + half - 1 + ((man >> shift) & 1)) >> shift;
+        return sign | rounded as u16;
+    }
+
+    let half_man = man >> 13;
+    let round_bits = man & 0x1fff;
+    let mut half = u32::from(sign) | (exp as u32) << 10 | half_man;
+    if round_bits > 0x1000 || (round_bits == 0x1000 && half_man & 1 == 1) {
+        // Carry to exponent is correct, the largest value is rounded to infinity.
+        half += 1;
+    }
+
+    half as u16
+}
+
+/// Converts bits of IEEE 754 half-precision float to `f32`. The conversion is exact.
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = u32::from(half & 0x8000) << 16;
+    let exp = u32::from(half >> 10) & 0x1f;
+    let man = u32::from(half & 0x3ff);
+
+    match exp {
+        0 => {
+            let value = man as f32 / (1 << 24) as f32;
+            if sign != 0 {
+                -value
+            } else {
+                value
+            }
+        }
+        0x1f => f32::from_bits(sign | 0x7f80_0000 | man << 13),
+        _ => f32::from_bits(sign | (exp + 127 - 15) << 23 | man << 13),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{gilrs_with_gamepad, RawEventType};
+    use crate::GilrsBuilder;
+    use gilrs_core::native_ev_codes as nec;
+
+    #[test]
+    fn half_floats() {
+        let values = [
+            (0.0, 0x0000),
+            (-0.0, 0x8000),
+            (1.0, 0x3c00),
+            (-1.0, 0xbc00),
+            (0.5, 0x3800),
+            (65504.0, 0x7bff),
+            (1e6, 0x7c00),
+            (std::f32::INFINITY, 0x7c00),
+            (std::f32::NEG_INFINITY, 0xfc00),
+            // Smallest subnormal
+            (5.960_464_5e-8, 0x0001),
+            (1e-9, 0x0000),
+            // Halfway between 1.0 and next value, rounded to even
+            (1.000_488_3, 0x3c00),
+            (1.001_464_8, 0x3c02),
+        ];
+
+        for &(value, half) in &values {
+            assert_eq!(f32_to_f16(value), half, "{}", value);
+        }
+
+        assert!(f16_to_f32(f32_to_f16(std::f32::NAN)).is_nan());
+        for half in 0..=0x7bff {
+            assert_eq!(f32_to_f16(f16_to_f32(half)), half);
+            assert_eq!(f32_to_f16(-f16_to_f32(half)), half | 0x8000);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        let backend = gilrs.test_backend().unwrap();
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+        backend.push(id, RawEventType::ButtonPressed(nec::BTN_WEST));
+        backend.push(
+            id,
+            RawEventType::AxisValueChanged(-32768, nec::AXIS_LSTICKX),
+        );
+        backend.push(id, RawEventType::AxisValueChanged(12345, nec::AXIS_LSTICKY));
+        while gilrs.next_event().is_some() {}
+
+        let gamepad = gilrs.gamepad(id);
+        let compact = gamepad.compact_state();
+        for &btn in Button::all() {
+            assert_eq!(
+                compact.is_pressed(btn),
+                gamepad.is_pressed(btn),
+                "{:?}",
+                btn
+            );
+        }
+        assert!(compact.is_pressed(Button::South));
+        assert_eq!(compact.value(Axis::LeftStickX), -1.0);
+        let y = gamepad.value(Axis::LeftStickY);
+        assert!((compact.value(Axis::LeftStickY) - y).abs() < 0.001);
+        assert_eq!(compact.value(Axis::RightStickX), 0.0);
+
+        let bytes = compact.to_bytes();
+        assert_eq!(bytes.len(), 24);
+        assert_eq!(bytes[0], CompactState::VERSION);
+        assert_eq!(CompactState::from_bytes(&bytes), Ok(compact));
+
+        // Bits in padding are ignored.
+        let mut padded = bytes;
+        padded[3] |= 0x80;
+        assert_eq!(CompactState::from_bytes(&padded), Ok(compact));
+
+        gilrs.release_all(Some(id));
+        while gilrs.next_event().is_some() {}
+        let released = gilrs.gamepad(id).compact_state();
+        assert_eq!(released, CompactState::default());
+        assert_ne!(released.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        let bytes = CompactState::default().to_bytes();
+
+        assert_eq!(
+            CompactState::from_bytes(&[]),
+            Err(CompactDeserError::InvalidLength(0))
+        );
+        assert_eq!(
+            CompactState::from_bytes(&bytes[..10]),
+            Err(CompactDeserError::InvalidLength(10))
+        );
+        let mut longer = bytes.to_vec();
+        longer.push(0);
+        assert_eq!(
+            CompactState::from_bytes(&longer),
+            Err(CompactDeserError::InvalidLength(CompactState::LEN + 1))
+        );
+
+        let mut other_version = bytes;
+        other_version[0] = 2;
+        assert_eq!(
+            CompactState::from_bytes(&other_version),
+            Err(CompactDeserError::UnsupportedVersion(2))
+        );
+    }
+}
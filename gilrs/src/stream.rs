@@ -0,0 +1,341 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Asynchronous access to events.
+//!
+//! `EventStream` implements `futures_core::Stream` and returns the same events as
+//! `Gilrs::next_event()` (including `Connected`, `Disconnected` and events created by default
+//! filters), but instead of returning `None` it waits until next event is available. It doesn't
+//! depend on any particular executor. `next_event()` returns future that can be awaited without
+//! `StreamExt` from `futures`.
+//!
+//! Stream owns `Gilrs`. Use `gilrs()` and `gilrs_mut()` to access gamepads and `into_inner()` to
+//! get it back, stream can be recreated at any time with `Gilrs::into_event_stream()`.
+//!
+//! When there are no events, helper thread waits until one of backend's descriptors (udev monitor
+//! and gamepads on Linux) becomes readable and then wakes the task. To deliver events that gilrs
+//! creates itself, like `Disconnected` after reconnect grace period, task is also woken if
+//! nothing happened for 100 ms. Other backends don't expose descriptors, so the thread wakes the
+//! task every few milliseconds instead. Thread is stopped when stream is dropped.
+//!
+//! ```no_run
+//! use gilrs::{EventStream, Gilrs};
+//!
+//! async fn print_events(events: &mut EventStream) {
+//!     loop {
+//!         let ev = events.next_event().await;
+//!         println!("{:?}", ev);
+//!     }
+//! }
+//!
+//! let mut events = Gilrs::new().unwrap().into_event_stream();
+//!
+//! // With tokio, events can be handled until shutdown signal is received:
+//! //
+//! // tokio::select! {
+//! //     _ = print_events(&mut events) => (),
+//! //     _ = shutdown => (),
+//! // }
+//! # let _ = print_events(&mut events);
+//! ```
+
+use crate::{Event, Gilrs};
+
+use futures_core::Stream;
+
+use std::future::Future;
+use std::os::raw::c_int;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+
+const WAKE_INTERVAL: Duration = Duration::from_millis(4);
+#[cfg(target_os = "linux")]
+const FD_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Stream of events. Use `Gilrs::into_event_stream()` or `EventStream::new()` to create it.
+#[derive(Debug)]
+pub struct EventStream {
+    gilrs: Gilrs,
+    waiter: Option<Waiter>,
+}
+
+impl EventStream {
+    /// Creates stream that reads events from `gilrs`.
+    pub fn new(gilrs: Gilrs) -> Self {
+        EventStream {
+            gilrs,
+            waiter: None,
+        }
+    }
+
+    /// Returns future that resolves to next event.
+    pub fn next_event(&mut self) -> NextEvent<'_> {
+        NextEvent { stream: self }
+    }
+
+    /// Returns `Gilrs` that this stream reads events from.
+    pub fn gilrs(&self) -> &Gilrs {
+        &self.gilrs
+    }
+
+    /// Returns mutable reference to `Gilrs` that this stream reads events from.
+    pub fn gilrs_mut(&mut self) -> &mut Gilrs {
+        &mut self.gilrs
+    }
+
+    /// Stops the stream and returns `Gilrs` that it was reading events from.
+    pub fn into_inner(self) -> Gilrs {
+        self.gilrs
+    }
+
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<Event> {
+        match self.gilrs.next_event() {
+            Some(ev) => Poll::Ready(ev),
+            None => {
+                // List of descriptors changes when gamepads are connected or disconnected, so it
+                // has to be updated every time.
+                let fds = self.gilrs.event_fds();
+                self.waiter
+                    .get_or_insert_with(Waiter::spawn)
+                    .register(cx.waker(), fds);
+
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Never returns `Poll::Ready(None)`.
+impl Stream for EventStream {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        self.get_mut().poll_event(cx).map(Some)
+    }
+}
+
+/// Future returned by `EventStream::next_event()`.
+#[derive(Debug)]
+pub struct NextEvent<'s> {
+    stream: &'s mut EventStream,
+}
+
+impl Future for NextEvent<'_> {
+    type Output = Event;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Event> {
+        self.get_mut().stream.poll_event(cx)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Registration {
+    waker: Option<Waker>,
+    fds: Option<Vec<c_int>>,
+    stop: bool,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    registration: Mutex<Registration>,
+    cond: Condvar,
+}
+
+/// Helper thread that wakes task when backend has new events.
+#[derive(Debug)]
+struct Waiter {
+    shared: Arc<Shared>,
+}
+
+impl Waiter {
+    fn spawn() -> Self {
+        let shared = Arc::new(Shared::default());
+        let thread_shared = shared.clone();
+
+        thread::spawn(move || {
+            let shared = thread_shared;
+            let mut registration = shared.registration.lock().unwrap();
+            loop {
+                if registration.stop {
+                    return;
+                }
+                if registration.waker.is_none() {
+                    registration = shared.cond.wait(registration).unwrap();
+                    continue;
+                }
+
+                let fds = registration.fds.take();
+                drop(registration);
+                wait(fds.as_deref());
+
+                // Task could register new waker in the meantime, wake the latest one.
+                registration = shared.registration.lock().unwrap();
+                if let Some(waker) = registration.waker.take() {
+                    waker.wake();
+                }
+            }
+        });
+
+        Waiter { shared }
+    }
+
+    fn register(&self, waker: &Waker, fds: Option<Vec<c_int>>) {
+        let mut registration = self.shared.registration.lock().unwrap();
+        registration.waker = Some(waker.clone());
+        registration.fds = fds;
+        self.shared.cond.notify_one();
+    }
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        self.shared.registration.lock().unwrap().stop = true;
+        self.shared.cond.notify_one();
+    }
+}
+
+/// Blocks until one of `fds` is readable. Errors are ignored, they only cause spurious wakeup.
+#[cfg(target_os = "linux")]
+fn wait(fds: Option<&[c_int]>) {
+    let fds = match fds {
+        Some(fds) => fds,
+        None => return thread::sleep(WAKE_INTERVAL),
+    };
+
+    let mut pollfds: Vec<_> = fds
+        .iter()
+        .map(|&fd| libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        })
+        .collect();
+
+    unsafe {
+        libc::poll(
+            pollfds.as_mut_ptr(),
+            pollfds.len() as libc::nfds_t,
+            FD_TIMEOUT.as_millis() as c_int,
+        );
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn wait(_: Option<&[c_int]>) {
+    thread::sleep(WAKE_INTERVAL);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{gilrs_with_gamepad, native_ev_codes as nec, RawEventType};
+    use crate::{EventType, GilrsBuilder};
+
+    use std::sync::mpsc;
+    use std::task::{RawWaker, RawWakerVTable};
+
+    // Waker that sends message to channel when woken.
+    fn channel_waker(tx: &mpsc::Sender<()>) -> Waker {
+        unsafe fn clone(data: *const ()) -> RawWaker {
+            let tx = (*(data as *const mpsc::Sender<()>)).clone();
+            RawWaker::new(Box::into_raw(Box::new(tx)) as *const (), &VTABLE)
+        }
+        unsafe fn wake(data: *const ()) {
+            wake_by_ref(data);
+            drop_tx(data);
+        }
+        unsafe fn wake_by_ref(data: *const ()) {
+            let _ = (*(data as *const mpsc::Sender<()>)).send(());
+        }
+        unsafe fn drop_tx(data: *const ()) {
+            drop(Box::from_raw(data as *mut mpsc::Sender<()>));
+        }
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake_by_ref, drop_tx);
+
+        unsafe { Waker::from_raw(clone(tx as *const mpsc::Sender<()> as *const ())) }
+    }
+
+    #[test]
+    fn stream() {
+        let (tx, rx) = mpsc::channel();
+        let waker = channel_waker(&tx);
+        let mut cx = Context::from_waker(&waker);
+
+        let (mut gilrs, id) = gilrs_with_gamepad(GilrsBuilder::new());
+        gilrs
+            .test_backend()
+            .unwrap()
+            .push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+
+        let mut stream = gilrs.into_event_stream();
+        match Pin::new(&mut stream).poll_next(&mut cx) {
+            Poll::Ready(Some(ev)) => match ev.event {
+                EventType::ButtonPressed(..) => (),
+                e => panic!("Unexpected event {:?}", e),
+            },
+            p => panic!("Unexpected poll result {:?}", p),
+        }
+        // Companion event created by gilrs
+        match Pin::new(&mut stream.next_event()).poll(&mut cx) {
+            Poll::Ready(ev) => match ev.event {
+                EventType::ButtonChanged(..) => (),
+                e => panic!("Unexpected event {:?}", e),
+            },
+            p => panic!("Unexpected poll result {:?}", p),
+        }
+
+        assert!(Pin::new(&mut stream).poll_next(&mut cx).is_pending());
+        rx.recv_timeout(Duration::from_secs(1)).unwrap();
+
+        stream
+            .gilrs_mut()
+            .test_backend()
+            .unwrap()
+            .push(id, RawEventType::Disconnected);
+        match Pin::new(&mut stream).poll_next(&mut cx) {
+            Poll::Ready(Some(ev)) => assert_eq!(ev.event, EventType::Disconnected),
+            p => panic!("Unexpected poll result {:?}", p),
+        }
+        let gilrs = stream.into_inner();
+
+        // Recreated stream
+        let mut stream = gilrs.into_event_stream();
+        assert!(Pin::new(&mut stream).poll_next(&mut cx).is_pending());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn wake_on_readable_fd() {
+        let (tx, rx) = mpsc::channel();
+        let waker = channel_waker(&tx);
+
+        let mut pipe = [0; 2];
+        assert_eq!(unsafe { libc::pipe(pipe.as_mut_ptr()) }, 0);
+
+        let waiter = Waiter::spawn();
+        waiter.register(&waker, Some(vec![pipe[0]]));
+        // Nothing to read, task is only woken after timeout.
+        assert!(rx.recv_timeout(FD_TIMEOUT / 2).is_err());
+        rx.recv_timeout(FD_TIMEOUT).unwrap();
+
+        waiter.register(&waker, Some(vec![pipe[0]]));
+        assert_eq!(
+            unsafe { libc::write(pipe[1], [1u8].as_ptr() as *const _, 1) },
+            1
+        );
+        rx.recv_timeout(FD_TIMEOUT / 2).unwrap();
+
+        drop(waiter);
+        unsafe {
+            libc::close(pipe[0]);
+            libc::close(pipe[1]);
+        }
+    }
+}
@@ -0,0 +1,541 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Utilities for testing code that uses gilrs without real hardware.
+//!
+//! This module is only available with `test_utils` feature. It provides `TestBackend` – a
+//! replacement for platform backend that reports only gamepads and events added by you. Create
+//! `Gilrs` with [`GilrsBuilder::build_with_backend()`](../struct.GilrsBuilder.html#method.build_with_backend)
+//! and access backend later with [`Gilrs::test_backend()`](../struct.Gilrs.html#method.test_backend).
+//!
+//! `TestBackend` has its own clock which is used as timestamp of pushed events and by filters
+//! that need current time, like `Repeat`.
+//!
+//! # Example
+//!
+//! ```
+//! use gilrs::test_utils::{native_ev_codes as nec, AxisInfo, RawEventType, TestBackend};
+//! use gilrs::{Button, EventType, GilrsBuilder};
+//!
+//! let mut backend = TestBackend::new();
+//! let stick = AxisInfo {
+//!     min: -128,
+//!     max: 127,
+//!     deadzone: None,
+//! };
+//! let id = backend.add_device(
+//!     "Test Gamepad",
+//!     [0; 16],
+//!     &[nec::BTN_SOUTH, nec::BTN_EAST],
+//!     &[(nec::AXIS_LSTICKX, stick), (nec::AXIS_LSTICKY, stick)],
+//! );
+//!
+//! let mut gilrs = GilrsBuilder::new()
+//!     .add_included_mappings(false)
+//!     .add_env_mappings(false)
+//!     .build_with_backend(backend)
+//!     .unwrap();
+//! match gilrs.next_event().unwrap().event {
+//!     EventType::Connected(info) => assert_eq!(info.name, "Test Gamepad"),
+//!     e => panic!("Unexpected event {:?}", e),
+//! }
+//!
+//! let backend = gilrs.test_backend().unwrap();
+//! backend.push(id, RawEventType::ButtonPressed(nec::BTN_SOUTH));
+//!
+//! match gilrs.next_event().unwrap().event {
+//!     EventType::ButtonPressed(Button::South, _) => (),
+//!     e => panic!("Unexpected event {:?}", e),
+//! }
+//! assert!(gilrs.gamepad(id).is_pressed(Button::South));
+//! ```
+
+use crate::gamepad::GamepadId;
+
+pub use gilrs_core::{
+    native_ev_codes, AxisInfo, EvCode, Event as RawEvent, EventType as RawEventType, PowerInfo,
+    RawState,
+};
+
+use gilrs_core::{LedError, PowerOffError};
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+#[cfg(feature = "raw-ff")]
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Scriptable backend. See [module documentation](index.html) for more info.
+#[derive(Debug)]
+pub struct TestBackend {
+    gamepads: Vec<TestGamepad>,
+    events: VecDeque<RawEvent>,
+    time: SystemTime,
+    y_axis_reversed: bool,
+}
+
+impl TestBackend {
+    /// Creates backend without any gamepads. Clock is set to `UNIX_EPOCH`.
+    pub fn new() -> Self {
+        TestBackend {
+            gamepads: Vec::new(),
+            events: VecDeque::new(),
+            time: UNIX_EPOCH,
+            y_axis_reversed: gilrs_core::IS_Y_AXIS_REVERSED,
+        }
+    }
+
+    /// Sets whether Y axes of gamepads point down, like on platforms where
+    /// `gilrs_core::IS_Y_AXIS_REVERSED` is true. Applies to existing and new gamepads. By default
+    /// backend behaves like platform it runs on.
+    pub fn set_y_axis_reversed(&mut self, reversed: bool) {
+        self.y_axis_reversed = reversed;
+        for gamepad in &mut self.gamepads {
+            gamepad.y_axis_reversed = reversed;
+        }
+    }
+
+    /// Adds new connected gamepad and queues `Connected` event for it. Returns ID of the
+    /// gamepad. IDs are assigned in order in which gamepads are added, starting from 0.
+    ///
+    /// IDs used by `TestBackend` are backend's IDs. They are the same as `GamepadId`s, unless
+    /// gamepad that lost connection is restored by device added later (see
+    /// `GilrsBuilder::reconnect_grace()`).
+    ///
+    /// If mapping for `uuid` is not available, gamepad will use default mapping which is based
+    /// on `native_ev_codes`.
+    pub fn add_device(
+        &mut self,
+        name: &str,
+        uuid: [u8; 16],
+        buttons: &[EvCode],
+        axes: &[(EvCode, AxisInfo)],
+    ) -> GamepadId {
+        let id = GamepadId(self.gamepads.len());
+        self.gamepads.push(TestGamepad {
+            name: name.to_owned(),
+            uuid,
+            buttons: buttons.to_vec(),
+            axes: axes.iter().map(|&(code, _)| code).collect(),
+            axes_info: axes.iter().map(|&(_, info)| info).collect(),
+            is_connected: true,
+            is_suspended: false,
+            power_info: PowerInfo::Unknown,
+            powered_off: Cell::new(false),
+            ff_supported: false,
+            #[cfg(feature = "raw-ff")]
+            ff_magnitudes: Arc::new(Mutex::new((0, 0))),
+            has_touchpad: false,
+            y_axis_reversed: self.y_axis_reversed,
+            state: None,
+            serial_number: None,
+        });
+        self.push(id, RawEventType::Connected);
+
+        id
+    }
+
+    /// Marks connected or suspended gamepad as disconnected and queues `Disconnected` event for
+    /// it. Does nothing if gamepad is already disconnected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no gamepad with given ID.
+    pub fn remove_device(&mut self, id: GamepadId) {
+        let gamepad = &mut self.gamepads[id.0];
+
+        if gamepad.is_connected || gamepad.is_suspended {
+            gamepad.is_connected = false;
+            gamepad.is_suspended = false;
+            self.push(id, RawEventType::Disconnected);
+        }
+    }
+
+    /// Marks disconnected gamepad as connected again and queues `Connected` event for it. Platform
+    /// backends reuse ID when device with the same UUID reconnects. Does nothing if gamepad is
+    /// connected or suspended.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no gamepad with given ID.
+    pub fn reconnect_device(&mut self, id: GamepadId) {
+        let gamepad = &mut self.gamepads[id.0];
+
+        if !gamepad.is_connected && !gamepad.is_suspended {
+            gamepad.is_connected = true;
+            self.push(id, RawEventType::Connected);
+        }
+    }
+
+    /// Marks connected gamepad as suspended and queues `Suspended` event for it. Does nothing if
+    /// gamepad is not connected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no gamepad with given ID.
+    pub fn suspend_device(&mut self, id: GamepadId) {
+        let gamepad = &mut self.gamepads[id.0];
+
+        if gamepad.is_connected {
+            gamepad.is_connected = false;
+            gamepad.is_suspended = true;
+            self.push(id, RawEventType::Suspended);
+        }
+    }
+
+    /// Marks suspended gamepad as connected again and queues `Resumed` event for it. Does nothing
+    /// if gamepad is not suspended.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no gamepad with given ID.
+    pub fn resume_device(&mut self, id: GamepadId) {
+        let gamepad = &mut self.gamepads[id.0];
+
+        if gamepad.is_suspended {
+            gamepad.is_connected = true;
+            gamepad.is_suspended = false;
+            self.push(id, RawEventType::Resumed);
+        }
+    }
+
+    /// Sets power info reported by gamepad. Gamepads have `PowerInfo::Unknown` by default.
+    ///
+    /// Gamepads with `PowerInfo::Discharging` can be powered off with `Gamepad::power_off()`,
+    /// which has the same effect as `remove_device()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no gamepad with given ID.
+    pub fn set_power_info(&mut self, id: GamepadId, power_info: PowerInfo) {
+        self.gamepads[id.0].power_info = power_info;
+    }
+
+    /// Sets whether gamepad reports force feedback support. Test gamepads can't play effects, this
+    /// only changes result of `Gamepad::is_ff_supported()` and, with `raw-ff` feature, of
+    /// `Gamepad::ff_device_raw()`. Should be called before the `Connected` event of gamepad is
+    /// processed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no gamepad with given ID.
+    pub fn set_ff_supported(&mut self, id: GamepadId, supported: bool) {
+        self.gamepads[id.0].ff_supported = supported;
+    }
+
+    /// Returns magnitudes of strong and weak motor last set through
+    /// [`RawFfHandle`](../ff/struct.RawFfHandle.html). Motors are stopped when last handle is
+    /// dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no gamepad with given ID.
+    #[cfg(feature = "raw-ff")]
+    pub fn ff_magnitudes(&self, id: GamepadId) -> (u16, u16) {
+        *self.gamepads[id.0].ff_magnitudes.lock().unwrap()
+    }
+
+    /// Sets whether gamepad has touchpad. This only changes result of `Gamepad::has_touchpad()`,
+    /// `TouchpadTouch` events are pushed as any other event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no gamepad with given ID.
+    pub fn set_touchpad(&mut self, id: GamepadId, has_touchpad: bool) {
+        self.gamepads[id.0].has_touchpad = has_touchpad;
+    }
+
+    /// Sets serial number reported by gamepad. Gamepads don't have serial number by default.
+    /// Changing it while gamepad is disconnected simulates other device with the same UUID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no gamepad with given ID.
+    pub fn set_serial_number(&mut self, id: GamepadId, serial_number: Option<&str>) {
+        self.gamepads[id.0].serial_number = serial_number.map(str::to_owned);
+    }
+
+    /// Sets state of buttons and axes that is returned when gilrs reads current state of gamepad,
+    /// see [`Gilrs::resync()`](../struct.Gilrs.html#method.resync). If `None`, gamepad behaves
+    /// like on platforms that can't read state of devices, which is the default.
+    ///
+    /// State is not updated by pushed events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no gamepad with given ID.
+    pub fn set_device_state(&mut self, id: GamepadId, state: Option<RawState>) {
+        self.gamepads[id.0].state = state;
+    }
+
+    /// Queues event. It will be returned by backend as is, even if it is inconsistent with state
+    /// of gamepads.
+    pub fn push_event(&mut self, event: RawEvent) {
+        self.events.push_back(event);
+    }
+
+    /// Queues event with current time of backend's clock.
+    pub fn push(&mut self, id: GamepadId, event: RawEventType) {
+        let time = self.time;
+        self.push_event(RawEvent {
+            id: id.0,
+            event,
+            time,
+        });
+    }
+
+    /// Returns current time of backend's clock.
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+
+    /// Sets backend's clock.
+    pub fn set_time(&mut self, time: SystemTime) {
+        self.time = time;
+    }
+
+    /// Moves backend's clock forward.
+    pub fn advance_time(&mut self, duration: Duration) {
+        self.time += duration;
+    }
+
+    pub(crate) fn next_event(&mut self) -> Option<RawEvent> {
+        for id in 0..self.gamepads.len() {
+            if self.gamepads[id].powered_off.replace(false) {
+                self.remove_device(GamepadId(id));
+            }
+        }
+
+        self.events.pop_front()
+    }
+
+    pub(crate) fn gamepad(&self, id: usize) -> Option<&TestGamepad> {
+        self.gamepads.get(id)
+    }
+
+    pub(crate) fn last_gamepad_hint(&self) -> usize {
+        self.gamepads.len()
+    }
+}
+
+impl Default for TestBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct TestGamepad {
+    name: String,
+    uuid: [u8; 16],
+    buttons: Vec<EvCode>,
+    axes: Vec<EvCode>,
+    axes_info: Vec<AxisInfo>,
+    is_connected: bool,
+    is_suspended: bool,
+    power_info: PowerInfo,
+    // Set by `power_off()`, gamepad is removed on next call to `TestBackend::next_event()`.
+    powered_off: Cell<bool>,
+    ff_supported: bool,
+    // Shared with `TestFfDevice`s of gamepad.
+    #[cfg(feature = "raw-ff")]
+    ff_magnitudes: Arc<Mutex<(u16, u16)>>,
+    has_touchpad: bool,
+    y_axis_reversed: bool,
+    // Set by `TestBackend::set_device_state()`.
+    state: Option<RawState>,
+    serial_number: Option<String>,
+}
+
+impl TestGamepad {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn uuid(&self) -> [u8; 16] {
+        self.uuid
+    }
+
+    pub fn serial_number(&self) -> Option<&str> {
+        self.serial_number.as_deref()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    pub fn power_info(&self) -> PowerInfo {
+        self.power_info
+    }
+
+    pub fn power_off(&self) -> Result<(), PowerOffError> {
+        if !self.is_connected {
+            return Err(PowerOffError::Disconnected);
+        }
+
+        match self.power_info {
+            PowerInfo::Discharging(_) => {
+                self.powered_off.set(true);
+                Ok(())
+            }
+            _ => Err(PowerOffError::NotSupported),
+        }
+    }
+
+    /// Test gamepads accept any valid player number while connected.
+    pub fn set_player_indicator(&self, _player: Option<u8>) -> Result<(), LedError> {
+        if self.is_connected {
+            Ok(())
+        } else {
+            Err(LedError::Disconnected)
+        }
+    }
+
+    pub fn is_ff_supported(&self) -> bool {
+        self.ff_supported
+    }
+
+    #[cfg(feature = "raw-ff")]
+    pub fn ff_device(&self) -> Option<TestFfDevice> {
+        if self.ff_supported {
+            Some(TestFfDevice(self.ff_magnitudes.clone()))
+        } else {
+            None
+        }
+    }
+
+    pub fn has_touchpad(&self) -> bool {
+        self.has_touchpad
+    }
+
+    pub fn is_y_axis_reversed(&self) -> bool {
+        self.y_axis_reversed
+    }
+
+    pub fn current_state(&self) -> Option<RawState> {
+        if self.is_connected {
+            self.state.clone()
+        } else {
+            None
+        }
+    }
+
+    pub fn buttons(&self) -> &[EvCode] {
+        &self.buttons
+    }
+
+    pub fn axes(&self) -> &[EvCode] {
+        &self.axes
+    }
+
+    pub fn axis_info(&self, nec: EvCode) -> Option<&AxisInfo> {
+        self.axes
+            .iter()
+            .position(|&code| code == nec)
+            .map(|idx| &self.axes_info[idx])
+    }
+}
+
+/// Force feedback device of test gamepad. It only remembers magnitudes, which can be read with
+/// `TestBackend::ff_magnitudes()`.
+#[cfg(feature = "raw-ff")]
+#[derive(Debug)]
+pub(crate) struct TestFfDevice(Arc<Mutex<(u16, u16)>>);
+
+#[cfg(feature = "raw-ff")]
+impl TestFfDevice {
+    pub fn set_ff_state(&mut self, strong: u16, weak: u16, _min_duration: Duration) {
+        *self.0.lock().unwrap() = (strong, weak);
+    }
+}
+
+/// Creates `Gilrs` with `TestBackend` and one connected gamepad. `Connected` event is already
+/// consumed.
+#[cfg(test)]
+pub(crate) fn gilrs_with_gamepad(builder: crate::GilrsBuilder) -> (crate::Gilrs, GamepadId) {
+    use self::native_ev_codes as nec;
+
+    let stick = AxisInfo {
+        min: -32768,
+        max: 32767,
+        deadzone: None,
+    };
+    let trigger = AxisInfo {
+        min: 0,
+        max: 255,
+        deadzone: Some(0),
+    };
+
+    let mut backend = TestBackend::new();
+    let id = backend.add_device(
+        "Test Gamepad",
+        [0; 16],
+        &[nec::BTN_SOUTH, nec::BTN_EAST, nec::BTN_NORTH, nec::BTN_WEST],
+        &[
+            (nec::AXIS_LSTICKX, stick),
+            (nec::AXIS_LSTICKY, stick),
+            (nec::AXIS_RT2, trigger),
+        ],
+    );
+
+    let mut gilrs = builder
+        .add_included_mappings(false)
+        .add_env_mappings(false)
+        .build_with_backend(backend)
+        .unwrap();
+    match gilrs.next_event().map(|ev| ev.event) {
+        Some(crate::EventType::Connected(_)) => (),
+        e => panic!("Unexpected event {:?}", e),
+    }
+
+    (gilrs, id)
+}
+
+/// Calls `f` and returns records logged by it on the current thread. Other threads keep logging
+/// to nowhere, so tests running in parallel don't see each other's records.
+#[cfg(test)]
+// `const` initializer of thread local requires Rust 1.59.
+#[allow(clippy::missing_const_for_thread_local)]
+pub(crate) fn capture_logs<F: FnOnce()>(f: F) -> Vec<(log::Level, String)> {
+    use log::{Log, Metadata, Record};
+    use std::cell::RefCell;
+    use std::sync::Once;
+
+    struct CaptureLogger;
+
+    thread_local! {
+        static RECORDS: RefCell<Option<Vec<(log::Level, String)>>> = RefCell::new(None);
+    }
+
+    impl Log for CaptureLogger {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            RECORDS.with(|records| records.borrow().is_some())
+        }
+
+        fn log(&self, record: &Record<'_>) {
+            RECORDS.with(|records| {
+                if let Some(ref mut records) = *records.borrow_mut() {
+                    records.push((record.level(), record.args().to_string()));
+                }
+            });
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CaptureLogger = CaptureLogger;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        log::set_logger(&LOGGER).expect("another logger is already set");
+        log::set_max_level(log::LevelFilter::Trace);
+    });
+
+    RECORDS.with(|records| *records.borrow_mut() = Some(Vec::new()));
+    f();
+    RECORDS.with(|records| records.borrow_mut().take().unwrap_or_default())
+}
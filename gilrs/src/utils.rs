@@ -7,19 +7,24 @@
 
 pub use gilrs_core::utils::*;
 
-/// Like `(a: f32 / b).ceil()` but for integers.
-pub fn ceil_div(a: u32, b: u32) -> u32 {
-    if a == 0 {
-        0
-    } else {
-        1 + ((a - 1) / b)
-    }
-}
+use std::time::Instant;
 
 pub fn clamp(x: f32, min: f32, max: f32) -> f32 {
     x.max(min).min(max)
 }
 
+/// Returns current `Instant` or `None` on wasm, where `Instant::now()` panics.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn instant_now() -> Option<Instant> {
+    Some(Instant::now())
+}
+
+/// Returns current `Instant` or `None` on wasm, where `Instant::now()` panics.
+#[cfg(target_arch = "wasm32")]
+pub fn instant_now() -> Option<Instant> {
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;